@@ -0,0 +1,36 @@
+-- 情报命中记录，每条记录对应一封邮件命中的一条情报
+CREATE TABLE IF NOT EXISTS alert_intelligence
+(
+    id UInt64,
+    mail_id UInt64,
+    timestamp DateTime,
+    intelligence_id UUID,
+    description String,
+    source_industry String,
+    first_discovered_time DateTime,
+    last_active_time DateTime,
+    intelligence_update_time DateTime,
+    intelligence_expiration_time DateTime,
+    attribute Enum8('Domain' = 1, 'Url' = 2, 'EmailAddress' = 3, 'Ipv4' = 4, 'Md5' = 5, 'UrlDomain' = 6, 'EmailDomain' = 7, 'Sha256' = 8),
+    intelligence_type String,
+    urgency Enum8('High' = 1, 'Medium' = 2, 'Low' = 3),
+    value String,
+    pattern String,
+    info String,
+    threat_actor String,
+    joint_prevention_and_control String,
+    display_to_name String,
+    display_to_address String,
+    display_to_account String,
+    display_to_domain String,
+    is_deleted UInt8 DEFAULT 0,
+    updated_at DateTime DEFAULT now(),
+    source Enum8('Local' = 1, 'Cloud' = 2),
+    source_id UInt64,
+    source_mime_type String,
+    parent_source Enum8('Email' = 1, 'File' = 2, 'EmailHeader' = 3, 'EmailBody' = 4, 'QrCode' = 5, 'Text' = 6, 'Url' = 7, 'Smtp' = 8),
+    scan_time_us UInt64
+)
+ENGINE = ReplacingMergeTree(updated_at)
+PARTITION BY toYYYYMM(timestamp)
+ORDER BY (intelligence_id, mail_id, id);
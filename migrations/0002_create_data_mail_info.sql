@@ -0,0 +1,50 @@
+-- 邮件检测记录
+CREATE TABLE IF NOT EXISTS data_mail_info
+(
+    id UInt64,
+    action Enum8('Accept' = 1, 'Discard' = 2, 'Reject' = 3, 'Quarantine' = 4),
+    timestamp DateTime,
+    send_time DateTime,
+    subject String,
+    bcc_name String,
+    bcc_email String,
+    bcc_email_account String,
+    bcc_email_domain String,
+    display_from String,
+    display_to_name String,
+    display_to_address String,
+    display_to_account String,
+    display_to_domain String,
+    config String,
+    sasl_login String,
+    sasl_method String,
+    client_ip String,
+    client_ptr String,
+    client_port Int32,
+    client_helo String,
+    client_active_connections Int32,
+    client_envelope_from_name String,
+    client_envelope_from_address String,
+    client_envelope_from_account String,
+    client_envelope_from_domain String,
+    client_envelope_to_name String,
+    client_envelope_to_address String,
+    client_envelope_to_account String,
+    client_envelope_to_domain String,
+    tls String,
+    server String,
+    protocol_version String,
+    text_body String,
+    html_body String,
+    deconstruction_modules String,
+    detection_modules String,
+    hash_sha1 String,
+    hash_sha256 String,
+    hash_md5 String,
+    direction String,
+    protocol_check String,
+    extract_password String
+)
+ENGINE = MergeTree
+PARTITION BY toYYYYMM(timestamp)
+ORDER BY (timestamp, id);
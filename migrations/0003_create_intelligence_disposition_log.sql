@@ -0,0 +1,12 @@
+-- 情报处置变更记录：加入或移出白名单、黑名单，上报或撤销上报
+CREATE TABLE IF NOT EXISTS intelligence_disposition_log
+(
+    intelligence_id UUID,
+    disposition Enum8('white' = 1, 'black' = 2, 'report' = 3),
+    enabled UInt8,
+    operator String,
+    reason String,
+    created_at DateTime DEFAULT now()
+)
+ENGINE = MergeTree
+ORDER BY (intelligence_id, created_at);
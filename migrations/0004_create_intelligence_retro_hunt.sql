@@ -0,0 +1,14 @@
+-- 情报回溯排查任务结果
+CREATE TABLE IF NOT EXISTS intelligence_retro_hunt
+(
+    intelligence_id UUID,
+    job_id String,
+    operator String,
+    scan_start_time DateTime,
+    scan_end_time DateTime,
+    scanned_mails UInt64,
+    matched_mails UInt64,
+    finished_at DateTime DEFAULT now()
+)
+ENGINE = MergeTree
+ORDER BY (intelligence_id, finished_at);
@@ -0,0 +1,16 @@
+-- 邮件附件入库记录，文件内容保存在附件存储中，file_path为存储内的相对路径
+CREATE TABLE IF NOT EXISTS data_mail_attachment
+(
+    mail_id UInt64,
+    attachment_id String,
+    filename String,
+    file_path String,
+    size UInt64,
+    hash_md5 String,
+    hash_sha1 String,
+    hash_sha256 String,
+    timestamp DateTime DEFAULT now()
+)
+ENGINE = MergeTree
+PARTITION BY toYYYYMM(timestamp)
+ORDER BY (mail_id, attachment_id);
@@ -0,0 +1,2 @@
+-- 原始邮件在文件存储中的路径，与附件路径一样相对于存储根目录，未入库时为空
+ALTER TABLE data_mail_info ADD COLUMN IF NOT EXISTS eml_path String DEFAULT '';
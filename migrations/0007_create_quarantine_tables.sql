@@ -0,0 +1,35 @@
+-- 隔离邮件处置请求：每次状态变更写入一行新版本，按version合并，查询时使用FINAL取最新状态
+CREATE TABLE IF NOT EXISTS quarantine_action
+(
+    action_id String,
+    mail_id String,
+    kind String,
+    state String,
+    recipients Array(String),
+    reason String,
+    requested_by String,
+    requested_at DateTime64(3),
+    reviewed_by String,
+    reviewed_at DateTime64(3),
+    completed_at DateTime64(3),
+    error String,
+    version UInt64
+)
+ENGINE = ReplacingMergeTree(version)
+ORDER BY action_id;
+
+-- 隔离邮件处置审计记录，只追加不修改；version为事件后处置请求的版本号，同一时刻的记录按其排序
+CREATE TABLE IF NOT EXISTS quarantine_audit
+(
+    timestamp DateTime64(3),
+    action_id String,
+    mail_id String,
+    kind String,
+    event String,
+    actor String,
+    detail String,
+    version UInt64
+)
+ENGINE = MergeTree
+PARTITION BY toYYYYMM(timestamp)
+ORDER BY (mail_id, action_id, timestamp);
@@ -1,7 +1,47 @@
 use std::net::{IpAddr, SocketAddr};
-use crate::db::DbConfig;
+use std::path::PathBuf;
+use std::time::Duration;
+use crate::db::{Backoff, DbConfig};
 use std::env;
-use tracing::{info, debug};
+use tracing::{info, debug, warn};
+
+/// 隔离邮件处置后端配置
+#[derive(Debug, Clone)]
+pub enum MailActionBackendConfig {
+    /// 落盘到目录
+    FileDrop {
+        /// 落盘目录
+        dir: PathBuf,
+    },
+    /// 通过SMTP中继投递
+    SmtpRelay {
+        /// 中继地址
+        host: String,
+        /// 中继端口
+        port: u16,
+    },
+}
+
+/// 隔离邮件处置操作人配置
+#[derive(Debug, Clone)]
+pub struct QuarantineOperatorConfig {
+    /// 操作人名称，记录为申请人或审批人
+    pub name: String,
+    /// 操作人令牌的SHA256（十六进制小写），配置中不保存令牌明文
+    pub token_sha256: String,
+}
+
+/// 图片条码识别后端配置
+#[derive(Debug, Clone)]
+pub enum BarcodeDecoderConfig {
+    /// 关闭识别
+    Disabled,
+    /// 调用zbarimg命令行
+    Zbar {
+        /// zbarimg程序路径
+        program: PathBuf,
+    },
+}
 
 /// 应用配置
 #[derive(Debug, Clone)]
@@ -12,6 +52,26 @@ pub struct AppConfig {
     pub jaeger_endpoint: String,
     /// 数据库配置
     pub db_config: DbConfig,
+    /// 建立连接时是否校验数据库结构
+    pub schema_check: bool,
+    /// 数据库重连的退避间隔
+    pub db_retry: Backoff,
+    /// 附件与原始邮件的存储目录，入库记录中的文件路径相对于该目录
+    pub mail_storage_dir: PathBuf,
+    /// 安全下载模式下样本压缩包的密码
+    pub safe_download_password: String,
+    /// 批量导出文件存放目录
+    pub export_dir: PathBuf,
+    /// 同步导出的最大邮件数，超过后转为异步任务
+    pub export_sync_limit: usize,
+    /// 隔离邮件处置后端
+    pub mail_action_backend: MailActionBackendConfig,
+    /// 隔离邮件处置是否需要双人审批
+    pub quarantine_four_eyes: bool,
+    /// 隔离邮件处置操作人，为空时拒绝全部处置请求
+    pub quarantine_operators: Vec<QuarantineOperatorConfig>,
+    /// 图片条码识别后端
+    pub barcode_decoder: BarcodeDecoderConfig,
 }
 
 impl Default for AppConfig {
@@ -20,6 +80,20 @@ impl Default for AppConfig {
             server_addr: SocketAddr::new(IpAddr::from([127, 0, 0, 1]), 6000),
             jaeger_endpoint: "http://localhost:4317".to_string(),
             db_config: DbConfig::default(),
+            schema_check: true,
+            db_retry: Backoff::default(),
+            mail_storage_dir: PathBuf::from("/data/mail-storage"),
+            safe_download_password: "infected".to_string(),
+            export_dir: env::temp_dir().join("analysis-api-exports"),
+            export_sync_limit: 20,
+            mail_action_backend: MailActionBackendConfig::FileDrop {
+                dir: env::temp_dir().join("analysis-api-mail-actions"),
+            },
+            quarantine_four_eyes: false,
+            quarantine_operators: Vec::new(),
+            barcode_decoder: BarcodeDecoderConfig::Zbar {
+                program: PathBuf::from("zbarimg"),
+            },
         }
     }
 }
@@ -69,6 +143,33 @@ fn get_env_optional_string(key: &str) -> Option<String> {
     }
 }
 
+/// 解析隔离邮件处置操作人，格式为`名称:令牌SHA256,名称:令牌SHA256`
+///
+/// 名称为空或包含控制字符、名称为system、哈希不是64位十六进制的条目跳过并记录警告
+fn parse_quarantine_operators(value: &str) -> Vec<QuarantineOperatorConfig> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .filter_map(|entry| {
+            let parsed = entry.rsplit_once(':').and_then(|(name, hash)| {
+                let name = name.trim();
+                let hash = hash.trim();
+                let valid_name = !name.is_empty() && name != "system" && !name.chars().any(char::is_control);
+                let valid_hash = hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit());
+                (valid_name && valid_hash).then(|| QuarantineOperatorConfig {
+                    name: name.to_string(),
+                    token_sha256: hash.to_ascii_lowercase(),
+                })
+            });
+            if parsed.is_none() {
+                warn!("忽略无效的隔离处置操作人配置: {}", entry.split(':').next().unwrap_or_default());
+            }
+            parsed
+        })
+        .collect()
+}
+
 /// 获取应用配置
 pub fn get_config() -> AppConfig {
     info!("加载应用配置...");
@@ -97,6 +198,63 @@ pub fn get_config() -> AppConfig {
         password: db_password,
         database: db_name.clone(),
     };
+    let schema_check: bool = get_env_or_default("DB_SCHEMA_CHECK", true);
+    let default_retry = Backoff::default();
+    let db_retry = Backoff {
+        initial: Duration::from_secs(get_env_or_default("DB_RETRY_INITIAL_SECS", default_retry.initial.as_secs()).max(1)),
+        max: Duration::from_secs(get_env_or_default("DB_RETRY_MAX_SECS", default_retry.max.as_secs()).max(1)),
+    };
+    
+    // 附件存储配置
+    let mail_storage_dir = PathBuf::from(get_env_string_or_default("MAIL_STORAGE_DIR", "/data/mail-storage"));
+    
+    // 安全下载配置
+    let safe_download_password = get_env_optional_string("SAFE_DOWNLOAD_PASSWORD")
+        .unwrap_or_else(|| "infected".to_string());
+    
+    // 批量导出配置
+    let export_dir = get_env_optional_string("EXPORT_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| env::temp_dir().join("analysis-api-exports"));
+    let export_sync_limit: usize = get_env_or_default("EXPORT_SYNC_LIMIT", 20);
+    
+    // 隔离邮件处置配置
+    let mail_action_backend = match get_env_string_or_default("MAIL_ACTION_BACKEND", "file").as_str() {
+        "smtp" => MailActionBackendConfig::SmtpRelay {
+            host: get_env_string_or_default("MAIL_ACTION_SMTP_HOST", "127.0.0.1"),
+            port: get_env_or_default("MAIL_ACTION_SMTP_PORT", 1025),
+        },
+        other => {
+            if other != "file" {
+                debug!("未知的隔离邮件处置后端: {}，使用file", other);
+            }
+            MailActionBackendConfig::FileDrop {
+                dir: get_env_optional_string("MAIL_ACTION_DROP_DIR")
+                    .map(PathBuf::from)
+                    .unwrap_or_else(|| env::temp_dir().join("analysis-api-mail-actions")),
+            }
+        }
+    };
+    let quarantine_four_eyes: bool = get_env_or_default("QUARANTINE_FOUR_EYES", false);
+    let quarantine_operators = get_env_optional_string("QUARANTINE_OPERATORS")
+        .map(|value| parse_quarantine_operators(&value))
+        .unwrap_or_default();
+    if quarantine_operators.is_empty() {
+        warn!("未配置隔离邮件处置操作人，隔离邮件处置接口将拒绝全部请求");
+    }
+    
+    // 图片条码识别配置
+    let barcode_decoder = match get_env_string_or_default("BARCODE_DECODER", "zbar").as_str() {
+        "none" => BarcodeDecoderConfig::Disabled,
+        other => {
+            if other != "zbar" {
+                debug!("未知的条码识别后端: {}，使用zbar", other);
+            }
+            BarcodeDecoderConfig::Zbar {
+                program: PathBuf::from(get_env_string_or_default("ZBAR_PROGRAM", "zbarimg")),
+            }
+        }
+    };
     
     info!("配置加载完成: 服务器地址={}, 数据库={}", server_addr, db_name);
     
@@ -104,5 +262,15 @@ pub fn get_config() -> AppConfig {
         server_addr,
         jaeger_endpoint,
         db_config,
+        schema_check,
+        db_retry,
+        mail_storage_dir,
+        safe_download_password,
+        export_dir,
+        export_sync_limit,
+        mail_action_backend,
+        quarantine_four_eyes,
+        quarantine_operators,
+        barcode_decoder,
     }
 } 
\ No newline at end of file
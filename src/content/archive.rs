@@ -0,0 +1,881 @@
+//! 压缩包附件解析
+//!
+//! 支持ZIP、7z的成员列举与解压哈希，RAR仅解析文件头列举成员（不解压）。
+//! 嵌套压缩包按深度限制递归展开，并通过条目数、单文件大小、总解压大小和压缩比
+//! 四项限制防御压缩炸弹
+
+use std::io::{Cursor, Read};
+
+use serde::{Deserialize, Serialize};
+
+use crate::content::digest::compute_digests;
+use crate::content::file_type::{detect_file_type, FileKind};
+
+/// 压缩包解析限制
+#[derive(Debug, Clone)]
+pub struct ArchiveLimits {
+    /// 嵌套压缩包最大展开深度（顶层为1）
+    pub max_depth: usize,
+    /// 最多列举的条目数（含嵌套）
+    pub max_entries: usize,
+    /// 单个条目最大解压大小（字节）
+    pub max_entry_size: u64,
+    /// 所有条目累计最大解压大小（字节）
+    pub max_total_size: u64,
+    /// 单个条目最大压缩比
+    pub max_ratio: u64,
+}
+
+impl Default for ArchiveLimits {
+    fn default() -> Self {
+        Self {
+            max_depth: 3,
+            max_entries: 1000,
+            max_entry_size: 64 * 1024 * 1024,
+            max_total_size: 256 * 1024 * 1024,
+            max_ratio: 200,
+        }
+    }
+}
+
+/// 压缩包条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveEntry {
+    /// 条目路径
+    pub name: String,
+    /// 解压后大小（字节）
+    pub size: u64,
+    /// 压缩后大小（字节），固实压缩的7z条目为0
+    pub compressed_size: u64,
+    /// 是否为目录
+    pub is_dir: bool,
+    /// 是否加密
+    pub encrypted: bool,
+    /// MD5，未解压时为空
+    pub md5: Option<String>,
+    /// SHA1，未解压时为空
+    pub sha1: Option<String>,
+    /// SHA256，未解压时为空
+    pub sha256: Option<String>,
+    /// 根据文件签名识别出的类型，未解压时为空
+    pub detected_type: Option<FileKind>,
+    /// 未能解压的原因
+    pub error: Option<String>,
+    /// 嵌套压缩包的成员
+    pub children: Vec<ArchiveEntry>,
+}
+
+impl ArchiveEntry {
+    fn new(name: String, size: u64, compressed_size: u64, is_dir: bool, encrypted: bool) -> Self {
+        Self {
+            name,
+            size,
+            compressed_size,
+            is_dir,
+            encrypted,
+            md5: None,
+            sha1: None,
+            sha256: None,
+            detected_type: None,
+            error: None,
+            children: Vec::new(),
+        }
+    }
+}
+
+/// 压缩包解析结果
+#[derive(Debug, Clone)]
+pub struct ArchiveListing {
+    /// 压缩包格式
+    pub format: FileKind,
+    /// 是否使用了解压密码
+    pub password_used: bool,
+    /// 是否因触发限制而截断
+    pub truncated: bool,
+    /// 解析过程中的警告
+    pub warnings: Vec<String>,
+    /// 顶层条目
+    pub entries: Vec<ArchiveEntry>,
+}
+
+/// 条目读取失败的原因
+enum ReadFailure {
+    /// 解压出错，加密条目通常是密码错误
+    Decode(String),
+    /// 实际解压大小超过限制
+    Limit,
+}
+
+impl ReadFailure {
+    fn reason(self) -> String {
+        match self {
+            ReadFailure::Decode(reason) => reason,
+            ReadFailure::Limit => "实际解压大小超过上限，疑似压缩炸弹".to_string(),
+        }
+    }
+}
+
+/// 7z第一个条目的读取结果，用于判断密码是否正确
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FirstEntry {
+    /// 没有读取任何条目（没有内容或超出限制）
+    Skipped,
+    /// 读取成功
+    Read,
+    /// 解压失败
+    Failed,
+}
+
+/// 尝试密码前的解析状态，密码错误时恢复
+struct InspectorState {
+    entries_left: usize,
+    bytes_left: u64,
+    truncated: bool,
+    warnings: usize,
+}
+
+/// 解析过程中的共享状态
+struct Inspector<'a> {
+    limits: &'a ArchiveLimits,
+    passwords: &'a [String],
+    entries_left: usize,
+    bytes_left: u64,
+    password_used: bool,
+    truncated: bool,
+    warnings: Vec<String>,
+}
+
+/// 解析压缩包附件
+///
+/// `passwords`为候选解压密码，按顺序尝试；不是支持的压缩格式时返回错误
+pub fn inspect_archive(data: &[u8], passwords: &[String], limits: &ArchiveLimits) -> anyhow::Result<ArchiveListing> {
+    let format = detect_file_type(data);
+    if !matches!(format, FileKind::Zip | FileKind::SevenZip | FileKind::Rar) {
+        return Err(anyhow::anyhow!("不支持的压缩包格式: {:?}", format));
+    }
+
+    let mut inspector = Inspector {
+        limits,
+        passwords,
+        entries_left: limits.max_entries,
+        bytes_left: limits.max_total_size,
+        password_used: false,
+        truncated: false,
+        warnings: Vec::new(),
+    };
+    let entries = inspector.inspect(format, data, 1)?;
+
+    Ok(ArchiveListing {
+        format,
+        password_used: inspector.password_used,
+        truncated: inspector.truncated,
+        warnings: inspector.warnings,
+        entries,
+    })
+}
+
+impl Inspector<'_> {
+    fn inspect(&mut self, format: FileKind, data: &[u8], depth: usize) -> anyhow::Result<Vec<ArchiveEntry>> {
+        match format {
+            FileKind::Zip => self.inspect_zip(data, depth),
+            FileKind::SevenZip => self.inspect_7z(data, depth),
+            FileKind::Rar => self.inspect_rar(data),
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// 占用一个条目名额，超出时标记截断
+    fn take_entry_slot(&mut self) -> bool {
+        if self.entries_left == 0 {
+            if !self.truncated {
+                self.warnings.push(format!("条目数超过上限{}，其余条目未列出", self.limits.max_entries));
+            }
+            self.truncated = true;
+            return false;
+        }
+        self.entries_left -= 1;
+        true
+    }
+
+    /// 检查条目是否允许解压，不允许时返回原因
+    fn check_extract(&self, size: u64, compressed_size: u64) -> Option<String> {
+        if size > self.limits.max_entry_size {
+            return Some(format!("条目大小{}超过单文件上限{}", size, self.limits.max_entry_size));
+        }
+        if size > self.bytes_left {
+            return Some("累计解压大小超过上限".to_string());
+        }
+        if compressed_size > 0 && size / compressed_size > self.limits.max_ratio {
+            return Some(format!("压缩比{}超过上限{}，疑似压缩炸弹", size / compressed_size, self.limits.max_ratio));
+        }
+        None
+    }
+
+    /// 在限制范围内读取条目内容，实际大小超出声明或限制时返回错误
+    fn read_bounded(&mut self, reader: &mut dyn Read) -> Result<Vec<u8>, ReadFailure> {
+        let cap = self.limits.max_entry_size.min(self.bytes_left);
+        let mut buffer = Vec::new();
+        reader
+            .take(cap + 1)
+            .read_to_end(&mut buffer)
+            .map_err(|e| ReadFailure::Decode(format!("解压失败: {}", e)))?;
+        if buffer.len() as u64 > cap {
+            self.truncated = true;
+            return Err(ReadFailure::Limit);
+        }
+        self.bytes_left -= buffer.len() as u64;
+        Ok(buffer)
+    }
+
+    /// 填充条目的哈希与类型，并按需展开嵌套压缩包
+    fn fill_entry(&mut self, entry: &mut ArchiveEntry, data: Vec<u8>, depth: usize) {
+        let digests = compute_digests(&data);
+        let detected_type = detect_file_type(&data);
+        entry.md5 = Some(digests.md5);
+        entry.sha1 = Some(digests.sha1);
+        entry.sha256 = Some(digests.sha256);
+        entry.detected_type = Some(detected_type);
+
+        if matches!(detected_type, FileKind::Zip | FileKind::SevenZip | FileKind::Rar) {
+            if depth >= self.limits.max_depth {
+                self.warnings.push(format!("{} 超过嵌套深度上限{}，未展开", entry.name, self.limits.max_depth));
+                return;
+            }
+            match self.inspect(detected_type, &data, depth + 1) {
+                Ok(children) => entry.children = children,
+                Err(e) => entry.error = Some(format!("嵌套压缩包解析失败: {}", e)),
+            }
+        }
+    }
+
+    fn inspect_zip(&mut self, data: &[u8], depth: usize) -> anyhow::Result<Vec<ArchiveEntry>> {
+        let mut archive = zip::ZipArchive::new(Cursor::new(data))?;
+        let mut entries = Vec::new();
+
+        for index in 0..archive.len() {
+            if !self.take_entry_slot() {
+                break;
+            }
+
+            let (mut entry, encrypted) = {
+                let file = archive.by_index_raw(index)?;
+                let entry = ArchiveEntry::new(
+                    file.name().to_string(),
+                    file.size(),
+                    file.compressed_size(),
+                    file.is_dir(),
+                    file.encrypted(),
+                );
+                (entry, file.encrypted())
+            };
+
+            if entry.is_dir {
+                entries.push(entry);
+                continue;
+            }
+            if let Some(reason) = self.check_extract(entry.size, entry.compressed_size) {
+                entry.error = Some(reason);
+                entries.push(entry);
+                continue;
+            }
+
+            let content = if encrypted {
+                self.read_encrypted_zip_entry(&mut archive, index)
+            } else {
+                match archive.by_index(index) {
+                    Ok(mut file) => self.read_bounded(&mut file).map_err(ReadFailure::reason),
+                    Err(e) => Err(format!("解压失败: {}", e)),
+                }
+            };
+
+            match content {
+                Ok(content) => self.fill_entry(&mut entry, content, depth),
+                Err(reason) => entry.error = Some(reason),
+            }
+            entries.push(entry);
+        }
+
+        Ok(entries)
+    }
+
+    /// 依次尝试候选密码解压ZIP加密条目
+    fn read_encrypted_zip_entry(
+        &mut self,
+        archive: &mut zip::ZipArchive<Cursor<&[u8]>>,
+        index: usize,
+    ) -> Result<Vec<u8>, String> {
+        if self.passwords.is_empty() {
+            return Err("条目已加密且没有可用的解压密码".to_string());
+        }
+
+        for password in self.passwords {
+            let mut file = match archive.by_index_decrypt(index, password.as_bytes()) {
+                Ok(file) => file,
+                Err(_) => continue,
+            };
+            // ZipCrypto的密码校验存在误判，解压失败时继续尝试下一个密码
+            if let Ok(content) = self.read_bounded(&mut file) {
+                self.password_used = true;
+                return Ok(content);
+            }
+        }
+
+        Err("解压密码错误".to_string())
+    }
+
+    fn inspect_7z(&mut self, data: &[u8], depth: usize) -> anyhow::Result<Vec<ArchiveEntry>> {
+        let len = data.len() as u64;
+        let open = |password: sevenz_rust::Password| sevenz_rust::SevenZReader::new(Cursor::new(data), len, password);
+        let mut last_error = match open(sevenz_rust::Password::empty()) {
+            Ok(reader) if !Self::encrypted_7z(&reader) => return Ok(self.extract_7z(reader, false, depth).0),
+            Ok(_) => anyhow::anyhow!("条目已加密且没有可用的解压密码"),
+            Err(e) => anyhow::anyhow!("{}", e),
+        };
+
+        // 只加密内容时打开不校验密码，读出第一个条目才能确认密码正确；
+        // 每次尝试前恢复解析状态，密码错误的尝试不占用条目和大小名额
+        let state = self.save_state();
+        let mut rejected = None;
+        for password in self.passwords {
+            let reader = match open(password.as_str().into()) {
+                Ok(reader) => reader,
+                Err(e) => {
+                    last_error = anyhow::anyhow!("{}", e);
+                    continue;
+                }
+            };
+            self.restore_state(&state);
+            let (entries, first) = self.extract_7z(reader, true, depth);
+            match first {
+                FirstEntry::Failed => rejected = Some(entries),
+                FirstEntry::Read => {
+                    self.password_used = true;
+                    return Ok(entries);
+                }
+                FirstEntry::Skipped => return Ok(entries),
+            }
+        }
+
+        let mut entries = rejected.ok_or(last_error)?;
+        for entry in entries.iter_mut().filter(|entry| !entry.is_dir) {
+            entry.error = Some("解压密码错误".to_string());
+        }
+        Ok(entries)
+    }
+
+    /// 列举7z条目并按顺序解压，返回条目和第一个条目的读取结果
+    fn extract_7z(
+        &mut self,
+        mut reader: sevenz_rust::SevenZReader<Cursor<&[u8]>>,
+        encrypted: bool,
+        depth: usize,
+    ) -> (Vec<ArchiveEntry>, FirstEntry) {
+        let mut entries = Vec::new();
+        let mut pending = Vec::new();
+        for (index, file) in reader.archive().files.iter().enumerate() {
+            if !self.take_entry_slot() {
+                break;
+            }
+            entries.push(ArchiveEntry::new(
+                file.name.clone(),
+                file.size,
+                file.compressed_size,
+                file.is_directory,
+                encrypted,
+            ));
+            if !file.is_directory && file.has_stream {
+                pending.push(index);
+            }
+        }
+
+        // 7z固实压缩只能按顺序解压，遇到超限条目时停止后续解压
+        let mut extracted: Vec<(String, Vec<u8>)> = Vec::new();
+        let mut stop_reason: Option<String> = None;
+        let mut first = FirstEntry::Skipped;
+        let result = reader.for_each_entries(|file, stream| {
+            if file.is_directory || !file.has_stream {
+                return Ok(true);
+            }
+            if let Some(reason) = self.check_extract(file.size, file.compressed_size) {
+                stop_reason = Some(reason);
+                return Ok(false);
+            }
+            let content = self.read_bounded(stream);
+            if extracted.is_empty() {
+                first = match &content {
+                    Ok(_) => FirstEntry::Read,
+                    Err(ReadFailure::Decode(_)) => FirstEntry::Failed,
+                    Err(ReadFailure::Limit) => FirstEntry::Skipped,
+                };
+            }
+            match content {
+                Ok(content) => {
+                    extracted.push((file.name.clone(), content));
+                    Ok(true)
+                }
+                Err(failure) => {
+                    stop_reason = Some(failure.reason());
+                    Ok(false)
+                }
+            }
+        });
+        if let Err(e) = result {
+            if extracted.is_empty() && first == FirstEntry::Skipped {
+                first = FirstEntry::Failed;
+            }
+            stop_reason = Some(format!("解压失败: {}", e));
+        }
+        if first == FirstEntry::Failed {
+            return (entries, first);
+        }
+
+        for (name, content) in extracted {
+            let target = entries
+                .iter_mut()
+                .find(|entry| entry.name == name && entry.md5.is_none() && !entry.is_dir);
+            if let Some(entry) = target {
+                self.fill_entry(entry, content, depth);
+            }
+        }
+        if let Some(reason) = stop_reason {
+            for index in pending {
+                if let Some(entry) = entries.get_mut(index)
+                    && entry.md5.is_none()
+                    && entry.error.is_none()
+                {
+                    entry.error = Some(reason.clone());
+                }
+            }
+        }
+
+        (entries, first)
+    }
+
+    /// 7z压缩包的内容是否使用AES加密
+    fn encrypted_7z(reader: &sevenz_rust::SevenZReader<Cursor<&[u8]>>) -> bool {
+        reader.archive().folders.iter().any(|folder| {
+            folder
+                .coders
+                .iter()
+                .any(|coder| coder.decompression_method_id() == sevenz_rust::SevenZMethod::ID_AES256SHA256)
+        })
+    }
+
+    fn save_state(&self) -> InspectorState {
+        InspectorState {
+            entries_left: self.entries_left,
+            bytes_left: self.bytes_left,
+            truncated: self.truncated,
+            warnings: self.warnings.len(),
+        }
+    }
+
+    fn restore_state(&mut self, state: &InspectorState) {
+        self.entries_left = state.entries_left;
+        self.bytes_left = state.bytes_left;
+        self.truncated = state.truncated;
+        self.warnings.truncate(state.warnings);
+    }
+
+    fn inspect_rar(&mut self, data: &[u8]) -> anyhow::Result<Vec<ArchiveEntry>> {
+        let headers = rar::list_entries(data)?;
+        let mut entries = Vec::new();
+        for header in headers {
+            if !self.take_entry_slot() {
+                break;
+            }
+            let mut entry = ArchiveEntry::new(header.name, header.size, header.compressed_size, header.is_dir, header.encrypted);
+            if !entry.is_dir {
+                entry.error = Some("RAR条目仅列举，不解压".to_string());
+            }
+            entries.push(entry);
+        }
+        Ok(entries)
+    }
+}
+
+/// RAR文件头解析（RAR4与RAR5），只读取条目元数据
+mod rar {
+    use anyhow::{anyhow, Result};
+
+    pub struct RarHeader {
+        pub name: String,
+        pub size: u64,
+        pub compressed_size: u64,
+        pub is_dir: bool,
+        pub encrypted: bool,
+    }
+
+    const RAR4_SIGNATURE: &[u8] = b"Rar!\x1a\x07\x00";
+    pub(super) const RAR5_SIGNATURE: &[u8] = b"Rar!\x1a\x07\x01\x00";
+
+    pub fn list_entries(data: &[u8]) -> Result<Vec<RarHeader>> {
+        if data.starts_with(RAR5_SIGNATURE) {
+            list_rar5(data)
+        } else if data.starts_with(RAR4_SIGNATURE) {
+            list_rar4(data)
+        } else {
+            Err(anyhow!("无法识别的RAR版本"))
+        }
+    }
+
+    fn read_u16(data: &[u8], pos: usize) -> Option<u16> {
+        Some(u16::from_le_bytes(data.get(pos..pos + 2)?.try_into().ok()?))
+    }
+
+    fn read_u32(data: &[u8], pos: usize) -> Option<u32> {
+        Some(u32::from_le_bytes(data.get(pos..pos + 4)?.try_into().ok()?))
+    }
+
+    fn list_rar4(data: &[u8]) -> Result<Vec<RarHeader>> {
+        const FILE_HEADER: u8 = 0x74;
+        const END_HEADER: u8 = 0x7B;
+        let truncated = || anyhow!("RAR文件头不完整");
+
+        let mut entries = Vec::new();
+        let mut pos = RAR4_SIGNATURE.len();
+        while pos + 7 <= data.len() {
+            let head_type = data[pos + 2];
+            let flags = read_u16(data, pos + 3).ok_or_else(truncated)?;
+            let head_size = read_u16(data, pos + 5).ok_or_else(truncated)? as usize;
+            if head_size < 7 {
+                return Err(anyhow!("RAR文件头损坏"));
+            }
+            // 主头部设置了加密标志时，后续文件头无法读取
+            if head_type == 0x73 && flags & 0x0080 != 0 {
+                return Err(anyhow!("RAR文件头已加密，无法列举"));
+            }
+
+            let mut add_size = 0u64;
+            if head_type == FILE_HEADER {
+                let pack_low = read_u32(data, pos + 7).ok_or_else(truncated)? as u64;
+                let unpack_low = read_u32(data, pos + 11).ok_or_else(truncated)? as u64;
+                let name_size = read_u16(data, pos + 26).ok_or_else(truncated)? as usize;
+                let attributes = read_u32(data, pos + 28).ok_or_else(truncated)?;
+                let (pack_high, unpack_high, name_pos) = if flags & 0x0100 != 0 {
+                    (
+                        read_u32(data, pos + 32).ok_or_else(truncated)? as u64,
+                        read_u32(data, pos + 36).ok_or_else(truncated)? as u64,
+                        pos + 40,
+                    )
+                } else {
+                    (0, 0, pos + 32)
+                };
+                let raw_name = data.get(name_pos..name_pos + name_size).ok_or_else(truncated)?;
+                // Unicode文件名以NUL分隔，前半部分为兼容名称
+                let raw_name = raw_name.split(|&b| b == 0).next().unwrap_or(raw_name);
+
+                add_size = (pack_high << 32) | pack_low;
+                entries.push(RarHeader {
+                    name: String::from_utf8_lossy(raw_name).replace('\\', "/"),
+                    size: (unpack_high << 32) | unpack_low,
+                    compressed_size: add_size,
+                    is_dir: flags & 0x00E0 == 0x00E0 || attributes & 0x10 != 0,
+                    encrypted: flags & 0x0004 != 0,
+                });
+            } else if head_type == END_HEADER {
+                break;
+            } else if flags & 0x8000 != 0 {
+                add_size = read_u32(data, pos + 7).ok_or_else(truncated)? as u64;
+            }
+
+            pos = usize::try_from(add_size)
+                .ok()
+                .and_then(|add_size| pos.checked_add(head_size)?.checked_add(add_size))
+                .ok_or_else(truncated)?;
+        }
+
+        Ok(entries)
+    }
+
+    /// 读取RAR5变长整数
+    fn read_vint(data: &[u8], pos: &mut usize) -> Option<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = *data.get(*pos)?;
+            *pos += 1;
+            value |= ((byte & 0x7F) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Some(value);
+            }
+        }
+        None
+    }
+
+    /// 读取RAR5变长整数作为长度或偏移，超出usize时视为损坏
+    fn read_len(data: &[u8], pos: &mut usize) -> Option<usize> {
+        usize::try_from(read_vint(data, pos)?).ok()
+    }
+
+    fn list_rar5(data: &[u8]) -> Result<Vec<RarHeader>> {
+        const FILE_HEADER: u64 = 2;
+        const ENCRYPTION_HEADER: u64 = 4;
+        const END_HEADER: u64 = 5;
+        let truncated = || anyhow!("RAR文件头不完整");
+        // 在文件范围内前进`len`字节
+        let advance = |pos: usize, len: usize| pos.checked_add(len).filter(|&end| end <= data.len());
+
+        let mut entries = Vec::new();
+        let mut pos = RAR5_SIGNATURE.len();
+        while pos + 4 < data.len() {
+            // 跳过头部CRC32
+            let mut cursor = pos + 4;
+            let header_size = read_len(data, &mut cursor).ok_or_else(truncated)?;
+            let header_start = cursor;
+            let header_end = advance(header_start, header_size).ok_or_else(truncated)?;
+            // 头部各字段只在头部范围内读取
+            let header = &data[..header_end];
+            let header_type = read_vint(header, &mut cursor).ok_or_else(truncated)?;
+            let header_flags = read_vint(header, &mut cursor).ok_or_else(truncated)?;
+            if header_flags & 0x01 != 0 {
+                read_vint(header, &mut cursor).ok_or_else(truncated)?;
+            }
+            let data_size = if header_flags & 0x02 != 0 {
+                read_len(header, &mut cursor).ok_or_else(truncated)?
+            } else {
+                0
+            };
+
+            match header_type {
+                ENCRYPTION_HEADER => return Err(anyhow!("RAR文件头已加密，无法列举")),
+                END_HEADER => break,
+                FILE_HEADER => {
+                    let file_flags = read_vint(header, &mut cursor).ok_or_else(truncated)?;
+                    let unpacked_size = read_vint(header, &mut cursor).ok_or_else(truncated)?;
+                    read_vint(header, &mut cursor).ok_or_else(truncated)?; // 文件属性
+                    if file_flags & 0x02 != 0 {
+                        cursor = advance(cursor, 4).filter(|&end| end <= header_end).ok_or_else(truncated)?; // 修改时间
+                    }
+                    if file_flags & 0x04 != 0 {
+                        cursor = advance(cursor, 4).filter(|&end| end <= header_end).ok_or_else(truncated)?; // 数据CRC32
+                    }
+                    read_vint(header, &mut cursor).ok_or_else(truncated)?; // 压缩信息
+                    read_vint(header, &mut cursor).ok_or_else(truncated)?; // 主机系统
+                    let name_len = read_len(header, &mut cursor).ok_or_else(truncated)?;
+                    let name_end = advance(cursor, name_len).filter(|&end| end <= header_end).ok_or_else(truncated)?;
+                    let name = &header[cursor..name_end];
+                    cursor = name_end;
+
+                    // 附加区中存在加密记录（类型0x01）即表示内容已加密
+                    let encrypted = header_flags & 0x01 != 0 && {
+                        let extra = &header[cursor..];
+                        let mut p = 0;
+                        read_vint(extra, &mut p).is_some() && read_vint(extra, &mut p) == Some(0x01)
+                    };
+
+                    entries.push(RarHeader {
+                        name: String::from_utf8_lossy(name).to_string(),
+                        size: unpacked_size,
+                        compressed_size: data_size as u64,
+                        is_dir: file_flags & 0x01 != 0,
+                        encrypted,
+                    });
+                }
+                _ => {}
+            }
+
+            // 最后一个条目的数据区可能被截断，不影响已列举的条目
+            pos = header_end.checked_add(data_size).ok_or_else(truncated)?;
+        }
+
+        Ok(entries)
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn zip_with(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let options = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+        for (name, data) in files {
+            writer.start_file(*name, options).unwrap();
+            writer.write_all(data).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    fn encrypted_7z(name: &str, data: &[u8], password: &str) -> Vec<u8> {
+        let mut writer = sevenz_rust::SevenZWriter::new(Cursor::new(Vec::new())).unwrap();
+        writer.set_content_methods(vec![
+            sevenz_rust::AesEncoderOptions::new(password.into()).into(),
+            sevenz_rust::SevenZMethod::LZMA2.into(),
+        ]);
+        // 只加密内容，打开时不会校验密码
+        writer.set_encrypt_header(false);
+        let mut entry = sevenz_rust::SevenZArchiveEntry::new();
+        entry.name = name.to_string();
+        entry.has_stream = true;
+        writer.push_archive_entry(entry, Some(data)).unwrap();
+        writer.finish().unwrap().into_inner()
+    }
+
+    fn passwords(list: &[&str]) -> Vec<String> {
+        list.iter().map(|password| password.to_string()).collect()
+    }
+
+    #[test]
+    fn nested_archives_stop_at_max_depth() {
+        let inner = zip_with(&[("payload.txt", b"hello")]);
+        let middle = zip_with(&[("inner.zip", &inner)]);
+        let outer = zip_with(&[("middle.zip", &middle)]);
+        let limits = ArchiveLimits { max_depth: 2, ..ArchiveLimits::default() };
+
+        let listing = inspect_archive(&outer, &[], &limits).unwrap();
+        let middle = &listing.entries[0];
+        assert_eq!(middle.detected_type, Some(FileKind::Zip));
+        assert_eq!(middle.children[0].name, "inner.zip");
+        assert!(middle.children[0].md5.is_some());
+        assert!(middle.children[0].children.is_empty());
+        assert!(listing.warnings.iter().any(|warning| warning.contains("嵌套深度")));
+    }
+
+    #[test]
+    fn entry_count_limit_truncates_listing() {
+        let data = zip_with(&[("a.txt", b"a"), ("b.txt", b"b"), ("c.txt", b"c")]);
+        let limits = ArchiveLimits { max_entries: 2, ..ArchiveLimits::default() };
+
+        let listing = inspect_archive(&data, &[], &limits).unwrap();
+        assert_eq!(listing.entries.len(), 2);
+        assert!(listing.truncated);
+        assert_eq!(listing.warnings.len(), 1);
+    }
+
+    #[test]
+    fn size_limits_skip_extraction() {
+        let data = zip_with(&[("a.bin", &[1u8; 100]), ("b.bin", &[2u8; 100]), ("big.bin", &[3u8; 300])]);
+        let limits = ArchiveLimits {
+            max_entry_size: 200,
+            max_total_size: 150,
+            ..ArchiveLimits::default()
+        };
+
+        let listing = inspect_archive(&data, &[], &limits).unwrap();
+        let [a, b, big] = listing.entries.as_slice() else {
+            panic!("应列出三个条目");
+        };
+        assert!(a.md5.is_some() && a.error.is_none());
+        assert!(b.error.as_deref().is_some_and(|error| error.contains("累计")), "{:?}", b.error);
+        assert!(big.error.as_deref().is_some_and(|error| error.contains("单文件上限")), "{:?}", big.error);
+    }
+
+    #[test]
+    fn compression_ratio_limit_flags_bombs() {
+        let data = zip_with(&[("zeros.bin", &vec![0u8; 512 * 1024])]);
+
+        let listing = inspect_archive(&data, &[], &ArchiveLimits::default()).unwrap();
+        let entry = &listing.entries[0];
+        assert!(entry.md5.is_none());
+        assert!(entry.error.as_deref().is_some_and(|error| error.contains("压缩比")), "{:?}", entry.error);
+    }
+
+    #[test]
+    fn seven_zip_password_is_checked_on_first_entry() {
+        let data = encrypted_7z("secret.txt", b"attack at dawn", "correct");
+
+        let listing = inspect_archive(&data, &passwords(&["wrong", "correct"]), &ArchiveLimits::default()).unwrap();
+        assert!(listing.password_used);
+        let entry = &listing.entries[0];
+        assert!(entry.encrypted);
+        assert_eq!(entry.md5, Some(compute_digests(b"attack at dawn").md5));
+
+        let listing = inspect_archive(&data, &passwords(&["wrong"]), &ArchiveLimits::default()).unwrap();
+        assert!(!listing.password_used);
+        assert_eq!(listing.entries.len(), 1);
+        assert_eq!(listing.entries[0].error.as_deref(), Some("解压密码错误"));
+
+        assert!(inspect_archive(&data, &[], &ArchiveLimits::default()).is_err());
+    }
+
+    #[test]
+    fn truncated_zip_is_rejected() {
+        let data = zip_with(&[("a.txt", b"hello")]);
+        assert!(inspect_archive(&data[..data.len() / 2], &[], &ArchiveLimits::default()).is_err());
+    }
+
+    fn vint(mut value: u64) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        loop {
+            let byte = (value & 0x7F) as u8;
+            value >>= 7;
+            if value == 0 {
+                bytes.push(byte);
+                return bytes;
+            }
+            bytes.push(byte | 0x80);
+        }
+    }
+
+    /// RAR5文件头，`name_len`为记录的文件名长度
+    fn rar5_file(name: &[u8], name_len: u64, packed: &[u8]) -> Vec<u8> {
+        let mut header = Vec::new();
+        header.extend(vint(2)); // 文件头
+        header.extend(vint(0x02)); // 带数据区
+        header.extend(vint(packed.len() as u64));
+        header.extend(vint(0)); // 文件标志
+        header.extend(vint(1234)); // 解压后大小
+        header.extend(vint(0x20)); // 文件属性
+        header.extend(vint(0)); // 压缩信息
+        header.extend(vint(0)); // 主机系统
+        header.extend(vint(name_len));
+        header.extend_from_slice(name);
+
+        let mut block = vec![0u8; 4];
+        block.extend(vint(header.len() as u64));
+        block.extend(header);
+        block.extend_from_slice(packed);
+        block
+    }
+
+    fn rar5(blocks: &[Vec<u8>]) -> Vec<u8> {
+        let mut data = rar::RAR5_SIGNATURE.to_vec();
+        for block in blocks {
+            data.extend_from_slice(block);
+        }
+        data
+    }
+
+    #[test]
+    fn rar5_headers_are_listed() {
+        let data = rar5(&[rar5_file(b"docs/a.txt", 10, b"packed"), rar5_file(b"b.exe", 5, b"xyz")]);
+        let entries = rar::list_entries(&data).unwrap();
+        let names: Vec<&str> = entries.iter().map(|entry| entry.name.as_str()).collect();
+        assert_eq!(names, ["docs/a.txt", "b.exe"]);
+        assert_eq!(entries[0].size, 1234);
+        assert_eq!(entries[0].compressed_size, 6);
+    }
+
+    #[test]
+    fn malformed_rar5_headers_are_rejected() {
+        // 文件名长度超出头部
+        assert!(rar::list_entries(&rar5(&[rar5_file(b"a.txt", 500, b"")])).is_err());
+        assert!(rar::list_entries(&rar5(&[rar5_file(b"a.txt", u64::MAX >> 1, b"")])).is_err());
+
+        // 头部大小超出文件
+        let mut huge = vec![0u8; 4];
+        huge.extend(vint(u64::MAX >> 1));
+        huge.extend(vint(2));
+        assert!(rar::list_entries(&rar5(&[huge])).is_err());
+
+        // 头部被截断
+        let block = rar5_file(b"a.txt", 5, b"");
+        assert!(rar::list_entries(&rar5(&[block[..block.len() - 3].to_vec()])).is_err());
+
+        // 变长整数没有结束
+        let mut endless = vec![0u8; 4];
+        endless.extend([0xFF; 12]);
+        assert!(rar::list_entries(&rar5(&[endless])).is_err());
+    }
+
+    #[test]
+    fn truncated_rar4_header_is_rejected() {
+        let mut data = b"Rar!\x1a\x07\x00".to_vec();
+        // 文件头声明了名称但数据不完整
+        data.extend([0, 0, 0x74, 0, 0, 40, 0]);
+        data.extend([0u8; 10]);
+        assert!(rar::list_entries(&data).is_err());
+    }
+}
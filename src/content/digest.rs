@@ -0,0 +1,186 @@
+//! 文件哈希计算
+
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll, ready};
+
+use md5::Md5;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncRead, ReadBuf};
+
+/// 文件哈希值（小写十六进制）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileDigests {
+    /// MD5
+    pub md5: String,
+    /// SHA1
+    pub sha1: String,
+    /// SHA256
+    pub sha256: String,
+}
+
+/// 计算文件的MD5、SHA1和SHA256
+pub fn compute_digests(data: &[u8]) -> FileDigests {
+    let mut hasher = DigestHasher::default();
+    hasher.update(data);
+    hasher.finish()
+}
+
+/// 增量哈希计算，用于边读取边计算大文件的哈希
+#[derive(Default)]
+pub struct DigestHasher {
+    md5: Md5,
+    sha1: Sha1,
+    sha256: Sha256,
+    len: u64,
+}
+
+impl DigestHasher {
+    /// 追加一段数据
+    pub fn update(&mut self, chunk: &[u8]) {
+        self.md5.update(chunk);
+        self.sha1.update(chunk);
+        self.sha256.update(chunk);
+        self.len += chunk.len() as u64;
+    }
+
+    /// 已读取的字节数
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// 是否还没有读取数据
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// 结束计算并返回哈希值
+    pub fn finish(self) -> FileDigests {
+        FileDigests {
+            md5: hex::encode(self.md5.finalize()),
+            sha1: hex::encode(self.sha1.finalize()),
+            sha256: hex::encode(self.sha256.finalize()),
+        }
+    }
+}
+
+/// 读取结束时的校验，参数为实际读取的字节数和哈希值，返回错误时读取失败
+pub type DigestCheck = Box<dyn FnOnce(u64, FileDigests) -> io::Result<()> + Send>;
+
+/// 边读取边计算哈希的读取器
+///
+/// 读到`expected_len`字节或提前结束时调用校验，校验失败时返回错误而不返回最后一段数据，
+/// 下游拿到的内容不会是完整且未经校验的文件；超出`expected_len`的数据同样返回错误
+pub struct DigestReader<R> {
+    inner: R,
+    hasher: DigestHasher,
+    expected_len: u64,
+    check: Option<DigestCheck>,
+}
+
+impl<R> DigestReader<R> {
+    /// 包装读取器，`expected_len`为记录的文件大小
+    pub fn new(inner: R, expected_len: u64, check: DigestCheck) -> Self {
+        Self {
+            inner,
+            hasher: DigestHasher::default(),
+            expected_len,
+            check: Some(check),
+        }
+    }
+
+    /// 执行校验，只执行一次
+    fn finish(&mut self) -> io::Result<()> {
+        match self.check.take() {
+            Some(check) => {
+                let hasher = std::mem::take(&mut self.hasher);
+                let len = hasher.len();
+                check(len, hasher.finish())
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for DigestReader<R> {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if buf.remaining() == 0 {
+            return Poll::Ready(Ok(()));
+        }
+        let before = buf.filled().len();
+        ready!(Pin::new(&mut this.inner).poll_read(cx, buf))?;
+        let read = buf.filled().len() - before;
+
+        if read == 0 {
+            // 文件比记录的短
+            this.finish()?;
+            return Poll::Ready(Ok(()));
+        }
+        if this.check.is_none() {
+            buf.set_filled(before);
+            return Poll::Ready(Err(io::Error::new(io::ErrorKind::InvalidData, "文件比记录的大小更长")));
+        }
+        this.hasher.update(&buf.filled()[before..]);
+        if this.hasher.len() >= this.expected_len
+            && let Err(e) = this.finish()
+        {
+            buf.set_filled(before);
+            return Poll::Ready(Err(e));
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncReadExt;
+
+    /// 内容与期望的哈希不一致时返回错误
+    fn check_sha256(expected: String) -> DigestCheck {
+        Box::new(move |_, digests| {
+            if digests.sha256 == expected {
+                Ok(())
+            } else {
+                Err(io::Error::new(io::ErrorKind::InvalidData, "sha256 mismatch"))
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn digest_reader_passes_matching_content() {
+        let data = vec![7u8; 200_000];
+        let expected = compute_digests(&data).sha256;
+        let mut reader = DigestReader::new(data.as_slice(), data.len() as u64, check_sha256(expected));
+        let mut out = Vec::new();
+        reader.read_to_end(&mut out).await.unwrap();
+        assert_eq!(out, data);
+    }
+
+    #[tokio::test]
+    async fn digest_reader_withholds_the_last_chunk_on_mismatch() {
+        let data = vec![7u8; 100];
+        let mut reader = DigestReader::new(data.as_slice(), 100, check_sha256("0".repeat(64)));
+        let mut buf = [0u8; 64];
+        assert_eq!(reader.read(&mut buf).await.unwrap(), 64);
+        assert_eq!(reader.read(&mut buf).await.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[tokio::test]
+    async fn digest_reader_checks_truncated_and_longer_files() {
+        let data = vec![7u8; 100];
+        let expected = compute_digests(&data).sha256;
+
+        let mut out = Vec::new();
+        let mut truncated = DigestReader::new(&data[..50], 100, check_sha256(expected.clone()));
+        assert!(truncated.read_to_end(&mut out).await.is_err());
+
+        let longer = [data.as_slice(), b"extra"].concat();
+        let mut longer = DigestReader::new(longer.as_slice(), 100, check_sha256(expected));
+        let mut buf = [0u8; 100];
+        assert_eq!(longer.read(&mut buf).await.unwrap(), 100);
+        assert!(longer.read(&mut buf).await.is_err());
+    }
+}
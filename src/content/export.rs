@@ -0,0 +1,409 @@
+//! 邮件批量导出
+//!
+//! 支持两种格式：包含`.eml`文件与`index.csv`索引的ZIP，以及单个mbox（mboxrd变体）文件。
+//! 导出器按邮件逐封写入，调用方无需一次性把所有邮件读入内存。
+//! 每写完一封邮件就把内容交给下层写入器，下层写入器不需要支持定位，可以直接是响应流
+
+use std::collections::HashSet;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use zip::write::SimpleFileOptions;
+
+use crate::content::digest::compute_digests;
+use crate::content::sample_zip::safe_name;
+
+/// ZIP导出中的索引文件名
+pub const INDEX_NAME: &str = "index.csv";
+
+/// 导出格式
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// `.eml`文件加CSV索引的ZIP
+    #[default]
+    Zip,
+    /// 单个mbox文件
+    Mbox,
+}
+
+impl ExportFormat {
+    /// 导出文件的Content-Type
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Zip => "application/zip",
+            ExportFormat::Mbox => "application/mbox",
+        }
+    }
+
+    /// 导出文件扩展名（不含点）
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Zip => "zip",
+            ExportFormat::Mbox => "mbox",
+        }
+    }
+}
+
+/// 待导出的单封邮件
+#[derive(Debug, Clone)]
+pub struct ExportItem {
+    /// 邮件ID
+    pub mail_id: String,
+    /// 邮件时间
+    pub timestamp: Option<DateTime<Utc>>,
+    /// 发件人
+    pub sender: String,
+    /// 收件人
+    pub recipients: Vec<String>,
+    /// 主题
+    pub subject: String,
+    /// 原始邮件内容
+    pub data: Vec<u8>,
+    /// 获取失败的原因，失败的邮件只记录在索引中
+    pub error: Option<String>,
+}
+
+/// CSV索引行
+#[derive(Debug, Serialize)]
+struct IndexRow {
+    mail_id: String,
+    file_name: String,
+    timestamp: String,
+    sender: String,
+    recipients: String,
+    subject: String,
+    size: u64,
+    sha256: String,
+    status: &'static str,
+    error: String,
+}
+
+/// 邮件导出器
+pub struct MailExporter<W: Write>(ExporterKind<W>);
+
+/// 各格式导出器的内部状态
+enum ExporterKind<W: Write> {
+    /// ZIP导出
+    Zip {
+        writer: Box<zip::ZipWriter<SpoolWriter<W>>>,
+        index: Vec<IndexRow>,
+        used_names: HashSet<String>,
+    },
+    /// mbox导出
+    Mbox { writer: SpoolWriter<W> },
+}
+
+impl<W: Write> MailExporter<W> {
+    /// 创建指定格式的导出器
+    pub fn new(format: ExportFormat, writer: W) -> Self {
+        let writer = SpoolWriter::new(writer);
+        match format {
+            ExportFormat::Zip => {
+                let mut used_names = HashSet::new();
+                used_names.insert(INDEX_NAME.to_string());
+                // 每个文件补写完本地头后刷新，逐个文件输出
+                let mut writer = zip::ZipWriter::new(writer);
+                writer.set_flush_on_finish_file(true);
+                MailExporter(ExporterKind::Zip {
+                    writer: Box::new(writer),
+                    index: Vec::new(),
+                    used_names,
+                })
+            }
+            ExportFormat::Mbox => MailExporter(ExporterKind::Mbox { writer }),
+        }
+    }
+
+    /// 写入一封邮件
+    ///
+    /// 获取失败的邮件在ZIP中只写入索引，在mbox中跳过
+    pub fn add(&mut self, item: &ExportItem) -> anyhow::Result<()> {
+        match &mut self.0 {
+            ExporterKind::Zip { writer, index, used_names } => {
+                let mut row = IndexRow {
+                    mail_id: item.mail_id.clone(),
+                    file_name: String::new(),
+                    timestamp: item.timestamp.map(|t| t.to_rfc3339()).unwrap_or_default(),
+                    sender: csv_safe(&item.sender),
+                    recipients: csv_safe(&item.recipients.join("; ")),
+                    subject: csv_safe(&item.subject),
+                    size: item.data.len() as u64,
+                    sha256: String::new(),
+                    status: "ok",
+                    error: String::new(),
+                };
+
+                match &item.error {
+                    Some(error) => {
+                        row.status = "failed";
+                        row.error = csv_safe(error);
+                    }
+                    None => {
+                        let name = unique_eml_name(&item.mail_id, used_names);
+                        let options = SimpleFileOptions::default()
+                            .compression_method(zip::CompressionMethod::Deflated);
+                        writer.start_file(name.as_str(), options)?;
+                        writer.write_all(&item.data)?;
+                        row.file_name = name;
+                        row.sha256 = compute_digests(&item.data).sha256;
+                    }
+                }
+                index.push(row);
+            }
+            ExporterKind::Mbox { writer } => {
+                if item.error.is_none() {
+                    write_mbox_message(writer, item)?;
+                    writer.flush()?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 完成导出并刷新写入器，ZIP会在末尾写入索引
+    pub fn finish(self) -> anyhow::Result<W> {
+        match self.0 {
+            ExporterKind::Zip { mut writer, index, .. } => {
+                let mut csv_writer = csv::Writer::from_writer(Vec::new());
+                for row in &index {
+                    csv_writer.serialize(row)?;
+                }
+                // 没有数据行时serialize不会输出表头，手动补上
+                if index.is_empty() {
+                    csv_writer.write_record([
+                        "mail_id", "file_name", "timestamp", "sender", "recipients",
+                        "subject", "size", "sha256", "status", "error",
+                    ])?;
+                }
+                let data = csv_writer.into_inner().map_err(|e| anyhow::anyhow!("写入索引失败: {}", e))?;
+
+                writer.start_file(INDEX_NAME, SimpleFileOptions::default())?;
+                writer.write_all(&data)?;
+                Ok(writer.finish()?.into_inner()?)
+            }
+            ExporterKind::Mbox { writer } => Ok(writer.into_inner()?),
+        }
+    }
+}
+
+/// 只缓冲最近一次刷新之后内容的写入器
+///
+/// ZipWriter写完一个文件后回到该文件的本地头补写CRC和大小，随后刷新写入器，
+/// 因此只需允许在缓冲范围内定位；刷新时把缓冲的内容交给下层写入器，之后不能再定位到这部分内容
+struct SpoolWriter<W: Write> {
+    /// 下层写入器
+    inner: W,
+    /// 尚未刷新的内容
+    buffer: Vec<u8>,
+    /// 缓冲区起点在整个输出中的位置
+    base: u64,
+    /// 当前位置
+    position: u64,
+}
+
+impl<W: Write> SpoolWriter<W> {
+    /// 创建写入器
+    fn new(inner: W) -> Self {
+        Self { inner, buffer: Vec::new(), base: 0, position: 0 }
+    }
+
+    /// 刷新剩余内容并返回下层写入器
+    fn into_inner(mut self) -> io::Result<W> {
+        self.flush()?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write> Write for SpoolWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.position < self.base {
+            return Err(io::Error::new(io::ErrorKind::Unsupported, "不能写入已输出的内容"));
+        }
+        let offset = (self.position - self.base) as usize;
+        let overwrite = buf.len().min(self.buffer.len() - offset);
+        self.buffer[offset..offset + overwrite].copy_from_slice(&buf[..overwrite]);
+        self.buffer.extend_from_slice(&buf[overwrite..]);
+        self.position += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.buffer.is_empty() {
+            self.inner.write_all(&self.buffer)?;
+            self.base += self.buffer.len() as u64;
+            self.buffer.clear();
+        }
+        self.inner.flush()
+    }
+}
+
+/// ZipWriter要求写入器可读才能开启刷新选项，只能读取尚未输出的内容
+impl<W: Write> Read for SpoolWriter<W> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.position < self.base {
+            return Err(io::Error::new(io::ErrorKind::Unsupported, "不能读取已输出的内容"));
+        }
+        let offset = (self.position - self.base) as usize;
+        let len = buf.len().min(self.buffer.len() - offset);
+        buf[..len].copy_from_slice(&self.buffer[offset..offset + len]);
+        self.position += len as u64;
+        Ok(len)
+    }
+}
+
+impl<W: Write> Seek for SpoolWriter<W> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let end = self.base + self.buffer.len() as u64;
+        let target = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(delta) => self.position.checked_add_signed(delta),
+            SeekFrom::End(delta) => end.checked_add_signed(delta),
+        };
+        match target {
+            Some(target) if (self.base..=end).contains(&target) => {
+                self.position = target;
+                Ok(target)
+            }
+            _ => Err(io::Error::new(io::ErrorKind::Unsupported, "只能在尚未输出的内容中定位")),
+        }
+    }
+}
+
+/// 按mboxrd格式写入一封邮件
+///
+/// 正文中以若干个`>`加`From `开头的行前再加一个`>`，换行统一为LF
+fn write_mbox_message<W: Write>(writer: &mut W, item: &ExportItem) -> std::io::Result<()> {
+    let sender = item
+        .sender
+        .split_whitespace()
+        .next()
+        .filter(|s| !s.is_empty())
+        .unwrap_or("MAILER-DAEMON");
+    let date = item.timestamp.unwrap_or_else(Utc::now).format("%a %b %e %H:%M:%S %Y");
+    writeln!(writer, "From {} {}", sender, date)?;
+
+    let text = String::from_utf8_lossy(&item.data);
+    for line in text.trim_end_matches(['\r', '\n']).split('\n') {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        if line.trim_start_matches('>').starts_with("From ") {
+            writer.write_all(b">")?;
+        }
+        writer.write_all(line.as_bytes())?;
+        writer.write_all(b"\n")?;
+    }
+    writer.write_all(b"\n")
+}
+
+/// 生成不重复的eml文件名
+fn unique_eml_name(mail_id: &str, used: &mut HashSet<String>) -> String {
+    let base = safe_name(mail_id);
+    let name = format!("{}.eml", base);
+    if used.insert(name.clone()) {
+        return name;
+    }
+    (1..)
+        .map(|n| format!("{}({}).eml", base, n))
+        .find(|candidate| used.insert(candidate.clone()))
+        .unwrap_or(name)
+}
+
+/// 防止CSV公式注入：以公式字符开头的字段前加单引号
+///
+/// 主题、发件人等字段由攻击者控制，索引文件常被直接用表格软件打开
+fn csv_safe(value: &str) -> String {
+    if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Read};
+
+    fn item(mail_id: &str, data: &str) -> ExportItem {
+        ExportItem {
+            mail_id: mail_id.to_string(),
+            timestamp: None,
+            sender: "=cmd|' /C calc'!A0".to_string(),
+            recipients: vec!["a@example.org".to_string()],
+            subject: "测试".to_string(),
+            data: data.as_bytes().to_vec(),
+            error: None,
+        }
+    }
+
+    /// 记录每次写入的下层写入器，不支持定位
+    #[derive(Default)]
+    struct Chunks(Vec<Vec<u8>>);
+
+    impl Write for Chunks {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.push(buf.to_vec());
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn zip_streams_one_file_at_a_time() {
+        let mut exporter = MailExporter::new(ExportFormat::Zip, Chunks::default());
+        exporter.add(&item("1", "Subject: a\r\n\r\nfirst")).unwrap();
+        exporter.add(&item("1", "Subject: b\r\n\r\nsecond")).unwrap();
+        let mut missing = item("2", "");
+        missing.error = Some("邮件未找到".to_string());
+        exporter.add(&missing).unwrap();
+        let chunks = exporter.finish().unwrap().0;
+        // 两个邮件文件各一块，索引和中央目录在最后
+        assert!(chunks.len() >= 3, "{}", chunks.len());
+
+        let mut archive = zip::ZipArchive::new(Cursor::new(chunks.concat())).unwrap();
+        let mut second = String::new();
+        archive.by_name("1(1).eml").unwrap().read_to_string(&mut second).unwrap();
+        assert!(second.ends_with("second"));
+        let mut index = String::new();
+        archive.by_name(INDEX_NAME).unwrap().read_to_string(&mut index).unwrap();
+        let rows: Vec<&str> = index.lines().collect();
+        assert_eq!(rows.len(), 4);
+        assert!(rows[1].contains(",'=cmd"), "{}", rows[1]);
+        assert!(rows[3].starts_with("2,,") && rows[3].contains("failed"), "{}", rows[3]);
+    }
+
+    #[test]
+    fn mbox_escapes_from_lines() {
+        let mut exporter = MailExporter::new(ExportFormat::Mbox, Chunks::default());
+        exporter.add(&item("1", "Subject: a\r\n\r\nFrom here\r\n>From there\r\n")).unwrap();
+        exporter.add(&item("2", "Subject: b\r\n\r\nbody")).unwrap();
+        let chunks = exporter.finish().unwrap().0;
+        // 每封邮件写入后立即输出
+        assert_eq!(chunks.len(), 2);
+        let text = String::from_utf8(chunks.concat()).unwrap();
+        assert!(text.starts_with("From =cmd|' "));
+        assert!(text.contains("\n>From here\n>>From there\n\nFrom "), "{}", text);
+    }
+
+    #[test]
+    fn spool_seeks_only_within_buffer() {
+        let mut writer = SpoolWriter::new(Vec::new());
+        writer.write_all(b"hello world").unwrap();
+        writer.seek(SeekFrom::Start(0)).unwrap();
+        writer.write_all(b"HELLO").unwrap();
+        assert_eq!(writer.seek(SeekFrom::End(0)).unwrap(), 11);
+        writer.flush().unwrap();
+        writer.write_all(b"!").unwrap();
+        assert_eq!(writer.stream_position().unwrap(), 12);
+        assert!(writer.seek(SeekFrom::Start(3)).is_err());
+        assert!(writer.seek(SeekFrom::End(1)).is_err());
+        let mut tail = [0u8; 4];
+        writer.seek(SeekFrom::Current(-1)).unwrap();
+        assert_eq!(writer.read(&mut tail).unwrap(), 1);
+        assert_eq!(writer.into_inner().unwrap(), b"HELLO world!");
+    }
+}
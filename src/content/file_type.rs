@@ -0,0 +1,461 @@
+//! 基于文件签名（魔数）的附件类型识别
+//!
+//! 附件扩展名由发件人控制，不可信。这里根据文件头部签名判断真实类型，
+//! 并据此给出安全的下载Content-Type
+
+use serde::{Deserialize, Serialize};
+
+/// 识别出的文件类型
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum FileKind {
+    /// Windows可执行文件（PE）
+    Pe,
+    /// Linux可执行文件（ELF）
+    Elf,
+    /// macOS可执行文件（Mach-O）
+    MachO,
+    /// OLE2复合文档（doc/xls/ppt/msg）
+    Ole2,
+    /// Word文档（OOXML）
+    Docx,
+    /// Excel表格（OOXML）
+    Xlsx,
+    /// PowerPoint演示文稿（OOXML）
+    Pptx,
+    /// PDF文档
+    Pdf,
+    /// RTF文档
+    Rtf,
+    /// ZIP压缩包
+    Zip,
+    /// RAR压缩包
+    Rar,
+    /// 7z压缩包
+    SevenZip,
+    /// GZIP压缩包
+    Gzip,
+    /// PNG图片
+    Png,
+    /// JPEG图片
+    Jpeg,
+    /// GIF图片
+    Gif,
+    /// BMP图片
+    Bmp,
+    /// WebP图片
+    Webp,
+    /// Shell脚本（带shebang）
+    ShellScript,
+    /// PowerShell脚本
+    PowerShell,
+    /// VBScript脚本
+    VbScript,
+    /// JavaScript/JScript脚本
+    JavaScript,
+    /// Windows批处理脚本
+    Batch,
+    /// HTML文档
+    Html,
+    /// 纯文本
+    Text,
+    /// 无法识别
+    Unknown,
+}
+
+impl FileKind {
+    /// 类型对应的MIME
+    pub fn mime(&self) -> &'static str {
+        match self {
+            FileKind::Pe => "application/vnd.microsoft.portable-executable",
+            FileKind::Elf => "application/x-executable",
+            FileKind::MachO => "application/x-mach-binary",
+            FileKind::Ole2 => "application/x-ole-storage",
+            FileKind::Docx => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+            FileKind::Xlsx => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+            FileKind::Pptx => "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+            FileKind::Pdf => "application/pdf",
+            FileKind::Rtf => "application/rtf",
+            FileKind::Zip => "application/zip",
+            FileKind::Rar => "application/x-rar-compressed",
+            FileKind::SevenZip => "application/x-7z-compressed",
+            FileKind::Gzip => "application/gzip",
+            FileKind::Png => "image/png",
+            FileKind::Jpeg => "image/jpeg",
+            FileKind::Gif => "image/gif",
+            FileKind::Bmp => "image/bmp",
+            FileKind::Webp => "image/webp",
+            FileKind::ShellScript => "text/x-shellscript",
+            FileKind::PowerShell => "text/x-powershell",
+            FileKind::VbScript => "text/vbscript",
+            FileKind::JavaScript => "text/javascript",
+            FileKind::Batch => "text/x-bat",
+            FileKind::Html => "text/html",
+            FileKind::Text => "text/plain",
+            FileKind::Unknown => "application/octet-stream",
+        }
+    }
+
+    /// 与该类型相符的扩展名（小写，不含点）
+    pub fn extensions(&self) -> &'static [&'static str] {
+        match self {
+            FileKind::Pe => &["exe", "dll", "scr", "sys", "cpl", "ocx", "com", "efi"],
+            FileKind::Elf => &["elf", "so", "bin", "o"],
+            FileKind::MachO => &["dylib", "bundle", "macho"],
+            FileKind::Ole2 => &["doc", "xls", "ppt", "msg", "msi", "dot", "xlt", "pps", "vsd", "pub"],
+            FileKind::Docx => &["docx", "docm", "dotx", "dotm"],
+            FileKind::Xlsx => &["xlsx", "xlsm", "xltx", "xltm", "xlam"],
+            FileKind::Pptx => &["pptx", "pptm", "ppsx", "ppsm", "potx"],
+            FileKind::Pdf => &["pdf"],
+            FileKind::Rtf => &["rtf", "doc"],
+            FileKind::Zip => &["zip", "jar", "apk", "xpi", "odt", "ods", "odp", "epub"],
+            FileKind::Rar => &["rar"],
+            FileKind::SevenZip => &["7z"],
+            FileKind::Gzip => &["gz", "tgz"],
+            FileKind::Png => &["png"],
+            FileKind::Jpeg => &["jpg", "jpeg", "jpe", "jfif"],
+            FileKind::Gif => &["gif"],
+            FileKind::Bmp => &["bmp", "dib"],
+            FileKind::Webp => &["webp"],
+            FileKind::ShellScript => &["sh", "bash", "zsh", "py", "pl", "rb"],
+            FileKind::PowerShell => &["ps1", "psm1", "psd1"],
+            FileKind::VbScript => &["vbs", "vbe", "wsf"],
+            FileKind::JavaScript => &["js", "jse", "wsf", "hta"],
+            FileKind::Batch => &["bat", "cmd"],
+            FileKind::Html => &["html", "htm", "xhtml", "hta", "shtml", "svg"],
+            FileKind::Text => &["txt", "log", "csv", "md", "ini", "cfg", "conf", "xml", "json", "eml"],
+            FileKind::Unknown => &[],
+        }
+    }
+
+    /// 是否为可执行文件或脚本
+    pub fn is_executable(&self) -> bool {
+        matches!(
+            self,
+            FileKind::Pe
+                | FileKind::Elf
+                | FileKind::MachO
+                | FileKind::ShellScript
+                | FileKind::PowerShell
+                | FileKind::VbScript
+                | FileKind::JavaScript
+                | FileKind::Batch
+        )
+    }
+
+    /// 是否为压缩包
+    pub fn is_archive(&self) -> bool {
+        matches!(self, FileKind::Zip | FileKind::Rar | FileKind::SevenZip | FileKind::Gzip)
+    }
+
+    /// 判断扩展名与识别结果是否不符
+    ///
+    /// 纯文本和无法识别的内容不做判断；无扩展名但识别出具体类型时视为不符
+    pub fn extension_mismatch(&self, extension: &str) -> bool {
+        if matches!(self, FileKind::Text | FileKind::Unknown) {
+            return false;
+        }
+        let extension = extension.trim().trim_start_matches('.').to_ascii_lowercase();
+        !self.extensions().contains(&extension.as_str())
+    }
+
+    /// 下载时使用的Content-Type
+    ///
+    /// 可执行文件、脚本和HTML一律按二进制流下发，防止浏览器直接解析或执行
+    pub fn download_content_type(&self) -> &'static str {
+        if self.is_executable() || matches!(self, FileKind::Html) {
+            "application/octet-stream"
+        } else {
+            self.mime()
+        }
+    }
+}
+
+/// 根据文件内容识别类型
+pub fn detect_file_type(data: &[u8]) -> FileKind {
+    if data.starts_with(b"MZ") && is_pe(data) {
+        return FileKind::Pe;
+    }
+    if data.starts_with(b"\x7fELF") {
+        return FileKind::Elf;
+    }
+    if is_mach_o(data) {
+        return FileKind::MachO;
+    }
+    if data.starts_with(&[0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1]) {
+        return FileKind::Ole2;
+    }
+    if data.starts_with(b"PK\x03\x04") || data.starts_with(b"PK\x05\x06") {
+        return detect_zip_container(data);
+    }
+    if data.starts_with(b"Rar!\x1a\x07") {
+        return FileKind::Rar;
+    }
+    if data.starts_with(&[0x37, 0x7A, 0xBC, 0xAF, 0x27, 0x1C]) {
+        return FileKind::SevenZip;
+    }
+    if data.starts_with(&[0x1F, 0x8B]) {
+        return FileKind::Gzip;
+    }
+    // PDF规范允许签名出现在前1024字节内
+    if contains(&data[..data.len().min(1024)], b"%PDF-") {
+        return FileKind::Pdf;
+    }
+    if data.starts_with(b"{\\rtf") {
+        return FileKind::Rtf;
+    }
+    if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        return FileKind::Png;
+    }
+    if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        return FileKind::Jpeg;
+    }
+    if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+        return FileKind::Gif;
+    }
+    if data.starts_with(b"BM") && data.len() > 14 {
+        return FileKind::Bmp;
+    }
+    if data.len() >= 12 && data.starts_with(b"RIFF") && &data[8..12] == b"WEBP" {
+        return FileKind::Webp;
+    }
+
+    detect_text(data)
+}
+
+/// PE文件：MZ头中e_lfanew指向"PE\0\0"签名
+fn is_pe(data: &[u8]) -> bool {
+    if data.len() < 0x40 {
+        return false;
+    }
+    let offset = u32::from_le_bytes([data[0x3C], data[0x3D], data[0x3E], data[0x3F]]) as usize;
+    // 部分畸形样本e_lfanew越界，仍按DOS/PE可执行文件处理
+    match data.get(offset..offset + 4) {
+        Some(signature) => signature == b"PE\0\0",
+        None => true,
+    }
+}
+
+/// Mach-O文件（含通用二进制）
+///
+/// 通用二进制与Java class文件的魔数同为0xCAFEBABE，按其后的大端u32区分：
+/// 通用二进制是架构数，通常只有几个；Java class是版本号，主版本号不小于45
+fn is_mach_o(data: &[u8]) -> bool {
+    const MAGICS: &[[u8; 4]] = &[
+        [0xFE, 0xED, 0xFA, 0xCE],
+        [0xFE, 0xED, 0xFA, 0xCF],
+        [0xCE, 0xFA, 0xED, 0xFE],
+        [0xCF, 0xFA, 0xED, 0xFE],
+    ];
+    /// 通用二进制的最大架构数
+    const MAX_FAT_ARCHS: u32 = 20;
+    if MAGICS.iter().any(|magic| data.starts_with(magic)) {
+        return true;
+    }
+    if !(data.starts_with(&[0xCA, 0xFE, 0xBA, 0xBE]) || data.starts_with(&[0xCA, 0xFE, 0xBA, 0xBF])) {
+        return false;
+    }
+    data.get(4..8)
+        .map(|count| u32::from_be_bytes([count[0], count[1], count[2], count[3]]))
+        .is_some_and(|count| (1..=MAX_FAT_ARCHS).contains(&count))
+}
+
+/// 区分OOXML文档与普通ZIP压缩包
+fn detect_zip_container(data: &[u8]) -> FileKind {
+    if contains(data, b"[Content_Types].xml") {
+        if contains(data, b"word/") {
+            return FileKind::Docx;
+        }
+        if contains(data, b"xl/") {
+            return FileKind::Xlsx;
+        }
+        if contains(data, b"ppt/") {
+            return FileKind::Pptx;
+        }
+    }
+    FileKind::Zip
+}
+
+/// 文本类内容识别：脚本、HTML或纯文本
+fn detect_text(data: &[u8]) -> FileKind {
+    let sample = &data[..data.len().min(4096)];
+    if sample.is_empty() || !looks_like_text(sample) {
+        return FileKind::Unknown;
+    }
+
+    let text = String::from_utf8_lossy(sample).to_ascii_lowercase();
+    let trimmed = text.trim_start_matches('\u{feff}').trim_start();
+
+    if trimmed.starts_with("#!") {
+        return FileKind::ShellScript;
+    }
+    if trimmed.starts_with("<!doctype html")
+        || trimmed.starts_with("<html")
+        || trimmed.starts_with("<hta:")
+        || text.contains("<script")
+        || trimmed.starts_with("<svg")
+    {
+        return FileKind::Html;
+    }
+    if trimmed.starts_with("@echo off") || text.contains("\n@echo off") {
+        return FileKind::Batch;
+    }
+    if ["invoke-expression", "iex(", "-encodedcommand", "new-object system.net", "set-executionpolicy"]
+        .iter()
+        .any(|marker| text.contains(marker))
+    {
+        return FileKind::PowerShell;
+    }
+    if ["wscript.createobject", "createobject(\"", "on error resume next", "end sub"]
+        .iter()
+        .any(|marker| text.contains(marker))
+    {
+        return FileKind::VbScript;
+    }
+    if ["new activexobject", "wscript.shell", "eval(", "function(", "document.write("]
+        .iter()
+        .any(|marker| text.contains(marker))
+    {
+        return FileKind::JavaScript;
+    }
+
+    FileKind::Text
+}
+
+/// 粗略判断内容是否为文本：不含NUL且控制字符占比很低
+fn looks_like_text(sample: &[u8]) -> bool {
+    if sample.contains(&0) {
+        return false;
+    }
+    let control = sample
+        .iter()
+        .filter(|&&b| b < 0x20 && !matches!(b, b'\n' | b'\r' | b'\t' | 0x0C))
+        .count();
+    control * 100 < sample.len()
+}
+
+/// 字节串查找
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    !needle.is_empty() && haystack.windows(needle.len()).any(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 以`head`开头、补零到`len`字节的内容
+    fn padded(head: &[u8], len: usize) -> Vec<u8> {
+        let mut data = head.to_vec();
+        data.resize(len.max(head.len()), 0);
+        data
+    }
+
+    /// e_lfanew为`e_lfanew`、头部之后紧跟`signature`的MZ文件
+    fn mz(e_lfanew: u8, signature: &[u8]) -> Vec<u8> {
+        let mut data = padded(b"MZ", 0x40);
+        data[0x3C] = e_lfanew;
+        data.extend_from_slice(signature);
+        data
+    }
+
+    #[test]
+    fn detects_binary_signatures() {
+        let cases: Vec<(Vec<u8>, FileKind)> = vec![
+            (mz(0x40, b"PE\0\0"), FileKind::Pe),
+            // e_lfanew越界的畸形样本仍按PE处理，签名不符则不是PE
+            (mz(0xFF, b""), FileKind::Pe),
+            (mz(0x40, b"NE\0\0"), FileKind::Unknown),
+            (padded(b"\x7fELF\x02\x01\x01", 64), FileKind::Elf),
+            (padded(&[0xFE, 0xED, 0xFA, 0xCE], 32), FileKind::MachO),
+            (padded(&[0xCF, 0xFA, 0xED, 0xFE], 32), FileKind::MachO),
+            // 通用二进制：2个架构
+            (padded(&[0xCA, 0xFE, 0xBA, 0xBE, 0, 0, 0, 2], 64), FileKind::MachO),
+            (padded(&[0xCA, 0xFE, 0xBA, 0xBF, 0, 0, 0, 1], 64), FileKind::MachO),
+            // Java class：次版本号0，主版本号52
+            (padded(&[0xCA, 0xFE, 0xBA, 0xBE, 0, 0, 0, 0x34], 64), FileKind::Unknown),
+            (padded(&[0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1], 512), FileKind::Ole2),
+            (b"PK\x03\x04[Content_Types].xml word/document.xml".to_vec(), FileKind::Docx),
+            (b"PK\x03\x04[Content_Types].xml xl/workbook.xml".to_vec(), FileKind::Xlsx),
+            (b"PK\x03\x04[Content_Types].xml ppt/presentation.xml".to_vec(), FileKind::Pptx),
+            (b"PK\x03\x04word/document.xml".to_vec(), FileKind::Zip),
+            (padded(b"PK\x05\x06", 22), FileKind::Zip),
+            (padded(b"Rar!\x1a\x07\x01\x00", 16), FileKind::Rar),
+            (padded(&[0x37, 0x7A, 0xBC, 0xAF, 0x27, 0x1C], 32), FileKind::SevenZip),
+            (padded(&[0x1F, 0x8B, 0x08], 20), FileKind::Gzip),
+            (b"%PDF-1.7\n".to_vec(), FileKind::Pdf),
+            // 签名前有其他内容
+            (padded(b"junk", 1000).into_iter().chain(b"%PDF-1.4".iter().copied()).collect(), FileKind::Pdf),
+            (padded(b"junk", 1030).into_iter().chain(b"%PDF-1.4".iter().copied()).collect(), FileKind::Unknown),
+            (b"{\\rtf1\\ansi".to_vec(), FileKind::Rtf),
+            (padded(b"\x89PNG\r\n\x1a\n", 32), FileKind::Png),
+            (padded(&[0xFF, 0xD8, 0xFF, 0xE0], 32), FileKind::Jpeg),
+            (padded(b"GIF87a", 16), FileKind::Gif),
+            (padded(b"GIF89a", 16), FileKind::Gif),
+            (padded(b"BM", 54), FileKind::Bmp),
+            (b"BM".to_vec(), FileKind::Text),
+            (padded(b"RIFF\0\0\0\0WEBPVP8 ", 32), FileKind::Webp),
+            (padded(b"RIFF\0\0\0\0WAVEfmt ", 32), FileKind::Unknown),
+        ];
+        for (data, expected) in cases {
+            assert_eq!(detect_file_type(&data), expected, "{:02x?}", &data[..data.len().min(16)]);
+        }
+    }
+
+    #[test]
+    fn detects_text_kinds() {
+        let cases = [
+            ("#!/bin/sh\nrm -rf /tmp/x\n", FileKind::ShellScript),
+            ("\u{feff}  #!/usr/bin/env python3\n", FileKind::ShellScript),
+            ("<!DOCTYPE html><html></html>", FileKind::Html),
+            ("<HTA:APPLICATION ID=\"x\">", FileKind::Html),
+            ("hello <script>alert(1)</script>", FileKind::Html),
+            ("<svg xmlns=\"http://www.w3.org/2000/svg\"/>", FileKind::Html),
+            ("@ECHO OFF\r\ndel /q *", FileKind::Batch),
+            ("rem x\n@echo off\n", FileKind::Batch),
+            ("powershell -EncodedCommand SQBFAFgA", FileKind::PowerShell),
+            ("IEX(New-Object System.Net.WebClient)", FileKind::PowerShell),
+            ("On Error Resume Next\nSet o = WScript.CreateObject(\"x\")", FileKind::VbScript),
+            ("var s = new ActiveXObject(\"WScript.Shell\");", FileKind::JavaScript),
+            ("您好，附件是本月的发票。\n", FileKind::Text),
+            ("", FileKind::Unknown),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(detect_file_type(text.as_bytes()), expected, "{}", text);
+        }
+        // 控制字符过多视为二进制
+        assert_eq!(detect_file_type(&[0x01; 64]), FileKind::Unknown);
+    }
+
+    #[test]
+    fn extension_mismatch_cases() {
+        let cases = [
+            (FileKind::Pdf, "pdf", false),
+            (FileKind::Pdf, ".PDF", false),
+            (FileKind::Pdf, " pdf ", false),
+            (FileKind::Pdf, "docx", true),
+            (FileKind::Pe, "", true),
+            (FileKind::Pe, "scr", false),
+            (FileKind::Pe, "pdf", true),
+            (FileKind::Rtf, "doc", false),
+            (FileKind::Ole2, "doc", false),
+            (FileKind::Html, "hta", false),
+            (FileKind::Zip, "docx", true),
+            (FileKind::MachO, "dylib", false),
+            // 纯文本和无法识别的内容不判断
+            (FileKind::Text, "exe", false),
+            (FileKind::Unknown, "", false),
+        ];
+        for (kind, extension, expected) in cases {
+            assert_eq!(kind.extension_mismatch(extension), expected, "{:?} {:?}", kind, extension);
+        }
+    }
+
+    #[test]
+    fn executables_download_as_binary() {
+        assert_eq!(FileKind::MachO.download_content_type(), "application/octet-stream");
+        assert_eq!(FileKind::Html.download_content_type(), "application/octet-stream");
+        assert_eq!(FileKind::Pdf.download_content_type(), "application/pdf");
+        assert!(FileKind::Batch.is_executable() && !FileKind::Zip.is_executable());
+        assert!(FileKind::Gzip.is_archive() && !FileKind::Docx.is_archive());
+    }
+}
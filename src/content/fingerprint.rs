@@ -0,0 +1,269 @@
+//! 邮件相似度指纹与聚类
+//!
+//! 指纹由三部分组成：归一化主题与正文的SimHash（字符3-gram）、正文中链接的主机名集合、
+//! 附件哈希集合。附件哈希相同的邮件直接视为同一批次，否则按文本与链接的加权相似度判断。
+//! 聚类采用单链接方式：相似度达到阈值的两封邮件归入同一批次，关系可传递
+
+use std::collections::BTreeSet;
+
+use super::url::url_host;
+
+/// SimHash的分片长度（字符）
+const SHINGLE: usize = 3;
+/// 同时存在链接时文本相似度的权重
+const TEXT_WEIGHT: f64 = 0.7;
+
+/// 邮件相似度指纹
+#[derive(Debug, Clone, Default)]
+pub struct MailFingerprint {
+    /// 归一化主题与正文的SimHash，文本为空时为None
+    pub simhash: Option<u64>,
+    /// 正文链接的主机名（小写）
+    pub hosts: BTreeSet<String>,
+    /// 附件哈希（小写）
+    pub attachments: BTreeSet<String>,
+}
+
+impl MailFingerprint {
+    /// 根据主题、正文（纯文本优先，否则使用HTML）和附件哈希计算指纹
+    pub fn new<'a>(
+        subject: &str,
+        text_body: &str,
+        html_body: &str,
+        attachments: impl IntoIterator<Item = &'a str>,
+    ) -> Self {
+        let body = if text_body.trim().is_empty() {
+            strip_tags(html_body)
+        } else {
+            text_body.to_string()
+        };
+        let text = normalize_text(&format!("{} {}", subject, body));
+
+        let mut hosts: BTreeSet<String> = BTreeSet::new();
+        for source in [text_body, html_body] {
+            hosts.extend(extract_urls(source).into_iter().filter_map(|url| url_host(&url)));
+        }
+
+        Self {
+            simhash: (!text.is_empty()).then(|| simhash(&text)),
+            hosts,
+            attachments: attachments
+                .into_iter()
+                .map(|hash| hash.trim().to_lowercase())
+                .filter(|hash| !hash.is_empty())
+                .collect(),
+        }
+    }
+
+    /// 两封邮件的相似度，0到1
+    pub fn similarity(&self, other: &MailFingerprint) -> f64 {
+        if !self.attachments.is_disjoint(&other.attachments) {
+            return 1.0;
+        }
+        let text = match (self.simhash, other.simhash) {
+            (Some(a), Some(b)) => 1.0 - f64::from((a ^ b).count_ones()) / 64.0,
+            _ => 0.0,
+        };
+        if self.hosts.is_empty() && other.hosts.is_empty() {
+            return text;
+        }
+        let shared = self.hosts.intersection(&other.hosts).count() as f64;
+        let total = self.hosts.union(&other.hosts).count() as f64;
+        TEXT_WEIGHT * text + (1.0 - TEXT_WEIGHT) * (shared / total)
+    }
+}
+
+/// 按相似度阈值聚类，返回每个批次的成员下标（按下标排序），批次按首个成员排序
+pub fn cluster(fingerprints: &[MailFingerprint], threshold: f64) -> Vec<Vec<usize>> {
+    let mut parent: Vec<usize> = (0..fingerprints.len()).collect();
+    fn find(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+
+    for i in 0..fingerprints.len() {
+        for j in i + 1..fingerprints.len() {
+            if fingerprints[i].similarity(&fingerprints[j]) >= threshold {
+                let (a, b) = (find(&mut parent, i), find(&mut parent, j));
+                if a != b {
+                    parent[a.max(b)] = a.min(b);
+                }
+            }
+        }
+    }
+
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    let mut slots: Vec<Option<usize>> = vec![None; fingerprints.len()];
+    for i in 0..fingerprints.len() {
+        let root = find(&mut parent, i);
+        match slots[root] {
+            Some(slot) => groups[slot].push(i),
+            None => {
+                slots[root] = Some(groups.len());
+                groups.push(vec![i]);
+            }
+        }
+    }
+    groups
+}
+
+/// 归一化文本：去掉链接，数字统一为0，转为小写并合并空白，减少模板中个性化字段的影响
+fn normalize_text(text: &str) -> String {
+    let mut text = text.to_string();
+    for url in extract_urls(&text) {
+        text = text.replace(&url, " ");
+    }
+    text.chars()
+        .map(|c| if c.is_ascii_digit() { '0' } else { c })
+        .collect::<String>()
+        .to_lowercase()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// 字符3-gram的SimHash
+fn simhash(text: &str) -> u64 {
+    let chars: Vec<char> = text.chars().collect();
+    let mut weights = [0i64; 64];
+    let mut add = |gram: &[char]| {
+        let hash = feature_hash(&gram.iter().collect::<String>());
+        for (bit, weight) in weights.iter_mut().enumerate() {
+            *weight += if (hash >> bit) & 1 == 1 { 1 } else { -1 };
+        }
+    };
+    if chars.len() < SHINGLE {
+        add(&chars);
+    } else {
+        chars.windows(SHINGLE).for_each(&mut add);
+    }
+    weights
+        .iter()
+        .enumerate()
+        .filter(|(_, weight)| **weight > 0)
+        .fold(0, |hash, (bit, _)| hash | (1 << bit))
+}
+
+/// FNV-1a哈希，再用SplitMix64的混合步骤打散各位
+fn feature_hash(feature: &str) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in feature.bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    hash ^= hash >> 30;
+    hash = hash.wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    hash ^= hash >> 27;
+    hash = hash.wrapping_mul(0x94d0_49bb_1331_11eb);
+    hash ^ (hash >> 31)
+}
+
+/// 提取http与https链接，遇到空白、引号或尖括号结束
+fn extract_urls(text: &str) -> Vec<String> {
+    let lower = text.to_ascii_lowercase();
+    let mut urls = Vec::new();
+    let mut offset = 0;
+    while let Some(found) = lower[offset..].find("http") {
+        let start = offset + found;
+        let rest = &lower[start..];
+        if rest.starts_with("http://") || rest.starts_with("https://") {
+            let len = text[start..]
+                .find(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | '<' | '>'))
+                .unwrap_or(text.len() - start);
+            urls.push(text[start..start + len].to_string());
+            offset = start + len;
+        } else {
+            offset = start + 4;
+        }
+    }
+    urls
+}
+
+/// 去掉HTML标签，只保留文本
+fn strip_tags(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => {
+                in_tag = false;
+                text.push(' ');
+            }
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 只有SimHash和链接主机名的指纹
+    fn print(simhash: u64, hosts: &[&str]) -> MailFingerprint {
+        MailFingerprint {
+            simhash: Some(simhash),
+            hosts: hosts.iter().map(|host| host.to_string()).collect(),
+            attachments: BTreeSet::new(),
+        }
+    }
+
+    #[test]
+    fn simhash_ignores_digits_links_and_case() {
+        let a = MailFingerprint::new("账户异常", "请在24小时内点击 https://a.example.com/x?t=1 完成验证", "", []);
+        let b = MailFingerprint::new("账户异常", "请在12小时内点击 https://b.example.com/y?t=2 完成验证", "", []);
+        assert_eq!(a.simhash, b.simhash);
+        assert_eq!(normalize_text("Hello   World 2025"), "hello world 0000");
+
+        let other = MailFingerprint::new("会议纪要", "本周例会改到周四下午，请各部门提前准备材料", "", []);
+        assert!(a.similarity(&other) < 0.9);
+        // 纯文本为空时使用去掉标签的HTML
+        let html = MailFingerprint::new("账户异常", "", "<p>请在24小时内点击</p><b>完成验证</b>", []);
+        assert_eq!(html.simhash, MailFingerprint::new("账户异常", "请在24小时内点击 完成验证", "", []).simhash);
+        assert!(MailFingerprint::new("", " ", "", []).simhash.is_none());
+    }
+
+    #[test]
+    fn similarity_follows_hamming_distance() {
+        assert_eq!(print(0, &[]).similarity(&print(0, &[])), 1.0);
+        assert_eq!(print(0, &[]).similarity(&print(0b1111, &[])), 1.0 - 4.0 / 64.0);
+        assert_eq!(print(0, &[]).similarity(&print(u64::MAX, &[])), 0.0);
+        // 存在链接时按权重合并文本与主机名的相似度
+        assert_eq!(print(0, &["a.com"]).similarity(&print(0, &["b.com"])), TEXT_WEIGHT);
+        let half = print(0, &["a.com", "b.com"]).similarity(&print(0, &["a.com", "c.com"]));
+        assert!((half - (TEXT_WEIGHT + (1.0 - TEXT_WEIGHT) / 3.0)).abs() < 1e-9);
+        // 没有文本时文本相似度为0
+        let empty = MailFingerprint::default();
+        assert_eq!(empty.similarity(&print(0, &[])), 0.0);
+    }
+
+    #[test]
+    fn shared_attachment_is_identical() {
+        let a = MailFingerprint::new("发票", "请查收", "", [" ABCDEF ", ""]);
+        let b = MailFingerprint::new("会议纪要", "本周例会改到周四", "", ["abcdef"]);
+        assert_eq!(a.attachments, BTreeSet::from(["abcdef".to_string()]));
+        assert_eq!(a.similarity(&b), 1.0);
+    }
+
+    #[test]
+    fn cluster_links_transitively() {
+        // 0与1、1与2各相差8位，0与2相差16位
+        let prints = [
+            print(0, &[]),
+            print(0xff, &[]),
+            print(0xffff, &[]),
+            print(u64::MAX, &[]),
+            print(0xff, &[]),
+        ];
+        let threshold = 1.0 - 8.0 / 64.0;
+        assert!(prints[0].similarity(&prints[2]) < threshold);
+        assert_eq!(cluster(&prints, threshold), vec![vec![0, 1, 2, 4], vec![3]]);
+        assert_eq!(cluster(&prints, 1.0), vec![vec![0], vec![1, 4], vec![2], vec![3]]);
+        assert!(cluster(&[], 0.5).is_empty());
+    }
+}
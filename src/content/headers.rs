@@ -0,0 +1,258 @@
+//! 邮件头解析
+//!
+//! 只解析会话重建所需的头字段：Message-ID、In-Reply-To、References、Subject、From、To、Cc和Date。
+//! 支持折叠行与RFC 2047编码字（B与Q编码，按声明的字符集解码，包括GBK等中文字符集）
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// 主题中表示回复或转发的前缀（小写）
+const REPLY_PREFIXES: [&str; 9] = ["re", "fw", "fwd", "aw", "sv", "回复", "答复", "转发", "回覆"];
+
+/// 邮箱地址
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Mailbox {
+    /// 显示名称
+    pub name: String,
+    /// 邮箱地址（小写）
+    pub address: String,
+}
+
+/// 会话重建使用的邮件头
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MessageHeaders {
+    /// Message-ID，不含尖括号
+    pub message_id: Option<String>,
+    /// In-Reply-To中的Message-ID
+    pub in_reply_to: Vec<String>,
+    /// References中的Message-ID，按从早到晚的顺序
+    pub references: Vec<String>,
+    /// 解码后的主题
+    pub subject: String,
+    /// 发件人
+    pub from: Option<Mailbox>,
+    /// 收件人
+    pub to: Vec<Mailbox>,
+    /// 抄送
+    pub cc: Vec<Mailbox>,
+    /// 发信时间
+    pub date: Option<DateTime<Utc>>,
+}
+
+/// 解析原始邮件的头部，正文不会被读取
+pub fn parse_headers(raw: &[u8]) -> MessageHeaders {
+    let text = String::from_utf8_lossy(header_block(raw));
+    let mut headers = MessageHeaders::default();
+
+    for (name, value) in unfold(&text) {
+        match name.to_ascii_lowercase().as_str() {
+            "message-id" => headers.message_id = message_ids(&value).into_iter().next(),
+            "in-reply-to" => headers.in_reply_to = message_ids(&value),
+            "references" => headers.references = message_ids(&value),
+            "subject" => headers.subject = decode_words(&value).trim().to_string(),
+            "from" => headers.from = mailboxes(&value).into_iter().next(),
+            "to" => headers.to.extend(mailboxes(&value)),
+            "cc" => headers.cc.extend(mailboxes(&value)),
+            "date" => headers.date = parse_date(&value),
+            _ => {}
+        }
+    }
+
+    headers
+}
+
+/// 归一化主题：去掉回复与转发前缀，合并空白并转为小写，用于判断是否属于同一会话
+pub fn normalize_subject(subject: &str) -> String {
+    let mut rest = subject.trim();
+    while let Some(stripped) = strip_reply_prefix(rest) {
+        rest = stripped.trim_start();
+    }
+    rest.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+/// 去掉一个回复或转发前缀，如`Re:`、`RE[2]:`、`Fwd：`、`回复：`
+fn strip_reply_prefix(subject: &str) -> Option<&str> {
+    for prefix in REPLY_PREFIXES {
+        let Some(head) = subject.get(..prefix.len()) else { continue };
+        if head.to_lowercase() != prefix {
+            continue;
+        }
+        let mut rest = subject[prefix.len()..].trim_start();
+        // 可选的回复次数，如[2]或(2)
+        for (open, close) in [('[', ']'), ('(', ')')] {
+            if let Some(inner) = rest.strip_prefix(open)
+                && let Some(end) = inner.find(close)
+                && inner[..end].chars().all(|c| c.is_ascii_digit())
+            {
+                rest = inner[end + close.len_utf8()..].trim_start();
+            }
+        }
+        if let Some(rest) = rest.strip_prefix(':').or_else(|| rest.strip_prefix('：')) {
+            return Some(rest);
+        }
+    }
+    None
+}
+
+/// 头部与正文以第一个空行分隔
+fn header_block(raw: &[u8]) -> &[u8] {
+    let end = raw
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .or_else(|| raw.windows(2).position(|window| window == b"\n\n"))
+        .unwrap_or(raw.len());
+    &raw[..end]
+}
+
+/// 展开折叠行，返回（字段名, 字段值）
+fn unfold(text: &str) -> Vec<(String, String)> {
+    let mut fields: Vec<(String, String)> = Vec::new();
+    for line in text.lines() {
+        if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = fields.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+        } else if let Some((name, value)) = line.split_once(':') {
+            fields.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+    fields
+}
+
+/// 提取尖括号中的Message-ID，没有尖括号时按空白分隔
+fn message_ids(value: &str) -> Vec<String> {
+    let mut ids = Vec::new();
+    let mut rest = value;
+    while let Some(start) = rest.find('<') {
+        let Some(end) = rest[start..].find('>') else { break };
+        let id = rest[start + 1..start + end].trim();
+        if !id.is_empty() {
+            ids.push(id.to_string());
+        }
+        rest = &rest[start + end + 1..];
+    }
+    if ids.is_empty() {
+        ids = value.split_whitespace().map(str::to_string).collect();
+    }
+    ids
+}
+
+/// 解析地址列表，引号内的逗号不作为分隔符
+fn mailboxes(value: &str) -> Vec<Mailbox> {
+    let mut items = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in value.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                current.push(c);
+            }
+            ',' | ';' if !quoted => items.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    items.push(current);
+
+    items
+        .iter()
+        .filter_map(|item| {
+            let item = item.trim();
+            let (name, address) = match (item.rfind('<'), item.rfind('>')) {
+                (Some(start), Some(end)) if start < end => (&item[..start], &item[start + 1..end]),
+                _ => ("", item),
+            };
+            let address = address.trim().to_lowercase();
+            address.contains('@').then(|| Mailbox {
+                name: decode_words(name.trim()).trim().trim_matches('"').trim().to_string(),
+                address,
+            })
+        })
+        .collect()
+}
+
+/// 解析Date头，忽略末尾的时区注释如`(CST)`
+fn parse_date(value: &str) -> Option<DateTime<Utc>> {
+    let value = match value.find('(') {
+        Some(index) => &value[..index],
+        None => value,
+    };
+    DateTime::parse_from_rfc2822(value.trim())
+        .ok()
+        .map(|date| date.with_timezone(&Utc))
+}
+
+/// 解码RFC 2047编码字，相邻编码字之间的空白被忽略
+fn decode_words(value: &str) -> String {
+    let mut output = String::new();
+    let mut rest = value;
+    let mut after_word = false;
+
+    while let Some(start) = rest.find("=?") {
+        let Some((decoded, len)) = decode_word(&rest[start..]) else {
+            output.push_str(&rest[..start + 2]);
+            rest = &rest[start + 2..];
+            after_word = false;
+            continue;
+        };
+        let between = &rest[..start];
+        if !(after_word && between.trim().is_empty()) {
+            output.push_str(between);
+        }
+        output.push_str(&decoded);
+        rest = &rest[start + len..];
+        after_word = true;
+    }
+    output.push_str(rest);
+    output
+}
+
+/// 解码一个以`=?`开头的编码字，返回（解码结果, 编码字长度）
+fn decode_word(word: &str) -> Option<(String, usize)> {
+    let mut parts = word[2..].splitn(3, '?');
+    let charset = parts.next()?;
+    let encoding = parts.next()?;
+    let rest = parts.next()?;
+    let end = rest.find("?=")?;
+    let text = &rest[..end];
+    let len = 2 + charset.len() + 1 + encoding.len() + 1 + end + 2;
+
+    let bytes = match encoding.to_ascii_uppercase().as_str() {
+        "B" => STANDARD.decode(text.trim_end_matches('=')).or_else(|_| STANDARD.decode(text)).ok()?,
+        "Q" => decode_q(text),
+        _ => return None,
+    };
+    // 字符集可能带有语言后缀，如utf-8*zh
+    let label = charset.split('*').next().unwrap_or(charset);
+    let encoding = encoding_rs::Encoding::for_label(label.as_bytes()).unwrap_or(encoding_rs::UTF_8);
+    let (decoded, _, _) = encoding.decode(&bytes);
+    Some((decoded.into_owned(), len))
+}
+
+/// Q编码：下划线表示空格，`=XX`表示一个字节
+fn decode_q(text: &str) -> Vec<u8> {
+    let bytes = text.as_bytes();
+    let mut output = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'_' => output.push(b' '),
+            b'=' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+                match hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                    Some(byte) => {
+                        output.push(byte);
+                        i += 2;
+                    }
+                    None => output.push(b'='),
+                }
+            }
+            byte => output.push(byte),
+        }
+        i += 1;
+    }
+    output
+}
@@ -0,0 +1,389 @@
+//! 邮件HTML正文安全预览
+//!
+//! 处理流程：
+//! 1. 使用ammonia清洗HTML，移除脚本、表单、事件处理器以及所有远程资源引用
+//! 2. 在清洗结果上做二次处理：链接去除可点击的href，改为展示去武器化(defang)后的地址
+//! 3. 在文本节点中查找命中的情报值，并使用`<mark>`包裹，方便分析人员定位
+
+use std::borrow::Cow;
+
+/// 需要在正文中高亮的情报值
+#[derive(Debug, Clone)]
+pub struct IocTerm {
+    /// 情报ID
+    pub intelligence_id: String,
+    /// 情报属性，如domain、url等
+    pub attribute: String,
+    /// 情报值
+    pub value: String,
+}
+
+/// 预览渲染结果
+#[derive(Debug, Clone)]
+pub struct RenderedPreview {
+    /// 清洗并高亮后的HTML
+    pub html: String,
+    /// 每个情报值的命中次数，与传入的情报值列表一一对应
+    pub hit_counts: Vec<u32>,
+}
+
+/// 会引用外部资源的属性，一律移除
+const RESOURCE_ATTRIBUTES: &[&str] = &["src", "srcset", "background", "poster", "longdesc", "cite"];
+
+/// 需要连同内容一起移除的标签
+const CLEAN_CONTENT_TAGS: &[&str] = &[
+    "script", "style", "noscript", "template", "iframe", "frame", "frameset",
+    "object", "embed", "applet", "form",
+];
+
+/// 生成邮件正文的安全预览
+///
+/// HTML正文为空时使用纯文本正文，纯文本会被转义后放入`<pre>`中
+pub fn render_preview(html_body: &str, text_body: &str, terms: &[IocTerm]) -> RenderedPreview {
+    let source: Cow<str> = if html_body.trim().is_empty() {
+        Cow::Owned(format!("<pre>{}</pre>", escape_html(text_body)))
+    } else {
+        Cow::Borrowed(html_body)
+    };
+
+    let sanitized = sanitize(&source);
+    let mut hit_counts = vec![0u32; terms.len()];
+    let html = decorate(&sanitized, terms, &mut hit_counts);
+
+    RenderedPreview { html, hit_counts }
+}
+
+/// 对URL进行去武器化处理，如 `https://evil.com/a` -> `hxxps://evil[.]com/a`
+pub fn defang_url(url: &str) -> String {
+    let url = url.trim();
+
+    if let Some(pos) = url.find("://") {
+        let scheme = &url[..pos];
+        let rest = &url[pos + 3..];
+        let host_end = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+        let scheme = match scheme.to_ascii_lowercase().as_str() {
+            "http" => "hxxp".to_string(),
+            "https" => "hxxps".to_string(),
+            "ftp" => "fxp".to_string(),
+            other => other.to_string(),
+        };
+        return format!("{}://{}{}", scheme, rest[..host_end].replace('.', "[.]"), &rest[host_end..]);
+    }
+
+    if let Some(address) = url.strip_prefix("mailto:") {
+        return format!("mailto:{}", address.replace('@', "[@]").replace('.', "[.]"));
+    }
+
+    url.replace('.', "[.]")
+}
+
+/// 使用ammonia清洗HTML
+fn sanitize(html: &str) -> String {
+    ammonia::Builder::default()
+        .add_clean_content_tags(CLEAN_CONTENT_TAGS)
+        .url_relative(ammonia::UrlRelative::Deny)
+        .link_rel(None)
+        .attribute_filter(|_element, attribute, value| {
+            if RESOURCE_ATTRIBUTES.contains(&attribute) {
+                None
+            } else {
+                Some(value.into())
+            }
+        })
+        .clean(html)
+        .to_string()
+}
+
+/// 对清洗后的HTML做链接去武器化和情报高亮
+///
+/// ammonia输出的HTML是规范化的：属性值统一使用双引号且内部的引号已被转义，
+/// 注释已被移除，因此可以安全地按标签/文本切分
+fn decorate(sanitized: &str, terms: &[IocTerm], hit_counts: &mut [u32]) -> String {
+    let mut out = String::with_capacity(sanitized.len() + sanitized.len() / 4);
+    let mut pending_link: Option<String> = None;
+    let mut rest = sanitized;
+
+    while !rest.is_empty() {
+        if rest.starts_with('<') {
+            let end = tag_end(rest);
+            let tag = &rest[..end];
+
+            if is_open_tag(tag, "a") {
+                pending_link = attribute_value(tag, "href").map(|href| defang_url(&unescape_html(href)));
+                match &pending_link {
+                    Some(defanged) => out.push_str(&format!(
+                        "<a class=\"defanged-link\" title=\"{}\">",
+                        escape_html(defanged)
+                    )),
+                    None => out.push_str("<a>"),
+                }
+            } else if tag.eq_ignore_ascii_case("</a>") {
+                out.push_str(tag);
+                if let Some(defanged) = pending_link.take() {
+                    out.push_str(&format!(
+                        " <span class=\"defanged-url\">[{}]</span>",
+                        escape_html(&defanged)
+                    ));
+                }
+            } else {
+                out.push_str(tag);
+            }
+            rest = &rest[end..];
+        } else {
+            let end = rest.find('<').unwrap_or(rest.len());
+            highlight_text(&rest[..end], terms, hit_counts, &mut out);
+            rest = &rest[end..];
+        }
+    }
+
+    out
+}
+
+/// 在文本节点中查找情报值并包裹高亮标记
+///
+/// 匹配不区分ASCII大小写；多个情报值重叠时优先保留起始位置更靠前、长度更长的匹配
+fn highlight_text(escaped: &str, terms: &[IocTerm], hit_counts: &mut [u32], out: &mut String) {
+    let text = unescape_html(escaped);
+    // to_ascii_lowercase不改变字节长度，因此匹配位置可以直接用于原文本
+    let lower = text.to_ascii_lowercase();
+
+    let mut matches: Vec<(usize, usize, usize)> = Vec::new();
+    for (index, term) in terms.iter().enumerate() {
+        let needle = term.value.trim().to_ascii_lowercase();
+        if needle.is_empty() {
+            continue;
+        }
+        for (start, _) in lower.match_indices(&needle) {
+            matches.push((start, start + needle.len(), index));
+        }
+    }
+
+    if matches.is_empty() {
+        out.push_str(escaped);
+        return;
+    }
+
+    matches.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)));
+
+    let mut cursor = 0;
+    for (start, end, index) in matches {
+        if start < cursor {
+            continue;
+        }
+        let term = &terms[index];
+        out.push_str(&escape_html(&text[cursor..start]));
+        out.push_str(&format!(
+            "<mark class=\"ioc-hit\" data-intelligence-id=\"{}\" data-attribute=\"{}\">{}</mark>",
+            escape_html(&term.intelligence_id),
+            escape_html(&term.attribute),
+            escape_html(&text[start..end])
+        ));
+        hit_counts[index] += 1;
+        cursor = end;
+    }
+    out.push_str(&escape_html(&text[cursor..]));
+}
+
+/// 查找标签结束位置（`>`之后的索引），忽略引号内的`>`
+fn tag_end(input: &str) -> usize {
+    let mut in_quote = false;
+    for (index, ch) in input.char_indices() {
+        match ch {
+            '"' => in_quote = !in_quote,
+            '>' if !in_quote => return index + 1,
+            _ => {}
+        }
+    }
+    input.len()
+}
+
+/// 判断是否为指定名称的开始标签
+fn is_open_tag(tag: &str, name: &str) -> bool {
+    let Some(rest) = tag.strip_prefix('<') else {
+        return false;
+    };
+    rest.len() > name.len()
+        && rest[..name.len()].eq_ignore_ascii_case(name)
+        && matches!(rest.as_bytes()[name.len()], b' ' | b'>' | b'/')
+}
+
+/// 读取标签中的属性值（仍为转义状态）
+fn attribute_value<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let marker = format!(" {}=\"", name);
+    let start = tag.find(&marker)? + marker.len();
+    let end = tag[start..].find('"')? + start;
+    Some(&tag[start..end])
+}
+
+/// HTML转义
+fn escape_html(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    for ch in input.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+/// 还原ammonia序列化时产生的实体
+fn unescape_html(input: &str) -> String {
+    const ENTITIES: &[(&str, char)] = &[
+        ("&amp;", '&'),
+        ("&lt;", '<'),
+        ("&gt;", '>'),
+        ("&quot;", '"'),
+        ("&nbsp;", '\u{a0}'),
+    ];
+
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(pos) = rest.find('&') {
+        out.push_str(&rest[..pos]);
+        rest = &rest[pos..];
+        match ENTITIES.iter().find(|(entity, _)| rest.starts_with(entity)) {
+            Some((entity, ch)) => {
+                out.push(*ch);
+                rest = &rest[entity.len()..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn term(value: &str) -> IocTerm {
+        IocTerm {
+            intelligence_id: "id-1".to_string(),
+            attribute: "Domain".to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    /// 所有标签的文本，用于检查`<mark>`只出现在文本节点中
+    fn tags(html: &str) -> Vec<&str> {
+        let mut tags = Vec::new();
+        let mut rest = html;
+        while let Some(start) = rest.find('<') {
+            let end = tag_end(&rest[start..]) + start;
+            tags.push(&rest[start..end]);
+            rest = &rest[end..];
+        }
+        tags
+    }
+
+    #[test]
+    fn strips_scripts_styles_and_event_handlers() {
+        let html = "<p onclick=\"steal()\" onmouseover=\"x()\">hi</p><script>alert(1)</script>\
+                    <style>p{color:red}</style><img src=\"https://tracker.example/p.gif\" onerror=\"x()\">\
+                    <form action=\"https://evil.example\"><input name=\"password\"></form>";
+        let preview = render_preview(html, "", &[]);
+
+        assert!(!preview.html.contains("onclick"));
+        assert!(!preview.html.contains("onmouseover"));
+        assert!(!preview.html.contains("onerror"));
+        assert!(!preview.html.contains("alert"));
+        assert!(!preview.html.contains("color:red"));
+        assert!(!preview.html.contains("tracker.example"));
+        assert!(!preview.html.contains("evil.example"));
+        assert!(!preview.html.contains("password"));
+        assert!(preview.html.contains("<p>hi</p>"));
+    }
+
+    #[test]
+    fn defangs_links_and_removes_href() {
+        let html = "<a href=\"https://login.examp1e.com/verify?u=a&amp;t=1\">点击验证</a>";
+        let preview = render_preview(html, "", &[]);
+
+        assert!(!preview.html.contains("href"));
+        assert!(preview.html.contains("title=\"hxxps://login[.]examp1e[.]com/verify?u=a&amp;t=1\""));
+        assert!(preview.html.contains("点击验证</a> <span class=\"defanged-url\">[hxxps://login[.]examp1e[.]com/verify?u=a&amp;t=1]</span>"));
+    }
+
+    #[test]
+    fn javascript_links_are_not_clickable() {
+        let preview = render_preview("<a href=\"javascript:alert(1)\">x</a>", "", &[]);
+        assert!(!preview.html.contains("href"));
+        assert!(!preview.html.contains("javascript"));
+    }
+
+    #[test]
+    fn quoted_gt_inside_attribute_stays_in_tag() {
+        let html = "<p title=\"a > examp1e.com\">examp1e.com</p>";
+        let preview = render_preview(html, "", &[term("examp1e.com")]);
+        assert_eq!(preview.hit_counts, vec![1]);
+        assert!(preview.html.starts_with("<p title=\"a &gt; examp1e.com\"><mark"));
+
+        // 不依赖ammonia对属性中`>`的转义：引号内的`>`不会结束标签
+        let mut hit_counts = vec![0];
+        let decorated = decorate(html, &[term("examp1e.com")], &mut hit_counts);
+        assert_eq!(hit_counts, vec![1]);
+        assert!(decorated.starts_with("<p title=\"a > examp1e.com\"><mark"));
+    }
+
+    #[test]
+    fn never_marks_inside_tags_or_attributes() {
+        let html = "<a href=\"https://examp1e.com/\" title=\"examp1e.com\">visit examp1e.com</a>\
+                    <abbr title=\"EXAMP1E.COM\">x</abbr>";
+        let preview = render_preview(html, "", &[term("examp1e.com")]);
+
+        assert_eq!(preview.hit_counts, vec![1]);
+        for tag in tags(&preview.html) {
+            if !tag.starts_with("<mark") && tag != "</mark>" {
+                assert!(!tag.contains("ioc-hit"), "{}", tag);
+            }
+        }
+        assert!(preview.html.contains("visit <mark class=\"ioc-hit\" data-intelligence-id=\"id-1\" data-attribute=\"Domain\">examp1e.com</mark>"));
+    }
+
+    #[test]
+    fn matches_across_entities_and_keeps_them_escaped() {
+        let html = "<p>a&lt;b &amp; login.examp1e.com?u=1&amp;t=2</p>";
+        let preview = render_preview(html, "", &[term("login.examp1e.com?u=1&t=2"), term("<b")]);
+
+        assert_eq!(preview.hit_counts, vec![1, 1]);
+        assert!(preview.html.contains(">login.examp1e.com?u=1&amp;t=2</mark>"));
+        assert!(preview.html.contains(">&lt;b</mark>"));
+        assert!(!preview.html.contains("<b"));
+    }
+
+    #[test]
+    fn overlapping_terms_prefer_earliest_longest() {
+        let preview = render_preview("<p>login.examp1e.com</p>", "", &[term("examp1e.com"), term("login.examp1e.com")]);
+        assert_eq!(preview.hit_counts, vec![0, 1]);
+    }
+
+    #[test]
+    fn text_body_is_escaped() {
+        let preview = render_preview("", "<script>alert(1)</script> examp1e.com", &[term("EXAMP1E.com")]);
+        assert!(preview.html.starts_with("<pre>&lt;script&gt;"));
+        assert_eq!(preview.hit_counts, vec![1]);
+    }
+
+    #[test]
+    fn defang_url_forms() {
+        assert_eq!(defang_url("http://a.b/c.d"), "hxxp://a[.]b/c.d");
+        assert_eq!(defang_url("FTP://files.example"), "fxp://files[.]example");
+        assert_eq!(defang_url("mailto:a@b.c"), "mailto:a[@]b[.]c");
+        assert_eq!(defang_url("example.com"), "example[.]com");
+    }
+
+    #[test]
+    fn attribute_value_ignores_prefixed_names() {
+        let tag = "<a data-href=\"x\" href=\"https://a.example/\">";
+        assert_eq!(attribute_value(tag, "href"), Some("https://a.example/"));
+        assert_eq!(tag_end("<p title=\"a>b\">rest"), 15);
+    }
+}
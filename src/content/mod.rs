@@ -0,0 +1,27 @@
+//! 邮件内容处理模块
+//!
+//! 负责对邮件正文、附件等原始内容进行安全处理与解析，不依赖数据库
+
+pub mod html;
+pub mod file_type;
+pub mod digest;
+pub mod archive;
+pub mod sample_zip;
+pub mod export;
+pub mod headers;
+pub mod thread;
+pub mod fingerprint;
+pub mod url;
+pub mod pdf;
+
+pub use html::{render_preview, IocTerm};
+pub use file_type::{detect_file_type, FileKind};
+pub use digest::{compute_digests, DigestCheck, DigestHasher, DigestReader, FileDigests};
+pub use archive::{inspect_archive, ArchiveEntry, ArchiveLimits, ArchiveListing};
+pub use sample_zip::{build_sample_zip, SampleFile};
+pub use export::{ExportFormat, ExportItem, MailExporter};
+pub use headers::{normalize_subject, parse_headers, Mailbox, MessageHeaders};
+pub use thread::{reconstruct_thread, ThreadLink, ThreadMessage, ThreadPosition};
+pub use fingerprint::{cluster, MailFingerprint};
+pub use url::{normalize_url, url_host, url_path};
+pub use pdf::extract_pdf_images;
@@ -0,0 +1,122 @@
+//! PDF内嵌图片提取
+//!
+//! 只提取DCTDecode编码的图片流，其内容就是完整的JPEG文件，可以直接交给条码识别。
+//! 其他编码（FlateDecode等）需要按颜色空间重建像素，暂不处理
+
+/// 单个PDF最多提取的图片数
+const MAX_PDF_IMAGES: usize = 32;
+
+/// 单张图片的最大大小
+const MAX_PDF_IMAGE_SIZE: usize = 16 * 1024 * 1024;
+
+/// 提取PDF中以JPEG存储的图片
+pub fn extract_pdf_images(data: &[u8]) -> Vec<Vec<u8>> {
+    let mut images = Vec::new();
+    let mut offset = 0;
+
+    while images.len() < MAX_PDF_IMAGES {
+        let Some(start) = find(&data[offset..], b"stream").map(|index| offset + index) else {
+            break;
+        };
+        offset = start + b"stream".len();
+        // 跳过endstream
+        if data[..start].ends_with(b"end") {
+            continue;
+        }
+
+        // 流之前最近的对象字典
+        let dict_start = rfind(&data[..start], b"obj").unwrap_or(0);
+        let dict = &data[dict_start..start];
+        if find(dict, b"/DCTDecode").is_none() || find(dict, b"/Image").is_none() {
+            continue;
+        }
+
+        // stream关键字后是CRLF或LF
+        let mut body_start = offset;
+        if data.get(body_start) == Some(&b'\r') {
+            body_start += 1;
+        }
+        if data.get(body_start) == Some(&b'\n') {
+            body_start += 1;
+        }
+        let Some(body_end) = find(&data[body_start..], b"endstream").map(|index| body_start + index) else {
+            break;
+        };
+        // endstream之前的换行不属于流内容
+        let body = &data[body_start..body_end];
+        let body = body.strip_suffix(b"\n").unwrap_or(body);
+        let body = body.strip_suffix(b"\r").unwrap_or(body);
+        if body.starts_with(&[0xFF, 0xD8]) && body.len() <= MAX_PDF_IMAGE_SIZE {
+            images.push(body.to_vec());
+        }
+        offset = body_end + b"endstream".len();
+    }
+
+    images
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+fn rfind(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).rposition(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JPEG: &[u8] = &[0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x10, 0xFF, 0xD9];
+
+    fn image_object(id: u32, filter: &str, body: &[u8], newline: &[u8]) -> Vec<u8> {
+        let mut object = format!(
+            "{} 0 obj\n<< /Type /XObject /Subtype /Image /Filter /{} /Length {} >>\nstream",
+            id,
+            filter,
+            body.len()
+        )
+        .into_bytes();
+        object.extend_from_slice(newline);
+        object.extend_from_slice(body);
+        object.extend_from_slice(b"\nendstream\nendobj\n");
+        object
+    }
+
+    #[test]
+    fn extracts_dct_images_only() {
+        let mut pdf = b"%PDF-1.4\n".to_vec();
+        pdf.extend(image_object(1, "DCTDecode", JPEG, b"\r\n"));
+        pdf.extend(image_object(2, "FlateDecode", b"x\x9c", b"\n"));
+        // 内容流不是图片
+        pdf.extend(b"3 0 obj\n<< /Length 5 /Filter /DCTDecode >>\nstream\n\xFF\xD8abc\nendstream\nendobj\n");
+        pdf.extend(image_object(4, "DCTDecode", JPEG, b"\n"));
+        pdf.extend(b"%%EOF\n");
+
+        assert_eq!(extract_pdf_images(&pdf), [JPEG.to_vec(), JPEG.to_vec()]);
+    }
+
+    #[test]
+    fn skips_non_jpeg_and_endstream_keywords() {
+        let mut pdf = b"%PDF-1.4\n".to_vec();
+        pdf.extend(image_object(1, "DCTDecode", b"not a jpeg", b"\n"));
+        pdf.extend(b"endstream endstream\n");
+        pdf.extend(image_object(2, "DCTDecode", JPEG, b"\n"));
+
+        assert_eq!(extract_pdf_images(&pdf), [JPEG.to_vec()]);
+    }
+
+    #[test]
+    fn truncated_stream_is_ignored() {
+        let mut pdf = b"%PDF-1.4\n".to_vec();
+        pdf.extend(image_object(1, "DCTDecode", JPEG, b"\n"));
+        let complete = pdf.len();
+        pdf.extend(image_object(2, "DCTDecode", JPEG, b"\n"));
+        // 截断在第二个图片流中间
+        pdf.truncate(complete + 80);
+
+        assert_eq!(extract_pdf_images(&pdf), [JPEG.to_vec()]);
+        assert!(extract_pdf_images(b"stream").is_empty());
+        assert!(extract_pdf_images(b"").is_empty());
+    }
+}
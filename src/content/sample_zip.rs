@@ -0,0 +1,115 @@
+//! 恶意样本安全打包
+//!
+//! 将样本文件放入AES-256加密的ZIP中下发，避免浏览器或杀毒软件直接接触原始样本。
+//! 压缩包内附带未加密的manifest.json，列出每个文件的哈希，无需解压即可核对
+
+use std::collections::HashSet;
+use std::io::{Cursor, Write};
+
+use chrono::Utc;
+use serde::Serialize;
+use zip::write::SimpleFileOptions;
+
+use crate::content::digest::compute_digests;
+use crate::content::file_type::{detect_file_type, FileKind};
+
+/// manifest文件名
+pub const MANIFEST_NAME: &str = "manifest.json";
+
+/// 待打包的样本文件
+#[derive(Debug, Clone)]
+pub struct SampleFile {
+    /// 来源ID（附件ID或邮件ID）
+    pub source_id: String,
+    /// 文件名
+    pub name: String,
+    /// 文件内容
+    pub data: Vec<u8>,
+}
+
+/// manifest中的文件条目
+#[derive(Debug, Serialize)]
+struct ManifestEntry<'a> {
+    source_id: &'a str,
+    name: String,
+    size: u64,
+    md5: String,
+    sha1: String,
+    sha256: String,
+    detected_type: FileKind,
+}
+
+/// manifest内容
+#[derive(Debug, Serialize)]
+struct Manifest<'a> {
+    email_id: Option<&'a str>,
+    generated_at: String,
+    encryption: &'static str,
+    files: Vec<ManifestEntry<'a>>,
+}
+
+/// 将样本打包为加密ZIP
+///
+/// 样本使用AES-256加密，manifest.json不加密
+pub fn build_sample_zip(email_id: Option<&str>, files: &[SampleFile], password: &str) -> anyhow::Result<Vec<u8>> {
+    let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    let mut used_names = HashSet::new();
+    used_names.insert(MANIFEST_NAME.to_string());
+
+    let mut entries = Vec::with_capacity(files.len());
+    for file in files {
+        let name = unique_name(&safe_name(&file.name), &mut used_names);
+        let options = SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Deflated)
+            .with_aes_encryption(zip::AesMode::Aes256, password);
+        writer.start_file(name.as_str(), options)?;
+        writer.write_all(&file.data)?;
+
+        let digests = compute_digests(&file.data);
+        entries.push(ManifestEntry {
+            source_id: &file.source_id,
+            name,
+            size: file.data.len() as u64,
+            md5: digests.md5,
+            sha1: digests.sha1,
+            sha256: digests.sha256,
+            detected_type: detect_file_type(&file.data),
+        });
+    }
+
+    let manifest = Manifest {
+        email_id,
+        generated_at: Utc::now().to_rfc3339(),
+        encryption: "aes-256",
+        files: entries,
+    };
+    writer.start_file(MANIFEST_NAME, SimpleFileOptions::default())?;
+    writer.write_all(&serde_json::to_vec_pretty(&manifest)?)?;
+
+    Ok(writer.finish()?.into_inner())
+}
+
+/// 去掉路径部分，防止压缩包内出现目录穿越
+pub(crate) fn safe_name(name: &str) -> String {
+    let name = name.rsplit(['/', '\\']).next().unwrap_or("").trim();
+    if name.is_empty() || name == "." || name == ".." {
+        "sample.bin".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+/// 文件重名时追加序号
+fn unique_name(name: &str, used: &mut HashSet<String>) -> String {
+    if used.insert(name.to_string()) {
+        return name.to_string();
+    }
+    let (stem, extension) = match name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => (stem, format!(".{}", extension)),
+        _ => (name, String::new()),
+    };
+    (1..)
+        .map(|n| format!("{}({}){}", stem, n, extension))
+        .find(|candidate| used.insert(candidate.clone()))
+        .unwrap_or_else(|| name.to_string())
+}
@@ -0,0 +1,171 @@
+//! 邮件会话重建
+//!
+//! 按In-Reply-To与References建立回复关系，找不到被回复邮件时按归一化主题归入会话根节点。
+//! 这是JWZ算法的简化版本：只在给定的候选邮件中建立关系，不为缺失的邮件生成占位节点
+
+use std::collections::{HashMap, VecDeque};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::content::headers::{normalize_subject, MessageHeaders};
+
+/// 参与会话重建的邮件
+#[derive(Debug, Clone)]
+pub struct ThreadMessage {
+    /// 邮件头
+    pub headers: MessageHeaders,
+    /// 邮件时间，用于确定根节点和同级邮件的顺序
+    pub time: DateTime<Utc>,
+}
+
+/// 邮件与父节点的关联方式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ThreadLink {
+    /// 会话根节点
+    Root,
+    /// 通过In-Reply-To关联
+    InReplyTo,
+    /// 通过References关联
+    References,
+    /// 找不到被回复邮件，按主题归入根节点
+    Subject,
+}
+
+/// 邮件在会话树中的位置
+#[derive(Debug, Clone)]
+pub struct ThreadPosition {
+    /// 在输入列表中的下标
+    pub index: usize,
+    /// 父节点下标，根节点为None
+    pub parent: Option<usize>,
+    /// 深度，根节点为0
+    pub depth: usize,
+    /// 与父节点的关联方式
+    pub link: ThreadLink,
+}
+
+/// 重建`seed`所在的会话
+///
+/// 与`seed`通过Message-ID引用或相同归一化主题（可传递地）相连的邮件构成会话，
+/// 返回按深度优先排列的会话树，同级邮件按时间排序
+pub fn reconstruct_thread(messages: &[ThreadMessage], seed: usize) -> Vec<ThreadPosition> {
+    if seed >= messages.len() {
+        return vec![];
+    }
+
+    // 同一Message-ID出现多次（如多收件人分别投递）时取最早的一封
+    let mut order: Vec<usize> = (0..messages.len()).collect();
+    order.sort_by_key(|&i| (messages[i].time, i));
+    let mut by_id: HashMap<&str, usize> = HashMap::new();
+    for &i in &order {
+        if let Some(id) = &messages[i].headers.message_id {
+            by_id.entry(id.as_str()).or_insert(i);
+        }
+    }
+    let subjects: Vec<String> = messages
+        .iter()
+        .map(|message| normalize_subject(&message.headers.subject))
+        .collect();
+
+    let members = connected(messages, seed, &by_id, &subjects);
+
+    // 优先使用In-Reply-To，其次使用References中最近的一个
+    let mut parents: Vec<Option<(usize, ThreadLink)>> = vec![None; messages.len()];
+    for &i in order.iter().filter(|&&i| members[i]) {
+        let headers = &messages[i].headers;
+        let candidates = headers
+            .in_reply_to
+            .iter()
+            .map(|id| (id, ThreadLink::InReplyTo))
+            .chain(headers.references.iter().rev().map(|id| (id, ThreadLink::References)));
+        for (id, link) in candidates {
+            if let Some(&parent) = by_id.get(id.as_str())
+                && parent != i
+                && members[parent]
+                && !is_ancestor(&parents, i, parent)
+            {
+                parents[i] = Some((parent, link));
+                break;
+            }
+        }
+    }
+
+    // 最早的无父节点邮件作为根，其余无父节点邮件归入根节点
+    let Some(root) = order.iter().copied().find(|&i| members[i] && parents[i].is_none()) else {
+        return vec![];
+    };
+    for &i in &order {
+        if members[i] && i != root && parents[i].is_none() {
+            parents[i] = Some((root, ThreadLink::Subject));
+        }
+    }
+
+    let mut children: HashMap<usize, Vec<usize>> = HashMap::new();
+    for &i in &order {
+        if let Some((parent, _)) = parents[i] {
+            children.entry(parent).or_default().push(i);
+        }
+    }
+
+    // 深度优先遍历，children已按时间排序
+    let mut positions = Vec::new();
+    let mut stack = vec![(root, 0)];
+    while let Some((index, depth)) = stack.pop() {
+        positions.push(ThreadPosition {
+            index,
+            parent: parents[index].map(|(parent, _)| parent),
+            depth,
+            link: parents[index].map(|(_, link)| link).unwrap_or(ThreadLink::Root),
+        });
+        if let Some(kids) = children.get(&index) {
+            stack.extend(kids.iter().rev().map(|&kid| (kid, depth + 1)));
+        }
+    }
+    positions
+}
+
+/// 从`seed`出发，找出通过Message-ID引用或相同主题相连的全部邮件
+fn connected(messages: &[ThreadMessage], seed: usize, by_id: &HashMap<&str, usize>, subjects: &[String]) -> Vec<bool> {
+    // 被引用关系是单向的，这里同时记录反向边
+    let mut edges: Vec<Vec<usize>> = vec![Vec::new(); messages.len()];
+    for (i, message) in messages.iter().enumerate() {
+        for id in message.headers.in_reply_to.iter().chain(&message.headers.references) {
+            if let Some(&j) = by_id.get(id.as_str())
+                && j != i
+            {
+                edges[i].push(j);
+                edges[j].push(i);
+            }
+        }
+    }
+
+    let mut members = vec![false; messages.len()];
+    members[seed] = true;
+    let mut queue = VecDeque::from([seed]);
+    while let Some(i) = queue.pop_front() {
+        let same_subject = (0..messages.len()).filter(|&j| !subjects[i].is_empty() && subjects[j] == subjects[i]);
+        for j in edges[i].iter().copied().chain(same_subject).collect::<Vec<_>>() {
+            if !members[j] {
+                members[j] = true;
+                queue.push_back(j);
+            }
+        }
+    }
+    members
+}
+
+/// `node`是否为`candidate`的祖先（或就是它），用于避免引用成环
+fn is_ancestor(parents: &[Option<(usize, ThreadLink)>], node: usize, candidate: usize) -> bool {
+    let mut current = Some(candidate);
+    let mut steps = 0;
+    while let Some(index) = current {
+        if index == node || steps > parents.len() {
+            return true;
+        }
+        current = parents[index].map(|(parent, _)| parent);
+        steps += 1;
+    }
+    false
+}
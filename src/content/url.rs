@@ -0,0 +1,108 @@
+//! 链接归一化
+//!
+//! 二维码、条码中解出的内容格式不统一（大写、缺少协议、带`URL:`前缀等），
+//! 归一化后才能与正文链接去重并与情报值比对
+
+/// 二维码内容中常见的链接前缀
+const URL_PREFIXES: &[&str] = &["urlto:", "url:"];
+
+/// 将解码出的内容归一化为http或https链接，不是链接时返回None
+///
+/// 协议与主机名转为小写，去掉默认端口、主机名末尾的点和片段，路径为空时补为`/`。
+/// 路径与查询参数大小写敏感，保持不变
+pub fn normalize_url(raw: &str) -> Option<String> {
+    let mut text = raw.trim_matches(|c: char| c.is_whitespace() || c.is_control());
+    for prefix in URL_PREFIXES {
+        if text.len() > prefix.len() && text.is_char_boundary(prefix.len()) && text[..prefix.len()].eq_ignore_ascii_case(prefix) {
+            text = text[prefix.len()..].trim();
+        }
+    }
+    if text.is_empty() || text.chars().any(char::is_whitespace) {
+        return None;
+    }
+
+    let (scheme, rest) = match text.split_once("://") {
+        Some((scheme, rest)) => (scheme.to_ascii_lowercase(), rest),
+        // 没有协议的裸域名，如www.example.com/login；带@的是邮件地址或mailto
+        None if !text.contains('@') => ("http".to_string(), text),
+        None => return None,
+    };
+    if scheme != "http" && scheme != "https" {
+        return None;
+    }
+
+    let rest = rest.split('#').next().unwrap_or_default();
+    let split = rest.find(['/', '?']).unwrap_or(rest.len());
+    let (authority, path) = rest.split_at(split);
+
+    // 保留用户信息，user@host形式常被用来伪装真实主机
+    let (userinfo, host_port) = match authority.rsplit_once('@') {
+        Some((userinfo, host_port)) => (Some(userinfo), host_port),
+        None => (None, authority),
+    };
+    let (host, port) = match host_port.find(']') {
+        // IPv6地址本身包含冒号
+        Some(end) => (&host_port[..=end], host_port[end + 1..].strip_prefix(':')),
+        None => match host_port.rsplit_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (host_port, None),
+        },
+    };
+    let host = host.trim_end_matches('.').to_lowercase();
+    if !valid_host(&host) {
+        return None;
+    }
+    let port = match port {
+        Some("") | None => None,
+        Some(port) => {
+            let port: u16 = port.parse().ok()?;
+            let default = if scheme == "https" { 443 } else { 80 };
+            (port != default).then_some(port)
+        }
+    };
+
+    let mut url = format!("{}://", scheme);
+    if let Some(userinfo) = userinfo {
+        url.push_str(userinfo);
+        url.push('@');
+    }
+    url.push_str(&host);
+    if let Some(port) = port {
+        url.push_str(&format!(":{}", port));
+    }
+    if !path.starts_with('/') {
+        url.push('/');
+    }
+    url.push_str(path);
+    Some(url)
+}
+
+/// 链接的主机名（小写，不含端口与用户信息）
+pub fn url_host(url: &str) -> Option<String> {
+    let rest = url.split_once("://")?.1;
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host = authority.rsplit('@').next()?.split(':').next()?.to_lowercase();
+    (!host.is_empty()).then_some(host)
+}
+
+/// 链接的路径，不含查询参数与片段
+pub fn url_path(url: &str) -> String {
+    let rest = url.split_once("://").map(|(_, rest)| rest).unwrap_or(url);
+    let rest = rest.split(['?', '#']).next().unwrap_or_default();
+    match rest.find('/') {
+        Some(index) => rest[index..].to_string(),
+        None => "/".to_string(),
+    }
+}
+
+/// 主机名只能是带点的域名、IPv4或带方括号的IPv6地址
+fn valid_host(host: &str) -> bool {
+    if host.starts_with('[') {
+        return host.ends_with(']') && host.len() > 2;
+    }
+    host.contains('.')
+        && !host.starts_with('.')
+        && host
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '.' || c == '_')
+}
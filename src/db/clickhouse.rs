@@ -3,6 +3,7 @@ use clickhouse::{Client, Row};
 use tracing::{debug, info, error};
 use serde::{de::DeserializeOwned, Serialize};
 use crate::db::{DbConfig, /* DbError, */ DbResult};
+use crate::db::query::{Param, Query};
 
 /// ClickHouse客户端
 #[derive(Clone)]
@@ -44,6 +45,11 @@ impl ClickHouseClient {
         })
     }
     
+    /// 测试连接是否可用
+    pub async fn ping(&self) -> DbResult<()> {
+        ping(&self.client).await
+    }
+
     /// 获取内部客户端引用
     pub fn inner(&self) -> &Client {
         &self.client
@@ -54,13 +60,24 @@ impl ClickHouseClient {
         &self.database
     }
     
-    /// 执行查询并返回行列表
-    pub async fn query<R>(&self, query: &str) -> DbResult<Vec<R>> 
+    /// 执行参数化查询并返回行列表
+    ///
+    /// 参数按`?`出现的顺序绑定，由客户端负责转义，用户输入不直接拼接进SQL
+    pub async fn fetch<R>(&self, query: &Query) -> DbResult<Vec<R>>
     where
         R: Row + DeserializeOwned + Send + 'static,
     {
-        debug!("执行ClickHouse查询: {}", query);
-        let result = self.client.query(query).fetch_all().await
+        debug!("执行ClickHouse查询: {}，参数个数: {}", query.sql(), query.params().len());
+        query.validate()?;
+        let mut bound = self.client.query(query.sql());
+        for param in query.params() {
+            bound = match param {
+                Param::Text(value) => bound.bind(value.as_str()),
+                Param::UInt(value) => bound.bind(*value),
+                Param::Int(value) => bound.bind(*value),
+            };
+        }
+        let result = bound.fetch_all().await
             .context("执行ClickHouse查询失败")?;
         Ok(result)
     }
@@ -80,6 +97,8 @@ impl ClickHouseClient {
     }
     
     /// 执行不返回结果的查询（如CREATE TABLE等）
+    ///
+    /// 只用于迁移等代码中固定的语句，不能拼接用户输入
     pub async fn exec(&self, query: &str) -> DbResult<()> {
         debug!("执行ClickHouse命令: {}", query);
         self.client.query(query).execute().await
@@ -0,0 +1,381 @@
+//! 数据库连接管理
+//!
+//! 启动时连接失败以内存模式运行，后台按退避间隔重连，连上后切换到ClickHouse存储库。
+//! 已连接的数据库中途断开时进入不可用状态，请求返回503直到重连成功，不再回退到模拟数据
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use tokio::sync::{Notify, RwLock};
+use tracing::{error, info, warn};
+
+use crate::db::{ClickHouseClient, DbConfig, DbError, DbResult, Migrator};
+
+/// 数据访问模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DbMode {
+    /// 从未连上数据库，使用内存模拟数据
+    Memory,
+    /// 使用ClickHouse
+    Database,
+    /// 数据库连接已断开，正在重连
+    Unavailable,
+}
+
+impl DbMode {
+    /// 序列化使用的稳定名称
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DbMode::Memory => "memory",
+            DbMode::Database => "database",
+            DbMode::Unavailable => "unavailable",
+        }
+    }
+
+    /// 中文显示名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            DbMode::Memory => "内存模式",
+            DbMode::Database => "数据库模式",
+            DbMode::Unavailable => "数据库不可用",
+        }
+    }
+}
+
+/// 重连的退避间隔，每次失败后翻倍，不超过上限
+#[derive(Debug, Clone, Copy)]
+pub struct Backoff {
+    /// 首次重连前的等待时间
+    pub initial: Duration,
+    /// 最长等待时间
+    pub max: Duration,
+}
+
+impl Backoff {
+    /// 失败后的下一次等待时间
+    fn next(&self, current: Duration) -> Duration {
+        (current * 2).min(self.max)
+    }
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            initial: Duration::from_secs(2),
+            max: Duration::from_secs(60),
+        }
+    }
+}
+
+/// 连接状态
+#[derive(Debug, Clone)]
+pub struct ConnectionStatus {
+    /// 当前模式
+    pub mode: DbMode,
+    /// 进入当前模式的时间
+    pub since: DateTime<Utc>,
+    /// 最近一次连接失败或断开的原因
+    pub last_error: Option<String>,
+    /// 下一次重连的时间，已连接时为None
+    pub next_retry: Option<DateTime<Utc>>,
+}
+
+impl ConnectionStatus {
+    /// 距下一次重连的秒数，至少为1
+    fn retry_after_secs(&self) -> u64 {
+        self.next_retry.map_or(1, |at| (at - Utc::now()).num_seconds().max(1) as u64)
+    }
+}
+
+/// 数据库连接已断开
+///
+/// 不可用状态下的查询和查询期间发现连接断开的失败返回该错误，路由层据此返回503
+#[derive(Debug, Clone, Copy)]
+pub struct DbUnavailable {
+    /// 距下一次重连的秒数
+    pub retry_after_secs: u64,
+}
+
+impl fmt::Display for DbUnavailable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "数据库暂不可用，正在重连，请在{}秒后重试", self.retry_after_secs)
+    }
+}
+
+impl std::error::Error for DbUnavailable {}
+
+/// 连接状态与当前客户端
+struct State {
+    status: ConnectionStatus,
+    client: Option<Arc<ClickHouseClient>>,
+}
+
+/// 建立连接失败的原因
+enum ConnectFailure {
+    /// 数据库不可达
+    Connect(DbError),
+    /// 表结构与服务不一致
+    Schema(DbError),
+}
+
+/// 数据库连接管理器
+///
+/// 存储库每次查询前通过`client`取得当前客户端，连接恢复或断开后新的查询立即生效
+pub struct ConnectionManager {
+    /// 数据库配置
+    config: DbConfig,
+    /// 建立连接时是否校验数据库结构
+    schema_check: bool,
+    /// 重连的退避间隔
+    backoff: Backoff,
+    /// 连接状态
+    state: RwLock<State>,
+    /// 连接断开时唤醒重连任务
+    lost: Notify,
+}
+
+impl ConnectionManager {
+    /// 连接数据库并启动后台重连任务
+    ///
+    /// 数据库不可达时以内存模式启动；连接成功但表结构与服务不一致时返回错误，避免带着错误的结构运行
+    pub async fn start(config: DbConfig, schema_check: bool, backoff: Backoff) -> DbResult<Arc<Self>> {
+        let manager = Arc::new(Self {
+            config,
+            schema_check,
+            backoff,
+            state: RwLock::new(State {
+                status: ConnectionStatus {
+                    mode: DbMode::Memory,
+                    since: Utc::now(),
+                    last_error: None,
+                    next_retry: None,
+                },
+                client: None,
+            }),
+            lost: Notify::new(),
+        });
+
+        match manager.establish().await {
+            Ok(client) => manager.set_connected(client).await,
+            Err(ConnectFailure::Schema(e)) => return Err(e),
+            Err(ConnectFailure::Connect(e)) => {
+                info!("ClickHouse连接失败: {:?}，使用内存模式，后台继续重连", e);
+                manager.state.write().await.status.last_error = Some(format!("{:#}", e));
+            }
+        }
+
+        tokio::spawn(manager.clone().reconnect());
+        Ok(manager)
+    }
+
+    /// 当前连接状态
+    pub async fn status(&self) -> ConnectionStatus {
+        self.state.read().await.status.clone()
+    }
+
+    /// 当前模式
+    pub async fn mode(&self) -> DbMode {
+        self.state.read().await.status.mode
+    }
+
+    /// 距下一次重连的秒数，至少为1
+    pub async fn retry_after_secs(&self) -> u64 {
+        self.state.read().await.status.retry_after_secs()
+    }
+
+    /// 数据库不可用的错误，带有距下一次重连的秒数
+    pub async fn unavailable(&self) -> DbUnavailable {
+        DbUnavailable {
+            retry_after_secs: self.retry_after_secs().await,
+        }
+    }
+
+    /// 当前使用的客户端：数据库模式返回客户端，内存模式返回None，数据库不可用时返回错误
+    pub async fn client(&self) -> DbResult<Option<Arc<ClickHouseClient>>> {
+        let state = self.state.read().await;
+        match state.status.mode {
+            DbMode::Database => Ok(state.client.clone()),
+            DbMode::Memory => Ok(None),
+            DbMode::Unavailable => Err(DbUnavailable {
+                retry_after_secs: state.status.retry_after_secs(),
+            }
+            .into()),
+        }
+    }
+
+    /// 查询失败后检查连接，数据库已不可达时进入不可用状态并唤醒重连任务，返回连接是否已断开
+    ///
+    /// SQL错误等与连接无关的失败不影响状态
+    pub async fn check(&self, client: &ClickHouseClient) -> bool {
+        let Err(e) = client.ping().await else {
+            return false;
+        };
+        let mut state = self.state.write().await;
+        // 其他请求已处理过本次断开
+        if state.status.mode != DbMode::Database {
+            return true;
+        }
+        state.status = ConnectionStatus {
+            mode: DbMode::Unavailable,
+            since: Utc::now(),
+            last_error: Some(format!("{:#}", e)),
+            next_retry: None,
+        };
+        state.client = None;
+        error!("ClickHouse连接已断开，请求将返回503直到重连成功: {:#}", e);
+        self.lost.notify_one();
+        true
+    }
+
+    /// 后台重连：未连接时按退避间隔重试，连接成功后等待下一次断开
+    async fn reconnect(self: Arc<Self>) {
+        let mut delay = self.backoff.initial;
+        loop {
+            if self.mode().await == DbMode::Database {
+                self.lost.notified().await;
+                delay = self.backoff.initial;
+                continue;
+            }
+
+            let next_retry = Utc::now() + chrono::Duration::from_std(delay).unwrap_or_default();
+            self.state.write().await.status.next_retry = Some(next_retry);
+            tokio::time::sleep(delay).await;
+
+            match self.establish().await {
+                Ok(client) => {
+                    info!("ClickHouse连接已恢复，切换到数据库模式");
+                    self.set_connected(client).await;
+                }
+                Err(ConnectFailure::Connect(e) | ConnectFailure::Schema(e)) => {
+                    delay = self.backoff.next(delay);
+                    warn!("ClickHouse重连失败，{}秒后重试: {:#}", delay.as_secs(), e);
+                    self.state.write().await.status.last_error = Some(format!("{:#}", e));
+                }
+            }
+        }
+    }
+
+    /// 连接数据库，按配置校验表结构
+    async fn establish(&self) -> Result<ClickHouseClient, ConnectFailure> {
+        let client = ClickHouseClient::new(self.config.clone())
+            .await
+            .map_err(ConnectFailure::Connect)?;
+        if self.schema_check {
+            Migrator::new(&client).verify().await.map_err(ConnectFailure::Schema)?;
+        } else {
+            info!("已关闭数据库结构校验");
+        }
+        Ok(client)
+    }
+
+    /// 切换到数据库模式
+    async fn set_connected(&self, client: ClickHouseClient) {
+        *self.state.write().await = State {
+            status: ConnectionStatus {
+                mode: DbMode::Database,
+                since: Utc::now(),
+                last_error: None,
+                next_retry: None,
+            },
+            client: Some(Arc::new(client)),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use axum::http::StatusCode;
+
+    /// 模拟ClickHouse的HTTP接口，`up`为false时所有请求返回503，返回接口地址
+    async fn stub_server(up: Arc<AtomicBool>) -> String {
+        let app = axum::Router::new().fallback(move || {
+            let up = up.clone();
+            async move {
+                if up.load(Ordering::SeqCst) {
+                    StatusCode::OK
+                } else {
+                    StatusCode::SERVICE_UNAVAILABLE
+                }
+            }
+        });
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        url
+    }
+
+    /// 等待后台重连任务切换到`mode`
+    async fn wait_for(manager: &ConnectionManager, mode: DbMode) {
+        for _ in 0..500 {
+            if manager.mode().await == mode {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("未切换到{}", mode.label());
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_max() {
+        let backoff = Backoff::default();
+        let mut delay = backoff.initial;
+        let delays: Vec<u64> = (0..7)
+            .map(|_| {
+                delay = backoff.next(delay);
+                delay.as_secs()
+            })
+            .collect();
+        assert_eq!(delays, [4, 8, 16, 32, 60, 60, 60]);
+    }
+
+    #[tokio::test]
+    async fn switches_between_memory_database_and_unavailable() {
+        let up = Arc::new(AtomicBool::new(false));
+        let config = DbConfig {
+            url: stub_server(up.clone()).await,
+            database: "test".to_string(),
+            username: None,
+            password: None,
+        };
+        let backoff = Backoff {
+            initial: Duration::from_millis(20),
+            max: Duration::from_millis(50),
+        };
+
+        // 启动时连接失败，以内存模式运行
+        let manager = ConnectionManager::start(config, false, backoff).await.unwrap();
+        let status = manager.status().await;
+        assert_eq!(status.mode, DbMode::Memory);
+        assert!(status.last_error.is_some());
+        assert!(manager.client().await.unwrap().is_none());
+
+        // 后台重连成功后切换到数据库模式
+        up.store(true, Ordering::SeqCst);
+        wait_for(&manager, DbMode::Database).await;
+        let client = manager.client().await.unwrap().unwrap();
+        assert!(manager.status().await.last_error.is_none());
+        // 连接正常时查询失败不影响状态
+        assert!(!manager.check(&client).await);
+        assert_eq!(manager.mode().await, DbMode::Database);
+
+        // 连接断开后进入不可用状态，不回退到内存模式
+        up.store(false, Ordering::SeqCst);
+        assert!(manager.check(&client).await);
+        assert_eq!(manager.mode().await, DbMode::Unavailable);
+        let Err(error) = manager.client().await else {
+            panic!("数据库不可用时应返回错误");
+        };
+        assert!(error.downcast_ref::<DbUnavailable>().is_some_and(|e| e.retry_after_secs >= 1));
+        // 其他请求再次发现断开时不重复处理
+        assert!(manager.check(&client).await);
+        assert_eq!(manager.mode().await, DbMode::Unavailable);
+
+        // 重连成功后恢复
+        up.store(true, Ordering::SeqCst);
+        wait_for(&manager, DbMode::Database).await;
+        assert!(manager.client().await.unwrap().is_some());
+    }
+}
@@ -0,0 +1,578 @@
+//! 数据库结构迁移
+//!
+//! migrations目录下的SQL按版本号顺序执行，执行记录和SQL的SHA256写入schema_migrations表。
+//! 已执行的迁移不允许修改，结构变更需要新增迁移。ClickHouse没有DDL事务，
+//! 迁移中的语句应可重复执行（如CREATE TABLE IF NOT EXISTS、ADD COLUMN IF NOT EXISTS），
+//! 中途失败后修正问题重新执行`migrate up`即可
+
+use std::collections::HashMap;
+
+use anyhow::{anyhow, bail};
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use tracing::{info, warn};
+
+use crate::db::{
+    ClickHouseClient, Conditions, CountResult, DbResult, NewSchemaMigrationRow, Order, SchemaColumnRow,
+    SchemaMigrationRow, SelectQuery,
+};
+
+/// 迁移记录表
+const MIGRATIONS_TABLE: &str = "schema_migrations";
+
+/// 一条迁移
+#[derive(Debug, Clone, Copy)]
+pub struct Migration {
+    /// 版本号，按升序执行
+    pub version: u32,
+    /// 迁移名称
+    pub name: &'static str,
+    /// 迁移SQL，多条语句以行尾分号分隔
+    pub sql: &'static str,
+}
+
+impl Migration {
+    /// 迁移SQL的SHA256，忽略换行符差异
+    pub fn checksum(&self) -> String {
+        hex::encode(Sha256::digest(self.sql.replace("\r\n", "\n").as_bytes()))
+    }
+
+    /// 拆分为单条语句，ClickHouse的HTTP接口每次只能执行一条
+    fn statements(&self) -> Vec<String> {
+        let sql: Vec<&str> = self
+            .sql
+            .lines()
+            .map(str::trim_end)
+            .filter(|line| !line.trim_start().starts_with("--"))
+            .collect();
+        sql.join("\n")
+            .split(";\n")
+            .map(|statement| statement.trim().trim_end_matches(';').trim().to_string())
+            .filter(|statement| !statement.is_empty())
+            .collect()
+    }
+}
+
+/// 全部迁移，新增迁移追加到末尾
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "create_alert_intelligence",
+        sql: include_str!("../../migrations/0001_create_alert_intelligence.sql"),
+    },
+    Migration {
+        version: 2,
+        name: "create_data_mail_info",
+        sql: include_str!("../../migrations/0002_create_data_mail_info.sql"),
+    },
+    Migration {
+        version: 3,
+        name: "create_intelligence_disposition_log",
+        sql: include_str!("../../migrations/0003_create_intelligence_disposition_log.sql"),
+    },
+    Migration {
+        version: 4,
+        name: "create_intelligence_retro_hunt",
+        sql: include_str!("../../migrations/0004_create_intelligence_retro_hunt.sql"),
+    },
+    Migration {
+        version: 5,
+        name: "create_data_mail_attachment",
+        sql: include_str!("../../migrations/0005_create_data_mail_attachment.sql"),
+    },
+    Migration {
+        version: 6,
+        name: "add_data_mail_info_eml_path",
+        sql: include_str!("../../migrations/0006_add_data_mail_info_eml_path.sql"),
+    },
+    Migration {
+        version: 7,
+        name: "create_quarantine_tables",
+        sql: include_str!("../../migrations/0007_create_quarantine_tables.sql"),
+    },
+];
+
+const ATTRIBUTE_ENUM: &str = "Enum8('Domain' = 1, 'Url' = 2, 'EmailAddress' = 3, 'Ipv4' = 4, 'Md5' = 5, \
+     'UrlDomain' = 6, 'EmailDomain' = 7, 'Sha256' = 8)";
+const URGENCY_ENUM: &str = "Enum8('High' = 1, 'Medium' = 2, 'Low' = 3)";
+const SOURCE_ENUM: &str = "Enum8('Local' = 1, 'Cloud' = 2)";
+const PARENT_SOURCE_ENUM: &str = "Enum8('Email' = 1, 'File' = 2, 'EmailHeader' = 3, 'EmailBody' = 4, \
+     'QrCode' = 5, 'Text' = 6, 'Url' = 7, 'Smtp' = 8)";
+const ACTION_ENUM: &str = "Enum8('Accept' = 1, 'Discard' = 2, 'Reject' = 3, 'Quarantine' = 4)";
+const DISPOSITION_ENUM: &str = "Enum8('white' = 1, 'black' = 2, 'report' = 3)";
+
+/// 服务依赖的表结构：表名与字段（名称、类型）
+///
+/// alert_intelligence与data_mail_info的字段与AlertIntelligence、DataMailInfo的COLUMN_NAMES一致，
+/// 修改模型或新增迁移时需要同步更新
+const EXPECTED_SCHEMA: &[(&str, &[(&str, &str)])] = &[
+    (
+        "alert_intelligence",
+        &[
+            ("id", "UInt64"),
+            ("mail_id", "UInt64"),
+            ("timestamp", "DateTime"),
+            ("intelligence_id", "UUID"),
+            ("description", "String"),
+            ("source_industry", "String"),
+            ("first_discovered_time", "DateTime"),
+            ("last_active_time", "DateTime"),
+            ("intelligence_update_time", "DateTime"),
+            ("intelligence_expiration_time", "DateTime"),
+            ("attribute", ATTRIBUTE_ENUM),
+            ("intelligence_type", "String"),
+            ("urgency", URGENCY_ENUM),
+            ("value", "String"),
+            ("pattern", "String"),
+            ("info", "String"),
+            ("threat_actor", "String"),
+            ("joint_prevention_and_control", "String"),
+            ("display_to_name", "String"),
+            ("display_to_address", "String"),
+            ("display_to_account", "String"),
+            ("display_to_domain", "String"),
+            ("is_deleted", "UInt8"),
+            ("updated_at", "DateTime"),
+            ("source", SOURCE_ENUM),
+            ("source_id", "UInt64"),
+            ("source_mime_type", "String"),
+            ("parent_source", PARENT_SOURCE_ENUM),
+            ("scan_time_us", "UInt64"),
+        ],
+    ),
+    (
+        "data_mail_info",
+        &[
+            ("id", "UInt64"),
+            ("action", ACTION_ENUM),
+            ("timestamp", "DateTime"),
+            ("send_time", "DateTime"),
+            ("subject", "String"),
+            ("bcc_name", "String"),
+            ("bcc_email", "String"),
+            ("bcc_email_account", "String"),
+            ("bcc_email_domain", "String"),
+            ("display_from", "String"),
+            ("display_to_name", "String"),
+            ("display_to_address", "String"),
+            ("display_to_account", "String"),
+            ("display_to_domain", "String"),
+            ("config", "String"),
+            ("sasl_login", "String"),
+            ("sasl_method", "String"),
+            ("client_ip", "String"),
+            ("client_ptr", "String"),
+            ("client_port", "Int32"),
+            ("client_helo", "String"),
+            ("client_active_connections", "Int32"),
+            ("client_envelope_from_name", "String"),
+            ("client_envelope_from_address", "String"),
+            ("client_envelope_from_account", "String"),
+            ("client_envelope_from_domain", "String"),
+            ("client_envelope_to_name", "String"),
+            ("client_envelope_to_address", "String"),
+            ("client_envelope_to_account", "String"),
+            ("client_envelope_to_domain", "String"),
+            ("tls", "String"),
+            ("server", "String"),
+            ("protocol_version", "String"),
+            ("text_body", "String"),
+            ("html_body", "String"),
+            ("deconstruction_modules", "String"),
+            ("detection_modules", "String"),
+            ("hash_sha1", "String"),
+            ("hash_sha256", "String"),
+            ("hash_md5", "String"),
+            ("direction", "String"),
+            ("protocol_check", "String"),
+            ("extract_password", "String"),
+            ("eml_path", "String"),
+        ],
+    ),
+    (
+        "intelligence_disposition_log",
+        &[
+            ("intelligence_id", "UUID"),
+            ("disposition", DISPOSITION_ENUM),
+            ("enabled", "UInt8"),
+            ("operator", "String"),
+            ("reason", "String"),
+            ("created_at", "DateTime"),
+        ],
+    ),
+    (
+        "intelligence_retro_hunt",
+        &[
+            ("intelligence_id", "UUID"),
+            ("job_id", "String"),
+            ("operator", "String"),
+            ("scan_start_time", "DateTime"),
+            ("scan_end_time", "DateTime"),
+            ("scanned_mails", "UInt64"),
+            ("matched_mails", "UInt64"),
+            ("finished_at", "DateTime"),
+        ],
+    ),
+    (
+        "data_mail_attachment",
+        &[
+            ("mail_id", "UInt64"),
+            ("attachment_id", "String"),
+            ("filename", "String"),
+            ("file_path", "String"),
+            ("size", "UInt64"),
+            ("hash_md5", "String"),
+            ("hash_sha1", "String"),
+            ("hash_sha256", "String"),
+            ("timestamp", "DateTime"),
+        ],
+    ),
+    (
+        "quarantine_action",
+        &[
+            ("action_id", "String"),
+            ("mail_id", "String"),
+            ("kind", "String"),
+            ("state", "String"),
+            ("recipients", "Array(String)"),
+            ("reason", "String"),
+            ("requested_by", "String"),
+            ("requested_at", "DateTime64(3)"),
+            ("reviewed_by", "String"),
+            ("reviewed_at", "DateTime64(3)"),
+            ("completed_at", "DateTime64(3)"),
+            ("error", "String"),
+            ("version", "UInt64"),
+        ],
+    ),
+    (
+        "quarantine_audit",
+        &[
+            ("timestamp", "DateTime64(3)"),
+            ("action_id", "String"),
+            ("mail_id", "String"),
+            ("kind", "String"),
+            ("event", "String"),
+            ("actor", "String"),
+            ("detail", "String"),
+            ("version", "UInt64"),
+        ],
+    ),
+];
+
+/// 迁移状态
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MigrationState {
+    /// 已执行
+    Applied {
+        /// 执行时间
+        applied_at: DateTime<Utc>,
+    },
+    /// 未执行
+    Pending,
+    /// 已执行，但执行后迁移SQL被修改
+    Modified {
+        /// 执行时间
+        applied_at: DateTime<Utc>,
+    },
+    /// 数据库中有记录，但当前版本没有该迁移（由更新的版本执行）
+    Unknown {
+        /// 执行时间
+        applied_at: DateTime<Utc>,
+    },
+}
+
+impl MigrationState {
+    /// 状态显示名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Applied { .. } => "已执行",
+            Self::Pending => "未执行",
+            Self::Modified { .. } => "已修改",
+            Self::Unknown { .. } => "未知",
+        }
+    }
+}
+
+/// 单条迁移的状态
+#[derive(Debug, Clone)]
+pub struct MigrationStatus {
+    /// 版本号
+    pub version: u32,
+    /// 迁移名称
+    pub name: String,
+    /// 状态
+    pub state: MigrationState,
+}
+
+/// 迁移执行器
+pub struct Migrator<'a> {
+    client: &'a ClickHouseClient,
+}
+
+impl<'a> Migrator<'a> {
+    /// 创建迁移执行器
+    pub fn new(client: &'a ClickHouseClient) -> Self {
+        Self { client }
+    }
+
+    /// 查询全部迁移的状态，按版本号排序
+    pub async fn status(&self) -> DbResult<Vec<MigrationStatus>> {
+        let mut applied: HashMap<u32, SchemaMigrationRow> =
+            self.applied().await?.into_iter().map(|row| (row.version, row)).collect();
+        let applied_at = |row: &SchemaMigrationRow| {
+            DateTime::from_timestamp(i64::from(row.applied_secs), 0).unwrap_or_default()
+        };
+
+        let mut statuses: Vec<MigrationStatus> = MIGRATIONS
+            .iter()
+            .map(|migration| {
+                let state = match applied.remove(&migration.version) {
+                    Some(row) if row.checksum == migration.checksum() => {
+                        MigrationState::Applied { applied_at: applied_at(&row) }
+                    }
+                    Some(row) => MigrationState::Modified { applied_at: applied_at(&row) },
+                    None => MigrationState::Pending,
+                };
+                MigrationStatus {
+                    version: migration.version,
+                    name: migration.name.to_string(),
+                    state,
+                }
+            })
+            .collect();
+        statuses.extend(applied.into_values().map(|row| MigrationStatus {
+            version: row.version,
+            state: MigrationState::Unknown { applied_at: applied_at(&row) },
+            name: row.name,
+        }));
+        statuses.sort_by_key(|status| status.version);
+        Ok(statuses)
+    }
+
+    /// 按顺序执行未执行的迁移，返回本次执行的版本号
+    ///
+    /// 有已修改的迁移时拒绝执行
+    pub async fn up(&self) -> DbResult<Vec<u32>> {
+        self.client
+            .exec(&format!(
+                "CREATE TABLE IF NOT EXISTS {} (version UInt32, name String, checksum String, \
+                 applied_at DateTime DEFAULT now()) ENGINE = MergeTree ORDER BY version",
+                MIGRATIONS_TABLE
+            ))
+            .await?;
+
+        let statuses = self.status().await?;
+        let modified: Vec<String> = statuses
+            .iter()
+            .filter(|status| matches!(status.state, MigrationState::Modified { .. }))
+            .map(|status| format!("{:04}_{}", status.version, status.name))
+            .collect();
+        if !modified.is_empty() {
+            bail!("以下迁移执行后被修改，请恢复原SQL并新增迁移: {}", modified.join(", "));
+        }
+
+        let mut executed = Vec::new();
+        for migration in MIGRATIONS {
+            let pending = statuses
+                .iter()
+                .any(|status| status.version == migration.version && status.state == MigrationState::Pending);
+            if !pending {
+                continue;
+            }
+            info!("执行迁移: {:04}_{}", migration.version, migration.name);
+            for statement in migration.statements() {
+                self.client.exec(&statement).await.map_err(|e| {
+                    anyhow!("迁移{:04}_{}执行失败: {:#}", migration.version, migration.name, e)
+                })?;
+            }
+            self.client
+                .insert(
+                    MIGRATIONS_TABLE,
+                    vec![NewSchemaMigrationRow {
+                        version: migration.version,
+                        name: migration.name.to_string(),
+                        checksum: migration.checksum(),
+                    }],
+                )
+                .await?;
+            executed.push(migration.version);
+        }
+        Ok(executed)
+    }
+
+    /// 启动时校验数据库结构
+    ///
+    /// 缺少表或字段、字段类型不符、已执行的迁移被修改时返回错误，错误信息列出全部问题；
+    /// 只有未执行的迁移而结构符合要求时（如表由其他系统创建）仅记录警告
+    pub async fn verify(&self) -> DbResult<()> {
+        let statuses = self.status().await?;
+        let mut problems: Vec<String> = statuses
+            .iter()
+            .filter(|status| matches!(status.state, MigrationState::Modified { .. }))
+            .map(|status| format!("迁移{:04}_{}执行后被修改", status.version, status.name))
+            .collect();
+
+        let query = SelectQuery::from("system.columns")
+            .columns(["table AS table_name", "name AS column_name", "type AS column_type"])
+            .filter(
+                Conditions::all()
+                    .raw("database = currentDatabase()")
+                    .in_list("table", EXPECTED_SCHEMA.iter().map(|(table, _)| *table)),
+            )
+            .build();
+        let live: HashMap<(String, String), String> = self
+            .client
+            .fetch::<SchemaColumnRow>(&query)
+            .await?
+            .into_iter()
+            .map(|row| ((row.table_name, row.column_name), row.column_type))
+            .collect();
+
+        for (table, columns) in EXPECTED_SCHEMA {
+            if !live.keys().any(|(name, _)| name == table) {
+                problems.push(format!("缺少表{}", table));
+                continue;
+            }
+            for (column, expected) in *columns {
+                match live.get(&(table.to_string(), column.to_string())) {
+                    None => problems.push(format!("表{}缺少字段{}（{}）", table, column, expected)),
+                    Some(actual) if !type_matches(expected, actual) => problems.push(format!(
+                        "表{}字段{}类型应为{}，实际为{}",
+                        table, column, expected, actual
+                    )),
+                    Some(_) => {}
+                }
+            }
+        }
+
+        if !problems.is_empty() {
+            bail!(
+                "数据库结构与服务不一致:\n  - {}\n请执行`analysis-api migrate up`，或按migrations目录中的SQL修正表结构",
+                problems.join("\n  - ")
+            );
+        }
+
+        let pending: Vec<String> = statuses
+            .iter()
+            .filter(|status| status.state == MigrationState::Pending)
+            .map(|status| format!("{:04}_{}", status.version, status.name))
+            .collect();
+        if !pending.is_empty() {
+            warn!("数据库结构符合要求，但有未记录执行的迁移: {}", pending.join(", "));
+        }
+        info!("数据库结构校验通过");
+        Ok(())
+    }
+
+    /// 已执行的迁移，迁移记录表不存在时为空
+    async fn applied(&self) -> DbResult<Vec<SchemaMigrationRow>> {
+        let exists_query = SelectQuery::from("system.tables")
+            .column("count() AS count")
+            .filter(
+                Conditions::all()
+                    .raw("database = currentDatabase()")
+                    .eq("name", MIGRATIONS_TABLE),
+            )
+            .build();
+        let exists = self.client.fetch::<CountResult>(&exists_query).await?;
+        if exists.first().is_none_or(|row| row.count == 0) {
+            return Ok(Vec::new());
+        }
+        let applied_query = SelectQuery::from(MIGRATIONS_TABLE)
+            .columns([
+                "version",
+                "any(name) AS name",
+                "any(checksum) AS checksum",
+                "toUInt32(toUnixTimestamp(min(applied_at))) AS applied_secs",
+            ])
+            .group_by("version")
+            .order_by("version", Order::Asc)
+            .build();
+        self.client.fetch(&applied_query).await
+    }
+}
+
+/// 比较字段类型，忽略LowCardinality包装、DateTime时区和空白差异，Nullable包装内的类型同样处理
+fn type_matches(expected: &str, actual: &str) -> bool {
+    fn normalize(column_type: &str) -> String {
+        let column_type: String = column_type.chars().filter(|c| !c.is_whitespace()).collect();
+        if let Some(inner) = column_type.strip_prefix("LowCardinality(").and_then(|rest| rest.strip_suffix(')')) {
+            return normalize(inner);
+        }
+        if let Some(inner) = column_type.strip_prefix("Nullable(").and_then(|rest| rest.strip_suffix(')')) {
+            return format!("Nullable({})", normalize(inner));
+        }
+        if column_type.starts_with("DateTime(") {
+            return "DateTime".to_string();
+        }
+        if let Some((precision, _timezone)) = column_type.strip_prefix("DateTime64(").and_then(|rest| rest.split_once(',')) {
+            return format!("DateTime64({})", precision);
+        }
+        column_type
+    }
+    normalize(expected) == normalize(actual)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn migration(sql: &'static str) -> Migration {
+        Migration { version: 1, name: "test", sql }
+    }
+
+    #[test]
+    fn checksum_ignores_line_endings() {
+        assert_eq!(
+            migration("SELECT 1;\n").checksum(),
+            "b4e0497804e46e0a0b0b8c31975b062152d551bac49c3c2e80932567b4085dcd"
+        );
+        assert_eq!(migration("SELECT 1;\r\nSELECT 2;\r\n").checksum(), migration("SELECT 1;\nSELECT 2;\n").checksum());
+        assert_ne!(migration("SELECT 1;\n").checksum(), migration("SELECT 1; \n").checksum());
+    }
+
+    #[test]
+    fn statements_split_on_line_ending_semicolons() {
+        let sql = "-- 建表\nCREATE TABLE a\n(\n    id UInt64 -- 注释保留在语句中\n);\n\n;\n\
+                   ALTER TABLE a ADD COLUMN IF NOT EXISTS s String DEFAULT ';';  \r\n\
+                   INSERT INTO a VALUES (1);";
+        assert_eq!(migration(sql).statements(), [
+            "CREATE TABLE a\n(\n    id UInt64 -- 注释保留在语句中\n)",
+            "ALTER TABLE a ADD COLUMN IF NOT EXISTS s String DEFAULT ';'",
+            "INSERT INTO a VALUES (1)",
+        ]);
+        assert!(migration("-- 只有注释\n;\n").statements().is_empty());
+        assert!(migration("").statements().is_empty());
+    }
+
+    #[test]
+    fn bundled_migrations_are_ordered() {
+        assert!(MIGRATIONS.windows(2).all(|pair| pair[0].version < pair[1].version));
+        assert!(MIGRATIONS.iter().all(|migration| !migration.statements().is_empty()));
+    }
+
+    #[test]
+    fn type_matches_ignores_wrappers_and_timezones() {
+        let matching = [
+            ("String", "LowCardinality(String)"),
+            ("Nullable(String)", "LowCardinality(Nullable(String))"),
+            ("DateTime", "DateTime('Asia/Shanghai')"),
+            ("Nullable(DateTime)", "Nullable(DateTime('UTC'))"),
+            ("DateTime64(3)", "DateTime64(3, 'UTC')"),
+            ("Array(String)", "Array( String )"),
+        ];
+        for (expected, actual) in matching {
+            assert!(type_matches(expected, actual), "{} {}", expected, actual);
+        }
+        let different = [
+            ("String", "Nullable(String)"),
+            ("DateTime", "DateTime64(3)"),
+            ("DateTime64(3)", "DateTime64(6, 'UTC')"),
+            ("UInt32", "UInt64"),
+        ];
+        for (expected, actual) in different {
+            assert!(!type_matches(expected, actual), "{} {}", expected, actual);
+        }
+    }
+}
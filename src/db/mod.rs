@@ -1,21 +1,33 @@
 //! 数据库模块
 
 pub mod models;
-// 保留repository模块但标记为废弃
-// pub mod repository;
+pub mod repository;
 pub mod clickhouse;
+pub mod connection;
+pub mod migrations;
+pub mod query;
 
 // 导出主要类型
 pub use models::{
     UserEvent, AnalysisResult, CountResult,
+    MailBodyRow, MailIntelligenceValueRow, MailExtractPasswordRow, MailFileRow, MailAttachmentRow, MailSearchRow,
+    MailDeliveryRow, NamedCountRow, CountSpanRow, TrendCountRow, HitSummaryRow, HitTrendRow, ThreatActorRow,
+    CustomIntelligenceRow, RecipientRankRow,
+    SenderSummaryRow, SendingSourceRow, HashMailRow, HashIntelligenceRow,
+    ThreadMailRow, CampaignMailRow, CampaignHitRow, TimelineIntelRow, TimelineMailRow, TimelineBucketRow,
+    TimelineUpdateRow, TimelineDispositionRow, TimelineRetroHuntRow, TimelineMailHitRow, SpreadSnapshotRow,
+    QuarantineActionRow, QuarantineAuditRow, SchemaMigrationRow, NewSchemaMigrationRow, SchemaColumnRow,
 };
-// 移除repository的导出
-// pub use repository::{
-//     UserEventRepository, AnalysisResultRepository,
-//     ClickHouseUserEventRepository, ClickHouseAnalysisResultRepository,
-//     InMemoryUserEventRepository, InMemoryAnalysisResultRepository,
-// };
+pub use repository::{
+    Repositories, IntelligenceHitRepository, MailInfoRepository, DispositionRepository, StatisticsRepository,
+    AttachmentRepository, QuarantineRepository, BlobStore, Blob, BlobRead, FileBlobStore, ClickHouseRepository, InMemoryRepository, ManagedRepository, TimelineHits, HashMails, RecipientProfileData, SenderProfileData,
+};
+#[cfg(test)]
+pub(crate) use repository::FIXTURE_TIME;
 pub use clickhouse::ClickHouseClient;
+pub use connection::{Backoff, ConnectionManager, ConnectionStatus, DbMode, DbUnavailable};
+pub use migrations::Migrator;
+pub use query::{Conditions, LikeMatch, Order, Param, Query, SelectQuery};
 
 /// 数据库配置
 #[derive(Clone, Debug)]
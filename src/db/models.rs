@@ -1,56 +1,177 @@
+use std::fmt;
+use std::marker::PhantomData;
+use std::str::FromStr;
+
 use chrono::{DateTime, Utc};
 use clickhouse::Row;
-use serde::{Deserialize, Serialize};
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use uuid::Uuid;
 
-/// 情报属性枚举类型
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum AttributeType {
-    Domain = 1,
-    Url = 2,
-    EmailAddress = 3,
-    Ipv4 = 4,
-    Md5 = 5,
-    UrlDomain = 6,
-    EmailDomain = 7,
-    Sha256 = 8,
+/// 定义与ClickHouse Enum8列对应的枚举
+///
+/// RowBinary格式按Int8读写Enum8，因此序列化为判别值而不是变体名称；反序列化同时接受判别值
+/// （Enum8或UInt8列）和Enum8中的名称（如`toString(attribute)`的查询结果）
+macro_rules! stored_enum {
+    (
+        $(#[$meta:meta])*
+        $name:ident ($label:literal) {
+            $($variant:ident = $code:literal,)+
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        #[repr(i8)]
+        pub enum $name {
+            $($variant = $code,)+
+        }
+
+        impl $name {
+            /// 全部取值，按判别值排序
+            pub const ALL: &'static [$name] = &[$($name::$variant),+];
+
+            /// 数据库中存储的判别值
+            pub fn code(self) -> i8 {
+                self as i8
+            }
+
+            /// Enum8中的名称
+            pub fn as_str(self) -> &'static str {
+                match self {
+                    $($name::$variant => stringify!($variant),)+
+                }
+            }
+        }
+
+        impl TryFrom<i8> for $name {
+            type Error = String;
+
+            fn try_from(code: i8) -> Result<Self, Self::Error> {
+                Self::ALL
+                    .iter()
+                    .copied()
+                    .find(|value| value.code() == code)
+                    .ok_or_else(|| format!("无效的{}: {}", $label, code))
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = String;
+
+            fn from_str(value: &str) -> Result<Self, Self::Err> {
+                Self::ALL
+                    .iter()
+                    .copied()
+                    .find(|item| item.as_str() == value)
+                    .ok_or_else(|| format!("无效的{}: {}", $label, value))
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_i8(self.code())
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                deserializer.deserialize_i8(StoredEnumVisitor::<$name>::new($label))
+            }
+        }
+    };
 }
 
-/// 情报紧急程度枚举
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum UrgencyLevel {
-    High = 1,
-    Medium = 2,
-    Low = 3,
+/// 按判别值或名称解析`stored_enum!`定义的枚举
+struct StoredEnumVisitor<T> {
+    /// 枚举的中文名称，用于错误信息
+    label: &'static str,
+    marker: PhantomData<T>,
 }
 
-/// 情报来源类型枚举
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum SourceType {
-    Local = 1,
-    Cloud = 2,
+impl<T> StoredEnumVisitor<T> {
+    fn new(label: &'static str) -> Self {
+        Self { label, marker: PhantomData }
+    }
 }
 
-/// 父文件来源类型枚举
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum ParentSourceType {
-    Email = 1,
-    File = 2,
-    EmailHeader = 3,
-    EmailBody = 4,
-    QrCode = 5,
-    Text = 6,
-    Url = 7,
-    Smtp = 8,
+impl<T> Visitor<'_> for StoredEnumVisitor<T>
+where
+    T: TryFrom<i8, Error = String> + FromStr<Err = String>,
+{
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}的判别值或名称", self.label)
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<T, E> {
+        let code = i8::try_from(value).map_err(|_| E::custom(format!("无效的{}: {}", self.label, value)))?;
+        T::try_from(code).map_err(E::custom)
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<T, E> {
+        let code = i8::try_from(value).map_err(|_| E::custom(format!("无效的{}: {}", self.label, value)))?;
+        T::try_from(code).map_err(E::custom)
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<T, E> {
+        value.parse().map_err(E::custom)
+    }
 }
 
-/// 处置动作枚举
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum ActionType {
-    Accept = 1,
-    Discard = 2,
-    Reject = 3,
-    Quarantine = 4,
+stored_enum! {
+    /// 情报属性枚举类型
+    AttributeType("情报属性") {
+        Domain = 1,
+        Url = 2,
+        EmailAddress = 3,
+        Ipv4 = 4,
+        Md5 = 5,
+        UrlDomain = 6,
+        EmailDomain = 7,
+        Sha256 = 8,
+    }
+}
+
+stored_enum! {
+    /// 情报紧急程度枚举
+    UrgencyLevel("紧急程度") {
+        High = 1,
+        Medium = 2,
+        Low = 3,
+    }
+}
+
+stored_enum! {
+    /// 情报来源类型枚举
+    SourceType("情报来源") {
+        Local = 1,
+        Cloud = 2,
+    }
+}
+
+stored_enum! {
+    /// 父文件来源类型枚举
+    ParentSourceType("父文件来源") {
+        Email = 1,
+        File = 2,
+        EmailHeader = 3,
+        EmailBody = 4,
+        QrCode = 5,
+        Text = 6,
+        Url = 7,
+        Smtp = 8,
+    }
+}
+
+stored_enum! {
+    /// 处置动作枚举
+    ActionType("处置动作") {
+        Accept = 1,
+        Discard = 2,
+        Reject = 3,
+        Quarantine = 4,
+    }
 }
 
 /// 警报情报模型 - 对应alert_intelligence表
@@ -218,6 +339,8 @@ pub struct DataMailInfo {
     pub protocol_check: String,
     /// 提取密码
     pub extract_password: String,
+    /// 原始邮件在文件存储中的路径
+    pub eml_path: String,
 }
 
 impl Row for DataMailInfo {
@@ -235,10 +358,738 @@ impl Row for DataMailInfo {
         "tls", "server", "protocol_version", "text_body", "html_body",
         "deconstruction_modules", "detection_modules", "hash_sha1",
         "hash_sha256", "hash_md5", "direction", "protocol_check",
-        "extract_password"
+        "extract_password", "eml_path"
+    ];
+}
+
+/// 邮件正文查询结果 - data_mail_info表的投影
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MailBodyRow {
+    /// 邮件主题
+    pub subject: String,
+    /// 文本内容
+    pub text_body: String,
+    /// HTML内容
+    pub html_body: String,
+}
+
+impl Row for MailBodyRow {
+    const COLUMN_NAMES: &'static [&'static str] = &["subject", "text_body", "html_body"];
+}
+
+/// 邮件命中情报值查询结果 - alert_intelligence表的投影
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MailIntelligenceValueRow {
+    /// 情报ID（字符串形式）
+    pub intelligence_id: String,
+    /// 情报属性（枚举名称）
+    pub attribute: String,
+    /// 情报值
+    pub value: String,
+}
+
+impl Row for MailIntelligenceValueRow {
+    const COLUMN_NAMES: &'static [&'static str] = &["intelligence_id", "attribute", "value"];
+}
+
+/// 邮件提取密码查询结果 - data_mail_info表的投影
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MailExtractPasswordRow {
+    /// 提取密码
+    pub extract_password: String,
+}
+
+impl Row for MailExtractPasswordRow {
+    const COLUMN_NAMES: &'static [&'static str] = &["extract_password"];
+}
+
+/// 原始邮件文件 - data_mail_info表的投影
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MailFileRow {
+    /// 原始邮件在文件存储中的路径，未入库时为空
+    pub eml_path: String,
+    /// 邮件文件SHA256
+    pub hash_sha256: String,
+}
+
+impl Row for MailFileRow {
+    const COLUMN_NAMES: &'static [&'static str] = &["eml_path", "hash_sha256"];
+}
+
+/// 邮件附件记录 - data_mail_attachment表的投影
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MailAttachmentRow {
+    /// 所属邮件ID
+    pub mail_id: u64,
+    /// 附件ID
+    pub attachment_id: String,
+    /// 文件名
+    pub filename: String,
+    /// 附件存储中的路径
+    pub file_path: String,
+    /// 入库时的文件大小（字节）
+    pub size: u64,
+    /// 入库时的MD5
+    pub hash_md5: String,
+    /// 入库时的SHA1
+    pub hash_sha1: String,
+    /// 入库时的SHA256
+    pub hash_sha256: String,
+}
+
+impl Row for MailAttachmentRow {
+    const COLUMN_NAMES: &'static [&'static str] = &[
+        "mail_id", "attachment_id", "filename", "file_path", "size", "hash_md5", "hash_sha1", "hash_sha256"
+    ];
+}
+
+/// 邮件搜索结果 - data_mail_info表的投影
+///
+/// 枚举列以字符串形式读取，时间列以Unix时间戳读取。别名与原列名不同，避免在WHERE中遮蔽原列
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MailSearchRow {
+    /// 邮件唯一ID
+    pub id: u64,
+    /// 处置动作（枚举名称）
+    pub action_name: String,
+    /// 邮件检测时间（Unix时间戳，秒）
+    pub timestamp_secs: u32,
+    /// 邮件主题
+    pub subject: String,
+    /// 显示发件人
+    pub display_from: String,
+    /// 显示收件人完整邮箱地址
+    pub display_to_address: String,
+    /// 显示收件人邮箱账号部分
+    pub display_to_account: String,
+    /// 显示收件人邮箱完整域名
+    pub display_to_domain: String,
+    /// 认证用户名
+    pub sasl_login: String,
+    /// 客户端IP
+    pub client_ip: String,
+    /// 信封发件人完整邮箱地址
+    pub client_envelope_from_address: String,
+    /// 信封收件人完整邮箱地址
+    pub client_envelope_to_address: String,
+    /// 信封收件人邮箱账号部分
+    pub client_envelope_to_account: String,
+    /// 信封收件人邮箱完整域名
+    pub client_envelope_to_domain: String,
+    /// 传输层安全协议信息
+    pub tls: String,
+    /// 协议信息
+    pub protocol_version: String,
+    /// 文本内容
+    pub text_body: String,
+    /// 文件SHA1哈希值
+    pub hash_sha1: String,
+    /// 文件SHA256哈希值
+    pub hash_sha256: String,
+    /// MD5哈希值
+    pub hash_md5: String,
+    /// 邮件方向
+    pub direction: String,
+}
+
+impl Row for MailSearchRow {
+    const COLUMN_NAMES: &'static [&'static str] = &[
+        "id", "action_name", "timestamp_secs", "subject", "display_from",
+        "display_to_address", "display_to_account", "display_to_domain",
+        "sasl_login", "client_ip", "client_envelope_from_address",
+        "client_envelope_to_address", "client_envelope_to_account",
+        "client_envelope_to_domain", "tls", "protocol_version", "text_body",
+        "hash_sha1", "hash_sha256", "hash_md5", "direction"
     ];
 }
 
+/// 邮件投递信息查询结果 - data_mail_info表的投影
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MailDeliveryRow {
+    /// 处置动作（枚举名称）
+    pub action_name: String,
+    /// 信封发件人完整邮箱地址
+    pub client_envelope_from_address: String,
+    /// 信封收件人完整邮箱地址
+    pub client_envelope_to_address: String,
+}
+
+impl Row for MailDeliveryRow {
+    const COLUMN_NAMES: &'static [&'static str] = &[
+        "action_name", "client_envelope_from_address", "client_envelope_to_address"
+    ];
+}
+
+/// 分组计数结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NamedCountRow {
+    /// 分组名称
+    pub name: String,
+    /// 计数值
+    pub count: u64,
+}
+
+impl Row for NamedCountRow {
+    const COLUMN_NAMES: &'static [&'static str] = &["name", "count"];
+}
+
+/// 计数与首末时间聚合结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CountSpanRow {
+    /// 计数值
+    pub count: u64,
+    /// 最早时间（Unix时间戳，秒），没有数据时为0
+    pub first_secs: u32,
+    /// 最晚时间（Unix时间戳，秒），没有数据时为0
+    pub last_secs: u32,
+}
+
+impl Row for CountSpanRow {
+    const COLUMN_NAMES: &'static [&'static str] = &["count", "first_secs", "last_secs"];
+}
+
+/// 按时间分桶的邮件计数结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrendCountRow {
+    /// 分桶起始时间（Unix时间戳，秒）
+    pub bucket_secs: u32,
+    /// 邮件数
+    pub total: u64,
+    /// 命中情报的邮件数
+    pub hits: u64,
+}
+
+impl Row for TrendCountRow {
+    const COLUMN_NAMES: &'static [&'static str] = &["bucket_secs", "total", "hits"];
+}
+
+/// 看板命中汇总 - alert_intelligence表在时间范围内的聚合
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HitSummaryRow {
+    /// 命中情报的邮件数
+    pub hit_mails: u64,
+    /// 受影响的收件人数
+    pub recipients: u64,
+    /// 受影响的收件单位（收件人域名）数
+    pub units: u64,
+    /// 命中的情报数
+    pub intelligence: u64,
+    /// 命中情报的来源数
+    pub sources: u64,
+}
+
+impl Row for HitSummaryRow {
+    const COLUMN_NAMES: &'static [&'static str] = &["hit_mails", "recipients", "units", "intelligence", "sources"];
+}
+
+/// 看板命中趋势 - alert_intelligence表按时间分桶的聚合
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HitTrendRow {
+    /// 分桶起始时间（Unix时间戳，秒）
+    pub bucket_secs: u32,
+    /// 命中情报的邮件数
+    pub hit_mails: u64,
+    /// 受影响的收件人数
+    pub recipients: u64,
+    /// 受影响的收件单位数
+    pub units: u64,
+    /// 命中的情报数
+    pub intelligence: u64,
+}
+
+impl Row for HitTrendRow {
+    const COLUMN_NAMES: &'static [&'static str] = &["bucket_secs", "hit_mails", "recipients", "units", "intelligence"];
+}
+
+/// 命中的攻击组织 - alert_intelligence表按攻击组织的聚合
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreatActorRow {
+    /// 攻击组织名称
+    pub name: String,
+    /// 攻击组织类型（如APT、黑产），情报未记录时为空
+    pub kind: String,
+    /// 命中的邮件数
+    pub count: u64,
+}
+
+impl Row for ThreatActorRow {
+    const COLUMN_NAMES: &'static [&'static str] = &["name", "kind", "count"];
+}
+
+/// 本地自定义情报的命中情况 - alert_intelligence表中来源为Local的情报
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CustomIntelligenceRow {
+    /// 时间范围内命中的自定义情报数
+    pub hits: u64,
+    /// 有命中记录的自定义情报总数，不限时间范围
+    pub total: u64,
+}
+
+impl Row for CustomIntelligenceRow {
+    const COLUMN_NAMES: &'static [&'static str] = &["hits", "total"];
+}
+
+/// 收件人暴露度排行 - data_mail_info表按收件人的聚合
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecipientRankRow {
+    /// 收件人邮箱地址（小写）
+    pub address: String,
+    /// 收到的邮件数
+    pub received: u64,
+    /// 命中情报的邮件数
+    pub targeted: u64,
+    /// 最近命中时间（Unix时间戳，秒），没有命中时为0
+    pub last_secs: u32,
+}
+
+impl Row for RecipientRankRow {
+    const COLUMN_NAMES: &'static [&'static str] = &["address", "received", "targeted", "last_secs"];
+}
+
+/// 发件人发信概况 - data_mail_info表的聚合
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SenderSummaryRow {
+    /// 邮件数
+    pub total: u64,
+    /// 不同收件人数
+    pub recipients: u64,
+    /// 使用TLS传输的邮件数
+    pub tls_mails: u64,
+    /// 经过SASL认证的邮件数
+    pub authenticated: u64,
+    /// 命中情报的邮件数
+    pub hit_mails: u64,
+}
+
+impl Row for SenderSummaryRow {
+    const COLUMN_NAMES: &'static [&'static str] = &["total", "recipients", "tls_mails", "authenticated", "hit_mails"];
+}
+
+/// 发件人发信来源 - data_mail_info表按客户端IP与PTR的聚合
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SendingSourceRow {
+    /// 客户端IP
+    pub client_ip: String,
+    /// 客户端PTR
+    pub client_ptr: String,
+    /// 邮件数
+    pub count: u64,
+    /// 最近发信时间（Unix时间戳，秒）
+    pub last_secs: u32,
+}
+
+impl Row for SendingSourceRow {
+    const COLUMN_NAMES: &'static [&'static str] = &["client_ip", "client_ptr", "count", "last_secs"];
+}
+
+/// 哈希关联邮件 - data_mail_info表的投影
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HashMailRow {
+    /// 邮件唯一ID
+    pub id: u64,
+    /// 处置动作（枚举名称）
+    pub action_name: String,
+    /// 邮件检测时间（Unix时间戳，秒）
+    pub timestamp_secs: u32,
+    /// 邮件主题
+    pub subject: String,
+    /// 显示发件人
+    pub display_from: String,
+    /// 信封发件人完整邮箱地址
+    pub client_envelope_from_address: String,
+    /// 全部收件人（小写、去重）
+    pub recipient_list: Vec<String>,
+    /// 邮件文件哈希是否与查询值相同
+    pub mail_match: u8,
+    /// 邮件是否命中了该哈希的情报
+    pub intel_match: u8,
+}
+
+impl Row for HashMailRow {
+    const COLUMN_NAMES: &'static [&'static str] = &[
+        "id", "action_name", "timestamp_secs", "subject", "display_from",
+        "client_envelope_from_address", "recipient_list", "mail_match", "intel_match"
+    ];
+}
+
+/// 哈希对应的情报 - alert_intelligence表按情报ID的聚合
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HashIntelligenceRow {
+    /// 情报ID（字符串形式）
+    pub intel_id: String,
+    /// 情报属性（枚举名称）
+    pub attribute_name: String,
+    /// 情报分类
+    pub type_name: String,
+    /// 紧急程度（枚举名称）
+    pub urgency_name: String,
+    /// 情报描述
+    pub description_text: String,
+    /// 攻击组织名称
+    pub actor_name: String,
+    /// 命中的邮件数
+    pub hit_mails: u64,
+    /// 首次命中时间（Unix时间戳，秒）
+    pub first_secs: u32,
+    /// 最近命中时间（Unix时间戳，秒）
+    pub last_secs: u32,
+}
+
+impl Row for HashIntelligenceRow {
+    const COLUMN_NAMES: &'static [&'static str] = &[
+        "intel_id", "attribute_name", "type_name", "urgency_name", "description_text",
+        "actor_name", "hit_mails", "first_secs", "last_secs"
+    ];
+}
+
+/// 会话候选邮件 - data_mail_info表的投影
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreadMailRow {
+    /// 邮件唯一ID
+    pub id: u64,
+    /// 处置动作（枚举名称）
+    pub action_name: String,
+    /// 邮件检测时间（Unix时间戳，秒）
+    pub timestamp_secs: u32,
+    /// 邮件主题
+    pub subject: String,
+    /// 显示发件人
+    pub display_from: String,
+    /// 是否命中情报
+    pub hit: u8,
+}
+
+impl Row for ThreadMailRow {
+    const COLUMN_NAMES: &'static [&'static str] = &[
+        "id", "action_name", "timestamp_secs", "subject", "display_from", "hit"
+    ];
+}
+
+/// 批次聚类候选邮件 - data_mail_info表的投影
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CampaignMailRow {
+    /// 邮件唯一ID
+    pub id: u64,
+    /// 处置动作（枚举名称）
+    pub action_name: String,
+    /// 邮件检测时间（Unix时间戳，秒）
+    pub timestamp_secs: u32,
+    /// 邮件主题
+    pub subject: String,
+    /// 纯文本正文
+    pub text_body: String,
+    /// HTML正文
+    pub html_body: String,
+    /// 显示发件人
+    pub display_from: String,
+    /// 信封发件人完整邮箱地址
+    pub client_envelope_from_address: String,
+    /// 全部收件人（小写、去重）
+    pub recipient_list: Vec<String>,
+}
+
+impl Row for CampaignMailRow {
+    const COLUMN_NAMES: &'static [&'static str] = &[
+        "id", "action_name", "timestamp_secs", "subject", "text_body", "html_body",
+        "display_from", "client_envelope_from_address", "recipient_list"
+    ];
+}
+
+/// 批次聚类候选邮件命中的情报 - alert_intelligence表的投影
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CampaignHitRow {
+    /// 邮件ID
+    pub mail_id: u64,
+    /// 情报ID（字符串形式）
+    pub intel_id: String,
+    /// 情报属性（枚举名称）
+    pub attribute_name: String,
+    /// 情报值
+    pub value_text: String,
+    /// 情报分类
+    pub type_name: String,
+    /// 紧急程度（枚举名称）
+    pub urgency_name: String,
+    /// 攻击组织名称
+    pub actor_name: String,
+}
+
+impl Row for CampaignHitRow {
+    const COLUMN_NAMES: &'static [&'static str] = &[
+        "mail_id", "intel_id", "attribute_name", "value_text", "type_name", "urgency_name", "actor_name"
+    ];
+}
+
+/// 情报的来源与首次发现时间 - alert_intelligence表的聚合
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelineIntelRow {
+    /// 情报ID（字符串形式）
+    pub intel_id: String,
+    /// 命中记录数
+    pub count: u64,
+    /// 首次发现时间（Unix时间戳，秒）
+    pub first_secs: u32,
+    /// 情报来源（枚举名称）
+    pub source_name: String,
+    /// 情报来源行业（JSON数组）
+    pub industry: String,
+    /// 情报过期时间（Unix时间戳，秒）
+    pub expire_secs: u32,
+    /// 最新的情报描述
+    pub description_text: String,
+    /// 情报属性（枚举名称）
+    pub attribute_name: String,
+    /// 情报值
+    pub value_text: String,
+}
+
+impl Row for TimelineIntelRow {
+    const COLUMN_NAMES: &'static [&'static str] = &[
+        "intel_id", "count", "first_secs", "source_name", "industry", "expire_secs", "description_text",
+        "attribute_name", "value_text"
+    ];
+}
+
+/// 情报更新记录 - alert_intelligence表按更新时间分组的投影
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelineUpdateRow {
+    /// 情报ID（字符串形式）
+    pub intel_id: String,
+    /// 情报更新时间（Unix时间戳，秒）
+    pub update_secs: u32,
+    /// 该次更新的情报描述
+    pub description_text: String,
+}
+
+impl Row for TimelineUpdateRow {
+    const COLUMN_NAMES: &'static [&'static str] = &["intel_id", "update_secs", "description_text"];
+}
+
+/// 情报处置变更记录 - 对应intelligence_disposition_log表
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelineDispositionRow {
+    /// 情报ID（字符串形式）
+    pub intel_id: String,
+    /// 处置类型：white、black或report
+    pub disposition_name: String,
+    /// 1表示加入名单或上报，0表示移出名单或撤销上报
+    pub enabled: u8,
+    /// 操作人
+    pub operator: String,
+    /// 处置原因
+    pub reason: String,
+    /// 处置时间（Unix时间戳，秒）
+    pub created_secs: u32,
+}
+
+impl Row for TimelineDispositionRow {
+    const COLUMN_NAMES: &'static [&'static str] = &[
+        "intel_id", "disposition_name", "enabled", "operator", "reason", "created_secs"
+    ];
+}
+
+/// 情报回溯排查结果 - 对应intelligence_retro_hunt表
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelineRetroHuntRow {
+    /// 情报ID（字符串形式）
+    pub intel_id: String,
+    /// 排查任务ID
+    pub job_id: String,
+    /// 发起人
+    pub operator: String,
+    /// 排查范围起点（Unix时间戳，秒）
+    pub scan_start_secs: u32,
+    /// 排查范围终点（Unix时间戳，秒）
+    pub scan_end_secs: u32,
+    /// 扫描的邮件数
+    pub scanned_mails: u64,
+    /// 新命中的邮件数
+    pub matched_mails: u64,
+    /// 完成时间（Unix时间戳，秒）
+    pub finished_secs: u32,
+}
+
+impl Row for TimelineRetroHuntRow {
+    const COLUMN_NAMES: &'static [&'static str] = &[
+        "intel_id", "job_id", "operator", "scan_start_secs", "scan_end_secs", "scanned_mails", "matched_mails", "finished_secs"
+    ];
+}
+
+/// 时间线邮件 - data_mail_info表的投影
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelineMailRow {
+    /// 邮件唯一ID
+    pub id: u64,
+    /// 处置动作（枚举名称）
+    pub action_name: String,
+    /// 邮件检测时间（Unix时间戳，秒）
+    pub timestamp_secs: u32,
+    /// 发件人，优先使用显示发件人
+    pub sender_address: String,
+    /// 全部收件人（小写、去重）
+    pub recipient_list: Vec<String>,
+}
+
+impl Row for TimelineMailRow {
+    const COLUMN_NAMES: &'static [&'static str] = &[
+        "id", "action_name", "timestamp_secs", "sender_address", "recipient_list"
+    ];
+}
+
+/// 时间线邮件命中的情报 - alert_intelligence表的投影
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelineMailHitRow {
+    /// 关联邮件的ID
+    pub mail_id: u64,
+    /// 情报ID（字符串形式）
+    pub intel_id: String,
+}
+
+impl Row for TimelineMailHitRow {
+    const COLUMN_NAMES: &'static [&'static str] = &["mail_id", "intel_id"];
+}
+
+/// 按时间分桶与处置动作的邮件计数结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelineBucketRow {
+    /// 分桶起始时间（Unix时间戳，秒）
+    pub bucket_secs: u32,
+    /// 处置动作（枚举名称）
+    pub action_name: String,
+    /// 邮件数
+    pub count: u64,
+}
+
+impl Row for TimelineBucketRow {
+    const COLUMN_NAMES: &'static [&'static str] = &["bucket_secs", "action_name", "count"];
+}
+
+/// 联防联控快照 - alert_intelligence表按联防联控信息去重的投影
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpreadSnapshotRow {
+    /// 首次出现该快照的检测时间（Unix时间戳，秒）
+    pub timestamp_secs: u32,
+    /// 联防联控信息（JSON）
+    pub joint_text: String,
+}
+
+impl Row for SpreadSnapshotRow {
+    const COLUMN_NAMES: &'static [&'static str] = &["timestamp_secs", "joint_text"];
+}
+
+/// 已执行的迁移 - schema_migrations表的投影
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaMigrationRow {
+    /// 迁移版本号
+    pub version: u32,
+    /// 迁移名称
+    pub name: String,
+    /// 执行时迁移SQL的SHA256
+    pub checksum: String,
+    /// 执行时间（Unix时间戳，秒）
+    pub applied_secs: u32,
+}
+
+impl Row for SchemaMigrationRow {
+    const COLUMN_NAMES: &'static [&'static str] = &["version", "name", "checksum", "applied_secs"];
+}
+
+/// 新执行的迁移 - 写入schema_migrations表，执行时间由表默认值填充
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewSchemaMigrationRow {
+    /// 迁移版本号
+    pub version: u32,
+    /// 迁移名称
+    pub name: String,
+    /// 迁移SQL的SHA256
+    pub checksum: String,
+}
+
+impl Row for NewSchemaMigrationRow {
+    const COLUMN_NAMES: &'static [&'static str] = &["version", "name", "checksum"];
+}
+
+/// 隔离处置请求 - 对应quarantine_action表，写入与查询共用
+///
+/// 每次状态变更写入一行新版本，查询时按version取最新版本
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuarantineActionRow {
+    /// 处置请求ID
+    pub action_id: String,
+    /// 邮件ID
+    pub mail_id: String,
+    /// 处置类型：release、delete或redeliver
+    pub kind: String,
+    /// 处置状态：requested、approved、done、failed或rejected
+    pub state: String,
+    /// 投递收件人，删除时为空
+    pub recipients: Vec<String>,
+    /// 处置原因
+    pub reason: String,
+    /// 申请人
+    pub requested_by: String,
+    /// 申请时间（Unix时间戳，毫秒）
+    pub requested_at: i64,
+    /// 审批人，未审批时为空
+    pub reviewed_by: String,
+    /// 审批时间（Unix时间戳，毫秒），未审批时为0
+    pub reviewed_at: i64,
+    /// 执行完成时间（Unix时间戳，毫秒），未执行时为0
+    pub completed_at: i64,
+    /// 执行失败或驳回的原因
+    pub error: String,
+    /// 版本号，随状态流转递增
+    pub version: u64,
+}
+
+impl Row for QuarantineActionRow {
+    const COLUMN_NAMES: &'static [&'static str] = &[
+        "action_id", "mail_id", "kind", "state", "recipients", "reason", "requested_by", "requested_at",
+        "reviewed_by", "reviewed_at", "completed_at", "error", "version",
+    ];
+}
+
+/// 隔离处置审计记录 - 对应quarantine_audit表，写入与查询共用
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuarantineAuditRow {
+    /// 记录时间（Unix时间戳，毫秒）
+    pub timestamp: i64,
+    /// 处置请求ID
+    pub action_id: String,
+    /// 邮件ID
+    pub mail_id: String,
+    /// 处置类型
+    pub kind: String,
+    /// 事件类型：requested、approved、rejected、executed或failed
+    pub event: String,
+    /// 操作人
+    pub actor: String,
+    /// 详细说明
+    pub detail: String,
+    /// 事件后处置请求的版本号，同一时刻的记录按版本号排序
+    pub version: u64,
+}
+
+impl Row for QuarantineAuditRow {
+    const COLUMN_NAMES: &'static [&'static str] =
+        &["timestamp", "action_id", "mail_id", "kind", "event", "actor", "detail", "version"];
+}
+
+/// 表字段 - system.columns表的投影
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaColumnRow {
+    /// 表名
+    pub table_name: String,
+    /// 字段名
+    pub column_name: String,
+    /// 字段类型
+    pub column_type: String,
+}
+
+impl Row for SchemaColumnRow {
+    const COLUMN_NAMES: &'static [&'static str] = &["table_name", "column_name", "column_type"];
+}
+
 /// 查询计数结果
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CountResult {
@@ -299,4 +1150,117 @@ impl AnalysisResult {
     //     "result_id", "analysis_name", "result_data", 
     //     "created_at", "updated_at", "parameters"
     // ];
-} 
\ No newline at end of file
+} 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::de::IntoDeserializer;
+    use serde::de::value::{Error as ValueError, I8Deserializer, U8Deserializer};
+
+    /// 与migrations中Enum8定义一致的判别值和名称
+    fn assert_stored<T>(expected: &[(T, i8, &str)], all: &[T], code: fn(T) -> i8, name: fn(T) -> &'static str)
+    where
+        T: Copy + fmt::Debug + PartialEq + Serialize + for<'de> Deserialize<'de>
+            + TryFrom<i8, Error = String> + FromStr<Err = String>,
+    {
+        assert_eq!(all, expected.iter().map(|(value, _, _)| *value).collect::<Vec<_>>().as_slice());
+        for &(value, stored, stored_name) in expected {
+            assert_eq!(code(value), stored, "{:?}", value);
+            assert_eq!(name(value), stored_name, "{:?}", value);
+            assert_eq!(T::try_from(stored), Ok(value));
+            assert_eq!(stored_name.parse::<T>(), Ok(value));
+
+            // Enum8列按Int8读取，UInt8列按无符号整数读取
+            let deserializer: I8Deserializer<ValueError> = stored.into_deserializer();
+            assert_eq!(T::deserialize(deserializer), Ok(value));
+            let deserializer: U8Deserializer<ValueError> = (stored as u8).into_deserializer();
+            assert_eq!(T::deserialize(deserializer), Ok(value));
+
+            let json = serde_json::to_string(&value).unwrap();
+            assert_eq!(json, stored.to_string());
+            assert_eq!(serde_json::from_str::<T>(&json).unwrap(), value);
+        }
+        assert!(T::try_from(0).is_err());
+        assert!(T::try_from(expected.len() as i8 + 1).is_err());
+        assert!("Unknown".parse::<T>().is_err());
+        assert!(serde_json::from_str::<T>("300").is_err());
+    }
+
+    #[test]
+    fn attribute_type_round_trips() {
+        assert_stored(
+            &[
+                (AttributeType::Domain, 1, "Domain"),
+                (AttributeType::Url, 2, "Url"),
+                (AttributeType::EmailAddress, 3, "EmailAddress"),
+                (AttributeType::Ipv4, 4, "Ipv4"),
+                (AttributeType::Md5, 5, "Md5"),
+                (AttributeType::UrlDomain, 6, "UrlDomain"),
+                (AttributeType::EmailDomain, 7, "EmailDomain"),
+                (AttributeType::Sha256, 8, "Sha256"),
+            ],
+            AttributeType::ALL,
+            AttributeType::code,
+            AttributeType::as_str,
+        );
+    }
+
+    #[test]
+    fn urgency_level_round_trips() {
+        assert_stored(
+            &[
+                (UrgencyLevel::High, 1, "High"),
+                (UrgencyLevel::Medium, 2, "Medium"),
+                (UrgencyLevel::Low, 3, "Low"),
+            ],
+            UrgencyLevel::ALL,
+            UrgencyLevel::code,
+            UrgencyLevel::as_str,
+        );
+    }
+
+    #[test]
+    fn source_type_round_trips() {
+        assert_stored(
+            &[(SourceType::Local, 1, "Local"), (SourceType::Cloud, 2, "Cloud")],
+            SourceType::ALL,
+            SourceType::code,
+            SourceType::as_str,
+        );
+    }
+
+    #[test]
+    fn parent_source_type_round_trips() {
+        assert_stored(
+            &[
+                (ParentSourceType::Email, 1, "Email"),
+                (ParentSourceType::File, 2, "File"),
+                (ParentSourceType::EmailHeader, 3, "EmailHeader"),
+                (ParentSourceType::EmailBody, 4, "EmailBody"),
+                (ParentSourceType::QrCode, 5, "QrCode"),
+                (ParentSourceType::Text, 6, "Text"),
+                (ParentSourceType::Url, 7, "Url"),
+                (ParentSourceType::Smtp, 8, "Smtp"),
+            ],
+            ParentSourceType::ALL,
+            ParentSourceType::code,
+            ParentSourceType::as_str,
+        );
+    }
+
+    #[test]
+    fn action_type_round_trips() {
+        assert_stored(
+            &[
+                (ActionType::Accept, 1, "Accept"),
+                (ActionType::Discard, 2, "Discard"),
+                (ActionType::Reject, 3, "Reject"),
+                (ActionType::Quarantine, 4, "Quarantine"),
+            ],
+            ActionType::ALL,
+            ActionType::code,
+            ActionType::as_str,
+        );
+    }
+}
@@ -0,0 +1,525 @@
+//! 参数化查询构建
+//!
+//! 服务层的查询都通过这里拼装：表名、字段和表达式由代码给出，
+//! 用户输入（关键字、ID、时间等）一律作为参数以`?`占位，由clickhouse客户端转义后绑定。
+//! 字段、表达式等结构性片段不能来自用户输入，排序字段需要通过白名单映射
+
+use anyhow::anyhow;
+use chrono::{DateTime, Utc};
+
+use crate::db::DbResult;
+
+/// 查询参数
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Param {
+    /// 字符串
+    Text(String),
+    /// 无符号整数
+    UInt(u64),
+    /// 有符号整数
+    Int(i64),
+}
+
+impl From<String> for Param {
+    fn from(value: String) -> Self {
+        Self::Text(value)
+    }
+}
+
+impl From<&String> for Param {
+    fn from(value: &String) -> Self {
+        Self::Text(value.clone())
+    }
+}
+
+impl From<&str> for Param {
+    fn from(value: &str) -> Self {
+        Self::Text(value.to_string())
+    }
+}
+
+impl From<u64> for Param {
+    fn from(value: u64) -> Self {
+        Self::UInt(value)
+    }
+}
+
+impl From<&u64> for Param {
+    fn from(value: &u64) -> Self {
+        Self::UInt(*value)
+    }
+}
+
+impl From<u32> for Param {
+    fn from(value: u32) -> Self {
+        Self::UInt(u64::from(value))
+    }
+}
+
+impl From<i64> for Param {
+    fn from(value: i64) -> Self {
+        Self::Int(value)
+    }
+}
+
+/// SQL片段及其参数，参数按`?`出现的顺序排列
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Query {
+    sql: String,
+    params: Vec<Param>,
+}
+
+impl Query {
+    /// 不带参数的固定SQL，不能包含用户输入
+    pub fn new(sql: impl Into<String>) -> Self {
+        Self::with_params(sql, Vec::<Param>::new())
+    }
+
+    /// 带占位符的SQL，参数个数须与`?`个数一致，执行前由`validate`检查
+    pub fn with_params<P: Into<Param>>(sql: impl Into<String>, params: impl IntoIterator<Item = P>) -> Self {
+        let sql = sql.into();
+        let params: Vec<Param> = params.into_iter().map(Into::into).collect();
+        Self { sql, params }
+    }
+
+    /// 检查占位符与参数个数是否一致，不一致时参数会错位绑定，拒绝执行
+    pub fn validate(&self) -> DbResult<()> {
+        let placeholders = self.sql.matches('?').count();
+        if placeholders != self.params.len() {
+            return Err(anyhow!(
+                "占位符与参数个数不一致: 占位符{}个，参数{}个: {}",
+                placeholders,
+                self.params.len(),
+                self.sql
+            ));
+        }
+        Ok(())
+    }
+
+    /// SQL文本
+    pub fn sql(&self) -> &str {
+        &self.sql
+    }
+
+    /// 按顺序排列的参数
+    pub fn params(&self) -> &[Param] {
+        &self.params
+    }
+
+    /// 将片段嵌入固定SQL中，如`countIf(id IN (` + 子查询 + `)) AS hits`
+    pub fn wrap(prefix: &str, inner: &Query, suffix: &str) -> Self {
+        let mut query = Self::new(prefix);
+        query.push(inner);
+        query.sql.push_str(suffix);
+        query
+    }
+
+    /// 追加另一个片段
+    fn push(&mut self, other: &Query) {
+        self.sql.push_str(&other.sql);
+        self.params.extend(other.params.iter().cloned());
+    }
+}
+
+/// LIKE匹配方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LikeMatch {
+    /// 包含
+    Contains,
+    /// 前缀
+    Prefix,
+    /// 后缀
+    Suffix,
+}
+
+/// 转义LIKE中的通配符，使关键字按字面匹配
+pub fn escape_like(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '%' | '_') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// 一组查询条件
+///
+/// `all`以AND连接、`any`以OR连接，嵌套的条件组加括号。没有条件时AND组恒为真、OR组恒为假
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conditions {
+    any: bool,
+    clauses: Vec<Query>,
+}
+
+impl Default for Conditions {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+impl Conditions {
+    /// 全部满足
+    pub fn all() -> Self {
+        Self { any: false, clauses: Vec::new() }
+    }
+
+    /// 任一满足
+    pub fn any() -> Self {
+        Self { any: true, clauses: Vec::new() }
+    }
+
+    /// 是否没有条件
+    pub fn is_empty(&self) -> bool {
+        self.clauses.is_empty()
+    }
+
+    /// 固定条件，不能包含用户输入
+    pub fn raw(self, sql: impl Into<String>) -> Self {
+        self.push(Query::new(sql))
+    }
+
+    /// 带占位符的条件，参数个数须与`?`个数一致
+    ///
+    /// 条件须自成一体，包含OR时自行加括号或使用`any`条件组
+    pub fn push(mut self, clause: Query) -> Self {
+        self.clauses.push(clause);
+        self
+    }
+
+    /// 表达式等于参数
+    pub fn eq(self, expr: &str, value: impl Into<Param>) -> Self {
+        self.push(Query::with_params(format!("{} = ?", expr), [value]))
+    }
+
+    /// 表达式在参数列表中，列表为空时条件不成立
+    pub fn in_list<P: Into<Param>>(self, expr: &str, values: impl IntoIterator<Item = P>) -> Self {
+        let params: Vec<Param> = values.into_iter().map(Into::into).collect();
+        if params.is_empty() {
+            return self.raw("0");
+        }
+        let placeholders = vec!["?"; params.len()].join(", ");
+        self.push(Query::with_params(format!("{} IN ({})", expr, placeholders), params))
+    }
+
+    /// 表达式在子查询结果中
+    pub fn in_subquery(self, expr: &str, subquery: Query) -> Self {
+        self.push(Query::wrap(&format!("{} IN (", expr), &subquery, ")"))
+    }
+
+    /// 表达式不在子查询结果中
+    pub fn not_in_subquery(self, expr: &str, subquery: Query) -> Self {
+        self.push(Query::wrap(&format!("{} NOT IN (", expr), &subquery, ")"))
+    }
+
+    /// 按LIKE匹配关键字，通配符会被转义；`case_insensitive`时使用ILIKE
+    pub fn like(self, expr: &str, keyword: &str, mode: LikeMatch, case_insensitive: bool) -> Self {
+        let escaped = escape_like(keyword);
+        let pattern = match mode {
+            LikeMatch::Contains => format!("%{}%", escaped),
+            LikeMatch::Prefix => format!("{}%", escaped),
+            LikeMatch::Suffix => format!("%{}", escaped),
+        };
+        let operator = if case_insensitive { "ILIKE" } else { "LIKE" };
+        self.push(Query::with_params(format!("{} {} ?", expr, operator), [pattern]))
+    }
+
+    /// 时间范围（闭区间），未指定的一端不限制
+    pub fn time_range(self, column: &str, start: Option<DateTime<Utc>>, end: Option<DateTime<Utc>>) -> Self {
+        let mut conditions = self;
+        if let Some(start) = start {
+            conditions = conditions.push(Query::with_params(format!("{} >= toDateTime(?)", column), [start.timestamp()]));
+        }
+        if let Some(end) = end {
+            conditions = conditions.push(Query::with_params(format!("{} <= toDateTime(?)", column), [end.timestamp()]));
+        }
+        conditions
+    }
+
+    /// 嵌套条件组，空组按其恒定值处理
+    pub fn group(self, group: Conditions) -> Self {
+        self.push(group.build())
+    }
+
+    /// 合并另一组条件的各项（按本组的连接方式）
+    pub fn extend(mut self, other: Conditions) -> Self {
+        self.clauses.extend(other.clauses);
+        self
+    }
+
+    /// 生成条件表达式
+    pub fn build(&self) -> Query {
+        match self.clauses.len() {
+            0 => Query::new(if self.any { "0" } else { "1" }),
+            1 => self.clauses[0].clone(),
+            _ => {
+                let separator = Query::new(if self.any { " OR " } else { " AND " });
+                let mut query = Query::new("(");
+                for (index, clause) in self.clauses.iter().enumerate() {
+                    if index > 0 {
+                        query.push(&separator);
+                    }
+                    query.push(clause);
+                }
+                query.sql.push(')');
+                query
+            }
+        }
+    }
+}
+
+/// 排序方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    /// 升序
+    Asc,
+    /// 降序
+    Desc,
+}
+
+impl Order {
+    fn sql(self) -> &'static str {
+        match self {
+            Self::Asc => "ASC",
+            Self::Desc => "DESC",
+        }
+    }
+}
+
+/// SELECT查询
+#[derive(Debug, Clone)]
+pub struct SelectQuery {
+    distinct: bool,
+    columns: Vec<Query>,
+    from: Query,
+    filter: Conditions,
+    group_by: Vec<String>,
+    having: Conditions,
+    order_by: Vec<String>,
+    limit: Option<u64>,
+    offset: Option<u64>,
+}
+
+impl SelectQuery {
+    /// 从表查询
+    pub fn from(table: &str) -> Self {
+        Self::from_query(Query::new(table))
+    }
+
+    /// 从子查询查询
+    pub fn from_subquery(subquery: Query) -> Self {
+        Self::from_query(Query::wrap("(", &subquery, ")"))
+    }
+
+    fn from_query(from: Query) -> Self {
+        Self {
+            distinct: false,
+            columns: Vec::new(),
+            from,
+            filter: Conditions::all(),
+            group_by: Vec::new(),
+            having: Conditions::all(),
+            order_by: Vec::new(),
+            limit: None,
+            offset: None,
+        }
+    }
+
+    /// 去重
+    pub fn distinct(mut self) -> Self {
+        self.distinct = true;
+        self
+    }
+
+    /// 查询的字段或表达式
+    pub fn column(self, expr: impl Into<String>) -> Self {
+        self.column_query(Query::new(expr))
+    }
+
+    /// 多个字段或表达式
+    pub fn columns<S: Into<String>>(mut self, exprs: impl IntoIterator<Item = S>) -> Self {
+        self.columns.extend(exprs.into_iter().map(|expr| Query::new(expr)));
+        self
+    }
+
+    /// 带参数的表达式，如`countIf(id IN (子查询)) AS hits`
+    pub fn column_query(mut self, expr: Query) -> Self {
+        self.columns.push(expr);
+        self
+    }
+
+    /// WHERE条件，多次调用以AND合并
+    pub fn filter(mut self, conditions: Conditions) -> Self {
+        self.filter = self.filter.group(conditions);
+        self
+    }
+
+    /// GROUP BY表达式
+    pub fn group_by(mut self, expr: impl Into<String>) -> Self {
+        self.group_by.push(expr.into());
+        self
+    }
+
+    /// HAVING条件，多次调用以AND合并
+    pub fn having(mut self, conditions: Conditions) -> Self {
+        self.having = self.having.group(conditions);
+        self
+    }
+
+    /// 按固定表达式排序
+    pub fn order_by(mut self, expr: &str, order: Order) -> Self {
+        self.order_by.push(format!("{} {}", expr, order.sql()));
+        self
+    }
+
+    /// 按外部传入的字段名排序，字段名须在白名单（字段名、排序表达式）中
+    pub fn order_by_field(self, field: &str, allowed: &[(&str, &str)], order: Order) -> DbResult<Self> {
+        let (_, expr) = allowed
+            .iter()
+            .find(|(name, _)| *name == field)
+            .ok_or_else(|| anyhow!("不支持的排序字段: {}", field))?;
+        Ok(self.order_by(expr, order))
+    }
+
+    /// 最多返回的行数
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// 跳过的行数
+    pub fn offset(mut self, offset: u64) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// 生成查询
+    pub fn build(&self) -> Query {
+        let mut query = Query::new(if self.distinct { "SELECT DISTINCT " } else { "SELECT " });
+        for (index, column) in self.columns.iter().enumerate() {
+            if index > 0 {
+                query.sql.push_str(", ");
+            }
+            query.push(column);
+        }
+        query.sql.push_str(" FROM ");
+        query.push(&self.from);
+        if !self.filter.is_empty() {
+            query.sql.push_str(" WHERE ");
+            query.push(&self.filter.build());
+        }
+        if !self.group_by.is_empty() {
+            query.sql.push_str(&format!(" GROUP BY {}", self.group_by.join(", ")));
+        }
+        if !self.having.is_empty() {
+            query.sql.push_str(" HAVING ");
+            query.push(&self.having.build());
+        }
+        if !self.order_by.is_empty() {
+            query.sql.push_str(&format!(" ORDER BY {}", self.order_by.join(", ")));
+        }
+        // 行数为数值，直接拼接
+        if let Some(limit) = self.limit {
+            query.sql.push_str(&format!(" LIMIT {}", limit));
+        }
+        if let Some(offset) = self.offset {
+            query.sql.push_str(&format!(" OFFSET {}", offset));
+        }
+        query
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_like_escapes_wildcards_and_backslash() {
+        assert_eq!(escape_like("100%"), "100\\%");
+        assert_eq!(escape_like("a_b"), "a\\_b");
+        assert_eq!(escape_like("C:\\tmp"), "C:\\\\tmp");
+        assert_eq!(escape_like("%_\\"), "\\%\\_\\\\");
+        assert_eq!(escape_like("普通关键字"), "普通关键字");
+    }
+
+    #[test]
+    fn like_binds_escaped_pattern() {
+        let query = Conditions::all().like("subject", "50%_off", LikeMatch::Contains, true).build();
+        assert_eq!(query.sql(), "subject ILIKE ?");
+        assert_eq!(query.params(), &[Param::Text("%50\\%\\_off%".to_string())]);
+
+        let prefix = Conditions::all().like("subject", "a", LikeMatch::Prefix, false).build();
+        assert_eq!(prefix.sql(), "subject LIKE ?");
+        assert_eq!(prefix.params(), &[Param::Text("a%".to_string())]);
+
+        let suffix = Conditions::all().like("subject", "a", LikeMatch::Suffix, false).build();
+        assert_eq!(suffix.params(), &[Param::Text("%a".to_string())]);
+    }
+
+    #[test]
+    fn validate_rejects_placeholder_mismatch() {
+        assert!(Query::with_params("id = ? AND name = ?", [1u64]).validate().is_err());
+        assert!(Query::with_params("id = ?", [1u64, 2u64]).validate().is_err());
+        assert!(Query::new("name = '?'").validate().is_err());
+        assert!(Query::with_params("id = ? AND name = ?", [Param::UInt(1), Param::from("x")]).validate().is_ok());
+    }
+
+    #[test]
+    fn built_queries_keep_params_in_placeholder_order() {
+        let subquery = SelectQuery::from("alert_intelligence")
+            .column("mail_id")
+            .filter(Conditions::all().eq("intelligence_id", "i-1"))
+            .build();
+        let query = SelectQuery::from("data_mail_info")
+            .column("id")
+            .filter(
+                Conditions::all()
+                    .in_subquery("id", subquery)
+                    .in_list("action", ["Accept", "Reject"])
+                    .group(Conditions::any().eq("subject", "?").eq("id", 7u64)),
+            )
+            .limit(10)
+            .build();
+
+        assert_eq!(
+            query.sql(),
+            "SELECT id FROM data_mail_info WHERE (id IN (SELECT mail_id FROM alert_intelligence WHERE intelligence_id = ?) \
+             AND action IN (?, ?) AND (subject = ? OR id = ?)) LIMIT 10"
+        );
+        assert_eq!(
+            query.params(),
+            &[
+                Param::from("i-1"),
+                Param::from("Accept"),
+                Param::from("Reject"),
+                Param::from("?"),
+                Param::UInt(7),
+            ]
+        );
+        assert!(query.validate().is_ok());
+    }
+
+    #[test]
+    fn empty_condition_groups() {
+        assert_eq!(Conditions::all().build().sql(), "1");
+        assert_eq!(Conditions::any().build().sql(), "0");
+        assert_eq!(Conditions::all().in_list("id", Vec::<u64>::new()).build().sql(), "0");
+    }
+
+    #[test]
+    fn order_by_field_rejects_unlisted_fields() {
+        const ALLOWED: &[(&str, &str)] = &[("hit_emails", "uniqExact(mail_id)")];
+
+        let query = SelectQuery::from("alert_intelligence")
+            .column("intelligence_id")
+            .order_by_field("hit_emails", ALLOWED, Order::Desc)
+            .unwrap()
+            .build();
+        assert_eq!(query.sql(), "SELECT intelligence_id FROM alert_intelligence ORDER BY uniqExact(mail_id) DESC");
+
+        for field in ["timestamp; DROP TABLE data_mail_info", "uniqExact(mail_id)", "HIT_EMAILS", ""] {
+            assert!(SelectQuery::from("t").order_by_field(field, ALLOWED, Order::Asc).is_err(), "{}", field);
+        }
+    }
+}
@@ -1,54 +0,0 @@
-// 注意：此文件已被废弃，因为当前的路由(/hello和/alerts)不需要这些数据库访问功能
-// 可以根据需要在将来恢复相关功能
-
-// use std::sync::Arc;
-// use async_trait::async_trait;
-// use chrono::{DateTime, Utc};
-// use uuid::Uuid;
-// use tracing::{info, instrument};
-
-// use crate::db::{ClickHouseClient, DbResult, UserEvent, AnalysisResult, CountResult};
-// use crate::sql::{event_queries, user_queries};
-
-// /// 用户事件存储库特性
-// #[async_trait]
-// pub trait UserEventRepository: Send + Sync + 'static {
-//     /// 创建用户事件表
-//     async fn create_table_if_not_exists(&self) -> DbResult<()>;
-    
-//     /// 记录新的用户事件
-//     async fn record_event(&self, event: UserEvent) -> DbResult<()>;
-    
-//     /// 按用户ID查询事件
-//     async fn find_by_user_id(&self, user_id: u64) -> DbResult<Vec<UserEvent>>;
-    
-//     /// 按事件类型查询事件
-//     async fn find_by_event_type(&self, event_type: &str) -> DbResult<Vec<UserEvent>>;
-    
-//     /// 查询时间范围内的事件
-//     async fn find_by_time_range(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> DbResult<Vec<UserEvent>>;
-    
-//     /// 计算用户事件数量
-//     async fn count_events(&self, user_id: Option<u64>, event_type: Option<&str>) -> DbResult<u64>;
-// }
-
-// /// 分析结果存储库特性
-// #[async_trait]
-// pub trait AnalysisResultRepository: Send + Sync + 'static {
-//     /// 创建分析结果表
-//     async fn create_table_if_not_exists(&self) -> DbResult<()>;
-    
-//     /// 保存分析结果
-//     async fn save_result(&self, result: AnalysisResult) -> DbResult<()>;
-    
-//     /// 按ID查询结果
-//     async fn find_by_id(&self, result_id: Uuid) -> DbResult<Option<AnalysisResult>>;
-    
-//     /// 按分析名称查询结果
-//     async fn find_by_analysis_name(&self, name: &str) -> DbResult<Vec<AnalysisResult>>;
-    
-//     /// 查询最新的分析结果
-//     async fn find_latest(&self, limit: u32) -> DbResult<Vec<AnalysisResult>>;
-// }
-
-// ... 以下代码省略，所有实现代码均被注释 ... 
\ No newline at end of file
@@ -0,0 +1,53 @@
+//! 本地目录中的文件存储
+
+use std::io::ErrorKind;
+use std::path::{Component, Path, PathBuf};
+use anyhow::bail;
+use async_trait::async_trait;
+
+use super::{Blob, BlobStore};
+use crate::db::DbResult;
+
+/// 以本地目录为根的文件存储，入库记录中的路径均相对于根目录
+#[derive(Debug, Clone)]
+pub struct FileBlobStore {
+    /// 存储根目录
+    root: PathBuf,
+}
+
+impl FileBlobStore {
+    /// 创建以`root`为根目录的文件存储
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// 存储路径对应的本地路径，路径以`/`开头时同样视为相对于根目录，包含`..`等成分时返回错误
+    fn resolve(&self, path: &str) -> DbResult<PathBuf> {
+        let relative = Path::new(path.trim_start_matches('/'));
+        if relative.as_os_str().is_empty()
+            || relative.components().any(|component| !matches!(component, Component::Normal(_)))
+        {
+            bail!("无效的存储路径: {}", path);
+        }
+        Ok(self.root.join(relative))
+    }
+}
+
+#[async_trait]
+impl BlobStore for FileBlobStore {
+    async fn open(&self, path: &str) -> DbResult<Option<Blob>> {
+        let file = match tokio::fs::File::open(self.resolve(path)?).await {
+            Ok(file) => file,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let metadata = file.metadata().await?;
+        if !metadata.is_file() {
+            return Ok(None);
+        }
+        Ok(Some(Blob {
+            size: metadata.len(),
+            reader: Box::new(file),
+        }))
+    }
+}
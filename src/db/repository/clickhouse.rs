@@ -0,0 +1,1108 @@
+//! ClickHouse存储库
+
+use std::sync::Arc;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use ::clickhouse::Row;
+use tracing::info;
+use uuid::Uuid;
+
+use super::{
+    AttachmentRepository, DispositionRepository, HashMails, IntelligenceHitRepository, MailInfoRepository, QuarantineRepository,
+    RecipientProfileData, SenderProfileData, StatisticsRepository, TimelineHits, intelligence_attributes, search,
+};
+use crate::db::{
+    CampaignHitRow, CampaignMailRow, ClickHouseClient, Conditions, CountResult, CountSpanRow, CustomIntelligenceRow, DbResult,
+    HashIntelligenceRow, HashMailRow, HitSummaryRow, HitTrendRow, LikeMatch, MailAttachmentRow, MailBodyRow, MailDeliveryRow,
+    MailExtractPasswordRow, MailFileRow, MailIntelligenceValueRow, MailSearchRow, NamedCountRow, Order, Query,
+    QuarantineActionRow, QuarantineAuditRow,
+    RecipientRankRow, SelectQuery, SenderSummaryRow, SendingSourceRow, SpreadSnapshotRow, ThreadMailRow, ThreatActorRow,
+    TimelineBucketRow, TimelineDispositionRow, TimelineIntelRow, TimelineMailHitRow, TimelineMailRow,
+    TimelineRetroHuntRow, TimelineUpdateRow, TrendCountRow,
+};
+use crate::models::domain::email::EmailSearchCriteria;
+use crate::models::domain::hash::{HashKind, HashPivotFilter};
+use crate::models::domain::intelligence::{
+    Intelligence, IntelligenceFilter, SortField, SortOrder, SourceType, StatusKey,
+};
+use crate::models::domain::recipient::{RecipientProfileFilter, RecipientRankFilter};
+use crate::models::domain::sender::{SenderKind, SenderProfileFilter};
+use crate::models::domain::spread::SpreadFilter;
+use crate::models::domain::statistics::TrendInterval;
+use crate::models::domain::timeline::TimelineFilter;
+
+/// 邮件的全部收件人：显示收件人与信封收件人（可能以逗号或分号分隔多个），去重并转为小写
+const RECIPIENTS_EXPR: &str = "arrayDistinct(arrayFilter(x -> x != '', arrayConcat(\
+     [trimBoth(lowerUTF8(display_to_address))], \
+     arrayMap(x -> trimBoth(x), splitByRegexp('[,;]', lowerUTF8(client_envelope_to_address))))))";
+
+/// 附件记录的查询字段，与`MailAttachmentRow`一致
+const ATTACHMENT_COLUMNS: [&str; 8] = [
+    "mail_id", "attachment_id", "filename", "file_path", "size", "hash_md5", "hash_sha1", "hash_sha256",
+];
+
+/// 攻击组织名称：threat_actor为JSON时取name字段，否则视为名称本身
+const THREAT_ACTOR_EXPR: &str =
+    "if(isValidJSON(threat_actor), JSONExtractString(threat_actor, 'name'), trimBoth(threat_actor))";
+
+/// 攻击组织类型：threat_actor为JSON时取type字段，否则为空
+const THREAT_ACTOR_KIND_EXPR: &str = "if(isValidJSON(threat_actor), JSONExtractString(threat_actor, 'type'), '')";
+
+/// 情报命中的看板计数：命中邮件、受影响的收件人与收件单位、命中情报
+const HIT_COUNT_COLUMNS: [&str; 4] = [
+    "uniqExact(mail_id) AS hit_mails",
+    "uniqExactIf(lowerUTF8(trimBoth(display_to_address)), trimBoth(display_to_address) != '') AS recipients",
+    "uniqExactIf(lowerUTF8(trimBoth(display_to_domain)), trimBoth(display_to_domain) != '') AS units",
+    "uniqExact(intelligence_id) AS intelligence",
+];
+
+/// 邮件的发件人地址：信封发件人与显示发件人中的邮箱地址，转为小写
+const SENDER_ADDRESSES_EXPR: &str = "arrayFilter(x -> x != '', [lowerUTF8(client_envelope_from_address), \
+     lowerUTF8(extract(display_from, '[^<>[:space:]]+@[^<>[:space:]]+'))])";
+
+/// 命中数与首末时间，对应`CountSpanRow`
+const SPAN_COLUMNS: [&str; 3] = [
+    "count() AS count",
+    "toUInt32(toUnixTimestamp(min(timestamp))) AS first_secs",
+    "toUInt32(toUnixTimestamp(max(timestamp))) AS last_secs",
+];
+
+/// 每类生命周期事件最多读取的记录数
+const MAX_LIFECYCLE_EVENTS: u64 = 500;
+/// 单个情报最多读取的联防联控快照数
+const MAX_SPREAD_SNAPSHOTS: u64 = 10000;
+/// 哈希关联最多返回的受影响收件人数
+const MAX_PIVOT_RECIPIENTS: u64 = 1000;
+/// 会话候选邮件与起始邮件的最大时间间隔（天）
+const THREAD_WINDOW_DAYS: i64 = 90;
+/// 最多参与会话重建的候选邮件数
+const MAX_THREAD_MAILS: u64 = 200;
+
+/// 情报列表允许的排序字段及对应的聚合表达式
+const SORT_FIELDS: &[(&str, &str)] = &[
+    ("latest_hits_time", "max(timestamp)"),
+    ("hit_emails", "uniqExact(mail_id)"),
+    ("impact_users", "uniqExact(display_to_address)"),
+];
+
+/// 时间范围条件
+fn window(start_time: DateTime<Utc>, end_time: DateTime<Utc>) -> Conditions {
+    Conditions::all().time_range("timestamp", Some(start_time), Some(end_time))
+}
+
+/// 时间范围内命中情报的邮件ID子查询
+fn hit_mails(start_time: DateTime<Utc>, end_time: DateTime<Utc>) -> Query {
+    SelectQuery::from("alert_intelligence")
+        .column("mail_id")
+        .filter(Conditions::all().raw("is_deleted = 0").extend(window(start_time, end_time)))
+        .build()
+}
+
+/// 趋势分桶表达式，与`TrendInterval::bucket_start`保持一致，按UTC对齐
+fn bucket_sql(interval: TrendInterval) -> String {
+    format!(
+        "toUInt32(intDiv(toUnixTimestamp(timestamp) + {offset}, {secs}) * {secs} - {offset})",
+        offset = interval.offset(),
+        secs = interval.seconds(),
+    )
+}
+
+/// 查询ClickHouse的存储库
+pub struct ClickHouseRepository {
+    /// ClickHouse客户端
+    client: Arc<ClickHouseClient>,
+}
+
+impl ClickHouseRepository {
+    /// 创建ClickHouse存储库
+    pub fn new(client: Arc<ClickHouseClient>) -> Self {
+        Self { client }
+    }
+
+    /// 取单行结果
+    async fn fetch_one<R>(&self, query: &Query) -> DbResult<Option<R>>
+    where
+        R: clickhouse::Row + serde::de::DeserializeOwned + Send + 'static,
+    {
+        Ok(self.client.fetch::<R>(query).await?.into_iter().next())
+    }
+
+    /// 由情报列表的过滤条件组装WHERE条件，过滤值和类型值均作为参数绑定
+    fn intelligence_conditions(filter: &IntelligenceFilter) -> Conditions {
+        let mut conditions = Conditions::all()
+            .raw("is_deleted = 0")
+            .time_range("timestamp", Some(filter.start_time), Some(filter.end_time));
+        if let Some(source) = &filter.source {
+            let source = match source {
+                SourceType::Local => "Local",
+                SourceType::Cloud => "Cloud",
+            };
+            conditions = conditions.eq("toString(source)", source);
+        }
+        if let Some(keyword) = filter.filter.as_deref().map(str::trim).filter(|keyword| !keyword.is_empty()) {
+            conditions = conditions.like("value", keyword, LikeMatch::Contains, true);
+        }
+        // 多个情报类型之间为“或”，类型下列出的子类型为空时不限子类型
+        if let Some(types) = filter.intelligence_type.as_ref().filter(|types| !types.is_empty()) {
+            let mut any_type = Conditions::any();
+            for (intelligence_type, sub_types) in types {
+                let mut type_conditions = Conditions::all()
+                    .in_list("toString(attribute)", intelligence_attributes(intelligence_type));
+                if !sub_types.is_empty() {
+                    type_conditions = type_conditions.in_list("intelligence_type", sub_types);
+                }
+                any_type = any_type.group(type_conditions);
+            }
+            conditions = conditions.group(any_type);
+        }
+        // 处置状态以每种处置的最新记录为准
+        for (key, enabled) in &filter.status {
+            let disposition = match key {
+                StatusKey::IsWhite => "white",
+                StatusKey::IsBlack => "black",
+                StatusKey::IsReport => "report",
+            };
+            let disposed = SelectQuery::from("intelligence_disposition_log")
+                .column("intelligence_id")
+                .filter(Conditions::all().eq("toString(disposition)", disposition))
+                .group_by("intelligence_id")
+                .having(Conditions::all().raw("argMax(enabled, created_at) = 1"))
+                .build();
+            conditions = if *enabled {
+                conditions.in_subquery("intelligence_id", disposed)
+            } else {
+                conditions.not_in_subquery("intelligence_id", disposed)
+            };
+        }
+        conditions
+    }
+
+    /// 当前页的情报ID查询，排序字段经白名单映射
+    fn intelligence_page(filter: &IntelligenceFilter, conditions: Conditions) -> DbResult<SelectQuery> {
+        let field = match filter.sort_by {
+            SortField::LatestHitsTime => "latest_hits_time",
+            SortField::HitEmails => "hit_emails",
+            SortField::ImpactUsers => "impact_users",
+        };
+        let order = match filter.sort_order {
+            SortOrder::Asc => Order::Asc,
+            SortOrder::Desc => Order::Desc,
+        };
+        let page_size = filter.page_size as u64;
+        Ok(SelectQuery::from("alert_intelligence")
+            .column("intelligence_id")
+            .filter(conditions)
+            .group_by("intelligence_id")
+            .order_by_field(field, SORT_FIELDS, order)?
+            .order_by("intelligence_id", Order::Asc)
+            .limit(page_size)
+            .offset(filter.page.saturating_sub(1) as u64 * page_size))
+    }
+
+    /// 满足条件的未删除情报值（去重）
+    fn intelligence_values(conditions: Conditions) -> Query {
+        SelectQuery::from("alert_intelligence")
+            .distinct()
+            .columns([
+                "toString(intelligence_id) AS intelligence_id",
+                "toString(attribute) AS attribute",
+                "value",
+            ])
+            .filter(Conditions::all().raw("is_deleted = 0").extend(conditions))
+            .build()
+    }
+
+    /// 会话邮件的投影字段，`hit_mails`限定判断命中时读取的命中记录
+    fn thread_mails(hit_mails: Conditions) -> SelectQuery {
+        let hit_mails = SelectQuery::from("alert_intelligence")
+            .column("mail_id")
+            .filter(Conditions::all().raw("is_deleted = 0").extend(hit_mails))
+            .build();
+        SelectQuery::from("data_mail_info")
+            .columns([
+                "id",
+                "toString(action) AS action_name",
+                "toUInt32(toUnixTimestamp(timestamp)) AS timestamp_secs",
+                "subject",
+                "display_from",
+            ])
+            .column_query(Query::wrap("toUInt8(id IN (", &hit_mails, ")) AS hit"))
+    }
+
+    /// 邮件文件哈希所在的列
+    fn hash_column(kind: HashKind) -> &'static str {
+        match kind {
+            HashKind::Md5 => "hash_md5",
+            HashKind::Sha1 => "hash_sha1",
+            HashKind::Sha256 => "hash_sha256",
+        }
+    }
+
+    /// 命中该哈希的情报记录条件
+    fn hash_hits(attribute: &str, hash: &str) -> Conditions {
+        Conditions::all()
+            .raw("is_deleted = 0")
+            .eq("toString(attribute)", attribute)
+            .eq("lowerUTF8(value)", hash)
+    }
+
+    /// 发件人匹配条件，发件人以参数形式绑定
+    fn sender_condition(kind: SenderKind, sender: &str) -> Query {
+        let expr = match kind {
+            SenderKind::Address => format!("has({}, ?)", SENDER_ADDRESSES_EXPR),
+            SenderKind::Domain => format!(
+                "has(arrayMap(x -> arrayElement(splitByChar('@', x), -1), {}), ?)",
+                SENDER_ADDRESSES_EXPR
+            ),
+        };
+        Query::with_params(expr, [sender])
+    }
+}
+
+#[async_trait]
+impl IntelligenceHitRepository for ClickHouseRepository {
+    /// 目前只统计总数，情报详情的组装暂未实现
+    async fn list_intelligence(&self, filter: &IntelligenceFilter) -> DbResult<(u64, Vec<Intelligence>)> {
+        let conditions = Self::intelligence_conditions(filter);
+        let total_query = SelectQuery::from("alert_intelligence")
+            .column("uniqExact(intelligence_id) AS count")
+            .filter(conditions.clone())
+            .build();
+        let total = self
+            .fetch_one::<CountResult>(&total_query)
+            .await?
+            .map_or(0, |row| row.count);
+
+        let _page_query = Self::intelligence_page(filter, conditions)?;
+        info!(
+            "数据库查询框架已准备，但情报详情暂未实现。查询范围: {:?} 到 {:?}, 共{}条",
+            filter.start_time, filter.end_time, total
+        );
+
+        Ok((total, vec![]))
+    }
+
+    async fn timeline(&self, filter: &TimelineFilter, max_intelligence: usize) -> DbResult<TimelineHits> {
+        let client = &self.client;
+        let mut ids: Vec<String> = filter.intelligence_ids.iter().map(Uuid::to_string).collect();
+        if let Some(actor) = &filter.threat_actor {
+            let actor_query = SelectQuery::from("alert_intelligence")
+                .columns(["toString(intelligence_id) AS name", "count() AS count"])
+                .filter(
+                    Conditions::all()
+                        .raw("is_deleted = 0")
+                        .push(Query::with_params(format!("lowerUTF8({}) = lowerUTF8(?)", THREAT_ACTOR_EXPR), [actor])),
+                )
+                .group_by("name")
+                .order_by("count", Order::Desc)
+                .limit(max_intelligence as u64)
+                .build();
+            for row in client.fetch::<NamedCountRow>(&actor_query).await? {
+                if !ids.contains(&row.name) {
+                    ids.push(row.name);
+                }
+            }
+        }
+        if ids.is_empty() {
+            return Ok(TimelineHits::empty());
+        }
+        let intel_hits = Conditions::all()
+            .raw("is_deleted = 0")
+            .in_list("toString(intelligence_id)", &ids);
+
+        let intel_query = SelectQuery::from("alert_intelligence")
+            .columns([
+                "toString(intelligence_id) AS intel_id",
+                "count() AS count",
+                "toUInt32(toUnixTimestamp(min(first_discovered_time))) AS first_secs",
+                "toString(any(source)) AS source_name",
+                "any(source_industry) AS industry",
+                "toUInt32(toUnixTimestamp(max(intelligence_expiration_time))) AS expire_secs",
+                "argMax(description, intelligence_update_time) AS description_text",
+                "toString(any(attribute)) AS attribute_name",
+                "any(value) AS value_text",
+            ])
+            .filter(intel_hits.clone())
+            .group_by("intel_id")
+            .build();
+        let updates_query = SelectQuery::from("alert_intelligence")
+            .columns([
+                "toString(intelligence_id) AS intel_id",
+                "toUInt32(toUnixTimestamp(intelligence_update_time)) AS update_secs",
+                "any(description) AS description_text",
+            ])
+            .filter(intel_hits.clone())
+            .group_by("intel_id")
+            .group_by("update_secs")
+            .order_by("update_secs", Order::Desc)
+            .limit(MAX_LIFECYCLE_EVENTS)
+            .build();
+
+        // 命中任一情报的邮件，再按时间、处置动作和收件人域名过滤
+        let mut matched = Conditions::all()
+            .in_subquery(
+                "id",
+                SelectQuery::from("alert_intelligence").column("mail_id").filter(intel_hits.clone()).build(),
+            )
+            .time_range("timestamp", filter.start_time, filter.end_time);
+        if !filter.actions.is_empty() {
+            matched = matched.in_list("lowerUTF8(toString(action))", filter.actions.iter().map(|status| status.as_str()));
+        }
+        if let Some(domain) = &filter.recipient_domain {
+            matched = matched.group(
+                Conditions::any()
+                    .eq("lowerUTF8(display_to_domain)", domain)
+                    .eq("lowerUTF8(client_envelope_to_domain)", domain),
+            );
+        }
+
+        let span_query = SelectQuery::from("data_mail_info")
+            .columns(SPAN_COLUMNS)
+            .filter(matched.clone())
+            .build();
+        let (intel, span, updates) = tokio::try_join!(
+            client.fetch::<TimelineIntelRow>(&intel_query),
+            client.fetch::<CountSpanRow>(&span_query),
+            client.fetch::<TimelineUpdateRow>(&updates_query),
+        )?;
+
+        let (mails, newer_secs, buckets) = match filter.interval {
+            Some(interval) => {
+                let buckets_query = SelectQuery::from("data_mail_info")
+                    .column(format!("{} AS bucket_secs", bucket_sql(interval)))
+                    .columns(["toString(action) AS action_name", "count() AS count"])
+                    .filter(matched)
+                    .group_by("bucket_secs")
+                    .group_by("action_name")
+                    .build();
+                (Vec::new(), None, client.fetch::<TimelineBucketRow>(&buckets_query).await?)
+            }
+            None => {
+                // 非首页时多取上一页的最后一封邮件作为分页边界
+                let offset = u64::from(filter.page - 1) * u64::from(filter.page_size);
+                let (offset, limit) = if offset > 0 {
+                    (offset - 1, filter.page_size + 1)
+                } else {
+                    (0, filter.page_size)
+                };
+                let mails_query = SelectQuery::from("data_mail_info")
+                    .columns([
+                        "id",
+                        "toString(action) AS action_name",
+                        "toUInt32(toUnixTimestamp(timestamp)) AS timestamp_secs",
+                        "if(display_from != '', display_from, client_envelope_from_address) AS sender_address",
+                    ])
+                    .column(format!("{} AS recipient_list", RECIPIENTS_EXPR))
+                    .filter(matched)
+                    .order_by("timestamp", Order::Desc)
+                    .order_by("id", Order::Desc)
+                    .limit(u64::from(limit))
+                    .offset(offset)
+                    .build();
+                let mut mails = client.fetch::<TimelineMailRow>(&mails_query).await?;
+                let newer_secs = if filter.page > 1 && !mails.is_empty() {
+                    Some(mails.remove(0).timestamp_secs)
+                } else {
+                    None
+                };
+                (mails, newer_secs, Vec::new())
+            }
+        };
+
+        // 当前页邮件命中了哪些时间线情报
+        let mail_hits = if mails.is_empty() {
+            Vec::new()
+        } else {
+            let hits_query = SelectQuery::from("alert_intelligence")
+                .columns(["mail_id", "toString(intelligence_id) AS intel_id"])
+                .filter(intel_hits.in_list("mail_id", mails.iter().map(|row| row.id)))
+                .group_by("mail_id")
+                .group_by("intel_id")
+                .build();
+            client.fetch::<TimelineMailHitRow>(&hits_query).await?
+        };
+
+        Ok(TimelineHits {
+            intel,
+            span: span.into_iter().next().unwrap_or_default(),
+            mails,
+            mail_hits,
+            newer_secs,
+            buckets,
+            updates,
+        })
+    }
+
+    async fn spread_snapshots(&self, filter: &SpreadFilter) -> DbResult<(u64, Vec<SpreadSnapshotRow>)> {
+        let intel = Conditions::all()
+            .raw("is_deleted = 0")
+            .eq("toString(intelligence_id)", filter.intelligence_id.to_string());
+        let count_query = SelectQuery::from("alert_intelligence")
+            .column("count() AS count")
+            .filter(intel.clone())
+            .build();
+        let snapshots_query = SelectQuery::from("alert_intelligence")
+            .columns([
+                "toUInt32(toUnixTimestamp(min(timestamp))) AS timestamp_secs",
+                "joint_prevention_and_control AS joint_text",
+            ])
+            .filter(intel.raw("joint_prevention_and_control != ''"))
+            .group_by("joint_text")
+            .order_by("timestamp_secs", Order::Asc)
+            .limit(MAX_SPREAD_SNAPSHOTS)
+            .build();
+
+        let (count, snapshots) = tokio::try_join!(
+            self.client.fetch::<CountResult>(&count_query),
+            self.client.fetch::<SpreadSnapshotRow>(&snapshots_query),
+        )?;
+        Ok((count.first().map(|row| row.count).unwrap_or_default(), snapshots))
+    }
+
+    async fn campaign_hits(&self, mail_ids: &[u64]) -> DbResult<Vec<CampaignHitRow>> {
+        if mail_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let query = SelectQuery::from("alert_intelligence")
+            .columns([
+                "mail_id",
+                "toString(intelligence_id) AS intel_id",
+                "toString(attribute) AS attribute_name",
+                "value AS value_text",
+                "intelligence_type AS type_name",
+                "toString(urgency) AS urgency_name",
+            ])
+            .column(format!("{} AS actor_name", THREAT_ACTOR_EXPR))
+            .filter(Conditions::all().raw("is_deleted = 0").in_list("mail_id", mail_ids))
+            .build();
+        self.client.fetch::<CampaignHitRow>(&query).await
+    }
+
+    async fn hash_intelligence(&self, attribute: &str, hash: &str) -> DbResult<Vec<HashIntelligenceRow>> {
+        let query = SelectQuery::from("alert_intelligence")
+            .columns([
+                "toString(intelligence_id) AS intel_id",
+                "any(toString(attribute)) AS attribute_name",
+                "any(intelligence_type) AS type_name",
+                "any(toString(urgency)) AS urgency_name",
+                "any(description) AS description_text",
+            ])
+            .column(format!("any({}) AS actor_name", THREAT_ACTOR_EXPR))
+            .columns([
+                "uniqExact(mail_id) AS hit_mails",
+                "toUInt32(toUnixTimestamp(min(timestamp))) AS first_secs",
+                "toUInt32(toUnixTimestamp(max(timestamp))) AS last_secs",
+            ])
+            .filter(Self::hash_hits(attribute, hash))
+            .group_by("intel_id")
+            .order_by("last_secs", Order::Desc)
+            .build();
+        self.client.fetch::<HashIntelligenceRow>(&query).await
+    }
+
+    async fn mail_intelligence(&self, mail_id: u64) -> DbResult<Vec<MailIntelligenceValueRow>> {
+        let query = Self::intelligence_values(Conditions::all().eq("mail_id", mail_id));
+        self.client.fetch::<MailIntelligenceValueRow>(&query).await
+    }
+
+    async fn url_intelligence(&self, attributes: &[&str], candidates: &[String]) -> DbResult<Vec<MailIntelligenceValueRow>> {
+        let query = Self::intelligence_values(
+            Conditions::all()
+                .in_list("toString(attribute)", attributes.iter().copied())
+                .in_list("lowerUTF8(value)", candidates),
+        );
+        self.client.fetch::<MailIntelligenceValueRow>(&query).await
+    }
+}
+
+#[async_trait]
+impl MailInfoRepository for ClickHouseRepository {
+    async fn search_mails(&self, criteria: &EmailSearchCriteria) -> DbResult<(u64, Vec<MailSearchRow>)> {
+        let mut conditions = Conditions::all().time_range("timestamp", criteria.start_time, criteria.end_time);
+        for term in search::terms(criteria) {
+            conditions = conditions.group(term.to_conditions());
+        }
+        if let Some(intelligence_id) = &criteria.intelligence_id {
+            let hits = SelectQuery::from("alert_intelligence")
+                .column("mail_id")
+                .filter(Conditions::all().raw("is_deleted = 0").eq("toString(intelligence_id)", intelligence_id.as_str()))
+                .build();
+            conditions = conditions.in_subquery("id", hits);
+        }
+        if let Some(mail_ids) = &criteria.mail_ids {
+            conditions = conditions.in_list("id", mail_ids.iter().copied());
+        }
+
+        let count_query = SelectQuery::from("data_mail_info")
+            .column("count() AS count")
+            .filter(conditions.clone())
+            .build();
+        let total = self
+            .fetch_one::<CountResult>(&count_query)
+            .await?
+            .map_or(0, |row| row.count);
+
+        let offset = u64::from(criteria.page - 1) * u64::from(criteria.page_size);
+        let rows_query = SelectQuery::from("data_mail_info")
+            .columns([
+                "id",
+                "toString(action) AS action_name",
+                "toUnixTimestamp(timestamp) AS timestamp_secs",
+                "subject",
+                "display_from",
+                "display_to_address",
+                "display_to_account",
+                "display_to_domain",
+                "sasl_login",
+                "client_ip",
+                "client_envelope_from_address",
+                "client_envelope_to_address",
+                "client_envelope_to_account",
+                "client_envelope_to_domain",
+                "tls",
+                "protocol_version",
+                "text_body",
+                "hash_sha1",
+                "hash_sha256",
+                "hash_md5",
+                "direction",
+            ])
+            .filter(conditions)
+            .order_by("timestamp", Order::Desc)
+            .order_by("id", Order::Desc)
+            .limit(u64::from(criteria.page_size))
+            .offset(offset)
+            .build();
+        let rows = self.client.fetch::<MailSearchRow>(&rows_query).await?;
+
+        Ok((total, rows))
+    }
+
+    async fn thread_seed(&self, mail_id: u64) -> DbResult<Option<ThreadMailRow>> {
+        let query = Self::thread_mails(Conditions::all().eq("mail_id", mail_id))
+            .filter(Conditions::all().eq("id", mail_id))
+            .limit(1)
+            .build();
+        self.fetch_one::<ThreadMailRow>(&query).await
+    }
+
+    async fn thread_candidates(&self, seed: &ThreadMailRow, subject: &str) -> DbResult<Vec<ThreadMailRow>> {
+        let time = DateTime::from_timestamp(i64::from(seed.timestamp_secs), 0)
+            .ok_or_else(|| anyhow!("邮件时间无效: {}", seed.timestamp_secs))?;
+        let window = Conditions::all().time_range(
+            "timestamp",
+            Some(time - Duration::days(THREAD_WINDOW_DAYS)),
+            Some(time + Duration::days(THREAD_WINDOW_DAYS)),
+        );
+        let query = Self::thread_mails(window.clone())
+            .filter(window.like("subject", subject, LikeMatch::Contains, true))
+            .order_by("timestamp", Order::Asc)
+            .limit(MAX_THREAD_MAILS)
+            .build();
+        self.client.fetch::<ThreadMailRow>(&query).await
+    }
+
+    async fn campaign_mails(
+        &self,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+        limit: u64,
+    ) -> DbResult<Vec<CampaignMailRow>> {
+        let query = SelectQuery::from("data_mail_info")
+            .columns([
+                "id",
+                "toString(action) AS action_name",
+                "toUInt32(toUnixTimestamp(timestamp)) AS timestamp_secs",
+                "subject",
+                "text_body",
+                "html_body",
+                "display_from",
+                "client_envelope_from_address",
+            ])
+            .column(format!("{} AS recipient_list", RECIPIENTS_EXPR))
+            .filter(window(start_time, end_time))
+            .order_by("timestamp", Order::Desc)
+            .limit(limit)
+            .build();
+        self.client.fetch::<CampaignMailRow>(&query).await
+    }
+
+    async fn hash_mails(&self, filter: &HashPivotFilter, attachment_mails: &[u64]) -> DbResult<HashMails> {
+        let column = Self::hash_column(filter.kind);
+        // SHA1没有对应的情报属性
+        let intel_mails = filter
+            .kind
+            .intelligence_attribute()
+            .map(|attribute| Self::hash_hits(attribute, &filter.hash))
+            .map(|intel| SelectQuery::from("alert_intelligence").column("mail_id").filter(intel).build());
+
+        // 关联条件：邮件文件哈希相同、命中该哈希的情报或包含哈希相同的附件
+        let mut matched = Conditions::any().eq(&format!("lowerUTF8({})", column), &filter.hash);
+        if let Some(intel_mails) = &intel_mails {
+            matched = matched.in_subquery("id", intel_mails.clone());
+        }
+        if !attachment_mails.is_empty() {
+            matched = matched.in_list("id", attachment_mails);
+        }
+
+        let intel_match = match &intel_mails {
+            Some(intel_mails) => Query::wrap("toUInt8(id IN (", intel_mails, ")) AS intel_match"),
+            None => Query::new("toUInt8(0) AS intel_match"),
+        };
+        let mails_query = SelectQuery::from("data_mail_info")
+            .columns([
+                "id",
+                "toString(action) AS action_name",
+                "toUInt32(toUnixTimestamp(timestamp)) AS timestamp_secs",
+                "subject",
+                "display_from",
+                "client_envelope_from_address",
+            ])
+            .column(format!("{} AS recipient_list", RECIPIENTS_EXPR))
+            .column_query(Query::with_params(
+                format!("toUInt8(lowerUTF8({}) = ?) AS mail_match", column),
+                [&filter.hash],
+            ))
+            .column_query(intel_match)
+            .filter(matched.clone())
+            .order_by("timestamp", Order::Desc)
+            .limit(u64::from(filter.limit))
+            .build();
+        let summary_query = SelectQuery::from("data_mail_info")
+            .columns(SPAN_COLUMNS)
+            .filter(matched.clone())
+            .build();
+        let recipients_query = SelectQuery::from("data_mail_info")
+            .column(format!("arrayJoin({}) AS name", RECIPIENTS_EXPR))
+            .column("count() AS count")
+            .filter(matched)
+            .group_by("name")
+            .order_by("count", Order::Desc)
+            .order_by("name", Order::Asc)
+            .limit(MAX_PIVOT_RECIPIENTS)
+            .build();
+
+        let (mails, summary, recipients) = tokio::try_join!(
+            self.client.fetch::<HashMailRow>(&mails_query),
+            self.client.fetch::<CountSpanRow>(&summary_query),
+            self.client.fetch::<NamedCountRow>(&recipients_query),
+        )?;
+
+        Ok(HashMails {
+            mails,
+            summary: summary.into_iter().next().unwrap_or_default(),
+            recipients,
+        })
+    }
+
+    async fn mail_file(&self, mail_id: u64) -> DbResult<Option<MailFileRow>> {
+        let query = SelectQuery::from("data_mail_info")
+            .columns(["eml_path", "hash_sha256"])
+            .filter(Conditions::all().eq("id", mail_id))
+            .limit(1)
+            .build();
+        self.fetch_one::<MailFileRow>(&query).await
+    }
+
+    async fn delivery(&self, mail_id: u64) -> DbResult<Option<MailDeliveryRow>> {
+        let query = SelectQuery::from("data_mail_info")
+            .columns([
+                "toString(action) AS action_name",
+                "client_envelope_from_address",
+                "client_envelope_to_address",
+            ])
+            .filter(Conditions::all().eq("id", mail_id))
+            .limit(1)
+            .build();
+        self.fetch_one::<MailDeliveryRow>(&query).await
+    }
+
+    async fn extract_password(&self, mail_id: u64) -> DbResult<Option<String>> {
+        let query = SelectQuery::from("data_mail_info")
+            .column("extract_password")
+            .filter(Conditions::all().eq("id", mail_id))
+            .limit(1)
+            .build();
+        Ok(self.fetch_one::<MailExtractPasswordRow>(&query).await?.map(|row| row.extract_password))
+    }
+
+    async fn mail_body(&self, mail_id: u64) -> DbResult<Option<MailBodyRow>> {
+        let query = SelectQuery::from("data_mail_info")
+            .columns(["subject", "text_body", "html_body"])
+            .filter(Conditions::all().eq("id", mail_id))
+            .limit(1)
+            .build();
+        self.fetch_one::<MailBodyRow>(&query).await
+    }
+}
+
+#[async_trait]
+impl DispositionRepository for ClickHouseRepository {
+    async fn dispositions(&self, intelligence_ids: &[String]) -> DbResult<Vec<TimelineDispositionRow>> {
+        let query = SelectQuery::from("intelligence_disposition_log")
+            .columns([
+                "toString(intelligence_id) AS intel_id",
+                "toString(disposition) AS disposition_name",
+                "enabled",
+                "operator",
+                "reason",
+                "toUInt32(toUnixTimestamp(created_at)) AS created_secs",
+            ])
+            .filter(Conditions::all().in_list("toString(intelligence_id)", intelligence_ids))
+            .order_by("created_at", Order::Desc)
+            .limit(MAX_LIFECYCLE_EVENTS)
+            .build();
+        self.client.fetch::<TimelineDispositionRow>(&query).await
+    }
+
+    async fn retro_hunts(&self, intelligence_ids: &[String]) -> DbResult<Vec<TimelineRetroHuntRow>> {
+        let query = SelectQuery::from("intelligence_retro_hunt")
+            .columns([
+                "toString(intelligence_id) AS intel_id",
+                "job_id",
+                "operator",
+                "toUInt32(toUnixTimestamp(scan_start_time)) AS scan_start_secs",
+                "toUInt32(toUnixTimestamp(scan_end_time)) AS scan_end_secs",
+                "scanned_mails",
+                "matched_mails",
+                "toUInt32(toUnixTimestamp(finished_at)) AS finished_secs",
+            ])
+            .filter(Conditions::all().in_list("toString(intelligence_id)", intelligence_ids))
+            .order_by("finished_at", Order::Desc)
+            .limit(MAX_LIFECYCLE_EVENTS)
+            .build();
+        self.client.fetch::<TimelineRetroHuntRow>(&query).await
+    }
+}
+
+#[async_trait]
+impl AttachmentRepository for ClickHouseRepository {
+    async fn attachments(&self, mail_ids: Option<&[u64]>) -> DbResult<Vec<MailAttachmentRow>> {
+        let mut conditions = Conditions::all();
+        if let Some(mail_ids) = mail_ids {
+            if mail_ids.is_empty() {
+                return Ok(Vec::new());
+            }
+            conditions = conditions.in_list("mail_id", mail_ids.iter().copied());
+        }
+        let query = SelectQuery::from("data_mail_attachment")
+            .columns(ATTACHMENT_COLUMNS)
+            .filter(conditions)
+            .order_by("mail_id", Order::Asc)
+            .order_by("attachment_id", Order::Asc)
+            .build();
+        self.client.fetch::<MailAttachmentRow>(&query).await
+    }
+
+    async fn attachment(&self, attachment_id: &str) -> DbResult<Option<MailAttachmentRow>> {
+        let query = SelectQuery::from("data_mail_attachment")
+            .columns(ATTACHMENT_COLUMNS)
+            .filter(Conditions::all().eq("attachment_id", attachment_id))
+            .limit(1)
+            .build();
+        self.fetch_one::<MailAttachmentRow>(&query).await
+    }
+
+    async fn attachments_by_hash(&self, kind: HashKind, hash: &str) -> DbResult<Vec<MailAttachmentRow>> {
+        let column = Self::hash_column(kind);
+        let query = SelectQuery::from("data_mail_attachment")
+            .columns(ATTACHMENT_COLUMNS)
+            .filter(Conditions::all().eq(&format!("lowerUTF8({})", column), hash.to_lowercase()))
+            .order_by("mail_id", Order::Asc)
+            .order_by("attachment_id", Order::Asc)
+            .build();
+        self.client.fetch::<MailAttachmentRow>(&query).await
+    }
+}
+
+#[async_trait]
+impl QuarantineRepository for ClickHouseRepository {
+    async fn save_action(&self, action: &QuarantineActionRow) -> DbResult<()> {
+        self.client.insert("quarantine_action", vec![action.clone()]).await
+    }
+
+    async fn action(&self, action_id: &str) -> DbResult<Option<QuarantineActionRow>> {
+        let query = SelectQuery::from("quarantine_action FINAL")
+            .columns(QuarantineActionRow::COLUMN_NAMES.iter().copied())
+            .filter(Conditions::all().eq("action_id", action_id))
+            .limit(1)
+            .build();
+        self.fetch_one::<QuarantineActionRow>(&query).await
+    }
+
+    async fn actions(&self, mail_id: Option<&str>, state: Option<&str>) -> DbResult<Vec<QuarantineActionRow>> {
+        let mut conditions = Conditions::all();
+        if let Some(mail_id) = mail_id {
+            conditions = conditions.eq("mail_id", mail_id);
+        }
+        if let Some(state) = state {
+            conditions = conditions.eq("state", state);
+        }
+        let query = SelectQuery::from("quarantine_action FINAL")
+            .columns(QuarantineActionRow::COLUMN_NAMES.iter().copied())
+            .filter(conditions)
+            .order_by("requested_at", Order::Desc)
+            .build();
+        self.client.fetch::<QuarantineActionRow>(&query).await
+    }
+
+    async fn append_audit(&self, entry: &QuarantineAuditRow) -> DbResult<()> {
+        self.client.insert("quarantine_audit", vec![entry.clone()]).await
+    }
+
+    async fn audit(&self, mail_id: Option<&str>, action_id: Option<&str>) -> DbResult<Vec<QuarantineAuditRow>> {
+        let mut conditions = Conditions::all();
+        if let Some(mail_id) = mail_id {
+            conditions = conditions.eq("mail_id", mail_id);
+        }
+        if let Some(action_id) = action_id {
+            conditions = conditions.eq("action_id", action_id);
+        }
+        let query = SelectQuery::from("quarantine_audit")
+            .columns(QuarantineAuditRow::COLUMN_NAMES.iter().copied())
+            .filter(conditions)
+            .order_by("timestamp", Order::Asc)
+            .order_by("version", Order::Asc)
+            .build();
+        self.client.fetch::<QuarantineAuditRow>(&query).await
+    }
+}
+
+#[async_trait]
+impl StatisticsRepository for ClickHouseRepository {
+    async fn recipient_profile(&self, filter: &RecipientProfileFilter) -> DbResult<RecipientProfileData> {
+        let client = &self.client;
+        let hit_mails = hit_mails(filter.start_time, filter.end_time);
+        let mails = window(filter.start_time, filter.end_time)
+            .push(Query::with_params(format!("has({}, ?)", RECIPIENTS_EXPR), [&filter.address]));
+        let hits = Conditions::all()
+            .raw("is_deleted = 0")
+            .extend(window(filter.start_time, filter.end_time))
+            .in_subquery("mail_id", SelectQuery::from("data_mail_info").column("id").filter(mails.clone()).build());
+
+        let actions = SelectQuery::from("data_mail_info")
+            .columns(["toString(action) AS name", "count() AS count"])
+            .filter(mails.clone())
+            .group_by("name")
+            .build();
+        let summary = SelectQuery::from("data_mail_info")
+            .columns(SPAN_COLUMNS)
+            .filter(mails.clone().in_subquery("id", hit_mails.clone()))
+            .build();
+        let types = SelectQuery::from("alert_intelligence")
+            .columns(["intelligence_type AS name", "count() AS count"])
+            .filter(hits.clone())
+            .group_by("name")
+            .order_by("count", Order::Desc)
+            .order_by("name", Order::Asc)
+            .build();
+        let urgency = SelectQuery::from("alert_intelligence")
+            .columns(["toString(urgency) AS name", "count() AS count"])
+            .filter(hits.clone())
+            .group_by("name")
+            .build();
+        let actors = SelectQuery::from("alert_intelligence")
+            .column(format!("{} AS name", THREAT_ACTOR_EXPR))
+            .column("uniqExact(mail_id) AS count")
+            .filter(hits.raw("name != ''"))
+            .group_by("name")
+            .order_by("count", Order::Desc)
+            .order_by("name", Order::Asc)
+            .limit(u64::from(filter.top_actors))
+            .build();
+        let trend = SelectQuery::from("data_mail_info")
+            .column(format!("{} AS bucket_secs", bucket_sql(filter.interval)))
+            .column("count() AS total")
+            .column_query(Query::wrap("countIf(id IN (", &hit_mails, ")) AS hits"))
+            .filter(mails)
+            .group_by("bucket_secs")
+            .build();
+
+        let (actions, summary, types, urgency, actors, trend) = tokio::try_join!(
+            client.fetch::<NamedCountRow>(&actions),
+            client.fetch::<CountSpanRow>(&summary),
+            client.fetch::<NamedCountRow>(&types),
+            client.fetch::<NamedCountRow>(&urgency),
+            client.fetch::<NamedCountRow>(&actors),
+            client.fetch::<TrendCountRow>(&trend),
+        )?;
+
+        Ok(RecipientProfileData {
+            actions,
+            summary: summary.into_iter().next().unwrap_or_default(),
+            types,
+            urgency,
+            actors,
+            trend,
+        })
+    }
+
+    async fn recipient_rank(&self, filter: &RecipientRankFilter) -> DbResult<Vec<RecipientRankRow>> {
+        let recipients = SelectQuery::from("data_mail_info")
+            .column(format!("arrayJoin({}) AS address", RECIPIENTS_EXPR))
+            .column("timestamp")
+            .column_query(Query::wrap("id IN (", &hit_mails(filter.start_time, filter.end_time), ") AS hit"))
+            .filter(window(filter.start_time, filter.end_time))
+            .build();
+        let query = SelectQuery::from_subquery(recipients)
+            .columns([
+                "address",
+                "count() AS received",
+                "countIf(hit) AS targeted",
+                "toUInt32(toUnixTimestamp(maxIf(timestamp, hit))) AS last_secs",
+            ])
+            .group_by("address")
+            .having(Conditions::all().raw("targeted > 0"))
+            .order_by("targeted", Order::Desc)
+            .order_by("received", Order::Desc)
+            .order_by("address", Order::Asc)
+            .limit(u64::from(filter.limit))
+            .build();
+        self.client.fetch::<RecipientRankRow>(&query).await
+    }
+
+    async fn sender_profile(&self, filter: &SenderProfileFilter) -> DbResult<SenderProfileData> {
+        let client = &self.client;
+        let hit_mails = hit_mails(filter.start_time, filter.end_time);
+        let sender = Self::sender_condition(filter.kind, &filter.sender);
+        let mails = window(filter.start_time, filter.end_time).push(sender.clone());
+
+        // 首次与最近来信时间不受时间范围限制
+        let lifetime = SelectQuery::from("data_mail_info")
+            .columns(SPAN_COLUMNS)
+            .filter(Conditions::all().push(sender))
+            .build();
+        let summary = SelectQuery::from("data_mail_info")
+            .column("count() AS total")
+            .column(format!("uniqExactArray({}) AS recipients", RECIPIENTS_EXPR))
+            .columns(["countIf(tls != '') AS tls_mails", "countIf(sasl_login != '') AS authenticated"])
+            .column_query(Query::wrap("countIf(id IN (", &hit_mails, ")) AS hit_mails"))
+            .filter(mails.clone())
+            .build();
+        let actions = SelectQuery::from("data_mail_info")
+            .columns(["toString(action) AS name", "count() AS count"])
+            .filter(mails.clone())
+            .group_by("name")
+            .build();
+        let sources = SelectQuery::from("data_mail_info")
+            .columns([
+                "client_ip",
+                "client_ptr",
+                "count() AS count",
+                "toUInt32(toUnixTimestamp(max(timestamp))) AS last_secs",
+            ])
+            .filter(mails.clone())
+            .group_by("client_ip")
+            .group_by("client_ptr")
+            .order_by("count", Order::Desc)
+            .order_by("last_secs", Order::Desc)
+            .limit(u64::from(filter.top_ips))
+            .build();
+        let tls = SelectQuery::from("data_mail_info")
+            .columns(["tls AS name", "count() AS count"])
+            .filter(mails.clone().raw("tls != ''"))
+            .group_by("name")
+            .order_by("count", Order::Desc)
+            .order_by("name", Order::Asc)
+            .build();
+        let types = SelectQuery::from("alert_intelligence")
+            .columns(["intelligence_type AS name", "count() AS count"])
+            .filter(
+                Conditions::all()
+                    .raw("is_deleted = 0")
+                    .extend(window(filter.start_time, filter.end_time))
+                    .in_subquery("mail_id", SelectQuery::from("data_mail_info").column("id").filter(mails.clone()).build()),
+            )
+            .group_by("name")
+            .order_by("count", Order::Desc)
+            .order_by("name", Order::Asc)
+            .build();
+        let trend = SelectQuery::from("data_mail_info")
+            .column(format!("{} AS bucket_secs", bucket_sql(filter.interval)))
+            .column("count() AS total")
+            .column_query(Query::wrap("countIf(id IN (", &hit_mails, ")) AS hits"))
+            .filter(mails)
+            .group_by("bucket_secs")
+            .build();
+
+        let (lifetime, summary, actions, sources, tls, types, trend) = tokio::try_join!(
+            client.fetch::<CountSpanRow>(&lifetime),
+            client.fetch::<SenderSummaryRow>(&summary),
+            client.fetch::<NamedCountRow>(&actions),
+            client.fetch::<SendingSourceRow>(&sources),
+            client.fetch::<NamedCountRow>(&tls),
+            client.fetch::<NamedCountRow>(&types),
+            client.fetch::<TrendCountRow>(&trend),
+        )?;
+
+        Ok(SenderProfileData {
+            lifetime: lifetime.into_iter().next().unwrap_or_default(),
+            summary: summary.into_iter().next().unwrap_or_default(),
+            actions,
+            sources,
+            tls,
+            types,
+            trend,
+        })
+    }
+
+    async fn hit_summary(&self, start_time: DateTime<Utc>, end_time: DateTime<Utc>) -> DbResult<HitSummaryRow> {
+        let query = SelectQuery::from("alert_intelligence")
+            .columns(HIT_COUNT_COLUMNS)
+            .column("uniqExact(source_id) AS sources")
+            .filter(Conditions::all().raw("is_deleted = 0").extend(window(start_time, end_time)))
+            .build();
+        Ok(self.fetch_one::<HitSummaryRow>(&query).await?.unwrap_or_default())
+    }
+
+    async fn hit_trend(
+        &self,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+        interval: TrendInterval,
+    ) -> DbResult<Vec<HitTrendRow>> {
+        let query = SelectQuery::from("alert_intelligence")
+            .column(format!("{} AS bucket_secs", bucket_sql(interval)))
+            .columns(HIT_COUNT_COLUMNS)
+            .filter(Conditions::all().raw("is_deleted = 0").extend(window(start_time, end_time)))
+            .group_by("bucket_secs")
+            .order_by("bucket_secs", Order::Asc)
+            .build();
+        self.client.fetch::<HitTrendRow>(&query).await
+    }
+
+    async fn threat_actors(&self, start_time: DateTime<Utc>, end_time: DateTime<Utc>) -> DbResult<Vec<ThreatActorRow>> {
+        let query = SelectQuery::from("alert_intelligence")
+            .column(format!("{} AS name", THREAT_ACTOR_EXPR))
+            .column(format!("anyIf({expr}, {expr} != '') AS kind", expr = THREAT_ACTOR_KIND_EXPR))
+            .column("uniqExact(mail_id) AS count")
+            .filter(
+                Conditions::all()
+                    .raw("is_deleted = 0")
+                    .extend(window(start_time, end_time))
+                    .raw("name != ''"),
+            )
+            .group_by("name")
+            .order_by("count", Order::Desc)
+            .order_by("name", Order::Asc)
+            .build();
+        self.client.fetch::<ThreatActorRow>(&query).await
+    }
+
+    async fn custom_intelligence(
+        &self,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+    ) -> DbResult<CustomIntelligenceRow> {
+        let query = SelectQuery::from("alert_intelligence")
+            .column_query(Query::with_params(
+                "uniqExactIf(intelligence_id, timestamp >= toDateTime(?) AND timestamp <= toDateTime(?)) AS hits",
+                [start_time.timestamp(), end_time.timestamp()],
+            ))
+            .column("uniqExact(intelligence_id) AS total")
+            .filter(Conditions::all().raw("is_deleted = 0").raw("source = 'Local'"))
+            .build();
+        Ok(self.fetch_one::<CustomIntelligenceRow>(&query).await?.unwrap_or_default())
+    }
+}
@@ -0,0 +1,166 @@
+//! 内存模式的附件与原始邮件
+//!
+//! 附件记录的大小与哈希按入库时的内容计算，存储中的内容可能与之不同，用于模拟损坏与丢失的文件
+
+use std::io::Write;
+use chrono::DateTime;
+use zip::write::SimpleFileOptions;
+
+use super::MOCK_EXTRACT_PASSWORD;
+use crate::content::compute_digests;
+use crate::db::MailAttachmentRow;
+
+/// 模拟HTML附件内容
+const MOCK_HTML_CONTENT: &[u8] = b"<html><body><form action=\"https://login.examp1e.com/verify\" method=\"post\">\
+<input name=\"password\" type=\"password\"></form></body></html>";
+
+/// 模拟PDF附件内容
+const MOCK_PDF_CONTENT: &[u8] = b"%PDF-1.5\n1 0 obj\n<</Type/Catalog/Pages 2 0 R>>\nendobj\n2 0 obj\n<</Type/Pages/Kids[3 0 R]/Count 1>>\nendobj\n3 0 obj\n<</Type/Page/MediaBox[0 0 595 842]/Parent 2 0 R/Resources<<>>>>\nendobj\nxref\n0 4\n0000000000 65535 f \n0000000018 00000 n \n0000000063 00000 n \n0000000114 00000 n \n\ntrailer\n<</Size 4/Root 1 0 R>>\nstartxref\n178\n%%EOF";
+
+/// 模拟二维码图片附件，内容为HTTPS://LOGIN.EXAMP1E.COM/QR
+const MOCK_QR_PNG: &[u8] = &[
+    0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48, 0x44, 0x52,
+    0x00, 0x00, 0x00, 0x42, 0x00, 0x00, 0x00, 0x42, 0x01, 0x00, 0x00, 0x00, 0x00, 0xcb, 0x2f, 0x3d,
+    0x45, 0x00, 0x00, 0x00, 0x9c, 0x49, 0x44, 0x41, 0x54, 0x78, 0xda, 0xad, 0xd2, 0x31, 0x0a, 0x04,
+    0x31, 0x08, 0x05, 0x50, 0xc1, 0x36, 0x90, 0xab, 0x04, 0x6c, 0x03, 0x5e, 0x3d, 0x60, 0x3b, 0xe0,
+    0x55, 0x04, 0xdb, 0x01, 0x77, 0x97, 0x25, 0xb0, 0x3f, 0xdb, 0x8e, 0xd5, 0x6b, 0xe4, 0x7f, 0x44,
+    0xaa, 0x3d, 0xf4, 0x90, 0x88, 0x2d, 0x06, 0x29, 0x48, 0xb3, 0x69, 0x16, 0x6a, 0x88, 0x2e, 0xe1,
+    0x53, 0x9c, 0xff, 0x8a, 0xcc, 0x43, 0x9a, 0x11, 0x52, 0x28, 0x62, 0x91, 0x9d, 0xbb, 0x55, 0xd5,
+    0x79, 0xf7, 0xdb, 0xd2, 0xec, 0x8d, 0x17, 0xea, 0xee, 0x26, 0x64, 0xa8, 0x91, 0x73, 0xda, 0xad,
+    0xa0, 0xe5, 0xa9, 0xe6, 0x05, 0x12, 0xf3, 0xf7, 0xca, 0xa1, 0xee, 0x2d, 0x1c, 0x35, 0xd2, 0xad,
+    0xec, 0xd0, 0x5d, 0x7c, 0x85, 0xa2, 0x32, 0x85, 0x04, 0x55, 0xc5, 0xa5, 0x54, 0x20, 0x62, 0x27,
+    0x19, 0x0a, 0xfa, 0x5c, 0x43, 0xb9, 0x40, 0x43, 0x56, 0xd1, 0x3c, 0xe5, 0x7d, 0x7e, 0x33, 0x7e,
+    0x94, 0x8b, 0x12, 0xa5, 0x69, 0xfd, 0xb2, 0x02, 0x11, 0x53, 0x8b, 0xa5, 0xa0, 0x67, 0x7f, 0xe8,
+    0x05, 0xc2, 0x42, 0x9b, 0x7c, 0xed, 0xf3, 0xb9, 0x9b, 0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4e,
+    0x44, 0xae, 0x42, 0x60, 0x82,
+];
+
+/// 模拟附件：入库记录与存储中的内容
+pub(super) struct MockAttachment {
+    /// 入库记录
+    pub row: MailAttachmentRow,
+    /// 存储中的内容，文件已丢失时为None
+    pub stored: Option<Vec<u8>>,
+}
+
+/// 全部模拟附件
+pub(super) fn mock_attachments() -> Vec<MockAttachment> {
+    // 压缩包使用随机盐加密，只生成一次，保证记录的哈希与存储内容一致
+    let archive = build_mock_archive().unwrap_or_default();
+    let truncated = MOCK_PDF_CONTENT[..MOCK_PDF_CONTENT.len() / 2].to_vec();
+    [
+        (1, "att_001", "test.pdf", "/attachments/2024/03/test.pdf", MOCK_PDF_CONTENT, Some(MOCK_PDF_CONTENT.to_vec())),
+        (1, "att_002", "invoice.zip", "/attachments/2024/03/invoice.zip", archive.as_slice(), Some(archive.clone())),
+        // 存储中的文件入库后被截断
+        (5, "att_003", "发票.pdf", "/attachments/2024/03/fapiao.pdf", MOCK_PDF_CONTENT, Some(truncated)),
+        // 存储中的文件已丢失
+        (2, "att_004", "verify.html", "/attachments/2024/03/verify.html", MOCK_HTML_CONTENT, None),
+        // 二维码钓鱼图片
+        (1, "att_005", "scan.png", "/attachments/2024/03/scan.png", MOCK_QR_PNG, Some(MOCK_QR_PNG.to_vec())),
+    ]
+    .into_iter()
+    .map(|(mail_id, id, filename, file_path, recorded, stored)| {
+        let digests = compute_digests(recorded);
+        MockAttachment {
+            row: MailAttachmentRow {
+                mail_id,
+                attachment_id: id.to_string(),
+                filename: filename.to_string(),
+                file_path: file_path.to_string(),
+                size: recorded.len() as u64,
+                hash_md5: digests.md5,
+                hash_sha1: digests.sha1,
+                hash_sha256: digests.sha256,
+            },
+            stored,
+        }
+    })
+    .collect()
+}
+
+/// 模拟原始邮件
+pub(super) struct MockMessage {
+    /// 邮件ID
+    pub mail_id: u64,
+    /// 文件存储中的路径
+    pub path: String,
+    /// 邮件内容
+    pub data: Vec<u8>,
+}
+
+impl MockMessage {
+    /// 由邮件头和正文组装原始邮件，`timestamp`为发信时间（Unix时间戳，秒）
+    pub fn new(mail_id: u64, headers: &[(&str, &str)], timestamp: i64, body: &str) -> Self {
+        let mut eml = String::new();
+        for (name, value) in headers {
+            eml.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        let date = DateTime::from_timestamp(timestamp, 0).unwrap_or_default();
+        eml.push_str(&format!("Date: {}\r\n", date.to_rfc2822()));
+        eml.push_str("Content-Type: text/plain; charset=utf-8\r\n\r\n");
+        eml.push_str(&body.replace('\n', "\r\n"));
+        eml.push_str("\r\n");
+        Self {
+            mail_id,
+            path: format!("/eml/{}.eml", mail_id),
+            data: eml.into_bytes(),
+        }
+    }
+}
+
+/// 仿冒CEO的商务邮件诈骗往来，邮件ID为101至105，`now`为数据集的时间基准（Unix时间戳，秒）
+pub(super) fn thread_messages(now: i64) -> Vec<MockMessage> {
+    [
+        (101, 5, "\"张总\" <ceo@example.org>", "finance@example.org", "付款安排", "m101@example.org", ""),
+        (
+            102, 4, "finance@example.org", "\"张总\" <ceo@example.org>", "Re: 付款安排",
+            "m102@example.org", "<m101@example.org>",
+        ),
+        // 仿冒域名插入会话，主题使用编码字
+        (
+            103, 3, "\"张总\" <ceo@examp1e.org>", "finance@example.org", "=?UTF-8?B?UkU6IOS7mOasvuWuieaOkg==?=",
+            "m103@examp1e.org", "<m101@example.org> <m102@example.org>",
+        ),
+        (
+            104, 2, "finance@example.org", "\"张总\" <ceo@examp1e.org>", "回复：RE: 付款安排",
+            "m104@example.org", "<m101@example.org> <m102@example.org> <m103@examp1e.org>",
+        ),
+        // 转发没有回复关系，只能按主题归入会话
+        (105, 1, "finance@example.org", "accounting@example.org", "Fwd: 付款安排", "m105@example.org", ""),
+    ]
+    .into_iter()
+    .map(|(mail_id, days_ago, from, to, subject, message_id, references)| {
+        let message_id = format!("<{}>", message_id);
+        let mut headers = vec![("From", from), ("To", to), ("Subject", subject), ("Message-ID", message_id.as_str())];
+        if let Some(parent) = references.rsplit(' ').next().filter(|parent| !parent.is_empty()) {
+            headers.push(("In-Reply-To", parent));
+            headers.push(("References", references));
+        }
+        let body = format!("关于供应商付款的往来邮件。\n邮件ID: {}", mail_id);
+        MockMessage::new(mail_id, &headers, now - days_ago * 86400, &body)
+    })
+    .collect()
+}
+
+/// 构建模拟压缩包：加密的可执行文件和一个嵌套压缩包
+fn build_mock_archive() -> zip::result::ZipResult<Vec<u8>> {
+    let mut nested = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    nested.start_file("readme.txt", SimpleFileOptions::default())?;
+    nested.write_all("请使用密码打开发票文件。".as_bytes())?;
+    let nested = nested.finish()?.into_inner();
+
+    let mut archive = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    archive.start_file(
+        "invoice.pdf.exe",
+        SimpleFileOptions::default().with_aes_encryption(zip::AesMode::Aes256, MOCK_EXTRACT_PASSWORD),
+    )?;
+    let mut executable = vec![0u8; 0x80];
+    executable[..2].copy_from_slice(b"MZ");
+    executable[0x3C] = 0x40;
+    executable[0x40..0x44].copy_from_slice(b"PE\0\0");
+    archive.write_all(&executable)?;
+    archive.start_file("docs/nested.zip", SimpleFileOptions::default())?;
+    archive.write_all(&nested)?;
+
+    Ok(archive.finish()?.into_inner())
+}
@@ -0,0 +1,238 @@
+//! 随连接状态切换的存储库
+//!
+//! 每次查询按`ConnectionManager`的当前模式选择ClickHouse或内存存储库；数据库查询失败时检查连接，
+//! 连接已断开则进入不可用状态，本次及之后的查询返回`DbUnavailable`错误，不再使用模拟数据。
+//! 文件内容同样随模式切换：数据库模式读取附件存储目录，内存模式读取模拟数据集
+
+use std::sync::Arc;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use super::{
+    AttachmentRepository, Blob, BlobStore, ClickHouseRepository, DispositionRepository, FileBlobStore, HashMails,
+    InMemoryRepository, IntelligenceHitRepository, MailInfoRepository, QuarantineRepository, RecipientProfileData, SenderProfileData,
+    StatisticsRepository, TimelineHits,
+};
+use crate::db::{
+    CampaignHitRow, CampaignMailRow, ConnectionManager, CustomIntelligenceRow, DbResult, HashIntelligenceRow, HitSummaryRow,
+    HitTrendRow, MailAttachmentRow, MailBodyRow,
+    MailDeliveryRow, MailFileRow, MailIntelligenceValueRow, MailSearchRow, QuarantineActionRow, QuarantineAuditRow,
+    RecipientRankRow, SpreadSnapshotRow, ThreadMailRow, ThreatActorRow, TimelineDispositionRow, TimelineRetroHuntRow,
+};
+use crate::models::domain::email::EmailSearchCriteria;
+use crate::models::domain::hash::{HashKind, HashPivotFilter};
+use crate::models::domain::intelligence::{Intelligence, IntelligenceFilter};
+use crate::models::domain::recipient::{RecipientProfileFilter, RecipientRankFilter};
+use crate::models::domain::sender::SenderProfileFilter;
+use crate::models::domain::spread::SpreadFilter;
+use crate::models::domain::statistics::TrendInterval;
+use crate::models::domain::timeline::TimelineFilter;
+
+/// 按当前模式调用对应存储库的同名方法
+macro_rules! dispatch {
+    ($self:ident.$method:ident($($arg:expr),*)) => {
+        match $self.manager.client().await? {
+            Some(client) => match ClickHouseRepository::new(client.clone()).$method($($arg),*).await {
+                // 因连接断开而失败的查询标记为数据库不可用
+                Err(e) if $self.manager.check(&client).await => Err(e.context($self.manager.unavailable().await)),
+                result => result,
+            },
+            None => $self.memory.$method($($arg),*).await,
+        }
+    };
+}
+
+/// 随连接状态切换的存储库
+pub struct ManagedRepository {
+    /// 数据库连接管理器
+    manager: Arc<ConnectionManager>,
+    /// 内存模式使用的存储库
+    memory: InMemoryRepository,
+    /// 数据库模式使用的文件存储
+    files: FileBlobStore,
+}
+
+impl ManagedRepository {
+    /// 创建随连接状态切换的存储库，内存模式的模拟数据以创建时刻为时间基准
+    pub fn new(manager: Arc<ConnectionManager>, files: FileBlobStore) -> Self {
+        Self {
+            manager,
+            memory: InMemoryRepository::new(Utc::now()),
+            files,
+        }
+    }
+}
+
+#[async_trait]
+impl IntelligenceHitRepository for ManagedRepository {
+    async fn list_intelligence(&self, filter: &IntelligenceFilter) -> DbResult<(u64, Vec<Intelligence>)> {
+        dispatch!(self.list_intelligence(filter))
+    }
+
+    async fn timeline(&self, filter: &TimelineFilter, max_intelligence: usize) -> DbResult<TimelineHits> {
+        dispatch!(self.timeline(filter, max_intelligence))
+    }
+
+    async fn spread_snapshots(&self, filter: &SpreadFilter) -> DbResult<(u64, Vec<SpreadSnapshotRow>)> {
+        dispatch!(self.spread_snapshots(filter))
+    }
+
+    async fn campaign_hits(&self, mail_ids: &[u64]) -> DbResult<Vec<CampaignHitRow>> {
+        dispatch!(self.campaign_hits(mail_ids))
+    }
+
+    async fn hash_intelligence(&self, attribute: &str, hash: &str) -> DbResult<Vec<HashIntelligenceRow>> {
+        dispatch!(self.hash_intelligence(attribute, hash))
+    }
+
+    async fn mail_intelligence(&self, mail_id: u64) -> DbResult<Vec<MailIntelligenceValueRow>> {
+        dispatch!(self.mail_intelligence(mail_id))
+    }
+
+    async fn url_intelligence(&self, attributes: &[&str], candidates: &[String]) -> DbResult<Vec<MailIntelligenceValueRow>> {
+        dispatch!(self.url_intelligence(attributes, candidates))
+    }
+}
+
+#[async_trait]
+impl MailInfoRepository for ManagedRepository {
+    async fn search_mails(&self, criteria: &EmailSearchCriteria) -> DbResult<(u64, Vec<MailSearchRow>)> {
+        dispatch!(self.search_mails(criteria))
+    }
+
+    async fn thread_seed(&self, mail_id: u64) -> DbResult<Option<ThreadMailRow>> {
+        dispatch!(self.thread_seed(mail_id))
+    }
+
+    async fn thread_candidates(&self, seed: &ThreadMailRow, subject: &str) -> DbResult<Vec<ThreadMailRow>> {
+        dispatch!(self.thread_candidates(seed, subject))
+    }
+
+    async fn campaign_mails(
+        &self,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+        limit: u64,
+    ) -> DbResult<Vec<CampaignMailRow>> {
+        dispatch!(self.campaign_mails(start_time, end_time, limit))
+    }
+
+    async fn hash_mails(&self, filter: &HashPivotFilter, attachment_mails: &[u64]) -> DbResult<HashMails> {
+        dispatch!(self.hash_mails(filter, attachment_mails))
+    }
+
+    async fn mail_file(&self, mail_id: u64) -> DbResult<Option<MailFileRow>> {
+        dispatch!(self.mail_file(mail_id))
+    }
+
+    async fn delivery(&self, mail_id: u64) -> DbResult<Option<MailDeliveryRow>> {
+        dispatch!(self.delivery(mail_id))
+    }
+
+    async fn extract_password(&self, mail_id: u64) -> DbResult<Option<String>> {
+        dispatch!(self.extract_password(mail_id))
+    }
+
+    async fn mail_body(&self, mail_id: u64) -> DbResult<Option<MailBodyRow>> {
+        dispatch!(self.mail_body(mail_id))
+    }
+}
+
+#[async_trait]
+impl DispositionRepository for ManagedRepository {
+    async fn dispositions(&self, intelligence_ids: &[String]) -> DbResult<Vec<TimelineDispositionRow>> {
+        dispatch!(self.dispositions(intelligence_ids))
+    }
+
+    async fn retro_hunts(&self, intelligence_ids: &[String]) -> DbResult<Vec<TimelineRetroHuntRow>> {
+        dispatch!(self.retro_hunts(intelligence_ids))
+    }
+}
+
+#[async_trait]
+impl StatisticsRepository for ManagedRepository {
+    async fn recipient_profile(&self, filter: &RecipientProfileFilter) -> DbResult<RecipientProfileData> {
+        dispatch!(self.recipient_profile(filter))
+    }
+
+    async fn recipient_rank(&self, filter: &RecipientRankFilter) -> DbResult<Vec<RecipientRankRow>> {
+        dispatch!(self.recipient_rank(filter))
+    }
+
+    async fn sender_profile(&self, filter: &SenderProfileFilter) -> DbResult<SenderProfileData> {
+        dispatch!(self.sender_profile(filter))
+    }
+
+    async fn hit_summary(&self, start_time: DateTime<Utc>, end_time: DateTime<Utc>) -> DbResult<HitSummaryRow> {
+        dispatch!(self.hit_summary(start_time, end_time))
+    }
+
+    async fn hit_trend(
+        &self,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+        interval: TrendInterval,
+    ) -> DbResult<Vec<HitTrendRow>> {
+        dispatch!(self.hit_trend(start_time, end_time, interval))
+    }
+
+    async fn threat_actors(&self, start_time: DateTime<Utc>, end_time: DateTime<Utc>) -> DbResult<Vec<ThreatActorRow>> {
+        dispatch!(self.threat_actors(start_time, end_time))
+    }
+
+    async fn custom_intelligence(
+        &self,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+    ) -> DbResult<CustomIntelligenceRow> {
+        dispatch!(self.custom_intelligence(start_time, end_time))
+    }
+}
+
+#[async_trait]
+impl AttachmentRepository for ManagedRepository {
+    async fn attachments(&self, mail_ids: Option<&[u64]>) -> DbResult<Vec<MailAttachmentRow>> {
+        dispatch!(self.attachments(mail_ids))
+    }
+
+    async fn attachment(&self, attachment_id: &str) -> DbResult<Option<MailAttachmentRow>> {
+        dispatch!(self.attachment(attachment_id))
+    }
+
+    async fn attachments_by_hash(&self, kind: HashKind, hash: &str) -> DbResult<Vec<MailAttachmentRow>> {
+        dispatch!(self.attachments_by_hash(kind, hash))
+    }
+}
+
+#[async_trait]
+impl QuarantineRepository for ManagedRepository {
+    async fn save_action(&self, action: &QuarantineActionRow) -> DbResult<()> {
+        dispatch!(self.save_action(action))
+    }
+
+    async fn action(&self, action_id: &str) -> DbResult<Option<QuarantineActionRow>> {
+        dispatch!(self.action(action_id))
+    }
+
+    async fn actions(&self, mail_id: Option<&str>, state: Option<&str>) -> DbResult<Vec<QuarantineActionRow>> {
+        dispatch!(self.actions(mail_id, state))
+    }
+
+    async fn append_audit(&self, entry: &QuarantineAuditRow) -> DbResult<()> {
+        dispatch!(self.append_audit(entry))
+    }
+
+    async fn audit(&self, mail_id: Option<&str>, action_id: Option<&str>) -> DbResult<Vec<QuarantineAuditRow>> {
+        dispatch!(self.audit(mail_id, action_id))
+    }
+}
+
+#[async_trait]
+impl BlobStore for ManagedRepository {
+    async fn open(&self, path: &str) -> DbResult<Option<Blob>> {
+        match self.manager.client().await? {
+            Some(_) => self.files.open(path).await,
+            None => self.memory.open(path).await,
+        }
+    }
+}
@@ -0,0 +1,1341 @@
+//! 内存存储库
+//!
+//! 基于固定的模拟邮件与情报命中数据集计算各类查询结果。数据集的时间以创建存储库时指定的时刻为基准，
+//! 之后不再变化，同一查询多次调用的结果相同，不同接口看到的邮件、情报和处置记录也彼此一致
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use super::fixtures::{MockAttachment, MockMessage, mock_attachments, thread_messages};
+use super::{
+    AttachmentRepository, Blob, BlobStore, DispositionRepository, HashMails, IntelligenceHitRepository, MailInfoRepository, QuarantineRepository,
+    RecipientProfileData,
+    SenderProfileData, StatisticsRepository, TimelineHits, intelligence_attributes, search,
+};
+use crate::db::{
+    CampaignHitRow, CampaignMailRow, CountSpanRow, CustomIntelligenceRow, DbResult, HashIntelligenceRow, HashMailRow,
+    HitSummaryRow, HitTrendRow, MailAttachmentRow,
+    MailBodyRow, MailDeliveryRow, MailFileRow, MailIntelligenceValueRow, MailSearchRow, QuarantineActionRow, QuarantineAuditRow,
+    RecipientRankRow, SenderSummaryRow,
+    SendingSourceRow, SpreadSnapshotRow, ThreadMailRow, ThreatActorRow, TimelineBucketRow, TimelineDispositionRow, TimelineIntelRow,
+    TimelineMailHitRow, TimelineMailRow, TimelineRetroHuntRow, TimelineUpdateRow, TrendCountRow,
+};
+use crate::content::compute_digests;
+use crate::models::domain::email::EmailSearchCriteria;
+use crate::models::domain::hash::{HashKind, HashPivotFilter};
+use crate::models::domain::intelligence::{
+    BasicInfo, Intelligence, IntelligenceFilter, IntelligenceStatus, SortField, SortOrder, SourceType, StatusKey,
+};
+use crate::models::domain::recipient::{RecipientProfileFilter, RecipientRankFilter};
+use crate::models::domain::sender::{SenderKind, SenderProfileFilter};
+use crate::models::domain::spread::SpreadFilter;
+use crate::models::domain::statistics::TrendInterval;
+use crate::models::domain::timeline::TimelineFilter;
+use crate::services::aggregation::count_by;
+
+/// 模拟邮件记录的压缩包提取密码
+pub(crate) const MOCK_EXTRACT_PASSWORD: &str = "infected";
+/// 模拟情报的来源
+const MOCK_SOURCE: &str = "Local";
+/// 模拟情报的来源行业
+const MOCK_INDUSTRY: &str = "[\"金融行业\"]";
+/// 单元测试使用的时间基准（2025-06-02 12:00:00 UTC），测试结果不随运行时刻变化
+#[cfg(test)]
+pub(crate) const FIXTURE_TIME: i64 = 1_748_865_600;
+/// 模拟收件人域名对应的命中单位：域名、单位名称、行业代码、行业名称
+const MOCK_UNITS: &[(&str, &str, &str, &str)] = &[
+    ("example.org", "某城市商业银行", "J66", "货币金融服务"),
+    ("example.net", "某证券公司", "J67", "资本市场服务"),
+    ("example.com", "某大学", "P", "教育"),
+];
+/// 模拟攻击组织的类型
+const MOCK_ACTOR_KINDS: &[(&str, &str)] = &[("海莲花", "APT"), ("银狐", "黑产")];
+
+/// 模拟邮件
+#[derive(Clone)]
+struct MockMail {
+    id: u64,
+    timestamp: i64,
+    action: &'static str,
+    subject: &'static str,
+    sender: &'static str,
+    recipients: &'static [&'static str],
+    client_ip: &'static str,
+    client_ptr: &'static str,
+    tls: &'static str,
+    sasl_login: &'static str,
+    hash_md5: &'static str,
+    body: &'static str,
+}
+
+/// 模拟情报命中
+#[derive(Clone)]
+struct MockHit {
+    mail_id: u64,
+    intelligence_id: &'static str,
+    attribute: &'static str,
+    value: &'static str,
+    intelligence_type: &'static str,
+    urgency: &'static str,
+    threat_actor: &'static str,
+}
+
+/// 模拟数据集
+#[derive(Clone)]
+struct MockDataset {
+    mails: Vec<MockMail>,
+    hits: Vec<MockHit>,
+}
+
+impl MockDataset {
+    /// 以`now`（Unix时间戳，秒）为基准的全部模拟数据
+    fn new(now: i64) -> Self {
+        let mail = |id: u64, hours_ago: i64, action: &'static str, subject: &'static str, sender: &'static str, recipients: &'static [&'static str]| {
+            MockMail {
+                id,
+                timestamp: now - hours_ago * 3600,
+                action,
+                subject,
+                sender,
+                recipients,
+                client_ip: "203.0.113.10",
+                client_ptr: "mail.example.com",
+                tls: "TLSv1.3",
+                sasl_login: "",
+                hash_md5: "",
+                body: "",
+            }
+        };
+        let hit = |mail_id: u64, intelligence_id: &'static str, attribute: &'static str, value: &'static str, intelligence_type: &'static str, urgency: &'static str, threat_actor: &'static str| {
+            MockHit { mail_id, intelligence_id, attribute, value, intelligence_type, urgency, threat_actor }
+        };
+
+        let mut phishing = mail(2, 1, "Quarantine", "【紧急】账户异常，请立即验证", "it-support@examp1e.com", &["finance@example.org"]);
+        phishing.client_ip = "198.51.100.23";
+        phishing.client_ptr = "";
+        phishing.tls = "";
+        phishing.hash_md5 = "d41d8cd98f00b204e9800998ecf8427e";
+        phishing.body = "尊敬的用户：\n您的邮箱账户存在异常登录，已被临时冻结。请在24小时内点击以下链接完成身份验证，\
+                         否则账户将被永久停用。\nhttps://login.examp1e.com/verify?u=finance&t=8f3a21\nIT支持中心";
+        // 与phishing同一模板的变体，没有命中情报
+        let mut variant = mail(8, 5, "Accept", "【紧急】您的账户异常，请尽快验证", "it-admin@examp1e.com", &["ceo@example.org"]);
+        variant.client_ip = "198.51.100.25";
+        variant.client_ptr = "";
+        variant.tls = "";
+        variant.body = "尊敬的用户：\n您的邮箱账户存在异常登录，已被临时冻结。请在12小时内点击以下链接完成身份验证，\
+                        否则账户将被永久停用。\nhttps://login.examp1e.com/verify?u=ceo&t=c07d94\nIT支持中心";
+        let mut outbound = mail(3, 2, "Accept", "Re: 季度报表", "finance@example.org", &["partner@example.net"]);
+        outbound.client_ip = "192.0.2.5";
+        outbound.client_ptr = "";
+        outbound.sasl_login = "finance";
+        let mut spoofed = mail(4, 26, "Reject", "工资调整通知", "it-support@examp1e.com", &["finance@example.org", "ceo@example.org"]);
+        spoofed.client_ip = "198.51.100.24";
+        spoofed.client_ptr = "vps-24.examp1e.com";
+        spoofed.tls = "TLSv1.2";
+
+        Self {
+            mails: vec![
+                mail(1, 0, "Accept", "Test Email", "sender@example.com", &["recipient@example.com"]),
+                phishing,
+                outbound,
+                spoofed,
+                mail(5, 50, "Quarantine", "发票已开具，请查收", "billing@invoice-examp1e.com", &["finance@example.org"]),
+                mail(6, 74, "Accept", "会议纪要", "sender@example.com", &["finance@example.org"]),
+                mail(7, 30, "Discard", "限时优惠", "noreply@bulk.example.net", &["ceo@example.org"]),
+                variant,
+            ],
+            hits: vec![
+                hit(2, "3f2504e0-4f89-41d3-9a0c-0305e82c3301", "Domain", "examp1e.com", "钓鱼欺诈", "High", "海莲花"),
+                hit(2, "6fa459ea-ee8a-4ca4-894e-db77e160355e", "UrlDomain", "login.examp1e.com", "仿冒域名", "Medium", "海莲花"),
+                hit(4, "16fd2706-8baf-433b-82eb-8c7fada847da", "EmailAddress", "it-support@examp1e.com", "钓鱼欺诈", "High", "银狐"),
+                // 与模拟PDF附件的MD5相同
+                hit(5, "7c9e6679-7425-40de-944b-e07fc1f90ae7", "Md5", "2175a8a09d67ab1f0a76b161514432ca", "恶意附件", "High", "海莲花"),
+                hit(7, "886313e1-3b8a-4372-9b90-0c9aee199e5d", "EmailAddress", "noreply@bulk.example.net", "傀儡账号", "Low", ""),
+            ],
+        }
+    }
+
+    /// 只保留时间范围内的邮件及其命中记录
+    fn within(&self, start_time: DateTime<Utc>, end_time: DateTime<Utc>) -> Self {
+        let mut dataset = self.clone();
+        let in_window = |timestamp: i64| timestamp >= start_time.timestamp() && timestamp <= end_time.timestamp();
+        dataset.mails.retain(|mail| in_window(mail.timestamp));
+        let ids: HashSet<u64> = dataset.mails.iter().map(|mail| mail.id).collect();
+        dataset.hits.retain(|hit| ids.contains(&hit.mail_id));
+        dataset
+    }
+
+    /// 命中情报的邮件ID
+    fn hit_ids(&self) -> HashSet<u64> {
+        self.hits.iter().map(|hit| hit.mail_id).collect()
+    }
+
+    /// 邮件检测时间
+    fn mail_time(&self, mail_id: u64) -> Option<i64> {
+        self.mails.iter().find(|mail| mail.id == mail_id).map(|mail| mail.timestamp)
+    }
+
+    /// 一组命中记录的看板计数，受影响的收件人取命中邮件的收件人，模拟情报都来自同一来源
+    fn hit_summary<'a>(&self, hits: impl IntoIterator<Item = &'a MockHit>) -> HitSummaryRow {
+        let hits: Vec<&MockHit> = hits.into_iter().collect();
+        let mail_ids: HashSet<u64> = hits.iter().map(|hit| hit.mail_id).collect();
+        let recipients: HashSet<&str> = self
+            .mails
+            .iter()
+            .filter(|mail| mail_ids.contains(&mail.id))
+            .flat_map(|mail| mail.recipients.iter().copied())
+            .collect();
+        let units: HashSet<&str> = recipients
+            .iter()
+            .filter_map(|address| address.split_once('@').map(|(_, domain)| domain))
+            .collect();
+        let intelligence: HashSet<&str> = hits.iter().map(|hit| hit.intelligence_id).collect();
+        HitSummaryRow {
+            hit_mails: mail_ids.len() as u64,
+            recipients: recipients.len() as u64,
+            units: units.len() as u64,
+            intelligence: intelligence.len() as u64,
+            sources: u64::from(!hits.is_empty()),
+        }
+    }
+}
+
+/// 模拟情报的生命周期
+struct MockLifecycle {
+    /// 过期时间
+    expire_secs: u32,
+    /// 更新记录，按时间倒序
+    updates: Vec<TimelineUpdateRow>,
+    /// 处置变更，按时间倒序
+    dispositions: Vec<TimelineDispositionRow>,
+    /// 回溯排查结果
+    retro_hunts: Vec<TimelineRetroHuntRow>,
+}
+
+/// 按时间分桶计数，返回（邮件数, 命中情报的邮件数）
+fn trend<'a>(
+    interval: TrendInterval,
+    mails: impl IntoIterator<Item = &'a MockMail>,
+    hit_ids: &HashSet<u64>,
+) -> Vec<TrendCountRow> {
+    let mut buckets: HashMap<i64, (u64, u64)> = HashMap::new();
+    for mail in mails {
+        let entry = buckets.entry(interval.bucket_start(mail.timestamp)).or_default();
+        entry.0 += 1;
+        if hit_ids.contains(&mail.id) {
+            entry.1 += 1;
+        }
+    }
+    buckets
+        .into_iter()
+        .map(|(bucket, (total, hits))| TrendCountRow {
+            bucket_secs: bucket as u32,
+            total,
+            hits,
+        })
+        .collect()
+}
+
+/// 一组时间的数量及首末时间
+fn span(times: &[u32]) -> CountSpanRow {
+    CountSpanRow {
+        count: times.len() as u64,
+        first_secs: times.iter().copied().min().unwrap_or(0),
+        last_secs: times.iter().copied().max().unwrap_or(0),
+    }
+}
+
+/// 基于模拟数据集的内存存储库，用于内存模式和服务的单元测试
+pub struct InMemoryRepository {
+    /// 模拟数据集
+    dataset: MockDataset,
+    /// 数据集的时间基准（Unix时间戳，秒）
+    now: i64,
+    /// 附件记录与存储中的内容
+    attachments: Vec<MockAttachment>,
+    /// 模拟邮件与会话邮件的原始邮件
+    messages: Vec<MockMessage>,
+    /// 隔离处置请求的最新状态，按处置请求ID索引
+    quarantine_actions: RwLock<HashMap<String, QuarantineActionRow>>,
+    /// 隔离处置审计记录，按写入顺序
+    quarantine_audit: RwLock<Vec<QuarantineAuditRow>>,
+}
+
+impl InMemoryRepository {
+    /// 以固定时刻`FIXTURE_TIME`为基准的内存存储库，供单元测试使用
+    #[cfg(test)]
+    pub(crate) fn fixture() -> Self {
+        Self::new(DateTime::from_timestamp(FIXTURE_TIME, 0).expect("固定时间基准有效"))
+    }
+
+    /// 以`now`为时间基准创建内存存储库，同一基准下的查询结果固定不变
+    pub fn new(now: DateTime<Utc>) -> Self {
+        let now = now.timestamp();
+        let dataset = MockDataset::new(now);
+        let messages = dataset.mails.iter().map(Self::message).chain(thread_messages(now)).collect();
+        Self {
+            dataset,
+            now,
+            attachments: mock_attachments(),
+            messages,
+            quarantine_actions: RwLock::new(HashMap::new()),
+            quarantine_audit: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// 模拟情报的生命周期：过期时间、更新记录、处置变更和回溯排查结果，情报没有命中时返回None
+    ///
+    /// 以最早命中的邮件时间作为首次发现时间
+    fn lifecycle(&self, intel_id: &str) -> Option<MockLifecycle> {
+        let hits: Vec<&MockHit> = self.dataset.hits.iter().filter(|hit| hit.intelligence_id == intel_id).collect();
+        let description = hits.first()?.intelligence_type;
+        let first_secs = hits
+            .iter()
+            .filter_map(|hit| self.dataset.mail_time(hit.mail_id))
+            .min()
+            .unwrap_or_default();
+
+        let at = |minutes: i64| (first_secs + minutes * 60) as u32;
+        let update = |minutes: i64, description: &str| TimelineUpdateRow {
+            intel_id: intel_id.to_string(),
+            update_secs: at(minutes),
+            description_text: description.to_string(),
+        };
+        let disposition = |minutes: i64, name: &str, enabled: u8, operator: &str, reason: &str| TimelineDispositionRow {
+            intel_id: intel_id.to_string(),
+            disposition_name: name.to_string(),
+            enabled,
+            operator: operator.to_string(),
+            reason: reason.to_string(),
+            created_secs: at(minutes),
+        };
+
+        Some(match intel_id {
+            "3f2504e0-4f89-41d3-9a0c-0305e82c3301" => MockLifecycle {
+                expire_secs: at(30 * 24 * 60),
+                updates: vec![update(20, "钓鱼欺诈，仿冒企业邮箱登录页面"), update(0, description)],
+                dispositions: vec![
+                    disposition(30, "report", 1, "analyst01", "上报至情报平台"),
+                    disposition(25, "black", 1, "analyst01", "确认仿冒登录页面"),
+                ],
+                retro_hunts: vec![TimelineRetroHuntRow {
+                    intel_id: intel_id.to_string(),
+                    job_id: "retro-0001".to_string(),
+                    operator: "analyst01".to_string(),
+                    scan_start_secs: at(-7 * 24 * 60),
+                    scan_end_secs: at(35),
+                    scanned_mails: 8,
+                    matched_mails: 1,
+                    finished_secs: at(40),
+                }],
+            },
+            // 营销账号，误报加白后已过期
+            "886313e1-3b8a-4372-9b90-0c9aee199e5d" => MockLifecycle {
+                expire_secs: at(24 * 60),
+                updates: vec![update(0, description)],
+                dispositions: vec![disposition(60, "white", 1, "analyst02", "营销邮件，误报")],
+                retro_hunts: Vec::new(),
+            },
+            _ => MockLifecycle {
+                expire_secs: at(30 * 24 * 60),
+                updates: vec![update(0, description)],
+                dispositions: Vec::new(),
+                retro_hunts: Vec::new(),
+            },
+        })
+    }
+
+    /// 情报当前的处置状态，以每种处置的最新记录为准
+    fn status(&self, intel_id: &str) -> IntelligenceStatus {
+        let dispositions = self.lifecycle(intel_id).map(|lifecycle| lifecycle.dispositions).unwrap_or_default();
+        // 处置记录按时间倒序，第一条即最新记录
+        let enabled = |name: &str| {
+            dispositions
+                .iter()
+                .find(|row| row.disposition_name == name)
+                .is_some_and(|row| row.enabled != 0)
+        };
+        IntelligenceStatus {
+            is_white: enabled("white"),
+            is_black: enabled("black"),
+            is_report: enabled("report"),
+        }
+    }
+
+    /// 模拟邮件的原始邮件
+    fn message(mail: &MockMail) -> MockMessage {
+        let recipients = mail.recipients.join(", ");
+        let message_id = format!("<mock-{}@example.org>", mail.id);
+        let headers = [
+            ("From", mail.sender),
+            ("To", recipients.as_str()),
+            ("Subject", mail.subject),
+            ("Message-ID", message_id.as_str()),
+        ];
+        let body = if mail.body.is_empty() { "邮件内容" } else { mail.body };
+        MockMessage::new(mail.id, &headers, mail.timestamp, body)
+    }
+
+    /// 模拟会话候选邮件，与`thread_messages`中的原始邮件对应
+    fn thread_rows(&self) -> Vec<ThreadMailRow> {
+        let row = |id: u64, days_ago: i64, action: &str, subject: &str, from: &str, hit: u8| ThreadMailRow {
+            id,
+            action_name: action.to_string(),
+            timestamp_secs: (self.now - days_ago * 86400) as u32,
+            subject: subject.to_string(),
+            display_from: from.to_string(),
+            hit,
+        };
+        vec![
+            row(101, 5, "Accept", "付款安排", "ceo@example.org", 0),
+            row(102, 4, "Accept", "Re: 付款安排", "finance@example.org", 0),
+            row(103, 3, "Accept", "RE: 付款安排", "ceo@examp1e.org", 1),
+            row(104, 2, "Accept", "回复：RE: 付款安排", "finance@example.org", 0),
+            row(105, 1, "Accept", "Fwd: 付款安排", "finance@example.org", 0),
+        ]
+    }
+
+    /// 邮件的搜索投影行，第一个收件人作为显示收件人，全部收件人以逗号连接作为信封收件人
+    fn search_row(mail: &MockMail) -> MailSearchRow {
+        let to = mail.recipients.first().copied().unwrap_or_default();
+        let (account, domain) = to.split_once('@').unwrap_or_default();
+        let envelope = |part: fn((&'static str, &'static str)) -> &'static str| {
+            mail.recipients
+                .iter()
+                .filter_map(|recipient| recipient.split_once('@').map(part))
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        MailSearchRow {
+            id: mail.id,
+            action_name: mail.action.to_string(),
+            timestamp_secs: mail.timestamp as u32,
+            subject: mail.subject.to_string(),
+            display_from: mail.sender.to_string(),
+            display_to_address: to.to_string(),
+            display_to_account: account.to_string(),
+            display_to_domain: domain.to_string(),
+            sasl_login: mail.sasl_login.to_string(),
+            client_ip: mail.client_ip.to_string(),
+            client_envelope_from_address: mail.sender.to_string(),
+            client_envelope_to_address: mail.recipients.join(","),
+            client_envelope_to_account: envelope(|(account, _)| account),
+            client_envelope_to_domain: envelope(|(_, domain)| domain),
+            tls: mail.tls.to_string(),
+            protocol_version: if mail.tls.is_empty() { "ESMTP" } else { "ESMTPS" }.to_string(),
+            text_body: mail.body.to_string(),
+            hash_sha1: String::new(),
+            hash_sha256: String::new(),
+            hash_md5: mail.hash_md5.to_string(),
+            direction: if mail.sasl_login.is_empty() { "inbound" } else { "outbound" }.to_string(),
+        }
+    }
+
+    /// 邮件是否来自该发件人
+    fn sender_matches(filter: &SenderProfileFilter, mail: &MockMail) -> bool {
+        match filter.kind {
+            SenderKind::Address => mail.sender == filter.sender,
+            SenderKind::Domain => mail.sender.rsplit('@').next() == Some(filter.sender.as_str()),
+        }
+    }
+}
+
+#[async_trait]
+impl IntelligenceHitRepository for InMemoryRepository {
+    async fn list_intelligence(&self, filter: &IntelligenceFilter) -> DbResult<(u64, Vec<Intelligence>)> {
+        // 模拟情报均为本地情报
+        if filter.source == Some(SourceType::Cloud) {
+            return Ok((0, Vec::new()));
+        }
+        let keyword = filter
+            .filter
+            .as_deref()
+            .map(|keyword| keyword.trim().to_lowercase())
+            .filter(|keyword| !keyword.is_empty());
+        let dataset = self.dataset.within(filter.start_time, filter.end_time);
+
+        let mut grouped: BTreeMap<&str, Vec<&MockHit>> = BTreeMap::new();
+        for hit in &dataset.hits {
+            grouped.entry(hit.intelligence_id).or_default().push(hit);
+        }
+        let mut items: Vec<Intelligence> = grouped
+            .into_iter()
+            .filter_map(|(id, hits)| {
+                let first = hits[0];
+                let intelligence_type = filter
+                    .intelligence_type
+                    .as_ref()
+                    .filter(|types| !types.is_empty())
+                    .map_or(Some(()), |types| {
+                        types
+                            .iter()
+                            .any(|(intelligence_type, sub_types)| {
+                                intelligence_attributes(intelligence_type).any(|attribute| attribute == first.attribute)
+                                    && (sub_types.is_empty() || sub_types.iter().any(|sub| sub == first.intelligence_type))
+                            })
+                            .then_some(())
+                    });
+                intelligence_type?;
+                if keyword.as_ref().is_some_and(|keyword| !first.value.to_lowercase().contains(keyword)) {
+                    return None;
+                }
+                let status = self.status(id);
+                let disposed = |key: &StatusKey| match key {
+                    StatusKey::IsWhite => status.is_white,
+                    StatusKey::IsBlack => status.is_black,
+                    StatusKey::IsReport => status.is_report,
+                };
+                if filter.status.iter().any(|(key, enabled)| disposed(key) != *enabled) {
+                    return None;
+                }
+
+                let mail_ids: HashSet<u64> = hits.iter().map(|hit| hit.mail_id).collect();
+                let recipients: HashSet<&str> = dataset
+                    .mails
+                    .iter()
+                    .filter(|mail| mail_ids.contains(&mail.id))
+                    .flat_map(|mail| mail.recipients.iter().copied())
+                    .collect();
+                let times: Vec<i64> = mail_ids.iter().filter_map(|id| dataset.mail_time(*id)).collect();
+                let time = |secs: Option<&i64>| {
+                    secs.and_then(|secs| DateTime::from_timestamp(*secs, 0)).unwrap_or_else(Utc::now)
+                };
+                let lifecycle = self.lifecycle(id);
+                let intelligence_id = Uuid::parse_str(id).ok()?;
+                Some(Intelligence {
+                    id: intelligence_id,
+                    intelligence_id,
+                    value: first.value.to_string(),
+                    description: lifecycle
+                        .and_then(|lifecycle| lifecycle.updates.into_iter().next())
+                        .map(|update| update.description_text)
+                        .unwrap_or_default(),
+                    intelligence_type: super::intelligence_type(first.attribute)?,
+                    sub_type: first.intelligence_type.to_string(),
+                    source: "本地情报".to_string(),
+                    urgency: match first.urgency {
+                        "High" => "高",
+                        "Medium" => "中",
+                        "Low" => "低",
+                        other => other,
+                    }
+                    .to_string(),
+                    hit_emails: mail_ids.len() as i32,
+                    impact_users: recipients.len() as i32,
+                    first_found_time: time(times.iter().min()),
+                    latest_hits_time: time(times.iter().max()),
+                    status,
+                    basic_info: BasicInfo {
+                        file_name: None,
+                        file_path: None,
+                        file_size: None,
+                        file_type: None,
+                    },
+                    contribution_unit: 0,
+                    industry_distribution: Vec::new(),
+                })
+            })
+            .collect();
+
+        items.sort_by(|a, b| {
+            let ordering = match filter.sort_by {
+                SortField::LatestHitsTime => a.latest_hits_time.cmp(&b.latest_hits_time),
+                SortField::HitEmails => a.hit_emails.cmp(&b.hit_emails),
+                SortField::ImpactUsers => a.impact_users.cmp(&b.impact_users),
+            };
+            match filter.sort_order {
+                SortOrder::Asc => ordering,
+                SortOrder::Desc => ordering.reverse(),
+            }
+            .then(a.intelligence_id.cmp(&b.intelligence_id))
+        });
+
+        let total = items.len() as u64;
+        let items = items
+            .into_iter()
+            .skip(filter.page.saturating_sub(1) * filter.page_size)
+            .take(filter.page_size)
+            .collect();
+        Ok((total, items))
+    }
+
+    async fn timeline(&self, filter: &TimelineFilter, max_intelligence: usize) -> DbResult<TimelineHits> {
+        let dataset = &self.dataset;
+        let mut ids: Vec<String> = filter.intelligence_ids.iter().map(Uuid::to_string).collect();
+        if let Some(actor) = &filter.threat_actor {
+            let mut actor_intel = count_by(
+                dataset
+                    .hits
+                    .iter()
+                    .filter(|hit| hit.threat_actor.eq_ignore_ascii_case(actor))
+                    .map(|hit| hit.intelligence_id.to_string()),
+            );
+            actor_intel.truncate(max_intelligence);
+            for row in actor_intel {
+                if !ids.contains(&row.name) {
+                    ids.push(row.name);
+                }
+            }
+        }
+        let hits: Vec<&MockHit> = dataset.hits.iter().filter(|hit| ids.iter().any(|id| id == hit.intelligence_id)).collect();
+
+        let mut intel = Vec::new();
+        let mut updates = Vec::new();
+        for id in &ids {
+            let intel_hits: Vec<&&MockHit> = hits.iter().filter(|hit| hit.intelligence_id == id).collect();
+            let (Some(first_hit), Some(lifecycle)) = (intel_hits.first(), self.lifecycle(id)) else {
+                continue;
+            };
+            let first_secs = intel_hits
+                .iter()
+                .filter_map(|hit| dataset.mail_time(hit.mail_id))
+                .min()
+                .unwrap_or_default();
+            intel.push(TimelineIntelRow {
+                intel_id: id.clone(),
+                count: intel_hits.len() as u64,
+                first_secs: first_secs as u32,
+                source_name: MOCK_SOURCE.to_string(),
+                industry: MOCK_INDUSTRY.to_string(),
+                expire_secs: lifecycle.expire_secs,
+                description_text: lifecycle
+                    .updates
+                    .first()
+                    .map(|update| update.description_text.clone())
+                    .unwrap_or_default(),
+                attribute_name: first_hit.attribute.to_string(),
+                value_text: first_hit.value.to_string(),
+            });
+            updates.extend(lifecycle.updates);
+        }
+        if intel.is_empty() {
+            return Ok(TimelineHits::empty());
+        }
+        updates.sort_by_key(|update| std::cmp::Reverse(update.update_secs));
+
+        let mut matched: Vec<&MockMail> = dataset
+            .mails
+            .iter()
+            .filter(|mail| hits.iter().any(|hit| hit.mail_id == mail.id))
+            .filter(|mail| {
+                filter.start_time.is_none_or(|start| mail.timestamp >= start.timestamp())
+                    && filter.end_time.is_none_or(|end| mail.timestamp <= end.timestamp())
+                    && (filter.actions.is_empty() || filter.actions.iter().any(|status| status.as_str().eq_ignore_ascii_case(mail.action)))
+                    && filter.recipient_domain.as_ref().is_none_or(|domain| {
+                        mail.recipients
+                            .iter()
+                            .any(|recipient| recipient.rsplit_once('@').is_some_and(|(_, d)| d.eq_ignore_ascii_case(domain)))
+                    })
+            })
+            .collect();
+        matched.sort_by(|a, b| b.timestamp.cmp(&a.timestamp).then(b.id.cmp(&a.id)));
+        let times: Vec<u32> = matched.iter().map(|mail| mail.timestamp as u32).collect();
+
+        let (mails, newer_secs, buckets) = match filter.interval {
+            Some(interval) => {
+                let mut counts: BTreeMap<(i64, &str), u64> = BTreeMap::new();
+                for mail in &matched {
+                    *counts.entry((interval.bucket_start(mail.timestamp), mail.action)).or_default() += 1;
+                }
+                let buckets = counts
+                    .into_iter()
+                    .map(|((bucket, action), count)| TimelineBucketRow {
+                        bucket_secs: bucket as u32,
+                        action_name: action.to_string(),
+                        count,
+                    })
+                    .collect();
+                (Vec::new(), None, buckets)
+            }
+            None => {
+                let offset = ((filter.page - 1) * filter.page_size) as usize;
+                let newer_secs = offset
+                    .checked_sub(1)
+                    .and_then(|index| matched.get(index))
+                    .map(|mail| mail.timestamp as u32);
+                let mails = matched
+                    .iter()
+                    .skip(offset)
+                    .take(filter.page_size as usize)
+                    .map(|mail| TimelineMailRow {
+                        id: mail.id,
+                        action_name: mail.action.to_string(),
+                        timestamp_secs: mail.timestamp as u32,
+                        sender_address: mail.sender.to_string(),
+                        recipient_list: mail.recipients.iter().map(|recipient| recipient.to_string()).collect(),
+                    })
+                    .collect();
+                (mails, newer_secs, Vec::new())
+            }
+        };
+        let mail_hits = hits
+            .iter()
+            .filter(|hit| mails.iter().any(|mail: &TimelineMailRow| mail.id == hit.mail_id))
+            .map(|hit| TimelineMailHitRow {
+                mail_id: hit.mail_id,
+                intel_id: hit.intelligence_id.to_string(),
+            })
+            .collect();
+
+        Ok(TimelineHits {
+            intel,
+            span: span(&times),
+            mails,
+            mail_hits,
+            newer_secs,
+            buckets,
+            updates,
+        })
+    }
+
+    /// 快照由情报的命中邮件按时间累计得到，收件人域名对应命中单位；
+    /// 相邻快照交替使用snake_case数组与camelCase带units外层的对象，覆盖不同版本的字段格式
+    async fn spread_snapshots(&self, filter: &SpreadFilter) -> DbResult<(u64, Vec<SpreadSnapshotRow>)> {
+        let id = filter.intelligence_id.to_string();
+        let mut hit_mails: Vec<&MockMail> = self
+            .dataset
+            .hits
+            .iter()
+            .filter(|hit| hit.intelligence_id == id)
+            .filter_map(|hit| self.dataset.mails.iter().find(|mail| mail.id == hit.mail_id))
+            .collect();
+        hit_mails.sort_by_key(|mail| (mail.timestamp, mail.id));
+        let count = hit_mails.len() as u64;
+
+        // 收件人域名 -> (首次命中, 最近命中, 命中数)
+        let mut units: BTreeMap<&str, (i64, i64, u64)> = BTreeMap::new();
+        let mut snapshots = Vec::with_capacity(hit_mails.len());
+        for (index, mail) in hit_mails.iter().enumerate() {
+            let domains: HashSet<&str> = mail
+                .recipients
+                .iter()
+                .filter_map(|recipient| recipient.split_once('@').map(|(_, domain)| domain))
+                .collect();
+            for domain in domains {
+                let unit = units.entry(domain).or_insert((mail.timestamp, mail.timestamp, 0));
+                unit.1 = mail.timestamp;
+                unit.2 += 1;
+            }
+
+            let camel_case = index % 2 == 1;
+            let records: Vec<serde_json::Value> = units
+                .iter()
+                .map(|(domain, (first, last, hits))| {
+                    let (unit_name, code, industry) = MOCK_UNITS
+                        .iter()
+                        .find(|(unit_domain, ..)| unit_domain == domain)
+                        .map_or((*domain, "", ""), |(_, name, code, industry)| (*name, *code, *industry));
+                    if camel_case {
+                        serde_json::json!({
+                            "unitName": unit_name,
+                            "industry": {"code": code, "name": industry},
+                            "hitCount": hits,
+                            "firstHitTime": first,
+                            "lastHitTime": last,
+                        })
+                    } else {
+                        let time = |secs: i64| {
+                            DateTime::from_timestamp(secs, 0)
+                                .map(|time| time.format("%Y-%m-%d %H:%M:%S").to_string())
+                                .unwrap_or_default()
+                        };
+                        serde_json::json!({
+                            "unit_name": unit_name,
+                            "industry": code,
+                            "hit_count": hits,
+                            "first_hit_time": time(*first),
+                            "last_hit_time": time(*last),
+                        })
+                    }
+                })
+                .collect();
+            let joint = if camel_case {
+                serde_json::json!({"units": records})
+            } else {
+                serde_json::Value::Array(records)
+            };
+            snapshots.push(SpreadSnapshotRow {
+                timestamp_secs: mail.timestamp as u32,
+                joint_text: joint.to_string(),
+            });
+        }
+        Ok((count, snapshots))
+    }
+
+    async fn campaign_hits(&self, mail_ids: &[u64]) -> DbResult<Vec<CampaignHitRow>> {
+        Ok(self
+            .dataset
+            .hits
+            .iter()
+            .filter(|hit| mail_ids.contains(&hit.mail_id))
+            .map(|hit| CampaignHitRow {
+                mail_id: hit.mail_id,
+                intel_id: hit.intelligence_id.to_string(),
+                attribute_name: hit.attribute.to_string(),
+                value_text: hit.value.to_string(),
+                type_name: hit.intelligence_type.to_string(),
+                urgency_name: hit.urgency.to_string(),
+                actor_name: hit.threat_actor.to_string(),
+            })
+            .collect())
+    }
+
+    async fn hash_intelligence(&self, attribute: &str, hash: &str) -> DbResult<Vec<HashIntelligenceRow>> {
+        let hits: Vec<&MockHit> = self
+            .dataset
+            .hits
+            .iter()
+            .filter(|hit| hit.attribute == attribute && hit.value.eq_ignore_ascii_case(hash))
+            .collect();
+        let intel_mails: HashSet<u64> = hits.iter().map(|hit| hit.mail_id).collect();
+        let times: Vec<u32> = intel_mails
+            .iter()
+            .filter_map(|id| self.dataset.mail_time(*id))
+            .map(|secs| secs as u32)
+            .collect();
+        let times = span(&times);
+
+        Ok(hits
+            .first()
+            .map(|hit| HashIntelligenceRow {
+                intel_id: hit.intelligence_id.to_string(),
+                attribute_name: hit.attribute.to_string(),
+                type_name: hit.intelligence_type.to_string(),
+                urgency_name: hit.urgency.to_string(),
+                description_text: "钓鱼邮件投递的恶意文档".to_string(),
+                actor_name: hit.threat_actor.to_string(),
+                hit_mails: intel_mails.len() as u64,
+                first_secs: times.first_secs,
+                last_secs: times.last_secs,
+            })
+            .into_iter()
+            .collect())
+    }
+
+    async fn mail_intelligence(&self, mail_id: u64) -> DbResult<Vec<MailIntelligenceValueRow>> {
+        // 与预览用的模拟正文对应
+        if mail_id == 1 {
+            return Ok(vec![MailIntelligenceValueRow {
+                intelligence_id: "00000000-0000-0000-0000-000000000001".to_string(),
+                attribute: "domain".to_string(),
+                value: "example.com".to_string(),
+            }]);
+        }
+        let mut values: Vec<MailIntelligenceValueRow> = Vec::new();
+        for hit in self.dataset.hits.iter().filter(|hit| hit.mail_id == mail_id) {
+            if !values.iter().any(|value| value.intelligence_id == hit.intelligence_id) {
+                values.push(MailIntelligenceValueRow {
+                    intelligence_id: hit.intelligence_id.to_string(),
+                    attribute: hit.attribute.to_string(),
+                    value: hit.value.to_string(),
+                });
+            }
+        }
+        Ok(values)
+    }
+
+    async fn url_intelligence(&self, attributes: &[&str], candidates: &[String]) -> DbResult<Vec<MailIntelligenceValueRow>> {
+        let mut values: Vec<MailIntelligenceValueRow> = Vec::new();
+        for hit in &self.dataset.hits {
+            if attributes.contains(&hit.attribute)
+                && candidates.contains(&hit.value.to_lowercase())
+                && !values.iter().any(|value| value.intelligence_id == hit.intelligence_id)
+            {
+                values.push(MailIntelligenceValueRow {
+                    intelligence_id: hit.intelligence_id.to_string(),
+                    attribute: hit.attribute.to_string(),
+                    value: hit.value.to_string(),
+                });
+            }
+        }
+        Ok(values)
+    }
+}
+
+#[async_trait]
+impl MailInfoRepository for InMemoryRepository {
+    async fn search_mails(&self, criteria: &EmailSearchCriteria) -> DbResult<(u64, Vec<MailSearchRow>)> {
+        let terms = search::terms(criteria);
+        let hit_mails: Option<HashSet<u64>> = criteria.intelligence_id.as_ref().map(|intelligence_id| {
+            self.dataset
+                .hits
+                .iter()
+                .filter(|hit| hit.intelligence_id == intelligence_id)
+                .map(|hit| hit.mail_id)
+                .collect()
+        });
+        let mut matched: Vec<MailSearchRow> = self
+            .dataset
+            .mails
+            .iter()
+            .map(Self::search_row)
+            .filter(|row| {
+                criteria.start_time.is_none_or(|start| i64::from(row.timestamp_secs) >= start.timestamp())
+                    && criteria.end_time.is_none_or(|end| i64::from(row.timestamp_secs) <= end.timestamp())
+                    && terms.iter().all(|term| term.matches(row))
+                    && hit_mails.as_ref().is_none_or(|mails| mails.contains(&row.id))
+                    && criteria.mail_ids.as_ref().is_none_or(|ids| ids.contains(&row.id))
+            })
+            .collect();
+        matched.sort_by(|a, b| b.timestamp_secs.cmp(&a.timestamp_secs).then(b.id.cmp(&a.id)));
+
+        let total = matched.len() as u64;
+        let offset = ((criteria.page - 1) * criteria.page_size) as usize;
+        let rows = matched
+            .into_iter()
+            .skip(offset)
+            .take(criteria.page_size as usize)
+            .collect();
+        Ok((total, rows))
+    }
+
+    async fn thread_seed(&self, mail_id: u64) -> DbResult<Option<ThreadMailRow>> {
+        Ok(self.thread_rows().into_iter().find(|row| row.id == mail_id))
+    }
+
+    /// 模拟邮件都属于同一会话，全部作为候选
+    async fn thread_candidates(&self, _seed: &ThreadMailRow, _subject: &str) -> DbResult<Vec<ThreadMailRow>> {
+        Ok(self.thread_rows())
+    }
+
+    async fn campaign_mails(
+        &self,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+        limit: u64,
+    ) -> DbResult<Vec<CampaignMailRow>> {
+        let mut mails = self.dataset.within(start_time, end_time).mails;
+        mails.sort_by_key(|mail| std::cmp::Reverse(mail.timestamp));
+        Ok(mails
+            .iter()
+            .take(limit as usize)
+            .map(|mail| CampaignMailRow {
+                id: mail.id,
+                action_name: mail.action.to_string(),
+                timestamp_secs: mail.timestamp as u32,
+                subject: mail.subject.to_string(),
+                text_body: mail.body.to_string(),
+                html_body: String::new(),
+                display_from: mail.sender.to_string(),
+                client_envelope_from_address: mail.sender.to_string(),
+                recipient_list: mail.recipients.iter().map(|address| address.to_string()).collect(),
+            })
+            .collect())
+    }
+
+    async fn hash_mails(&self, filter: &HashPivotFilter, attachment_mails: &[u64]) -> DbResult<HashMails> {
+        let dataset = &self.dataset;
+        let attribute = filter.kind.intelligence_attribute();
+        let intel_mails: HashSet<u64> = dataset
+            .hits
+            .iter()
+            .filter(|hit| Some(hit.attribute) == attribute && hit.value.eq_ignore_ascii_case(&filter.hash))
+            .map(|hit| hit.mail_id)
+            .collect();
+
+        // 模拟邮件只记录了MD5
+        let mail_match = |mail: &MockMail| {
+            filter.kind == HashKind::Md5 && !mail.hash_md5.is_empty() && mail.hash_md5.eq_ignore_ascii_case(&filter.hash)
+        };
+        let mut mails: Vec<&MockMail> = dataset
+            .mails
+            .iter()
+            .filter(|mail| mail_match(mail) || intel_mails.contains(&mail.id) || attachment_mails.contains(&mail.id))
+            .collect();
+        mails.sort_by_key(|mail| std::cmp::Reverse(mail.timestamp));
+        let times: Vec<u32> = mails.iter().map(|mail| mail.timestamp as u32).collect();
+
+        Ok(HashMails {
+            summary: span(&times),
+            recipients: count_by(
+                mails
+                    .iter()
+                    .flat_map(|mail| mail.recipients.iter().map(|address| address.to_string())),
+            ),
+            mails: mails
+                .into_iter()
+                .take(filter.limit as usize)
+                .map(|mail| HashMailRow {
+                    id: mail.id,
+                    action_name: mail.action.to_string(),
+                    timestamp_secs: mail.timestamp as u32,
+                    subject: mail.subject.to_string(),
+                    display_from: mail.sender.to_string(),
+                    client_envelope_from_address: mail.sender.to_string(),
+                    recipient_list: mail.recipients.iter().map(|address| address.to_string()).collect(),
+                    mail_match: u8::from(mail_match(mail)),
+                    intel_match: u8::from(intel_mails.contains(&mail.id)),
+                })
+                .collect(),
+        })
+    }
+
+    /// 模拟邮件没有记录邮件文件哈希
+    async fn mail_file(&self, mail_id: u64) -> DbResult<Option<MailFileRow>> {
+        Ok(self.messages.iter().find(|message| message.mail_id == mail_id).map(|message| MailFileRow {
+            eml_path: message.path.clone(),
+            hash_sha256: compute_digests(&message.data).sha256,
+        }))
+    }
+
+    async fn delivery(&self, mail_id: u64) -> DbResult<Option<MailDeliveryRow>> {
+        Ok(self.dataset.mails.iter().find(|mail| mail.id == mail_id).map(|mail| MailDeliveryRow {
+            action_name: mail.action.to_string(),
+            client_envelope_from_address: mail.sender.to_string(),
+            client_envelope_to_address: mail.recipients.join(","),
+        }))
+    }
+
+    async fn extract_password(&self, mail_id: u64) -> DbResult<Option<String>> {
+        Ok((mail_id == 1).then(|| MOCK_EXTRACT_PASSWORD.to_string()))
+    }
+
+    /// 邮件1使用包含脚本、跟踪像素和表单的模拟正文，用于演示预览的清洗效果
+    async fn mail_body(&self, mail_id: u64) -> DbResult<Option<MailBodyRow>> {
+        if mail_id == 1 {
+            return Ok(Some(MailBodyRow {
+                subject: "Test Email".to_string(),
+                text_body: "您的账户存在异常，请访问 https://example.com/test 进行验证。".to_string(),
+                html_body: "<html><head><script>alert(1)</script></head><body>\
+                            <p onclick=\"steal()\">您的账户存在异常，请访问\
+                            <a href=\"https://example.com/test\">example.com</a> 进行验证。</p>\
+                            <img src=\"https://tracker.example.net/pixel.gif\" alt=\"logo\">\
+                            <form action=\"https://example.com/login\"><input name=\"password\"></form>\
+                            </body></html>".to_string(),
+            }));
+        }
+        Ok(self.dataset.mails.iter().find(|mail| mail.id == mail_id).map(|mail| MailBodyRow {
+            subject: mail.subject.to_string(),
+            text_body: mail.body.to_string(),
+            html_body: String::new(),
+        }))
+    }
+}
+
+#[async_trait]
+impl DispositionRepository for InMemoryRepository {
+    async fn dispositions(&self, intelligence_ids: &[String]) -> DbResult<Vec<TimelineDispositionRow>> {
+        let mut rows: Vec<TimelineDispositionRow> = intelligence_ids
+            .iter()
+            .filter_map(|id| self.lifecycle(id))
+            .flat_map(|lifecycle| lifecycle.dispositions)
+            .collect();
+        rows.sort_by_key(|row| std::cmp::Reverse(row.created_secs));
+        Ok(rows)
+    }
+
+    async fn retro_hunts(&self, intelligence_ids: &[String]) -> DbResult<Vec<TimelineRetroHuntRow>> {
+        let mut rows: Vec<TimelineRetroHuntRow> = intelligence_ids
+            .iter()
+            .filter_map(|id| self.lifecycle(id))
+            .flat_map(|lifecycle| lifecycle.retro_hunts)
+            .collect();
+        rows.sort_by_key(|row| std::cmp::Reverse(row.finished_secs));
+        Ok(rows)
+    }
+}
+
+#[async_trait]
+impl AttachmentRepository for InMemoryRepository {
+    async fn attachments(&self, mail_ids: Option<&[u64]>) -> DbResult<Vec<MailAttachmentRow>> {
+        let mut rows: Vec<MailAttachmentRow> = self
+            .attachments
+            .iter()
+            .map(|attachment| &attachment.row)
+            .filter(|row| mail_ids.is_none_or(|ids| ids.contains(&row.mail_id)))
+            .cloned()
+            .collect();
+        rows.sort_by(|a, b| (a.mail_id, &a.attachment_id).cmp(&(b.mail_id, &b.attachment_id)));
+        Ok(rows)
+    }
+
+    async fn attachment(&self, attachment_id: &str) -> DbResult<Option<MailAttachmentRow>> {
+        Ok(self
+            .attachments
+            .iter()
+            .find(|attachment| attachment.row.attachment_id == attachment_id)
+            .map(|attachment| attachment.row.clone()))
+    }
+
+    async fn attachments_by_hash(&self, kind: HashKind, hash: &str) -> DbResult<Vec<MailAttachmentRow>> {
+        let mut rows = self.attachments(None).await?;
+        rows.retain(|row| {
+            let digest = match kind {
+                HashKind::Md5 => &row.hash_md5,
+                HashKind::Sha1 => &row.hash_sha1,
+                HashKind::Sha256 => &row.hash_sha256,
+            };
+            !digest.is_empty() && digest.eq_ignore_ascii_case(hash)
+        });
+        Ok(rows)
+    }
+}
+
+#[async_trait]
+impl QuarantineRepository for InMemoryRepository {
+    async fn save_action(&self, action: &QuarantineActionRow) -> DbResult<()> {
+        let mut actions = self.quarantine_actions.write().await;
+        let newer = actions
+            .get(&action.action_id)
+            .is_none_or(|stored| stored.version <= action.version);
+        if newer {
+            actions.insert(action.action_id.clone(), action.clone());
+        }
+        Ok(())
+    }
+
+    async fn action(&self, action_id: &str) -> DbResult<Option<QuarantineActionRow>> {
+        let actions = self.quarantine_actions.read().await;
+        Ok(actions.get(action_id).cloned())
+    }
+
+    async fn actions(&self, mail_id: Option<&str>, state: Option<&str>) -> DbResult<Vec<QuarantineActionRow>> {
+        let actions = self.quarantine_actions.read().await;
+        let mut rows: Vec<QuarantineActionRow> = actions
+            .values()
+            .filter(|row| mail_id.is_none_or(|id| row.mail_id == id))
+            .filter(|row| state.is_none_or(|state| row.state == state))
+            .cloned()
+            .collect();
+        rows.sort_by_key(|row| std::cmp::Reverse(row.requested_at));
+        Ok(rows)
+    }
+
+    async fn append_audit(&self, entry: &QuarantineAuditRow) -> DbResult<()> {
+        let mut audit = self.quarantine_audit.write().await;
+        audit.push(entry.clone());
+        Ok(())
+    }
+
+    async fn audit(&self, mail_id: Option<&str>, action_id: Option<&str>) -> DbResult<Vec<QuarantineAuditRow>> {
+        let audit = self.quarantine_audit.read().await;
+        let mut rows: Vec<QuarantineAuditRow> = audit
+            .iter()
+            .filter(|row| mail_id.is_none_or(|id| row.mail_id == id))
+            .filter(|row| action_id.is_none_or(|id| row.action_id == id))
+            .cloned()
+            .collect();
+        rows.sort_by_key(|row| (row.timestamp, row.version));
+        Ok(rows)
+    }
+}
+
+#[async_trait]
+impl BlobStore for InMemoryRepository {
+    async fn open(&self, path: &str) -> DbResult<Option<Blob>> {
+        let message = self.messages.iter().find(|message| message.path == path).map(|message| message.data.clone());
+        Ok(self
+            .attachments
+            .iter()
+            .find(|attachment| attachment.row.file_path == path)
+            .and_then(|attachment| attachment.stored.clone())
+            .or(message)
+            .map(|data| Blob {
+                size: data.len() as u64,
+                reader: Box::new(std::io::Cursor::new(data)),
+            }))
+    }
+}
+
+#[async_trait]
+impl StatisticsRepository for InMemoryRepository {
+    async fn recipient_profile(&self, filter: &RecipientProfileFilter) -> DbResult<RecipientProfileData> {
+        let dataset = self.dataset.within(filter.start_time, filter.end_time);
+        let hit_ids = dataset.hit_ids();
+        let mails: Vec<&MockMail> = dataset
+            .mails
+            .iter()
+            .filter(|mail| mail.recipients.contains(&filter.address.as_str()))
+            .collect();
+        let mail_ids: HashSet<u64> = mails.iter().map(|mail| mail.id).collect();
+        let hits: Vec<&MockHit> = dataset.hits.iter().filter(|hit| mail_ids.contains(&hit.mail_id)).collect();
+        let targeted: Vec<u32> = mails
+            .iter()
+            .filter(|mail| hit_ids.contains(&mail.id))
+            .map(|mail| mail.timestamp as u32)
+            .collect();
+
+        // 攻击组织按命中的邮件数统计，同一封邮件只计一次
+        let actor_mails: HashSet<(&str, u64)> = hits
+            .iter()
+            .filter(|hit| !hit.threat_actor.is_empty())
+            .map(|hit| (hit.threat_actor, hit.mail_id))
+            .collect();
+        let mut actors = count_by(actor_mails.into_iter().map(|(actor, _)| actor.to_string()));
+        actors.truncate(filter.top_actors as usize);
+
+        Ok(RecipientProfileData {
+            actions: count_by(mails.iter().map(|mail| mail.action.to_string())),
+            summary: span(&targeted),
+            types: count_by(hits.iter().map(|hit| hit.intelligence_type.to_string())),
+            urgency: count_by(hits.iter().map(|hit| hit.urgency.to_string())),
+            actors,
+            trend: trend(filter.interval, mails.iter().copied(), &hit_ids),
+        })
+    }
+
+    async fn recipient_rank(&self, filter: &RecipientRankFilter) -> DbResult<Vec<RecipientRankRow>> {
+        let dataset = self.dataset.within(filter.start_time, filter.end_time);
+        let hit_ids = dataset.hit_ids();
+
+        let mut ranks: HashMap<&str, RecipientRankRow> = HashMap::new();
+        for mail in &dataset.mails {
+            for address in mail.recipients {
+                let row = ranks.entry(address).or_insert_with(|| RecipientRankRow {
+                    address: address.to_string(),
+                    received: 0,
+                    targeted: 0,
+                    last_secs: 0,
+                });
+                row.received += 1;
+                if hit_ids.contains(&mail.id) {
+                    row.targeted += 1;
+                    row.last_secs = row.last_secs.max(mail.timestamp as u32);
+                }
+            }
+        }
+
+        let mut rows: Vec<RecipientRankRow> = ranks.into_values().filter(|row| row.targeted > 0).collect();
+        rows.sort_by(|a, b| {
+            b.targeted
+                .cmp(&a.targeted)
+                .then(b.received.cmp(&a.received))
+                .then_with(|| a.address.cmp(&b.address))
+        });
+        rows.truncate(filter.limit as usize);
+        Ok(rows)
+    }
+
+    async fn sender_profile(&self, filter: &SenderProfileFilter) -> DbResult<SenderProfileData> {
+        let lifetime: Vec<u32> = self
+            .dataset
+            .mails
+            .iter()
+            .filter(|mail| Self::sender_matches(filter, mail))
+            .map(|mail| mail.timestamp as u32)
+            .collect();
+
+        let dataset = self.dataset.within(filter.start_time, filter.end_time);
+        let hit_ids = dataset.hit_ids();
+        let mails: Vec<&MockMail> = dataset
+            .mails
+            .iter()
+            .filter(|mail| Self::sender_matches(filter, mail))
+            .collect();
+        let mail_ids: HashSet<u64> = mails.iter().map(|mail| mail.id).collect();
+        let recipients: HashSet<&str> = mails.iter().flat_map(|mail| mail.recipients.iter().copied()).collect();
+
+        let mut sources: HashMap<(&str, &str), SendingSourceRow> = HashMap::new();
+        for mail in &mails {
+            let row = sources
+                .entry((mail.client_ip, mail.client_ptr))
+                .or_insert_with(|| SendingSourceRow {
+                    client_ip: mail.client_ip.to_string(),
+                    client_ptr: mail.client_ptr.to_string(),
+                    count: 0,
+                    last_secs: 0,
+                });
+            row.count += 1;
+            row.last_secs = row.last_secs.max(mail.timestamp as u32);
+        }
+        let mut sources: Vec<SendingSourceRow> = sources.into_values().collect();
+        sources.sort_by(|a, b| b.count.cmp(&a.count).then(b.last_secs.cmp(&a.last_secs)));
+        sources.truncate(filter.top_ips as usize);
+
+        Ok(SenderProfileData {
+            lifetime: span(&lifetime),
+            summary: SenderSummaryRow {
+                total: mails.len() as u64,
+                recipients: recipients.len() as u64,
+                tls_mails: mails.iter().filter(|mail| !mail.tls.is_empty()).count() as u64,
+                authenticated: mails.iter().filter(|mail| !mail.sasl_login.is_empty()).count() as u64,
+                hit_mails: mails.iter().filter(|mail| hit_ids.contains(&mail.id)).count() as u64,
+            },
+            actions: count_by(mails.iter().map(|mail| mail.action.to_string())),
+            sources,
+            tls: count_by(mails.iter().filter(|mail| !mail.tls.is_empty()).map(|mail| mail.tls.to_string())),
+            types: count_by(
+                dataset
+                    .hits
+                    .iter()
+                    .filter(|hit| mail_ids.contains(&hit.mail_id))
+                    .map(|hit| hit.intelligence_type.to_string()),
+            ),
+            trend: trend(filter.interval, mails.iter().copied(), &hit_ids),
+        })
+    }
+
+    async fn hit_summary(&self, start_time: DateTime<Utc>, end_time: DateTime<Utc>) -> DbResult<HitSummaryRow> {
+        let dataset = self.dataset.within(start_time, end_time);
+        Ok(dataset.hit_summary(&dataset.hits))
+    }
+
+    async fn hit_trend(
+        &self,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+        interval: TrendInterval,
+    ) -> DbResult<Vec<HitTrendRow>> {
+        let dataset = self.dataset.within(start_time, end_time);
+        let mut buckets: BTreeMap<i64, Vec<&MockHit>> = BTreeMap::new();
+        for hit in &dataset.hits {
+            if let Some(timestamp) = dataset.mail_time(hit.mail_id) {
+                buckets.entry(interval.bucket_start(timestamp)).or_default().push(hit);
+            }
+        }
+        Ok(buckets
+            .into_iter()
+            .map(|(bucket, hits)| {
+                let summary = dataset.hit_summary(hits);
+                HitTrendRow {
+                    bucket_secs: bucket as u32,
+                    hit_mails: summary.hit_mails,
+                    recipients: summary.recipients,
+                    units: summary.units,
+                    intelligence: summary.intelligence,
+                }
+            })
+            .collect())
+    }
+
+    async fn threat_actors(&self, start_time: DateTime<Utc>, end_time: DateTime<Utc>) -> DbResult<Vec<ThreatActorRow>> {
+        let dataset = self.dataset.within(start_time, end_time);
+        // 攻击组织按命中的邮件数统计，同一封邮件只计一次
+        let actor_mails: HashSet<(&str, u64)> = dataset
+            .hits
+            .iter()
+            .filter(|hit| !hit.threat_actor.is_empty())
+            .map(|hit| (hit.threat_actor, hit.mail_id))
+            .collect();
+        Ok(count_by(actor_mails.into_iter().map(|(actor, _)| actor.to_string()))
+            .into_iter()
+            .map(|row| ThreatActorRow {
+                kind: MOCK_ACTOR_KINDS
+                    .iter()
+                    .find(|(name, _)| *name == row.name)
+                    .map(|(_, kind)| kind.to_string())
+                    .unwrap_or_default(),
+                name: row.name,
+                count: row.count,
+            })
+            .collect())
+    }
+
+    async fn custom_intelligence(
+        &self,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+    ) -> DbResult<CustomIntelligenceRow> {
+        // 模拟情报都是本地情报
+        let total: HashSet<&str> = self.dataset.hits.iter().map(|hit| hit.intelligence_id).collect();
+        let dataset = self.dataset.within(start_time, end_time);
+        let hits: HashSet<&str> = dataset.hits.iter().map(|hit| hit.intelligence_id).collect();
+        Ok(CustomIntelligenceRow {
+            hits: hits.len() as u64,
+            total: total.len() as u64,
+        })
+    }
+}
@@ -0,0 +1,342 @@
+//! 数据访问存储库
+//!
+//! 服务层通过这里的特性读取情报命中、邮件信息、处置记录和统计聚合，不直接依赖数据库连接。
+//! `ClickHouseRepository`查询数据库，`InMemoryRepository`基于固定的模拟数据集计算，
+//! 两者返回相同结构的结果，内存模式下各接口的数据彼此一致。`ManagedRepository`按连接状态在两者之间切换。
+//! 附件等文件内容通过`BlobStore`读取，数据库模式下来自`FileBlobStore`，内存模式下来自模拟数据集
+
+mod blob;
+mod clickhouse;
+mod fixtures;
+mod managed;
+mod memory;
+mod search;
+
+use std::sync::Arc;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use tokio::io::{AsyncRead, AsyncSeek};
+
+use crate::db::models::AttributeType;
+use crate::db::{
+    CampaignHitRow, CampaignMailRow, ClickHouseClient, ConnectionManager, CountSpanRow, CustomIntelligenceRow, DbResult,
+    HashIntelligenceRow, HashMailRow, HitSummaryRow, HitTrendRow, MailAttachmentRow, QuarantineActionRow, QuarantineAuditRow, MailBodyRow, MailFileRow, MailDeliveryRow, MailIntelligenceValueRow, MailSearchRow,
+    NamedCountRow, RecipientRankRow, SenderSummaryRow, SendingSourceRow, SpreadSnapshotRow, ThreadMailRow, ThreatActorRow, TimelineBucketRow, TimelineDispositionRow,
+    TimelineIntelRow, TimelineMailHitRow, TimelineMailRow, TimelineRetroHuntRow, TimelineUpdateRow, TrendCountRow,
+};
+use crate::models::domain::email::EmailSearchCriteria;
+use crate::models::domain::hash::{HashKind, HashPivotFilter};
+use crate::models::domain::intelligence::{Intelligence, IntelligenceFilter, IntelligenceType};
+use crate::models::domain::recipient::{RecipientProfileFilter, RecipientRankFilter};
+use crate::models::domain::sender::SenderProfileFilter;
+use crate::models::domain::spread::SpreadFilter;
+use crate::models::domain::statistics::TrendInterval;
+use crate::models::domain::timeline::TimelineFilter;
+
+pub use blob::FileBlobStore;
+pub use clickhouse::ClickHouseRepository;
+pub use managed::ManagedRepository;
+pub use memory::InMemoryRepository;
+pub(crate) use memory::MOCK_EXTRACT_PASSWORD;
+#[cfg(test)]
+pub(crate) use memory::FIXTURE_TIME;
+
+/// 情报类型对应的情报属性名称
+fn intelligence_attributes(intelligence_type: &IntelligenceType) -> impl Iterator<Item = &'static str> {
+    intelligence_type.attributes().iter().map(|attribute| attribute.as_str())
+}
+
+/// 情报属性对应的情报类型，未知属性或不属于任何类型的属性返回None
+fn intelligence_type(attribute: &str) -> Option<IntelligenceType> {
+    attribute.parse::<AttributeType>().ok()?.try_into().ok()
+}
+
+/// 情报命中存储库（alert_intelligence）
+#[async_trait]
+pub trait IntelligenceHitRepository: Send + Sync {
+    /// 按过滤条件分页查询情报，返回总数和当前页情报
+    async fn list_intelligence(&self, filter: &IntelligenceFilter) -> DbResult<(u64, Vec<Intelligence>)>;
+
+    /// 时间线的情报、命中邮件和情报更新记录，情报都不存在时`intel`为空。
+    /// 按攻击组织查询时最多取命中最多的`max_intelligence`个情报
+    async fn timeline(&self, filter: &TimelineFilter, max_intelligence: usize) -> DbResult<TimelineHits>;
+
+    /// 情报的命中记录数和按时间排序、去重后的联防联控快照
+    async fn spread_snapshots(&self, filter: &SpreadFilter) -> DbResult<(u64, Vec<SpreadSnapshotRow>)>;
+
+    /// 邮件命中的情报
+    async fn campaign_hits(&self, mail_ids: &[u64]) -> DbResult<Vec<CampaignHitRow>>;
+
+    /// 命中该哈希的情报，按最近命中时间倒序
+    async fn hash_intelligence(&self, attribute: &str, hash: &str) -> DbResult<Vec<HashIntelligenceRow>>;
+
+    /// 邮件命中的情报值（去重）
+    async fn mail_intelligence(&self, mail_id: u64) -> DbResult<Vec<MailIntelligenceValueRow>>;
+
+    /// 情报值（小写）在候选值中的链接类情报
+    async fn url_intelligence(&self, attributes: &[&str], candidates: &[String]) -> DbResult<Vec<MailIntelligenceValueRow>>;
+}
+
+/// 邮件信息存储库（data_mail_info）
+#[async_trait]
+pub trait MailInfoRepository: Send + Sync {
+    /// 按条件搜索邮件，返回匹配总数和当前页邮件，按时间倒序
+    async fn search_mails(&self, criteria: &EmailSearchCriteria) -> DbResult<(u64, Vec<MailSearchRow>)>;
+
+    /// 会话重建的起始邮件
+    async fn thread_seed(&self, mail_id: u64) -> DbResult<Option<ThreadMailRow>>;
+
+    /// 起始邮件前后时间窗口内主题包含归一化主题的候选邮件，按时间正序
+    async fn thread_candidates(&self, seed: &ThreadMailRow, subject: &str) -> DbResult<Vec<ThreadMailRow>>;
+
+    /// 时间范围内的邮件，按时间倒序，最多`limit`封
+    async fn campaign_mails(
+        &self,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+        limit: u64,
+    ) -> DbResult<Vec<CampaignMailRow>>;
+
+    /// 邮件文件哈希相同、命中该哈希的情报或包含哈希相同附件的邮件
+    async fn hash_mails(&self, filter: &HashPivotFilter, attachment_mails: &[u64]) -> DbResult<HashMails>;
+
+    /// 原始邮件在文件存储中的路径与入库时记录的SHA256，两者都可能为空
+    async fn mail_file(&self, mail_id: u64) -> DbResult<Option<MailFileRow>>;
+
+    /// 邮件的处置动作与信封信息
+    async fn delivery(&self, mail_id: u64) -> DbResult<Option<MailDeliveryRow>>;
+
+    /// 扫描器记录的压缩包提取密码，可能为空
+    async fn extract_password(&self, mail_id: u64) -> DbResult<Option<String>>;
+
+    /// 邮件主题与正文
+    async fn mail_body(&self, mail_id: u64) -> DbResult<Option<MailBodyRow>>;
+}
+
+/// 情报处置存储库（intelligence_disposition_log、intelligence_retro_hunt）
+#[async_trait]
+pub trait DispositionRepository: Send + Sync {
+    /// 情报的处置变更记录，按时间倒序
+    async fn dispositions(&self, intelligence_ids: &[String]) -> DbResult<Vec<TimelineDispositionRow>>;
+
+    /// 情报的回溯排查结果，按完成时间倒序
+    async fn retro_hunts(&self, intelligence_ids: &[String]) -> DbResult<Vec<TimelineRetroHuntRow>>;
+}
+
+/// 统计聚合存储库
+#[async_trait]
+pub trait StatisticsRepository: Send + Sync {
+    /// 收件人暴露画像的聚合数据
+    async fn recipient_profile(&self, filter: &RecipientProfileFilter) -> DbResult<RecipientProfileData>;
+
+    /// 时间范围内被针对的收件人，按命中情报的邮件数倒序
+    async fn recipient_rank(&self, filter: &RecipientRankFilter) -> DbResult<Vec<RecipientRankRow>>;
+
+    /// 发件人信誉画像的聚合数据
+    async fn sender_profile(&self, filter: &SenderProfileFilter) -> DbResult<SenderProfileData>;
+
+    /// 时间范围内的情报命中汇总
+    async fn hit_summary(&self, start_time: DateTime<Utc>, end_time: DateTime<Utc>) -> DbResult<HitSummaryRow>;
+
+    /// 时间范围内按时间分桶的情报命中数，只返回有命中的分桶
+    async fn hit_trend(
+        &self,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+        interval: TrendInterval,
+    ) -> DbResult<Vec<HitTrendRow>>;
+
+    /// 时间范围内命中的攻击组织，按命中邮件数倒序
+    async fn threat_actors(&self, start_time: DateTime<Utc>, end_time: DateTime<Utc>) -> DbResult<Vec<ThreatActorRow>>;
+
+    /// 本地自定义情报在时间范围内的命中数与总数
+    async fn custom_intelligence(
+        &self,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+    ) -> DbResult<CustomIntelligenceRow>;
+}
+
+/// 邮件附件存储库（data_mail_attachment）
+#[async_trait]
+pub trait AttachmentRepository: Send + Sync {
+    /// 邮件的附件记录，`mail_ids`为None时返回全部记录，按邮件ID和附件ID排序
+    async fn attachments(&self, mail_ids: Option<&[u64]>) -> DbResult<Vec<MailAttachmentRow>>;
+
+    /// 单个附件记录
+    async fn attachment(&self, attachment_id: &str) -> DbResult<Option<MailAttachmentRow>>;
+
+    /// 入库时记录的哈希与`hash`相同的附件（不区分大小写），按邮件ID和附件ID排序
+    async fn attachments_by_hash(&self, kind: HashKind, hash: &str) -> DbResult<Vec<MailAttachmentRow>>;
+}
+
+/// 隔离处置存储库（quarantine_action、quarantine_audit）
+#[async_trait]
+pub trait QuarantineRepository: Send + Sync {
+    /// 写入处置请求的当前状态，版本号更大的记录覆盖同一请求的旧记录
+    async fn save_action(&self, action: &QuarantineActionRow) -> DbResult<()>;
+
+    /// 单个处置请求的最新状态
+    async fn action(&self, action_id: &str) -> DbResult<Option<QuarantineActionRow>>;
+
+    /// 处置请求的最新状态，按申请时间倒序
+    async fn actions(&self, mail_id: Option<&str>, state: Option<&str>) -> DbResult<Vec<QuarantineActionRow>>;
+
+    /// 追加审计记录
+    async fn append_audit(&self, entry: &QuarantineAuditRow) -> DbResult<()>;
+
+    /// 审计记录，按时间顺序
+    async fn audit(&self, mail_id: Option<&str>, action_id: Option<&str>) -> DbResult<Vec<QuarantineAuditRow>>;
+}
+
+/// 可随机读取的存储文件
+pub trait BlobRead: AsyncRead + AsyncSeek + Send + Unpin {}
+
+impl<T: AsyncRead + AsyncSeek + Send + Unpin> BlobRead for T {}
+
+/// 已打开的存储文件
+pub struct Blob {
+    /// 文件大小（字节）
+    pub size: u64,
+    /// 文件内容
+    pub reader: Box<dyn BlobRead>,
+}
+
+/// 附件与原始邮件的文件存储
+#[async_trait]
+pub trait BlobStore: Send + Sync {
+    /// 打开存储中的文件，`path`为入库记录中的路径，文件不存在时返回None
+    async fn open(&self, path: &str) -> DbResult<Option<Blob>>;
+}
+
+/// 服务层使用的全部存储库
+#[derive(Clone)]
+pub struct Repositories {
+    /// 情报命中
+    pub hits: Arc<dyn IntelligenceHitRepository>,
+    /// 邮件信息
+    pub mails: Arc<dyn MailInfoRepository>,
+    /// 情报处置
+    pub dispositions: Arc<dyn DispositionRepository>,
+    /// 统计聚合
+    pub statistics: Arc<dyn StatisticsRepository>,
+    /// 邮件附件记录
+    pub attachments: Arc<dyn AttachmentRepository>,
+    /// 隔离处置记录
+    pub quarantine: Arc<dyn QuarantineRepository>,
+    /// 附件与原始邮件的文件存储
+    pub blobs: Arc<dyn BlobStore>,
+}
+
+impl Repositories {
+    /// 查询ClickHouse的存储库，文件从`files`读取
+    pub fn clickhouse(client: Arc<ClickHouseClient>, files: FileBlobStore) -> Self {
+        Self::from_backend(Arc::new(ClickHouseRepository::new(client)), Arc::new(files))
+    }
+
+    /// 内存模式的存储库，模拟数据以创建时刻为时间基准
+    pub fn in_memory() -> Self {
+        let memory = Arc::new(InMemoryRepository::new(Utc::now()));
+        Self::from_backend(memory.clone(), memory)
+    }
+
+    /// 随连接状态在ClickHouse与内存模式之间切换的存储库，数据库模式下文件从`files`读取
+    pub fn managed(manager: Arc<ConnectionManager>, files: FileBlobStore) -> Self {
+        let managed = Arc::new(ManagedRepository::new(manager, files));
+        Self::from_backend(managed.clone(), managed)
+    }
+
+    /// 由同时实现全部存储库特性的后端和文件存储构建
+    pub fn from_backend<R>(backend: Arc<R>, blobs: Arc<dyn BlobStore>) -> Self
+    where
+        R: IntelligenceHitRepository + MailInfoRepository + DispositionRepository + StatisticsRepository
+            + AttachmentRepository + QuarantineRepository + 'static,
+    {
+        Self {
+            hits: backend.clone(),
+            mails: backend.clone(),
+            dispositions: backend.clone(),
+            statistics: backend.clone(),
+            attachments: backend.clone(),
+            quarantine: backend,
+            blobs,
+        }
+    }
+}
+
+/// 时间线的查询结果
+pub struct TimelineHits {
+    /// 时间线包含的情报，不存在的情报没有记录
+    pub intel: Vec<TimelineIntelRow>,
+    /// 满足条件的邮件数及首末时间
+    pub span: CountSpanRow,
+    /// 当前页邮件，指定分桶粒度时为空
+    pub mails: Vec<TimelineMailRow>,
+    /// 当前页邮件命中的时间线情报
+    pub mail_hits: Vec<TimelineMailHitRow>,
+    /// 上一页最后一封邮件的时间，用于划分各页的生命周期事件
+    pub newer_secs: Option<u32>,
+    /// 各分桶按处置动作分组的邮件数，未指定分桶粒度时为空
+    pub buckets: Vec<TimelineBucketRow>,
+    /// 情报更新记录，按时间倒序
+    pub updates: Vec<TimelineUpdateRow>,
+}
+
+impl TimelineHits {
+    /// 没有任何情报时的空结果
+    pub fn empty() -> Self {
+        Self {
+            intel: Vec::new(),
+            span: CountSpanRow::default(),
+            mails: Vec::new(),
+            mail_hits: Vec::new(),
+            newer_secs: None,
+            buckets: Vec::new(),
+            updates: Vec::new(),
+        }
+    }
+}
+
+/// 哈希关联的邮件
+pub struct HashMails {
+    /// 关联邮件，按时间倒序
+    pub mails: Vec<HashMailRow>,
+    /// 关联邮件总数及首末时间
+    pub summary: CountSpanRow,
+    /// 受影响的收件人
+    pub recipients: Vec<NamedCountRow>,
+}
+
+/// 收件人暴露画像的聚合结果
+pub struct RecipientProfileData {
+    /// 按处置动作分组的邮件数
+    pub actions: Vec<NamedCountRow>,
+    /// 命中情报的邮件数及首末时间
+    pub summary: CountSpanRow,
+    /// 按情报类型分组的命中数
+    pub types: Vec<NamedCountRow>,
+    /// 按紧急程度分组的命中数
+    pub urgency: Vec<NamedCountRow>,
+    /// 按命中邮件数排序的攻击组织
+    pub actors: Vec<NamedCountRow>,
+    /// 按时间分桶的邮件数与命中情报的邮件数
+    pub trend: Vec<TrendCountRow>,
+}
+
+/// 发件人信誉画像的聚合结果
+pub struct SenderProfileData {
+    /// 不限时间范围的来信数及首末时间
+    pub lifetime: CountSpanRow,
+    /// 时间范围内的发信汇总
+    pub summary: SenderSummaryRow,
+    /// 按处置动作分组的邮件数
+    pub actions: Vec<NamedCountRow>,
+    /// 发信来源
+    pub sources: Vec<SendingSourceRow>,
+    /// 按TLS版本分组的邮件数
+    pub tls: Vec<NamedCountRow>,
+    /// 按情报类型分组的命中数
+    pub types: Vec<NamedCountRow>,
+    /// 按时间分桶的邮件数与命中情报的邮件数
+    pub trend: Vec<TrendCountRow>,
+}
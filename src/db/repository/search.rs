@@ -0,0 +1,234 @@
+//! 邮件搜索条件
+//!
+//! 将搜索条件转换为列匹配条件，数据库中生成查询条件，内存模式下直接匹配投影行
+
+use crate::db::{Conditions, LikeMatch, MailSearchRow, Query};
+use crate::models::domain::email::EmailSearchCriteria;
+
+/// 可搜索的data_mail_info列
+#[derive(Debug, Clone, Copy)]
+enum SearchColumn {
+    Subject,
+    DisplayFrom,
+    DisplayToAddress,
+    DisplayToAccount,
+    DisplayToDomain,
+    EnvelopeToAddress,
+    EnvelopeToAccount,
+    EnvelopeToDomain,
+    ClientIp,
+    SaslLogin,
+    Direction,
+    Action,
+    HashMd5,
+    HashSha1,
+    HashSha256,
+    Tls,
+    ProtocolVersion,
+}
+
+impl SearchColumn {
+    /// 列在SQL中的表达式
+    fn expr(&self) -> &'static str {
+        match self {
+            SearchColumn::Subject => "subject",
+            SearchColumn::DisplayFrom => "display_from",
+            SearchColumn::DisplayToAddress => "display_to_address",
+            SearchColumn::DisplayToAccount => "display_to_account",
+            SearchColumn::DisplayToDomain => "display_to_domain",
+            SearchColumn::EnvelopeToAddress => "client_envelope_to_address",
+            SearchColumn::EnvelopeToAccount => "client_envelope_to_account",
+            SearchColumn::EnvelopeToDomain => "client_envelope_to_domain",
+            SearchColumn::ClientIp => "client_ip",
+            SearchColumn::SaslLogin => "sasl_login",
+            SearchColumn::Direction => "direction",
+            SearchColumn::Action => "toString(action)",
+            SearchColumn::HashMd5 => "hash_md5",
+            SearchColumn::HashSha1 => "hash_sha1",
+            SearchColumn::HashSha256 => "hash_sha256",
+            SearchColumn::Tls => "tls",
+            SearchColumn::ProtocolVersion => "protocol_version",
+        }
+    }
+
+    /// 从投影行中取出列值，用于内存模式下的过滤
+    fn value<'a>(&self, row: &'a MailSearchRow) -> &'a str {
+        match self {
+            SearchColumn::Subject => &row.subject,
+            SearchColumn::DisplayFrom => &row.display_from,
+            SearchColumn::DisplayToAddress => &row.display_to_address,
+            SearchColumn::DisplayToAccount => &row.display_to_account,
+            SearchColumn::DisplayToDomain => &row.display_to_domain,
+            SearchColumn::EnvelopeToAddress => &row.client_envelope_to_address,
+            SearchColumn::EnvelopeToAccount => &row.client_envelope_to_account,
+            SearchColumn::EnvelopeToDomain => &row.client_envelope_to_domain,
+            SearchColumn::ClientIp => &row.client_ip,
+            SearchColumn::SaslLogin => &row.sasl_login,
+            SearchColumn::Direction => &row.direction,
+            SearchColumn::Action => &row.action_name,
+            SearchColumn::HashMd5 => &row.hash_md5,
+            SearchColumn::HashSha1 => &row.hash_sha1,
+            SearchColumn::HashSha256 => &row.hash_sha256,
+            SearchColumn::Tls => &row.tls,
+            SearchColumn::ProtocolVersion => &row.protocol_version,
+        }
+    }
+}
+
+/// 匹配方式，均不区分大小写
+#[derive(Debug, Clone, Copy)]
+enum MatchMode {
+    /// 包含关键字
+    Contains,
+    /// 完全相等
+    Equals,
+    /// 逗号或分号分隔的列表中任一项完全相等，用于可能包含多个收件人的列
+    ListEquals,
+}
+
+/// 将逗号或分号分隔的列表拆分为各项，与`list_items_sql`的拆分方式一致
+pub(super) fn list_items(value: &str) -> impl Iterator<Item = &str> {
+    value.split([',', ';']).map(str::trim).filter(|item| !item.is_empty())
+}
+
+/// 拆分列表列的SQL
+fn list_items_sql(expr: &str) -> String {
+    format!("arrayMap(x -> trimBoth(x), splitByRegexp('[,;]', {}))", expr)
+}
+
+/// 单个搜索条件：任一列匹配任一值即满足
+#[derive(Debug, Clone)]
+pub(super) struct SearchTerm {
+    columns: &'static [SearchColumn],
+    mode: MatchMode,
+    values: Vec<String>,
+}
+
+impl SearchTerm {
+    /// 生成查询条件，值以参数形式绑定
+    pub(super) fn to_conditions(&self) -> Conditions {
+        let mut conditions = Conditions::any();
+        for column in self.columns {
+            for value in &self.values {
+                conditions = match self.mode {
+                    MatchMode::Contains => conditions.like(column.expr(), value, LikeMatch::Contains, true),
+                    MatchMode::Equals => conditions.eq(&format!("lowerUTF8({})", column.expr()), value),
+                    MatchMode::ListEquals => conditions.push(Query::with_params(
+                        format!("has({}, ?)", list_items_sql(&format!("lowerUTF8({})", column.expr()))),
+                        [value.as_str()],
+                    )),
+                };
+            }
+        }
+        conditions
+    }
+
+    /// 判断投影行是否满足条件
+    pub(super) fn matches(&self, row: &MailSearchRow) -> bool {
+        self.columns.iter().any(|column| {
+            let actual = column.value(row).to_lowercase();
+            self.values.iter().any(|value| match self.mode {
+                MatchMode::Contains => actual.contains(value.as_str()),
+                MatchMode::Equals => actual == *value,
+                MatchMode::ListEquals => list_items(&actual).any(|item| item == value),
+            })
+        })
+    }
+}
+
+/// 将搜索条件转换为列匹配条件，空字符串视为未指定
+pub(super) fn terms(criteria: &EmailSearchCriteria) -> Vec<SearchTerm> {
+    use SearchColumn::*;
+
+    let fields: [(&Option<String>, &'static [SearchColumn], MatchMode); 11] = [
+        (&criteria.subject, &[Subject], MatchMode::Contains),
+        (&criteria.display_from, &[DisplayFrom], MatchMode::Contains),
+        (&criteria.recipient_address, &[DisplayToAddress, EnvelopeToAddress], MatchMode::ListEquals),
+        (&criteria.recipient_account, &[DisplayToAccount, EnvelopeToAccount], MatchMode::ListEquals),
+        (&criteria.recipient_domain, &[DisplayToDomain, EnvelopeToDomain], MatchMode::ListEquals),
+        (&criteria.client_ip, &[ClientIp], MatchMode::Equals),
+        (&criteria.sasl_login, &[SaslLogin], MatchMode::Equals),
+        (&criteria.direction, &[Direction], MatchMode::Equals),
+        (&criteria.hash, &[HashMd5, HashSha1, HashSha256], MatchMode::Equals),
+        (&criteria.tls, &[Tls], MatchMode::Contains),
+        (&criteria.protocol, &[ProtocolVersion], MatchMode::Contains),
+    ];
+
+    let mut terms: Vec<SearchTerm> = fields
+        .into_iter()
+        .filter_map(|(value, columns, mode)| {
+            let value = value.as_deref()?.trim();
+            (!value.is_empty()).then(|| SearchTerm {
+                columns,
+                mode,
+                values: vec![value.to_lowercase()],
+            })
+        })
+        .collect();
+
+    if !criteria.actions.is_empty() {
+        terms.push(SearchTerm {
+            columns: &[Action],
+            mode: MatchMode::Equals,
+            values: criteria.actions.iter().map(|status| status.as_str().to_string()).collect(),
+        });
+    }
+
+    terms
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(display_to: &str, envelope_to: &str) -> MailSearchRow {
+        MailSearchRow {
+            id: 1,
+            action_name: "Accept".to_string(),
+            timestamp_secs: 0,
+            subject: String::new(),
+            display_from: String::new(),
+            display_to_address: display_to.to_string(),
+            display_to_account: String::new(),
+            display_to_domain: String::new(),
+            sasl_login: String::new(),
+            client_ip: String::new(),
+            client_envelope_from_address: String::new(),
+            client_envelope_to_address: envelope_to.to_string(),
+            client_envelope_to_account: String::new(),
+            client_envelope_to_domain: String::new(),
+            tls: String::new(),
+            protocol_version: String::new(),
+            text_body: String::new(),
+            hash_sha1: String::new(),
+            hash_sha256: String::new(),
+            hash_md5: String::new(),
+            direction: String::new(),
+        }
+    }
+
+    fn recipient(address: &str) -> Vec<SearchTerm> {
+        terms(&EmailSearchCriteria {
+            recipient_address: Some(address.to_string()),
+            ..EmailSearchCriteria::default()
+        })
+    }
+
+    #[test]
+    fn recipient_matches_any_listed_address() {
+        let terms = recipient(" CEO@example.org ");
+        assert_eq!(terms.len(), 1);
+        assert!(terms[0].matches(&row("finance@example.org", "finance@example.org, CEO@example.org")));
+        assert!(terms[0].matches(&row("a@example.org", "a@example.org;ceo@example.org")));
+        assert!(terms[0].matches(&row("ceo@example.org", "")));
+        // 列表项须完全相等
+        assert!(!terms[0].matches(&row("", "vice-ceo@example.org,ceo@example.org.cn")));
+    }
+
+    #[test]
+    fn recipient_condition_splits_lists() {
+        let query = recipient("ceo@example.org")[0].to_conditions().build();
+        assert!(query.sql().contains("has(arrayMap(x -> trimBoth(x), splitByRegexp('[,;]', lowerUTF8(client_envelope_to_address))), ?)"), "{}", query.sql());
+        assert_eq!(query.params().len(), 2);
+    }
+}
@@ -3,9 +3,12 @@
 pub mod config;
 pub mod telemetry;
 pub mod server;
+pub mod migrate;
 pub mod db;
 pub mod routes;
 pub mod services;
 pub mod models;
+pub mod content;
 
-pub use server::run_server; 
\ No newline at end of file
+pub use server::run_server;
+pub use migrate::run_migrate; 
\ No newline at end of file
@@ -7,6 +7,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         Err(e) => println!("加载.env文件失败: {}", e),
     }
     
-    // 运行服务器
-    analysis_api::run_server().await
+    // `migrate up|status`执行数据库迁移，否则运行服务器
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("migrate") => analysis_api::run_migrate(&args[1..]).await,
+        _ => analysis_api::run_server().await,
+    }
 }
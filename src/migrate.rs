@@ -0,0 +1,46 @@
+//! 数据库迁移命令
+//!
+//! `analysis-api migrate up`执行未执行的迁移，`analysis-api migrate status`查看各迁移的状态
+
+use crate::config::get_config;
+use crate::db::ClickHouseClient;
+use crate::db::migrations::{MigrationState, Migrator};
+
+/// 执行迁移子命令，args为`migrate`之后的参数
+pub async fn run_migrate(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let config = get_config();
+    let command = args.first().map(String::as_str);
+    if !matches!(command, Some("up" | "status")) {
+        return Err("用法: analysis-api migrate <up|status>".into());
+    }
+
+    let client = ClickHouseClient::new(config.db_config.clone()).await?;
+    let migrator = Migrator::new(&client);
+    match command {
+        Some("up") => {
+            let executed = migrator.up().await?;
+            if executed.is_empty() {
+                println!("数据库 {} 已是最新，没有需要执行的迁移", client.database());
+            } else {
+                for version in &executed {
+                    println!("已执行迁移 {:04}", version);
+                }
+                println!("共执行 {} 个迁移", executed.len());
+            }
+            migrator.verify().await?;
+        }
+        _ => {
+            println!("数据库: {}", client.database());
+            for status in migrator.status().await? {
+                let applied_at = match &status.state {
+                    MigrationState::Applied { applied_at }
+                    | MigrationState::Modified { applied_at }
+                    | MigrationState::Unknown { applied_at } => applied_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+                    MigrationState::Pending => String::new(),
+                };
+                println!("{:04}  {:<40} {:<6} {}", status.version, status.name, status.state.label(), applied_at);
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,184 @@
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use crate::models::api::statistics::NamedCountResponse;
+use crate::models::domain::campaign::{Campaign, CampaignIntelligence, CampaignList, CampaignMail};
+use crate::models::domain::email::EmailStatus;
+
+/// 钓鱼批次聚类查询参数 - API模型
+#[derive(Debug, Deserialize)]
+pub struct CampaignQuery {
+    /// 开始时间
+    pub start_time: DateTime<Utc>,
+    /// 结束时间
+    pub end_time: DateTime<Utc>,
+    /// 批次最少包含的邮件数，默认2
+    pub min_size: Option<u32>,
+    /// 归入同一批次的最低相似度，0.5到1，默认0.85
+    pub min_similarity: Option<f64>,
+    /// 只返回包含命中该情报邮件的批次
+    pub intelligence_id: Option<String>,
+    /// 只返回包含该邮件的批次
+    pub mail_id: Option<String>,
+    /// 返回的批次数量，默认20
+    pub limit: Option<u32>,
+}
+
+/// 批次成员邮件 - API模型
+#[derive(Debug, Serialize)]
+pub struct CampaignMailResponse {
+    /// 邮件ID
+    pub mail_id: String,
+    /// 邮件检测时间
+    pub timestamp: DateTime<Utc>,
+    /// 邮件主题
+    pub subject: String,
+    /// 发件人
+    pub sender: String,
+    /// 收件人列表
+    pub recipients: Vec<String>,
+    /// 处置动作
+    pub status: EmailStatus,
+    /// 处置动作中文名称
+    pub status_label: String,
+    /// 是否命中情报
+    pub hit_intelligence: bool,
+    /// 与批次代表邮件的相似度，0到1
+    pub similarity: f64,
+}
+
+// 从领域模型转换
+impl From<CampaignMail> for CampaignMailResponse {
+    fn from(mail: CampaignMail) -> Self {
+        Self {
+            mail_id: mail.mail_id,
+            timestamp: mail.timestamp,
+            subject: mail.subject,
+            sender: mail.sender,
+            recipients: mail.recipients,
+            status: mail.status,
+            status_label: mail.status.label().to_string(),
+            hit_intelligence: mail.hit_intelligence,
+            similarity: mail.similarity,
+        }
+    }
+}
+
+/// 批次关联的情报 - API模型
+#[derive(Debug, Serialize)]
+pub struct CampaignIntelligenceResponse {
+    /// 情报ID
+    pub intelligence_id: String,
+    /// 情报属性
+    pub attribute: String,
+    /// 情报值
+    pub value: String,
+    /// 情报分类
+    pub intelligence_type: String,
+    /// 紧急程度
+    pub urgency: String,
+    /// 攻击组织名称
+    pub threat_actor: String,
+    /// 批次内命中该情报的邮件数
+    pub hit_mails: u64,
+}
+
+// 从领域模型转换
+impl From<CampaignIntelligence> for CampaignIntelligenceResponse {
+    fn from(intel: CampaignIntelligence) -> Self {
+        Self {
+            intelligence_id: intel.intelligence_id,
+            attribute: intel.attribute,
+            value: intel.value,
+            intelligence_type: intel.intelligence_type,
+            urgency: intel.urgency,
+            threat_actor: intel.threat_actor,
+            hit_mails: intel.hit_mails,
+        }
+    }
+}
+
+/// 钓鱼批次 - API模型
+#[derive(Debug, Serialize)]
+pub struct CampaignResponse {
+    /// 批次ID
+    pub campaign_id: String,
+    /// 代表主题
+    pub subject: String,
+    /// 邮件数
+    pub size: u64,
+    /// 首次出现时间
+    pub first_seen: DateTime<Utc>,
+    /// 最近出现时间
+    pub last_seen: DateTime<Utc>,
+    /// 发件人及其邮件数
+    pub senders: Vec<NamedCountResponse>,
+    /// 收件人及其邮件数
+    pub recipients: Vec<NamedCountResponse>,
+    /// 正文链接的主机名
+    pub hosts: Vec<String>,
+    /// 附件哈希
+    pub attachment_hashes: Vec<String>,
+    /// 命中情报的邮件数
+    pub hit_mails: u64,
+    /// 未命中情报、由同批次邮件暴露的邮件数
+    pub unhit_mails: u64,
+    /// 批次内邮件命中的情报
+    pub intelligence: Vec<CampaignIntelligenceResponse>,
+    /// 成员邮件
+    pub mails: Vec<CampaignMailResponse>,
+}
+
+// 从领域模型转换
+impl From<Campaign> for CampaignResponse {
+    fn from(campaign: Campaign) -> Self {
+        Self {
+            unhit_mails: campaign.size.saturating_sub(campaign.hit_mails),
+            campaign_id: campaign.campaign_id,
+            subject: campaign.subject,
+            size: campaign.size,
+            first_seen: campaign.first_seen,
+            last_seen: campaign.last_seen,
+            senders: campaign.senders.into_iter().map(Into::into).collect(),
+            recipients: campaign.recipients.into_iter().map(Into::into).collect(),
+            hosts: campaign.hosts,
+            attachment_hashes: campaign.attachment_hashes,
+            hit_mails: campaign.hit_mails,
+            intelligence: campaign.intelligence.into_iter().map(Into::into).collect(),
+            mails: campaign.mails.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// 钓鱼批次聚类结果 - API模型
+#[derive(Debug, Serialize)]
+pub struct CampaignListData {
+    /// 参与聚类的邮件数
+    pub analyzed_mails: u64,
+    /// 时间范围内邮件超过上限，只分析了最近的部分
+    pub truncated: bool,
+    /// 满足条件的批次总数
+    pub total: u64,
+    /// 批次列表
+    pub campaigns: Vec<CampaignResponse>,
+}
+
+// 从领域模型转换
+impl From<CampaignList> for CampaignListData {
+    fn from(list: CampaignList) -> Self {
+        Self {
+            analyzed_mails: list.analyzed_mails,
+            truncated: list.truncated,
+            total: list.total,
+            campaigns: list.campaigns.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// 钓鱼批次聚类响应 - API模型
+#[derive(Debug, Serialize)]
+pub struct CampaignListResponse {
+    /// 状态码
+    pub code: u32,
+    /// 聚类结果
+    pub data: CampaignListData,
+}
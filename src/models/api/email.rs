@@ -1,6 +1,40 @@
 use chrono::{DateTime, Utc};
 use serde::{Serialize, Deserialize};
-use crate::models::domain::email::{Email, Attachment, Url};
+use crate::content::{ArchiveEntry, FileKind};
+use crate::models::domain::email::{
+    Email, EmailStatus, Attachment, Url, UrlIntelligence, UrlSource, EmailPreview, IocHighlight, ArchiveInspection,
+    ActionCount,
+};
+
+/// 邮件状态过滤值 - API模型
+///
+/// 兼容单个字符串和字符串数组两种写法
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum StatusList {
+    /// 单个状态
+    One(String),
+    /// 多个状态
+    Many(Vec<String>),
+}
+
+impl StatusList {
+    /// 解析为去重后的邮件状态列表，存在无效值时返回错误
+    pub fn parse(self) -> Result<Vec<EmailStatus>, String> {
+        let values = match self {
+            StatusList::One(value) => vec![value],
+            StatusList::Many(values) => values,
+        };
+        let mut statuses = Vec::new();
+        for value in values {
+            let status: EmailStatus = value.parse()?;
+            if !statuses.contains(&status) {
+                statuses.push(status);
+            }
+        }
+        Ok(statuses)
+    }
+}
 
 /// 关联邮件查询参数 - API模型
 #[derive(Debug, Deserialize)]
@@ -11,8 +45,8 @@ pub struct RelatedEmailsQuery {
     pub end_time: DateTime<Utc>,
     /// 情报ID
     pub intelligence_id: String,
-    /// 邮件状态
-    pub status: Option<String>,
+    /// 邮件状态，可为单个值或数组
+    pub status: Option<StatusList>,
     /// 页码
     pub page: Option<u32>,
     /// 每页大小
@@ -32,8 +66,14 @@ pub struct AttachmentResponse {
     pub size: u64,
     /// 文件扩展名
     pub file_extension: String,
+    /// 根据文件签名识别出的类型
+    pub detected_type: FileKind,
+    /// 扩展名是否与识别出的类型不符
+    pub extension_mismatch: bool,
     /// 文件MD5值
     pub md5: String,
+    /// 文件SHA256值
+    pub sha256: String,
 }
 
 // 从领域模型转换
@@ -45,7 +85,32 @@ impl From<Attachment> for AttachmentResponse {
             file_path: attachment.file_path,
             size: attachment.size,
             file_extension: attachment.file_extension,
+            detected_type: attachment.detected_type,
+            extension_mismatch: attachment.extension_mismatch,
             md5: attachment.md5,
+            sha256: attachment.sha256,
+        }
+    }
+}
+
+/// URL命中的情报 - API模型
+#[derive(Debug, Serialize)]
+pub struct UrlIntelligenceResponse {
+    /// 情报ID
+    pub intelligence_id: String,
+    /// 情报属性
+    pub attribute: String,
+    /// 情报值
+    pub value: String,
+}
+
+// 从领域模型转换
+impl From<UrlIntelligence> for UrlIntelligenceResponse {
+    fn from(intelligence: UrlIntelligence) -> Self {
+        Self {
+            intelligence_id: intelligence.intelligence_id,
+            attribute: intelligence.attribute,
+            value: intelligence.value,
         }
     }
 }
@@ -59,6 +124,12 @@ pub struct UrlResponse {
     pub url: String,
     /// URL路径
     pub path: String,
+    /// URL来源：email_body、qr_code或barcode
+    pub source: UrlSource,
+    /// 解出该URL的附件ID
+    pub attachment_id: Option<String>,
+    /// 命中的情报
+    pub intelligence: Vec<UrlIntelligenceResponse>,
 }
 
 // 从领域模型转换
@@ -68,6 +139,9 @@ impl From<Url> for UrlResponse {
             id: url.id,
             url: url.url,
             path: url.path,
+            source: url.source,
+            attachment_id: url.attachment_id,
+            intelligence: url.intelligence.into_iter().map(Into::into).collect(),
         }
     }
 }
@@ -92,7 +166,9 @@ pub struct EmailResponse {
     /// 邮件内容
     pub content: String,
     /// 邮件状态
-    pub status: String,
+    pub status: EmailStatus,
+    /// 邮件状态显示名称
+    pub status_label: String,
     /// 邮件源代码
     pub source_code: String,
 }
@@ -110,6 +186,7 @@ impl From<Email> for EmailResponse {
             urls: email.urls.into_iter().map(UrlResponse::from).collect(),
             content: email.content,
             status: email.status,
+            status_label: email.status.label().to_string(),
             source_code: email.source_code,
         }
     }
@@ -121,7 +198,241 @@ pub struct RelatedEmailsResponse {
     /// 状态码
     pub code: u32,
     /// 总数
-    pub total: u32,
+    pub total: u64,
     /// 邮件列表
     pub data: Vec<EmailResponse>,
-} 
\ No newline at end of file
+}
+
+/// 邮件搜索查询参数 - API模型
+#[derive(Debug, Deserialize)]
+pub struct EmailSearchQuery {
+    /// 开始时间
+    pub start_time: Option<DateTime<Utc>>,
+    /// 结束时间
+    pub end_time: Option<DateTime<Utc>>,
+    /// 主题关键字
+    pub subject: Option<String>,
+    /// 显示发件人关键字
+    pub display_from: Option<String>,
+    /// 收件人完整邮箱地址
+    pub recipient_address: Option<String>,
+    /// 收件人邮箱账号
+    pub recipient_account: Option<String>,
+    /// 收件人邮箱域名
+    pub recipient_domain: Option<String>,
+    /// 客户端IP
+    pub client_ip: Option<String>,
+    /// 认证用户名
+    pub sasl_login: Option<String>,
+    /// 邮件方向
+    pub direction: Option<String>,
+    /// 处置动作，可为单个值或数组
+    pub action: Option<StatusList>,
+    /// 哈希值（MD5、SHA1或SHA256）
+    pub hash: Option<String>,
+    /// 传输层安全协议关键字
+    pub tls: Option<String>,
+    /// 协议信息关键字
+    pub protocol: Option<String>,
+    /// 页码
+    pub page: Option<u32>,
+    /// 每页大小
+    pub page_size: Option<u32>,
+}
+
+/// 邮件搜索响应 - API模型
+#[derive(Debug, Serialize)]
+pub struct EmailSearchResponse {
+    /// 状态码
+    pub code: u32,
+    /// 匹配总数
+    pub total: u64,
+    /// 当前页邮件列表
+    pub data: Vec<EmailResponse>,
+}
+
+/// 邮件预览查询参数 - API模型
+#[derive(Debug, Deserialize)]
+pub struct EmailPreviewQuery {
+    /// 邮件ID
+    pub email_id: String,
+}
+
+/// 情报高亮信息 - API模型
+#[derive(Debug, Serialize)]
+pub struct IocHighlightResponse {
+    /// 情报ID
+    pub intelligence_id: String,
+    /// 情报属性
+    pub attribute: String,
+    /// 情报值
+    pub value: String,
+    /// 在正文中的命中次数
+    pub hit_count: u32,
+}
+
+// 从领域模型转换
+impl From<IocHighlight> for IocHighlightResponse {
+    fn from(highlight: IocHighlight) -> Self {
+        Self {
+            intelligence_id: highlight.intelligence_id,
+            attribute: highlight.attribute,
+            value: highlight.value,
+            hit_count: highlight.hit_count,
+        }
+    }
+}
+
+/// 邮件预览数据 - API模型
+#[derive(Debug, Serialize)]
+pub struct EmailPreviewData {
+    /// 邮件ID
+    pub email_id: String,
+    /// 主题
+    pub subject: String,
+    /// 清洗并高亮后的HTML
+    pub html: String,
+    /// 情报高亮信息
+    pub highlights: Vec<IocHighlightResponse>,
+}
+
+// 从领域模型转换
+impl From<EmailPreview> for EmailPreviewData {
+    fn from(preview: EmailPreview) -> Self {
+        Self {
+            email_id: preview.email_id,
+            subject: preview.subject,
+            html: preview.html,
+            highlights: preview.highlights.into_iter().map(IocHighlightResponse::from).collect(),
+        }
+    }
+}
+
+/// 邮件预览响应 - API模型
+#[derive(Debug, Serialize)]
+pub struct EmailPreviewResponse {
+    /// 状态码
+    pub code: u32,
+    /// 数据
+    pub data: EmailPreviewData,
+}
+
+/// 压缩包附件解析请求参数 - API模型
+#[derive(Debug, Deserialize)]
+pub struct ArchiveInspectionQuery {
+    /// 邮件ID，用于读取扫描器记录的提取密码
+    pub email_id: String,
+    /// 附件ID
+    pub attachment_id: String,
+    /// 解压密码，优先于邮件记录的提取密码尝试
+    #[serde(default)]
+    pub password: Option<String>,
+}
+
+/// 压缩包条目 - API模型
+#[derive(Debug, Serialize)]
+pub struct ArchiveEntryResponse {
+    /// 条目路径
+    pub name: String,
+    /// 解压后大小（字节）
+    pub size: u64,
+    /// 压缩后大小（字节）
+    pub compressed_size: u64,
+    /// 是否为目录
+    pub is_dir: bool,
+    /// 是否加密
+    pub encrypted: bool,
+    /// MD5
+    pub md5: Option<String>,
+    /// SHA1
+    pub sha1: Option<String>,
+    /// SHA256
+    pub sha256: Option<String>,
+    /// 根据文件签名识别出的类型
+    pub detected_type: Option<FileKind>,
+    /// 未能解压的原因
+    pub error: Option<String>,
+    /// 嵌套压缩包的成员
+    pub children: Vec<ArchiveEntryResponse>,
+}
+
+// 从领域模型转换
+impl From<ArchiveEntry> for ArchiveEntryResponse {
+    fn from(entry: ArchiveEntry) -> Self {
+        Self {
+            name: entry.name,
+            size: entry.size,
+            compressed_size: entry.compressed_size,
+            is_dir: entry.is_dir,
+            encrypted: entry.encrypted,
+            md5: entry.md5,
+            sha1: entry.sha1,
+            sha256: entry.sha256,
+            detected_type: entry.detected_type,
+            error: entry.error,
+            children: entry.children.into_iter().map(ArchiveEntryResponse::from).collect(),
+        }
+    }
+}
+
+/// 压缩包附件解析数据 - API模型
+#[derive(Debug, Serialize)]
+pub struct ArchiveInspectionData {
+    /// 附件ID
+    pub attachment_id: String,
+    /// 压缩包格式
+    pub format: FileKind,
+    /// 是否使用了解压密码
+    pub password_used: bool,
+    /// 是否因触发限制而截断
+    pub truncated: bool,
+    /// 解析过程中的警告
+    pub warnings: Vec<String>,
+    /// 顶层条目
+    pub entries: Vec<ArchiveEntryResponse>,
+}
+
+// 从领域模型转换
+impl From<ArchiveInspection> for ArchiveInspectionData {
+    fn from(inspection: ArchiveInspection) -> Self {
+        Self {
+            attachment_id: inspection.attachment_id,
+            format: inspection.format,
+            password_used: inspection.password_used,
+            truncated: inspection.truncated,
+            warnings: inspection.warnings,
+            entries: inspection.entries.into_iter().map(ArchiveEntryResponse::from).collect(),
+        }
+    }
+}
+
+/// 压缩包附件解析响应 - API模型
+#[derive(Debug, Serialize)]
+pub struct ArchiveInspectionResponse {
+    /// 状态码
+    pub code: u32,
+    /// 数据
+    pub data: ArchiveInspectionData,
+}
+
+/// 处置动作计数 - API模型
+#[derive(Debug, Serialize)]
+pub struct ActionCountResponse {
+    /// 处置动作
+    pub status: EmailStatus,
+    /// 处置动作中文名称
+    pub status_label: String,
+    /// 邮件数
+    pub count: u64,
+}
+
+// 从领域模型转换
+impl From<ActionCount> for ActionCountResponse {
+    fn from(action: ActionCount) -> Self {
+        Self {
+            status: action.status,
+            status_label: action.status.label().to_string(),
+            count: action.count,
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,104 @@
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use crate::content::ExportFormat;
+use crate::models::api::email::StatusList;
+use crate::models::domain::export::{ExportJob, ExportJobStatus};
+
+/// 导出邮件过滤条件 - API模型
+#[derive(Debug, Deserialize)]
+pub struct ExportFilterQuery {
+    /// 开始时间
+    pub start_time: DateTime<Utc>,
+    /// 结束时间
+    pub end_time: DateTime<Utc>,
+    /// 情报ID
+    pub intelligence_id: String,
+    /// 邮件状态，可为单个值或数组
+    pub status: Option<StatusList>,
+}
+
+/// 批量导出邮件请求 - API模型
+///
+/// `filter`和`mail_ids`二选一
+#[derive(Debug, Deserialize)]
+pub struct EmailExportRequest {
+    /// 导出格式，默认zip
+    #[serde(default)]
+    pub format: ExportFormat,
+    /// 按关联邮件查询条件导出
+    pub filter: Option<ExportFilterQuery>,
+    /// 按邮件ID列表导出
+    pub mail_ids: Option<Vec<String>>,
+    /// 强制以异步任务方式导出
+    #[serde(default)]
+    pub async_job: bool,
+}
+
+/// 导出任务查询参数 - API模型
+#[derive(Debug, Deserialize)]
+pub struct ExportJobQuery {
+    /// 任务ID
+    pub job_id: String,
+}
+
+/// 导出任务信息 - API模型
+#[derive(Debug, Serialize)]
+pub struct ExportJobData {
+    /// 任务ID
+    pub job_id: String,
+    /// 导出格式
+    pub format: ExportFormat,
+    /// 任务状态
+    pub status: ExportJobStatus,
+    /// 需要导出的邮件数
+    pub total: u32,
+    /// 已处理的邮件数
+    pub processed: u32,
+    /// 获取失败的邮件数
+    pub failed: u32,
+    /// 进度百分比（0-100）
+    pub progress: u32,
+    /// 创建时间
+    pub created_at: DateTime<Utc>,
+    /// 结束时间
+    pub finished_at: Option<DateTime<Utc>>,
+    /// 失败原因
+    pub error: Option<String>,
+    /// 下载地址，任务完成后提供
+    pub download_url: Option<String>,
+}
+
+// 从领域模型转换
+impl From<ExportJob> for ExportJobData {
+    fn from(job: ExportJob) -> Self {
+        // 没有邮件的任务完成即为100%
+        let progress = (job.processed * 100)
+            .checked_div(job.total)
+            .unwrap_or(if job.status == ExportJobStatus::Completed { 100 } else { 0 });
+        let download_url = (job.status == ExportJobStatus::Completed)
+            .then(|| format!("/email/export/download/{}", job.id));
+
+        Self {
+            job_id: job.id,
+            format: job.format,
+            status: job.status,
+            total: job.total,
+            processed: job.processed,
+            failed: job.failed,
+            progress,
+            created_at: job.created_at,
+            finished_at: job.finished_at,
+            error: job.error,
+            download_url,
+        }
+    }
+}
+
+/// 导出任务响应 - API模型
+#[derive(Debug, Serialize)]
+pub struct ExportJobResponse {
+    /// 状态码
+    pub code: u32,
+    /// 任务信息
+    pub data: ExportJobData,
+}
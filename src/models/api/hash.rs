@@ -0,0 +1,162 @@
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use crate::models::api::email::AttachmentResponse;
+use crate::models::api::statistics::NamedCountResponse;
+use crate::models::domain::email::EmailStatus;
+use crate::models::domain::hash::{
+    HashAttachment, HashIntelligence, HashKind, HashMail, HashMatchSource, HashPivot,
+};
+
+/// 哈希关联查询参数 - API模型
+#[derive(Debug, Deserialize)]
+pub struct HashPivotQuery {
+    /// MD5、SHA1或SHA256，根据长度识别算法，不区分大小写
+    pub hash: String,
+    /// 返回的关联邮件数量，默认100
+    pub limit: Option<u32>,
+}
+
+/// 关联到哈希的邮件 - API模型
+#[derive(Debug, Serialize)]
+pub struct HashMailResponse {
+    /// 邮件ID
+    pub mail_id: String,
+    /// 邮件检测时间
+    pub timestamp: DateTime<Utc>,
+    /// 邮件主题
+    pub subject: String,
+    /// 发件人
+    pub sender: String,
+    /// 收件人列表
+    pub recipients: Vec<String>,
+    /// 处置动作
+    pub status: EmailStatus,
+    /// 处置动作中文名称
+    pub status_label: String,
+    /// 关联方式
+    pub matched_by: Vec<HashMatchSource>,
+}
+
+// 从领域模型转换
+impl From<HashMail> for HashMailResponse {
+    fn from(mail: HashMail) -> Self {
+        Self {
+            mail_id: mail.mail_id,
+            timestamp: mail.timestamp,
+            subject: mail.subject,
+            sender: mail.sender,
+            recipients: mail.recipients,
+            status: mail.status,
+            status_label: mail.status.label().to_string(),
+            matched_by: mail.matched_by,
+        }
+    }
+}
+
+/// 哈希相同的附件 - API模型
+#[derive(Debug, Serialize)]
+pub struct HashAttachmentResponse {
+    /// 所属邮件ID
+    pub mail_id: String,
+    /// 附件信息
+    pub attachment: AttachmentResponse,
+}
+
+// 从领域模型转换
+impl From<HashAttachment> for HashAttachmentResponse {
+    fn from(item: HashAttachment) -> Self {
+        Self {
+            mail_id: item.mail_id,
+            attachment: AttachmentResponse::from(item.attachment),
+        }
+    }
+}
+
+/// 哈希对应的情报 - API模型
+#[derive(Debug, Serialize)]
+pub struct HashIntelligenceResponse {
+    /// 情报ID
+    pub intelligence_id: String,
+    /// 情报属性
+    pub attribute: String,
+    /// 情报分类
+    pub intelligence_type: String,
+    /// 紧急程度
+    pub urgency: String,
+    /// 情报描述
+    pub description: String,
+    /// 攻击组织名称
+    pub threat_actor: String,
+    /// 命中的邮件数
+    pub hit_mails: u64,
+    /// 首次命中时间
+    pub first_hit: Option<DateTime<Utc>>,
+    /// 最近命中时间
+    pub last_hit: Option<DateTime<Utc>>,
+}
+
+// 从领域模型转换
+impl From<HashIntelligence> for HashIntelligenceResponse {
+    fn from(intel: HashIntelligence) -> Self {
+        Self {
+            intelligence_id: intel.intelligence_id,
+            attribute: intel.attribute,
+            intelligence_type: intel.intelligence_type,
+            urgency: intel.urgency,
+            description: intel.description,
+            threat_actor: intel.threat_actor,
+            hit_mails: intel.hit_mails,
+            first_hit: intel.first_hit,
+            last_hit: intel.last_hit,
+        }
+    }
+}
+
+/// 哈希关联结果 - API模型
+#[derive(Debug, Serialize)]
+pub struct HashPivotData {
+    /// 哈希值（小写）
+    pub hash: String,
+    /// 哈希算法
+    pub kind: HashKind,
+    /// 关联邮件总数
+    pub total_mails: u64,
+    /// 首次出现时间
+    pub first_seen: Option<DateTime<Utc>>,
+    /// 最近出现时间
+    pub last_seen: Option<DateTime<Utc>>,
+    /// 关联邮件
+    pub mails: Vec<HashMailResponse>,
+    /// 哈希相同的附件
+    pub attachments: Vec<HashAttachmentResponse>,
+    /// 哈希对应的情报
+    pub intelligence: Vec<HashIntelligenceResponse>,
+    /// 受影响的收件人
+    pub recipients: Vec<NamedCountResponse>,
+}
+
+// 从领域模型转换
+impl From<HashPivot> for HashPivotData {
+    fn from(pivot: HashPivot) -> Self {
+        Self {
+            hash: pivot.hash,
+            kind: pivot.kind,
+            total_mails: pivot.total_mails,
+            first_seen: pivot.first_seen,
+            last_seen: pivot.last_seen,
+            mails: pivot.mails.into_iter().map(Into::into).collect(),
+            attachments: pivot.attachments.into_iter().map(Into::into).collect(),
+            intelligence: pivot.intelligence.into_iter().map(Into::into).collect(),
+            recipients: pivot.recipients.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// 哈希关联响应 - API模型
+#[derive(Debug, Serialize)]
+pub struct HashPivotResponse {
+    /// 状态码
+    pub code: u32,
+    /// 关联结果
+    pub data: HashPivotData,
+}
@@ -3,4 +3,14 @@
 pub mod statistics;
 pub mod email;
 pub mod intelligence;
-pub mod timeline;
\ No newline at end of file
+pub mod timeline;
+pub mod spread;
+pub mod export;
+pub mod quarantine;
+pub mod recipient;
+pub mod sender;
+pub mod hash;
+pub mod thread;
+pub mod campaign;
+pub mod storage;
+pub mod system;
\ No newline at end of file
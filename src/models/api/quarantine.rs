@@ -0,0 +1,174 @@
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use crate::models::domain::quarantine::{
+    AuditEntry, AuditEvent, QuarantineAction, QuarantineActionKind, QuarantineActionState,
+};
+
+/// 提交隔离邮件处置请求 - API模型
+///
+/// 放行、删除、重新投递共用；`recipients`仅对重新投递有效，为空时使用原收件人。
+/// 申请人取自`Authorization: Bearer`令牌认证的操作人
+#[derive(Debug, Deserialize)]
+pub struct QuarantineActionQuery {
+    /// 邮件ID
+    pub mail_id: String,
+    /// 处置原因
+    #[serde(default)]
+    pub reason: String,
+    /// 重新投递的收件人
+    #[serde(default)]
+    pub recipients: Vec<String>,
+}
+
+/// 批准处置请求 - API模型
+///
+/// 审批人取自`Authorization: Bearer`令牌认证的操作人
+#[derive(Debug, Deserialize)]
+pub struct QuarantineApproveQuery {
+    /// 处置请求ID
+    pub action_id: String,
+}
+
+/// 驳回处置请求 - API模型
+///
+/// 审批人取自`Authorization: Bearer`令牌认证的操作人
+#[derive(Debug, Deserialize)]
+pub struct QuarantineRejectQuery {
+    /// 处置请求ID
+    pub action_id: String,
+    /// 驳回原因
+    pub reason: String,
+}
+
+/// 处置请求列表查询参数 - API模型
+#[derive(Debug, Default, Deserialize)]
+pub struct QuarantineActionListQuery {
+    /// 处置请求ID，指定时只返回该请求
+    pub action_id: Option<String>,
+    /// 邮件ID
+    pub mail_id: Option<String>,
+    /// 处置状态
+    pub state: Option<QuarantineActionState>,
+}
+
+/// 审计记录查询参数 - API模型
+#[derive(Debug, Default, Deserialize)]
+pub struct QuarantineAuditQuery {
+    /// 邮件ID
+    pub mail_id: Option<String>,
+    /// 处置请求ID
+    pub action_id: Option<String>,
+}
+
+/// 处置请求信息 - API模型
+#[derive(Debug, Serialize)]
+pub struct QuarantineActionData {
+    /// 处置请求ID
+    pub action_id: String,
+    /// 邮件ID
+    pub mail_id: String,
+    /// 处置类型
+    pub kind: QuarantineActionKind,
+    /// 处置类型中文名称
+    pub kind_label: String,
+    /// 当前状态
+    pub state: QuarantineActionState,
+    /// 投递收件人
+    pub recipients: Vec<String>,
+    /// 处置原因
+    pub reason: String,
+    /// 申请人
+    pub requested_by: String,
+    /// 申请时间
+    pub requested_at: DateTime<Utc>,
+    /// 审批人
+    pub reviewed_by: Option<String>,
+    /// 审批时间
+    pub reviewed_at: Option<DateTime<Utc>>,
+    /// 执行完成时间
+    pub completed_at: Option<DateTime<Utc>>,
+    /// 执行失败或驳回的原因
+    pub error: Option<String>,
+}
+
+// 从领域模型转换
+impl From<QuarantineAction> for QuarantineActionData {
+    fn from(action: QuarantineAction) -> Self {
+        Self {
+            action_id: action.id,
+            mail_id: action.mail_id,
+            kind: action.kind,
+            kind_label: action.kind.label().to_string(),
+            state: action.state,
+            recipients: action.recipients,
+            reason: action.reason,
+            requested_by: action.requested_by,
+            requested_at: action.requested_at,
+            reviewed_by: action.reviewed_by,
+            reviewed_at: action.reviewed_at,
+            completed_at: action.completed_at,
+            error: action.error,
+        }
+    }
+}
+
+/// 审计记录 - API模型
+#[derive(Debug, Serialize)]
+pub struct AuditEntryData {
+    /// 记录时间
+    pub timestamp: DateTime<Utc>,
+    /// 处置请求ID
+    pub action_id: String,
+    /// 邮件ID
+    pub mail_id: String,
+    /// 处置类型
+    pub kind: QuarantineActionKind,
+    /// 事件类型
+    pub event: AuditEvent,
+    /// 操作人
+    pub actor: String,
+    /// 详细说明
+    pub detail: String,
+}
+
+// 从领域模型转换
+impl From<AuditEntry> for AuditEntryData {
+    fn from(entry: AuditEntry) -> Self {
+        Self {
+            timestamp: entry.timestamp,
+            action_id: entry.action_id,
+            mail_id: entry.mail_id,
+            kind: entry.kind,
+            event: entry.event,
+            actor: entry.actor,
+            detail: entry.detail,
+        }
+    }
+}
+
+/// 处置请求响应 - API模型
+#[derive(Debug, Serialize)]
+pub struct QuarantineActionResponse {
+    /// 状态码
+    pub code: u32,
+    /// 处置请求信息
+    pub data: QuarantineActionData,
+}
+
+/// 处置请求列表响应 - API模型
+#[derive(Debug, Serialize)]
+pub struct QuarantineActionListResponse {
+    /// 状态码
+    pub code: u32,
+    /// 处置请求列表
+    pub data: Vec<QuarantineActionData>,
+}
+
+/// 审计记录响应 - API模型
+#[derive(Debug, Serialize)]
+pub struct QuarantineAuditResponse {
+    /// 状态码
+    pub code: u32,
+    /// 审计记录
+    pub data: Vec<AuditEntryData>,
+}
@@ -0,0 +1,147 @@
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use crate::models::api::email::ActionCountResponse;
+use crate::models::api::statistics::NamedCountResponse;
+use crate::models::domain::recipient::{ExposureTrendPoint, RecipientExposure, RecipientProfile};
+use crate::models::domain::statistics::TrendInterval;
+
+/// 收件人暴露画像查询参数 - API模型
+#[derive(Debug, Deserialize)]
+pub struct RecipientProfileQuery {
+    /// 收件人邮箱地址
+    pub address: String,
+    /// 开始时间
+    pub start_time: DateTime<Utc>,
+    /// 结束时间
+    pub end_time: DateTime<Utc>,
+    /// 趋势分桶粒度，默认按天
+    #[serde(default)]
+    pub interval: TrendInterval,
+    /// 返回的攻击组织数量，默认10
+    pub top_actors: Option<u32>,
+}
+
+/// 收件人暴露排行查询参数 - API模型
+#[derive(Debug, Deserialize)]
+pub struct RecipientRankQuery {
+    /// 开始时间
+    pub start_time: DateTime<Utc>,
+    /// 结束时间
+    pub end_time: DateTime<Utc>,
+    /// 返回的收件人数量，默认20
+    pub limit: Option<u32>,
+}
+
+/// 收件人暴露趋势点 - API模型
+#[derive(Debug, Serialize)]
+pub struct ExposureTrendPointResponse {
+    /// 分桶起始时间
+    pub time: DateTime<Utc>,
+    /// 收到的邮件数
+    pub received: u64,
+    /// 命中情报的邮件数
+    pub targeted: u64,
+}
+
+// 从领域模型转换
+impl From<ExposureTrendPoint> for ExposureTrendPointResponse {
+    fn from(point: ExposureTrendPoint) -> Self {
+        Self {
+            time: point.time,
+            received: point.received,
+            targeted: point.targeted,
+        }
+    }
+}
+
+/// 收件人暴露画像 - API模型
+#[derive(Debug, Serialize)]
+pub struct RecipientProfileData {
+    /// 收件人邮箱地址
+    pub address: String,
+    /// 收到的邮件总数
+    pub total_received: u64,
+    /// 命中情报的邮件数
+    pub targeted_mails: u64,
+    /// 情报命中总次数
+    pub total_hits: u64,
+    /// 按情报类型统计的命中次数
+    pub hits_by_type: Vec<NamedCountResponse>,
+    /// 按紧急程度统计的命中次数
+    pub hits_by_urgency: Vec<NamedCountResponse>,
+    /// 按处置动作统计的邮件数
+    pub actions: Vec<ActionCountResponse>,
+    /// 首次被攻击时间
+    pub first_targeted: Option<DateTime<Utc>>,
+    /// 最近被攻击时间
+    pub last_targeted: Option<DateTime<Utc>>,
+    /// 主要攻击组织
+    pub top_threat_actors: Vec<NamedCountResponse>,
+    /// 趋势
+    pub trend: Vec<ExposureTrendPointResponse>,
+}
+
+// 从领域模型转换
+impl From<RecipientProfile> for RecipientProfileData {
+    fn from(profile: RecipientProfile) -> Self {
+        Self {
+            address: profile.address,
+            total_received: profile.total_received,
+            targeted_mails: profile.targeted_mails,
+            total_hits: profile.total_hits,
+            hits_by_type: profile.hits_by_type.into_iter().map(Into::into).collect(),
+            hits_by_urgency: profile.hits_by_urgency.into_iter().map(Into::into).collect(),
+            actions: profile.actions.into_iter().map(Into::into).collect(),
+            first_targeted: profile.first_targeted,
+            last_targeted: profile.last_targeted,
+            top_threat_actors: profile.top_threat_actors.into_iter().map(Into::into).collect(),
+            trend: profile.trend.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// 收件人暴露排行项 - API模型
+#[derive(Debug, Serialize)]
+pub struct RecipientExposureData {
+    /// 排名，从1开始
+    pub rank: u32,
+    /// 收件人邮箱地址
+    pub address: String,
+    /// 收到的邮件数
+    pub total_received: u64,
+    /// 命中情报的邮件数
+    pub targeted_mails: u64,
+    /// 最近被攻击时间
+    pub last_targeted: Option<DateTime<Utc>>,
+}
+
+impl RecipientExposureData {
+    /// 从领域模型转换，排名由调用方按顺序给出
+    pub fn new(rank: u32, exposure: RecipientExposure) -> Self {
+        Self {
+            rank,
+            address: exposure.address,
+            total_received: exposure.total_received,
+            targeted_mails: exposure.targeted_mails,
+            last_targeted: exposure.last_targeted,
+        }
+    }
+}
+
+/// 收件人暴露画像响应 - API模型
+#[derive(Debug, Serialize)]
+pub struct RecipientProfileResponse {
+    /// 状态码
+    pub code: u32,
+    /// 暴露画像
+    pub data: RecipientProfileData,
+}
+
+/// 收件人暴露排行响应 - API模型
+#[derive(Debug, Serialize)]
+pub struct RecipientRankResponse {
+    /// 状态码
+    pub code: u32,
+    /// 排行列表
+    pub data: Vec<RecipientExposureData>,
+}
@@ -0,0 +1,158 @@
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use crate::models::api::email::ActionCountResponse;
+use crate::models::api::statistics::NamedCountResponse;
+use crate::models::domain::sender::{SenderKind, SenderProfile, SenderTrendPoint, SendingSource};
+use crate::models::domain::statistics::TrendInterval;
+
+/// 发件人信誉画像查询参数 - API模型
+#[derive(Debug, Deserialize)]
+pub struct SenderProfileQuery {
+    /// 发件人邮箱地址或域名
+    pub sender: String,
+    /// 匹配方式，address或domain，不传时根据是否包含@推断
+    pub kind: Option<SenderKind>,
+    /// 开始时间
+    pub start_time: DateTime<Utc>,
+    /// 结束时间
+    pub end_time: DateTime<Utc>,
+    /// 趋势分桶粒度，默认按天
+    #[serde(default)]
+    pub interval: TrendInterval,
+    /// 返回的发信来源数量，默认10
+    pub top_ips: Option<u32>,
+}
+
+/// 发信来源 - API模型
+#[derive(Debug, Serialize)]
+pub struct SendingSourceResponse {
+    /// 客户端IP
+    pub ip: String,
+    /// 客户端PTR
+    pub ptr: String,
+    /// 邮件数
+    pub count: u64,
+    /// 最近一次发信时间
+    pub last_seen: Option<DateTime<Utc>>,
+}
+
+// 从领域模型转换
+impl From<SendingSource> for SendingSourceResponse {
+    fn from(source: SendingSource) -> Self {
+        Self {
+            ip: source.ip,
+            ptr: source.ptr,
+            count: source.count,
+            last_seen: source.last_seen,
+        }
+    }
+}
+
+/// 发件人发信趋势点 - API模型
+#[derive(Debug, Serialize)]
+pub struct SenderTrendPointResponse {
+    /// 分桶起始时间
+    pub time: DateTime<Utc>,
+    /// 邮件数
+    pub mails: u64,
+    /// 命中情报的邮件数
+    pub hit_mails: u64,
+}
+
+// 从领域模型转换
+impl From<SenderTrendPoint> for SenderTrendPointResponse {
+    fn from(point: SenderTrendPoint) -> Self {
+        Self {
+            time: point.time,
+            mails: point.mails,
+            hit_mails: point.hit_mails,
+        }
+    }
+}
+
+/// 发件人信誉画像 - API模型
+#[derive(Debug, Serialize)]
+pub struct SenderProfileData {
+    /// 发件人地址或域名（小写）
+    pub sender: String,
+    /// 匹配方式
+    pub kind: SenderKind,
+    /// 首次来信时间，不受查询时间范围限制
+    pub first_seen: Option<DateTime<Utc>>,
+    /// 最近来信时间，不受查询时间范围限制
+    pub last_seen: Option<DateTime<Utc>>,
+    /// 历史来信总数
+    pub lifetime_mails: u64,
+    /// 是否为首次来信的新发件人
+    pub new_sender: bool,
+    /// 时间范围内的邮件数
+    pub total_mails: u64,
+    /// 命中情报的邮件数
+    pub hit_mails: u64,
+    /// 不同收件人数
+    pub distinct_recipients: u64,
+    /// 发信来源
+    pub sending_sources: Vec<SendingSourceResponse>,
+    /// 使用TLS传输的邮件数
+    pub tls_mails: u64,
+    /// 使用TLS传输的邮件占比，0到1
+    pub tls_ratio: f64,
+    /// 按TLS版本统计的邮件数
+    pub tls_versions: Vec<NamedCountResponse>,
+    /// 经过SASL认证的邮件数
+    pub authenticated_mails: u64,
+    /// 未经认证的邮件数
+    pub unauthenticated_mails: u64,
+    /// 经过SASL认证的邮件占比，0到1
+    pub authenticated_ratio: f64,
+    /// 情报命中总次数
+    pub total_hits: u64,
+    /// 按情报类型统计的命中次数
+    pub hits_by_type: Vec<NamedCountResponse>,
+    /// 按处置动作统计的邮件数
+    pub actions: Vec<ActionCountResponse>,
+    /// 趋势
+    pub trend: Vec<SenderTrendPointResponse>,
+}
+
+/// 计算占比，没有邮件时为0
+fn ratio(part: u64, total: u64) -> f64 {
+    if total == 0 { 0.0 } else { part as f64 / total as f64 }
+}
+
+// 从领域模型转换
+impl From<SenderProfile> for SenderProfileData {
+    fn from(profile: SenderProfile) -> Self {
+        Self {
+            tls_ratio: ratio(profile.tls_mails, profile.total_mails),
+            unauthenticated_mails: profile.total_mails.saturating_sub(profile.authenticated_mails),
+            authenticated_ratio: ratio(profile.authenticated_mails, profile.total_mails),
+            sender: profile.sender,
+            kind: profile.kind,
+            first_seen: profile.first_seen,
+            last_seen: profile.last_seen,
+            lifetime_mails: profile.lifetime_mails,
+            new_sender: profile.new_sender,
+            total_mails: profile.total_mails,
+            hit_mails: profile.hit_mails,
+            distinct_recipients: profile.distinct_recipients,
+            sending_sources: profile.sending_sources.into_iter().map(Into::into).collect(),
+            tls_mails: profile.tls_mails,
+            tls_versions: profile.tls_versions.into_iter().map(Into::into).collect(),
+            authenticated_mails: profile.authenticated_mails,
+            total_hits: profile.total_hits,
+            hits_by_type: profile.hits_by_type.into_iter().map(Into::into).collect(),
+            actions: profile.actions.into_iter().map(Into::into).collect(),
+            trend: profile.trend.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// 发件人信誉画像响应 - API模型
+#[derive(Debug, Serialize)]
+pub struct SenderProfileResponse {
+    /// 状态码
+    pub code: u32,
+    /// 信誉画像
+    pub data: SenderProfileData,
+}
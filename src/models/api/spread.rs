@@ -0,0 +1,179 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::models::domain::spread::{Industry, Spread, SpreadBucket, SpreadIndustry, SpreadUnit};
+use crate::models::domain::statistics::TrendInterval;
+
+/// 情报跨单位扩散查询参数 - API模型
+#[derive(Debug, Deserialize)]
+pub struct SpreadQuery {
+    /// 情报ID
+    pub intelligence_id: Uuid,
+    /// 开始时间
+    pub start_time: Option<DateTime<Utc>>,
+    /// 结束时间
+    pub end_time: Option<DateTime<Utc>>,
+    /// 分桶粒度（hour、day或week），默认day
+    pub interval: Option<TrendInterval>,
+}
+
+/// GB/T 4754-2017行业 - API模型
+#[derive(Debug, Serialize)]
+pub struct IndustryResponse {
+    /// 行业代码，无法识别时为空
+    pub code: String,
+    /// 行业名称
+    pub name: String,
+}
+
+// 从领域模型转换为API模型
+impl From<Industry> for IndustryResponse {
+    fn from(industry: Industry) -> Self {
+        Self {
+            code: industry.code,
+            name: industry.name,
+        }
+    }
+}
+
+/// 命中单位 - API模型
+#[derive(Debug, Serialize)]
+pub struct SpreadUnitResponse {
+    /// 首次命中顺序，从1开始
+    pub order: u32,
+    /// 单位名称
+    pub unit_name: String,
+    /// 单位的行业（大类或门类）
+    pub industry: IndustryResponse,
+    /// 单位所属门类
+    pub section: IndustryResponse,
+    /// 首次命中时间
+    pub first_hit_time: DateTime<Utc>,
+    /// 最近命中时间
+    pub last_hit_time: DateTime<Utc>,
+    /// 累计命中数量
+    pub hit_count: u64,
+}
+
+// 从领域模型转换为API模型
+impl From<SpreadUnit> for SpreadUnitResponse {
+    fn from(unit: SpreadUnit) -> Self {
+        Self {
+            order: unit.order,
+            unit_name: unit.unit_name,
+            industry: unit.industry.into(),
+            section: unit.section.into(),
+            first_hit_time: unit.first_hit_time,
+            last_hit_time: unit.last_hit_time,
+            hit_count: unit.hit_count,
+        }
+    }
+}
+
+/// 命中门类 - API模型
+#[derive(Debug, Serialize)]
+pub struct SpreadIndustryResponse {
+    /// 首次命中顺序，从1开始
+    pub order: u32,
+    /// 门类代码
+    pub code: String,
+    /// 门类名称
+    pub name: String,
+    /// 首次命中时间
+    pub first_hit_time: DateTime<Utc>,
+    /// 命中单位数
+    pub unit_count: u64,
+    /// 累计命中数量
+    pub hit_count: u64,
+}
+
+// 从领域模型转换为API模型
+impl From<SpreadIndustry> for SpreadIndustryResponse {
+    fn from(industry: SpreadIndustry) -> Self {
+        Self {
+            order: industry.order,
+            code: industry.industry.code,
+            name: industry.industry.name,
+            first_hit_time: industry.first_hit_time,
+            unit_count: industry.unit_count,
+            hit_count: industry.hit_count,
+        }
+    }
+}
+
+/// 扩散分桶 - API模型
+#[derive(Debug, Serialize)]
+pub struct SpreadBucketResponse {
+    /// 分桶起始时间
+    pub time: DateTime<Utc>,
+    /// 分桶内命中的单位名称
+    pub units: Vec<String>,
+    /// 分桶内命中的门类
+    pub industries: Vec<IndustryResponse>,
+    /// 分桶内首次命中的单位数
+    pub new_units: u64,
+    /// 分桶内首次出现的门类数
+    pub new_industries: u64,
+    /// 截至分桶结束的累计单位数
+    pub cumulative_units: u64,
+    /// 截至分桶结束的累计门类数
+    pub cumulative_industries: u64,
+}
+
+// 从领域模型转换为API模型
+impl From<SpreadBucket> for SpreadBucketResponse {
+    fn from(bucket: SpreadBucket) -> Self {
+        Self {
+            time: bucket.time,
+            units: bucket.units,
+            industries: bucket.industries.into_iter().map(Into::into).collect(),
+            new_units: bucket.new_units,
+            new_industries: bucket.new_industries,
+            cumulative_units: bucket.cumulative_units,
+            cumulative_industries: bucket.cumulative_industries,
+        }
+    }
+}
+
+/// 情报跨单位扩散数据 - API模型
+#[derive(Debug, Serialize)]
+pub struct SpreadData {
+    /// 情报ID
+    pub intelligence_id: Uuid,
+    /// 分桶粒度
+    pub interval: TrendInterval,
+    /// 命中单位总数
+    pub total_units: u64,
+    /// 命中门类总数
+    pub total_industries: u64,
+    /// 全部命中单位，按首次命中时间正序
+    pub units: Vec<SpreadUnitResponse>,
+    /// 全部命中门类，按首次命中时间正序
+    pub industries: Vec<SpreadIndustryResponse>,
+    /// 时间范围内各分桶的命中情况，按时间正序
+    pub buckets: Vec<SpreadBucketResponse>,
+}
+
+// 从领域模型转换为API模型
+impl From<Spread> for SpreadData {
+    fn from(spread: Spread) -> Self {
+        Self {
+            intelligence_id: spread.intelligence_id,
+            interval: spread.interval,
+            total_units: spread.units.len() as u64,
+            total_industries: spread.industries.len() as u64,
+            units: spread.units.into_iter().map(SpreadUnitResponse::from).collect(),
+            industries: spread.industries.into_iter().map(SpreadIndustryResponse::from).collect(),
+            buckets: spread.buckets.into_iter().map(SpreadBucketResponse::from).collect(),
+        }
+    }
+}
+
+/// 情报跨单位扩散响应 - API模型
+#[derive(Debug, Serialize)]
+pub struct SpreadResponse {
+    /// 状态码
+    pub code: u32,
+    /// 数据
+    pub data: SpreadData,
+}
@@ -3,7 +3,7 @@ use serde::{Serialize, Deserialize};
 use std::collections::HashMap;
 use crate::models::domain::statistics::{
     ChangeDirection, BasicStatisticsItem, OrganizationStatisticsItem, 
-    IntelHitStatisticsItem, TrendChartItem, TrendPoint, StatisticsResult
+    IntelHitStatisticsItem, TrendChartItem, TrendPoint, StatisticsResult, NamedCount, TrendInterval
 };
 
 /// 统计数据查询参数
@@ -15,6 +15,9 @@ pub struct StatisticsQuery {
     pub end_time: DateTime<Utc>,
     /// 查询模块，用于区分查询哪个板块
     pub module: String,
+    /// 趋势分桶粒度，默认按天
+    #[serde(default)]
+    pub interval: TrendInterval,
     /// 额外参数，预留字段，用于未来扩展
     #[serde(default)]
     pub extras: HashMap<String, String>,
@@ -225,4 +228,23 @@ pub struct StatisticsResponse {
     pub code: u32,
     /// 响应数据
     pub data: StatisticsResponseData,
-} 
\ No newline at end of file
+}
+
+/// 分组计数 - API模型
+#[derive(Debug, Serialize)]
+pub struct NamedCountResponse {
+    /// 分组名称
+    pub name: String,
+    /// 计数值
+    pub count: u64,
+}
+
+// 从领域模型转换
+impl From<NamedCount> for NamedCountResponse {
+    fn from(item: NamedCount) -> Self {
+        Self {
+            name: item.name,
+            count: item.count,
+        }
+    }
+}
\ No newline at end of file
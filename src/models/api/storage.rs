@@ -0,0 +1,129 @@
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use crate::models::domain::storage::{IntegrityStatus, StorageCheck, StorageJobStatus, StorageVerifyJob};
+
+/// 创建存储校验任务请求 - API模型
+#[derive(Debug, Deserialize)]
+pub struct StorageVerifyRequest {
+    /// 只校验这些邮件的附件，不传时校验全部附件
+    pub mail_ids: Option<Vec<String>>,
+}
+
+/// 存储校验任务查询参数 - API模型
+#[derive(Debug, Deserialize)]
+pub struct StorageVerifyJobQuery {
+    /// 任务ID
+    pub job_id: String,
+}
+
+/// 单个附件的校验结果 - API模型
+#[derive(Debug, Serialize)]
+pub struct StorageCheckResponse {
+    /// 所属邮件ID
+    pub mail_id: String,
+    /// 附件ID
+    pub attachment_id: String,
+    /// 文件名
+    pub filename: String,
+    /// 存储路径
+    pub file_path: String,
+    /// 校验结果
+    pub status: IntegrityStatus,
+    /// 记录的文件大小
+    pub expected_size: u64,
+    /// 实际文件大小，文件缺失时为空
+    pub actual_size: Option<u64>,
+    /// 记录的MD5
+    pub expected_md5: String,
+    /// 实际MD5
+    pub actual_md5: Option<String>,
+    /// 记录的SHA256
+    pub expected_sha256: String,
+    /// 实际SHA256
+    pub actual_sha256: Option<String>,
+}
+
+// 从领域模型转换
+impl From<StorageCheck> for StorageCheckResponse {
+    fn from(check: StorageCheck) -> Self {
+        Self {
+            mail_id: check.mail_id,
+            attachment_id: check.attachment_id,
+            filename: check.filename,
+            file_path: check.file_path,
+            status: check.status,
+            expected_size: check.expected_size,
+            actual_size: check.actual_size,
+            expected_md5: check.expected_md5,
+            actual_md5: check.actual_md5,
+            expected_sha256: check.expected_sha256,
+            actual_sha256: check.actual_sha256,
+        }
+    }
+}
+
+/// 存储校验任务信息 - API模型
+#[derive(Debug, Serialize)]
+pub struct StorageVerifyJobData {
+    /// 任务ID
+    pub job_id: String,
+    /// 任务状态
+    pub status: StorageJobStatus,
+    /// 需要校验的附件数
+    pub total: u32,
+    /// 已校验的附件数
+    pub checked: u32,
+    /// 进度百分比（0-100）
+    pub progress: u32,
+    /// 校验通过的附件数
+    pub verified: u32,
+    /// 没有记录哈希的附件数
+    pub unverified: u32,
+    /// 内容损坏的附件数
+    pub corrupted: u32,
+    /// 文件缺失的附件数
+    pub missing: u32,
+    /// 损坏或缺失的附件
+    pub problems: Vec<StorageCheckResponse>,
+    /// 创建时间
+    pub created_at: DateTime<Utc>,
+    /// 结束时间
+    pub finished_at: Option<DateTime<Utc>>,
+    /// 失败原因
+    pub error: Option<String>,
+}
+
+// 从领域模型转换
+impl From<StorageVerifyJob> for StorageVerifyJobData {
+    fn from(job: StorageVerifyJob) -> Self {
+        // 没有附件的任务完成即为100%
+        let progress = (job.checked * 100)
+            .checked_div(job.total)
+            .unwrap_or(if job.status == StorageJobStatus::Completed { 100 } else { 0 });
+
+        Self {
+            job_id: job.id,
+            status: job.status,
+            total: job.total,
+            checked: job.checked,
+            progress,
+            verified: job.verified,
+            unverified: job.unverified,
+            corrupted: job.corrupted,
+            missing: job.missing,
+            problems: job.problems.into_iter().map(Into::into).collect(),
+            created_at: job.created_at,
+            finished_at: job.finished_at,
+            error: job.error,
+        }
+    }
+}
+
+/// 存储校验任务响应 - API模型
+#[derive(Debug, Serialize)]
+pub struct StorageVerifyJobResponse {
+    /// 状态码
+    pub code: u32,
+    /// 任务信息
+    pub data: StorageVerifyJobData,
+}
@@ -0,0 +1,40 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use crate::db::ConnectionStatus;
+
+/// 数据库连接状态 - API模型
+#[derive(Debug, Serialize)]
+pub struct DbStatusData {
+    /// 当前模式：memory、database或unavailable
+    pub mode: String,
+    /// 模式的中文名称
+    pub mode_label: String,
+    /// 进入当前模式的时间
+    pub since: DateTime<Utc>,
+    /// 最近一次连接失败或断开的原因
+    pub last_error: Option<String>,
+    /// 下一次重连的时间，已连接时为空
+    pub next_retry: Option<DateTime<Utc>>,
+}
+
+// 从领域模型转换
+impl From<ConnectionStatus> for DbStatusData {
+    fn from(status: ConnectionStatus) -> Self {
+        Self {
+            mode: status.mode.as_str().to_string(),
+            mode_label: status.mode.label().to_string(),
+            since: status.since,
+            last_error: status.last_error,
+            next_retry: status.next_retry,
+        }
+    }
+}
+
+/// 数据库连接状态响应 - API模型
+#[derive(Debug, Serialize)]
+pub struct DbStatusResponse {
+    /// 状态码
+    pub code: u32,
+    /// 连接状态
+    pub data: DbStatusData,
+}
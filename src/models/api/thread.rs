@@ -0,0 +1,132 @@
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use crate::content::ThreadLink;
+use crate::models::domain::email::EmailStatus;
+use crate::models::domain::thread::{MailThread, ThreadNode, ThreadParticipant};
+
+/// 邮件会话查询参数 - API模型
+#[derive(Debug, Deserialize)]
+pub struct ThreadQuery {
+    /// 邮件ID
+    pub mail_id: String,
+}
+
+/// 会话参与者 - API模型
+#[derive(Debug, Serialize)]
+pub struct ThreadParticipantResponse {
+    /// 邮箱地址
+    pub address: String,
+    /// 显示名称
+    pub name: String,
+    /// 发出的邮件数
+    pub sent: u64,
+    /// 收到（含抄送）的邮件数
+    pub received: u64,
+}
+
+// 从领域模型转换
+impl From<ThreadParticipant> for ThreadParticipantResponse {
+    fn from(participant: ThreadParticipant) -> Self {
+        Self {
+            address: participant.address,
+            name: participant.name,
+            sent: participant.sent,
+            received: participant.received,
+        }
+    }
+}
+
+/// 会话树节点 - API模型
+#[derive(Debug, Serialize)]
+pub struct ThreadNodeResponse {
+    /// 邮件ID
+    pub mail_id: String,
+    /// Message-ID
+    pub message_id: Option<String>,
+    /// 邮件主题
+    pub subject: String,
+    /// 发件人
+    pub sender: String,
+    /// 收件人与抄送
+    pub recipients: Vec<String>,
+    /// 邮件检测时间
+    pub time: DateTime<Utc>,
+    /// 邮件头中的发信时间
+    pub sent_time: Option<DateTime<Utc>>,
+    /// 处置动作
+    pub status: EmailStatus,
+    /// 处置动作中文名称
+    pub status_label: String,
+    /// 是否命中情报
+    pub hit_intelligence: bool,
+    /// 与父节点的关联方式
+    pub link: ThreadLink,
+    /// 回复，按时间排序
+    pub children: Vec<ThreadNodeResponse>,
+}
+
+// 从领域模型转换
+impl From<ThreadNode> for ThreadNodeResponse {
+    fn from(node: ThreadNode) -> Self {
+        Self {
+            mail_id: node.mail_id,
+            message_id: node.message_id,
+            subject: node.subject,
+            sender: node.sender,
+            recipients: node.recipients,
+            time: node.time,
+            sent_time: node.sent_time,
+            status: node.status,
+            status_label: node.status.label().to_string(),
+            hit_intelligence: node.hit_intelligence,
+            link: node.link,
+            children: node.children.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// 邮件会话 - API模型
+#[derive(Debug, Serialize)]
+pub struct MailThreadData {
+    /// 查询的邮件ID
+    pub mail_id: String,
+    /// 会话主题
+    pub subject: String,
+    /// 会话中的邮件数
+    pub message_count: u64,
+    /// 命中情报的邮件数
+    pub hit_messages: u64,
+    /// 最早邮件时间
+    pub first_time: DateTime<Utc>,
+    /// 最近邮件时间
+    pub last_time: DateTime<Utc>,
+    /// 参与者
+    pub participants: Vec<ThreadParticipantResponse>,
+    /// 会话树根节点
+    pub root: ThreadNodeResponse,
+}
+
+// 从领域模型转换
+impl From<MailThread> for MailThreadData {
+    fn from(thread: MailThread) -> Self {
+        Self {
+            mail_id: thread.mail_id,
+            subject: thread.subject,
+            message_count: thread.message_count,
+            hit_messages: thread.hit_messages,
+            first_time: thread.first_time,
+            last_time: thread.last_time,
+            participants: thread.participants.into_iter().map(Into::into).collect(),
+            root: ThreadNodeResponse::from(thread.root),
+        }
+    }
+}
+
+/// 邮件会话响应 - API模型
+#[derive(Debug, Serialize)]
+pub struct MailThreadResponse {
+    /// 状态码
+    pub code: u32,
+    /// 会话
+    pub data: MailThreadData,
+}
@@ -1,13 +1,61 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
-use crate::models::domain::timeline::{Timeline, TimelineEmail};
+use crate::models::api::email::{ActionCountResponse, StatusList};
+use crate::models::domain::email::EmailStatus;
+use crate::models::domain::statistics::TrendInterval;
+use crate::models::domain::timeline::{
+    Timeline, TimelineBucket, TimelineEmail, TimelineEvent, TimelineEventDetail, TimelineEventKind,
+    TimelineIntelligence, TimelineMatch,
+};
 
 /// 攻击时间线查询参数 - API模型
+///
+/// 情报ID、情报ID列表与攻击组织至少指定一项，同时指定时合并为一条时间线
 #[derive(Debug, Deserialize)]
 pub struct TimelineQuery {
+    /// 情报ID
+    pub intelligence_id: Option<Uuid>,
+    /// 情报ID列表
+    pub intelligence_ids: Option<Vec<Uuid>>,
+    /// 攻击组织，合并该组织的全部情报
+    pub threat_actor: Option<String>,
+    /// 开始时间
+    pub start_time: Option<DateTime<Utc>>,
+    /// 结束时间
+    pub end_time: Option<DateTime<Utc>>,
+    /// 处置动作，可为单个值或数组
+    pub action: Option<StatusList>,
+    /// 收件人邮箱域名
+    pub recipient_domain: Option<String>,
+    /// 页码
+    pub page: Option<u32>,
+    /// 每页大小
+    pub page_size: Option<u32>,
+    /// 分桶粒度（hour、day或week），指定时返回各分桶的邮件数而不是邮件列表
+    pub interval: Option<TrendInterval>,
+}
+
+/// 邮件命中的情报 - API模型
+#[derive(Debug, Serialize)]
+pub struct TimelineMatchResponse {
     /// 情报ID
     pub intelligence_id: Uuid,
+    /// 情报属性
+    pub attribute: String,
+    /// 情报值
+    pub value: String,
+}
+
+// 从领域模型转换为API模型
+impl From<TimelineMatch> for TimelineMatchResponse {
+    fn from(matched: TimelineMatch) -> Self {
+        Self {
+            intelligence_id: matched.intelligence_id,
+            attribute: matched.attribute,
+            value: matched.value,
+        }
+    }
 }
 
 /// 邮件信息 - API模型
@@ -18,11 +66,15 @@ pub struct TimelineEmailResponse {
     /// 邮件时间
     pub timestamp: DateTime<Utc>,
     /// 邮件状态
-    pub status: String,
+    pub status: EmailStatus,
+    /// 邮件状态显示名称
+    pub status_label: String,
     /// 发件人
     pub sender: String,
     /// 收件人
     pub recipient: Vec<String>,
+    /// 邮件命中的时间线情报
+    pub matched: Vec<TimelineMatchResponse>,
 }
 
 // 从领域模型转换为API模型
@@ -32,30 +84,187 @@ impl From<TimelineEmail> for TimelineEmailResponse {
             mail_id: email.mail_id,
             timestamp: email.timestamp,
             status: email.status,
+            status_label: email.status.label().to_string(),
             sender: email.sender,
             recipient: email.recipient,
+            matched: email.matched.into_iter().map(Into::into).collect(),
         }
     }
 }
 
-/// 攻击时间线数据 - API模型
+/// 时间线分桶 - API模型
 #[derive(Debug, Serialize)]
-pub struct TimelineData {
+pub struct TimelineBucketResponse {
+    /// 分桶起始时间
+    pub time: DateTime<Utc>,
+    /// 邮件数
+    pub total: u64,
+    /// 按处置动作分组的邮件数
+    pub actions: Vec<ActionCountResponse>,
+}
+
+// 从领域模型转换为API模型
+impl From<TimelineBucket> for TimelineBucketResponse {
+    fn from(bucket: TimelineBucket) -> Self {
+        Self {
+            time: bucket.time,
+            total: bucket.total,
+            actions: bucket.actions.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// 处置变更详情 - API模型
+#[derive(Debug, Serialize)]
+pub struct DispositionChangeResponse {
+    /// true表示加入名单或上报，false表示移出名单或撤销上报
+    pub enabled: bool,
+    /// 处置原因
+    pub reason: String,
+}
+
+/// 回溯排查结果 - API模型
+#[derive(Debug, Serialize)]
+pub struct RetroHuntResponse {
+    /// 排查任务ID
+    pub job_id: String,
+    /// 排查的邮件时间范围起点
+    pub scan_start_time: DateTime<Utc>,
+    /// 排查的邮件时间范围终点
+    pub scan_end_time: DateTime<Utc>,
+    /// 扫描的邮件数
+    pub scanned_mails: u64,
+    /// 新命中的邮件数
+    pub matched_mails: u64,
+}
+
+/// 时间线事件 - API模型
+///
+/// 详情按事件类型只填写其中一项：邮件命中为email，首次发现、更新和过期为description，
+/// 白名单、黑名单和上报为disposition，回溯排查为retro_hunt
+#[derive(Debug, Serialize)]
+pub struct TimelineEventResponse {
+    /// 事件类型
+    pub kind: TimelineEventKind,
+    /// 事件显示名称
+    pub kind_label: String,
+    /// 生命周期事件所属的情报，邮件命中事件为空
+    pub intelligence_id: Option<Uuid>,
+    /// 事件时间
+    pub time: DateTime<Utc>,
+    /// 操作人或来源，系统事件为空
+    pub actor: Option<String>,
+    /// 情报描述
+    pub description: Option<String>,
+    /// 命中的邮件
+    pub email: Option<TimelineEmailResponse>,
+    /// 处置变更
+    pub disposition: Option<DispositionChangeResponse>,
+    /// 回溯排查结果
+    pub retro_hunt: Option<RetroHuntResponse>,
+}
+
+// 从领域模型转换为API模型
+impl From<TimelineEvent> for TimelineEventResponse {
+    fn from(event: TimelineEvent) -> Self {
+        let mut response = Self {
+            kind: event.kind,
+            kind_label: event.label().to_string(),
+            intelligence_id: event.intelligence_id,
+            time: event.time,
+            actor: event.actor,
+            description: None,
+            email: None,
+            disposition: None,
+            retro_hunt: None,
+        };
+        match event.detail {
+            TimelineEventDetail::Email(email) => response.email = Some(email.into()),
+            TimelineEventDetail::Lifecycle { description } => response.description = Some(description),
+            TimelineEventDetail::Disposition(change) => {
+                response.disposition = Some(DispositionChangeResponse {
+                    enabled: change.enabled,
+                    reason: change.reason,
+                })
+            }
+            TimelineEventDetail::RetroHunt(result) => {
+                response.retro_hunt = Some(RetroHuntResponse {
+                    job_id: result.job_id,
+                    scan_start_time: result.scan_start_time,
+                    scan_end_time: result.scan_end_time,
+                    scanned_mails: result.scanned_mails,
+                    matched_mails: result.matched_mails,
+                })
+            }
+        }
+        response
+    }
+}
+
+/// 时间线情报 - API模型
+#[derive(Debug, Serialize)]
+pub struct TimelineIntelligenceResponse {
+    /// 情报ID
+    pub intelligence_id: Uuid,
+    /// 情报属性
+    pub attribute: String,
+    /// 情报值
+    pub value: String,
     /// 情报首次发现时间
     pub first_found_time: DateTime<Utc>,
     /// 情报来源
     pub source: String,
-    /// 相关邮件列表
+    /// 命中记录数
+    pub hit_count: u64,
+}
+
+// 从领域模型转换为API模型
+impl From<TimelineIntelligence> for TimelineIntelligenceResponse {
+    fn from(intelligence: TimelineIntelligence) -> Self {
+        Self {
+            intelligence_id: intelligence.intelligence_id,
+            attribute: intelligence.attribute,
+            value: intelligence.value,
+            first_found_time: intelligence.first_found_time,
+            source: intelligence.source,
+            hit_count: intelligence.hit_count,
+        }
+    }
+}
+
+/// 攻击时间线数据 - API模型
+#[derive(Debug, Serialize)]
+pub struct TimelineData {
+    /// 时间线包含的情报，按首次发现时间正序
+    pub intelligence: Vec<TimelineIntelligenceResponse>,
+    /// 最早的情报首次发现时间
+    pub first_found_time: DateTime<Utc>,
+    /// 情报来源，多个来源以顿号分隔
+    pub source: String,
+    /// 符合条件的邮件总数
+    pub total: u64,
+    /// 分桶粒度，未分桶时为空
+    pub interval: Option<TrendInterval>,
+    /// 当前页的邮件，按时间倒序，分桶时为空
     pub emails: Vec<TimelineEmailResponse>,
+    /// 各分桶的邮件数，按时间正序，未分桶时为空
+    pub buckets: Vec<TimelineBucketResponse>,
+    /// 邮件命中与情报生命周期事件，按时间倒序；分页时只包含本页邮件及其时间区间内的生命周期事件
+    pub events: Vec<TimelineEventResponse>,
 }
 
 // 从领域模型转换为API模型
 impl From<Timeline> for TimelineData {
     fn from(timeline: Timeline) -> Self {
         Self {
+            intelligence: timeline.intelligence.into_iter().map(TimelineIntelligenceResponse::from).collect(),
             first_found_time: timeline.first_found_time,
             source: timeline.source,
+            total: timeline.total,
+            interval: timeline.interval,
             emails: timeline.emails.into_iter().map(TimelineEmailResponse::from).collect(),
+            buckets: timeline.buckets.into_iter().map(TimelineBucketResponse::from).collect(),
+            events: timeline.events.into_iter().map(TimelineEventResponse::from).collect(),
         }
     }
 }
@@ -67,4 +276,4 @@ pub struct TimelineResponse {
     pub code: u32,
     /// 数据
     pub data: TimelineData,
-} 
\ No newline at end of file
+}
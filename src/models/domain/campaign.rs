@@ -0,0 +1,105 @@
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use crate::models::domain::email::EmailStatus;
+use crate::models::domain::statistics::NamedCount;
+
+/// 钓鱼批次聚类查询条件 - 领域模型
+#[derive(Debug, Clone)]
+pub struct CampaignFilter {
+    /// 开始时间
+    pub start_time: DateTime<Utc>,
+    /// 结束时间
+    pub end_time: DateTime<Utc>,
+    /// 批次最少包含的邮件数
+    pub min_size: u32,
+    /// 归入同一批次的最低相似度，0到1
+    pub min_similarity: f64,
+    /// 只返回包含命中该情报邮件的批次
+    pub intelligence_id: Option<String>,
+    /// 只返回包含该邮件的批次
+    pub mail_id: Option<u64>,
+    /// 返回的批次数量上限
+    pub limit: u32,
+}
+
+/// 批次成员邮件 - 领域模型
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CampaignMail {
+    /// 邮件ID
+    pub mail_id: String,
+    /// 邮件检测时间
+    pub timestamp: DateTime<Utc>,
+    /// 邮件主题
+    pub subject: String,
+    /// 发件人
+    pub sender: String,
+    /// 收件人列表
+    pub recipients: Vec<String>,
+    /// 处置动作
+    pub status: EmailStatus,
+    /// 是否命中情报
+    pub hit_intelligence: bool,
+    /// 与批次代表邮件（最早一封）的相似度
+    pub similarity: f64,
+}
+
+/// 批次关联的情报 - 领域模型
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CampaignIntelligence {
+    /// 情报ID
+    pub intelligence_id: String,
+    /// 情报属性
+    pub attribute: String,
+    /// 情报值
+    pub value: String,
+    /// 情报分类
+    pub intelligence_type: String,
+    /// 紧急程度
+    pub urgency: String,
+    /// 攻击组织名称
+    pub threat_actor: String,
+    /// 批次内命中该情报的邮件数
+    pub hit_mails: u64,
+}
+
+/// 钓鱼批次 - 领域模型
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Campaign {
+    /// 批次ID，取批次中最早一封邮件的ID
+    pub campaign_id: String,
+    /// 代表主题（最早一封邮件的主题）
+    pub subject: String,
+    /// 邮件数
+    pub size: u64,
+    /// 首次出现时间
+    pub first_seen: DateTime<Utc>,
+    /// 最近出现时间
+    pub last_seen: DateTime<Utc>,
+    /// 发件人及其邮件数，按邮件数倒序
+    pub senders: Vec<NamedCount>,
+    /// 收件人及其邮件数，按邮件数倒序
+    pub recipients: Vec<NamedCount>,
+    /// 正文链接的主机名
+    pub hosts: Vec<String>,
+    /// 附件哈希
+    pub attachment_hashes: Vec<String>,
+    /// 命中情报的邮件数
+    pub hit_mails: u64,
+    /// 批次内邮件命中的情报
+    pub intelligence: Vec<CampaignIntelligence>,
+    /// 成员邮件，按时间排序
+    pub mails: Vec<CampaignMail>,
+}
+
+/// 钓鱼批次聚类结果 - 领域模型
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CampaignList {
+    /// 参与聚类的邮件数
+    pub analyzed_mails: u64,
+    /// 时间范围内邮件超过上限，只分析了最近的部分
+    pub truncated: bool,
+    /// 满足条件的批次总数
+    pub total: u64,
+    /// 批次，按邮件数倒序，最多返回limit个
+    pub campaigns: Vec<Campaign>,
+}
@@ -1,5 +1,114 @@
+use std::fmt;
+use std::str::FromStr;
+
 use chrono::{DateTime, Utc};
 use serde::{Serialize, Deserialize};
+use tracing::warn;
+use crate::content::{ArchiveEntry, FileKind};
+use crate::db::models::ActionType;
+use crate::models::domain::storage::{IntegrityStatus, StoredBlob};
+
+/// 邮件状态，对应数据库中的处置动作
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum EmailStatus {
+    /// 接受
+    Accept,
+    /// 丢弃
+    Discard,
+    /// 拒绝
+    Reject,
+    /// 隔离
+    Quarantine,
+    /// 存储的处置动作无法识别，不参与状态过滤
+    Unknown,
+}
+
+impl EmailStatus {
+    /// 可用于过滤的全部状态
+    pub const ALL: [EmailStatus; 4] = [
+        EmailStatus::Accept,
+        EmailStatus::Discard,
+        EmailStatus::Reject,
+        EmailStatus::Quarantine,
+    ];
+
+    /// 序列化使用的稳定名称
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EmailStatus::Accept => "accept",
+            EmailStatus::Discard => "discard",
+            EmailStatus::Reject => "reject",
+            EmailStatus::Quarantine => "quarantine",
+            EmailStatus::Unknown => "unknown",
+        }
+    }
+
+    /// 中文显示名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            EmailStatus::Accept => "接受",
+            EmailStatus::Discard => "丢弃",
+            EmailStatus::Reject => "拒绝",
+            EmailStatus::Quarantine => "隔离",
+            EmailStatus::Unknown => "未知",
+        }
+    }
+}
+
+impl fmt::Display for EmailStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// 解析邮件状态，接受英文名称（不区分大小写）或中文显示名称
+impl FromStr for EmailStatus {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        EmailStatus::ALL
+            .into_iter()
+            .find(|status| status.as_str().eq_ignore_ascii_case(value) || status.label() == value)
+            .ok_or_else(|| format!("无效的邮件状态: {}，可选值: accept, discard, reject, quarantine", value))
+    }
+}
+
+impl From<ActionType> for EmailStatus {
+    fn from(action: ActionType) -> Self {
+        match action {
+            ActionType::Accept => EmailStatus::Accept,
+            ActionType::Discard => EmailStatus::Discard,
+            ActionType::Reject => EmailStatus::Reject,
+            ActionType::Quarantine => EmailStatus::Quarantine,
+        }
+    }
+}
+
+impl TryFrom<EmailStatus> for ActionType {
+    type Error = String;
+
+    fn try_from(status: EmailStatus) -> Result<Self, Self::Error> {
+        match status {
+            EmailStatus::Accept => Ok(ActionType::Accept),
+            EmailStatus::Discard => Ok(ActionType::Discard),
+            EmailStatus::Reject => Ok(ActionType::Reject),
+            EmailStatus::Quarantine => Ok(ActionType::Quarantine),
+            EmailStatus::Unknown => Err("未知的邮件状态没有对应的处置动作".to_string()),
+        }
+    }
+}
+
+impl EmailStatus {
+    /// 解析存储的处置动作名称，无法识别时返回`Unknown`而不是猜测为某个状态
+    pub fn from_stored(mail_id: u64, action_name: &str) -> Self {
+        action_name.parse::<ActionType>().map(Self::from).unwrap_or_else(|e| {
+            warn!("邮件{}的处置动作无法识别: {}", mail_id, e);
+            EmailStatus::Unknown
+        })
+    }
+}
 
 /// 邮件附件信息 - 领域模型
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,8 +123,72 @@ pub struct Attachment {
     pub size: u64,
     /// 文件扩展名
     pub file_extension: String,
+    /// 根据文件签名识别出的类型
+    pub detected_type: FileKind,
+    /// 扩展名是否与识别出的类型不符
+    pub extension_mismatch: bool,
     /// 文件MD5值
     pub md5: String,
+    /// 文件SHA256值
+    pub sha256: String,
+}
+
+/// 下载模式
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum DownloadMode {
+    /// 直接下载原始文件，需要显式指定
+    Raw,
+    /// 放入加密ZIP中下载，样本默认使用该模式
+    #[default]
+    Safe,
+}
+
+/// 下载内容的来源
+pub enum ContentBody {
+    /// 内存中生成的内容，如加密ZIP
+    Memory(Vec<u8>),
+    /// 存储中的原始文件，边读取边校验
+    Stored(StoredBlob),
+}
+
+/// 附件下载内容 - 领域模型
+pub struct AttachmentContent {
+    /// 文件名
+    pub filename: String,
+    /// 下载使用的Content-Type
+    pub content_type: String,
+    /// 根据文件签名识别出的类型
+    pub detected_type: FileKind,
+    /// 扩展名是否与识别出的类型不符
+    pub extension_mismatch: bool,
+    /// 与入库时记录的哈希比对的结果，存储中的文件在读取结束时才完成比对
+    pub integrity: IntegrityStatus,
+    /// 文件内容
+    pub body: ContentBody,
+}
+
+/// URL来源
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum UrlSource {
+    /// 邮件正文
+    EmailBody,
+    /// 图片附件或PDF内嵌图片中的二维码
+    QrCode,
+    /// 图片附件或PDF内嵌图片中的其他条码
+    Barcode,
+}
+
+/// URL命中的情报
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UrlIntelligence {
+    /// 情报ID
+    pub intelligence_id: String,
+    /// 情报属性
+    pub attribute: String,
+    /// 情报值
+    pub value: String,
 }
 
 /// URL信息 - 领域模型
@@ -23,10 +196,16 @@ pub struct Attachment {
 pub struct Url {
     /// URL ID
     pub id: String,
-    /// URL地址
+    /// URL地址，二维码与条码中解出的地址已归一化
     pub url: String,
     /// URL路径
     pub path: String,
+    /// URL来源
+    pub source: UrlSource,
+    /// 解出该URL的附件ID，来自正文时为None
+    pub attachment_id: Option<String>,
+    /// 命中的情报
+    pub intelligence: Vec<UrlIntelligence>,
 }
 
 /// 邮件信息 - 领域模型
@@ -49,7 +228,7 @@ pub struct Email {
     /// 邮件内容
     pub content: String,
     /// 邮件状态
-    pub status: String,
+    pub status: EmailStatus,
     /// 邮件源代码
     pub source_code: String,
 }
@@ -63,10 +242,131 @@ pub struct EmailFilter {
     pub end_time: DateTime<Utc>,
     /// 情报ID
     pub intelligence_id: String,
-    /// 邮件状态
-    pub status: Option<String>,
+    /// 邮件状态，为空表示不限
+    pub statuses: Vec<EmailStatus>,
+    /// 页码
+    pub page: u32,
+    /// 每页大小
+    pub page_size: u32,
+}
+
+/// 邮件搜索条件 - 领域模型
+///
+/// 各条件之间为AND关系，未指定的条件不参与过滤
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EmailSearchCriteria {
+    /// 开始时间
+    pub start_time: Option<DateTime<Utc>>,
+    /// 结束时间
+    pub end_time: Option<DateTime<Utc>>,
+    /// 主题关键字（不区分大小写）
+    pub subject: Option<String>,
+    /// 显示发件人关键字（不区分大小写）
+    pub display_from: Option<String>,
+    /// 收件人完整邮箱地址，匹配显示收件人或信封收件人
+    pub recipient_address: Option<String>,
+    /// 收件人邮箱账号，匹配显示收件人或信封收件人
+    pub recipient_account: Option<String>,
+    /// 收件人邮箱域名，匹配显示收件人或信封收件人
+    pub recipient_domain: Option<String>,
+    /// 客户端IP
+    pub client_ip: Option<String>,
+    /// 认证用户名
+    pub sasl_login: Option<String>,
+    /// 邮件方向
+    pub direction: Option<String>,
+    /// 处置动作，为空表示不限
+    pub actions: Vec<EmailStatus>,
+    /// 哈希值，匹配MD5、SHA1或SHA256
+    pub hash: Option<String>,
+    /// 传输层安全协议关键字
+    pub tls: Option<String>,
+    /// 协议信息关键字
+    pub protocol: Option<String>,
+    /// 命中的情报ID
+    pub intelligence_id: Option<String>,
+    /// 邮件ID，为None表示不限
+    pub mail_ids: Option<Vec<u64>>,
     /// 页码
     pub page: u32,
     /// 每页大小
     pub page_size: u32,
-} 
\ No newline at end of file
+}
+
+/// 情报高亮信息 - 领域模型
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IocHighlight {
+    /// 情报ID
+    pub intelligence_id: String,
+    /// 情报属性
+    pub attribute: String,
+    /// 情报值
+    pub value: String,
+    /// 在正文中的命中次数
+    pub hit_count: u32,
+}
+
+/// 邮件安全预览 - 领域模型
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailPreview {
+    /// 邮件ID
+    pub email_id: String,
+    /// 主题
+    pub subject: String,
+    /// 清洗并高亮后的HTML
+    pub html: String,
+    /// 情报高亮信息
+    pub highlights: Vec<IocHighlight>,
+}
+
+/// 压缩包附件解析结果 - 领域模型
+#[derive(Debug, Clone)]
+pub struct ArchiveInspection {
+    /// 附件ID
+    pub attachment_id: String,
+    /// 压缩包格式
+    pub format: FileKind,
+    /// 是否使用了解压密码
+    pub password_used: bool,
+    /// 是否因触发限制而截断
+    pub truncated: bool,
+    /// 解析过程中的警告
+    pub warnings: Vec<String>,
+    /// 顶层条目
+    pub entries: Vec<ArchiveEntry>,
+}
+
+/// 处置动作计数 - 领域模型
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionCount {
+    /// 处置动作
+    pub status: EmailStatus,
+    /// 邮件数
+    pub count: u64,
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stored_actions_map_to_statuses() {
+        for (name, status) in [
+            ("Accept", EmailStatus::Accept),
+            ("Discard", EmailStatus::Discard),
+            ("Reject", EmailStatus::Reject),
+            ("Quarantine", EmailStatus::Quarantine),
+        ] {
+            assert_eq!(EmailStatus::from_stored(1, name), status);
+            assert_eq!(ActionType::try_from(status).map(|action| action.as_str()), Ok(name));
+        }
+    }
+
+    #[test]
+    fn unrecognised_actions_are_unknown_not_accepted() {
+        for name in ["", "accept", "Bounce", "5"] {
+            assert_eq!(EmailStatus::from_stored(1, name), EmailStatus::Unknown);
+        }
+        assert!(ActionType::try_from(EmailStatus::Unknown).is_err());
+        assert!("unknown".parse::<EmailStatus>().is_err());
+    }
+}
@@ -0,0 +1,113 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use tokio::sync::mpsc;
+use crate::content::ExportFormat;
+use crate::models::domain::email::EmailFilter;
+
+/// 导出范围 - 领域模型
+#[derive(Debug, Clone)]
+pub enum ExportSelection {
+    /// 按关联邮件查询条件导出
+    Filter(EmailFilter),
+    /// 按邮件ID列表导出
+    MailIds(Vec<String>),
+}
+
+/// 导出任务状态
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportJobStatus {
+    /// 等待执行
+    Pending,
+    /// 执行中
+    Running,
+    /// 已完成，可以下载
+    Completed,
+    /// 执行失败
+    Failed,
+}
+
+/// 导出任务 - 领域模型
+#[derive(Debug, Clone)]
+pub struct ExportJob {
+    /// 任务ID
+    pub id: String,
+    /// 导出格式
+    pub format: ExportFormat,
+    /// 任务状态
+    pub status: ExportJobStatus,
+    /// 需要导出的邮件数
+    pub total: u32,
+    /// 已处理的邮件数
+    pub processed: u32,
+    /// 获取失败的邮件数
+    pub failed: u32,
+    /// 创建时间
+    pub created_at: DateTime<Utc>,
+    /// 结束时间
+    pub finished_at: Option<DateTime<Utc>>,
+    /// 失败原因
+    pub error: Option<String>,
+    /// 导出文件名
+    pub filename: String,
+    /// 导出文件在服务器上的路径
+    pub path: PathBuf,
+}
+
+/// 导出文件内容
+#[derive(Debug)]
+pub enum ExportData {
+    /// 同步导出的内容流：第一块内容已经生成，其余内容由后台逐块写入，导出失败时以错误结束
+    Stream {
+        /// 第一块内容
+        first: Vec<u8>,
+        /// 其余内容
+        rest: mpsc::Receiver<io::Result<Vec<u8>>>,
+    },
+    /// 异步任务生成的文件
+    File(PathBuf),
+}
+
+/// 导出文件 - 领域模型
+#[derive(Debug)]
+pub struct ExportFile {
+    /// 文件名
+    pub filename: String,
+    /// Content-Type
+    pub content_type: &'static str,
+    /// 文件内容
+    pub data: ExportData,
+}
+
+/// 导出请求的处理结果
+#[derive(Debug)]
+pub enum ExportOutcome {
+    /// 数量较少，直接返回导出文件
+    Ready(ExportFile),
+    /// 数量较多，已创建异步任务
+    Queued(ExportJob),
+}
+
+/// 导出失败的原因，路由层据此选择状态码
+#[derive(Debug, Clone)]
+pub enum ExportError {
+    /// 导出请求无效：邮件ID无效、邮件数超过上限或没有需要导出的邮件
+    InvalidRequest(String),
+    /// 原始邮件无法从文件存储读取
+    RawMailUnavailable(String),
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportError::InvalidRequest(message) => write!(f, "{}", message),
+            ExportError::RawMailUnavailable(mail_id) => write!(f, "原始邮件不可用: mail_id={}", mail_id),
+        }
+    }
+}
+
+impl std::error::Error for ExportError {}
@@ -0,0 +1,137 @@
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use crate::models::domain::email::{Attachment, EmailStatus};
+use crate::models::domain::statistics::NamedCount;
+
+/// 哈希算法
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum HashKind {
+    /// MD5，32位十六进制
+    Md5,
+    /// SHA1，40位十六进制
+    Sha1,
+    /// SHA256，64位十六进制
+    Sha256,
+}
+
+impl HashKind {
+    /// 根据长度识别哈希算法，非十六进制字符串返回None
+    pub fn detect(hash: &str) -> Option<Self> {
+        if !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        match hash.len() {
+            32 => Some(HashKind::Md5),
+            40 => Some(HashKind::Sha1),
+            64 => Some(HashKind::Sha256),
+            _ => None,
+        }
+    }
+
+    /// 对应的情报属性名称，SHA1没有对应的情报属性
+    pub fn intelligence_attribute(&self) -> Option<&'static str> {
+        match self {
+            HashKind::Md5 => Some("Md5"),
+            HashKind::Sha1 => None,
+            HashKind::Sha256 => Some("Sha256"),
+        }
+    }
+}
+
+/// 哈希关联查询条件 - 领域模型
+#[derive(Debug, Clone)]
+pub struct HashPivotFilter {
+    /// 哈希值（小写十六进制）
+    pub hash: String,
+    /// 哈希算法
+    pub kind: HashKind,
+    /// 返回的邮件数量上限
+    pub limit: u32,
+}
+
+/// 邮件与哈希的关联方式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum HashMatchSource {
+    /// 邮件文件本身的哈希
+    Mail,
+    /// 邮件附件的哈希
+    Attachment,
+    /// 邮件命中了该哈希的情报
+    Intelligence,
+}
+
+/// 关联到哈希的邮件 - 领域模型
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HashMail {
+    /// 邮件ID
+    pub mail_id: String,
+    /// 邮件检测时间
+    pub timestamp: DateTime<Utc>,
+    /// 邮件主题
+    pub subject: String,
+    /// 发件人
+    pub sender: String,
+    /// 收件人列表
+    pub recipients: Vec<String>,
+    /// 处置动作
+    pub status: EmailStatus,
+    /// 关联方式
+    pub matched_by: Vec<HashMatchSource>,
+}
+
+/// 哈希相同的附件 - 领域模型
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HashAttachment {
+    /// 所属邮件ID
+    pub mail_id: String,
+    /// 附件信息
+    pub attachment: Attachment,
+}
+
+/// 哈希对应的情报 - 领域模型
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HashIntelligence {
+    /// 情报ID
+    pub intelligence_id: String,
+    /// 情报属性
+    pub attribute: String,
+    /// 情报分类
+    pub intelligence_type: String,
+    /// 紧急程度
+    pub urgency: String,
+    /// 情报描述
+    pub description: String,
+    /// 攻击组织名称
+    pub threat_actor: String,
+    /// 命中的邮件数
+    pub hit_mails: u64,
+    /// 首次命中时间
+    pub first_hit: Option<DateTime<Utc>>,
+    /// 最近命中时间
+    pub last_hit: Option<DateTime<Utc>>,
+}
+
+/// 哈希关联结果 - 领域模型
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HashPivot {
+    /// 哈希值（小写十六进制）
+    pub hash: String,
+    /// 哈希算法
+    pub kind: HashKind,
+    /// 关联邮件总数
+    pub total_mails: u64,
+    /// 首次出现时间
+    pub first_seen: Option<DateTime<Utc>>,
+    /// 最近出现时间
+    pub last_seen: Option<DateTime<Utc>>,
+    /// 关联邮件，按时间倒序，最多返回limit封
+    pub mails: Vec<HashMail>,
+    /// 哈希相同的附件
+    pub attachments: Vec<HashAttachment>,
+    /// 哈希对应的情报
+    pub intelligence: Vec<HashIntelligence>,
+    /// 受影响的收件人及其收到的关联邮件数，按邮件数倒序
+    pub recipients: Vec<NamedCount>,
+}
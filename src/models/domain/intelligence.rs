@@ -2,6 +2,7 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use uuid::Uuid;
+use crate::db::models::{self, AttributeType, UrgencyLevel};
 
 /// 情报来源类型
 #[derive(Debug, Clone, Deserialize, Serialize, Eq, PartialEq, Hash)]
@@ -27,6 +28,56 @@ pub enum IntelligenceType {
     File,
 }
 
+impl From<models::SourceType> for SourceType {
+    fn from(source: models::SourceType) -> Self {
+        match source {
+            models::SourceType::Local => SourceType::Local,
+            models::SourceType::Cloud => SourceType::Cloud,
+        }
+    }
+}
+
+impl From<SourceType> for models::SourceType {
+    fn from(source: SourceType) -> Self {
+        match source {
+            SourceType::Local => models::SourceType::Local,
+            SourceType::Cloud => models::SourceType::Cloud,
+        }
+    }
+}
+
+impl IntelligenceType {
+    /// 全部情报类型
+    pub const ALL: [IntelligenceType; 4] = [
+        IntelligenceType::Account,
+        IntelligenceType::Domain,
+        IntelligenceType::Url,
+        IntelligenceType::File,
+    ];
+
+    /// 情报类型包含的情报属性
+    pub fn attributes(&self) -> &'static [AttributeType] {
+        match self {
+            IntelligenceType::Account => &[AttributeType::EmailAddress],
+            IntelligenceType::Domain => &[AttributeType::Domain, AttributeType::UrlDomain, AttributeType::EmailDomain],
+            IntelligenceType::Url => &[AttributeType::Url],
+            IntelligenceType::File => &[AttributeType::Md5, AttributeType::Sha256],
+        }
+    }
+}
+
+/// 情报属性归入的情报类型，IP情报不属于任何类型
+impl TryFrom<AttributeType> for IntelligenceType {
+    type Error = String;
+
+    fn try_from(attribute: AttributeType) -> Result<Self, Self::Error> {
+        IntelligenceType::ALL
+            .into_iter()
+            .find(|intelligence_type| intelligence_type.attributes().contains(&attribute))
+            .ok_or_else(|| format!("情报属性{}没有对应的情报类型", attribute.as_str()))
+    }
+}
+
 /// 处置状态键名
 #[derive(Debug, Clone, Deserialize, Serialize, Eq, PartialEq, Hash)]
 #[serde(rename_all = "camelCase")]
@@ -51,6 +102,26 @@ pub enum Urgency {
     Low, 
 }
 
+impl From<UrgencyLevel> for Urgency {
+    fn from(urgency: UrgencyLevel) -> Self {
+        match urgency {
+            UrgencyLevel::High => Urgency::High,
+            UrgencyLevel::Medium => Urgency::Medium,
+            UrgencyLevel::Low => Urgency::Low,
+        }
+    }
+}
+
+impl From<Urgency> for UrgencyLevel {
+    fn from(urgency: Urgency) -> Self {
+        match urgency {
+            Urgency::High => UrgencyLevel::High,
+            Urgency::Medium => UrgencyLevel::Medium,
+            Urgency::Low => UrgencyLevel::Low,
+        }
+    }
+}
+
 /// 排序字段
 #[derive(Debug, Clone, Deserialize, Serialize, Eq, PartialEq, Hash)]
 #[serde(rename_all = "snake_case")]
@@ -175,4 +246,59 @@ pub struct Intelligence {
     pub contribution_unit: i32,
     /// 命中行业分布
     pub industry_distribution: Vec<IndustryDistribution>,
-} 
\ No newline at end of file
+} 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn urgency_converts_both_ways() {
+        let pairs = [
+            (UrgencyLevel::High, Urgency::High),
+            (UrgencyLevel::Medium, Urgency::Medium),
+            (UrgencyLevel::Low, Urgency::Low),
+        ];
+        assert_eq!(pairs.len(), UrgencyLevel::ALL.len());
+        for (stored, urgency) in pairs {
+            assert_eq!(Urgency::from(stored), urgency);
+            assert_eq!(UrgencyLevel::from(urgency), stored);
+        }
+    }
+
+    #[test]
+    fn source_type_converts_both_ways() {
+        let pairs = [
+            (models::SourceType::Local, SourceType::Local),
+            (models::SourceType::Cloud, SourceType::Cloud),
+        ];
+        assert_eq!(pairs.len(), models::SourceType::ALL.len());
+        for (stored, source) in pairs {
+            assert_eq!(SourceType::from(stored), source.clone());
+            assert_eq!(models::SourceType::from(source), stored);
+        }
+    }
+
+    #[test]
+    fn attribute_type_maps_to_intelligence_type() {
+        let expected = [
+            (AttributeType::Domain, Some(IntelligenceType::Domain)),
+            (AttributeType::Url, Some(IntelligenceType::Url)),
+            (AttributeType::EmailAddress, Some(IntelligenceType::Account)),
+            (AttributeType::Ipv4, None),
+            (AttributeType::Md5, Some(IntelligenceType::File)),
+            (AttributeType::UrlDomain, Some(IntelligenceType::Domain)),
+            (AttributeType::EmailDomain, Some(IntelligenceType::Domain)),
+            (AttributeType::Sha256, Some(IntelligenceType::File)),
+        ];
+        assert_eq!(expected.len(), AttributeType::ALL.len());
+        for (attribute, intelligence_type) in expected {
+            assert_eq!(IntelligenceType::try_from(attribute).ok(), intelligence_type, "{:?}", attribute);
+        }
+        for intelligence_type in IntelligenceType::ALL {
+            for attribute in intelligence_type.attributes() {
+                assert_eq!(IntelligenceType::try_from(*attribute), Ok(intelligence_type.clone()));
+            }
+        }
+    }
+}
@@ -3,4 +3,13 @@
 pub mod statistics;
 pub mod email;
 pub mod intelligence;
-pub mod timeline;
\ No newline at end of file
+pub mod timeline;
+pub mod spread;
+pub mod export;
+pub mod quarantine;
+pub mod recipient;
+pub mod sender;
+pub mod hash;
+pub mod thread;
+pub mod campaign;
+pub mod storage;
\ No newline at end of file
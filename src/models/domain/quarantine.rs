@@ -0,0 +1,330 @@
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use crate::models::domain::email::EmailStatus;
+
+/// 隔离邮件处置类型
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum QuarantineActionKind {
+    /// 放行：投递给原收件人
+    Release,
+    /// 删除：从隔离区移除，不再投递
+    Delete,
+    /// 重新投递：投递给指定收件人（默认原收件人）
+    Redeliver,
+}
+
+impl QuarantineActionKind {
+    /// 全部处置类型
+    pub const ALL: [QuarantineActionKind; 3] = [
+        QuarantineActionKind::Release,
+        QuarantineActionKind::Delete,
+        QuarantineActionKind::Redeliver,
+    ];
+
+    /// 序列化使用的稳定名称
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QuarantineActionKind::Release => "release",
+            QuarantineActionKind::Delete => "delete",
+            QuarantineActionKind::Redeliver => "redeliver",
+        }
+    }
+
+    /// 中文显示名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            QuarantineActionKind::Release => "放行",
+            QuarantineActionKind::Delete => "删除",
+            QuarantineActionKind::Redeliver => "重新投递",
+        }
+    }
+}
+
+/// 处置请求状态
+///
+/// 状态流转：requested → approved → done/failed，requested也可被驳回为rejected
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum QuarantineActionState {
+    /// 已提交，等待审批
+    Requested,
+    /// 已批准，等待执行
+    Approved,
+    /// 执行成功
+    Done,
+    /// 执行失败
+    Failed,
+    /// 审批驳回
+    Rejected,
+}
+
+impl QuarantineActionState {
+    /// 全部处置状态
+    pub const ALL: [QuarantineActionState; 5] = [
+        QuarantineActionState::Requested,
+        QuarantineActionState::Approved,
+        QuarantineActionState::Done,
+        QuarantineActionState::Failed,
+        QuarantineActionState::Rejected,
+    ];
+
+    /// 序列化使用的稳定名称
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QuarantineActionState::Requested => "requested",
+            QuarantineActionState::Approved => "approved",
+            QuarantineActionState::Done => "done",
+            QuarantineActionState::Failed => "failed",
+            QuarantineActionState::Rejected => "rejected",
+        }
+    }
+
+    /// 状态的版本号，沿流转方向递增，持久化时新状态覆盖旧状态
+    pub fn revision(&self) -> u64 {
+        match self {
+            QuarantineActionState::Requested => 1,
+            QuarantineActionState::Approved | QuarantineActionState::Rejected => 2,
+            QuarantineActionState::Done | QuarantineActionState::Failed => 3,
+        }
+    }
+
+    /// 是否允许从当前状态流转到目标状态
+    pub fn can_transition_to(&self, next: QuarantineActionState) -> bool {
+        use QuarantineActionState::*;
+        matches!(
+            (self, next),
+            (Requested, Approved) | (Requested, Rejected) | (Approved, Done) | (Approved, Failed)
+        )
+    }
+
+    /// 是否处于未结束状态
+    pub fn is_pending(&self) -> bool {
+        matches!(self, QuarantineActionState::Requested | QuarantineActionState::Approved)
+    }
+}
+
+/// 解析处置类型的稳定名称
+impl FromStr for QuarantineActionKind {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        QuarantineActionKind::ALL
+            .into_iter()
+            .find(|kind| kind.as_str() == value)
+            .ok_or_else(|| format!("无效的处置类型: {}", value))
+    }
+}
+
+/// 解析处置状态的稳定名称
+impl FromStr for QuarantineActionState {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        QuarantineActionState::ALL
+            .into_iter()
+            .find(|state| state.as_str() == value)
+            .ok_or_else(|| format!("无效的处置状态: {}", value))
+    }
+}
+
+/// 已认证的处置操作人
+///
+/// 只能由`QuarantineService`根据配置的操作人令牌认证后创建，申请人与审批人都取自这里，
+/// 不接受请求参数中自行填写的身份
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Operator {
+    /// 操作人名称
+    name: String,
+}
+
+impl Operator {
+    /// 认证通过后创建操作人
+    pub(crate) fn authenticated(name: String) -> Self {
+        Self { name }
+    }
+
+    /// 操作人名称
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// 隔离邮件处置请求 - 领域模型
+#[derive(Debug, Clone)]
+pub struct QuarantineAction {
+    /// 处置请求ID
+    pub id: String,
+    /// 邮件ID
+    pub mail_id: String,
+    /// 处置类型
+    pub kind: QuarantineActionKind,
+    /// 当前状态
+    pub state: QuarantineActionState,
+    /// 投递收件人，删除时为空
+    pub recipients: Vec<String>,
+    /// 处置原因
+    pub reason: String,
+    /// 申请人
+    pub requested_by: String,
+    /// 申请时间
+    pub requested_at: DateTime<Utc>,
+    /// 审批人
+    pub reviewed_by: Option<String>,
+    /// 审批时间
+    pub reviewed_at: Option<DateTime<Utc>>,
+    /// 执行完成时间
+    pub completed_at: Option<DateTime<Utc>>,
+    /// 执行失败或驳回的原因
+    pub error: Option<String>,
+}
+
+impl QuarantineAction {
+    /// 流转到目标状态，不允许的流转返回错误
+    pub fn transition(&mut self, next: QuarantineActionState) -> Result<(), QuarantineError> {
+        if !self.state.can_transition_to(next) {
+            return Err(QuarantineError::InvalidState(format!(
+                "处置请求{}当前状态为{:?}，不能变更为{:?}",
+                self.id, self.state, next
+            )));
+        }
+        self.state = next;
+        Ok(())
+    }
+}
+
+/// 隔离邮件的投递信息 - 领域模型
+#[derive(Debug, Clone)]
+pub struct MailDeliveryInfo {
+    /// 邮件ID
+    pub mail_id: String,
+    /// 邮件状态
+    pub status: EmailStatus,
+    /// 信封发件人
+    pub envelope_from: String,
+    /// 原收件人
+    pub recipients: Vec<String>,
+}
+
+/// 待投递或删除的隔离邮件
+#[derive(Debug, Clone)]
+pub struct QuarantinedMessage {
+    /// 处置请求ID
+    pub action_id: String,
+    /// 邮件ID
+    pub mail_id: String,
+    /// 信封发件人
+    pub envelope_from: String,
+    /// 原始邮件内容
+    pub data: Vec<u8>,
+}
+
+/// 审计事件类型
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditEvent {
+    /// 提交处置请求
+    Requested,
+    /// 批准
+    Approved,
+    /// 驳回
+    Rejected,
+    /// 执行成功
+    Executed,
+    /// 执行失败
+    Failed,
+}
+
+impl AuditEvent {
+    /// 全部事件类型
+    pub const ALL: [AuditEvent; 5] = [
+        AuditEvent::Requested,
+        AuditEvent::Approved,
+        AuditEvent::Rejected,
+        AuditEvent::Executed,
+        AuditEvent::Failed,
+    ];
+
+    /// 序列化使用的稳定名称
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditEvent::Requested => "requested",
+            AuditEvent::Approved => "approved",
+            AuditEvent::Rejected => "rejected",
+            AuditEvent::Executed => "executed",
+            AuditEvent::Failed => "failed",
+        }
+    }
+}
+
+/// 解析审计事件类型的稳定名称
+impl FromStr for AuditEvent {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        AuditEvent::ALL
+            .into_iter()
+            .find(|event| event.as_str() == value)
+            .ok_or_else(|| format!("无效的审计事件类型: {}", value))
+    }
+}
+
+/// 审计记录 - 领域模型
+#[derive(Debug, Clone)]
+pub struct AuditEntry {
+    /// 记录时间
+    pub timestamp: DateTime<Utc>,
+    /// 处置请求ID
+    pub action_id: String,
+    /// 邮件ID
+    pub mail_id: String,
+    /// 处置类型
+    pub kind: QuarantineActionKind,
+    /// 事件类型
+    pub event: AuditEvent,
+    /// 操作人，系统自动执行时为system
+    pub actor: String,
+    /// 详细说明
+    pub detail: String,
+}
+
+/// 隔离处置错误
+#[derive(Debug)]
+pub enum QuarantineError {
+    /// 请求参数无效
+    InvalidInput(String),
+    /// 未提供有效的操作人令牌
+    Unauthorized(String),
+    /// 邮件或处置请求不存在
+    NotFound(String),
+    /// 当前状态不允许该操作
+    InvalidState(String),
+    /// 违反双人审批规则
+    Forbidden(String),
+    /// 内部错误
+    Internal(anyhow::Error),
+}
+
+impl fmt::Display for QuarantineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuarantineError::InvalidInput(msg)
+            | QuarantineError::Unauthorized(msg)
+            | QuarantineError::NotFound(msg)
+            | QuarantineError::InvalidState(msg)
+            | QuarantineError::Forbidden(msg) => f.write_str(msg),
+            QuarantineError::Internal(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for QuarantineError {}
+
+impl From<anyhow::Error> for QuarantineError {
+    fn from(e: anyhow::Error) -> Self {
+        QuarantineError::Internal(e)
+    }
+}
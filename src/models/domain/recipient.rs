@@ -0,0 +1,81 @@
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use crate::models::domain::email::ActionCount;
+use crate::models::domain::statistics::{NamedCount, TrendInterval};
+
+/// 收件人暴露画像查询条件 - 领域模型
+#[derive(Debug, Clone)]
+pub struct RecipientProfileFilter {
+    /// 收件人邮箱地址，匹配显示收件人或信封收件人，不区分大小写
+    pub address: String,
+    /// 开始时间
+    pub start_time: DateTime<Utc>,
+    /// 结束时间
+    pub end_time: DateTime<Utc>,
+    /// 趋势分桶粒度
+    pub interval: TrendInterval,
+    /// 返回的攻击组织数量
+    pub top_actors: u32,
+}
+
+/// 收件人暴露排行查询条件 - 领域模型
+#[derive(Debug, Clone)]
+pub struct RecipientRankFilter {
+    /// 开始时间
+    pub start_time: DateTime<Utc>,
+    /// 结束时间
+    pub end_time: DateTime<Utc>,
+    /// 返回的收件人数量
+    pub limit: u32,
+}
+
+/// 收件人暴露趋势点 - 领域模型
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExposureTrendPoint {
+    /// 分桶起始时间
+    pub time: DateTime<Utc>,
+    /// 收到的邮件数
+    pub received: u64,
+    /// 命中情报的邮件数
+    pub targeted: u64,
+}
+
+/// 收件人暴露画像 - 领域模型
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecipientProfile {
+    /// 收件人邮箱地址（小写）
+    pub address: String,
+    /// 收到的邮件总数
+    pub total_received: u64,
+    /// 命中情报的邮件数
+    pub targeted_mails: u64,
+    /// 情报命中总次数
+    pub total_hits: u64,
+    /// 按情报类型统计的命中次数，按次数倒序
+    pub hits_by_type: Vec<NamedCount>,
+    /// 按紧急程度统计的命中次数（高/中/低）
+    pub hits_by_urgency: Vec<NamedCount>,
+    /// 按处置动作统计的邮件数
+    pub actions: Vec<ActionCount>,
+    /// 首次被攻击时间
+    pub first_targeted: Option<DateTime<Utc>>,
+    /// 最近被攻击时间
+    pub last_targeted: Option<DateTime<Utc>>,
+    /// 针对该收件人的主要攻击组织，按命中邮件数倒序
+    pub top_threat_actors: Vec<NamedCount>,
+    /// 趋势
+    pub trend: Vec<ExposureTrendPoint>,
+}
+
+/// 收件人暴露排行项 - 领域模型
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecipientExposure {
+    /// 收件人邮箱地址（小写）
+    pub address: String,
+    /// 收到的邮件数
+    pub total_received: u64,
+    /// 命中情报的邮件数
+    pub targeted_mails: u64,
+    /// 最近被攻击时间
+    pub last_targeted: Option<DateTime<Utc>>,
+}
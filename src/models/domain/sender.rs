@@ -0,0 +1,105 @@
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use crate::models::domain::email::ActionCount;
+use crate::models::domain::statistics::{NamedCount, TrendInterval};
+
+/// 发件人匹配方式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SenderKind {
+    /// 按完整邮箱地址
+    Address,
+    /// 按域名
+    Domain,
+}
+
+impl SenderKind {
+    /// 根据输入推断匹配方式：包含@时按地址，否则按域名
+    pub fn detect(sender: &str) -> Self {
+        if sender.contains('@') && !sender.starts_with('@') {
+            SenderKind::Address
+        } else {
+            SenderKind::Domain
+        }
+    }
+}
+
+/// 发件人信誉画像查询条件 - 领域模型
+#[derive(Debug, Clone)]
+pub struct SenderProfileFilter {
+    /// 发件人地址或域名，匹配信封发件人或显示发件人，不区分大小写
+    pub sender: String,
+    /// 匹配方式
+    pub kind: SenderKind,
+    /// 开始时间
+    pub start_time: DateTime<Utc>,
+    /// 结束时间
+    pub end_time: DateTime<Utc>,
+    /// 趋势分桶粒度
+    pub interval: TrendInterval,
+    /// 返回的发信IP数量
+    pub top_ips: u32,
+}
+
+/// 发信来源 - 领域模型
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SendingSource {
+    /// 客户端IP
+    pub ip: String,
+    /// 客户端PTR
+    pub ptr: String,
+    /// 邮件数
+    pub count: u64,
+    /// 最近一次发信时间
+    pub last_seen: Option<DateTime<Utc>>,
+}
+
+/// 发件人发信趋势点 - 领域模型
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SenderTrendPoint {
+    /// 分桶起始时间
+    pub time: DateTime<Utc>,
+    /// 邮件数
+    pub mails: u64,
+    /// 命中情报的邮件数
+    pub hit_mails: u64,
+}
+
+/// 发件人信誉画像 - 领域模型
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SenderProfile {
+    /// 发件人地址或域名（小写）
+    pub sender: String,
+    /// 匹配方式
+    pub kind: SenderKind,
+    /// 首次来信时间，不受查询时间范围限制
+    pub first_seen: Option<DateTime<Utc>>,
+    /// 最近来信时间，不受查询时间范围限制
+    pub last_seen: Option<DateTime<Utc>>,
+    /// 历史来信总数，不受查询时间范围限制
+    pub lifetime_mails: u64,
+    /// 是否为新发件人：首次来信发生在查询时间范围内
+    pub new_sender: bool,
+    /// 时间范围内的邮件数
+    pub total_mails: u64,
+    /// 命中情报的邮件数
+    pub hit_mails: u64,
+    /// 不同收件人数
+    pub distinct_recipients: u64,
+    /// 发信来源（IP与PTR），按邮件数倒序
+    pub sending_sources: Vec<SendingSource>,
+    /// 使用TLS传输的邮件数
+    pub tls_mails: u64,
+    /// 按TLS版本统计的邮件数
+    pub tls_versions: Vec<NamedCount>,
+    /// 经过SASL认证的邮件数
+    pub authenticated_mails: u64,
+    /// 情报命中总次数
+    pub total_hits: u64,
+    /// 按情报类型统计的命中次数，按次数倒序
+    pub hits_by_type: Vec<NamedCount>,
+    /// 按处置动作统计的邮件数
+    pub actions: Vec<ActionCount>,
+    /// 趋势
+    pub trend: Vec<SenderTrendPoint>,
+}
@@ -0,0 +1,93 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+use crate::models::domain::statistics::TrendInterval;
+
+/// 情报跨单位扩散查询条件 - 领域模型
+#[derive(Debug, Clone)]
+pub struct SpreadFilter {
+    /// 情报ID
+    pub intelligence_id: Uuid,
+    /// 开始时间，为空时从首个命中单位开始
+    pub start_time: Option<DateTime<Utc>>,
+    /// 结束时间，为空时到最近一次命中为止
+    pub end_time: Option<DateTime<Utc>>,
+    /// 分桶粒度
+    pub interval: TrendInterval,
+}
+
+/// GB/T 4754-2017行业 - 领域模型
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Industry {
+    /// 行业代码，门类为字母（如J），大类为字母加两位数字（如J66），无法识别时为空
+    pub code: String,
+    /// 行业名称
+    pub name: String,
+}
+
+/// 命中单位 - 领域模型
+#[derive(Debug, Clone)]
+pub struct SpreadUnit {
+    /// 按首次命中时间的顺序，从1开始
+    pub order: u32,
+    /// 单位名称
+    pub unit_name: String,
+    /// 单位的行业
+    pub industry: Industry,
+    /// 单位所属门类
+    pub section: Industry,
+    /// 首次命中时间
+    pub first_hit_time: DateTime<Utc>,
+    /// 最近命中时间
+    pub last_hit_time: DateTime<Utc>,
+    /// 累计命中数量
+    pub hit_count: u64,
+}
+
+/// 命中行业（门类） - 领域模型
+#[derive(Debug, Clone)]
+pub struct SpreadIndustry {
+    /// 按首次命中时间的顺序，从1开始
+    pub order: u32,
+    /// 门类
+    pub industry: Industry,
+    /// 首次命中时间
+    pub first_hit_time: DateTime<Utc>,
+    /// 命中单位数
+    pub unit_count: u64,
+    /// 累计命中数量
+    pub hit_count: u64,
+}
+
+/// 扩散分桶 - 领域模型
+#[derive(Debug, Clone)]
+pub struct SpreadBucket {
+    /// 分桶起始时间
+    pub time: DateTime<Utc>,
+    /// 分桶内命中的单位名称，按首次命中顺序
+    pub units: Vec<String>,
+    /// 分桶内命中的门类，按首次命中顺序
+    pub industries: Vec<Industry>,
+    /// 分桶内首次命中的单位数
+    pub new_units: u64,
+    /// 分桶内首次出现的门类数
+    pub new_industries: u64,
+    /// 截至分桶结束的累计单位数
+    pub cumulative_units: u64,
+    /// 截至分桶结束的累计门类数
+    pub cumulative_industries: u64,
+}
+
+/// 情报跨单位扩散 - 领域模型
+#[derive(Debug, Clone)]
+pub struct Spread {
+    /// 情报ID
+    pub intelligence_id: Uuid,
+    /// 分桶粒度
+    pub interval: TrendInterval,
+    /// 全部命中单位，按首次命中时间正序
+    pub units: Vec<SpreadUnit>,
+    /// 全部命中门类，按首次命中时间正序
+    pub industries: Vec<SpreadIndustry>,
+    /// 各分桶的命中情况，按时间正序
+    pub buckets: Vec<SpreadBucket>,
+}
@@ -22,6 +22,8 @@ pub struct StatisticsFilter {
     pub end_time: DateTime<Utc>,
     /// 查询模块，用于区分查询哪个板块
     pub module: String,
+    /// 趋势分桶粒度
+    pub interval: TrendInterval,
     /// 额外参数，预留字段，用于未来扩展
     pub extras: HashMap<String, String>,
 }
@@ -123,4 +125,62 @@ pub enum StatisticsResult {
     IntelHitStats(Vec<IntelHitStatisticsItem>),
     /// 趋势图数据
     TrendChart(TrendChartItem),
-} 
\ No newline at end of file
+} 
+
+/// 趋势分桶粒度
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TrendInterval {
+    /// 按小时
+    Hour,
+    /// 按天
+    #[default]
+    Day,
+    /// 按周（周一开始）
+    Week,
+}
+
+impl TrendInterval {
+    /// 单个分桶的秒数
+    pub fn seconds(&self) -> i64 {
+        match self {
+            TrendInterval::Hour => 3600,
+            TrendInterval::Day => 86400,
+            TrendInterval::Week => 7 * 86400,
+        }
+    }
+
+    /// 分桶对齐前的偏移秒数
+    ///
+    /// 1970-01-01是周四，按周分桶时先偏移到周一再对齐
+    pub fn offset(&self) -> i64 {
+        match self {
+            TrendInterval::Week => 3 * 86400,
+            _ => 0,
+        }
+    }
+
+    /// 计算时间所在分桶的起始时间（UTC）
+    pub fn bucket_start(&self, secs: i64) -> i64 {
+        (secs + self.offset()).div_euclid(self.seconds()) * self.seconds() - self.offset()
+    }
+
+    /// 时间范围内的所有分桶起始时间
+    pub fn buckets(&self, start_time: DateTime<Utc>, end_time: DateTime<Utc>) -> Vec<i64> {
+        let first = self.bucket_start(start_time.timestamp());
+        let last = self.bucket_start(end_time.timestamp());
+        (0..)
+            .map(|i| first + i * self.seconds())
+            .take_while(|bucket| *bucket <= last)
+            .collect()
+    }
+}
+
+/// 分组计数 - 领域模型
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NamedCount {
+    /// 分组名称
+    pub name: String,
+    /// 计数值
+    pub count: u64,
+}
@@ -0,0 +1,155 @@
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use tokio::io::AsyncRead;
+use crate::content::{DigestCheck, DigestReader};
+use crate::db::BlobRead;
+use crate::models::domain::email::Attachment;
+
+/// 附件完整性状态
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum IntegrityStatus {
+    /// 内容与记录的哈希一致
+    Verified,
+    /// 没有记录哈希，无法校验
+    Unverified,
+    /// 内容与记录的哈希或大小不一致
+    Corrupted,
+    /// 存储中找不到文件
+    Missing,
+}
+
+impl IntegrityStatus {
+    /// 响应头与日志中使用的名称
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IntegrityStatus::Verified => "verified",
+            IntegrityStatus::Unverified => "unverified",
+            IntegrityStatus::Corrupted => "corrupted",
+            IntegrityStatus::Missing => "missing",
+        }
+    }
+}
+
+/// 附件完整性校验失败
+#[derive(Debug, Clone)]
+pub struct IntegrityError {
+    /// 附件ID
+    pub attachment_id: String,
+    /// 校验结果，Corrupted或Missing
+    pub status: IntegrityStatus,
+}
+
+impl fmt::Display for IntegrityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.status {
+            IntegrityStatus::Missing => write!(f, "附件未找到: {}", self.attachment_id),
+            _ => write!(f, "附件完整性校验失败: {}", self.attachment_id),
+        }
+    }
+}
+
+impl std::error::Error for IntegrityError {}
+
+/// 存储中的附件记录 - 领域模型
+#[derive(Debug, Clone)]
+pub struct StoredAttachment {
+    /// 所属邮件ID
+    pub mail_id: String,
+    /// 附件信息，哈希与大小为入库时的记录
+    pub attachment: Attachment,
+}
+
+/// 从存储打开的附件内容 - 领域模型
+pub struct StoredBlob {
+    /// 文件大小（字节）
+    pub size: u64,
+    /// 文件内容的SHA256，取入库时的记录，用作下载的ETag
+    pub sha256: String,
+    /// 文件内容，分段下载时从中间开始读取
+    pub reader: Box<dyn BlobRead>,
+    /// 读完整个文件后与入库记录的比对，没有记录哈希时为None
+    pub check: Option<DigestCheck>,
+}
+
+impl StoredBlob {
+    /// 从头读取完整文件，读到记录的大小时比对哈希，不一致时读取失败
+    pub fn into_verified_reader(self) -> Box<dyn AsyncRead + Send + Unpin> {
+        match self.check {
+            Some(check) => Box::new(DigestReader::new(self.reader, self.size, check)),
+            None => Box::new(self.reader),
+        }
+    }
+}
+
+/// 单个附件的校验结果 - 领域模型
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageCheck {
+    /// 所属邮件ID
+    pub mail_id: String,
+    /// 附件ID
+    pub attachment_id: String,
+    /// 文件名
+    pub filename: String,
+    /// 存储路径
+    pub file_path: String,
+    /// 校验结果
+    pub status: IntegrityStatus,
+    /// 记录的文件大小
+    pub expected_size: u64,
+    /// 实际文件大小，文件缺失时为None
+    pub actual_size: Option<u64>,
+    /// 记录的MD5
+    pub expected_md5: String,
+    /// 实际MD5
+    pub actual_md5: Option<String>,
+    /// 记录的SHA256
+    pub expected_sha256: String,
+    /// 实际SHA256
+    pub actual_sha256: Option<String>,
+}
+
+/// 存储校验任务状态
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageJobStatus {
+    /// 等待执行
+    Pending,
+    /// 执行中
+    Running,
+    /// 已完成
+    Completed,
+    /// 执行失败
+    Failed,
+}
+
+/// 存储校验任务 - 领域模型
+#[derive(Debug, Clone)]
+pub struct StorageVerifyJob {
+    /// 任务ID
+    pub id: String,
+    /// 任务状态
+    pub status: StorageJobStatus,
+    /// 需要校验的附件数
+    pub total: u32,
+    /// 已校验的附件数
+    pub checked: u32,
+    /// 校验通过的附件数
+    pub verified: u32,
+    /// 没有记录哈希的附件数
+    pub unverified: u32,
+    /// 内容损坏的附件数
+    pub corrupted: u32,
+    /// 文件缺失的附件数
+    pub missing: u32,
+    /// 损坏或缺失的附件
+    pub problems: Vec<StorageCheck>,
+    /// 创建时间
+    pub created_at: DateTime<Utc>,
+    /// 结束时间
+    pub finished_at: Option<DateTime<Utc>>,
+    /// 失败原因
+    pub error: Option<String>,
+}
@@ -0,0 +1,65 @@
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use crate::content::ThreadLink;
+use crate::models::domain::email::EmailStatus;
+
+/// 会话参与者 - 领域模型
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreadParticipant {
+    /// 邮箱地址（小写）
+    pub address: String,
+    /// 显示名称
+    pub name: String,
+    /// 发出的邮件数
+    pub sent: u64,
+    /// 收到（含抄送）的邮件数
+    pub received: u64,
+}
+
+/// 会话树节点 - 领域模型
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreadNode {
+    /// 邮件ID
+    pub mail_id: String,
+    /// Message-ID
+    pub message_id: Option<String>,
+    /// 邮件主题
+    pub subject: String,
+    /// 发件人
+    pub sender: String,
+    /// 收件人与抄送
+    pub recipients: Vec<String>,
+    /// 邮件检测时间
+    pub time: DateTime<Utc>,
+    /// 邮件头中的发信时间
+    pub sent_time: Option<DateTime<Utc>>,
+    /// 处置动作
+    pub status: EmailStatus,
+    /// 是否命中情报
+    pub hit_intelligence: bool,
+    /// 与父节点的关联方式
+    pub link: ThreadLink,
+    /// 回复，按时间排序
+    pub children: Vec<ThreadNode>,
+}
+
+/// 邮件会话 - 领域模型
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MailThread {
+    /// 查询的邮件ID
+    pub mail_id: String,
+    /// 会话主题（根节点主题）
+    pub subject: String,
+    /// 会话中的邮件数
+    pub message_count: u64,
+    /// 命中情报的邮件数
+    pub hit_messages: u64,
+    /// 最早邮件时间
+    pub first_time: DateTime<Utc>,
+    /// 最近邮件时间
+    pub last_time: DateTime<Utc>,
+    /// 参与者，按往来邮件数倒序
+    pub participants: Vec<ThreadParticipant>,
+    /// 会话树根节点
+    pub root: ThreadNode,
+}
@@ -1,5 +1,31 @@
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
+use crate::models::domain::email::{ActionCount, EmailStatus};
+use crate::models::domain::statistics::TrendInterval;
+
+/// 攻击时间线查询条件 - 领域模型
+#[derive(Debug, Clone)]
+pub struct TimelineFilter {
+    /// 情报ID，多个情报合并为一条时间线
+    pub intelligence_ids: Vec<Uuid>,
+    /// 攻击组织名称，该组织的全部情报并入时间线
+    pub threat_actor: Option<String>,
+    /// 开始时间
+    pub start_time: Option<DateTime<Utc>>,
+    /// 结束时间
+    pub end_time: Option<DateTime<Utc>>,
+    /// 处置动作，为空时不过滤
+    pub actions: Vec<EmailStatus>,
+    /// 收件人邮箱域名，匹配显示收件人或信封收件人域名
+    pub recipient_domain: Option<String>,
+    /// 页码
+    pub page: u32,
+    /// 每页大小
+    pub page_size: u32,
+    /// 分桶粒度，指定时返回各分桶的邮件数而不是邮件列表
+    pub interval: Option<TrendInterval>,
+}
 
 /// 邮件信息 - 领域模型
 #[derive(Debug, Clone)]
@@ -9,22 +35,168 @@ pub struct TimelineEmail {
     /// 邮件时间
     pub timestamp: DateTime<Utc>,
     /// 邮件状态
-    pub status: String,
+    pub status: EmailStatus,
     /// 发件人
     pub sender: String,
     /// 收件人
     pub recipient: Vec<String>,
+    /// 邮件命中的时间线情报
+    pub matched: Vec<TimelineMatch>,
 }
 
-/// 攻击时间线数据 - 领域模型
+/// 邮件命中的情报 - 领域模型
 #[derive(Debug, Clone)]
-pub struct Timeline {
+pub struct TimelineMatch {
+    /// 情报ID
+    pub intelligence_id: Uuid,
+    /// 情报属性
+    pub attribute: String,
+    /// 情报值
+    pub value: String,
+}
+
+/// 时间线包含的情报 - 领域模型
+#[derive(Debug, Clone)]
+pub struct TimelineIntelligence {
     /// 情报ID
     pub intelligence_id: Uuid,
+    /// 情报属性
+    pub attribute: String,
+    /// 情报值
+    pub value: String,
     /// 情报首次发现时间
     pub first_found_time: DateTime<Utc>,
     /// 情报来源
     pub source: String,
-    /// 相关邮件列表
+    /// 命中记录数
+    pub hit_count: u64,
+}
+
+/// 时间线分桶 - 领域模型
+#[derive(Debug, Clone)]
+pub struct TimelineBucket {
+    /// 分桶起始时间
+    pub time: DateTime<Utc>,
+    /// 邮件数
+    pub total: u64,
+    /// 按处置动作分组的邮件数
+    pub actions: Vec<ActionCount>,
+}
+
+/// 时间线事件类型
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum TimelineEventKind {
+    /// 命中情报的邮件
+    EmailHit,
+    /// 情报首次发现
+    FirstDiscovered,
+    /// 情报更新
+    Updated,
+    /// 加入或移出白名单
+    Whitelisted,
+    /// 加入或移出黑名单
+    Blacklisted,
+    /// 上报或撤销上报
+    Reported,
+    /// 情报过期
+    Expired,
+    /// 回溯排查完成
+    RetroHunt,
+}
+
+/// 处置变更详情
+#[derive(Debug, Clone)]
+pub struct DispositionChange {
+    /// true表示加入名单或上报，false表示移出名单或撤销上报
+    pub enabled: bool,
+    /// 处置原因
+    pub reason: String,
+}
+
+/// 回溯排查结果
+#[derive(Debug, Clone)]
+pub struct RetroHuntResult {
+    /// 排查任务ID
+    pub job_id: String,
+    /// 排查的邮件时间范围起点
+    pub scan_start_time: DateTime<Utc>,
+    /// 排查的邮件时间范围终点
+    pub scan_end_time: DateTime<Utc>,
+    /// 扫描的邮件数
+    pub scanned_mails: u64,
+    /// 新命中的邮件数
+    pub matched_mails: u64,
+}
+
+/// 时间线事件详情，与事件类型对应
+#[derive(Debug, Clone)]
+pub enum TimelineEventDetail {
+    /// 邮件命中
+    Email(TimelineEmail),
+    /// 首次发现、更新和过期，附当时的情报描述
+    Lifecycle { description: String },
+    /// 白名单、黑名单和上报的变更
+    Disposition(DispositionChange),
+    /// 回溯排查
+    RetroHunt(RetroHuntResult),
+}
+
+/// 时间线事件 - 领域模型
+#[derive(Debug, Clone)]
+pub struct TimelineEvent {
+    /// 事件类型
+    pub kind: TimelineEventKind,
+    /// 生命周期事件所属的情报，邮件命中的情报见邮件详情
+    pub intelligence_id: Option<Uuid>,
+    /// 事件时间
+    pub time: DateTime<Utc>,
+    /// 操作人或来源，系统事件为空
+    pub actor: Option<String>,
+    /// 事件详情
+    pub detail: TimelineEventDetail,
+}
+
+impl TimelineEvent {
+    /// 中文显示名称，撤销类的处置变更显示为移出或撤销
+    pub fn label(&self) -> &'static str {
+        let enabled = match &self.detail {
+            TimelineEventDetail::Disposition(change) => change.enabled,
+            _ => true,
+        };
+        match (self.kind, enabled) {
+            (TimelineEventKind::EmailHit, _) => "邮件命中",
+            (TimelineEventKind::FirstDiscovered, _) => "首次发现",
+            (TimelineEventKind::Updated, _) => "情报更新",
+            (TimelineEventKind::Whitelisted, true) => "加入白名单",
+            (TimelineEventKind::Whitelisted, false) => "移出白名单",
+            (TimelineEventKind::Blacklisted, true) => "加入黑名单",
+            (TimelineEventKind::Blacklisted, false) => "移出黑名单",
+            (TimelineEventKind::Reported, true) => "上报",
+            (TimelineEventKind::Reported, false) => "撤销上报",
+            (TimelineEventKind::Expired, _) => "情报过期",
+            (TimelineEventKind::RetroHunt, _) => "回溯排查",
+        }
+    }
+}
+
+/// 攻击时间线数据 - 领域模型
+#[derive(Debug, Clone)]
+pub struct Timeline {
+    /// 时间线包含的情报，按首次发现时间正序
+    pub intelligence: Vec<TimelineIntelligence>,
+    /// 最早的情报首次发现时间
+    pub first_found_time: DateTime<Utc>,
+    /// 情报来源，多个来源以顿号分隔
+    pub source: String,
+    /// 符合条件的邮件总数
+    pub total: u64,
+    /// 分桶粒度，为None时返回邮件列表
+    pub interval: Option<TrendInterval>,
+    /// 当前页的邮件，按时间倒序
     pub emails: Vec<TimelineEmail>,
-} 
\ No newline at end of file
+    /// 各分桶的邮件数，按时间正序
+    pub buckets: Vec<TimelineBucket>,
+    /// 邮件命中与情报生命周期事件，按时间倒序
+    pub events: Vec<TimelineEvent>,
+}
@@ -0,0 +1,62 @@
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+};
+use tracing::info;
+
+use crate::models::api::campaign::{CampaignListData, CampaignListResponse, CampaignQuery};
+use crate::models::domain::campaign::CampaignFilter;
+use crate::services::AppServices;
+use super::service_error;
+
+/// 默认的批次最少邮件数
+const DEFAULT_MIN_SIZE: u32 = 2;
+/// 默认的最低相似度
+const DEFAULT_MIN_SIMILARITY: f64 = 0.85;
+/// 默认返回的批次数量
+const DEFAULT_CAMPAIGN_LIMIT: u32 = 20;
+
+/// 聚类时间范围内的邮件，返回钓鱼批次
+pub async fn query_campaigns(
+    State(services): State<AppServices>,
+    Json(query): Json<CampaignQuery>,
+) -> Result<Json<CampaignListResponse>, (StatusCode, String)> {
+    info!("路由: 查询钓鱼批次: start={}, end={}", query.start_time, query.end_time);
+
+    if query.start_time > query.end_time {
+        return Err((StatusCode::BAD_REQUEST, "开始时间不能晚于结束时间".to_string()));
+    }
+    // 相似度过低时不同模板的邮件会被连成一片
+    let min_similarity = query.min_similarity.unwrap_or(DEFAULT_MIN_SIMILARITY);
+    if !(0.5..=1.0).contains(&min_similarity) {
+        return Err((StatusCode::BAD_REQUEST, "最低相似度必须在0.5到1之间".to_string()));
+    }
+    let mail_id = match query.mail_id.as_deref().map(str::trim) {
+        Some(id) => Some(
+            id.parse::<u64>()
+                .map_err(|_| (StatusCode::BAD_REQUEST, format!("无效的邮件ID: {}", id)))?,
+        ),
+        None => None,
+    };
+
+    let filter = CampaignFilter {
+        start_time: query.start_time,
+        end_time: query.end_time,
+        min_size: query.min_size.unwrap_or(DEFAULT_MIN_SIZE),
+        min_similarity,
+        intelligence_id: query.intelligence_id.map(|id| id.trim().to_string()).filter(|id| !id.is_empty()),
+        mail_id,
+        limit: query.limit.unwrap_or(DEFAULT_CAMPAIGN_LIMIT),
+    };
+
+    let list = services
+        .campaign
+        .list_campaigns(filter)
+        .await
+        .map_err(service_error(StatusCode::INTERNAL_SERVER_ERROR, "查询钓鱼批次失败"))?;
+
+    Ok(Json(CampaignListResponse {
+        code: 200,
+        data: CampaignListData::from(list),
+    }))
+}
@@ -1,14 +1,23 @@
 use axum::{
     body::{Body},
     extract::{Json, State},
-    http::{ StatusCode, header},
+    http::{ HeaderMap, HeaderName, HeaderValue, StatusCode, header},
     response::{Response},
 };
 use tracing::info;
 
-use crate::models::api::email::{EmailResponse, RelatedEmailsQuery, RelatedEmailsResponse};
-use crate::models::domain::email::EmailFilter;
+use crate::models::api::email::{
+    ArchiveInspectionData, ArchiveInspectionQuery, ArchiveInspectionResponse,
+    EmailPreviewData, EmailPreviewQuery, EmailPreviewResponse,
+    EmailResponse, EmailSearchQuery, EmailSearchResponse, RelatedEmailsQuery, RelatedEmailsResponse,
+    StatusList,
+};
+use crate::models::domain::email::{AttachmentContent, ContentBody, DownloadMode, EmailFilter, EmailSearchCriteria};
+use crate::models::domain::storage::{IntegrityError, IntegrityStatus};
 use crate::services::AppServices;
+use super::service_error;
+use super::AppError;
+use super::range::{content_disposition, ranged_response};
 
 /// 查询关联邮件
 pub async fn query_related_emails(
@@ -20,18 +29,30 @@ pub async fn query_related_emails(
         query.intelligence_id
     );
 
+    // 解析邮件状态，无效值返回400
+    let statuses = query
+        .status
+        .map(StatusList::parse)
+        .transpose()
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?
+        .unwrap_or_default();
+
     // 创建领域过滤器
     let filter = EmailFilter {
         start_time: query.start_time,
         end_time: query.end_time,
         intelligence_id: query.intelligence_id,
-        status: query.status,
+        statuses,
         page: query.page.unwrap_or(1),
         page_size: query.page_size.unwrap_or(10),
     };
 
     // 调用服务层获取关联邮件
-    let (total, emails) = services.email.get_related_emails(filter).await;
+    let (total, emails) = services
+        .email
+        .get_related_emails(filter)
+        .await
+        .map_err(service_error(StatusCode::INTERNAL_SERVER_ERROR, "查询关联邮件失败"))?;
 
     // 转换为API响应模型
     let email_responses = emails.into_iter().map(EmailResponse::from).collect();
@@ -44,48 +65,130 @@ pub async fn query_related_emails(
     }))
 }
 
+/// 按条件搜索邮件
+pub async fn search_emails(
+    State(services): State<AppServices>,
+    Json(query): Json<EmailSearchQuery>,
+) -> Result<Json<EmailSearchResponse>, (StatusCode, String)> {
+    info!("路由: 搜索邮件: {:?}", query);
+
+    // 校验时间范围
+    if let (Some(start_time), Some(end_time)) = (query.start_time, query.end_time)
+        && start_time > end_time
+    {
+        return Err((StatusCode::BAD_REQUEST, "开始时间不能晚于结束时间".to_string()));
+    }
+
+    // 解析处置动作，无效值返回400
+    let actions = query
+        .action
+        .map(StatusList::parse)
+        .transpose()
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?
+        .unwrap_or_default();
+
+    // 创建领域搜索条件
+    let criteria = EmailSearchCriteria {
+        start_time: query.start_time,
+        end_time: query.end_time,
+        subject: query.subject,
+        display_from: query.display_from,
+        recipient_address: query.recipient_address,
+        recipient_account: query.recipient_account,
+        recipient_domain: query.recipient_domain,
+        client_ip: query.client_ip,
+        sasl_login: query.sasl_login,
+        direction: query.direction,
+        actions,
+        hash: query.hash,
+        tls: query.tls,
+        protocol: query.protocol,
+        intelligence_id: None,
+        mail_ids: None,
+        page: query.page.unwrap_or(1),
+        page_size: query.page_size.unwrap_or(10),
+    };
+
+    // 调用服务层搜索邮件
+    let (total, emails) = services
+        .search
+        .search_emails(criteria)
+        .await
+        .map_err(service_error(StatusCode::INTERNAL_SERVER_ERROR, "搜索邮件失败"))?;
+
+    // 构建响应
+    Ok(Json(EmailSearchResponse {
+        code: 200,
+        total,
+        data: emails.into_iter().map(EmailResponse::from).collect(),
+    }))
+}
+
+/// 获取邮件正文安全预览
+pub async fn preview_email(
+    State(services): State<AppServices>,
+    Json(query): Json<EmailPreviewQuery>,
+) -> Result<Json<EmailPreviewResponse>, (StatusCode, String)> {
+    info!("路由: 获取邮件预览: email_id={}", query.email_id);
+
+    // 调用服务层生成安全预览
+    let preview = services
+        .email
+        .get_email_preview(&query.email_id)
+        .await
+        .map_err(service_error(StatusCode::NOT_FOUND, "获取邮件预览失败"))?;
+
+    Ok(Json(EmailPreviewResponse {
+        code: 200,
+        data: EmailPreviewData::from(preview),
+    }))
+}
+
 /// POST请求数据结构体 - 下载邮件EML
 #[derive(serde::Deserialize)]
 pub struct DownloadEmailRequest {
     /// 邮件ID
     pub email_id: String,
+    /// 下载模式，默认放入加密ZIP，raw表示下载原始文件
+    #[serde(default)]
+    pub mode: DownloadMode,
 }
 
 /// 下载邮件EML
 pub async fn download_email_eml(
     State(services): State<AppServices>,
+    headers: HeaderMap,
     Json(request): Json<DownloadEmailRequest>,
-) -> Response<Body> {
-    info!("路由: 下载邮件EML: email_id={}", request.email_id);
-
-    // 调用服务层获取EML数据
-    match services.email.download_email_eml(&request.email_id).await {
-        Ok(eml_data) => {
-            // 获取邮件详情以获取主题作为文件名
-            let filename = match services.email.get_email_detail(&request.email_id).await {
-                Ok(email) => format!("{}.eml", email.subject.replace(" ", "_")),
-                Err(_) => format!("email_{}.eml", request.email_id),
-            };
-
-            // 构建成功响应
-            Response::builder()
-                .status(StatusCode::OK)
-                .header(header::CONTENT_TYPE, "message/rfc822")
-                .header(
-                    header::CONTENT_DISPOSITION,
-                    format!("attachment; filename=\"{}\"", filename),
-                )
-                .body(Body::from(eml_data))
-                .unwrap()
-        }
-        Err(e) => {
-            // 构建错误响应
-            Response::builder()
-                .status(StatusCode::NOT_FOUND)
-                .body(Body::from(format!("下载邮件失败: {}", e)))
-                .unwrap()
-        }
+) -> Result<Response<Body>, AppError> {
+    info!("路由: 下载邮件EML: email_id={}, mode={:?}", request.email_id, request.mode);
+
+    // 安全模式下返回加密ZIP
+    if request.mode == DownloadMode::Safe {
+        let content = services
+            .email
+            .download_email_eml_safe(&request.email_id)
+            .await
+            .map_err(|e| AppError::from_service(StatusCode::NOT_FOUND, "下载邮件失败", e))?;
+        return file_response(&headers, content).await;
     }
+
+    // 打开存储中的原始邮件，读到末尾时比对入库记录的哈希
+    let blob = services
+        .email
+        .open_email_eml(&request.email_id)
+        .await
+        .map_err(|e| AppError::from_service(StatusCode::NOT_FOUND, "下载邮件失败", e))?;
+
+    // 获取邮件详情以获取主题作为文件名
+    let filename = match services.email.get_email_detail(&request.email_id).await {
+        Ok(email) => format!("{}.eml", email.subject.replace(" ", "_")),
+        Err(_) => format!("email_{}.eml", request.email_id),
+    };
+    // 构建成功响应
+    let builder = Response::builder()
+        .header(header::CONTENT_TYPE, "message/rfc822")
+        .header(header::CONTENT_DISPOSITION, content_disposition(&filename));
+    ranged_response(&headers, builder, ContentBody::Stored(blob), None).await
 }
 
 /// POST请求数据结构体 - 下载附件
@@ -93,50 +196,111 @@ pub async fn download_email_eml(
 pub struct DownloadAttachmentRequest {
     /// 附件ID
     pub attachment_id: String,
-    /// 文件路径
+    /// 文件路径，仅用于日志，实际路径以附件记录为准
     pub file_path: String,
+    /// 下载模式，默认放入加密ZIP，raw表示下载原始文件
+    #[serde(default)]
+    pub mode: DownloadMode,
 }
 
 /// 下载邮件附件
 pub async fn download_attachment(
     State(services): State<AppServices>,
+    headers: HeaderMap,
     Json(request): Json<DownloadAttachmentRequest>,
-) -> Response<Body> {
+) -> Result<Response<Body>, AppError> {
     info!(
-        "路由: 下载附件: attachment_id={}, file_path={}",
-        request.attachment_id, request.file_path
+        "路由: 下载附件: attachment_id={}, file_path={}, mode={:?}",
+        request.attachment_id, request.file_path, request.mode
     );
 
-    // URL解码文件路径
-    let decoded_path = match urlencoding::decode(&request.file_path) {
-        Ok(path) => path.into_owned(),
-        Err(_) => request.file_path.clone(),
+    // 调用服务层获取附件数据
+    let result = match request.mode {
+        DownloadMode::Raw => services.email.download_attachment(&request.attachment_id).await,
+        DownloadMode::Safe => services.email.download_attachment_safe(&request.attachment_id).await,
     };
 
-    // 调用服务层获取附件数据
+    match result {
+        Ok(content) => file_response(&headers, content).await,
+        Err(e) => Err(download_error("下载附件失败", e)),
+    }
+}
+
+/// POST请求数据结构体 - 打包下载附件
+#[derive(serde::Deserialize)]
+pub struct DownloadAttachmentBundleRequest {
+    /// 邮件ID
+    pub email_id: String,
+    /// 需要打包的附件ID，为空时打包全部附件
+    #[serde(default)]
+    pub attachment_ids: Option<Vec<String>>,
+}
+
+/// 将同一封邮件的多个附件打包为加密ZIP下载
+pub async fn download_attachment_bundle(
+    State(services): State<AppServices>,
+    headers: HeaderMap,
+    Json(request): Json<DownloadAttachmentBundleRequest>,
+) -> Result<Response<Body>, AppError> {
+    info!(
+        "路由: 打包下载附件: email_id={}, attachment_ids={:?}",
+        request.email_id, request.attachment_ids
+    );
+
     match services
         .email
-        .download_attachment(&request.attachment_id, &decoded_path)
+        .download_attachment_bundle(&request.email_id, request.attachment_ids)
         .await
     {
-        Ok((attachment_data, filename, content_type)) => {
-            // 构建成功响应
-            Response::builder()
-                .status(StatusCode::OK)
-                .header(header::CONTENT_TYPE, content_type)
-                .header(
-                    header::CONTENT_DISPOSITION,
-                    format!("attachment; filename=\"{}\"", filename),
-                )
-                .body(Body::from(attachment_data))
-                .unwrap()
-        }
-        Err(e) => {
-            // 构建错误响应
-            Response::builder()
-                .status(StatusCode::NOT_FOUND)
-                .body(Body::from(format!("下载附件失败: {}", e)))
-                .unwrap()
+        Ok(content) => file_response(&headers, content).await,
+        Err(e) => Err(download_error("打包下载附件失败", e)),
+    }
+}
+
+/// 构建文件下载响应，禁止浏览器嗅探和渲染文件内容，支持分段下载
+async fn file_response(request: &HeaderMap, content: AttachmentContent) -> Result<Response<Body>, AppError> {
+    let builder = Response::builder()
+        .header(header::CONTENT_TYPE, content.content_type)
+        .header(header::CONTENT_DISPOSITION, content_disposition(&content.filename))
+        .header(header::X_CONTENT_TYPE_OPTIONS, "nosniff")
+        .header(header::CONTENT_SECURITY_POLICY, "default-src 'none'; sandbox")
+        .header("X-Detected-Type", content.detected_type.mime())
+        .header("X-Extension-Mismatch", content.extension_mismatch.to_string());
+    ranged_response(request, builder, content.body, Some(content.integrity)).await
+}
+
+/// 附件下载失败的错误：内容损坏返回500并标记完整性状态，数据库不可用返回503，其余返回404
+fn download_error(action: &str, e: anyhow::Error) -> AppError {
+    match e.downcast_ref::<IntegrityError>() {
+        Some(integrity) if integrity.status == IntegrityStatus::Corrupted => {
+            AppError::new(StatusCode::INTERNAL_SERVER_ERROR, format!("{}: {}", action, e)).with_header(
+                HeaderName::from_static("x-integrity-status"),
+                HeaderValue::from_static(integrity.status.as_str()),
+            )
         }
+        _ => AppError::from_service(StatusCode::NOT_FOUND, action, e),
     }
 }
+
+/// 解析压缩包附件的成员
+pub async fn inspect_archive_attachment(
+    State(services): State<AppServices>,
+    Json(query): Json<ArchiveInspectionQuery>,
+) -> Result<Json<ArchiveInspectionResponse>, (StatusCode, String)> {
+    info!(
+        "路由: 解析压缩包附件: email_id={}, attachment_id={}",
+        query.email_id, query.attachment_id
+    );
+
+    // 调用服务层解析压缩包
+    let inspection = services
+        .email
+        .inspect_archive_attachment(&query.email_id, &query.attachment_id, query.password)
+        .await
+        .map_err(service_error(StatusCode::UNPROCESSABLE_ENTITY, "解析压缩包失败"))?;
+
+    Ok(Json(ArchiveInspectionResponse {
+        code: 200,
+        data: ArchiveInspectionData::from(inspection),
+    }))
+}
\ No newline at end of file
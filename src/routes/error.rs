@@ -0,0 +1,120 @@
+//! 路由层错误
+//!
+//! 直接构建`Response`的处理函数（如文件下载）返回`AppError`，按状态码、附加响应头和错误信息生成响应，
+//! 构建响应失败时返回500而不是panic。
+//! 服务层错误按类型映射状态码：数据库不可用（`DbUnavailable`）返回503，其余使用处理函数给定的状态码
+
+use axum::{
+    http::{HeaderMap, HeaderName, HeaderValue, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+
+use crate::db::DbUnavailable;
+
+/// 路由层错误
+#[derive(Debug)]
+pub struct AppError {
+    /// 响应状态码
+    status: StatusCode,
+    /// 错误信息，作为响应正文
+    message: String,
+    /// 附加的响应头
+    headers: HeaderMap,
+}
+
+impl AppError {
+    /// 创建错误
+    pub fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+            headers: HeaderMap::new(),
+        }
+    }
+
+    /// 附加响应头
+    pub fn with_header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.insert(name, value);
+        self
+    }
+
+    /// 服务层错误：数据库不可用时返回503和Retry-After，其余返回`status`
+    pub fn from_service(status: StatusCode, action: &str, e: anyhow::Error) -> Self {
+        match e.downcast_ref::<DbUnavailable>() {
+            Some(unavailable) => Self::from(*unavailable),
+            None => Self::new(status, format!("{}: {}", action, e)),
+        }
+    }
+
+    /// 响应状态码
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+}
+
+/// JSON接口的服务层错误：数据库不可用时返回503，其余返回`status`
+///
+/// JSON接口的错误不带响应头，503的Retry-After由`require_database`补充
+pub fn service_error(status: StatusCode, action: &str) -> impl FnOnce(anyhow::Error) -> (StatusCode, String) + '_ {
+    move |e| match e.downcast_ref::<DbUnavailable>() {
+        Some(unavailable) => (StatusCode::SERVICE_UNAVAILABLE, unavailable.to_string()),
+        None => (status, format!("{}: {}", action, e)),
+    }
+}
+
+impl From<DbUnavailable> for AppError {
+    fn from(e: DbUnavailable) -> Self {
+        Self::new(StatusCode::SERVICE_UNAVAILABLE, e.to_string())
+            .with_header(header::RETRY_AFTER, HeaderValue::from(e.retry_after_secs))
+    }
+}
+
+impl From<(StatusCode, String)> for AppError {
+    fn from((status, message): (StatusCode, String)) -> Self {
+        Self::new(status, message)
+    }
+}
+
+impl From<axum::http::Error> for AppError {
+    fn from(e: axum::http::Error) -> Self {
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, format!("构建响应失败: {}", e))
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        (self.status, self.headers, self.message).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{Context, anyhow};
+
+    #[test]
+    fn unavailable_database_maps_to_503() {
+        let e = Err::<(), _>(anyhow!("connection refused"))
+            .context(DbUnavailable { retry_after_secs: 7 })
+            .context("查询邮件失败")
+            .unwrap_err();
+        let response = AppError::from_service(StatusCode::NOT_FOUND, "下载邮件失败", e).into_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "7");
+
+        let e = anyhow::Error::new(DbUnavailable { retry_after_secs: 7 });
+        let (status, _) = service_error(StatusCode::INTERNAL_SERVER_ERROR, "查询统计数据失败")(e);
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[test]
+    fn other_errors_keep_status() {
+        let response = AppError::from_service(StatusCode::NOT_FOUND, "下载邮件失败", anyhow!("邮件不存在")).into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert!(!response.headers().contains_key(header::RETRY_AFTER));
+
+        let (status, message) = service_error(StatusCode::INTERNAL_SERVER_ERROR, "查询统计数据失败")(anyhow!("SQL错误"));
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(message, "查询统计数据失败: SQL错误");
+    }
+}
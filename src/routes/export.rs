@@ -0,0 +1,165 @@
+use axum::{
+    body::Body,
+    extract::{Json, Path, State},
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use futures::stream::{self, StreamExt};
+use tokio_util::io::ReaderStream;
+use tracing::info;
+
+use crate::models::api::email::StatusList;
+use crate::models::api::export::{
+    EmailExportRequest, ExportJobData, ExportJobQuery, ExportJobResponse,
+};
+use crate::models::domain::email::EmailFilter;
+use crate::models::domain::export::{ExportData, ExportError, ExportFile, ExportOutcome, ExportSelection};
+use crate::services::AppServices;
+use super::AppError;
+use super::range::content_disposition;
+
+/// 批量导出邮件
+///
+/// 数量较少时直接返回导出文件，否则返回202和异步任务信息
+pub async fn export_emails(
+    State(services): State<AppServices>,
+    Json(request): Json<EmailExportRequest>,
+) -> Result<Response<Body>, AppError> {
+    info!("路由: 批量导出邮件: format={:?}", request.format);
+
+    // 查询条件和邮件ID列表二选一
+    let selection = match (request.filter, request.mail_ids) {
+        (Some(filter), None) => {
+            // 解析邮件状态，无效值返回400
+            let statuses = filter
+                .status
+                .map(StatusList::parse)
+                .transpose()
+                .map_err(|e| (StatusCode::BAD_REQUEST, e))?
+                .unwrap_or_default();
+            ExportSelection::Filter(EmailFilter {
+                start_time: filter.start_time,
+                end_time: filter.end_time,
+                intelligence_id: filter.intelligence_id,
+                statuses,
+                page: 1,
+                page_size: 10,
+            })
+        }
+        (None, Some(mail_ids)) => ExportSelection::MailIds(mail_ids),
+        _ => {
+            return Err(AppError::new(StatusCode::BAD_REQUEST, "filter和mail_ids必须且只能指定一个"));
+        }
+    };
+
+    // 调用服务层导出
+    let outcome = services
+        .export
+        .export_emails(selection, request.format, request.async_job)
+        .await
+        .map_err(export_error)?;
+
+    match outcome {
+        ExportOutcome::Ready(file) => file_response(file).await,
+        ExportOutcome::Queued(job) => Ok((
+            StatusCode::ACCEPTED,
+            Json(ExportJobResponse {
+                code: 202,
+                data: ExportJobData::from(job),
+            }),
+        )
+            .into_response()),
+    }
+}
+
+/// 查询导出任务进度
+pub async fn query_export_job(
+    State(services): State<AppServices>,
+    Json(query): Json<ExportJobQuery>,
+) -> Result<Json<ExportJobResponse>, (StatusCode, String)> {
+    info!("路由: 查询导出任务: job_id={}", query.job_id);
+
+    let job = services
+        .export
+        .get_job(&query.job_id)
+        .await
+        .map_err(|e| (StatusCode::NOT_FOUND, e.to_string()))?;
+
+    Ok(Json(ExportJobResponse {
+        code: 200,
+        data: ExportJobData::from(job),
+    }))
+}
+
+/// 下载导出任务生成的文件
+pub async fn download_export(
+    State(services): State<AppServices>,
+    Path(job_id): Path<String>,
+) -> Result<Response<Body>, AppError> {
+    info!("路由: 下载导出文件: job_id={}", job_id);
+
+    // 任务不存在返回404，未完成返回409
+    let job = services
+        .export
+        .get_job(&job_id)
+        .await
+        .map_err(|e| (StatusCode::NOT_FOUND, e.to_string()))?;
+    let file = services
+        .export
+        .get_job_file(&job.id)
+        .await
+        .map_err(|e| (StatusCode::CONFLICT, e.to_string()))?;
+
+    file_response(file).await
+}
+
+/// 导出失败的错误：请求无效返回400，原始邮件无法读取返回502，数据库不可用返回503，其余返回500
+fn export_error(e: anyhow::Error) -> AppError {
+    let status = match e.downcast_ref::<ExportError>() {
+        Some(ExportError::InvalidRequest(_)) => StatusCode::BAD_REQUEST,
+        Some(ExportError::RawMailUnavailable(_)) => StatusCode::BAD_GATEWAY,
+        None => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    AppError::from_service(status, "导出邮件失败", e)
+}
+
+/// 构建导出文件下载响应，同步导出和任务文件都以流的形式返回
+async fn file_response(file: ExportFile) -> Result<Response<Body>, AppError> {
+    let body = match file.data {
+        ExportData::Stream { first, rest } => {
+            let rest = stream::unfold(rest, |mut rest| async move { rest.recv().await.map(|chunk| (chunk, rest)) });
+            Body::from_stream(stream::once(async { Ok(first) }).chain(rest))
+        }
+        ExportData::File(path) => {
+            let file = tokio::fs::File::open(&path)
+                .await
+                .map_err(|e| (StatusCode::GONE, format!("导出文件已不存在: {}", e)))?;
+            Body::from_stream(ReaderStream::new(file))
+        }
+    };
+
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, file.content_type)
+        .header(header::CONTENT_DISPOSITION, content_disposition(&file.filename))
+        .header(header::X_CONTENT_TYPE_OPTIONS, "nosniff")
+        .body(body)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::DbUnavailable;
+
+    #[test]
+    fn export_errors_map_to_status() {
+        let status = |e: anyhow::Error| export_error(e).status();
+        assert_eq!(status(ExportError::InvalidRequest("没有需要导出的邮件".to_string()).into()), StatusCode::BAD_REQUEST);
+        let storage = anyhow::anyhow!("邮件未找到").context(ExportError::RawMailUnavailable("2".to_string()));
+        assert_eq!(status(storage), StatusCode::BAD_GATEWAY);
+        let unavailable = anyhow::Error::new(DbUnavailable { retry_after_secs: 3 })
+            .context(ExportError::RawMailUnavailable("2".to_string()));
+        assert_eq!(status(unavailable), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(status(anyhow::anyhow!("写入失败")), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
@@ -0,0 +1,46 @@
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+};
+use tracing::info;
+
+use crate::models::api::hash::{HashPivotData, HashPivotQuery, HashPivotResponse};
+use crate::models::domain::hash::{HashKind, HashPivotFilter};
+use crate::services::AppServices;
+use super::service_error;
+
+/// 默认返回的关联邮件数量
+const DEFAULT_PIVOT_LIMIT: u32 = 100;
+
+/// 按哈希关联邮件、附件与情报
+pub async fn query_hash_pivot(
+    State(services): State<AppServices>,
+    Json(query): Json<HashPivotQuery>,
+) -> Result<Json<HashPivotResponse>, (StatusCode, String)> {
+    info!("路由: 哈希关联查询: hash={}", query.hash);
+
+    let hash = query.hash.trim();
+    let kind = HashKind::detect(hash).ok_or_else(|| {
+        (
+            StatusCode::BAD_REQUEST,
+            format!("无效的哈希值: {}，仅支持MD5、SHA1或SHA256的十六进制形式", hash),
+        )
+    })?;
+
+    let filter = HashPivotFilter {
+        hash: hash.to_string(),
+        kind,
+        limit: query.limit.unwrap_or(DEFAULT_PIVOT_LIMIT),
+    };
+
+    let pivot = services
+        .hash
+        .pivot(filter)
+        .await
+        .map_err(service_error(StatusCode::INTERNAL_SERVER_ERROR, "哈希关联查询失败"))?;
+
+    Ok(Json(HashPivotResponse {
+        code: 200,
+        data: HashPivotData::from(pivot),
+    }))
+}
@@ -5,6 +5,7 @@ use axum::{
 use tracing::{info, instrument};
 
 use crate::services::AppServices;
+use super::service_error;
 use crate::models::domain::intelligence::IntelligenceFilter;
 use crate::models::api::intelligence::{IntelligenceQueryParams, IntelligenceListResponse, IntelligenceListItem};
 
@@ -31,7 +32,11 @@ pub async fn list_intelligence(
     };
     
     // 调用服务层获取情报列表
-    let (total, intelligence_list) = services.intelligence.list_intelligence(filter).await;
+    let (total, intelligence_list) = services
+        .intelligence
+        .list_intelligence(filter)
+        .await
+        .map_err(service_error(StatusCode::INTERNAL_SERVER_ERROR, "查询情报列表失败"))?;
     
     // 转换为API响应模型
     let response_items = intelligence_list
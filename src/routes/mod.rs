@@ -2,14 +2,39 @@
 mod intelligence;
 mod email;
 mod timeline;
+mod spread;
 mod statistics;
+mod export;
+mod quarantine;
+mod recipient;
+mod sender;
+mod hash;
+mod thread;
+mod campaign;
+mod storage;
 mod hello;
+mod system;
+// 文件下载的分段请求处理
+mod range;
+// 路由层错误
+mod error;
 
 // 重新导出所有处理函数，使其可以通过routes模块访问
 pub use intelligence::*;
 pub use email::*;
 pub use timeline::*;
+pub use spread::*;
 pub use statistics::*;
+pub use export::*;
+pub use quarantine::*;
+pub use recipient::*;
+pub use sender::*;
+pub use hash::*;
+pub use thread::*;
+pub use campaign::*;
+pub use storage::*;
 pub use hello::*;
+pub use system::*;
+pub use error::{AppError, service_error};
 // 定义路由构建函数
 pub mod router; 
\ No newline at end of file
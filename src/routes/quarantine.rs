@@ -0,0 +1,186 @@
+use axum::{
+    extract::{Json, State},
+    http::{HeaderMap, StatusCode, header},
+};
+use tracing::info;
+
+use crate::db::DbUnavailable;
+use crate::models::api::quarantine::{
+    QuarantineActionData, QuarantineActionListQuery, QuarantineActionListResponse,
+    QuarantineActionQuery, QuarantineActionResponse, QuarantineApproveQuery,
+    QuarantineAuditQuery, QuarantineAuditResponse, QuarantineRejectQuery,
+};
+use crate::models::domain::quarantine::{Operator, QuarantineActionKind, QuarantineError};
+use crate::services::AppServices;
+use crate::services::quarantine_service::QuarantineActionRequest;
+
+/// 放行隔离邮件
+pub async fn release_quarantined_email(
+    State(services): State<AppServices>,
+    headers: HeaderMap,
+    Json(query): Json<QuarantineActionQuery>,
+) -> Result<Json<QuarantineActionResponse>, (StatusCode, String)> {
+    request_action(services, &headers, query, QuarantineActionKind::Release).await
+}
+
+/// 删除隔离邮件
+pub async fn delete_quarantined_email(
+    State(services): State<AppServices>,
+    headers: HeaderMap,
+    Json(query): Json<QuarantineActionQuery>,
+) -> Result<Json<QuarantineActionResponse>, (StatusCode, String)> {
+    request_action(services, &headers, query, QuarantineActionKind::Delete).await
+}
+
+/// 重新投递隔离邮件
+pub async fn redeliver_quarantined_email(
+    State(services): State<AppServices>,
+    headers: HeaderMap,
+    Json(query): Json<QuarantineActionQuery>,
+) -> Result<Json<QuarantineActionResponse>, (StatusCode, String)> {
+    request_action(services, &headers, query, QuarantineActionKind::Redeliver).await
+}
+
+/// 批准处置请求
+pub async fn approve_quarantine_action(
+    State(services): State<AppServices>,
+    headers: HeaderMap,
+    Json(query): Json<QuarantineApproveQuery>,
+) -> Result<Json<QuarantineActionResponse>, (StatusCode, String)> {
+    info!("路由: 批准隔离处置: action_id={}", query.action_id);
+
+    let approver = authenticate(&services, &headers)?;
+    let action = services
+        .quarantine
+        .approve(&query.action_id, &approver)
+        .await
+        .map_err(error_response)?;
+
+    Ok(Json(QuarantineActionResponse {
+        code: 200,
+        data: QuarantineActionData::from(action),
+    }))
+}
+
+/// 驳回处置请求
+pub async fn reject_quarantine_action(
+    State(services): State<AppServices>,
+    headers: HeaderMap,
+    Json(query): Json<QuarantineRejectQuery>,
+) -> Result<Json<QuarantineActionResponse>, (StatusCode, String)> {
+    info!("路由: 驳回隔离处置: action_id={}", query.action_id);
+
+    let approver = authenticate(&services, &headers)?;
+    let action = services
+        .quarantine
+        .reject(&query.action_id, &approver, &query.reason)
+        .await
+        .map_err(error_response)?;
+
+    Ok(Json(QuarantineActionResponse {
+        code: 200,
+        data: QuarantineActionData::from(action),
+    }))
+}
+
+/// 查询处置请求列表
+pub async fn list_quarantine_actions(
+    State(services): State<AppServices>,
+    Json(query): Json<QuarantineActionListQuery>,
+) -> Result<Json<QuarantineActionListResponse>, (StatusCode, String)> {
+    info!("路由: 查询隔离处置请求: {:?}", query);
+
+    // 指定处置请求ID时只返回该请求，不存在返回404
+    let actions = match &query.action_id {
+        Some(action_id) => vec![
+            services
+                .quarantine
+                .get_action(action_id)
+                .await
+                .map_err(error_response)?,
+        ],
+        None => services
+            .quarantine
+            .list_actions(query.mail_id.as_deref(), query.state)
+            .await
+            .map_err(error_response)?,
+    };
+
+    Ok(Json(QuarantineActionListResponse {
+        code: 200,
+        data: actions.into_iter().map(QuarantineActionData::from).collect(),
+    }))
+}
+
+/// 查询处置审计记录
+pub async fn query_quarantine_audit(
+    State(services): State<AppServices>,
+    Json(query): Json<QuarantineAuditQuery>,
+) -> Result<Json<QuarantineAuditResponse>, (StatusCode, String)> {
+    info!("路由: 查询隔离处置审计记录: {:?}", query);
+
+    let entries = services
+        .quarantine
+        .audit_trail(query.mail_id.as_deref(), query.action_id.as_deref())
+        .await
+        .map_err(error_response)?;
+
+    Ok(Json(QuarantineAuditResponse {
+        code: 200,
+        data: entries.into_iter().map(Into::into).collect(),
+    }))
+}
+
+/// 提交处置请求
+async fn request_action(
+    services: AppServices,
+    headers: &HeaderMap,
+    query: QuarantineActionQuery,
+    kind: QuarantineActionKind,
+) -> Result<Json<QuarantineActionResponse>, (StatusCode, String)> {
+    info!("路由: 提交隔离处置: mail_id={}, kind={:?}", query.mail_id, kind);
+
+    let operator = authenticate(&services, headers)?;
+    let action = services
+        .quarantine
+        .request_action(&operator, QuarantineActionRequest {
+            mail_id: query.mail_id,
+            kind,
+            reason: query.reason,
+            recipients: query.recipients,
+        })
+        .await
+        .map_err(error_response)?;
+
+    Ok(Json(QuarantineActionResponse {
+        code: 200,
+        data: QuarantineActionData::from(action),
+    }))
+}
+
+/// 按`Authorization: Bearer <令牌>`认证操作人
+fn authenticate(services: &AppServices, headers: &HeaderMap) -> Result<Operator, (StatusCode, String)> {
+    let token = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    services.quarantine.authenticate(token).map_err(error_response)
+}
+
+/// 将处置错误映射为HTTP状态码，数据库不可用返回503
+fn error_response(e: QuarantineError) -> (StatusCode, String) {
+    if let QuarantineError::Internal(inner) = &e
+        && let Some(unavailable) = inner.downcast_ref::<DbUnavailable>()
+    {
+        return (StatusCode::SERVICE_UNAVAILABLE, unavailable.to_string());
+    }
+    let status = match &e {
+        QuarantineError::InvalidInput(_) => StatusCode::BAD_REQUEST,
+        QuarantineError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+        QuarantineError::NotFound(_) => StatusCode::NOT_FOUND,
+        QuarantineError::InvalidState(_) => StatusCode::CONFLICT,
+        QuarantineError::Forbidden(_) => StatusCode::FORBIDDEN,
+        QuarantineError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, format!("隔离邮件处置失败: {}", e))
+}
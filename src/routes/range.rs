@@ -0,0 +1,373 @@
+//! 文件下载的分段与条件请求处理
+//!
+//! 存储中的文件支持单个`Range`分段（`bytes=a-b`、`bytes=a-`、`bytes=-n`）、`If-Range`、`If-Match`
+//! 与`If-None-Match`，ETag取入库时记录的SHA256，多段请求按完整文件返回。文件按块流式读取，
+//! 完整下载时读到末尾才比对哈希，分段下载不做比对，完整性状态标记为unverified。
+//! 加密ZIP等生成的内容每次请求都不同（随机盐、生成时间），不提供ETag，也不支持分段下载。
+//! 下载文件名按RFC 6266同时给出ASCII回退名和UTF-8编码的`filename*`
+
+use std::convert::Infallible;
+use std::io::SeekFrom;
+
+use axum::{
+    body::{Body, Bytes},
+    http::{header, response::Builder, HeaderMap, StatusCode},
+    response::Response,
+};
+use futures::stream;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio_util::io::ReaderStream;
+
+use super::AppError;
+use crate::models::domain::email::ContentBody;
+use crate::models::domain::storage::IntegrityStatus;
+
+/// 流式返回时每块的大小
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// 请求的字节范围
+#[derive(Debug, PartialEq, Eq)]
+enum ByteRange {
+    /// 返回完整文件
+    Full,
+    /// 返回闭区间[start, end]
+    Partial(u64, u64),
+    /// 范围超出文件大小
+    Unsatisfiable,
+}
+
+/// 构建文件下载响应
+///
+/// `builder`中已经设置了Content-Type等与内容相关的响应头，`integrity`不为None时写入X-Integrity-Status
+pub(super) async fn ranged_response(
+    request: &HeaderMap,
+    builder: Builder,
+    body: ContentBody,
+    integrity: Option<IntegrityStatus>,
+) -> Result<Response<Body>, AppError> {
+    let blob = match body {
+        ContentBody::Memory(data) => {
+            let builder = match integrity {
+                Some(status) => builder.header("X-Integrity-Status", status.as_str()),
+                None => builder,
+            };
+            return Ok(builder
+                .status(StatusCode::OK)
+                .header(header::ACCEPT_RANGES, "none")
+                .header(header::CACHE_CONTROL, "no-store")
+                .header(header::CONTENT_LENGTH, data.len())
+                .body(stream_body(Bytes::from(data)))?);
+        }
+        ContentBody::Stored(blob) => blob,
+    };
+
+    let etag = format!("\"{}\"", blob.sha256);
+    let len = blob.size;
+    let builder = builder
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header(header::ETAG, &etag);
+
+    // 客户端要求的版本与当前内容不一致
+    if let Some(value) = header_str(request, header::IF_MATCH)
+        && !etag_matches_strong(value, &etag)
+    {
+        return Ok(builder.status(StatusCode::PRECONDITION_FAILED).body(Body::empty())?);
+    }
+
+    // 客户端已有相同内容
+    if let Some(value) = header_str(request, header::IF_NONE_MATCH)
+        && etag_matches(value, &etag)
+    {
+        return Ok(builder.status(StatusCode::NOT_MODIFIED).body(Body::empty())?);
+    }
+
+    // If-Range与当前ETag不一致时说明文件已变化，返回完整文件
+    let range = match header_str(request, header::RANGE) {
+        Some(value) if header_str(request, header::IF_RANGE).is_none_or(|tag| tag.trim() == etag) => {
+            parse_range(value, len)
+        }
+        _ => ByteRange::Full,
+    };
+
+    // 分段读取时没有经过哈希比对
+    let builder = match integrity {
+        Some(IntegrityStatus::Verified) if matches!(range, ByteRange::Partial(..)) => {
+            builder.header("X-Integrity-Status", IntegrityStatus::Unverified.as_str())
+        }
+        Some(status) => builder.header("X-Integrity-Status", status.as_str()),
+        None => builder,
+    };
+
+    let response = match range {
+        ByteRange::Full => builder
+            .status(StatusCode::OK)
+            .header(header::CONTENT_LENGTH, len)
+            .body(Body::from_stream(ReaderStream::with_capacity(blob.into_verified_reader(), CHUNK_SIZE)))?,
+        ByteRange::Partial(start, end) => {
+            let mut reader = blob.reader;
+            reader.seek(SeekFrom::Start(start)).await.map_err(|e| {
+                AppError::new(StatusCode::INTERNAL_SERVER_ERROR, format!("读取文件失败: {}", e))
+            })?;
+            let reader = reader.take(end - start + 1);
+            builder
+                .status(StatusCode::PARTIAL_CONTENT)
+                .header(header::CONTENT_LENGTH, end - start + 1)
+                .header(header::CONTENT_RANGE, format!("bytes {}-{}/{}", start, end, len))
+                .body(Body::from_stream(ReaderStream::with_capacity(reader, CHUNK_SIZE)))?
+        }
+        ByteRange::Unsatisfiable => builder
+            .status(StatusCode::RANGE_NOT_SATISFIABLE)
+            .header(header::CONTENT_RANGE, format!("bytes */{}", len))
+            .body(Body::empty())?,
+    };
+    Ok(response)
+}
+
+/// 下载文件的Content-Disposition
+///
+/// `filename`为ASCII回退名：非ASCII字符、控制字符、引号、反斜杠和路径分隔符替换为下划线；
+/// `filename*`为百分号编码的UTF-8原名，同样去掉控制字符与路径分隔符
+pub(super) fn content_disposition(filename: &str) -> String {
+    let cleaned: String = filename
+        .chars()
+        .map(|c| if c.is_control() || matches!(c, '/' | '\\') { '_' } else { c })
+        .collect();
+    let cleaned = cleaned.trim();
+    let cleaned = if cleaned.is_empty() { "download" } else { cleaned };
+
+    let fallback: String = cleaned
+        .chars()
+        .map(|c| if (c.is_ascii_graphic() && c != '"') || c == ' ' { c } else { '_' })
+        .collect();
+    format!(
+        "attachment; filename=\"{}\"; filename*=UTF-8''{}",
+        fallback,
+        urlencoding::encode(cleaned)
+    )
+}
+
+/// 按块流式返回，各块共享同一份内存
+fn stream_body(data: Bytes) -> Body {
+    let chunks = (0..data.len())
+        .step_by(CHUNK_SIZE)
+        .map(move |start| Ok::<_, Infallible>(data.slice(start..(start + CHUNK_SIZE).min(data.len()))))
+        .collect::<Vec<_>>();
+    Body::from_stream(stream::iter(chunks))
+}
+
+fn header_str(headers: &HeaderMap, name: header::HeaderName) -> Option<&str> {
+    headers.get(name).and_then(|value| value.to_str().ok())
+}
+
+/// If-None-Match按弱比较匹配，`*`匹配任意内容
+fn etag_matches(value: &str, etag: &str) -> bool {
+    value
+        .split(',')
+        .map(|tag| tag.trim().trim_start_matches("W/"))
+        .any(|tag| tag == "*" || tag == etag)
+}
+
+/// If-Match按强比较匹配，弱ETag不匹配任何内容
+fn etag_matches_strong(value: &str, etag: &str) -> bool {
+    value.split(',').map(str::trim).any(|tag| tag == "*" || tag == etag)
+}
+
+/// 解析Range头，语法无效或包含多段时返回完整文件
+fn parse_range(value: &str, len: u64) -> ByteRange {
+    let Some(spec) = value
+        .trim()
+        .split_once('=')
+        .filter(|(unit, _)| unit.trim().eq_ignore_ascii_case("bytes"))
+        .map(|(_, spec)| spec.trim())
+    else {
+        return ByteRange::Full;
+    };
+    if spec.contains(',') {
+        return ByteRange::Full;
+    }
+    let Some((start, end)) = spec.split_once('-') else {
+        return ByteRange::Full;
+    };
+
+    match (start.trim(), end.trim()) {
+        // 最后n个字节
+        ("", suffix) => match suffix.parse::<u64>() {
+            Ok(0) => ByteRange::Unsatisfiable,
+            Ok(_) if len == 0 => ByteRange::Unsatisfiable,
+            Ok(suffix) => ByteRange::Partial(len.saturating_sub(suffix), len - 1),
+            Err(_) => ByteRange::Full,
+        },
+        (start, end) => {
+            let Ok(start) = start.parse::<u64>() else {
+                return ByteRange::Full;
+            };
+            let end = match end {
+                "" => u64::MAX,
+                end => match end.parse::<u64>() {
+                    Ok(end) if end >= start => end,
+                    _ => return ByteRange::Full,
+                },
+            };
+            if start >= len {
+                ByteRange::Unsatisfiable
+            } else {
+                ByteRange::Partial(start, end.min(len - 1))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use axum::http::HeaderValue;
+    use crate::models::domain::storage::StoredBlob;
+
+    const SHA256: &str = "0123456789abcdef";
+
+    fn stored(data: &[u8]) -> ContentBody {
+        ContentBody::Stored(StoredBlob {
+            size: data.len() as u64,
+            sha256: SHA256.to_string(),
+            reader: Box::new(Cursor::new(data.to_vec())),
+            check: None,
+        })
+    }
+
+    fn headers(pairs: &[(header::HeaderName, &str)]) -> HeaderMap {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.clone(), HeaderValue::from_str(value).unwrap()))
+            .collect()
+    }
+
+    async fn respond(request: &[(header::HeaderName, &str)], body: ContentBody) -> (Response<Body>, Vec<u8>) {
+        let response = ranged_response(
+            &headers(request),
+            Response::builder(),
+            body,
+            Some(IntegrityStatus::Verified),
+        )
+        .await
+        .unwrap();
+        let (parts, body) = response.into_parts();
+        let data = axum::body::to_bytes(body, usize::MAX).await.unwrap();
+        (Response::from_parts(parts, Body::empty()), data.to_vec())
+    }
+
+    #[test]
+    fn parse_range_handles_single_ranges() {
+        assert_eq!(parse_range("bytes=0-9", 100), ByteRange::Partial(0, 9));
+        assert_eq!(parse_range("bytes=90-200", 100), ByteRange::Partial(90, 99));
+        // 从某字节开始到末尾
+        assert_eq!(parse_range("bytes=10-", 100), ByteRange::Partial(10, 99));
+        // 最后n个字节，超过文件大小时返回整个文件
+        assert_eq!(parse_range("bytes=-10", 100), ByteRange::Partial(90, 99));
+        assert_eq!(parse_range("bytes=-500", 100), ByteRange::Partial(0, 99));
+    }
+
+    #[test]
+    fn parse_range_falls_back_to_full_or_unsatisfiable() {
+        // 多段请求与无效语法按完整文件返回
+        assert_eq!(parse_range("bytes=0-9,20-29", 100), ByteRange::Full);
+        assert_eq!(parse_range("items=0-9", 100), ByteRange::Full);
+        assert_eq!(parse_range("bytes=9-0", 100), ByteRange::Full);
+        assert_eq!(parse_range("bytes=abc", 100), ByteRange::Full);
+        // 超出文件大小
+        assert_eq!(parse_range("bytes=100-", 100), ByteRange::Unsatisfiable);
+        assert_eq!(parse_range("bytes=-0", 100), ByteRange::Unsatisfiable);
+        assert_eq!(parse_range("bytes=-10", 0), ByteRange::Unsatisfiable);
+    }
+
+    #[tokio::test]
+    async fn stored_file_supports_ranges() {
+        let data: Vec<u8> = (0..=255).collect();
+        let (response, body) = respond(&[], stored(&data)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::ETAG], format!("\"{}\"", SHA256));
+        assert_eq!(response.headers()["X-Integrity-Status"], "verified");
+        assert_eq!(body, data);
+
+        let (response, body) = respond(&[(header::RANGE, "bytes=10-19")], stored(&data)).await;
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(response.headers()[header::CONTENT_RANGE], "bytes 10-19/256");
+        assert_eq!(response.headers()["X-Integrity-Status"], "unverified");
+        assert_eq!(body, &data[10..20]);
+
+        // If-Range与当前ETag不一致时返回完整文件
+        let (response, body) = respond(
+            &[(header::RANGE, "bytes=10-19"), (header::IF_RANGE, "\"stale\"")],
+            stored(&data),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body, data);
+    }
+
+    #[tokio::test]
+    async fn stored_file_conditional_requests() {
+        let etag = format!("\"{}\"", SHA256);
+        let (response, body) = respond(&[(header::IF_NONE_MATCH, &format!("W/{}", etag))], stored(b"eml")).await;
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert!(body.is_empty());
+
+        let (response, _) = respond(&[(header::IF_MATCH, "\"other\"")], stored(b"eml")).await;
+        assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+        // If-Match使用强比较
+        let (response, _) = respond(&[(header::IF_MATCH, &format!("W/{}", etag))], stored(b"eml")).await;
+        assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+        let (response, body) = respond(&[(header::IF_MATCH, &etag)], stored(b"eml")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body, b"eml");
+
+        let (response, body) = respond(&[(header::RANGE, "bytes=3-")], stored(b"eml")).await;
+        assert_eq!(response.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+        assert_eq!(response.headers()[header::CONTENT_RANGE], "bytes */3");
+        assert!(body.is_empty());
+    }
+
+    #[tokio::test]
+    async fn generated_content_ignores_ranges() {
+        let (response, body) = respond(
+            &[(header::RANGE, "bytes=0-1"), (header::IF_NONE_MATCH, "*")],
+            ContentBody::Memory(b"zip".to_vec()),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().get(header::ETAG).is_none());
+        assert_eq!(response.headers()[header::ACCEPT_RANGES], "none");
+        assert_eq!(body, b"zip");
+    }
+
+    #[test]
+    fn content_disposition_escapes_quotes_and_encodes_utf8() {
+        assert_eq!(
+            content_disposition("report.pdf"),
+            "attachment; filename=\"report.pdf\"; filename*=UTF-8''report.pdf"
+        );
+        assert_eq!(
+            content_disposition("a\"b.pdf"),
+            "attachment; filename=\"a_b.pdf\"; filename*=UTF-8''a%22b.pdf"
+        );
+        assert_eq!(
+            content_disposition("发票 2024.pdf"),
+            "attachment; filename=\"__ 2024.pdf\"; filename*=UTF-8''%E5%8F%91%E7%A5%A8%202024.pdf"
+        );
+    }
+
+    #[test]
+    fn content_disposition_never_breaks_the_header() {
+        for filename in ["x.eml\r\nSet-Cookie: a=b", "../../etc/passwd", "a\\b\0c", "", "\r\n"] {
+            let value = content_disposition(filename);
+            assert!(HeaderValue::from_str(&value).is_ok(), "{}", value);
+            assert!(!value.contains('\r') && !value.contains('\n'));
+            assert!(!value.contains('/') && !value.contains('\\'), "{}", value);
+            assert_eq!(value.matches('"').count(), 2, "{}", value);
+        }
+        assert_eq!(
+            content_disposition("\r\n"),
+            "attachment; filename=\"__\"; filename*=UTF-8''__"
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+};
+use tracing::info;
+
+use crate::models::api::recipient::{
+    RecipientExposureData, RecipientProfileData, RecipientProfileQuery, RecipientProfileResponse,
+    RecipientRankQuery, RecipientRankResponse,
+};
+use crate::models::domain::recipient::{RecipientProfileFilter, RecipientRankFilter};
+use crate::services::AppServices;
+use super::service_error;
+use crate::services::recipient_service::MAX_TREND_BUCKETS;
+
+/// 默认返回的攻击组织数量
+const DEFAULT_TOP_ACTORS: u32 = 10;
+/// 默认返回的收件人数量
+const DEFAULT_RANK_LIMIT: u32 = 20;
+
+/// 查询收件人暴露画像
+pub async fn query_recipient_profile(
+    State(services): State<AppServices>,
+    Json(query): Json<RecipientProfileQuery>,
+) -> Result<Json<RecipientProfileResponse>, (StatusCode, String)> {
+    info!("路由: 查询收件人暴露画像: address={}", query.address);
+
+    if query.address.trim().is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "收件人地址不能为空".to_string()));
+    }
+    if query.start_time > query.end_time {
+        return Err((StatusCode::BAD_REQUEST, "开始时间不能晚于结束时间".to_string()));
+    }
+    // 分桶过多时要求调大粒度
+    if (query.end_time - query.start_time).num_seconds() / query.interval.seconds() >= MAX_TREND_BUCKETS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("时间范围内的趋势分桶超过{}个，请使用更大的分桶粒度", MAX_TREND_BUCKETS),
+        ));
+    }
+
+    let filter = RecipientProfileFilter {
+        address: query.address,
+        start_time: query.start_time,
+        end_time: query.end_time,
+        interval: query.interval,
+        top_actors: query.top_actors.unwrap_or(DEFAULT_TOP_ACTORS),
+    };
+
+    let profile = services
+        .recipient
+        .get_profile(filter)
+        .await
+        .map_err(service_error(StatusCode::INTERNAL_SERVER_ERROR, "查询收件人暴露画像失败"))?;
+
+    Ok(Json(RecipientProfileResponse {
+        code: 200,
+        data: RecipientProfileData::from(profile),
+    }))
+}
+
+/// 查询收件人暴露排行
+pub async fn query_recipient_rank(
+    State(services): State<AppServices>,
+    Json(query): Json<RecipientRankQuery>,
+) -> Result<Json<RecipientRankResponse>, (StatusCode, String)> {
+    info!(
+        "路由: 查询收件人暴露排行: start_time={}, end_time={}",
+        query.start_time, query.end_time
+    );
+
+    if query.start_time > query.end_time {
+        return Err((StatusCode::BAD_REQUEST, "开始时间不能晚于结束时间".to_string()));
+    }
+
+    let filter = RecipientRankFilter {
+        start_time: query.start_time,
+        end_time: query.end_time,
+        limit: query.limit.unwrap_or(DEFAULT_RANK_LIMIT),
+    };
+
+    let ranks = services
+        .recipient
+        .rank_recipients(filter)
+        .await
+        .map_err(service_error(StatusCode::INTERNAL_SERVER_ERROR, "查询收件人暴露排行失败"))?;
+
+    Ok(Json(RecipientRankResponse {
+        code: 200,
+        data: ranks
+            .into_iter()
+            .zip(1..)
+            .map(|(exposure, rank)| RecipientExposureData::new(rank, exposure))
+            .collect(),
+    }))
+}
@@ -1,4 +1,5 @@
 use axum::{
+    middleware,
     routing::{get, post},
     Router,
 };
@@ -8,22 +9,77 @@ use crate::server::AppState;
 
 /// 构建应用路由
 pub fn create_router(state: AppState) -> Router {
-    Router::new()
+    // 不依赖数据库的路由，数据库不可用时仍然可以访问
+    let system = Router::new()
         .route("/", get(super::hello))
+        // 添加GET方式的数据库连接状态查询
+        .route("/system/db-status", get(super::query_db_status))
+        .with_state(state.connection.clone());
+
+    Router::new()
         // 添加POST方式的情报查询
         .route("/intelligence/list", post(super::list_intelligence))
         // 添加POST方式的关联邮件查询
         .route("/intelligence/related-emails", post(super::query_related_emails))
         // 添加POST方式的攻击时间线查询
         .route("/intelligence/timeline", post(super::query_timeline))
+        // 添加POST方式的情报跨单位扩散查询
+        .route("/intelligence/spread", post(super::query_spread))
         // 添加POST方式的统计数据查询
         .route("/intelligence/statistics", post(super::query_statistics))
         // 添加POST方式的邮件EML下载
         .route("/email/download-eml", post(super::download_email_eml))
+        // 添加POST方式的邮件搜索
+        .route("/email/search", post(super::search_emails))
+        // 添加POST方式的邮件批量导出
+        .route("/email/export", post(super::export_emails))
+        // 添加POST方式的导出任务进度查询
+        .route("/email/export/status", post(super::query_export_job))
+        // 添加GET方式的导出文件下载
+        .route("/email/export/download/:job_id", get(super::download_export))
+        // 添加POST方式的邮件安全预览
+        .route("/email/preview", post(super::preview_email))
         // 添加POST方式的附件下载
         .route("/attachment/download", post(super::download_attachment))
+        // 添加POST方式的附件打包下载（加密ZIP）
+        .route("/attachment/download-bundle", post(super::download_attachment_bundle))
+        // 添加POST方式的压缩包附件解析
+        .route("/attachment/inspect-archive", post(super::inspect_archive_attachment))
+        // 添加POST方式的收件人暴露画像查询
+        .route("/recipient/profile", post(super::query_recipient_profile))
+        // 添加POST方式的收件人暴露排行查询
+        .route("/recipient/top", post(super::query_recipient_rank))
+        // 添加POST方式的发件人信誉画像查询
+        .route("/sender/profile", post(super::query_sender_profile))
+        // 添加POST方式的哈希关联查询
+        .route("/hash/pivot", post(super::query_hash_pivot))
+        // 添加POST方式的邮件会话查询
+        .route("/email/thread", post(super::query_mail_thread))
+        // 添加POST方式的钓鱼批次查询
+        .route("/campaign/list", post(super::query_campaigns))
+        // 添加POST方式的附件存储校验
+        .route("/storage/verify", post(super::start_storage_verify))
+        // 添加POST方式的存储校验任务查询
+        .route("/storage/verify/status", post(super::query_storage_verify_job))
+        // 添加POST方式的隔离邮件放行
+        .route("/quarantine/release", post(super::release_quarantined_email))
+        // 添加POST方式的隔离邮件删除
+        .route("/quarantine/delete", post(super::delete_quarantined_email))
+        // 添加POST方式的隔离邮件重新投递
+        .route("/quarantine/redeliver", post(super::redeliver_quarantined_email))
+        // 添加POST方式的隔离处置审批
+        .route("/quarantine/approve", post(super::approve_quarantine_action))
+        // 添加POST方式的隔离处置驳回
+        .route("/quarantine/reject", post(super::reject_quarantine_action))
+        // 添加POST方式的隔离处置请求查询
+        .route("/quarantine/actions", post(super::list_quarantine_actions))
+        // 添加POST方式的隔离处置审计记录查询
+        .route("/quarantine/audit", post(super::query_quarantine_audit))
+        // 数据库不可用时返回503
+        .route_layer(middleware::from_fn_with_state(state.connection, super::require_database))
         // 添加应用状态
         .with_state(state.services)
+        .merge(system)
         // 添加tracing中间件
         .layer(
             TraceLayer::new_for_http()
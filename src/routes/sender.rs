@@ -0,0 +1,56 @@
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+};
+use tracing::info;
+
+use crate::models::api::sender::{SenderProfileData, SenderProfileQuery, SenderProfileResponse};
+use crate::models::domain::sender::{SenderKind, SenderProfileFilter};
+use crate::services::AppServices;
+use super::service_error;
+use crate::services::recipient_service::MAX_TREND_BUCKETS;
+
+/// 默认返回的发信来源数量
+const DEFAULT_TOP_IPS: u32 = 10;
+
+/// 查询发件人信誉画像
+pub async fn query_sender_profile(
+    State(services): State<AppServices>,
+    Json(query): Json<SenderProfileQuery>,
+) -> Result<Json<SenderProfileResponse>, (StatusCode, String)> {
+    info!("路由: 查询发件人信誉画像: sender={}", query.sender);
+
+    if query.sender.trim().trim_start_matches('@').is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "发件人地址或域名不能为空".to_string()));
+    }
+    if query.start_time > query.end_time {
+        return Err((StatusCode::BAD_REQUEST, "开始时间不能晚于结束时间".to_string()));
+    }
+    // 分桶过多时要求调大粒度
+    if (query.end_time - query.start_time).num_seconds() / query.interval.seconds() >= MAX_TREND_BUCKETS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("时间范围内的趋势分桶超过{}个，请使用更大的分桶粒度", MAX_TREND_BUCKETS),
+        ));
+    }
+
+    let filter = SenderProfileFilter {
+        kind: query.kind.unwrap_or_else(|| SenderKind::detect(query.sender.trim())),
+        sender: query.sender,
+        start_time: query.start_time,
+        end_time: query.end_time,
+        interval: query.interval,
+        top_ips: query.top_ips.unwrap_or(DEFAULT_TOP_IPS),
+    };
+
+    let profile = services
+        .sender
+        .get_profile(filter)
+        .await
+        .map_err(service_error(StatusCode::INTERNAL_SERVER_ERROR, "查询发件人信誉画像失败"))?;
+
+    Ok(Json(SenderProfileResponse {
+        code: 200,
+        data: SenderProfileData::from(profile),
+    }))
+}
@@ -0,0 +1,52 @@
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+};
+use tracing::info;
+
+use crate::models::api::spread::{SpreadData, SpreadQuery, SpreadResponse};
+use crate::models::domain::spread::SpreadFilter;
+use crate::models::domain::statistics::TrendInterval;
+use crate::services::AppServices;
+use super::service_error;
+use crate::services::spread_service::MAX_SPREAD_BUCKETS;
+
+/// 查询情报在各单位、各行业间的扩散情况
+pub async fn query_spread(
+    State(services): State<AppServices>,
+    Json(query): Json<SpreadQuery>,
+) -> Result<Json<SpreadResponse>, (StatusCode, String)> {
+    info!("路由: 查询情报扩散，情报ID: {}", query.intelligence_id);
+
+    let interval = query.interval.unwrap_or(TrendInterval::Day);
+    if let (Some(start_time), Some(end_time)) = (query.start_time, query.end_time) {
+        if start_time > end_time {
+            return Err((StatusCode::BAD_REQUEST, "开始时间不能晚于结束时间".to_string()));
+        }
+        if (end_time - start_time).num_seconds() / interval.seconds() >= MAX_SPREAD_BUCKETS {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("时间范围内的分桶超过{}个，请使用更大的分桶粒度", MAX_SPREAD_BUCKETS),
+            ));
+        }
+    }
+
+    let filter = SpreadFilter {
+        intelligence_id: query.intelligence_id,
+        start_time: query.start_time,
+        end_time: query.end_time,
+        interval,
+    };
+
+    let spread = services
+        .spread
+        .get_spread(filter)
+        .await
+        .map_err(service_error(StatusCode::INTERNAL_SERVER_ERROR, "查询情报扩散失败"))?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "情报不存在".to_string()))?;
+
+    Ok(Json(SpreadResponse {
+        code: 200,
+        data: SpreadData::from(spread),
+    }))
+}
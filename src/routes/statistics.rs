@@ -5,10 +5,12 @@ use axum::{
 use tracing::info;
 
 use crate::services::AppServices;
+use super::service_error;
 use crate::models::api::statistics::{
     StatisticsQuery, StatisticsResponse, StatisticsResponseData
 };
 use crate::models::domain::statistics::StatisticsFilter;
+use crate::services::recipient_service::MAX_TREND_BUCKETS;
 
 /// 查询统计数据
 pub async fn query_statistics(
@@ -22,17 +24,33 @@ pub async fn query_statistics(
         query.module
     );
 
+    if query.start_time > query.end_time {
+        return Err((StatusCode::BAD_REQUEST, "开始时间不能晚于结束时间".to_string()));
+    }
+    // 分桶过多时要求调大粒度
+    if (query.end_time - query.start_time).num_seconds() / query.interval.seconds() >= MAX_TREND_BUCKETS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("时间范围内的趋势分桶超过{}个，请使用更大的分桶粒度", MAX_TREND_BUCKETS),
+        ));
+    }
+
     // 创建领域过滤器
     let filter = StatisticsFilter {
         start_time: query.start_time,
         end_time: query.end_time,
         module: query.module,
+        interval: query.interval,
         extras: query.extras,
     };
 
     // 调用服务层获取统计数据
-    let stats_result = services.statistics.get_statistics(filter).await;
-    
+    let stats_result = services
+        .statistics
+        .get_statistics(filter)
+        .await
+        .map_err(service_error(StatusCode::INTERNAL_SERVER_ERROR, "查询统计数据失败"))?;
+
     // 转换为API响应模型
     let response_data = StatisticsResponseData::from(stats_result);
 
@@ -0,0 +1,52 @@
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+};
+use tracing::info;
+
+use crate::models::api::storage::{
+    StorageVerifyJobData, StorageVerifyJobQuery, StorageVerifyJobResponse, StorageVerifyRequest,
+};
+use crate::services::AppServices;
+use super::service_error;
+
+/// 创建附件存储校验任务，返回202和任务信息
+pub async fn start_storage_verify(
+    State(services): State<AppServices>,
+    Json(request): Json<StorageVerifyRequest>,
+) -> Result<(StatusCode, Json<StorageVerifyJobResponse>), (StatusCode, String)> {
+    info!("路由: 创建存储校验任务: mail_ids={:?}", request.mail_ids);
+
+    let job = services
+        .storage
+        .start_verify(request.mail_ids)
+        .await
+        .map_err(service_error(StatusCode::INTERNAL_SERVER_ERROR, "创建存储校验任务失败"))?;
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(StorageVerifyJobResponse {
+            code: 202,
+            data: StorageVerifyJobData::from(job),
+        }),
+    ))
+}
+
+/// 查询存储校验任务进度与结果
+pub async fn query_storage_verify_job(
+    State(services): State<AppServices>,
+    Json(query): Json<StorageVerifyJobQuery>,
+) -> Result<Json<StorageVerifyJobResponse>, (StatusCode, String)> {
+    info!("路由: 查询存储校验任务: job_id={}", query.job_id);
+
+    let job = services
+        .storage
+        .get_job(&query.job_id)
+        .await
+        .map_err(|e| (StatusCode::NOT_FOUND, e.to_string()))?;
+
+    Ok(Json(StorageVerifyJobResponse {
+        code: 200,
+        data: StorageVerifyJobData::from(job),
+    }))
+}
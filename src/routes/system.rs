@@ -0,0 +1,44 @@
+use std::sync::Arc;
+use axum::{
+    extract::{Json, Request, State},
+    http::{HeaderValue, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use tracing::{info, warn};
+
+use crate::db::{ConnectionManager, DbMode};
+use crate::models::api::system::{DbStatusData, DbStatusResponse};
+use super::AppError;
+
+/// 查询数据库连接状态
+pub async fn query_db_status(State(manager): State<Arc<ConnectionManager>>) -> Json<DbStatusResponse> {
+    info!("路由: 查询数据库连接状态");
+
+    Json(DbStatusResponse {
+        code: 200,
+        data: DbStatusData::from(manager.status().await),
+    })
+}
+
+/// 数据库不可用时返回503和重试时间
+///
+/// 请求处理期间连接断开的，由存储库返回`DbUnavailable`错误，处理函数据此返回503；
+/// 已生成的响应不做替换，只为缺少Retry-After的503响应补充重试时间
+pub async fn require_database(
+    State(manager): State<Arc<ConnectionManager>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if manager.mode().await == DbMode::Unavailable {
+        return AppError::from(manager.unavailable().await).into_response();
+    }
+
+    let mut response = next.run(request).await;
+    if response.status() == StatusCode::SERVICE_UNAVAILABLE && !response.headers().contains_key(header::RETRY_AFTER) {
+        warn!("请求处理期间数据库不可用，返回503");
+        let retry_after = manager.retry_after_secs().await;
+        response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+    }
+    response
+}
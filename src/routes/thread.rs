@@ -0,0 +1,35 @@
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+};
+use tracing::info;
+
+use crate::models::api::thread::{MailThreadData, MailThreadResponse, ThreadQuery};
+use crate::services::AppServices;
+use super::service_error;
+
+/// 查询邮件所在的会话
+pub async fn query_mail_thread(
+    State(services): State<AppServices>,
+    Json(query): Json<ThreadQuery>,
+) -> Result<Json<MailThreadResponse>, (StatusCode, String)> {
+    info!("路由: 查询邮件会话: mail_id={}", query.mail_id);
+
+    let mail_id: u64 = query
+        .mail_id
+        .trim()
+        .parse()
+        .map_err(|_| (StatusCode::BAD_REQUEST, format!("无效的邮件ID: {}", query.mail_id)))?;
+
+    let thread = services
+        .thread
+        .get_thread(mail_id)
+        .await
+        .map_err(service_error(StatusCode::INTERNAL_SERVER_ERROR, "重建邮件会话失败"))?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "邮件未找到".to_string()))?;
+
+    Ok(Json(MailThreadResponse {
+        code: 200,
+        data: MailThreadData::from(thread),
+    }))
+}
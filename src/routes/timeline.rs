@@ -5,24 +5,93 @@ use axum::{
 use tracing::info;
 
 use crate::services::AppServices;
+use super::service_error;
+use crate::services::timeline_service::{MAX_TIMELINE_BUCKETS, MAX_TIMELINE_INTELLIGENCE};
+use crate::models::api::email::StatusList;
 use crate::models::api::timeline::{TimelineQuery, TimelineResponse, TimelineData};
+use crate::models::domain::timeline::TimelineFilter;
+
+/// 默认每页邮件数
+const DEFAULT_PAGE_SIZE: u32 = 20;
 
 /// 查询攻击时间线
 pub async fn query_timeline(
     State(services): State<AppServices>,
     Json(query): Json<TimelineQuery>,
 ) -> Result<Json<TimelineResponse>, (StatusCode, String)> {
-    info!("路由: 查询攻击时间线，情报ID: {}", query.intelligence_id);
-    
+    info!(
+        "路由: 查询攻击时间线，情报ID: {:?}, 情报ID列表: {:?}, 攻击组织: {:?}",
+        query.intelligence_id, query.intelligence_ids, query.threat_actor
+    );
+
+    // 合并情报ID并去重，至少需要情报ID或攻击组织之一
+    let mut intelligence_ids = Vec::new();
+    for id in query.intelligence_id.into_iter().chain(query.intelligence_ids.into_iter().flatten()) {
+        if !intelligence_ids.contains(&id) {
+            intelligence_ids.push(id);
+        }
+    }
+    let threat_actor = query.threat_actor.filter(|actor| !actor.trim().is_empty());
+    if intelligence_ids.is_empty() && threat_actor.is_none() {
+        return Err((StatusCode::BAD_REQUEST, "情报ID和攻击组织不能同时为空".to_string()));
+    }
+    if intelligence_ids.len() > MAX_TIMELINE_INTELLIGENCE {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("一次最多合并{}个情报", MAX_TIMELINE_INTELLIGENCE),
+        ));
+    }
+
+    // 校验时间范围
+    if let (Some(start_time), Some(end_time)) = (query.start_time, query.end_time) {
+        if start_time > end_time {
+            return Err((StatusCode::BAD_REQUEST, "开始时间不能晚于结束时间".to_string()));
+        }
+        // 分桶过多时要求调大粒度
+        if let Some(interval) = query.interval
+            && (end_time - start_time).num_seconds() / interval.seconds() >= MAX_TIMELINE_BUCKETS
+        {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("时间范围内的分桶超过{}个，请使用更大的分桶粒度", MAX_TIMELINE_BUCKETS),
+            ));
+        }
+    }
+
+    // 解析处置动作，无效值返回400
+    let actions = query
+        .action
+        .map(StatusList::parse)
+        .transpose()
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?
+        .unwrap_or_default();
+
+    let filter = TimelineFilter {
+        intelligence_ids,
+        threat_actor,
+        start_time: query.start_time,
+        end_time: query.end_time,
+        actions,
+        recipient_domain: query.recipient_domain,
+        page: query.page.unwrap_or(1),
+        page_size: query.page_size.unwrap_or(DEFAULT_PAGE_SIZE),
+        interval: query.interval,
+    };
+
     // 调用服务层获取时间线数据
-    let timeline = services.timeline.get_timeline(query.intelligence_id).await;
-    
+    let timeline = services
+        .timeline
+        .get_timeline(filter)
+        .await
+        .map_err(service_error(StatusCode::INTERNAL_SERVER_ERROR, "查询攻击时间线失败"))?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "情报不存在".to_string()))?;
+
     // 转换为API响应模型
     let timeline_data = TimelineData::from(timeline);
-    
+
     // 构建响应
     Ok(Json(TimelineResponse {
         code: 200,
         data: timeline_data,
     }))
-} 
\ No newline at end of file
+}
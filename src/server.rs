@@ -3,12 +3,14 @@ use std::sync::Arc;
 // 移除未使用的导入
 // use axum::Router;
 use tokio::net::TcpListener;
-use tracing::info;
+use tracing::{error, info};
 
 use crate::{
     telemetry::{init_tracing, shutdown_tracer},
     db::{
-        ClickHouseClient, 
+        ConnectionManager,
+        FileBlobStore,
+        Repositories,
         // 移除未使用的导入
         // ClickHouseUserEventRepository, 
         // ClickHouseAnalysisResultRepository,
@@ -24,42 +26,27 @@ use crate::{
 /// 应用状态
 #[derive(Clone)]
 pub struct AppState {
-    /// ClickHouse客户端
-    pub client: Option<Arc<crate::db::ClickHouseClient>>,
+    /// 数据库连接管理器
+    pub connection: Arc<ConnectionManager>,
     /// 应用服务
     pub services: AppServices,
 }
 
 /// 初始化数据库
+///
+/// 连接失败时先以内存模式运行，后台按退避间隔重连；连接成功但表结构与服务不一致时返回错误，
+/// 避免带着错误的结构运行
 async fn init_database() -> Result<AppState, Box<dyn std::error::Error>> {
     let config = crate::config::get_config();
-    
-    // 尝试创建ClickHouse客户端
-    match ClickHouseClient::new(config.db_config).await {
-        Ok(clickhouse_client) => {
-            // 创建ClickHouse存储库
-            let client = Arc::new(clickhouse_client);
-            
-            // 创建服务层，并传递数据库客户端
-            let services = AppServices::new(Some(client.clone()));
-            
-            Ok(AppState {
-                client: Some(client),
-                services,
-            })
-        },
-        Err(e) => {
-            info!("ClickHouse连接失败: {:?}，使用内存模式", e);
-            
-            // 创建服务层，使用None表示无数据库连接
-            let services = AppServices::new(None);
-            
-            Ok(AppState {
-                client: None,
-                services,
-            })
-        }
-    }
+
+    let connection = ConnectionManager::start(config.db_config.clone(), config.schema_check, config.db_retry).await?;
+    info!("数据访问模式: {}", connection.mode().await.label());
+
+    // 创建服务层，存储库随连接状态切换
+    let files = FileBlobStore::new(config.mail_storage_dir.clone());
+    let services = AppServices::new(Repositories::managed(connection.clone(), files), &config);
+
+    Ok(AppState { connection, services })
 }
 
 /// 运行HTTP服务器
@@ -71,7 +58,7 @@ pub async fn run_server() -> Result<(), Box<dyn std::error::Error>> {
     let config = crate::config::get_config();
     let server_addr = config.server_addr.to_string();
     
-    // 初始化数据库
+    // 初始化数据库，表结构不一致时直接退出
     info!("正在初始化数据库连接...");
     let state = match init_database().await {
         Ok(state) => {
@@ -79,11 +66,9 @@ pub async fn run_server() -> Result<(), Box<dyn std::error::Error>> {
             state
         },
         Err(e) => {
-            info!("数据库初始化失败: {}，使用内存模式运行", e);
-            AppState {
-                client: None,
-                services: AppServices::new(None),
-            }
+            error!("数据库初始化失败: {}", e);
+            shutdown_tracer();
+            return Err(e);
         }
     };
     
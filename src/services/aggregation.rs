@@ -0,0 +1,74 @@
+//! 画像类查询共用的结果整理工具
+//!
+//! 收件人、发件人等画像的聚合由存储库完成，这里把聚合行整理为领域模型
+
+use std::collections::HashMap;
+use chrono::{DateTime, Utc};
+use tracing::warn;
+
+use crate::db::{NamedCountRow, TrendCountRow};
+use crate::models::domain::email::{ActionCount, EmailStatus};
+use crate::models::domain::statistics::{NamedCount, TrendInterval};
+
+/// 时间戳为0表示没有数据
+pub(crate) fn optional_time(secs: u32) -> Option<DateTime<Utc>> {
+    (secs > 0).then(|| DateTime::from_timestamp(i64::from(secs), 0)).flatten()
+}
+
+/// 分组计数行转换为领域模型
+pub(crate) fn named_counts(rows: Vec<NamedCountRow>) -> Vec<NamedCount> {
+    rows.into_iter()
+        .map(|row| NamedCount { name: row.name, count: row.count })
+        .collect()
+}
+
+/// 按处置动作分组的计数行转换为领域模型，按接受、丢弃、拒绝、隔离排序
+pub(crate) fn action_counts(rows: Vec<NamedCountRow>) -> Vec<ActionCount> {
+    let mut actions: Vec<ActionCount> = rows
+        .into_iter()
+        .filter_map(|row| match row.name.parse::<EmailStatus>() {
+            Ok(status) => Some(ActionCount { status, count: row.count }),
+            Err(e) => {
+                warn!("处置动作无法识别: {}", e);
+                None
+            }
+        })
+        .collect();
+    actions.sort_by_key(|action| EmailStatus::ALL.iter().position(|status| *status == action.status));
+    actions
+}
+
+/// 补齐没有邮件的分桶，返回（分桶起始时间, 邮件数, 命中情报的邮件数）
+pub(crate) fn fill_trend(
+    interval: TrendInterval,
+    start_time: DateTime<Utc>,
+    end_time: DateTime<Utc>,
+    rows: Vec<TrendCountRow>,
+) -> Vec<(DateTime<Utc>, u64, u64)> {
+    let counts: HashMap<i64, (u64, u64)> = rows
+        .into_iter()
+        .map(|row| (i64::from(row.bucket_secs), (row.total, row.hits)))
+        .collect();
+    interval
+        .buckets(start_time, end_time)
+        .into_iter()
+        .map(|bucket| {
+            let (total, hits) = counts.get(&bucket).copied().unwrap_or_default();
+            (DateTime::from_timestamp(bucket, 0).unwrap_or(start_time), total, hits)
+        })
+        .collect()
+}
+
+/// 按名称计数，按次数倒序、名称正序
+pub(crate) fn count_by(keys: impl IntoIterator<Item = String>) -> Vec<NamedCountRow> {
+    let mut counts: HashMap<String, u64> = HashMap::new();
+    for key in keys {
+        *counts.entry(key).or_default() += 1;
+    }
+    let mut rows: Vec<NamedCountRow> = counts
+        .into_iter()
+        .map(|(name, count)| NamedCountRow { name, count })
+        .collect();
+    rows.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+    rows
+}
@@ -0,0 +1,220 @@
+//! 图片条码识别后端
+//!
+//! 服务层只关心解出的内容，具体识别交给后端实现。
+//! 当前提供调用zbarimg命令行的实现，未安装zbar的环境可以关闭识别
+
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::Duration;
+
+use anyhow::{Result, anyhow, bail};
+use async_trait::async_trait;
+use tokio::process::Command;
+use tracing::debug;
+use uuid::Uuid;
+
+use crate::content::FileKind;
+
+/// 识别出的条码
+#[derive(Debug, Clone)]
+pub struct BarcodeSymbol {
+    /// 是否为二维码（QR Code），否则为其他一维或二维条码
+    pub qr: bool,
+    /// 条码类型名称，如QR-Code、EAN-13
+    pub symbology: String,
+    /// 条码内容
+    pub data: String,
+}
+
+/// 图片条码识别后端
+#[async_trait]
+pub trait BarcodeDecoder: Send + Sync {
+    /// 后端名称，记录在日志中
+    fn name(&self) -> &'static str;
+
+    /// 识别图片中的全部条码，`kind`为图片的识别类型
+    async fn decode(&self, image: &[u8], kind: FileKind) -> Result<Vec<BarcodeSymbol>>;
+}
+
+/// 关闭识别，始终返回空结果
+pub struct DisabledDecoder;
+
+#[async_trait]
+impl BarcodeDecoder for DisabledDecoder {
+    fn name(&self) -> &'static str {
+        "disabled"
+    }
+
+    async fn decode(&self, _image: &[u8], _kind: FileKind) -> Result<Vec<BarcodeSymbol>> {
+        Ok(Vec::new())
+    }
+}
+
+/// zbarimg后端
+///
+/// 图片写入临时文件后调用`zbarimg --quiet --xml`，从XML输出中读取条码类型与内容
+pub struct ZbarDecoder {
+    /// zbarimg程序路径
+    program: PathBuf,
+    /// 单张图片的识别超时
+    timeout: Duration,
+}
+
+impl ZbarDecoder {
+    /// 创建zbarimg后端
+    pub fn new(program: PathBuf) -> Self {
+        Self {
+            program,
+            timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+#[async_trait]
+impl BarcodeDecoder for ZbarDecoder {
+    fn name(&self) -> &'static str {
+        "zbar"
+    }
+
+    async fn decode(&self, image: &[u8], kind: FileKind) -> Result<Vec<BarcodeSymbol>> {
+        let extension = kind.extensions().first().copied().unwrap_or("bin");
+        let path = std::env::temp_dir().join(format!("analysis-api-barcode-{}.{}", Uuid::new_v4(), extension));
+        tokio::fs::write(&path, image).await?;
+
+        let output = tokio::time::timeout(
+            self.timeout,
+            Command::new(&self.program)
+                .arg("--quiet")
+                .arg("--xml")
+                .arg(&path)
+                .stdin(Stdio::null())
+                .kill_on_drop(true)
+                .output(),
+        )
+        .await;
+        let _ = tokio::fs::remove_file(&path).await;
+
+        let output = output
+            .map_err(|_| anyhow!("条码识别超时"))?
+            .map_err(|e| anyhow!("无法启动{}: {}", self.program.display(), e))?;
+        // 退出码4表示图片中没有条码
+        match output.status.code() {
+            Some(0) => {}
+            Some(4) => return Ok(Vec::new()),
+            _ => bail!(
+                "条码识别失败: status={}, stderr={}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        }
+
+        let symbols = parse_zbar_xml(&String::from_utf8_lossy(&output.stdout));
+        debug!("zbarimg识别完成: symbols={}", symbols.len());
+        Ok(symbols)
+    }
+}
+
+/// 解析zbarimg的XML输出
+///
+/// 每个条码形如`<symbol type='QR-Code' ...><data><![CDATA[...]]></data></symbol>`，
+/// 内容包含`]]>`时会拆成多个CDATA段。二进制内容会以base64输出，不可能是链接，直接跳过
+fn parse_zbar_xml(xml: &str) -> Vec<BarcodeSymbol> {
+    xml.split("<symbol ")
+        .skip(1)
+        .filter_map(|symbol| {
+            let (attributes, body) = symbol.split_once('>')?;
+            let symbology = attribute(attributes, "type")?;
+            let data_start = body.find("<data")?;
+            let data = &body[data_start..];
+            let (data_tag, data) = data.split_once('>')?;
+            if attribute(data_tag, "format").is_some_and(|format| format == "base64") {
+                return None;
+            }
+            let data = data.split("</data>").next()?;
+            let data = match data.strip_prefix("<![CDATA[").and_then(|data| data.strip_suffix("]]>")) {
+                Some(data) => data.replace("]]><![CDATA[", ""),
+                None => unescape(data),
+            };
+            Some(BarcodeSymbol {
+                qr: symbology.eq_ignore_ascii_case("QR-Code"),
+                symbology: symbology.to_string(),
+                data,
+            })
+        })
+        .collect()
+}
+
+/// 读取XML标签中单引号或双引号包围的属性值，属性名前必须是空白或标签开头
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let pattern = format!("{}=", name);
+    let start = tag
+        .match_indices(&pattern)
+        .map(|(index, _)| index)
+        .find(|&index| tag[..index].chars().next_back().is_none_or(|c| c.is_whitespace() || c == '<'))?
+        + pattern.len();
+    let quote = tag[start..].chars().next()?;
+    if quote != '\'' && quote != '"' {
+        return None;
+    }
+    let value = &tag[start + 1..];
+    value.find(quote).map(|end| &value[..end])
+}
+
+/// 还原XML预定义实体
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_cdata_and_quote_styles() {
+        let xml = r#"<barcodes xmlns='http://zbar.sourceforge.net/2008/barcode'>
+<source href='/tmp/a.png'>
+<index num='0'>
+<symbol type='QR-Code' quality='1' orientation='UP'><data><![CDATA[https://example.com/a?x=1&y=2]]></data></symbol>
+<symbol type="EAN-13" quality="1"><data length="13">5901234123457</data></symbol>
+<symbol type='QR-Code' quality='1'><data><![CDATA[a]]]]><![CDATA[>b]]></data></symbol>
+</index>
+</source>
+</barcodes>"#;
+        let symbols = parse_zbar_xml(xml);
+        let parsed: Vec<(bool, &str, &str)> = symbols
+            .iter()
+            .map(|symbol| (symbol.qr, symbol.symbology.as_str(), symbol.data.as_str()))
+            .collect();
+        assert_eq!(
+            parsed,
+            [
+                (true, "QR-Code", "https://example.com/a?x=1&y=2"),
+                (false, "EAN-13", "5901234123457"),
+                (true, "QR-Code", "a]]>b"),
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_base64_and_malformed_symbols() {
+        let xml = "<symbol type='QR-Code'><data format='base64' length='4'><![CDATA[AAECAw==]]></data></symbol>\
+                   <symbol quality='1'><data><![CDATA[no type]]></data></symbol>\
+                   <symbol type=QR-Code><data><![CDATA[unquoted]]></data></symbol>\
+                   <symbol type='QR-Code'><data>https://example.com/?a=1&amp;b=2</data></symbol>\
+                   <symbol type='QR-Code'>";
+        let data: Vec<String> = parse_zbar_xml(xml).into_iter().map(|symbol| symbol.data).collect();
+        assert_eq!(data, ["https://example.com/?a=1&b=2"]);
+    }
+
+    #[test]
+    fn attribute_requires_whole_name() {
+        assert_eq!(attribute("symbol subtype='x' type='QR-Code'", "type"), Some("QR-Code"));
+        assert_eq!(attribute("symbol subtype='x'", "type"), None);
+        assert_eq!(attribute("data format=\"base64\"", "format"), Some("base64"));
+        assert_eq!(attribute("data format='base64", "format"), None);
+    }
+}
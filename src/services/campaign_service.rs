@@ -0,0 +1,254 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::sync::Arc;
+use chrono::{DateTime, Utc};
+use tracing::{info, warn};
+use anyhow::Result;
+
+use crate::content::{cluster, MailFingerprint};
+use crate::db::{AttachmentRepository, CampaignHitRow, CampaignMailRow, IntelligenceHitRepository, MailAttachmentRow, MailInfoRepository};
+use crate::models::domain::campaign::{Campaign, CampaignFilter, CampaignIntelligence, CampaignList, CampaignMail};
+use crate::models::domain::email::EmailStatus;
+use crate::services::aggregation::{count_by, named_counts};
+
+/// 最多参与聚类的邮件数，超出时只分析最近的邮件
+pub const MAX_CAMPAIGN_MAILS: u32 = 2000;
+/// 最多返回的批次数
+const MAX_CAMPAIGNS: u32 = 200;
+
+/// 钓鱼批次聚类服务
+///
+/// 为时间范围内的邮件计算相似度指纹（主题与正文的SimHash、链接主机名、附件哈希），
+/// 将同一模板的变体归为一个批次，并汇总批次内命中的情报，使一封邮件的命中能暴露同批次的其余邮件
+#[derive(Clone)]
+pub struct CampaignService {
+    /// 邮件信息存储库
+    mails: Arc<dyn MailInfoRepository>,
+    /// 情报命中存储库
+    hits: Arc<dyn IntelligenceHitRepository>,
+    /// 附件存储库
+    attachments: Arc<dyn AttachmentRepository>,
+}
+
+impl CampaignService {
+    /// 创建新的批次聚类服务实例
+    pub fn new(
+        mails: Arc<dyn MailInfoRepository>,
+        hits: Arc<dyn IntelligenceHitRepository>,
+        attachments: Arc<dyn AttachmentRepository>,
+    ) -> Self {
+        Self { mails, hits, attachments }
+    }
+
+    /// 聚类时间范围内的邮件并返回满足条件的批次
+    pub async fn list_campaigns(&self, mut filter: CampaignFilter) -> Result<CampaignList> {
+        filter.limit = filter.limit.clamp(1, MAX_CAMPAIGNS);
+        filter.min_size = filter.min_size.max(1);
+        info!(
+            "批次服务: 聚类: start={}, end={}, min_size={}, min_similarity={}",
+            filter.start_time, filter.end_time, filter.min_size, filter.min_similarity
+        );
+
+        // 多取一封用于判断是否超出上限
+        let mut mails = self
+            .mails
+            .campaign_mails(filter.start_time, filter.end_time, u64::from(MAX_CAMPAIGN_MAILS) + 1)
+            .await?;
+        let ids: Vec<u64> = mails.iter().map(|row| row.id).collect();
+        let hits = self.hits.campaign_hits(&ids).await?;
+        let attachments = self.attachments.attachments(Some(&ids)).await?;
+        let truncated = mails.len() > MAX_CAMPAIGN_MAILS as usize;
+        if truncated {
+            warn!("时间范围内邮件超过{}封，只分析最近的邮件", MAX_CAMPAIGN_MAILS);
+            mails.truncate(MAX_CAMPAIGN_MAILS as usize);
+        }
+
+        // 两两比较的计算量较大，放到阻塞线程中执行
+        let list = tokio::task::spawn_blocking(move || Self::build_campaigns(&filter, mails, hits, attachments, truncated)).await?;
+        info!("批次服务: 分析邮件{}封，得到批次{}个", list.analyzed_mails, list.total);
+        Ok(list)
+    }
+
+    /// 计算指纹、聚类并整理批次
+    fn build_campaigns(
+        filter: &CampaignFilter,
+        mut mails: Vec<CampaignMailRow>,
+        hits: Vec<CampaignHitRow>,
+        attachments: Vec<MailAttachmentRow>,
+        truncated: bool,
+    ) -> CampaignList {
+        // 按时间正序，批次中的第一封即最早的邮件
+        mails.sort_by_key(|row| (row.timestamp_secs, row.id));
+        let mut hits_by_mail: HashMap<u64, Vec<&CampaignHitRow>> = HashMap::new();
+        for hit in &hits {
+            hits_by_mail.entry(hit.mail_id).or_default().push(hit);
+        }
+        let mail_hits = |id: u64| hits_by_mail.get(&id).map(Vec::as_slice).unwrap_or_default();
+        // 附件哈希取入库时记录的SHA256，没有时退回MD5，与是否命中情报无关
+        let mut hashes_by_mail: HashMap<u64, Vec<&str>> = HashMap::new();
+        for attachment in &attachments {
+            let hash = if attachment.hash_sha256.is_empty() { &attachment.hash_md5 } else { &attachment.hash_sha256 };
+            hashes_by_mail.entry(attachment.mail_id).or_default().push(hash);
+        }
+
+        let fingerprints: Vec<MailFingerprint> = mails
+            .iter()
+            .map(|row| {
+                let attachments = hashes_by_mail.get(&row.id).into_iter().flatten().copied();
+                MailFingerprint::new(&row.subject, &row.text_body, &row.html_body, attachments)
+            })
+            .collect();
+
+        let mut campaigns: Vec<Campaign> = cluster(&fingerprints, filter.min_similarity)
+            .into_iter()
+            .filter(|members| members.len() >= filter.min_size as usize)
+            .filter(|members| {
+                filter
+                    .mail_id
+                    .is_none_or(|mail_id| members.iter().any(|&i| mails[i].id == mail_id))
+            })
+            .filter(|members| {
+                filter.intelligence_id.as_deref().is_none_or(|intel_id| {
+                    members
+                        .iter()
+                        .any(|&i| mail_hits(mails[i].id).iter().any(|hit| hit.intel_id.eq_ignore_ascii_case(intel_id)))
+                })
+            })
+            .map(|members| {
+                let rows: Vec<&CampaignMailRow> = members.iter().map(|&i| &mails[i]).collect();
+                let hits: Vec<&CampaignHitRow> = rows.iter().flat_map(|row| mail_hits(row.id).iter().copied()).collect();
+                let prints: Vec<&MailFingerprint> = members.iter().map(|&i| &fingerprints[i]).collect();
+                Self::build_campaign(&rows, &hits, &prints)
+            })
+            .collect();
+
+        campaigns.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| b.last_seen.cmp(&a.last_seen)));
+        let total = campaigns.len() as u64;
+        campaigns.truncate(filter.limit as usize);
+
+        CampaignList {
+            analyzed_mails: mails.len() as u64,
+            truncated,
+            total,
+            campaigns,
+        }
+    }
+
+    /// 整理一个批次，rows按时间正序
+    fn build_campaign(rows: &[&CampaignMailRow], hits: &[&CampaignHitRow], prints: &[&MailFingerprint]) -> Campaign {
+        let time = |row: &CampaignMailRow| DateTime::from_timestamp(i64::from(row.timestamp_secs), 0).unwrap_or_else(Utc::now);
+        let sender = |row: &CampaignMailRow| {
+            if row.display_from.is_empty() {
+                row.client_envelope_from_address.to_lowercase()
+            } else {
+                row.display_from.to_lowercase()
+            }
+        };
+        let hit_ids: HashSet<u64> = hits.iter().map(|hit| hit.mail_id).collect();
+
+        // 同一情报可能被多封邮件命中
+        let mut intelligence: BTreeMap<&str, (&CampaignHitRow, HashSet<u64>)> = BTreeMap::new();
+        for hit in hits {
+            intelligence
+                .entry(hit.intel_id.as_str())
+                .or_insert_with(|| (hit, HashSet::new()))
+                .1
+                .insert(hit.mail_id);
+        }
+        let mut intelligence: Vec<CampaignIntelligence> = intelligence
+            .into_values()
+            .map(|(hit, mails)| CampaignIntelligence {
+                intelligence_id: hit.intel_id.clone(),
+                attribute: hit.attribute_name.clone(),
+                value: hit.value_text.clone(),
+                intelligence_type: hit.type_name.clone(),
+                urgency: hit.urgency_name.clone(),
+                threat_actor: hit.actor_name.clone(),
+                hit_mails: mails.len() as u64,
+            })
+            .collect();
+        intelligence.sort_by_key(|intel| std::cmp::Reverse(intel.hit_mails));
+
+        let hosts: BTreeSet<&String> = prints.iter().flat_map(|print| &print.hosts).collect();
+        let attachment_hashes: BTreeSet<&String> = prints.iter().flat_map(|print| &print.attachments).collect();
+
+        Campaign {
+            campaign_id: rows[0].id.to_string(),
+            subject: rows[0].subject.clone(),
+            size: rows.len() as u64,
+            first_seen: time(rows[0]),
+            last_seen: rows.iter().map(|row| time(row)).max().unwrap_or_else(Utc::now),
+            senders: named_counts(count_by(rows.iter().map(|row| sender(row)))),
+            recipients: named_counts(count_by(rows.iter().flat_map(|row| row.recipient_list.iter().cloned()))),
+            hosts: hosts.into_iter().cloned().collect(),
+            attachment_hashes: attachment_hashes.into_iter().cloned().collect(),
+            hit_mails: hit_ids.len() as u64,
+            intelligence,
+            mails: rows
+                .iter()
+                .zip(prints)
+                .map(|(row, print)| CampaignMail {
+                    mail_id: row.id.to_string(),
+                    timestamp: time(row),
+                    subject: row.subject.clone(),
+                    sender: sender(row),
+                    recipients: row.recipient_list.clone(),
+                    status: EmailStatus::from_stored(row.id, &row.action_name),
+                    hit_intelligence: hit_ids.contains(&row.id),
+                    similarity: (prints[0].similarity(print) * 1000.0).round() / 1000.0,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{InMemoryRepository, FIXTURE_TIME};
+    use chrono::Duration;
+
+    fn service() -> CampaignService {
+        let repository = Arc::new(InMemoryRepository::fixture());
+        CampaignService::new(repository.clone(), repository.clone(), repository)
+    }
+
+    fn filter(min_similarity: f64) -> CampaignFilter {
+        let end_time = DateTime::from_timestamp(FIXTURE_TIME, 0).unwrap();
+        CampaignFilter {
+            start_time: end_time - Duration::days(7),
+            end_time,
+            min_size: 2,
+            min_similarity,
+            intelligence_id: None,
+            mail_id: None,
+            limit: 10,
+        }
+    }
+
+    #[tokio::test]
+    async fn stored_attachment_hashes_link_mails() {
+        // 邮件1与邮件5的PDF附件相同，邮件1的附件没有命中任何情报
+        let list = service().list_campaigns(filter(1.0)).await.unwrap();
+        assert_eq!(list.analyzed_mails, 8);
+        assert_eq!(list.total, 1);
+        let campaign = &list.campaigns[0];
+        let ids: Vec<&str> = campaign.mails.iter().map(|mail| mail.mail_id.as_str()).collect();
+        assert_eq!(ids, ["5", "1"]);
+        assert!(campaign.mails.iter().all(|mail| mail.similarity == 1.0));
+        assert_eq!(campaign.hit_mails, 1);
+        // 附件哈希来自附件记录，邮件1的每个附件都计入
+        assert_eq!(campaign.attachment_hashes.len(), 3);
+        assert!(campaign.intelligence.iter().any(|intel| intel.attribute == "Md5"));
+    }
+
+    #[tokio::test]
+    async fn template_variants_form_a_campaign() {
+        let mut filter = filter(0.8);
+        filter.mail_id = Some(8);
+        let list = service().list_campaigns(filter).await.unwrap();
+        assert_eq!(list.total, 1);
+        let ids: Vec<&str> = list.campaigns[0].mails.iter().map(|mail| mail.mail_id.as_str()).collect();
+        assert_eq!(ids, ["8", "2"]);
+        assert_eq!(list.campaigns[0].hosts, ["login.examp1e.com"]);
+    }
+}
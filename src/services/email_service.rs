@@ -1,208 +1,1060 @@
+use std::collections::HashMap;
+use std::io;
 use std::sync::Arc;
-use chrono::Utc;
-use tracing::info;
-use anyhow::{Result, Context, anyhow};
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio::sync::RwLock;
+use tracing::{debug, error, info, warn};
+use anyhow::{Result, anyhow};
 
-use crate::db::{ClickHouseClient, DbResult};
-use crate::models::domain::email::{Email, Attachment, Url, EmailFilter};
+use crate::content::{
+    build_sample_zip, compute_digests, detect_file_type, extract_pdf_images, inspect_archive, normalize_url,
+    render_preview, url_host, url_path, ArchiveLimits, DigestCheck, DigestHasher, FileDigests, FileKind, IocTerm,
+    SampleFile,
+};
+use crate::db::{
+    AttachmentRepository, Blob, BlobRead, BlobStore, IntelligenceHitRepository, MailAttachmentRow, MailInfoRepository,
+    MailSearchRow,
+};
+use crate::models::domain::hash::{HashAttachment, HashKind};
+use crate::models::domain::quarantine::MailDeliveryInfo;
+use crate::models::domain::storage::{IntegrityError, IntegrityStatus, StorageCheck, StoredAttachment, StoredBlob};
+use crate::models::domain::email::{
+    Email, Attachment, AttachmentContent, ArchiveInspection, ContentBody, Url, UrlIntelligence,
+    UrlSource, EmailFilter, EmailPreview, EmailSearchCriteria, IocHighlight,
+};
+use crate::services::{BarcodeDecoder, BarcodeSymbol};
+use crate::services::search_service::{email_from_row, MAX_SEARCH_PAGE_SIZE};
+
+/// 校验附件完整性时每次读取的块大小
+const VERIFY_CHUNK_SIZE: usize = 64 * 1024;
+
+/// 识别附件类型时读取的文件头大小
+const DETECT_SAMPLE_SIZE: u64 = 64 * 1024;
+
+/// 需要整体读入内存处理（打包、解析压缩包、识别条码、解析邮件头）的附件或原始邮件大小上限
+const MAX_BUFFERED_FILE: u64 = 100 * 1024 * 1024;
+
+/// 条码识别结果缓存的最大条目数
+const MAX_BARCODE_CACHE: usize = 1024;
+
+/// 可与链接匹配的情报属性
+const URL_INTELLIGENCE_ATTRIBUTES: &[&str] = &["Url", "Domain", "UrlDomain"];
 
 /// 邮件服务
 #[derive(Clone)]
 pub struct EmailService {
-    /// ClickHouse客户端，可选（可能运行在内存模式）
-    db_client: Option<Arc<ClickHouseClient>>,
+    /// 邮件信息存储库
+    mails: Arc<dyn MailInfoRepository>,
+    /// 情报命中存储库
+    hits: Arc<dyn IntelligenceHitRepository>,
+    /// 附件记录存储库
+    attachments: Arc<dyn AttachmentRepository>,
+    /// 附件文件存储
+    blobs: Arc<dyn BlobStore>,
+    /// 安全下载压缩包密码
+    safe_download_password: String,
+    /// 图片条码识别后端
+    barcode: Arc<dyn BarcodeDecoder>,
+    /// 条码识别结果，按附件SHA256缓存
+    barcode_cache: Arc<RwLock<HashMap<String, Vec<BarcodeSymbol>>>>,
 }
 
 impl EmailService {
     /// 创建新的邮件服务实例
-    pub fn new(db_client: Option<Arc<ClickHouseClient>>) -> Self {
-        Self { db_client }
+    pub fn new(
+        mails: Arc<dyn MailInfoRepository>,
+        hits: Arc<dyn IntelligenceHitRepository>,
+        attachments: Arc<dyn AttachmentRepository>,
+        blobs: Arc<dyn BlobStore>,
+        safe_download_password: String,
+        barcode: Arc<dyn BarcodeDecoder>,
+    ) -> Self {
+        Self {
+            mails,
+            hits,
+            attachments,
+            blobs,
+            safe_download_password,
+            barcode,
+            barcode_cache: Arc::new(RwLock::new(HashMap::new())),
+        }
     }
 
-    /// 查询与情报相关的邮件
-    pub async fn get_related_emails(&self, filter: EmailFilter) -> (u32, Vec<Email>) {
+    /// 查询与情报相关的邮件，返回匹配总数和当前页邮件
+    pub async fn get_related_emails(&self, filter: EmailFilter) -> Result<(u64, Vec<Email>)> {
         info!(
-            "邮件服务: 查询关联邮件: intelligence_id={}, page={}, page_size={}",
-            filter.intelligence_id, filter.page, filter.page_size
+            "邮件服务: 查询关联邮件: intelligence_id={}, statuses={:?}, page={}, page_size={}",
+            filter.intelligence_id, filter.statuses, filter.page, filter.page_size
         );
 
-        // 如果有数据库连接，记录一条日志
-        if let Some(_client) = &self.db_client {
-            info!("有数据库连接，但当前使用模拟数据 - 真实查询将在后续实现");
-            // 后续可以在这里实现从数据库获取数据的逻辑
-        } else {
-            info!("无数据库连接，使用模拟数据");
-        }
-
-        // 返回模拟数据
-        (1, vec![
-            Email {
-                id: "1".to_string(),
-                timestamp: Utc::now(),
-                subject: "Test Email".to_string(),
-                sender: "sender@example.com".to_string(),
-                recipients: vec!["recipient@example.com".to_string()],
-                attachments: vec![
-                    Attachment {
-                        id: "att_001".to_string(),
-                        filename: "test.pdf".to_string(),
-                        file_path: "/attachments/2024/03/test.pdf".to_string(),
-                        size: 1024,
-                        file_extension: "pdf".to_string(),
-                        md5: "d41d8cd98f00b204e9800998ecf8427e".to_string(),
-                    }
-                ],
-                urls: vec![
-                    Url {
-                        id: "url_001".to_string(),
-                        url: "https://example.com/test".to_string(),
-                        path: "/test".to_string(),
-                    }
-                ],
-                content: "邮件内容".to_string(),
-                status: "正常".to_string(),
-                source_code: "原始邮件代码".to_string(),
-            }
-        ])
+        let criteria = EmailSearchCriteria {
+            start_time: Some(filter.start_time),
+            end_time: Some(filter.end_time),
+            intelligence_id: Some(filter.intelligence_id),
+            actions: filter.statuses,
+            page: filter.page.max(1),
+            page_size: filter.page_size.clamp(1, MAX_SEARCH_PAGE_SIZE),
+            ..EmailSearchCriteria::default()
+        };
+        let (total, rows) = self.mails.search_mails(&criteria).await?;
+
+        let mut emails = Vec::with_capacity(rows.len());
+        for row in rows {
+            emails.push(self.complete_email(row).await?);
+        }
+        Ok((total, emails))
     }
 
     /// 下载邮件EML文件
+    ///
+    /// 从文件存储读取原始邮件，入库时记录了SHA256的与之比对，不一致时记录安全日志并返回错误
     pub async fn download_email_eml(&self, email_id: &str) -> Result<Vec<u8>> {
         info!("邮件服务: 下载邮件EML: email_id={}", email_id);
 
-        // 如果有数据库连接，尝试从数据库获取
-        if let Some(_client) = &self.db_client {
-            info!("有数据库连接，但当前使用模拟数据 - 真实下载将在后续实现");
-            // 后续可以在这里实现从数据库获取数据的逻辑
-        } else {
-            info!("无数据库连接，使用模拟数据");
-        }
-
-        // 模拟EML数据
-        // 在实际应用中，这里应该查询数据库或文件系统获取真实的EML数据
-        let eml_content = format!(
-            "From: sender@example.com\r\n\
-             To: recipient@example.com\r\n\
-             Subject: Test Email\r\n\
-             Date: {}\r\n\
-             Content-Type: text/plain\r\n\
-             \r\n\
-             这是一封测试邮件的内容。\r\n\
-             邮件ID: {}\r\n",
-            Utc::now().to_rfc2822(),
-            email_id
-        );
+        let blob = self.open_email_eml(email_id).await?;
+        if blob.size > MAX_BUFFERED_FILE {
+            return Err(anyhow!("原始邮件过大: {} 字节，超过 {} 字节", blob.size, MAX_BUFFERED_FILE));
+        }
 
-        Ok(eml_content.into_bytes())
+        let mut data = Vec::with_capacity(blob.size as usize);
+        blob.into_verified_reader()
+            .take(MAX_BUFFERED_FILE + 1)
+            .read_to_end(&mut data)
+            .await
+            .map_err(|e| anyhow!("读取原始邮件失败: {}: {}", email_id, e))?;
+        if data.len() as u64 > MAX_BUFFERED_FILE {
+            return Err(anyhow!("原始邮件过大: 超过 {} 字节", MAX_BUFFERED_FILE));
+        }
+        Ok(data)
+    }
+
+    /// 打开存储中的原始邮件，读完整个文件时与入库记录的SHA256比对
+    ///
+    /// 没有记录SHA256的邮件先完整读取一遍计算ETag，不做比对
+    pub async fn open_email_eml(&self, email_id: &str) -> Result<StoredBlob> {
+        let file = self
+            .mails
+            .mail_file(Self::mail_id(email_id)?)
+            .await?
+            .ok_or_else(|| anyhow!("邮件未找到"))?;
+        if file.eml_path.is_empty() {
+            return Err(anyhow!("原始邮件未入库: {}", email_id));
+        }
+        let Blob { size, mut reader } = self
+            .blobs
+            .open(&file.eml_path)
+            .await?
+            .ok_or_else(|| anyhow!("原始邮件文件不存在: {}", file.eml_path))?;
+
+        if file.hash_sha256.is_empty() {
+            let (_, digests) = Self::hash_reader(reader.as_mut()).await?;
+            reader.rewind().await?;
+            return Ok(StoredBlob { size, sha256: digests.sha256, reader, check: None });
+        }
+
+        let expected = file.hash_sha256.to_lowercase();
+        let (mail_id, eml_path, sha256) = (email_id.to_string(), file.eml_path, expected.clone());
+        let check: DigestCheck = Box::new(move |_, digests| {
+            if digests.sha256 == expected {
+                return Ok(());
+            }
+            error!(
+                target: "security",
+                "原始邮件完整性校验失败: mail_id={}, eml_path={}, expected_sha256={}, actual_sha256={}",
+                mail_id, eml_path, expected, digests.sha256
+            );
+            Err(io::Error::new(io::ErrorKind::InvalidData, format!("原始邮件完整性校验失败: {}", mail_id)))
+        });
+        Ok(StoredBlob { size, sha256, reader, check: Some(check) })
+    }
+
+    /// 解析数据库中的邮件ID
+    fn mail_id(email_id: &str) -> Result<u64> {
+        email_id.parse().map_err(|_| anyhow!("无效的邮件ID: {}", email_id))
     }
 
     /// 下载邮件附件
-    pub async fn download_attachment(&self, attachment_id: &str, file_path: &str) -> Result<(Vec<u8>, String, String)> {
-        info!("邮件服务: 下载附件: attachment_id={}, file_path={}", attachment_id, file_path);
+    ///
+    /// Content-Type根据文件签名识别结果决定，而不是附件扩展名。内容从存储流式读取，
+    /// 读完整个文件时与入库记录比对，不一致时下载中断
+    pub async fn download_attachment(&self, attachment_id: &str) -> Result<AttachmentContent> {
+        info!("邮件服务: 下载附件: attachment_id={}", attachment_id);
 
-        // 如果有数据库连接，尝试从数据库获取
-        if let Some(_client) = &self.db_client {
-            info!("有数据库连接，但当前使用模拟数据 - 真实下载将在后续实现");
-            // 后续可以在这里实现从数据库获取数据的逻辑
-        } else {
-            info!("无数据库连接，使用模拟数据");
-        }
-
-        // 从文件路径获取文件名和扩展名
-        let filename = file_path.split('/').last()
-            .ok_or_else(|| anyhow!("无效的文件路径"))?;
-        
-        // 从文件扩展名确定内容类型
-        let content_type = match filename.split('.').last() {
-            Some("pdf") => "application/pdf",
-            Some("doc") => "application/msword",
-            Some("docx") => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
-            Some("xls") => "application/vnd.ms-excel",
-            Some("xlsx") => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
-            Some("ppt") => "application/vnd.ms-powerpoint",
-            Some("pptx") => "application/vnd.openxmlformats-officedocument.presentationml.presentation",
-            Some("txt") => "text/plain",
-            Some("zip") => "application/zip",
-            Some("rar") => "application/x-rar-compressed",
-            Some("7z") => "application/x-7z-compressed",
-            Some("jpg") | Some("jpeg") => "image/jpeg",
-            Some("png") => "image/png",
-            Some("gif") => "image/gif",
-            Some("svg") => "image/svg+xml",
-            Some("mp3") => "audio/mpeg",
-            Some("mp4") => "video/mp4",
-            _ => "application/octet-stream", // 默认二进制类型
-        }.to_string();
-
-        // 模拟查找附件
-        if attachment_id == "att_001" {
-            // 模拟PDF文件内容
-            let pdf_content = b"%PDF-1.5\n1 0 obj\n<</Type/Catalog/Pages 2 0 R>>\nendobj\n2 0 obj\n<</Type/Pages/Kids[3 0 R]/Count 1>>\nendobj\n3 0 obj\n<</Type/Page/MediaBox[0 0 595 842]/Parent 2 0 R/Resources<<>>>>\nendobj\nxref\n0 4\n0000000000 65535 f \n0000000018 00000 n \n0000000063 00000 n \n0000000114 00000 n \n\ntrailer\n<</Size 4/Root 1 0 R>>\nstartxref\n178\n%%EOF".to_vec();
-            Ok((pdf_content, filename.to_string(), content_type))
-        } else {
-            Err(anyhow!("附件未找到"))
+        let record = self.find_attachment(attachment_id).await?;
+        let attachment = &record.attachment;
+        let mut blob = self.open_attachment(&record).await?;
+
+        // 根据文件签名确定真实类型
+        let detected_type = Self::detect_blob_type(blob.reader.as_mut()).await?;
+        let extension_mismatch = detected_type.extension_mismatch(&attachment.file_extension);
+        if extension_mismatch {
+            warn!(
+                "附件扩展名与实际类型不符: attachment_id={}, filename={}, detected_type={:?}",
+                attachment_id, attachment.filename, detected_type
+            );
+        }
+
+        // 入库时没有记录SHA256的附件先完整读取一遍，计算ETag
+        if blob.sha256.is_empty() {
+            let (_, digests) = Self::hash_reader(blob.reader.as_mut()).await?;
+            blob.reader.rewind().await?;
+            blob.sha256 = digests.sha256;
         }
+
+        Ok(AttachmentContent {
+            filename: attachment.filename.clone(),
+            content_type: detected_type.download_content_type().to_string(),
+            detected_type,
+            extension_mismatch,
+            integrity: Self::recorded_status(attachment),
+            body: ContentBody::Stored(blob),
+        })
     }
 
     /// 获取邮件详细信息
     pub async fn get_email_detail(&self, email_id: &str) -> Result<Email> {
         info!("邮件服务: 获取邮件详情: email_id={}", email_id);
 
-        // 如果有数据库连接，尝试从数据库获取
-        if let Some(_client) = &self.db_client {
-            info!("有数据库连接，但当前使用模拟数据 - 真实查询将在后续实现");
-            // 后续可以在这里实现从数据库获取数据的逻辑
+        let criteria = EmailSearchCriteria {
+            mail_ids: Some(vec![Self::mail_id(email_id)?]),
+            page: 1,
+            page_size: 1,
+            ..EmailSearchCriteria::default()
+        };
+        let (_, rows) = self.mails.search_mails(&criteria).await?;
+        let row = rows.into_iter().next().ok_or_else(|| anyhow!("邮件未找到"))?;
+        self.complete_email(row).await
+    }
+
+    /// 由搜索结果生成邮件，补充附件、正文链接和附件条码中的链接
+    async fn complete_email(&self, row: MailSearchRow) -> Result<Email> {
+        let mut email = email_from_row(row);
+        email.attachments = self.mail_attachments(&email.id).await?;
+        email.urls = Self::body_urls(&email.id, &email.content);
+        self.enrich_urls(&mut email).await;
+        Ok(email)
+    }
+
+    /// 正文中的链接，按首次出现的顺序去重
+    fn body_urls(email_id: &str, text: &str) -> Vec<Url> {
+        let mut urls: Vec<Url> = Vec::new();
+        let tokens = text.split(|c: char| c.is_whitespace() || matches!(c, '<' | '>' | '"' | '\'' | '(' | ')' | '[' | ']'));
+        for token in tokens {
+            let token = token.trim_end_matches(['.', ',', ';', ':', '!', '?', '。', '，', '；', '）']);
+            let lower = token.to_ascii_lowercase();
+            if !lower.starts_with("http://") && !lower.starts_with("https://") {
+                continue;
+            }
+            let Some(url) = normalize_url(token) else {
+                continue;
+            };
+            if urls.iter().any(|existing| existing.url == url) {
+                continue;
+            }
+            urls.push(Url {
+                id: format!("{}_url_{:03}", email_id, urls.len() + 1),
+                path: url_path(&url),
+                url,
+                source: UrlSource::EmailBody,
+                attachment_id: None,
+                intelligence: Vec::new(),
+            });
+        }
+        urls
+    }
+
+    /// 补充图片附件中二维码与条码解出的链接，并为全部链接匹配情报
+    ///
+    /// 识别或匹配失败只记录警告，不影响邮件本身的查询
+    async fn enrich_urls(&self, email: &mut Email) {
+        for attachment in &email.attachments {
+            let symbols = match self.attachment_barcodes(&email.id, attachment).await {
+                Ok(symbols) => symbols,
+                Err(e) => {
+                    warn!(
+                        "附件条码识别失败: decoder={}, attachment_id={}, error={}",
+                        self.barcode.name(), attachment.id, e
+                    );
+                    continue;
+                }
+            };
+            for symbol in symbols {
+                let Some(url) = normalize_url(&symbol.data) else {
+                    debug!("条码内容不是链接: attachment_id={}, symbology={}", attachment.id, symbol.symbology);
+                    continue;
+                };
+                let source = if symbol.qr { UrlSource::QrCode } else { UrlSource::Barcode };
+                if email.urls.iter().any(|existing| {
+                    existing.url == url && existing.source == source && existing.attachment_id.as_ref() == Some(&attachment.id)
+                }) {
+                    continue;
+                }
+                info!("附件条码解出链接: email_id={}, attachment_id={}, url={}", email.id, attachment.id, url);
+                email.urls.push(Url {
+                    id: format!("{}_url_{:03}", attachment.id, email.urls.len() + 1),
+                    path: url_path(&url),
+                    url,
+                    source,
+                    attachment_id: Some(attachment.id.clone()),
+                    intelligence: Vec::new(),
+                });
+            }
+        }
+
+        if let Err(e) = self.match_url_intelligence(&mut email.urls).await {
+            warn!("链接情报匹配失败: email_id={}, error={}", email.id, e);
+        }
+    }
+
+    /// 识别图片附件及PDF内嵌图片中的条码，结果按附件内容的SHA256缓存
+    async fn attachment_barcodes(&self, email_id: &str, attachment: &Attachment) -> Result<Vec<BarcodeSymbol>> {
+        let kind = attachment.detected_type;
+        if !matches!(kind, FileKind::Png | FileKind::Jpeg | FileKind::Gif | FileKind::Bmp | FileKind::Pdf) {
+            return Ok(Vec::new());
+        }
+
+        let recorded = Some(attachment.sha256.to_lowercase()).filter(|hash| !hash.is_empty());
+        if let Some(hash) = &recorded
+            && let Some(symbols) = self.barcode_cache.read().await.get(hash)
+        {
+            return Ok(symbols.clone());
+        }
+        let record = StoredAttachment {
+            mail_id: email_id.to_string(),
+            attachment: attachment.clone(),
+        };
+        let (data, _) = self.read_attachment(&record).await?;
+        let sha256 = recorded.unwrap_or_else(|| compute_digests(&data).sha256);
+
+        let images = if kind == FileKind::Pdf {
+            tokio::task::spawn_blocking(move || extract_pdf_images(&data))
+                .await?
+                .into_iter()
+                .map(|image| (image, FileKind::Jpeg))
+                .collect()
         } else {
-            info!("无数据库连接，使用模拟数据");
-        }
-
-        // 模拟邮件数据
-        if email_id == "1" {
-            Ok(Email {
-                id: "1".to_string(),
-                timestamp: Utc::now(),
-                subject: "Test Email".to_string(),
-                sender: "sender@example.com".to_string(),
-                recipients: vec!["recipient@example.com".to_string()],
-                attachments: vec![
-                    Attachment {
-                        id: "att_001".to_string(),
-                        filename: "test.pdf".to_string(),
-                        file_path: "/attachments/2024/03/test.pdf".to_string(),
-                        size: 1024,
-                        file_extension: "pdf".to_string(),
-                        md5: "d41d8cd98f00b204e9800998ecf8427e".to_string(),
-                    }
-                ],
-                urls: vec![
-                    Url {
-                        id: "url_001".to_string(),
-                        url: "https://example.com/test".to_string(),
-                        path: "/test".to_string(),
+            vec![(data, kind)]
+        };
+        let mut symbols = Vec::new();
+        for (image, kind) in images {
+            symbols.extend(self.barcode.decode(&image, kind).await?);
+        }
+
+        let mut cache = self.barcode_cache.write().await;
+        if cache.len() >= MAX_BARCODE_CACHE {
+            cache.clear();
+        }
+        cache.insert(sha256, symbols.clone());
+        Ok(symbols)
+    }
+
+    /// 按完整链接、主机名及其上级域名匹配Url、Domain与UrlDomain情报
+    async fn match_url_intelligence(&self, urls: &mut [Url]) -> Result<()> {
+        let mut candidates: Vec<String> = Vec::new();
+        for url in urls.iter() {
+            for candidate in Self::url_candidates(&url.url) {
+                if !candidates.contains(&candidate) {
+                    candidates.push(candidate);
+                }
+            }
+        }
+        if candidates.is_empty() {
+            return Ok(());
+        }
+
+        let values = self.hits.url_intelligence(URL_INTELLIGENCE_ATTRIBUTES, &candidates).await?;
+
+        for url in urls.iter_mut() {
+            let url_candidates = Self::url_candidates(&url.url);
+            let host = url_host(&url.url).unwrap_or_default();
+            url.intelligence = values
+                .iter()
+                .filter(|row| {
+                    let value = row.value.to_lowercase();
+                    match row.attribute.as_str() {
+                        // 链接情报需要完整匹配，域名情报匹配主机名及其子域名
+                        "Url" => url_candidates[..2.min(url_candidates.len())].contains(&value),
+                        _ => host == value || host.ends_with(&format!(".{}", value)),
                     }
-                ],
-                content: "邮件内容".to_string(),
-                status: "正常".to_string(),
-                source_code: "原始邮件代码".to_string(),
-            })
-        } else {
-            Err(anyhow!("邮件未找到"))
+                })
+                .map(|row| UrlIntelligence {
+                    intelligence_id: row.intelligence_id.clone(),
+                    attribute: row.attribute.clone(),
+                    value: row.value.clone(),
+                })
+                .collect();
+        }
+        Ok(())
+    }
+
+    /// 链接的匹配候选值（小写）：完整链接、去掉查询参数的链接、主机名及各级上级域名
+    fn url_candidates(url: &str) -> Vec<String> {
+        let url = url.to_lowercase();
+        let without_query = url.split('?').next().unwrap_or_default().to_string();
+        let mut candidates = vec![url.clone(), without_query];
+        if let Some(host) = url_host(&url) {
+            let mut domain = host.as_str();
+            loop {
+                candidates.push(domain.to_string());
+                match domain.split_once('.') {
+                    Some((_, parent)) if parent.contains('.') => domain = parent,
+                    _ => break,
+                }
+            }
         }
+        candidates
     }
 
-    // 保留此方法框架，供后续实现
-    #[allow(dead_code)]
-    async fn fetch_emails_from_db(
+    /// 以加密ZIP形式下载邮件EML
+    pub async fn download_email_eml_safe(&self, email_id: &str) -> Result<AttachmentContent> {
+        info!("邮件服务: 安全模式下载邮件EML: email_id={}", email_id);
+
+        let data = self.download_email_eml(email_id).await?;
+        let file = SampleFile {
+            source_id: email_id.to_string(),
+            name: format!("email_{}.eml", email_id),
+            data,
+        };
+
+        self.package_samples(Some(email_id), vec![file], format!("email_{}.zip", email_id), IntegrityStatus::Unverified)
+            .await
+    }
+
+    /// 以加密ZIP形式下载单个附件
+    pub async fn download_attachment_safe(&self, attachment_id: &str) -> Result<AttachmentContent> {
+        info!("邮件服务: 安全模式下载附件: attachment_id={}", attachment_id);
+
+        let record = self.find_attachment(attachment_id).await?;
+        let (data, integrity) = self.read_attachment(&record).await?;
+        let archive_name = format!("{}.zip", record.attachment.filename);
+        let file = SampleFile {
+            source_id: attachment_id.to_string(),
+            name: record.attachment.filename,
+            data,
+        };
+
+        self.package_samples(None, vec![file], archive_name, integrity).await
+    }
+
+    /// 将同一封邮件的多个附件打包为一个加密ZIP
+    ///
+    /// `attachment_ids`为空时打包邮件的全部附件
+    pub async fn download_attachment_bundle(
         &self,
-        _client: &ClickHouseClient,
-        filter: &EmailFilter,
-    ) -> DbResult<(u32, Vec<Email>)> {
-        info!(
-            "数据库查询框架已准备，但具体查询暂未实现。查询情报ID: {}",
-            filter.intelligence_id
+        email_id: &str,
+        attachment_ids: Option<Vec<String>>,
+    ) -> Result<AttachmentContent> {
+        info!("邮件服务: 打包下载邮件附件: email_id={}, attachment_ids={:?}", email_id, attachment_ids);
+
+        let records = self.attachment_records(Some(&[email_id.to_string()])).await?;
+        let selected: Vec<StoredAttachment> = match &attachment_ids {
+            Some(ids) => {
+                if let Some(missing) = ids.iter().find(|id| !records.iter().any(|r| &r.attachment.id == *id)) {
+                    return Err(anyhow!("附件不属于该邮件: {}", missing));
+                }
+                records.into_iter().filter(|r| ids.contains(&r.attachment.id)).collect()
+            }
+            None => records,
+        };
+        if selected.is_empty() {
+            return Err(anyhow!("邮件没有可下载的附件"));
+        }
+
+        // 任一附件未能校验时整个压缩包视为未校验
+        let mut integrity = IntegrityStatus::Verified;
+        let mut files = Vec::with_capacity(selected.len());
+        for record in selected {
+            let (data, status) = self.read_attachment(&record).await?;
+            if status != IntegrityStatus::Verified {
+                integrity = IntegrityStatus::Unverified;
+            }
+            files.push(SampleFile {
+                data,
+                source_id: record.attachment.id,
+                name: record.attachment.filename,
+            });
+        }
+
+        let archive_name = format!("email_{}_attachments.zip", email_id);
+        self.package_samples(Some(email_id), files, archive_name, integrity).await
+    }
+
+    /// 使用配置的密码打包样本，`integrity`为样本的校验结果
+    async fn package_samples(
+        &self,
+        email_id: Option<&str>,
+        files: Vec<SampleFile>,
+        archive_name: String,
+        integrity: IntegrityStatus,
+    ) -> Result<AttachmentContent> {
+        let password = self.safe_download_password.clone();
+        let email_id = email_id.map(str::to_string);
+
+        // 加密与哈希计算是CPU密集操作，放到阻塞线程池中执行
+        let data = tokio::task::spawn_blocking(move || {
+            build_sample_zip(email_id.as_deref(), &files, &password)
+        })
+        .await??;
+
+        Ok(AttachmentContent {
+            filename: archive_name,
+            content_type: FileKind::Zip.mime().to_string(),
+            detected_type: FileKind::Zip,
+            extension_mismatch: false,
+            integrity,
+            body: ContentBody::Memory(data),
+        })
+    }
+
+    /// 解析压缩包附件的成员
+    ///
+    /// 依次尝试请求中的密码和邮件记录的提取密码，嵌套压缩包按深度限制展开
+    pub async fn inspect_archive_attachment(
+        &self,
+        email_id: &str,
+        attachment_id: &str,
+        password: Option<String>,
+    ) -> Result<ArchiveInspection> {
+        info!("邮件服务: 解析压缩包附件: email_id={}, attachment_id={}", email_id, attachment_id);
+
+        let record = self.find_attachment(attachment_id).await?;
+        if record.mail_id != email_id {
+            return Err(anyhow!("附件不属于该邮件: {}", attachment_id));
+        }
+        let (data, _) = self.read_attachment(&record).await?;
+
+        let mut passwords: Vec<String> = password.into_iter().filter(|p| !p.is_empty()).collect();
+        if let Some(stored) = self.fetch_extract_password(email_id).await?
+            && !passwords.contains(&stored)
+        {
+            passwords.push(stored);
+        }
+
+        // 解压与哈希计算是CPU密集操作，放到阻塞线程池中执行
+        let listing = tokio::task::spawn_blocking(move || {
+            inspect_archive(&data, &passwords, &ArchiveLimits::default())
+        })
+        .await??;
+
+        Ok(ArchiveInspection {
+            attachment_id: attachment_id.to_string(),
+            format: listing.format,
+            password_used: listing.password_used,
+            truncated: listing.truncated,
+            warnings: listing.warnings,
+            entries: listing.entries,
+        })
+    }
+
+    /// 查找哈希值相同的附件，按入库时记录的哈希匹配
+    pub async fn find_attachments_by_hash(&self, kind: HashKind, hash: &str) -> Result<Vec<HashAttachment>> {
+        info!("邮件服务: 按哈希查找附件: kind={:?}, hash={}", kind, hash);
+
+        let mut matches = Vec::new();
+        for row in self.attachments.attachments_by_hash(kind, hash).await? {
+            let mut record = Self::stored_attachment(row);
+            self.identify(&mut record.attachment).await;
+            matches.push(HashAttachment {
+                mail_id: record.mail_id,
+                attachment: record.attachment,
+            });
+        }
+
+        Ok(matches)
+    }
+
+    /// 附件存储库中的附件记录，`mail_ids`为空时返回全部记录
+    ///
+    /// 记录中的文件类型尚未识别，需要时由调用方读取文件头识别
+    pub async fn attachment_records(&self, mail_ids: Option<&[String]>) -> Result<Vec<StoredAttachment>> {
+        let mail_ids = mail_ids
+            .map(|ids| ids.iter().map(|id| Self::mail_id(id)).collect::<Result<Vec<_>>>())
+            .transpose()?;
+        Ok(self
+            .attachments
+            .attachments(mail_ids.as_deref())
+            .await?
+            .into_iter()
+            .map(Self::stored_attachment)
+            .collect())
+    }
+
+    /// 按块读取存储中的附件，与入库时记录的哈希和大小比对
+    pub async fn check_attachment(&self, record: &StoredAttachment) -> Result<StorageCheck> {
+        let Some(mut blob) = self.blobs.open(&record.attachment.file_path).await? else {
+            return Ok(Self::storage_check(record, None));
+        };
+        let (size, digests) = Self::hash_reader(blob.reader.as_mut()).await?;
+        Ok(Self::storage_check(record, Some((size, &digests))))
+    }
+
+    /// 邮件的附件，文件类型按存储中的文件头识别
+    async fn mail_attachments(&self, email_id: &str) -> Result<Vec<Attachment>> {
+        let mail_id = Self::mail_id(email_id)?;
+        let mut attachments = Vec::new();
+        for row in self.attachments.attachments(Some(&[mail_id])).await? {
+            let mut attachment = Self::stored_attachment(row).attachment;
+            self.identify(&mut attachment).await;
+            attachments.push(attachment);
+        }
+        Ok(attachments)
+    }
+
+    /// 查询单个附件记录
+    async fn find_attachment(&self, attachment_id: &str) -> Result<StoredAttachment> {
+        self.attachments
+            .attachment(attachment_id)
+            .await?
+            .map(Self::stored_attachment)
+            .ok_or_else(|| anyhow!("附件未找到"))
+    }
+
+    /// 由入库记录构建附件，文件类型未识别
+    fn stored_attachment(row: MailAttachmentRow) -> StoredAttachment {
+        let file_extension = row.filename.rsplit_once('.').map(|(_, ext)| ext).unwrap_or("").to_string();
+        StoredAttachment {
+            mail_id: row.mail_id.to_string(),
+            attachment: Attachment {
+                id: row.attachment_id,
+                filename: row.filename,
+                file_path: row.file_path,
+                size: row.size,
+                file_extension,
+                detected_type: FileKind::Unknown,
+                extension_mismatch: false,
+                md5: row.hash_md5,
+                sha256: row.hash_sha256,
+            },
+        }
+    }
+
+    /// 按存储中的文件头识别附件类型，文件缺失或读取失败时保持未识别
+    async fn identify(&self, attachment: &mut Attachment) {
+        let detected = match self.blobs.open(&attachment.file_path).await {
+            Ok(Some(mut blob)) => Self::detect_blob_type(blob.reader.as_mut()).await.map_err(Into::into),
+            Ok(None) => return,
+            Err(e) => Err(e),
+        };
+        match detected {
+            Ok(kind) => {
+                attachment.detected_type = kind;
+                attachment.extension_mismatch = kind.extension_mismatch(&attachment.file_extension);
+            }
+            Err(e) => debug!("附件类型识别失败: attachment_id={}, error={}", attachment.id, e),
+        }
+    }
+
+    /// 查询邮件的处置状态与信封信息
+    pub async fn get_delivery_info(&self, email_id: &str) -> Result<MailDeliveryInfo> {
+        info!("邮件服务: 查询邮件投递信息: email_id={}", email_id);
+
+        let row = self
+            .mails
+            .delivery(Self::mail_id(email_id)?)
+            .await?
+            .ok_or_else(|| anyhow!("邮件未找到"))?;
+
+        // 信封收件人可能以逗号或分号分隔多个地址
+        let recipients = row
+            .client_envelope_to_address
+            .split([',', ';'])
+            .map(str::trim)
+            .filter(|address| !address.is_empty())
+            .map(str::to_string)
+            .collect();
+
+        Ok(MailDeliveryInfo {
+            mail_id: email_id.to_string(),
+            status: row.action_name.parse().map_err(|e: String| anyhow!(e))?,
+            envelope_from: row.client_envelope_from_address,
+            recipients,
+        })
+    }
+
+    /// 查询邮件记录的压缩包提取密码
+    async fn fetch_extract_password(&self, email_id: &str) -> Result<Option<String>> {
+        let password = self
+            .mails
+            .extract_password(Self::mail_id(email_id)?)
+            .await?
+            .filter(|password| !password.is_empty());
+
+        Ok(password)
+    }
+
+    /// 打开存储中的附件，读完整个文件时与入库记录比对
+    ///
+    /// 文件缺失或大小与记录不符时记录安全日志并返回`IntegrityError`，没有记录哈希的附件不做校验
+    async fn open_attachment(&self, record: &StoredAttachment) -> Result<StoredBlob> {
+        let attachment = &record.attachment;
+        let sha256 = attachment.sha256.to_lowercase();
+        let Some(Blob { size, reader }) = self.blobs.open(&attachment.file_path).await? else {
+            let check = Self::storage_check(record, None);
+            Self::log_integrity_failure(&check);
+            return Err(Self::integrity_error(&check).into());
+        };
+        if Self::recorded_status(attachment) == IntegrityStatus::Unverified {
+            return Ok(StoredBlob { size, sha256, reader, check: None });
+        }
+        if size != attachment.size {
+            let mut check = Self::storage_check(record, None);
+            check.status = IntegrityStatus::Corrupted;
+            check.actual_size = Some(size);
+            Self::log_integrity_failure(&check);
+            return Err(Self::integrity_error(&check).into());
+        }
+
+        let record = record.clone();
+        let check: DigestCheck = Box::new(move |size, digests| {
+            let check = Self::storage_check(&record, Some((size, &digests)));
+            if check.status == IntegrityStatus::Corrupted {
+                Self::log_integrity_failure(&check);
+                return Err(io::Error::new(io::ErrorKind::InvalidData, Self::integrity_error(&check)));
+            }
+            Ok(())
+        });
+        Ok(StoredBlob { size, sha256, reader, check: Some(check) })
+    }
+
+    /// 将附件完整读入内存并校验完整性，返回（内容, 校验结果）
+    async fn read_attachment(&self, record: &StoredAttachment) -> Result<(Vec<u8>, IntegrityStatus)> {
+        let blob = self.open_attachment(record).await?;
+        if blob.size > MAX_BUFFERED_FILE {
+            return Err(anyhow!("附件过大: {} 字节，超过 {} 字节", blob.size, MAX_BUFFERED_FILE));
+        }
+        let integrity = if blob.check.is_some() { IntegrityStatus::Verified } else { IntegrityStatus::Unverified };
+
+        let mut data = Vec::with_capacity(blob.size as usize);
+        let result = blob
+            .into_verified_reader()
+            .take(MAX_BUFFERED_FILE + 1)
+            .read_to_end(&mut data)
+            .await;
+        if let Err(e) = result {
+            // 校验失败时还原为IntegrityError，由下载接口返回对应的状态
+            return Err(match e.get_ref().and_then(|inner| inner.downcast_ref::<IntegrityError>()) {
+                Some(integrity) => integrity.clone().into(),
+                None => e.into(),
+            });
+        }
+        if data.len() as u64 > MAX_BUFFERED_FILE {
+            return Err(anyhow!("附件过大: 超过 {} 字节", MAX_BUFFERED_FILE));
+        }
+        Ok((data, integrity))
+    }
+
+    /// 入库记录能否用于校验：MD5和SHA256都没有记录时为Unverified
+    fn recorded_status(attachment: &Attachment) -> IntegrityStatus {
+        if attachment.md5.is_empty() && attachment.sha256.is_empty() {
+            IntegrityStatus::Unverified
+        } else {
+            IntegrityStatus::Verified
+        }
+    }
+
+    /// 读取文件头识别类型，之后回到文件开头
+    async fn detect_blob_type(reader: &mut dyn BlobRead) -> io::Result<FileKind> {
+        let mut head = Vec::new();
+        (&mut *reader).take(DETECT_SAMPLE_SIZE).read_to_end(&mut head).await?;
+        reader.rewind().await?;
+        Ok(detect_file_type(&head))
+    }
+
+    /// 按块读取到文件末尾，返回（大小, 哈希）
+    async fn hash_reader(reader: &mut dyn BlobRead) -> io::Result<(u64, FileDigests)> {
+        let mut hasher = DigestHasher::default();
+        let mut buf = vec![0u8; VERIFY_CHUNK_SIZE];
+        loop {
+            let read = reader.read(&mut buf).await?;
+            if read == 0 {
+                break;
+            }
+            hasher.update(&buf[..read]);
+        }
+        Ok((hasher.len(), hasher.finish()))
+    }
+
+    /// 与入库记录比对的结果，`actual`为实际读取的大小和哈希，文件缺失时为None
+    fn storage_check(record: &StoredAttachment, actual: Option<(u64, &FileDigests)>) -> StorageCheck {
+        let attachment = &record.attachment;
+        let mut check = StorageCheck {
+            mail_id: record.mail_id.clone(),
+            attachment_id: attachment.id.clone(),
+            filename: attachment.filename.clone(),
+            file_path: attachment.file_path.clone(),
+            status: IntegrityStatus::Missing,
+            expected_size: attachment.size,
+            actual_size: None,
+            expected_md5: attachment.md5.clone(),
+            actual_md5: None,
+            expected_sha256: attachment.sha256.clone(),
+            actual_sha256: None,
+        };
+        let Some((size, digests)) = actual else {
+            return check;
+        };
+
+        let matches = |expected: &str, actual: &str| expected.is_empty() || expected.eq_ignore_ascii_case(actual);
+        check.status = if Self::recorded_status(attachment) == IntegrityStatus::Unverified {
+            IntegrityStatus::Unverified
+        } else if size == attachment.size
+            && matches(&attachment.md5, &digests.md5)
+            && matches(&attachment.sha256, &digests.sha256)
+        {
+            IntegrityStatus::Verified
+        } else {
+            IntegrityStatus::Corrupted
+        };
+        check.actual_size = Some(size);
+        check.actual_md5 = Some(digests.md5.clone());
+        check.actual_sha256 = Some(digests.sha256.clone());
+        check
+    }
+
+    /// 记录附件损坏或缺失的安全日志
+    fn log_integrity_failure(check: &StorageCheck) {
+        error!(
+            target: "security",
+            "附件完整性校验失败: status={}, mail_id={}, attachment_id={}, file_path={}, \
+             expected_size={}, actual_size={:?}, expected_md5={}, actual_md5={:?}, \
+             expected_sha256={}, actual_sha256={:?}",
+            check.status.as_str(), check.mail_id, check.attachment_id, check.file_path,
+            check.expected_size, check.actual_size, check.expected_md5, check.actual_md5,
+            check.expected_sha256, check.actual_sha256
         );
-        
-        // 返回空列表，表示当前没有实现具体查询
-        Ok((0, vec![]))
     }
-} 
\ No newline at end of file
+
+    /// 校验结果对应的错误
+    fn integrity_error(check: &StorageCheck) -> IntegrityError {
+        IntegrityError {
+            attachment_id: check.attachment_id.clone(),
+            status: check.status,
+        }
+    }
+
+    /// 获取邮件正文的安全预览
+    ///
+    /// 正文经过清洗与链接去武器化，命中的情报值会被高亮
+    pub async fn get_email_preview(&self, email_id: &str) -> Result<EmailPreview> {
+        info!("邮件服务: 获取邮件预览: email_id={}", email_id);
+
+        let mail_id = Self::mail_id(email_id)?;
+        let body = self.mails.mail_body(mail_id).await?.ok_or_else(|| anyhow!("邮件未找到"))?;
+        let values = self.hits.mail_intelligence(mail_id).await?;
+
+        let terms: Vec<IocTerm> = values
+            .into_iter()
+            .map(|row| IocTerm {
+                intelligence_id: row.intelligence_id,
+                attribute: row.attribute,
+                value: row.value,
+            })
+            .collect();
+
+        let rendered = render_preview(&body.html_body, &body.text_body, &terms);
+
+        let highlights = terms
+            .into_iter()
+            .zip(rendered.hit_counts)
+            .map(|(term, hit_count)| IocHighlight {
+                intelligence_id: term.intelligence_id,
+                attribute: term.attribute,
+                value: term.value,
+                hit_count,
+            })
+            .collect();
+
+        Ok(EmailPreview {
+            email_id: email_id.to_string(),
+            subject: body.subject,
+            html: rendered.html,
+            highlights,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::domain::email::EmailStatus;
+    use crate::db::{AttachmentRepository, InMemoryRepository};
+    use crate::services::DisabledDecoder;
+
+    fn service() -> EmailService {
+        let memory = Arc::new(InMemoryRepository::fixture());
+        EmailService::new(
+            memory.clone(),
+            memory.clone(),
+            memory.clone(),
+            memory,
+            "infected".to_string(),
+            Arc::new(DisabledDecoder),
+        )
+    }
+
+    /// 记录送检图片的识别后端，PNG图片解出固定链接
+    #[derive(Default)]
+    struct RecordingDecoder {
+        decoded: std::sync::Mutex<Vec<(FileKind, usize)>>,
+    }
+
+    #[async_trait::async_trait]
+    impl BarcodeDecoder for RecordingDecoder {
+        fn name(&self) -> &'static str {
+            "recording"
+        }
+
+        async fn decode(&self, image: &[u8], kind: FileKind) -> Result<Vec<BarcodeSymbol>> {
+            self.decoded.lock().unwrap().push((kind, image.len()));
+            Ok(match kind {
+                FileKind::Png => vec![BarcodeSymbol {
+                    qr: true,
+                    symbology: "QR-Code".to_string(),
+                    data: "https://login.examp1e.org/verify".to_string(),
+                }],
+                _ => Vec::new(),
+            })
+        }
+    }
+
+    fn integrity_status(e: &anyhow::Error) -> Option<IntegrityStatus> {
+        e.downcast_ref::<IntegrityError>().map(|e| e.status)
+    }
+
+    #[tokio::test]
+    async fn barcodes_are_decoded_from_stored_attachments() {
+        let memory = Arc::new(InMemoryRepository::fixture());
+        let decoder = Arc::new(RecordingDecoder::default());
+        let service = EmailService::new(
+            memory.clone(),
+            memory.clone(),
+            memory.clone(),
+            memory.clone(),
+            "infected".to_string(),
+            decoder.clone(),
+        );
+
+        let email = service.get_email_detail("1").await.unwrap();
+        let url = email
+            .urls
+            .iter()
+            .find(|url| url.source == UrlSource::QrCode)
+            .expect("二维码图片应解出链接");
+        assert_eq!(url.url, "https://login.examp1e.org/verify");
+        assert_eq!(url.attachment_id.as_deref(), Some("att_005"));
+
+        // 只有图片与PDF送检，图片内容来自附件存储
+        let stored = memory.attachment("att_005").await.unwrap().unwrap();
+        let decoded = decoder.decoded.lock().unwrap().clone();
+        assert_eq!(decoded, [(FileKind::Png, stored.size as usize)]);
+
+        // 相同内容的附件使用缓存结果
+        service.get_email_detail("1").await.unwrap();
+        assert_eq!(decoder.decoded.lock().unwrap().len(), 1);
+    }
+
+    fn related(statuses: Vec<EmailStatus>) -> EmailFilter {
+        let end_time = chrono::DateTime::from_timestamp(crate::db::FIXTURE_TIME, 0).unwrap();
+        EmailFilter {
+            start_time: end_time - chrono::Duration::days(30),
+            end_time,
+            intelligence_id: "3f2504e0-4f89-41d3-9a0c-0305e82c3301".to_string(),
+            statuses,
+            page: 1,
+            page_size: 10,
+        }
+    }
+
+    #[tokio::test]
+    async fn related_emails_come_from_hits() {
+        let service = service();
+        let (total, emails) = service.get_related_emails(related(vec![])).await.unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(emails[0].id, "2");
+        assert_eq!(emails[0].status, EmailStatus::Quarantine);
+        assert_eq!(emails[0].subject, "【紧急】账户异常，请立即验证");
+
+        // 状态过滤作用于存储库中的邮件
+        let (total, emails) = service.get_related_emails(related(vec![EmailStatus::Accept])).await.unwrap();
+        assert_eq!(total, 0);
+        assert!(emails.is_empty());
+    }
+
+    #[tokio::test]
+    async fn email_detail_reads_stored_mail() {
+        let service = service();
+        let email = service.get_email_detail("2").await.unwrap();
+        assert_eq!(email.subject, "【紧急】账户异常，请立即验证");
+        assert_eq!(email.sender, "it-support@examp1e.com");
+        assert!(email.urls.iter().all(|url| url.source == UrlSource::EmailBody));
+
+        let error = service.get_email_detail("999").await.unwrap_err();
+        assert!(error.to_string().contains("邮件未找到"), "{}", error);
+        assert!(service.get_email_detail("abc").await.is_err());
+    }
+
+    #[test]
+    fn body_urls_are_normalized_and_deduplicated() {
+        let urls = EmailService::body_urls(
+            "9",
+            "请访问 https://Login.Example.com/verify?id=1。\n或<http://example.org/a>，重复：https://login.example.com/verify?id=1 ftp://x",
+        );
+        let found: Vec<(&str, &str)> = urls.iter().map(|url| (url.id.as_str(), url.path.as_str())).collect();
+        assert_eq!(found, [("9_url_001", "/verify"), ("9_url_002", "/a")]);
+    }
+
+    #[tokio::test]
+    async fn archive_inspection_requires_owning_email() {
+        let service = service();
+        let error = service.inspect_archive_attachment("2", "att_002", None).await.unwrap_err();
+        assert!(error.to_string().contains("附件不属于该邮件"), "{}", error);
+
+        let inspection = service.inspect_archive_attachment("1", "att_002", None).await.unwrap();
+        assert_eq!(inspection.format, FileKind::Zip);
+        assert!(!inspection.entries.is_empty());
+    }
+
+    #[tokio::test]
+    async fn stored_attachment_streams_and_verifies() {
+        let content = service().download_attachment("att_001").await.unwrap();
+        assert_eq!(content.detected_type, FileKind::Pdf);
+        assert_eq!(content.integrity, IntegrityStatus::Verified);
+        let ContentBody::Stored(blob) = content.body else {
+            panic!("附件应从存储流式读取");
+        };
+        let sha256 = blob.sha256.clone();
+        let mut data = Vec::new();
+        blob.into_verified_reader().read_to_end(&mut data).await.unwrap();
+        assert_eq!(compute_digests(&data).sha256, sha256);
+    }
+
+    #[tokio::test]
+    async fn corrupted_and_missing_attachments_are_rejected() {
+        let service = service();
+        let e = service.download_attachment("att_003").await.err().unwrap();
+        assert_eq!(integrity_status(&e), Some(IntegrityStatus::Corrupted));
+        let e = service.download_attachment_safe("att_004").await.err().unwrap();
+        assert_eq!(integrity_status(&e), Some(IntegrityStatus::Missing));
+        assert!(service.download_attachment("att_999").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn storage_check_reports_each_record() {
+        let service = service();
+        let mut statuses = Vec::new();
+        for record in service.attachment_records(None).await.unwrap() {
+            let check = service.check_attachment(&record).await.unwrap();
+            statuses.push((check.attachment_id, check.status));
+        }
+        assert_eq!(
+            statuses,
+            [
+                ("att_001".to_string(), IntegrityStatus::Verified),
+                ("att_002".to_string(), IntegrityStatus::Verified),
+                ("att_005".to_string(), IntegrityStatus::Verified),
+                ("att_004".to_string(), IntegrityStatus::Missing),
+                ("att_003".to_string(), IntegrityStatus::Corrupted),
+            ]
+        );
+    }
+}
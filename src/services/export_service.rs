@@ -0,0 +1,585 @@
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+use chrono::{Duration, Utc};
+use tokio::sync::{mpsc, RwLock};
+use tracing::{info, warn};
+use anyhow::{Result, anyhow};
+use uuid::Uuid;
+
+use crate::content::{ExportFormat, ExportItem, MailExporter};
+use crate::models::domain::email::{Email, EmailSearchCriteria};
+use crate::models::domain::export::{
+    ExportData, ExportError, ExportFile, ExportJob, ExportJobStatus, ExportOutcome, ExportSelection,
+};
+use crate::services::{EmailService, SearchService};
+
+/// 单次导出允许的最大邮件数
+const MAX_EXPORT_EMAILS: usize = 10_000;
+
+/// 按查询条件导出时每页获取的邮件数
+const EXPORT_PAGE_SIZE: u32 = 100;
+
+/// 邮件获取与文件写入之间的缓冲邮件数
+const EXPORT_CHANNEL_CAPACITY: usize = 16;
+
+/// 导出任务及文件的保留时间（小时）
+const EXPORT_RETENTION_HOURS: i64 = 24;
+
+/// 待导出的邮件
+struct ExportTarget {
+    /// 邮件ID
+    mail_id: String,
+    /// 邮件元数据，邮件不存在时为None
+    email: Option<Email>,
+}
+
+/// 把写入的内容逐块发送到响应流，只能在阻塞线程中使用
+struct ChunkWriter(mpsc::Sender<io::Result<Vec<u8>>>);
+
+impl Write for ChunkWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0
+            .blocking_send(Ok(buf.to_vec()))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "下载已中断"))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// 批量导出服务
+#[derive(Clone)]
+pub struct ExportService {
+    /// 邮件服务，用于获取原始邮件
+    email: EmailService,
+    /// 搜索服务，用于按条件或ID查询邮件元数据
+    search: SearchService,
+    /// 导出任务表
+    jobs: Arc<RwLock<HashMap<String, ExportJob>>>,
+    /// 导出文件存放目录
+    export_dir: PathBuf,
+    /// 同步导出的最大邮件数
+    sync_limit: usize,
+}
+
+impl ExportService {
+    /// 创建新的导出服务实例
+    pub fn new(email: EmailService, search: SearchService, export_dir: PathBuf, sync_limit: usize) -> Self {
+        Self {
+            email,
+            search,
+            jobs: Arc::new(RwLock::new(HashMap::new())),
+            export_dir,
+            sync_limit,
+        }
+    }
+
+    /// 导出邮件
+    ///
+    /// 邮件数不超过同步上限时以流的形式直接返回导出文件，否则创建异步任务
+    pub async fn export_emails(
+        &self,
+        selection: ExportSelection,
+        format: ExportFormat,
+        force_async: bool,
+    ) -> Result<ExportOutcome> {
+        let targets = self.resolve_targets(selection).await?;
+        info!(
+            "导出服务: 导出邮件: count={}, format={:?}, force_async={}",
+            targets.len(), format, force_async
+        );
+
+        let filename = format!("emails_{}.{}", Utc::now().format("%Y%m%d%H%M%S"), format.extension());
+
+        if !force_async && targets.len() <= self.sync_limit {
+            let data = self.stream_export(targets, format).await?;
+            return Ok(ExportOutcome::Ready(ExportFile {
+                filename,
+                content_type: format.content_type(),
+                data,
+            }));
+        }
+
+        self.purge_expired_jobs().await;
+
+        let id = Uuid::new_v4().to_string();
+        let job = ExportJob {
+            path: self.export_dir.join(format!("{}.{}", id, format.extension())),
+            id: id.clone(),
+            format,
+            status: ExportJobStatus::Pending,
+            total: targets.len() as u32,
+            processed: 0,
+            failed: 0,
+            created_at: Utc::now(),
+            finished_at: None,
+            error: None,
+            filename,
+        };
+        self.jobs.write().await.insert(id.clone(), job.clone());
+
+        let service = self.clone();
+        tokio::spawn(async move {
+            service.run_job(id, targets).await;
+        });
+
+        Ok(ExportOutcome::Queued(job))
+    }
+
+    /// 查询导出任务
+    pub async fn get_job(&self, job_id: &str) -> Result<ExportJob> {
+        self.jobs
+            .read()
+            .await
+            .get(job_id)
+            .cloned()
+            .ok_or_else(|| anyhow!("导出任务不存在: {}", job_id))
+    }
+
+    /// 获取已完成任务的导出文件
+    pub async fn get_job_file(&self, job_id: &str) -> Result<ExportFile> {
+        let job = self.get_job(job_id).await?;
+        if job.status != ExportJobStatus::Completed {
+            return Err(anyhow!("导出任务尚未完成: status={:?}", job.status));
+        }
+        Ok(ExportFile {
+            filename: job.filename,
+            content_type: job.format.content_type(),
+            data: ExportData::File(job.path),
+        })
+    }
+
+    /// 同步导出：后台逐封写入导出文件，写出的内容逐块发送到响应流，不在内存中保留整个文件
+    ///
+    /// 输出第一块内容之前失败时返回原始错误，由路由层选择状态码；
+    /// 之后失败时响应流以错误结束，客户端不会收到看似完整的残缺文件
+    async fn stream_export(&self, targets: Vec<ExportTarget>, format: ExportFormat) -> Result<ExportData> {
+        let (tx, mut rx) = mpsc::channel::<io::Result<Vec<u8>>>(EXPORT_CHANNEL_CAPACITY);
+        let service = self.clone();
+        let task = tokio::spawn(async move {
+            let writer = ChunkWriter(tx.clone());
+            let result = service.write_export(None, targets, format, writer).await.map(drop);
+            if let Err(e) = &result {
+                warn!("同步导出失败: {:#}", e);
+                let _ = tx.send(Err(io::Error::other(e.to_string()))).await;
+            }
+            result
+        });
+
+        let first = match rx.recv().await {
+            Some(Ok(chunk)) => chunk,
+            // 没有输出任何内容：导出失败，或mbox中的邮件全部获取失败
+            Some(Err(_)) | None => {
+                task.await??;
+                Vec::new()
+            }
+        };
+        Ok(ExportData::Stream { first, rest: rx })
+    }
+
+    /// 执行异步导出任务
+    async fn run_job(&self, job_id: String, targets: Vec<ExportTarget>) {
+        let Ok(job) = self.get_job(&job_id).await else {
+            return;
+        };
+        self.update_job(&job_id, |job| job.status = ExportJobStatus::Running).await;
+
+        let result = async {
+            tokio::fs::create_dir_all(&self.export_dir).await?;
+            // 导出器按邮件缓冲，逐封写入文件
+            let file = std::fs::File::create(&job.path)?;
+            self.write_export(Some(&job_id), targets, job.format, file).await?;
+            Ok::<_, anyhow::Error>(())
+        }
+        .await;
+
+        match result {
+            Ok(()) => {
+                info!("导出任务完成: job_id={}", job_id);
+                self.update_job(&job_id, |job| {
+                    job.status = ExportJobStatus::Completed;
+                    job.finished_at = Some(Utc::now());
+                })
+                .await;
+            }
+            Err(e) => {
+                warn!("导出任务失败: job_id={}, error={:#}", job_id, e);
+                let _ = tokio::fs::remove_file(&job.path).await;
+                self.update_job(&job_id, |job| {
+                    job.status = ExportJobStatus::Failed;
+                    job.finished_at = Some(Utc::now());
+                    job.error = Some(format!("{:#}", e));
+                })
+                .await;
+            }
+        }
+    }
+
+    /// 逐封获取邮件并写入导出文件
+    ///
+    /// 文件写入与压缩在阻塞线程池中进行，通过有界通道接收邮件，内存中只保留少量邮件。
+    /// 原始邮件无法读取时整个导出失败
+    async fn write_export<W>(
+        &self,
+        job_id: Option<&str>,
+        targets: Vec<ExportTarget>,
+        format: ExportFormat,
+        writer: W,
+    ) -> Result<W>
+    where
+        W: Write + Send + 'static,
+    {
+        let (tx, mut rx) = mpsc::channel::<ExportItem>(EXPORT_CHANNEL_CAPACITY);
+        let writer_task = tokio::task::spawn_blocking(move || {
+            let mut exporter = MailExporter::new(format, writer);
+            while let Some(item) = rx.blocking_recv() {
+                exporter.add(&item)?;
+            }
+            exporter.finish()
+        });
+
+        let mut fetch_error = None;
+        for target in targets {
+            let item = match self.fetch_export_item(target).await {
+                Ok(item) => item,
+                Err(e) => {
+                    fetch_error = Some(e);
+                    break;
+                }
+            };
+            let failed = item.error.is_some();
+            // 写入线程出错时通道已关闭，错误在下方统一返回
+            if tx.send(item).await.is_err() {
+                break;
+            }
+            if let Some(job_id) = job_id {
+                self.update_job(job_id, |job| {
+                    job.processed += 1;
+                    if failed {
+                        job.failed += 1;
+                    }
+                })
+                .await;
+            }
+        }
+        drop(tx);
+
+        let result = writer_task.await?;
+        match fetch_error {
+            Some(e) => Err(e),
+            None => result,
+        }
+    }
+
+    /// 读取单封邮件的原始内容
+    ///
+    /// 邮件不存在时只在索引中记录，原始邮件无法读取时返回错误
+    async fn fetch_export_item(&self, target: ExportTarget) -> Result<ExportItem> {
+        let Some(email) = target.email else {
+            warn!("导出邮件不存在: mail_id={}", target.mail_id);
+            return Ok(ExportItem {
+                mail_id: target.mail_id,
+                timestamp: None,
+                sender: String::new(),
+                recipients: Vec::new(),
+                subject: String::new(),
+                data: Vec::new(),
+                error: Some("邮件未找到".to_string()),
+            });
+        };
+        let data = self
+            .email
+            .download_email_eml(&email.id)
+            .await
+            .map_err(|e| e.context(ExportError::RawMailUnavailable(email.id.clone())))?;
+        Ok(ExportItem {
+            mail_id: email.id,
+            timestamp: Some(email.timestamp),
+            sender: email.sender,
+            recipients: email.recipients,
+            subject: email.subject,
+            data,
+            error: None,
+        })
+    }
+
+    /// 解析导出范围，通过邮件搜索得到去重后的邮件及其元数据
+    async fn resolve_targets(&self, selection: ExportSelection) -> Result<Vec<ExportTarget>> {
+        let targets = match selection {
+            ExportSelection::MailIds(ids) => {
+                let mut seen = HashSet::new();
+                let mut mail_ids = Vec::new();
+                for id in ids {
+                    let id = id.trim();
+                    if id.is_empty() {
+                        continue;
+                    }
+                    let id: u64 = id
+                        .parse()
+                        .map_err(|_| ExportError::InvalidRequest(format!("无效的邮件ID: {}", id)))?;
+                    if seen.insert(id) {
+                        mail_ids.push(id);
+                    }
+                }
+                if mail_ids.len() > MAX_EXPORT_EMAILS {
+                    return Err(ExportError::InvalidRequest(format!("导出邮件数超过上限{}，请缩小查询范围", MAX_EXPORT_EMAILS)).into());
+                }
+                self.lookup_mails(mail_ids).await?
+            }
+            ExportSelection::Filter(filter) => {
+                let mut criteria = EmailSearchCriteria {
+                    start_time: Some(filter.start_time),
+                    end_time: Some(filter.end_time),
+                    intelligence_id: Some(filter.intelligence_id),
+                    actions: filter.statuses,
+                    page: 1,
+                    page_size: EXPORT_PAGE_SIZE,
+                    ..EmailSearchCriteria::default()
+                };
+                let mut seen = HashSet::new();
+                let mut targets = Vec::new();
+                loop {
+                    let (total, emails) = self.search.search_emails(criteria.clone()).await?;
+                    if total as usize > MAX_EXPORT_EMAILS {
+                        return Err(ExportError::InvalidRequest(format!("导出邮件数超过上限{}，请缩小查询范围", MAX_EXPORT_EMAILS)).into());
+                    }
+                    if emails.is_empty() {
+                        break;
+                    }
+                    for email in emails {
+                        if seen.insert(email.id.clone()) {
+                            targets.push(ExportTarget {
+                                mail_id: email.id.clone(),
+                                email: Some(email),
+                            });
+                        }
+                    }
+                    if u64::from(criteria.page) * u64::from(criteria.page_size) >= total {
+                        break;
+                    }
+                    criteria.page += 1;
+                }
+                targets
+            }
+        };
+
+        if targets.is_empty() {
+            return Err(ExportError::InvalidRequest("没有需要导出的邮件".to_string()).into());
+        }
+        Ok(targets)
+    }
+
+    /// 按ID分批查询邮件元数据，保持请求中的顺序，不存在的邮件元数据为None
+    async fn lookup_mails(&self, mail_ids: Vec<u64>) -> Result<Vec<ExportTarget>> {
+        let mut found: HashMap<String, Email> = HashMap::new();
+        for chunk in mail_ids.chunks(EXPORT_PAGE_SIZE as usize) {
+            let criteria = EmailSearchCriteria {
+                mail_ids: Some(chunk.to_vec()),
+                page: 1,
+                page_size: EXPORT_PAGE_SIZE,
+                ..EmailSearchCriteria::default()
+            };
+            let (_, emails) = self.search.search_emails(criteria).await?;
+            found.extend(emails.into_iter().map(|email| (email.id.clone(), email)));
+        }
+
+        Ok(mail_ids
+            .into_iter()
+            .map(|mail_id| {
+                let mail_id = mail_id.to_string();
+                ExportTarget {
+                    email: found.remove(&mail_id),
+                    mail_id,
+                }
+            })
+            .collect())
+    }
+
+    /// 更新导出任务
+    async fn update_job(&self, job_id: &str, update: impl FnOnce(&mut ExportJob)) {
+        if let Some(job) = self.jobs.write().await.get_mut(job_id) {
+            update(job);
+        }
+    }
+
+    /// 清理超过保留时间的任务及其导出文件
+    async fn purge_expired_jobs(&self) {
+        let deadline = Utc::now() - Duration::hours(EXPORT_RETENTION_HOURS);
+        let expired: Vec<ExportJob> = {
+            let mut jobs = self.jobs.write().await;
+            let ids: Vec<String> = jobs
+                .values()
+                .filter(|job| job.finished_at.is_some_and(|finished| finished < deadline))
+                .map(|job| job.id.clone())
+                .collect();
+            ids.iter().filter_map(|id| jobs.remove(id)).collect()
+        };
+
+        for job in expired {
+            info!("清理过期导出任务: job_id={}", job.id);
+            let _ = tokio::fs::remove_file(&job.path).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use async_trait::async_trait;
+    use chrono::DateTime;
+    use crate::db::{Blob, BlobStore, DbResult, InMemoryRepository, FIXTURE_TIME};
+    use crate::services::DisabledDecoder;
+
+    /// 原始邮件全部缺失的文件存储
+    struct EmptyBlobs;
+
+    #[async_trait]
+    impl BlobStore for EmptyBlobs {
+        async fn open(&self, _path: &str) -> DbResult<Option<Blob>> {
+            Ok(None)
+        }
+    }
+
+    /// 只有第一次打开的文件可以读取的文件存储
+    struct FirstBlobOnly {
+        memory: InMemoryRepository,
+        opened: std::sync::atomic::AtomicBool,
+    }
+
+    #[async_trait]
+    impl BlobStore for FirstBlobOnly {
+        async fn open(&self, path: &str) -> DbResult<Option<Blob>> {
+            if self.opened.swap(true, std::sync::atomic::Ordering::SeqCst) {
+                return Ok(None);
+            }
+            self.memory.open(path).await
+        }
+    }
+
+    fn service(blobs: Option<Arc<dyn BlobStore>>) -> ExportService {
+        let memory = Arc::new(InMemoryRepository::fixture());
+        let blobs = blobs.unwrap_or_else(|| memory.clone());
+        let email = EmailService::new(
+            memory.clone(),
+            memory.clone(),
+            memory.clone(),
+            blobs,
+            "infected".to_string(),
+            Arc::new(DisabledDecoder),
+        );
+        let export_dir = std::env::temp_dir().join(format!("analysis-api-export-test-{}", Uuid::new_v4()));
+        ExportService::new(email, SearchService::new(memory), export_dir, 20)
+    }
+
+    fn filter(intelligence_id: &str) -> ExportSelection {
+        ExportSelection::Filter(crate::models::domain::email::EmailFilter {
+            start_time: DateTime::from_timestamp(FIXTURE_TIME, 0).unwrap() - Duration::days(365),
+            end_time: DateTime::from_timestamp(FIXTURE_TIME, 0).unwrap(),
+            intelligence_id: intelligence_id.to_string(),
+            statuses: Vec::new(),
+            page: 1,
+            page_size: 10,
+        })
+    }
+
+    /// 读完同步导出的内容流，返回各块内容，流以错误结束时返回错误
+    async fn collect(outcome: ExportOutcome) -> io::Result<Vec<Vec<u8>>> {
+        let ExportOutcome::Ready(ExportFile { data: ExportData::Stream { first, mut rest }, .. }) = outcome else {
+            panic!("应同步返回导出文件");
+        };
+        let mut chunks = vec![first];
+        while let Some(chunk) = rest.recv().await {
+            chunks.push(chunk?);
+        }
+        Ok(chunks)
+    }
+
+    /// 读取同步导出的ZIP，返回（EML文件名列表, CSV索引）
+    async fn read_zip(outcome: ExportOutcome) -> (Vec<String>, String) {
+        let data = collect(outcome).await.unwrap().concat();
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(data)).unwrap();
+        let names = archive.file_names().filter(|name| name.ends_with(".eml")).map(str::to_string).collect();
+        let mut index = String::new();
+        archive.by_name("index.csv").unwrap().read_to_string(&mut index).unwrap();
+        (names, index)
+    }
+
+    #[tokio::test]
+    async fn filter_export_uses_intelligence_hits() {
+        let service = service(None);
+        let outcome = service
+            .export_emails(filter("3f2504e0-4f89-41d3-9a0c-0305e82c3301"), ExportFormat::Zip, false)
+            .await
+            .unwrap();
+        let (names, index) = read_zip(outcome).await;
+        assert_eq!(names.len(), 1);
+        assert_eq!(index.lines().count(), 2);
+        assert!(index.lines().nth(1).unwrap().starts_with("2,"), "{}", index);
+
+        let error = service
+            .export_emails(filter("00000000-0000-0000-0000-000000000000"), ExportFormat::Zip, false)
+            .await
+            .unwrap_err();
+        assert!(matches!(error.downcast_ref::<ExportError>(), Some(ExportError::InvalidRequest(_))));
+    }
+
+    #[tokio::test]
+    async fn unknown_mail_ids_are_recorded_in_index() {
+        let selection = ExportSelection::MailIds(vec!["2".to_string(), "999999".to_string(), " 2 ".to_string()]);
+        let outcome = service(None).export_emails(selection, ExportFormat::Zip, false).await.unwrap();
+        let (names, index) = read_zip(outcome).await;
+        assert_eq!(names.len(), 1);
+        let rows: Vec<&str> = index.lines().skip(1).collect();
+        assert_eq!(rows.len(), 2);
+        assert!(rows[1].starts_with("999999,") && rows[1].contains("failed"), "{}", rows[1]);
+    }
+
+    #[tokio::test]
+    async fn missing_raw_messages_fail_the_export() {
+        let service = service(Some(Arc::new(EmptyBlobs)));
+        let selection = || ExportSelection::MailIds(vec!["2".to_string()]);
+        let error = service.export_emails(selection(), ExportFormat::Mbox, false).await.unwrap_err();
+        assert!(matches!(error.downcast_ref::<ExportError>(), Some(ExportError::RawMailUnavailable(_))), "{}", error);
+
+        let ExportOutcome::Queued(job) = service.export_emails(selection(), ExportFormat::Zip, true).await.unwrap() else {
+            panic!("应创建异步任务");
+        };
+        let job = loop {
+            let job = service.get_job(&job.id).await.unwrap();
+            if job.finished_at.is_some() {
+                break job;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        };
+        assert_eq!(job.status, ExportJobStatus::Failed);
+        assert!(job.error.is_some_and(|error| error.contains("原始邮件不可用")));
+        assert!(!job.path.exists());
+        assert!(service.get_job_file(&job.id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn sync_export_streams_each_mail() {
+        let selection = ExportSelection::MailIds(vec!["2".to_string(), "4".to_string(), "8".to_string()]);
+        let outcome = service(None).export_emails(selection, ExportFormat::Mbox, false).await.unwrap();
+        let chunks = collect(outcome).await.unwrap();
+        assert_eq!(chunks.len(), 3);
+        assert!(chunks.iter().all(|chunk| chunk.starts_with(b"From ")));
+    }
+
+    #[tokio::test]
+    async fn failures_after_output_end_the_stream_with_an_error() {
+        let blobs = FirstBlobOnly {
+            memory: InMemoryRepository::fixture(),
+            opened: std::sync::atomic::AtomicBool::new(false),
+        };
+        let selection = ExportSelection::MailIds(vec!["2".to_string(), "4".to_string()]);
+        let outcome = service(Some(Arc::new(blobs))).export_emails(selection, ExportFormat::Mbox, false).await.unwrap();
+        let error = collect(outcome).await.unwrap_err();
+        assert!(error.to_string().contains("原始邮件不可用"), "{}", error);
+    }
+}
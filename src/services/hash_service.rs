@@ -0,0 +1,193 @@
+use std::sync::Arc;
+use chrono::{DateTime, Utc};
+use tracing::{info, warn};
+use anyhow::Result;
+
+use crate::db::{HashIntelligenceRow, HashMails, IntelligenceHitRepository, MailInfoRepository};
+use crate::models::domain::email::EmailStatus;
+use crate::models::domain::hash::{
+    HashAttachment, HashIntelligence, HashMail, HashMatchSource, HashPivot, HashPivotFilter,
+};
+use crate::services::EmailService;
+use crate::services::aggregation::{named_counts, optional_time};
+
+/// 最多返回的关联邮件数
+pub const MAX_PIVOT_MAILS: u32 = 500;
+
+/// 哈希关联服务
+///
+/// 以MD5、SHA1或SHA256为线索，关联邮件文件哈希相同的邮件、哈希相同的附件及对应的情报，
+/// 并统计首次与最近出现时间和受影响的收件人
+#[derive(Clone)]
+pub struct HashService {
+    /// 邮件信息存储库
+    mails: Arc<dyn MailInfoRepository>,
+    /// 情报命中存储库
+    hits: Arc<dyn IntelligenceHitRepository>,
+    /// 邮件服务，用于查找附件
+    email: EmailService,
+}
+
+impl HashService {
+    /// 创建新的哈希关联服务实例
+    pub fn new(
+        mails: Arc<dyn MailInfoRepository>,
+        hits: Arc<dyn IntelligenceHitRepository>,
+        email: EmailService,
+    ) -> Self {
+        Self { mails, hits, email }
+    }
+
+    /// 按哈希关联邮件、附件与情报
+    pub async fn pivot(&self, mut filter: HashPivotFilter) -> Result<HashPivot> {
+        filter.hash = filter.hash.trim().to_lowercase();
+        filter.limit = filter.limit.clamp(1, MAX_PIVOT_MAILS);
+        info!("哈希服务: 关联查询: kind={:?}, hash={}, limit={}", filter.kind, filter.hash, filter.limit);
+
+        let attachments = self.email.find_attachments_by_hash(filter.kind, &filter.hash).await?;
+        // 附件所属的邮件同样视为关联邮件
+        let attachment_mails: Vec<u64> = attachments
+            .iter()
+            .filter_map(|item| match item.mail_id.parse() {
+                Ok(id) => Some(id),
+                Err(_) => {
+                    warn!("附件所属邮件ID无效: {}", item.mail_id);
+                    None
+                }
+            })
+            .collect();
+
+        let data = self.mails.hash_mails(&filter, &attachment_mails).await?;
+        // SHA1没有对应的情报属性
+        let intelligence = match filter.kind.intelligence_attribute() {
+            Some(attribute) => self.hits.hash_intelligence(attribute, &filter.hash).await?,
+            None => vec![],
+        };
+
+        Ok(Self::build_pivot(filter, data, intelligence, attachments, &attachment_mails))
+    }
+
+    /// 将查询结果整理为关联结果
+    fn build_pivot(
+        filter: HashPivotFilter,
+        data: HashMails,
+        intelligence: Vec<HashIntelligenceRow>,
+        attachments: Vec<HashAttachment>,
+        attachment_mails: &[u64],
+    ) -> HashPivot {
+        let mails = data
+            .mails
+            .into_iter()
+            .map(|row| {
+                let mut matched_by = Vec::new();
+                if row.mail_match != 0 {
+                    matched_by.push(HashMatchSource::Mail);
+                }
+                if attachment_mails.contains(&row.id) {
+                    matched_by.push(HashMatchSource::Attachment);
+                }
+                if row.intel_match != 0 {
+                    matched_by.push(HashMatchSource::Intelligence);
+                }
+                HashMail {
+                    mail_id: row.id.to_string(),
+                    timestamp: DateTime::from_timestamp(i64::from(row.timestamp_secs), 0).unwrap_or_else(Utc::now),
+                    subject: row.subject,
+                    sender: if row.display_from.is_empty() {
+                        row.client_envelope_from_address
+                    } else {
+                        row.display_from
+                    },
+                    recipients: row.recipient_list,
+                    status: EmailStatus::from_stored(row.id, &row.action_name),
+                    matched_by,
+                }
+            })
+            .collect();
+
+        let intelligence = intelligence
+            .into_iter()
+            .map(|row| HashIntelligence {
+                intelligence_id: row.intel_id,
+                attribute: row.attribute_name,
+                intelligence_type: row.type_name,
+                urgency: row.urgency_name,
+                description: row.description_text,
+                threat_actor: row.actor_name,
+                hit_mails: row.hit_mails,
+                first_hit: optional_time(row.first_secs),
+                last_hit: optional_time(row.last_secs),
+            })
+            .collect();
+
+        HashPivot {
+            hash: filter.hash,
+            kind: filter.kind,
+            total_mails: data.summary.count,
+            first_seen: optional_time(data.summary.first_secs),
+            last_seen: optional_time(data.summary.last_secs),
+            mails,
+            attachments,
+            intelligence,
+            recipients: named_counts(data.recipients),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{AttachmentRepository, InMemoryRepository};
+    use crate::models::domain::hash::HashKind;
+    use crate::services::DisabledDecoder;
+
+    fn service(memory: Arc<InMemoryRepository>) -> HashService {
+        let email = EmailService::new(
+            memory.clone(),
+            memory.clone(),
+            memory.clone(),
+            memory.clone(),
+            "infected".to_string(),
+            Arc::new(DisabledDecoder),
+        );
+        HashService::new(memory.clone(), memory, email)
+    }
+
+    #[tokio::test]
+    async fn attachment_hash_matches_stored_records() {
+        let memory = Arc::new(InMemoryRepository::fixture());
+        let row = memory.attachment("att_001").await.unwrap().unwrap();
+        assert_eq!(row.mail_id, 1);
+        let pivot = service(memory)
+            .pivot(HashPivotFilter {
+                hash: row.hash_sha256.to_uppercase(),
+                kind: HashKind::Sha256,
+                limit: 10,
+            })
+            .await
+            .unwrap();
+
+        // 同一份PDF出现在两封邮件中，按入库记录匹配，与存储中的文件是否损坏无关
+        let found: Vec<(&str, &str)> = pivot
+            .attachments
+            .iter()
+            .map(|item| (item.mail_id.as_str(), item.attachment.id.as_str()))
+            .collect();
+        assert_eq!(found, [("1", "att_001"), ("5", "att_003")]);
+        assert!(pivot.mails.iter().all(|mail| mail.matched_by.contains(&HashMatchSource::Attachment)));
+    }
+
+    #[tokio::test]
+    async fn unknown_hash_has_no_attachments() {
+        let pivot = service(Arc::new(InMemoryRepository::fixture()))
+            .pivot(HashPivotFilter {
+                hash: "0".repeat(64),
+                kind: HashKind::Sha256,
+                limit: 10,
+            })
+            .await
+            .unwrap();
+        assert!(pivot.attachments.is_empty());
+        assert!(pivot.mails.is_empty());
+    }
+}
@@ -1,9 +1,10 @@
 use std::sync::Arc;
+use anyhow::Result;
 use chrono::{Utc, Duration};
 use tracing::{info, instrument};
 use uuid::Uuid;
 
-use crate::db::{ClickHouseClient, DbResult};
+use crate::db::IntelligenceHitRepository;
 use crate::models::domain::intelligence::{
     Intelligence, IntelligenceFilter, IntelligenceStatus,
     BasicInfo, IndustryDistribution, IntelligenceType
@@ -12,51 +13,26 @@ use crate::models::domain::intelligence::{
 /// 情报服务
 #[derive(Clone)]
 pub struct IntelligenceService {
-    /// ClickHouse客户端，可选（可能运行在内存模式）
-    db_client: Option<Arc<ClickHouseClient>>,
+    /// 情报命中存储库
+    hits: Arc<dyn IntelligenceHitRepository>,
 }
 
 impl IntelligenceService {
     /// 创建新的情报服务实例
-    pub fn new(db_client: Option<Arc<ClickHouseClient>>) -> Self {
-        Self { db_client }
+    pub fn new(hits: Arc<dyn IntelligenceHitRepository>) -> Self {
+        Self { hits }
     }
 
     /// 查询情报列表
     #[instrument(skip(self))]
-    pub async fn list_intelligence(&self, filter: IntelligenceFilter) -> (u64, Vec<Intelligence>) {
+    pub async fn list_intelligence(&self, filter: IntelligenceFilter) -> Result<(u64, Vec<Intelligence>)> {
         info!(
             "情报服务: 查询情报列表，过滤条件: start_time={:?}, end_time={:?}, 分页: {}-{}",
             filter.start_time, filter.end_time, 
             (filter.page - 1) * filter.page_size, filter.page_size
         );
 
-        // 如果有数据库连接，记录一条日志
-        if let Some(_client) = &self.db_client {
-            info!("有数据库连接，但当前使用模拟数据 - 真实查询将在后续实现");
-            // 后续可以在这里实现从数据库获取数据的逻辑
-        } else {
-            info!("无数据库连接，使用模拟数据");
-        }
-
-        // 返回模拟数据
-        (0, vec![]) // 返回空列表，表示暂无数据
-    }
-
-    // 保留此方法框架，供后续实现
-    #[allow(dead_code)]
-    async fn fetch_intelligence_from_db(
-        &self,
-        _client: &ClickHouseClient,
-        filter: &IntelligenceFilter,
-    ) -> DbResult<(u64, Vec<Intelligence>)> {
-        info!(
-            "数据库查询框架已准备，但具体查询暂未实现。查询范围: {:?} 到 {:?}",
-            filter.start_time, filter.end_time
-        );
-        
-        // 返回空列表，表示当前没有实现具体查询
-        Ok((0, vec![]))
+        self.hits.list_intelligence(&filter).await
     }
 
     /// 生成模拟情报数据 - 仅用于开发测试
@@ -0,0 +1,322 @@
+//! 联防联控信息解析
+//!
+//! alert_intelligence表的joint_prevention_and_control字段是情报平台下发的JSON，
+//! 记录命中该情报的单位、归属行业和命中数量。不同版本的字段命名不统一（snake_case或camelCase，
+//! 行业可能是代码、名称或对象），这里统一解析为单位记录，行业按GB/T 4754-2017归入门类
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde_json::Value;
+
+use crate::models::domain::spread::Industry;
+
+/// GB/T 4754-2017门类：代码、名称、包含的大类代码范围
+const SECTIONS: &[(&str, &str, u8, u8)] = &[
+    ("A", "农、林、牧、渔业", 1, 5),
+    ("B", "采矿业", 6, 12),
+    ("C", "制造业", 13, 43),
+    ("D", "电力、热力、燃气及水生产和供应业", 44, 46),
+    ("E", "建筑业", 47, 50),
+    ("F", "批发和零售业", 51, 52),
+    ("G", "交通运输、仓储和邮政业", 53, 60),
+    ("H", "住宿和餐饮业", 61, 62),
+    ("I", "信息传输、软件和信息技术服务业", 63, 65),
+    ("J", "金融业", 66, 69),
+    ("K", "房地产业", 70, 70),
+    ("L", "租赁和商务服务业", 71, 72),
+    ("M", "科学研究和技术服务业", 73, 75),
+    ("N", "水利、环境和公共设施管理业", 76, 79),
+    ("O", "居民服务、修理和其他服务业", 80, 82),
+    ("P", "教育", 83, 83),
+    ("Q", "卫生和社会工作", 84, 85),
+    ("R", "文化、体育和娱乐业", 86, 90),
+    ("S", "公共管理、社会保障和社会组织", 91, 96),
+    ("T", "国际组织", 97, 97),
+];
+
+/// 包裹单位列表的字段名
+const LIST_KEYS: &[&str] = &["units", "records", "list", "data", "items"];
+
+/// 一条联防联控记录
+#[derive(Debug, Clone)]
+pub(crate) struct JointRecord {
+    /// 命中单位名称
+    pub unit_name: String,
+    /// 单位的行业，代码为大类或门类
+    pub industry: Industry,
+    /// 单位所属门类
+    pub section: Industry,
+    /// 命中数量（累计值），未提供时为None
+    pub hit_count: Option<u64>,
+    /// 单位首次命中时间
+    pub first_hit_time: Option<DateTime<Utc>>,
+    /// 单位最近命中时间
+    pub last_hit_time: Option<DateTime<Utc>>,
+}
+
+/// 解析联防联控JSON，无法解析或没有单位名称的记录会被跳过
+pub(crate) fn parse_joint_prevention(text: &str) -> Vec<JointRecord> {
+    let Ok(value) = serde_json::from_str::<Value>(text.trim()) else {
+        return Vec::new();
+    };
+    let records = match value {
+        Value::Array(records) => records,
+        Value::Object(ref object) => match object
+            .iter()
+            .find(|(key, value)| LIST_KEYS.contains(&normalize_key(key).as_str()) && value.is_array())
+        {
+            Some((_, Value::Array(records))) => records.clone(),
+            _ => vec![value],
+        },
+        _ => return Vec::new(),
+    };
+    records.iter().filter_map(parse_record).collect()
+}
+
+fn parse_record(record: &Value) -> Option<JointRecord> {
+    let object = record.as_object()?;
+    let field = |names: &[&str]| {
+        object
+            .iter()
+            .find(|(key, _)| names.contains(&normalize_key(key).as_str()))
+            .map(|(_, value)| value)
+    };
+
+    let unit_name = field(&["unitname", "unit", "hitunit", "name", "orgname", "organization"])
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|name| !name.is_empty())?
+        .to_string();
+
+    // 行业可以是代码、名称、"代码 名称"或{code, name}对象，代码和名称也可能分成两个字段
+    let mut code = field(&["industrycode"]).and_then(text_value);
+    let mut name = field(&["industryname"]).and_then(text_value);
+    match field(&["industry"]) {
+        Some(Value::Object(industry)) => {
+            code = code.or_else(|| industry.get("code").and_then(text_value));
+            name = name.or_else(|| industry.get("name").and_then(text_value));
+        }
+        Some(value) => {
+            if let Some(text) = text_value(value) {
+                let (prefix, rest) = split_code(&text);
+                code = code.or(prefix);
+                name = name.or(rest);
+            }
+        }
+        None => {}
+    }
+    let (industry, section) = resolve_industry(code.as_deref(), name.as_deref());
+
+    Some(JointRecord {
+        unit_name,
+        industry,
+        section,
+        hit_count: field(&["hitcount", "count", "hits", "hitnum"]).and_then(count_value),
+        first_hit_time: field(&["firsthittime", "firsttime", "firstseen"]).and_then(time_value),
+        last_hit_time: field(&["lasthittime", "lasttime", "lastseen", "hittime"]).and_then(time_value),
+    })
+}
+
+/// 解析行业，返回单位的行业与所属门类
+///
+/// 代码优先：大类代码（两位数字，可带门类字母）归入对应门类，门类代码直接查表；
+/// 只有名称时按门类名称匹配，"金融行业"这类俗称去掉"行业"后按包含关系匹配
+/// 大类没有名称时使用所属门类的名称
+pub(crate) fn resolve_industry(code: Option<&str>, name: Option<&str>) -> (Industry, Industry) {
+    let name = name.map(str::trim).filter(|name| !name.is_empty());
+    let code = code.map(|code| code.trim().to_ascii_uppercase()).filter(|code| !code.is_empty());
+
+    let section = code
+        .as_deref()
+        .and_then(section_by_code)
+        .or_else(|| name.and_then(section_by_name));
+    let Some((section_code, section_name)) = section else {
+        let unknown = Industry {
+            code: String::new(),
+            name: name.unwrap_or("未知行业").to_string(),
+        };
+        return (unknown.clone(), unknown);
+    };
+
+    // 大类代码统一补上门类字母，如66补为J66
+    let code = match code {
+        Some(code) if code.chars().all(|c| c.is_ascii_digit()) => format!("{}{}", section_code, code),
+        Some(code) if section_by_code(&code).is_some() => code,
+        _ => section_code.to_string(),
+    };
+    let industry = Industry {
+        name: name
+            .filter(|_| code != section_code)
+            .unwrap_or(section_name)
+            .to_string(),
+        code,
+    };
+    let section = Industry {
+        code: section_code.to_string(),
+        name: section_name.to_string(),
+    };
+    (industry, section)
+}
+
+/// 按门类代码或大类代码查找门类
+fn section_by_code(code: &str) -> Option<(&'static str, &'static str)> {
+    let letter = code.chars().next().filter(char::is_ascii_alphabetic);
+    let digits = &code[letter.map_or(0, char::len_utf8)..];
+    let division = if digits.is_empty() {
+        None
+    } else {
+        // 只取前两位大类代码，中类、小类代码更长
+        Some(digits.get(..2)?.parse::<u8>().ok()?)
+    };
+
+    let section = SECTIONS.iter().find(|(section, _, first, last)| match (letter, division) {
+        (Some(letter), None) => section.starts_with(letter),
+        (_, Some(division)) => (*first..=*last).contains(&division),
+        (None, None) => false,
+    })?;
+    // 字母与大类代码矛盾时视为无效
+    if letter.is_some_and(|letter| !section.0.starts_with(letter)) {
+        return None;
+    }
+    Some((section.0, section.1))
+}
+
+/// 按名称查找门类
+fn section_by_name(name: &str) -> Option<(&'static str, &'static str)> {
+    let term = name.trim_end_matches("行业").trim_end_matches('业');
+    SECTIONS
+        .iter()
+        .find(|(_, section, _, _)| *section == name)
+        .or_else(|| {
+            (term.chars().count() >= 2)
+                .then(|| SECTIONS.iter().find(|(_, section, _, _)| section.contains(term)))
+                .flatten()
+        })
+        .map(|(code, section, _, _)| (*code, *section))
+}
+
+/// 拆分"J66 货币金融服务"、"J-金融业"这类代码在前的行业文本
+fn split_code(text: &str) -> (Option<String>, Option<String>) {
+    let bytes = text.as_bytes();
+    let mut end = usize::from(bytes.first().is_some_and(u8::is_ascii_alphabetic));
+    while bytes.get(end).is_some_and(u8::is_ascii_digit) {
+        end += 1;
+    }
+    // 代码后必须是结尾或分隔符，避免把英文名称的首字母当成门类代码
+    if end == 0 || bytes.get(end).is_some_and(u8::is_ascii_alphanumeric) {
+        return (None, Some(text.to_string()));
+    }
+    let rest = text[end..].trim_start_matches([' ', '-', '_', ':', '：', '　']).trim();
+    (Some(text[..end].to_string()), (!rest.is_empty()).then(|| rest.to_string()))
+}
+
+/// 字段名去掉下划线并转小写，兼容snake_case与camelCase
+fn normalize_key(key: &str) -> String {
+    key.chars().filter(|c| *c != '_' && *c != '-').flat_map(char::to_lowercase).collect()
+}
+
+fn text_value(value: &Value) -> Option<String> {
+    match value {
+        Value::String(text) => Some(text.trim().to_string()).filter(|text| !text.is_empty()),
+        Value::Number(number) => Some(number.to_string()),
+        _ => None,
+    }
+}
+
+fn count_value(value: &Value) -> Option<u64> {
+    match value {
+        Value::Number(number) => number.as_u64(),
+        Value::String(text) => text.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// 时间可以是RFC 3339、"YYYY-MM-DD HH:MM:SS"（UTC）或Unix时间戳（秒或毫秒）
+fn time_value(value: &Value) -> Option<DateTime<Utc>> {
+    let from_number = |number: i64| {
+        let secs = if number > 100_000_000_000 { number / 1000 } else { number };
+        DateTime::from_timestamp(secs, 0).filter(|_| secs > 0)
+    };
+    match value {
+        Value::Number(number) => from_number(number.as_i64()?),
+        Value::String(text) => {
+            let text = text.trim();
+            DateTime::parse_from_rfc3339(text)
+                .map(|time| time.with_timezone(&Utc))
+                .ok()
+                .or_else(|| {
+                    NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S")
+                        .ok()
+                        .map(|time| time.and_utc())
+                })
+                .or_else(|| text.parse().ok().and_then(from_number))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_list_shapes_and_field_names() {
+        let wrapped = parse_joint_prevention(
+            r#"{"records": [{"unitName": "某银行", "industry_code": "66", "hitCount": "4",
+                "firstHitTime": 1748822400000, "last_hit_time": "2025-06-03T08:00:00+08:00"}]}"#,
+        );
+        assert_eq!(wrapped.len(), 1);
+        let record = &wrapped[0];
+        assert_eq!(record.unit_name, "某银行");
+        assert_eq!(record.industry.code, "J66");
+        assert_eq!(record.section.code, "J");
+        assert_eq!(record.hit_count, Some(4));
+        assert_eq!(record.first_hit_time.map(|time| time.timestamp()), Some(1_748_822_400));
+        assert_eq!(record.last_hit_time.map(|time| time.timestamp()), Some(1_748_908_800));
+
+        // 顶层数组，以及单个对象
+        assert_eq!(parse_joint_prevention(r#"[{"unit": "甲"}, {"unit": "乙"}]"#).len(), 2);
+        assert_eq!(parse_joint_prevention(r#"{"unit_name": "甲"}"#).len(), 1);
+    }
+
+    #[test]
+    fn skips_invalid_records() {
+        assert!(parse_joint_prevention("").is_empty());
+        assert!(parse_joint_prevention("not json").is_empty());
+        assert!(parse_joint_prevention("42").is_empty());
+        let records = parse_joint_prevention(r#"[{"unit_name": "  "}, {"industry": "J"}, "text", {"unit_name": "甲"}]"#);
+        let names: Vec<&str> = records.iter().map(|record| record.unit_name.as_str()).collect();
+        assert_eq!(names, ["甲"]);
+    }
+
+    #[test]
+    fn resolves_industry_forms() {
+        let cases: &[(Option<&str>, Option<&str>, &str, &str)] = &[
+            // 大类代码补上门类字母
+            (Some("66"), None, "J66", "J"),
+            (Some("j66"), Some("货币金融服务"), "J66", "J"),
+            // 门类代码
+            (Some("P"), None, "P", "P"),
+            // 只有名称，俗称按包含关系匹配
+            (None, Some("教育"), "P", "P"),
+            (None, Some("金融行业"), "J", "J"),
+            // 字母与大类矛盾、无法识别
+            (Some("A66"), None, "", ""),
+            (None, Some("航天"), "", ""),
+        ];
+        for (code, name, industry, section) in cases {
+            let (resolved, resolved_section) = resolve_industry(*code, *name);
+            assert_eq!(resolved.code, *industry, "{:?} {:?}", code, name);
+            assert_eq!(resolved_section.code, *section, "{:?} {:?}", code, name);
+        }
+        assert_eq!(resolve_industry(Some("66"), None).0.name, "金融业");
+        assert_eq!(resolve_industry(None, Some("航天")).0.name, "航天");
+    }
+
+    #[test]
+    fn splits_code_prefixed_industry() {
+        assert_eq!(split_code("J66 货币金融服务"), (Some("J66".to_string()), Some("货币金融服务".to_string())));
+        assert_eq!(split_code("J-金融业"), (Some("J".to_string()), Some("金融业".to_string())));
+        assert_eq!(split_code("Finance"), (None, Some("Finance".to_string())));
+        assert_eq!(split_code("66"), (Some("66".to_string()), None));
+    }
+}
@@ -0,0 +1,265 @@
+//! 隔离邮件处置后端
+//!
+//! 后端负责实际的投递与删除，服务层只负责状态流转与审计。
+//! 提供落盘（file-drop）和SMTP中继两种实现，均可指向本地替身环境（如本地目录或MailHog）
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::{Result, anyhow, bail};
+use async_trait::async_trait;
+use serde::Serialize;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tracing::{debug, info};
+
+use crate::models::domain::quarantine::QuarantinedMessage;
+
+/// 隔离邮件处置后端
+#[async_trait]
+pub trait MailActionBackend: Send + Sync {
+    /// 后端名称，记录在审计信息中
+    fn name(&self) -> &'static str;
+
+    /// 将邮件投递给指定收件人
+    async fn deliver(&self, message: &QuarantinedMessage, recipients: &[String]) -> Result<()>;
+
+    /// 从隔离区删除邮件
+    async fn discard(&self, message: &QuarantinedMessage) -> Result<()>;
+}
+
+/// 落盘投递的信封信息
+#[derive(Debug, Serialize)]
+struct DropEnvelope<'a> {
+    action_id: &'a str,
+    mail_id: &'a str,
+    operation: &'static str,
+    envelope_from: &'a str,
+    recipients: &'a [String],
+}
+
+/// 落盘后端
+///
+/// 投递时写入`<处置ID>.eml`和信封`<处置ID>.json`，删除时写入`<处置ID>.delete.json`，
+/// 由下游投递代理扫描目录处理。文件先写临时文件再重命名，避免被读到半个文件
+pub struct FileDropBackend {
+    /// 落盘目录
+    dir: PathBuf,
+}
+
+impl FileDropBackend {
+    /// 创建落盘后端
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// 原子写入文件
+    async fn write_atomic(&self, name: &str, data: &[u8]) -> Result<PathBuf> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+        let path = self.dir.join(name);
+        let tmp = self.dir.join(format!("{}.tmp", name));
+        tokio::fs::write(&tmp, data).await?;
+        tokio::fs::rename(&tmp, &path).await?;
+        Ok(path)
+    }
+}
+
+#[async_trait]
+impl MailActionBackend for FileDropBackend {
+    fn name(&self) -> &'static str {
+        "file-drop"
+    }
+
+    async fn deliver(&self, message: &QuarantinedMessage, recipients: &[String]) -> Result<()> {
+        let envelope = DropEnvelope {
+            action_id: &message.action_id,
+            mail_id: &message.mail_id,
+            operation: "deliver",
+            envelope_from: &message.envelope_from,
+            recipients,
+        };
+        // 先写邮件再写信封，下游以信封文件作为投递信号
+        self.write_atomic(&format!("{}.eml", message.action_id), &message.data).await?;
+        let path = self
+            .write_atomic(&format!("{}.json", message.action_id), &serde_json::to_vec_pretty(&envelope)?)
+            .await?;
+        info!("落盘投递: action_id={}, path={}", message.action_id, path.display());
+        Ok(())
+    }
+
+    async fn discard(&self, message: &QuarantinedMessage) -> Result<()> {
+        let envelope = DropEnvelope {
+            action_id: &message.action_id,
+            mail_id: &message.mail_id,
+            operation: "delete",
+            envelope_from: &message.envelope_from,
+            recipients: &[],
+        };
+        let path = self
+            .write_atomic(&format!("{}.delete.json", message.action_id), &serde_json::to_vec_pretty(&envelope)?)
+            .await?;
+        info!("落盘删除请求: action_id={}, path={}", message.action_id, path.display());
+        Ok(())
+    }
+}
+
+/// SMTP中继后端
+///
+/// 以明文SMTP将邮件交给中继投递，适用于内网中继或本地替身。
+/// SMTP没有删除通道，删除只记录状态，不做任何投递
+pub struct SmtpRelayBackend {
+    /// 中继地址
+    host: String,
+    /// 中继端口
+    port: u16,
+    /// EHLO使用的主机名
+    helo: String,
+    /// 单次读写超时
+    timeout: Duration,
+}
+
+impl SmtpRelayBackend {
+    /// 创建SMTP中继后端
+    pub fn new(host: String, port: u16) -> Self {
+        Self {
+            host,
+            port,
+            helo: "analysis-api.localdomain".to_string(),
+            timeout: Duration::from_secs(30),
+        }
+    }
+
+    /// 执行一次完整的SMTP会话
+    async fn send(&self, from: &str, recipients: &[String], data: &[u8]) -> Result<()> {
+        let stream = tokio::time::timeout(self.timeout, TcpStream::connect((self.host.as_str(), self.port)))
+            .await
+            .map_err(|_| anyhow!("连接SMTP中继超时: {}:{}", self.host, self.port))??;
+        let (reader, mut writer) = stream.into_split();
+        let mut session = SmtpSession {
+            reader: BufReader::new(reader),
+            timeout: self.timeout,
+        };
+
+        session.expect(220).await?;
+        session.command(&mut writer, &format!("EHLO {}", self.helo), 250).await?;
+        session.command(&mut writer, &format!("MAIL FROM:<{}>", from), 250).await?;
+        for recipient in recipients {
+            session.command(&mut writer, &format!("RCPT TO:<{}>", recipient), 250).await?;
+        }
+        session.command(&mut writer, "DATA", 354).await?;
+        writer.write_all(&dot_stuff(data)).await?;
+        session.command(&mut writer, ".", 250).await?;
+        // QUIT失败不影响已受理的投递
+        let _ = session.command(&mut writer, "QUIT", 221).await;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl MailActionBackend for SmtpRelayBackend {
+    fn name(&self) -> &'static str {
+        "smtp-relay"
+    }
+
+    async fn deliver(&self, message: &QuarantinedMessage, recipients: &[String]) -> Result<()> {
+        // 信封发件人允许为空（退信地址<>），收件人不能为空
+        validate_address(&message.envelope_from, true)?;
+        for recipient in recipients {
+            validate_address(recipient, false)?;
+        }
+        self.send(&message.envelope_from, recipients, &message.data).await?;
+        info!(
+            "SMTP中继投递: action_id={}, relay={}:{}, recipients={}",
+            message.action_id, self.host, self.port, recipients.len()
+        );
+        Ok(())
+    }
+
+    async fn discard(&self, message: &QuarantinedMessage) -> Result<()> {
+        info!("SMTP中继无删除通道，仅记录删除: action_id={}", message.action_id);
+        Ok(())
+    }
+}
+
+/// SMTP会话的应答读取
+struct SmtpSession<R> {
+    reader: BufReader<R>,
+    timeout: Duration,
+}
+
+impl<R: tokio::io::AsyncRead + Unpin> SmtpSession<R> {
+    /// 发送命令并校验应答码
+    async fn command<W: tokio::io::AsyncWrite + Unpin>(
+        &mut self,
+        writer: &mut W,
+        command: &str,
+        expected: u16,
+    ) -> Result<()> {
+        debug!("SMTP >> {}", command);
+        writer.write_all(command.as_bytes()).await?;
+        writer.write_all(b"\r\n").await?;
+        writer.flush().await?;
+        self.expect(expected).await
+    }
+
+    /// 读取一个（可能多行的）应答，应答码与预期不同时报错
+    ///
+    /// 预期250时也接受251（收件人将被转发）
+    async fn expect(&mut self, expected: u16) -> Result<()> {
+        let mut lines = Vec::new();
+        loop {
+            let mut line = String::new();
+            let read = tokio::time::timeout(self.timeout, self.reader.read_line(&mut line))
+                .await
+                .map_err(|_| anyhow!("等待SMTP应答超时"))??;
+            if read == 0 {
+                bail!("SMTP连接被关闭");
+            }
+            let line = line.trim_end().to_string();
+            debug!("SMTP << {}", line);
+            let last = line.as_bytes().get(3) != Some(&b'-');
+            lines.push(line);
+            if last {
+                break;
+            }
+        }
+
+        let reply = lines.join(" | ");
+        let code: u16 = reply
+            .get(..3)
+            .and_then(|code| code.parse().ok())
+            .ok_or_else(|| anyhow!("无法解析SMTP应答: {}", reply))?;
+        if code == expected || (expected == 250 && code == 251) {
+            Ok(())
+        } else {
+            bail!("SMTP应答异常，期望{}，实际: {}", expected, reply)
+        }
+    }
+}
+
+/// 邮件地址不能包含换行或尖括号，防止SMTP命令注入
+fn validate_address(address: &str, allow_empty: bool) -> Result<()> {
+    if (address.is_empty() && !allow_empty) || address.contains(['\r', '\n', '<', '>']) {
+        bail!("无效的邮件地址: {:?}", address);
+    }
+    Ok(())
+}
+
+/// 将邮件转换为DATA阶段的传输格式：统一CRLF，行首的点加倍
+fn dot_stuff(data: &[u8]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(data.len() + 64);
+    for line in data.split(|&b| b == b'\n') {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        if line.starts_with(b".") {
+            output.push(b'.');
+        }
+        output.extend_from_slice(line);
+        output.extend_from_slice(b"\r\n");
+    }
+    // split会在末尾换行后多出一个空行
+    if data.ends_with(b"\n") {
+        output.truncate(output.len() - 2);
+    }
+    output
+}
+
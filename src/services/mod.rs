@@ -5,15 +5,42 @@ pub mod statistics_service;
 pub mod email_service;
 pub mod intelligence_service;
 pub mod timeline_service;
+pub mod spread_service;
+pub mod export_service;
+pub mod search_service;
+pub mod mail_action_backend;
+pub mod barcode_decoder;
+pub mod quarantine_service;
+pub mod recipient_service;
+pub mod sender_service;
+pub mod hash_service;
+pub mod thread_service;
+pub mod campaign_service;
+pub mod storage_service;
+pub(crate) mod aggregation;
+pub(crate) mod joint_prevention;
 
 // 公开服务结构体
 pub use statistics_service::StatisticsService;
 pub use email_service::EmailService;
 pub use intelligence_service::IntelligenceService;
 pub use timeline_service::TimelineService;
+pub use spread_service::SpreadService;
+pub use export_service::ExportService;
+pub use search_service::SearchService;
+pub use mail_action_backend::{MailActionBackend, FileDropBackend, SmtpRelayBackend};
+pub use barcode_decoder::{BarcodeDecoder, BarcodeSymbol, DisabledDecoder, ZbarDecoder};
+pub use quarantine_service::QuarantineService;
+pub use recipient_service::RecipientService;
+pub use sender_service::SenderService;
+pub use hash_service::HashService;
+pub use thread_service::ThreadService;
+pub use campaign_service::CampaignService;
+pub use storage_service::StorageService;
 
 use std::sync::Arc;
-use crate::db::ClickHouseClient;
+use crate::config::{AppConfig, BarcodeDecoderConfig, MailActionBackendConfig};
+use crate::db::Repositories;
 
 // 服务集合结构体，用于依赖注入
 #[derive(Clone)]
@@ -22,15 +49,66 @@ pub struct AppServices {
     pub email: EmailService,
     pub intelligence: IntelligenceService,
     pub timeline: TimelineService,
+    pub spread: SpreadService,
+    pub export: ExportService,
+    pub search: SearchService,
+    pub quarantine: QuarantineService,
+    pub recipient: RecipientService,
+    pub sender: SenderService,
+    pub hash: HashService,
+    pub thread: ThreadService,
+    pub campaign: CampaignService,
+    pub storage: StorageService,
 }
 
 impl AppServices {
-    pub fn new(db_client: Option<Arc<ClickHouseClient>>) -> Self {
+    pub fn new(repositories: Repositories, config: &AppConfig) -> Self {
+        let barcode: Arc<dyn BarcodeDecoder> = match &config.barcode_decoder {
+            BarcodeDecoderConfig::Disabled => Arc::new(DisabledDecoder),
+            BarcodeDecoderConfig::Zbar { program } => Arc::new(ZbarDecoder::new(program.clone())),
+        };
+        let Repositories { hits, mails, dispositions, statistics, attachments, quarantine, blobs } = repositories;
+        let email = EmailService::new(
+            mails.clone(),
+            hits.clone(),
+            attachments.clone(),
+            blobs,
+            config.safe_download_password.clone(),
+            barcode,
+        );
+        let backend: Arc<dyn MailActionBackend> = match &config.mail_action_backend {
+            MailActionBackendConfig::FileDrop { dir } => Arc::new(FileDropBackend::new(dir.clone())),
+            MailActionBackendConfig::SmtpRelay { host, port } => {
+                Arc::new(SmtpRelayBackend::new(host.clone(), *port))
+            }
+        };
+        let search = SearchService::new(mails.clone());
         Self {
-            statistics: StatisticsService::new(db_client.clone()),
-            email: EmailService::new(db_client.clone()),
-            intelligence: IntelligenceService::new(db_client.clone()),
-            timeline: TimelineService::new(db_client.clone()),
+            statistics: StatisticsService::new(statistics.clone()),
+            export: ExportService::new(
+                email.clone(),
+                search.clone(),
+                config.export_dir.clone(),
+                config.export_sync_limit,
+            ),
+            quarantine: QuarantineService::new(
+                email.clone(),
+                backend,
+                quarantine,
+                config.quarantine_four_eyes,
+                &config.quarantine_operators,
+            ),
+            hash: HashService::new(mails.clone(), hits.clone(), email.clone()),
+            thread: ThreadService::new(mails.clone(), email.clone()),
+            storage: StorageService::new(email.clone()),
+            email,
+            intelligence: IntelligenceService::new(hits.clone()),
+            timeline: TimelineService::new(hits.clone(), dispositions),
+            spread: SpreadService::new(hits.clone()),
+            search,
+            recipient: RecipientService::new(statistics.clone()),
+            sender: SenderService::new(statistics),
+            campaign: CampaignService::new(mails, hits, attachments),
         }
     }
 } 
\ No newline at end of file
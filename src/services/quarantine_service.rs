@@ -0,0 +1,616 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use anyhow::anyhow;
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::config::QuarantineOperatorConfig;
+use crate::db::{DbUnavailable, QuarantineActionRow, QuarantineAuditRow, QuarantineRepository};
+use crate::models::domain::email::EmailStatus;
+use crate::models::domain::quarantine::{
+    AuditEntry, AuditEvent, Operator, QuarantineAction, QuarantineActionKind, QuarantineActionState,
+    QuarantineError, QuarantinedMessage,
+};
+use crate::services::EmailService;
+use crate::services::mail_action_backend::MailActionBackend;
+
+/// 系统自动操作时记录的操作人
+const SYSTEM_ACTOR: &str = "system";
+
+/// 隔离邮件处置请求参数
+#[derive(Debug, Clone)]
+pub struct QuarantineActionRequest {
+    /// 邮件ID
+    pub mail_id: String,
+    /// 处置类型
+    pub kind: QuarantineActionKind,
+    /// 处置原因
+    pub reason: String,
+    /// 重新投递的收件人，为空时使用原收件人
+    pub recipients: Vec<String>,
+}
+
+/// 隔离邮件处置服务
+///
+/// 负责处置请求的状态流转、双人审批与审计，实际投递与删除交给`MailActionBackend`。
+/// 处置请求与审计记录保存在`QuarantineRepository`中，服务重启后已完成的处置仍然生效
+#[derive(Clone)]
+pub struct QuarantineService {
+    /// 邮件服务，用于获取邮件状态与原始内容
+    email: EmailService,
+    /// 处置后端
+    backend: Arc<dyn MailActionBackend>,
+    /// 处置请求与审计记录存储库
+    repository: Arc<dyn QuarantineRepository>,
+    /// 是否需要双人审批
+    four_eyes: bool,
+    /// 操作人令牌的SHA256到操作人名称
+    operators: Arc<HashMap<String, String>>,
+    /// 串行化本进程内的冲突检查与状态流转，避免并发提交重复请求或重复执行
+    guard: Arc<Mutex<()>>,
+}
+
+impl QuarantineService {
+    /// 创建新的隔离邮件处置服务实例
+    pub fn new(
+        email: EmailService,
+        backend: Arc<dyn MailActionBackend>,
+        repository: Arc<dyn QuarantineRepository>,
+        four_eyes: bool,
+        operators: &[QuarantineOperatorConfig],
+    ) -> Self {
+        let operators = operators
+            .iter()
+            .map(|operator| (operator.token_sha256.to_ascii_lowercase(), operator.name.clone()))
+            .collect();
+        Self {
+            email,
+            backend,
+            repository,
+            four_eyes,
+            operators: Arc::new(operators),
+            guard: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// 根据请求携带的令牌认证操作人
+    ///
+    /// 令牌按SHA256与配置的操作人比对，未配置操作人时全部拒绝
+    pub fn authenticate(&self, token: Option<&str>) -> Result<Operator, QuarantineError> {
+        let token = token
+            .map(str::trim)
+            .filter(|token| !token.is_empty())
+            .ok_or_else(|| QuarantineError::Unauthorized("缺少操作人令牌".to_string()))?;
+        let digest = hex::encode(Sha256::digest(token.as_bytes()));
+        let name = self
+            .operators
+            .get(&digest)
+            .ok_or_else(|| QuarantineError::Unauthorized("操作人令牌无效".to_string()))?;
+        Ok(Operator::authenticated(name.clone()))
+    }
+
+    /// 提交处置请求
+    ///
+    /// 未启用双人审批时自动批准并立即执行
+    pub async fn request_action(
+        &self,
+        operator: &Operator,
+        request: QuarantineActionRequest,
+    ) -> Result<QuarantineAction, QuarantineError> {
+        info!(
+            "隔离处置服务: 提交处置请求: mail_id={}, kind={:?}, operator={}",
+            request.mail_id, request.kind, operator.name()
+        );
+
+        if let Some(recipient) = request.recipients.iter().find(|recipient| !Self::valid_recipient(recipient)) {
+            return Err(QuarantineError::InvalidInput(format!("无效的收件人: {:?}", recipient)));
+        }
+        let info = self
+            .email
+            .get_delivery_info(&request.mail_id)
+            .await
+            .map_err(|e| match e.downcast_ref::<DbUnavailable>() {
+                Some(_) => QuarantineError::Internal(e),
+                None => QuarantineError::NotFound(format!("邮件{}: {}", request.mail_id, e)),
+            })?;
+        if info.status != EmailStatus::Quarantine {
+            return Err(QuarantineError::InvalidState(format!(
+                "邮件{}的状态为{}，只有隔离邮件可以处置",
+                request.mail_id,
+                info.status.label()
+            )));
+        }
+
+        let recipients = match request.kind {
+            QuarantineActionKind::Delete => Vec::new(),
+            QuarantineActionKind::Release => info.recipients,
+            QuarantineActionKind::Redeliver if request.recipients.is_empty() => info.recipients,
+            QuarantineActionKind::Redeliver => request.recipients,
+        };
+        if request.kind != QuarantineActionKind::Delete && recipients.is_empty() {
+            return Err(QuarantineError::InvalidInput("没有可投递的收件人".to_string()));
+        }
+
+        let action = QuarantineAction {
+            id: Uuid::new_v4().to_string(),
+            mail_id: request.mail_id,
+            kind: request.kind,
+            state: QuarantineActionState::Requested,
+            recipients,
+            reason: request.reason,
+            requested_by: operator.name().to_string(),
+            requested_at: Utc::now(),
+            reviewed_by: None,
+            reviewed_at: None,
+            completed_at: None,
+            error: None,
+        };
+
+        // 检查与写入在同一把锁内完成，避免并发提交重复请求
+        {
+            let _guard = self.guard.lock().await;
+            let existing = self.load_actions(Some(&action.mail_id), None).await?;
+            Self::check_conflicts(existing.iter(), &action)?;
+            self.save(&action).await?;
+        }
+        self.record(&action, AuditEvent::Requested, operator.name(), &action.reason).await?;
+
+        if self.four_eyes {
+            return Ok(action);
+        }
+        let action = self
+            .review(&action.id, SYSTEM_ACTOR, QuarantineActionState::Approved, "未启用双人审批，自动批准")
+            .await?;
+        self.execute(action).await
+    }
+
+    /// 批准处置请求并执行
+    ///
+    /// 启用双人审批时审批人不能是申请人
+    pub async fn approve(&self, action_id: &str, approver: &Operator) -> Result<QuarantineAction, QuarantineError> {
+        info!("隔离处置服务: 批准处置请求: action_id={}, approver={}", action_id, approver.name());
+
+        let action = self
+            .review(action_id, approver.name(), QuarantineActionState::Approved, "")
+            .await?;
+        self.execute(action).await
+    }
+
+    /// 驳回处置请求
+    pub async fn reject(
+        &self,
+        action_id: &str,
+        approver: &Operator,
+        reason: &str,
+    ) -> Result<QuarantineAction, QuarantineError> {
+        info!("隔离处置服务: 驳回处置请求: action_id={}, approver={}", action_id, approver.name());
+
+        self.review(action_id, approver.name(), QuarantineActionState::Rejected, reason)
+            .await
+    }
+
+    /// 查询处置请求
+    pub async fn get_action(&self, action_id: &str) -> Result<QuarantineAction, QuarantineError> {
+        self.repository
+            .action(action_id)
+            .await?
+            .map(Self::action_from_row)
+            .transpose()?
+            .ok_or_else(|| QuarantineError::NotFound(format!("处置请求不存在: {}", action_id)))
+    }
+
+    /// 查询处置请求列表，按申请时间倒序
+    pub async fn list_actions(
+        &self,
+        mail_id: Option<&str>,
+        state: Option<QuarantineActionState>,
+    ) -> Result<Vec<QuarantineAction>, QuarantineError> {
+        self.load_actions(mail_id, state).await
+    }
+
+    /// 查询审计记录，按时间顺序
+    pub async fn audit_trail(
+        &self,
+        mail_id: Option<&str>,
+        action_id: Option<&str>,
+    ) -> Result<Vec<AuditEntry>, QuarantineError> {
+        self.repository
+            .audit(mail_id, action_id)
+            .await?
+            .into_iter()
+            .map(Self::audit_from_row)
+            .collect()
+    }
+
+    /// 审批处置请求：流转到批准或驳回状态
+    async fn review(
+        &self,
+        action_id: &str,
+        reviewer: &str,
+        next: QuarantineActionState,
+        detail: &str,
+    ) -> Result<QuarantineAction, QuarantineError> {
+        let action = {
+            let _guard = self.guard.lock().await;
+            let mut action = self.get_action(action_id).await?;
+            if self.four_eyes && reviewer == action.requested_by {
+                return Err(QuarantineError::Forbidden(format!(
+                    "已启用双人审批，申请人{}不能审批自己的请求",
+                    reviewer
+                )));
+            }
+            action.transition(next)?;
+            action.reviewed_by = Some(reviewer.to_string());
+            action.reviewed_at = Some(Utc::now());
+            if next == QuarantineActionState::Rejected {
+                action.error = Some(detail.to_string());
+            }
+            self.save(&action).await?;
+            action
+        };
+
+        let event = match next {
+            QuarantineActionState::Rejected => AuditEvent::Rejected,
+            _ => AuditEvent::Approved,
+        };
+        self.record(&action, event, reviewer, detail).await?;
+        Ok(action)
+    }
+
+    /// 执行已批准的处置请求
+    async fn execute(&self, action: QuarantineAction) -> Result<QuarantineAction, QuarantineError> {
+        let result = async {
+            let info = self.email.get_delivery_info(&action.mail_id).await?;
+            let data = self.email.download_email_eml(&action.mail_id).await?;
+            let message = QuarantinedMessage {
+                action_id: action.id.clone(),
+                mail_id: action.mail_id.clone(),
+                envelope_from: info.envelope_from,
+                data: Self::with_trace_header(&action, data),
+            };
+            match action.kind {
+                QuarantineActionKind::Delete => self.backend.discard(&message).await,
+                QuarantineActionKind::Release | QuarantineActionKind::Redeliver => {
+                    self.backend.deliver(&message, &action.recipients).await
+                }
+            }
+        }
+        .await;
+
+        let (next, event, detail) = match &result {
+            Ok(()) => (
+                QuarantineActionState::Done,
+                AuditEvent::Executed,
+                format!("由{}后端执行{}", self.backend.name(), action.kind.label()),
+            ),
+            Err(e) => {
+                warn!("隔离处置执行失败: action_id={}, error={}", action.id, e);
+                (
+                    QuarantineActionState::Failed,
+                    AuditEvent::Failed,
+                    format!("{}后端执行失败: {}", self.backend.name(), e),
+                )
+            }
+        };
+
+        let action = {
+            let _guard = self.guard.lock().await;
+            let mut stored = self.get_action(&action.id).await?;
+            stored.transition(next)?;
+            stored.completed_at = Some(Utc::now());
+            if let Err(e) = &result {
+                stored.error = Some(e.to_string());
+            }
+            self.save(&stored).await?;
+            stored
+        };
+        self.record(&action, event, SYSTEM_ACTOR, &detail).await?;
+        Ok(action)
+    }
+
+    /// 检查同一封邮件上是否存在冲突的处置请求
+    fn check_conflicts<'a>(
+        existing: impl Iterator<Item = &'a QuarantineAction>,
+        action: &QuarantineAction,
+    ) -> Result<(), QuarantineError> {
+        for other in existing.filter(|other| other.mail_id == action.mail_id) {
+            if other.state.is_pending() {
+                return Err(QuarantineError::InvalidState(format!(
+                    "邮件{}已有未完成的处置请求: {}",
+                    action.mail_id, other.id
+                )));
+            }
+            if other.state != QuarantineActionState::Done {
+                continue;
+            }
+            // 已删除的邮件不能再处置，已放行的邮件只能重新投递
+            let blocked = other.kind == QuarantineActionKind::Delete
+                || (other.kind == QuarantineActionKind::Release && action.kind != QuarantineActionKind::Redeliver);
+            if blocked {
+                return Err(QuarantineError::InvalidState(format!(
+                    "邮件{}已被{}，不能再{}",
+                    action.mail_id,
+                    other.kind.label(),
+                    action.kind.label()
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// 在邮件头部加入处置追踪头，便于在收件端识别放行来源
+    ///
+    /// 字段值中的控制字符一律去掉，避免CR/LF注入额外的邮件头
+    fn with_trace_header(action: &QuarantineAction, data: Vec<u8>) -> Vec<u8> {
+        let clean = |value: &str| value.chars().filter(|c| !c.is_control()).collect::<String>();
+        let header = format!(
+            "X-Quarantine-Action: {}; id={}; approved-by={}\r\n",
+            action.kind.as_str(),
+            clean(&action.id),
+            clean(action.reviewed_by.as_deref().unwrap_or(SYSTEM_ACTOR))
+        );
+        let mut output = header.into_bytes();
+        output.extend_from_slice(&data);
+        output
+    }
+
+    /// 重新投递的收件人不能为空或包含控制字符，避免注入SMTP命令
+    fn valid_recipient(recipient: &str) -> bool {
+        !recipient.trim().is_empty() && !recipient.chars().any(char::is_control)
+    }
+
+    /// 读取处置请求
+    async fn load_actions(
+        &self,
+        mail_id: Option<&str>,
+        state: Option<QuarantineActionState>,
+    ) -> Result<Vec<QuarantineAction>, QuarantineError> {
+        self.repository
+            .actions(mail_id, state.as_ref().map(QuarantineActionState::as_str))
+            .await?
+            .into_iter()
+            .map(Self::action_from_row)
+            .collect()
+    }
+
+    /// 保存处置请求的当前状态
+    async fn save(&self, action: &QuarantineAction) -> Result<(), QuarantineError> {
+        self.repository.save_action(&Self::action_row(action)).await?;
+        Ok(())
+    }
+
+    /// 追加审计记录
+    async fn record(
+        &self,
+        action: &QuarantineAction,
+        event: AuditEvent,
+        actor: &str,
+        detail: &str,
+    ) -> Result<(), QuarantineError> {
+        info!(
+            "隔离处置审计: action_id={}, mail_id={}, kind={:?}, event={:?}, actor={}, detail={}",
+            action.id, action.mail_id, action.kind, event, actor, detail
+        );
+        self.repository
+            .append_audit(&QuarantineAuditRow {
+                timestamp: Utc::now().timestamp_millis(),
+                action_id: action.id.clone(),
+                mail_id: action.mail_id.clone(),
+                kind: action.kind.as_str().to_string(),
+                event: event.as_str().to_string(),
+                actor: actor.to_string(),
+                detail: detail.to_string(),
+                version: action.state.revision(),
+            })
+            .await?;
+        Ok(())
+    }
+
+    /// 处置请求转为存储记录
+    fn action_row(action: &QuarantineAction) -> QuarantineActionRow {
+        let millis = |time: Option<DateTime<Utc>>| time.map(|time| time.timestamp_millis()).unwrap_or_default();
+        QuarantineActionRow {
+            action_id: action.id.clone(),
+            mail_id: action.mail_id.clone(),
+            kind: action.kind.as_str().to_string(),
+            state: action.state.as_str().to_string(),
+            recipients: action.recipients.clone(),
+            reason: action.reason.clone(),
+            requested_by: action.requested_by.clone(),
+            requested_at: action.requested_at.timestamp_millis(),
+            reviewed_by: action.reviewed_by.clone().unwrap_or_default(),
+            reviewed_at: millis(action.reviewed_at),
+            completed_at: millis(action.completed_at),
+            error: action.error.clone().unwrap_or_default(),
+            version: action.state.revision(),
+        }
+    }
+
+    /// 存储记录转为处置请求
+    fn action_from_row(row: QuarantineActionRow) -> Result<QuarantineAction, QuarantineError> {
+        let kind = row.kind.parse::<QuarantineActionKind>().map_err(|e| anyhow!(e))?;
+        let state = row.state.parse::<QuarantineActionState>().map_err(|e| anyhow!(e))?;
+        let time = |millis: i64| (millis > 0).then(|| DateTime::from_timestamp_millis(millis)).flatten();
+        Ok(QuarantineAction {
+            id: row.action_id,
+            mail_id: row.mail_id,
+            kind,
+            state,
+            recipients: row.recipients,
+            reason: row.reason,
+            requested_by: row.requested_by,
+            requested_at: time(row.requested_at).unwrap_or_default(),
+            reviewed_by: Some(row.reviewed_by).filter(|name| !name.is_empty()),
+            reviewed_at: time(row.reviewed_at),
+            completed_at: time(row.completed_at),
+            error: Some(row.error).filter(|error| !error.is_empty()),
+        })
+    }
+
+    /// 存储记录转为审计记录
+    fn audit_from_row(row: QuarantineAuditRow) -> Result<AuditEntry, QuarantineError> {
+        Ok(AuditEntry {
+            timestamp: DateTime::from_timestamp_millis(row.timestamp).unwrap_or_default(),
+            action_id: row.action_id,
+            mail_id: row.mail_id,
+            kind: row.kind.parse().map_err(|e: String| anyhow!(e))?,
+            event: row.event.parse().map_err(|e: String| anyhow!(e))?,
+            actor: row.actor,
+            detail: row.detail,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::InMemoryRepository;
+    use crate::services::DisabledDecoder;
+
+    /// 记录投递内容的处置后端
+    #[derive(Default)]
+    struct RecordingBackend {
+        delivered: std::sync::Mutex<Vec<(Vec<u8>, Vec<String>)>>,
+    }
+
+    #[async_trait::async_trait]
+    impl MailActionBackend for RecordingBackend {
+        fn name(&self) -> &'static str {
+            "recording"
+        }
+
+        async fn deliver(&self, message: &QuarantinedMessage, recipients: &[String]) -> anyhow::Result<()> {
+            self.delivered.lock().unwrap().push((message.data.clone(), recipients.to_vec()));
+            Ok(())
+        }
+
+        async fn discard(&self, _message: &QuarantinedMessage) -> anyhow::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn operators() -> Vec<QuarantineOperatorConfig> {
+        [("alice", "alice-token"), ("bob", "bob-token")]
+            .into_iter()
+            .map(|(name, token)| QuarantineOperatorConfig {
+                name: name.to_string(),
+                token_sha256: hex::encode(Sha256::digest(token.as_bytes())),
+            })
+            .collect()
+    }
+
+    fn service(
+        memory: Arc<InMemoryRepository>,
+        backend: Arc<RecordingBackend>,
+        four_eyes: bool,
+    ) -> QuarantineService {
+        let email = EmailService::new(
+            memory.clone(),
+            memory.clone(),
+            memory.clone(),
+            memory.clone(),
+            "infected".to_string(),
+            Arc::new(DisabledDecoder),
+        );
+        QuarantineService::new(email, backend, memory, four_eyes, &operators())
+    }
+
+    fn release(mail_id: &str) -> QuarantineActionRequest {
+        QuarantineActionRequest {
+            mail_id: mail_id.to_string(),
+            kind: QuarantineActionKind::Release,
+            reason: "误报".to_string(),
+            recipients: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn operators_are_authenticated_by_token() {
+        let service = service(Arc::new(InMemoryRepository::fixture()), Arc::default(), true);
+
+        assert_eq!(service.authenticate(Some("bob-token")).unwrap().name(), "bob");
+        for token in [None, Some(""), Some("bob"), Some("mallory-token")] {
+            assert!(matches!(service.authenticate(token), Err(QuarantineError::Unauthorized(_))));
+        }
+    }
+
+    #[tokio::test]
+    async fn four_eyes_uses_authenticated_operators() {
+        let backend = Arc::new(RecordingBackend::default());
+        let service = service(Arc::new(InMemoryRepository::fixture()), backend.clone(), true);
+        let alice = service.authenticate(Some("alice-token")).unwrap();
+        let bob = service.authenticate(Some("bob-token")).unwrap();
+
+        let action = service.request_action(&alice, release("2")).await.unwrap();
+        assert_eq!(action.state, QuarantineActionState::Requested);
+        assert_eq!(action.requested_by, "alice");
+        assert!(matches!(service.approve(&action.id, &alice).await, Err(QuarantineError::Forbidden(_))));
+
+        let action = service.approve(&action.id, &bob).await.unwrap();
+        assert_eq!(action.state, QuarantineActionState::Done);
+        assert_eq!(action.reviewed_by.as_deref(), Some("bob"));
+        let delivered = backend.delivered.lock().unwrap();
+        assert_eq!(delivered.len(), 1);
+        assert!(delivered[0].0.starts_with(b"X-Quarantine-Action: release; "));
+        assert_eq!(delivered[0].1, vec!["finance@example.org".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn control_characters_are_kept_out_of_headers_and_envelopes() {
+        let backend = Arc::new(RecordingBackend::default());
+        let service = service(Arc::new(InMemoryRepository::fixture()), backend.clone(), false);
+        let alice = service.authenticate(Some("alice-token")).unwrap();
+
+        let mut request = release("2");
+        request.kind = QuarantineActionKind::Redeliver;
+        request.recipients = vec!["ceo@example.org\r\nRCPT TO:<attacker@example.net>".to_string()];
+        let result = service.request_action(&alice, request).await;
+        assert!(matches!(result, Err(QuarantineError::InvalidInput(_))));
+        assert!(backend.delivered.lock().unwrap().is_empty());
+
+        let mut action = service.request_action(&alice, release("2")).await.unwrap();
+        action.reviewed_by = Some("bob\r\nBcc: attacker@example.net".to_string());
+        let data = QuarantineService::with_trace_header(&action, b"Subject: test\r\n".to_vec());
+        let header = String::from_utf8(data).unwrap();
+        assert_eq!(header.lines().count(), 2);
+        assert!(header.contains("approved-by=bobBcc: attacker@example.net\r\n"));
+    }
+
+    #[tokio::test]
+    async fn completed_actions_survive_restart() {
+        let memory = Arc::new(InMemoryRepository::fixture());
+        let backend = Arc::new(RecordingBackend::default());
+        let first = service(memory.clone(), backend.clone(), false);
+        let alice = first.authenticate(Some("alice-token")).unwrap();
+        let action = first.request_action(&alice, release("2")).await.unwrap();
+        assert_eq!(action.state, QuarantineActionState::Done);
+
+        // 新的服务实例只共享存储库，相当于服务重启
+        let restarted = service(memory, backend.clone(), false);
+        let result = restarted.request_action(&alice, release("2")).await;
+        assert!(matches!(result, Err(QuarantineError::InvalidState(_))));
+        assert_eq!(backend.delivered.lock().unwrap().len(), 1);
+
+        let stored = restarted.get_action(&action.id).await.unwrap();
+        assert_eq!(stored.state, QuarantineActionState::Done);
+        assert_eq!(stored.requested_by, "alice");
+        let events: Vec<(AuditEvent, String)> = restarted
+            .audit_trail(Some("2"), None)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|entry| (entry.event, entry.actor))
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                (AuditEvent::Requested, "alice".to_string()),
+                (AuditEvent::Approved, SYSTEM_ACTOR.to_string()),
+                (AuditEvent::Executed, SYSTEM_ACTOR.to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,110 @@
+use std::sync::Arc;
+use tracing::info;
+use anyhow::Result;
+
+use crate::db::{RecipientProfileData, StatisticsRepository};
+use crate::models::domain::recipient::{
+    ExposureTrendPoint, RecipientExposure, RecipientProfile, RecipientProfileFilter, RecipientRankFilter,
+};
+use crate::models::domain::statistics::NamedCount;
+use crate::services::aggregation::{action_counts, fill_trend, named_counts, optional_time};
+
+/// 趋势最多返回的分桶数
+pub const MAX_TREND_BUCKETS: i64 = 2000;
+/// 排行最多返回的收件人数
+pub const MAX_RANK_LIMIT: u32 = 200;
+/// 画像最多返回的攻击组织数
+pub const MAX_TOP_ACTORS: u32 = 50;
+
+/// 收件人暴露画像服务
+///
+/// 以显示收件人和信封收件人为键，统计收件人收到的邮件、命中的情报及针对他们的攻击组织
+#[derive(Clone)]
+pub struct RecipientService {
+    /// 统计聚合存储库
+    statistics: Arc<dyn StatisticsRepository>,
+}
+
+impl RecipientService {
+    /// 创建新的收件人暴露画像服务实例
+    pub fn new(statistics: Arc<dyn StatisticsRepository>) -> Self {
+        Self { statistics }
+    }
+
+    /// 查询单个收件人的暴露画像
+    pub async fn get_profile(&self, mut filter: RecipientProfileFilter) -> Result<RecipientProfile> {
+        filter.address = filter.address.trim().to_lowercase();
+        filter.top_actors = filter.top_actors.clamp(1, MAX_TOP_ACTORS);
+        info!(
+            "收件人服务: 查询暴露画像: address={}, start_time={}, end_time={}, interval={:?}",
+            filter.address, filter.start_time, filter.end_time, filter.interval
+        );
+
+        let data = self.statistics.recipient_profile(&filter).await?;
+
+        Ok(Self::build_profile(&filter, data))
+    }
+
+    /// 查询时间范围内暴露度最高的收件人，按命中情报的邮件数倒序
+    pub async fn rank_recipients(&self, mut filter: RecipientRankFilter) -> Result<Vec<RecipientExposure>> {
+        filter.limit = filter.limit.clamp(1, MAX_RANK_LIMIT);
+        info!(
+            "收件人服务: 查询暴露排行: start_time={}, end_time={}, limit={}",
+            filter.start_time, filter.end_time, filter.limit
+        );
+
+        let rows = self.statistics.recipient_rank(&filter).await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| RecipientExposure {
+                address: row.address,
+                total_received: row.received,
+                targeted_mails: row.targeted,
+                last_targeted: optional_time(row.last_secs),
+            })
+            .collect())
+    }
+
+    /// 将聚合结果整理为画像
+    fn build_profile(filter: &RecipientProfileFilter, data: RecipientProfileData) -> RecipientProfile {
+        let actions = action_counts(data.actions);
+        let hits_by_type = named_counts(data.types);
+        let total_hits = hits_by_type.iter().map(|item| item.count).sum();
+
+        // 紧急程度按高、中、低排序，并转为中文名称
+        let mut hits_by_urgency: Vec<(usize, NamedCount)> = data
+            .urgency
+            .into_iter()
+            .map(|row| {
+                let (rank, label) = match row.name.as_str() {
+                    "High" => (0, "高"),
+                    "Medium" => (1, "中"),
+                    "Low" => (2, "低"),
+                    _ => (3, row.name.as_str()),
+                };
+                (rank, NamedCount { name: label.to_string(), count: row.count })
+            })
+            .collect();
+        hits_by_urgency.sort_by_key(|(rank, _)| *rank);
+
+        let trend = fill_trend(filter.interval, filter.start_time, filter.end_time, data.trend)
+            .into_iter()
+            .map(|(time, received, targeted)| ExposureTrendPoint { time, received, targeted })
+            .collect();
+
+        RecipientProfile {
+            address: filter.address.clone(),
+            total_received: actions.iter().map(|action| action.count).sum(),
+            targeted_mails: data.summary.count,
+            total_hits,
+            hits_by_type,
+            hits_by_urgency: hits_by_urgency.into_iter().map(|(_, item)| item).collect(),
+            actions,
+            first_targeted: optional_time(data.summary.first_secs),
+            last_targeted: optional_time(data.summary.last_secs),
+            top_threat_actors: named_counts(data.actors),
+            trend,
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use std::sync::Arc;
+use chrono::{DateTime, Utc};
+use tracing::info;
+use anyhow::Result;
+
+use crate::db::{MailInfoRepository, MailSearchRow};
+use crate::models::domain::email::{Email, EmailSearchCriteria, EmailStatus};
+
+/// 每页最多返回的邮件数
+pub(crate) const MAX_SEARCH_PAGE_SIZE: u32 = 100;
+
+/// 邮件搜索服务
+#[derive(Clone)]
+pub struct SearchService {
+    /// 邮件信息存储库
+    mails: Arc<dyn MailInfoRepository>,
+}
+
+impl SearchService {
+    /// 创建新的搜索服务实例
+    pub fn new(mails: Arc<dyn MailInfoRepository>) -> Self {
+        Self { mails }
+    }
+
+    /// 按条件搜索邮件，返回匹配总数和当前页邮件
+    pub async fn search_emails(&self, mut criteria: EmailSearchCriteria) -> Result<(u64, Vec<Email>)> {
+        criteria.page = criteria.page.max(1);
+        criteria.page_size = criteria.page_size.clamp(1, MAX_SEARCH_PAGE_SIZE);
+        info!(
+            "搜索服务: 搜索邮件: page={}, page_size={}, criteria={:?}",
+            criteria.page, criteria.page_size, criteria
+        );
+
+        let (total, rows) = self.mails.search_mails(&criteria).await?;
+
+        Ok((total, rows.into_iter().map(email_from_row).collect()))
+    }
+}
+
+/// 将投影行转换为邮件领域模型，附件与链接另行补充
+pub(crate) fn email_from_row(row: MailSearchRow) -> Email {
+    let sender = if row.display_from.is_empty() {
+        row.client_envelope_from_address
+    } else {
+        row.display_from
+    };
+    // 显示收件人与信封收件人都可能是逗号或分号分隔的多个地址
+    let mut recipients: Vec<String> = Vec::new();
+    for list in [&row.display_to_address, &row.client_envelope_to_address] {
+        for address in list.split([',', ';']).map(str::trim).filter(|address| !address.is_empty()) {
+            if !recipients.iter().any(|existing| existing.eq_ignore_ascii_case(address)) {
+                recipients.push(address.to_string());
+            }
+        }
+    }
+
+    Email {
+        id: row.id.to_string(),
+        timestamp: DateTime::from_timestamp(i64::from(row.timestamp_secs), 0).unwrap_or_else(Utc::now),
+        subject: row.subject,
+        sender,
+        recipients,
+        attachments: vec![],
+        urls: vec![],
+        content: row.text_body,
+        status: EmailStatus::from_stored(row.id, &row.action_name),
+        source_code: String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::InMemoryRepository;
+
+    #[tokio::test]
+    async fn recipient_search_matches_any_recipient() {
+        let service = SearchService::new(Arc::new(InMemoryRepository::fixture()));
+        let (total, emails) = service
+            .search_emails(EmailSearchCriteria {
+                recipient_address: Some("CEO@example.org".to_string()),
+                page: 1,
+                page_size: 10,
+                ..EmailSearchCriteria::default()
+            })
+            .await
+            .unwrap();
+        let ids: Vec<&str> = emails.iter().map(|email| email.id.as_str()).collect();
+        assert_eq!(total, 3, "{:?}", ids);
+        assert!(ids.contains(&"4"), "{:?}", ids);
+        // 收件人拆分为单个地址
+        assert!(emails.iter().all(|email| email.recipients.iter().all(|address| !address.contains(','))));
+        assert!(emails.iter().all(|email| email.recipients.iter().any(|address| address == "ceo@example.org")));
+    }
+}
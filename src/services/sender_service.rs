@@ -0,0 +1,79 @@
+use std::sync::Arc;
+use tracing::info;
+use anyhow::Result;
+
+use crate::db::{SenderProfileData, StatisticsRepository};
+use crate::models::domain::sender::{SenderProfile, SenderProfileFilter, SenderTrendPoint, SendingSource};
+use crate::services::aggregation::{action_counts, fill_trend, named_counts, optional_time};
+
+/// 画像最多返回的发信来源数
+pub const MAX_TOP_SOURCES: u32 = 50;
+
+/// 发件人信誉画像服务
+///
+/// 按信封发件人或显示发件人聚合发信量、收件人、发信来源、TLS与SASL认证情况、情报命中与处置，
+/// 并根据历史首次来信时间标记新发件人
+#[derive(Clone)]
+pub struct SenderService {
+    /// 统计聚合存储库
+    statistics: Arc<dyn StatisticsRepository>,
+}
+
+impl SenderService {
+    /// 创建新的发件人信誉画像服务实例
+    pub fn new(statistics: Arc<dyn StatisticsRepository>) -> Self {
+        Self { statistics }
+    }
+
+    /// 查询发件人信誉画像
+    pub async fn get_profile(&self, mut filter: SenderProfileFilter) -> Result<SenderProfile> {
+        filter.sender = filter.sender.trim().trim_start_matches('@').to_lowercase();
+        filter.top_ips = filter.top_ips.clamp(1, MAX_TOP_SOURCES);
+        info!(
+            "发件人服务: 查询信誉画像: sender={}, kind={:?}, start_time={}, end_time={}",
+            filter.sender, filter.kind, filter.start_time, filter.end_time
+        );
+
+        let data = self.statistics.sender_profile(&filter).await?;
+
+        Ok(Self::build_profile(&filter, data))
+    }
+
+    /// 将聚合结果整理为画像
+    fn build_profile(filter: &SenderProfileFilter, data: SenderProfileData) -> SenderProfile {
+        let first_seen = optional_time(data.lifetime.first_secs);
+        let hits_by_type = named_counts(data.types);
+
+        SenderProfile {
+            sender: filter.sender.clone(),
+            kind: filter.kind,
+            first_seen,
+            last_seen: optional_time(data.lifetime.last_secs),
+            lifetime_mails: data.lifetime.count,
+            new_sender: first_seen.is_some_and(|first| first >= filter.start_time && first <= filter.end_time),
+            total_mails: data.summary.total,
+            hit_mails: data.summary.hit_mails,
+            distinct_recipients: data.summary.recipients,
+            sending_sources: data
+                .sources
+                .into_iter()
+                .map(|row| SendingSource {
+                    ip: row.client_ip,
+                    ptr: row.client_ptr,
+                    count: row.count,
+                    last_seen: optional_time(row.last_secs),
+                })
+                .collect(),
+            tls_mails: data.summary.tls_mails,
+            tls_versions: named_counts(data.tls),
+            authenticated_mails: data.summary.authenticated,
+            total_hits: hits_by_type.iter().map(|item| item.count).sum(),
+            hits_by_type,
+            actions: action_counts(data.actions),
+            trend: fill_trend(filter.interval, filter.start_time, filter.end_time, data.trend)
+                .into_iter()
+                .map(|(time, mails, hit_mails)| SenderTrendPoint { time, mails, hit_mails })
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,304 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Arc;
+use chrono::{DateTime, Utc};
+use tracing::info;
+use anyhow::Result;
+
+use crate::db::{IntelligenceHitRepository, SpreadSnapshotRow};
+use crate::models::domain::spread::{Industry, Spread, SpreadBucket, SpreadFilter, SpreadIndustry, SpreadUnit};
+use crate::services::joint_prevention::parse_joint_prevention;
+
+/// 最多返回的分桶数，超过时只返回有单位命中的分桶
+pub const MAX_SPREAD_BUCKETS: i64 = 2000;
+
+/// 情报跨单位扩散服务
+///
+/// 每条命中记录都带有当时的联防联控快照，快照中的单位列表和命中数量是累计值。
+/// 按时间顺序合并快照，得到各单位的首次命中时间和各次命中时间
+#[derive(Clone)]
+pub struct SpreadService {
+    /// 情报命中存储库
+    hits: Arc<dyn IntelligenceHitRepository>,
+}
+
+/// 合并快照过程中的单位状态
+struct UnitState {
+    unit_name: String,
+    industry: Industry,
+    section: Industry,
+    first_secs: i64,
+    last_secs: i64,
+    hit_count: Option<u64>,
+    /// 各次命中时间（Unix时间戳，秒）
+    hits: BTreeSet<i64>,
+}
+
+impl SpreadService {
+    /// 创建新的扩散服务实例
+    pub fn new(hits: Arc<dyn IntelligenceHitRepository>) -> Self {
+        Self { hits }
+    }
+
+    /// 查询情报在各单位、各行业间的扩散情况，情报不存在时返回None
+    pub async fn get_spread(&self, filter: SpreadFilter) -> Result<Option<Spread>> {
+        info!(
+            "扩散服务: 查询情报扩散: intelligence_id={}, start_time={:?}, end_time={:?}, interval={:?}",
+            filter.intelligence_id, filter.start_time, filter.end_time, filter.interval
+        );
+
+        let (count, snapshots) = self.hits.spread_snapshots(&filter).await?;
+        if count == 0 {
+            return Ok(None);
+        }
+
+        Ok(Some(Self::build_spread(&filter, snapshots)))
+    }
+
+    /// 按时间顺序合并快照，计算首次命中顺序、各分桶的命中单位和累计数量
+    fn build_spread(filter: &SpreadFilter, snapshots: Vec<SpreadSnapshotRow>) -> Spread {
+        let mut states: HashMap<String, UnitState> = HashMap::new();
+        for snapshot in snapshots {
+            let snapshot_secs = i64::from(snapshot.timestamp_secs);
+            for record in parse_joint_prevention(&snapshot.joint_text) {
+                // 没有命中时间的记录以快照出现的时间作为命中时间
+                let seen = record
+                    .last_hit_time
+                    .or(record.first_hit_time)
+                    .map_or(snapshot_secs, |time| time.timestamp());
+                let first = record.first_hit_time.map_or(seen, |time| time.timestamp()).min(seen);
+                let state = states.entry(record.unit_name.to_lowercase()).or_insert_with(|| UnitState {
+                    unit_name: record.unit_name.clone(),
+                    industry: record.industry.clone(),
+                    section: record.section.clone(),
+                    first_secs: first,
+                    last_secs: seen,
+                    hit_count: None,
+                    hits: BTreeSet::new(),
+                });
+                state.first_secs = state.first_secs.min(first);
+                state.last_secs = state.last_secs.max(seen);
+                state.hits.extend([first, seen]);
+                state.hit_count = state.hit_count.max(record.hit_count);
+                // 以较新快照中能识别的行业为准
+                if !record.section.code.is_empty() {
+                    state.industry = record.industry;
+                    state.section = record.section;
+                }
+            }
+        }
+
+        let mut states: Vec<UnitState> = states.into_values().collect();
+        states.sort_by(|a, b| a.first_secs.cmp(&b.first_secs).then_with(|| a.unit_name.cmp(&b.unit_name)));
+
+        let time = |secs: i64| DateTime::from_timestamp(secs, 0).unwrap_or_else(Utc::now);
+        let units: Vec<SpreadUnit> = states
+            .iter()
+            .enumerate()
+            .map(|(index, state)| SpreadUnit {
+                order: index as u32 + 1,
+                unit_name: state.unit_name.clone(),
+                industry: state.industry.clone(),
+                section: state.section.clone(),
+                first_hit_time: time(state.first_secs),
+                last_hit_time: time(state.last_secs),
+                hit_count: state.hit_count.unwrap_or(state.hits.len() as u64),
+            })
+            .collect();
+
+        // 单位已按首次命中排序，门类第一次出现的位置就是门类的首次命中顺序
+        let mut industries: Vec<SpreadIndustry> = Vec::new();
+        for unit in &units {
+            match industries.iter_mut().find(|industry| industry.industry == unit.section) {
+                Some(industry) => {
+                    industry.unit_count += 1;
+                    industry.hit_count += unit.hit_count;
+                }
+                None => industries.push(SpreadIndustry {
+                    order: industries.len() as u32 + 1,
+                    industry: unit.section.clone(),
+                    first_hit_time: unit.first_hit_time,
+                    unit_count: 1,
+                    hit_count: unit.hit_count,
+                }),
+            }
+        }
+
+        let buckets = Self::build_buckets(filter, &states, &units, &industries);
+        Spread {
+            intelligence_id: filter.intelligence_id,
+            interval: filter.interval,
+            units,
+            industries,
+            buckets,
+        }
+    }
+
+    /// 计算时间范围内各分桶的命中单位与门类，并补齐没有命中的分桶
+    ///
+    /// 累计数量从首个命中单位开始计算，不受开始时间影响
+    fn build_buckets(
+        filter: &SpreadFilter,
+        states: &[UnitState],
+        units: &[SpreadUnit],
+        industries: &[SpreadIndustry],
+    ) -> Vec<SpreadBucket> {
+        let interval = filter.interval;
+        let start = filter
+            .start_time
+            .map(|time| time.timestamp())
+            .or_else(|| states.iter().map(|state| state.first_secs).min());
+        let end = filter
+            .end_time
+            .map(|time| time.timestamp())
+            .or_else(|| states.iter().map(|state| state.last_secs).max());
+        // 只给出开始或结束时间时，另一端取自命中记录，可能与给出的一端前后颠倒
+        let (Some(start), Some(end)) = (start, end) else {
+            return Vec::new();
+        };
+        if start > end {
+            return Vec::new();
+        }
+
+        // 分桶 -> 分桶内命中的单位下标（按首次命中顺序）
+        let mut grouped: BTreeMap<i64, BTreeSet<usize>> = BTreeMap::new();
+        for (index, state) in states.iter().enumerate() {
+            for hit in state.hits.range(start..=end) {
+                grouped.entry(interval.bucket_start(*hit)).or_default().insert(index);
+            }
+        }
+        if let (Some(start_time), Some(end_time)) = (DateTime::from_timestamp(start, 0), DateTime::from_timestamp(end, 0))
+            && (end - start) / interval.seconds() < MAX_SPREAD_BUCKETS
+        {
+            for bucket in interval.buckets(start_time, end_time) {
+                grouped.entry(bucket).or_default();
+            }
+        }
+
+        let section_order = |section: &Industry| industries.iter().position(|industry| industry.industry == *section);
+        grouped
+            .into_iter()
+            .map(|(bucket, indexes)| {
+                let bucket_end = bucket + interval.seconds();
+                let mut seen_sections: Vec<usize> = indexes
+                    .iter()
+                    .filter_map(|index| section_order(&units[*index].section))
+                    .collect();
+                seen_sections.sort_unstable();
+                seen_sections.dedup();
+                let count_in = |first: i64| u64::from(first >= bucket && first < bucket_end);
+                let count_before = |first: i64| u64::from(first < bucket_end);
+                SpreadBucket {
+                    time: DateTime::from_timestamp(bucket, 0).unwrap_or_else(Utc::now),
+                    units: indexes.iter().map(|index| units[*index].unit_name.clone()).collect(),
+                    industries: seen_sections.into_iter().map(|index| industries[index].industry.clone()).collect(),
+                    new_units: states.iter().map(|state| count_in(state.first_secs)).sum(),
+                    new_industries: industries
+                        .iter()
+                        .map(|industry| count_in(industry.first_hit_time.timestamp()))
+                        .sum(),
+                    cumulative_units: states.iter().map(|state| count_before(state.first_secs)).sum(),
+                    cumulative_industries: industries
+                        .iter()
+                        .map(|industry| count_before(industry.first_hit_time.timestamp()))
+                        .sum(),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::domain::statistics::TrendInterval;
+    use uuid::Uuid;
+
+    /// 2025-06-02 00:00:00 UTC
+    const DAY: i64 = 1_748_822_400;
+
+    fn time(secs: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(secs, 0).unwrap()
+    }
+
+    fn filter(start_time: Option<i64>, end_time: Option<i64>) -> SpreadFilter {
+        SpreadFilter {
+            intelligence_id: Uuid::nil(),
+            start_time: start_time.map(time),
+            end_time: end_time.map(time),
+            interval: TrendInterval::Day,
+        }
+    }
+
+    fn snapshots() -> Vec<SpreadSnapshotRow> {
+        vec![
+            SpreadSnapshotRow {
+                timestamp_secs: (DAY + 3600) as u32,
+                joint_text: r#"[{"unit_name": "某银行", "industry": "J66"}]"#.to_string(),
+            },
+            SpreadSnapshotRow {
+                timestamp_secs: (DAY + 86400 + 3600) as u32,
+                joint_text: r#"{"units": [
+                    {"unit_name": "某银行", "industry": "J66", "hit_count": 3},
+                    {"unitName": "某大学", "industryName": "教育", "lastHitTime": "2025-06-03 08:00:00"}
+                ]}"#
+                .to_string(),
+            },
+        ]
+    }
+
+    #[test]
+    fn merges_snapshots_in_first_hit_order() {
+        let spread = SpreadService::build_spread(&filter(None, None), snapshots());
+        let units: Vec<(&str, u64)> = spread.units.iter().map(|unit| (unit.unit_name.as_str(), unit.hit_count)).collect();
+        assert_eq!(units, [("某银行", 3), ("某大学", 1)]);
+        let industries: Vec<&str> = spread.industries.iter().map(|industry| industry.industry.code.as_str()).collect();
+        assert_eq!(industries, ["J", "P"]);
+
+        // 两天各一个分桶，累计数量随首次命中增长
+        let buckets: Vec<(i64, u64, u64)> = spread
+            .buckets
+            .iter()
+            .map(|bucket| (bucket.time.timestamp(), bucket.new_units, bucket.cumulative_units))
+            .collect();
+        assert_eq!(buckets, [(DAY, 1, 1), (DAY + 86400, 1, 2)]);
+        assert_eq!(spread.buckets[1].units, ["某银行", "某大学"]);
+    }
+
+    #[test]
+    fn fills_empty_buckets_in_range() {
+        let spread = SpreadService::build_spread(&filter(Some(DAY - 86400), Some(DAY + 3 * 86400)), snapshots());
+        assert_eq!(spread.buckets.len(), 5);
+        assert!(spread.buckets[0].units.is_empty());
+        assert_eq!(spread.buckets[4].cumulative_units, 2);
+    }
+
+    #[tokio::test]
+    async fn memory_snapshots_follow_hit_mails() {
+        let service = SpreadService::new(Arc::new(crate::db::InMemoryRepository::fixture()));
+        let mut filter = filter(None, None);
+        filter.intelligence_id = "3f2504e0-4f89-41d3-9a0c-0305e82c3301".parse().unwrap();
+        let spread = service.get_spread(filter).await.unwrap().unwrap();
+
+        // 与时间线一致：唯一一次命中是1小时前的邮件2，收件人属于example.org
+        let hit_secs = crate::db::FIXTURE_TIME - 3600;
+        let units: Vec<(&str, &str, u64, i64)> = spread
+            .units
+            .iter()
+            .map(|unit| (unit.unit_name.as_str(), unit.industry.code.as_str(), unit.hit_count, unit.first_hit_time.timestamp()))
+            .collect();
+        assert_eq!(units, [("某城市商业银行", "J66", 1, hit_secs)]);
+        assert_eq!(spread.buckets.len(), 1);
+        assert_eq!(spread.buckets[0].time.timestamp(), TrendInterval::Day.bucket_start(hit_secs));
+    }
+
+    #[test]
+    fn single_bound_outside_hits_has_no_buckets() {
+        // 开始时间晚于最后一次命中
+        let spread = SpreadService::build_spread(&filter(Some(DAY + 365 * 86400), None), snapshots());
+        assert!(spread.buckets.is_empty());
+        assert_eq!(spread.units.len(), 2);
+        // 结束时间早于首次命中
+        let spread = SpreadService::build_spread(&filter(None, Some(DAY - 365 * 86400)), snapshots());
+        assert!(spread.buckets.is_empty());
+    }
+}
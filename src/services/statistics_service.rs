@@ -1,224 +1,275 @@
-use chrono::{DateTime, Utc};
-use tracing::{info};
+use std::collections::HashMap;
 use std::sync::Arc;
-use crate::db::{ClickHouseClient, DbResult};
+use chrono::{DateTime, Duration, Utc};
+use tracing::info;
+use anyhow::Result;
+
+use crate::db::{HitSummaryRow, HitTrendRow, StatisticsRepository};
 use crate::models::domain::statistics::{
-    ChangeDirection, StatisticsItem, StatisticsFilter, 
-    BasicStatisticsItem, OrganizationStatisticsItem, 
-    IntelHitStatisticsItem, TrendChartItem, TrendPoint, 
+    ChangeDirection, StatisticsFilter, TrendInterval,
+    BasicStatisticsItem, OrganizationStatisticsItem,
+    IntelHitStatisticsItem, TrendChartItem, TrendPoint,
     StatisticsResult
 };
 
+/// 黑产组织的类型名称
+const BLACK_ACTOR_KIND: &str = "黑产";
+
+/// 基础趋势统计的指标：（名称, 周期总数, 分桶数值）
+type BasicMetric = (&'static str, fn(&HitSummaryRow) -> u64, fn(&HitTrendRow) -> u64);
+
 /// 统计服务
+///
+/// 看板各模块按查询时间范围统计命中情况，与等长的上一周期对比，并按分桶粒度给出趋势
 #[derive(Clone)]
 pub struct StatisticsService {
-    /// ClickHouse客户端，可选（可能运行在内存模式）
-    db_client: Option<Arc<ClickHouseClient>>,
+    /// 统计存储库
+    statistics: Arc<dyn StatisticsRepository>,
 }
 
 impl StatisticsService {
     /// 创建新的统计服务实例
-    pub fn new(db_client: Option<Arc<ClickHouseClient>>) -> Self {
-        Self { db_client }
+    pub fn new(statistics: Arc<dyn StatisticsRepository>) -> Self {
+        Self { statistics }
     }
 
     /// 查询统计数据
     pub async fn get_statistics(
         &self,
         filter: StatisticsFilter,
-    ) -> StatisticsResult {
+    ) -> Result<StatisticsResult> {
         info!(
-            "统计服务: 查询统计数据: start_time={}, end_time={}, module={}", 
-            filter.start_time, 
+            "统计服务: 查询统计数据: start_time={}, end_time={}, module={}, interval={:?}",
+            filter.start_time,
             filter.end_time,
-            filter.module
+            filter.module,
+            filter.interval
         );
 
-        // 如果有数据库连接，记录一条日志，但仍然使用模拟数据
-        if let Some(_client) = &self.db_client {
-            info!("有数据库连接，但当前使用模拟数据 - 真实查询将在后续实现");
-            // 后续可以在这里实现从数据库获取数据的逻辑
-        } else {
-            info!("无数据库连接，使用模拟数据");
-        }
-
-        // 根据module字段返回不同的数据 
+        // 根据module字段返回不同的数据，未知模块返回看板数据
         match filter.module.as_str() {
-            "dashboard" => self.get_dashboard_statistics(),
-            "intelligence" => self.get_intelligence_statistics(),
-            "apt_org" => self.get_apt_org_statistics(),
-            "intel_hit" => self.get_intel_hit_statistics(),
-            "trend_chart" => self.get_trend_chart_statistics(),
-            _ => self.get_default_statistics(), // 默认统计数据
+            "intelligence" => self.get_intelligence_statistics(&filter).await,
+            "apt_org" => self.get_apt_org_statistics(&filter).await,
+            "intel_hit" => self.get_intel_hit_statistics(&filter).await,
+            "trend_chart" => self.get_trend_chart_statistics(&filter).await,
+            _ => self.get_dashboard_statistics(&filter).await,
         }
     }
 
-    // 获取默认的统计数据
-    fn get_default_statistics(&self) -> StatisticsResult {
-        // 生成模拟数据 - 基础趋势统计
-        let items = vec![
-            BasicStatisticsItem {
-                title: "命中邮件".to_string(),
-                current_total: 385,
-                previous_total: 308,
-                change_direction: ChangeDirection::Increase,
-                change_value: 77,
-                trend_x: vec![
-                    "2024-03-01".to_string(),
-                    "2024-03-02".to_string(),
-                    "2024-03-03".to_string(),
-                ],
-                trend_y: vec![308, 350, 385],
-            },
-            BasicStatisticsItem {
-                title: "受影响邮箱用户".to_string(),
-                current_total: 147,
-                previous_total: 117,
-                change_direction: ChangeDirection::Increase,
-                change_value: 30,
-                trend_x: vec![
-                    "2024-03-01".to_string(),
-                    "2024-03-02".to_string(),
-                    "2024-03-03".to_string(),
-                ],
-                trend_y: vec![117, 130, 147],
-            },
-            BasicStatisticsItem {
-                title: "命中单位".to_string(),
-                current_total: 32,
-                previous_total: 25,
-                change_direction: ChangeDirection::Increase,
-                change_value: 7,
-                trend_x: vec![
-                    "2024-03-01".to_string(),
-                    "2024-03-02".to_string(),
-                    "2024-03-03".to_string(),
-                ],
-                trend_y: vec![25, 28, 32],
-            }
-        ];
-        
-        StatisticsResult::BasicStats(items)
-    }
-
-    // 获取仪表盘的统计数据
-    fn get_dashboard_statistics(&self) -> StatisticsResult {
-        // 简化起见，返回与默认相同的数据
-        self.get_default_statistics()
-    }
-
-    // 获取情报相关的统计数据
-    fn get_intelligence_statistics(&self) -> StatisticsResult {
-        // 生成模拟数据 - 基础趋势统计
-        let items = vec![
-            BasicStatisticsItem {
-                title: "情报数量".to_string(),
-                current_total: 523,
-                previous_total: 478,
-                change_direction: ChangeDirection::Increase,
-                change_value: 45,
-                trend_x: vec![
-                    "2024-03-01".to_string(),
-                    "2024-03-02".to_string(),
-                    "2024-03-03".to_string(),
-                ],
-                trend_y: vec![478, 500, 523],
-            },
-            BasicStatisticsItem {
-                title: "活跃情报源".to_string(),
-                current_total: 8,
-                previous_total: 7,
-                change_direction: ChangeDirection::Increase,
-                change_value: 1,
-                trend_x: vec![
-                    "2024-03-01".to_string(),
-                    "2024-03-02".to_string(),
-                    "2024-03-03".to_string(),
-                ],
-                trend_y: vec![7, 7, 8],
-            }
-        ];
-        
-        StatisticsResult::BasicStats(items)
-    }
-    
-    // 获取APT/黑产组织统计数据
-    fn get_apt_org_statistics(&self) -> StatisticsResult {
-        let items = vec![
-            OrganizationStatisticsItem {
-                title: "APT/黑产组织".to_string(),
-                total_count: 25,
-                black_count: 18,
-                apt_count: 7,
-            },
-        ];
-        
-        StatisticsResult::OrgStats(items)
-    }
-    
-    // 获取自定义情报命中统计数据
-    fn get_intel_hit_statistics(&self) -> StatisticsResult {
-        let items = vec![
-            IntelHitStatisticsItem {
-                title: "自定义情报命中".to_string(),
-                hit_custom_intel_count: 12,
-                total_custom_intel_count: 500,
-            },
-        ];
-        
-        StatisticsResult::IntelHitStats(items)
-    }
-    
-    // 获取趋势图数据
-    fn get_trend_chart_statistics(&self) -> StatisticsResult {
-        let trend = TrendChartItem {
-            x_axis: vec![
-                "2024-03-01".to_string(),
-                "2024-03-02".to_string(),
-                "2024-03-03".to_string(),
-                "2024-03-04".to_string(),
-                "2024-03-05".to_string(),
-            ],
-            y_axis: vec![
-                TrendPoint {
-                    hit_emails: 308,
-                    hit_intelligence: 150,
-                },
-                TrendPoint {
-                    hit_emails: 320,
-                    hit_intelligence: 165,
-                },
-                TrendPoint {
-                    hit_emails: 350,
-                    hit_intelligence: 180,
-                },
-                TrendPoint {
-                    hit_emails: 370,
-                    hit_intelligence: 190,
-                },
-                TrendPoint {
-                    hit_emails: 385,
-                    hit_intelligence: 200,
-                },
-            ]
-        };
-        
-        StatisticsResult::TrendChart(trend)
+    /// 看板统计：命中邮件、受影响邮箱用户、命中单位
+    async fn get_dashboard_statistics(&self, filter: &StatisticsFilter) -> Result<StatisticsResult> {
+        let items = self
+            .basic_statistics(filter, &[
+                ("命中邮件", |row| row.hit_mails, |row| row.hit_mails),
+                ("受影响邮箱用户", |row| row.recipients, |row| row.recipients),
+                ("命中单位", |row| row.units, |row| row.units),
+            ])
+            .await?;
+        Ok(StatisticsResult::BasicStats(items))
     }
 
-    // 保留此方法的框架，但不实现具体查询逻辑
-    #[allow(dead_code)]
-    async fn fetch_statistics_from_db(
+    /// 情报统计：命中的情报数、活跃情报源
+    ///
+    /// 情报源不按分桶统计，趋势中给出每个分桶的命中情报数
+    async fn get_intelligence_statistics(&self, filter: &StatisticsFilter) -> Result<StatisticsResult> {
+        let items = self
+            .basic_statistics(filter, &[
+                ("情报数量", |row| row.intelligence, |row| row.intelligence),
+                ("活跃情报源", |row| row.sources, |row| row.intelligence),
+            ])
+            .await?;
+        Ok(StatisticsResult::BasicStats(items))
+    }
+
+    /// APT/黑产组织统计，按情报记录的攻击组织类型区分
+    async fn get_apt_org_statistics(&self, filter: &StatisticsFilter) -> Result<StatisticsResult> {
+        let actors = self.statistics.threat_actors(filter.start_time, filter.end_time).await?;
+        let apt_count = actors.iter().filter(|actor| actor.kind.eq_ignore_ascii_case("APT")).count() as u64;
+        let black_count = actors.iter().filter(|actor| actor.kind == BLACK_ACTOR_KIND).count() as u64;
+        Ok(StatisticsResult::OrgStats(vec![OrganizationStatisticsItem {
+            title: "APT/黑产组织".to_string(),
+            total_count: actors.len() as u64,
+            black_count,
+            apt_count,
+        }]))
+    }
+
+    /// 自定义情报命中统计
+    async fn get_intel_hit_statistics(&self, filter: &StatisticsFilter) -> Result<StatisticsResult> {
+        let custom = self.statistics.custom_intelligence(filter.start_time, filter.end_time).await?;
+        Ok(StatisticsResult::IntelHitStats(vec![IntelHitStatisticsItem {
+            title: "自定义情报命中".to_string(),
+            hit_custom_intel_count: custom.hits,
+            total_custom_intel_count: custom.total,
+        }]))
+    }
+
+    /// 命中邮件与命中情报的趋势图
+    async fn get_trend_chart_statistics(&self, filter: &StatisticsFilter) -> Result<StatisticsResult> {
+        let trend = self.trend(filter).await?;
+        Ok(StatisticsResult::TrendChart(TrendChartItem {
+            x_axis: trend.iter().map(|(bucket, _)| bucket_label(filter.interval, *bucket)).collect(),
+            y_axis: trend
+                .iter()
+                .map(|(_, row)| TrendPoint {
+                    hit_emails: row.hit_mails,
+                    hit_intelligence: row.intelligence,
+                })
+                .collect(),
+        }))
+    }
+
+    /// 按指标生成基础趋势统计项
+    async fn basic_statistics(
         &self,
-        _client: &ClickHouseClient,
         filter: &StatisticsFilter,
-    ) -> DbResult<StatisticsResult> {
-        info!(
-            "数据库查询框架已准备，但具体查询暂未实现。查询时间范围: {} 到 {}, 模块: {}",
-            filter.start_time, filter.end_time, filter.module
-        );
-        
-        // 返回空的基础统计列表
-        Ok(StatisticsResult::BasicStats(vec![]))
+        metrics: &[BasicMetric],
+    ) -> Result<Vec<BasicStatisticsItem>> {
+        // 上一周期与当前周期等长并紧邻其前
+        let length = filter.end_time - filter.start_time;
+        let previous_end = filter.start_time - Duration::seconds(1);
+        let current = self.statistics.hit_summary(filter.start_time, filter.end_time).await?;
+        let previous = self.statistics.hit_summary(previous_end - length, previous_end).await?;
+        let trend = self.trend(filter).await?;
+        let trend_x: Vec<String> = trend.iter().map(|(bucket, _)| bucket_label(filter.interval, *bucket)).collect();
+
+        Ok(metrics
+            .iter()
+            .map(|(title, total, bucket_value)| {
+                let current_total = total(&current);
+                let previous_total = total(&previous);
+                BasicStatisticsItem {
+                    title: title.to_string(),
+                    current_total,
+                    previous_total,
+                    change_direction: change_direction(current_total, previous_total),
+                    change_value: current_total.abs_diff(previous_total),
+                    trend_x: trend_x.clone(),
+                    trend_y: trend.iter().map(|(_, row)| bucket_value(row)).collect(),
+                }
+            })
+            .collect())
+    }
+
+    /// 查询时间范围内的分桶趋势，补齐没有命中的分桶
+    async fn trend(&self, filter: &StatisticsFilter) -> Result<Vec<(DateTime<Utc>, HitTrendRow)>> {
+        let rows = self.statistics.hit_trend(filter.start_time, filter.end_time, filter.interval).await?;
+        let mut buckets: HashMap<i64, HitTrendRow> = rows
+            .into_iter()
+            .map(|row| (i64::from(row.bucket_secs), row))
+            .collect();
+        Ok(filter
+            .interval
+            .buckets(filter.start_time, filter.end_time)
+            .into_iter()
+            .map(|bucket| {
+                let row = buckets.remove(&bucket).unwrap_or_default();
+                (DateTime::from_timestamp(bucket, 0).unwrap_or(filter.start_time), row)
+            })
+            .collect())
+    }
+}
+
+/// 当前周期相对上一周期的变化方向
+fn change_direction(current: u64, previous: u64) -> ChangeDirection {
+    match current.cmp(&previous) {
+        std::cmp::Ordering::Greater => ChangeDirection::Increase,
+        std::cmp::Ordering::Less => ChangeDirection::Decrease,
+        std::cmp::Ordering::Equal => ChangeDirection::Unchanged,
+    }
+}
+
+/// 趋势横轴的时间标签，按小时分桶时精确到小时
+fn bucket_label(interval: TrendInterval, bucket: DateTime<Utc>) -> String {
+    match interval {
+        TrendInterval::Hour => bucket.format("%Y-%m-%d %H:00").to_string(),
+        TrendInterval::Day | TrendInterval::Week => bucket.format("%Y-%m-%d").to_string(),
     }
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{InMemoryRepository, FIXTURE_TIME};
+
+    fn filter(module: &str, days: i64) -> StatisticsFilter {
+        let end_time = DateTime::from_timestamp(FIXTURE_TIME, 0).unwrap();
+        StatisticsFilter {
+            start_time: end_time - Duration::days(days),
+            end_time,
+            module: module.to_string(),
+            interval: TrendInterval::Day,
+            extras: HashMap::new(),
+        }
+    }
+
+    fn service() -> StatisticsService {
+        StatisticsService::new(Arc::new(InMemoryRepository::fixture()))
+    }
+
+    #[tokio::test]
+    async fn dashboard_counts_hits_in_range() {
+        let StatisticsResult::BasicStats(items) = service().get_statistics(filter("dashboard", 3)).await.unwrap() else {
+            panic!("看板应返回基础趋势统计");
+        };
+        let titles: Vec<&str> = items.iter().map(|item| item.title.as_str()).collect();
+        assert_eq!(titles, ["命中邮件", "受影响邮箱用户", "命中单位"]);
+
+        let mails = &items[0];
+        // 分桶补齐到查询范围，趋势之和等于周期总数
+        assert_eq!(mails.trend_x.len(), 4);
+        assert_eq!(mails.trend_x.last().map(String::as_str), Some("2025-06-02"));
+        assert_eq!(mails.trend_y.iter().sum::<u64>(), mails.current_total);
+        assert!(mails.current_total > 0);
+        // 模拟数据都在最近几天，上一周期没有命中
+        assert_eq!(mails.previous_total, 0);
+        assert!(matches!(mails.change_direction, ChangeDirection::Increase));
+        assert_eq!(mails.change_value, mails.current_total);
+    }
+
+    #[tokio::test]
+    async fn empty_range_has_no_hits() {
+        let mut empty = filter("dashboard", 3);
+        empty.start_time -= Duration::days(300);
+        empty.end_time -= Duration::days(300);
+        let StatisticsResult::BasicStats(items) = service().get_statistics(empty).await.unwrap() else {
+            panic!("看板应返回基础趋势统计");
+        };
+        assert!(items.iter().all(|item| item.current_total == 0 && item.trend_y.iter().all(|value| *value == 0)));
+        assert!(items.iter().all(|item| matches!(item.change_direction, ChangeDirection::Unchanged)));
+    }
+
+    #[tokio::test]
+    async fn apt_org_counts_actor_kinds() {
+        let StatisticsResult::OrgStats(items) = service().get_statistics(filter("apt_org", 7)).await.unwrap() else {
+            panic!("组织统计应返回组织统计数据");
+        };
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].apt_count, 1);
+        assert_eq!(items[0].black_count, 1);
+        assert_eq!(items[0].total_count, 2);
+    }
+
+    #[tokio::test]
+    async fn trend_chart_matches_dashboard() {
+        let statistics = service();
+        let StatisticsResult::TrendChart(chart) = statistics.get_statistics(filter("trend_chart", 3)).await.unwrap() else {
+            panic!("趋势图应返回趋势图数据");
+        };
+        let StatisticsResult::BasicStats(items) = statistics.get_statistics(filter("dashboard", 3)).await.unwrap() else {
+            panic!("看板应返回基础趋势统计");
+        };
+        assert_eq!(chart.x_axis, items[0].trend_x);
+        let hit_emails: Vec<u64> = chart.y_axis.iter().map(|point| point.hit_emails).collect();
+        assert_eq!(hit_emails, items[0].trend_y);
+    }
+
+    #[tokio::test]
+    async fn intel_hit_counts_custom_intelligence() {
+        let StatisticsResult::IntelHitStats(items) = service().get_statistics(filter("intel_hit", 3)).await.unwrap() else {
+            panic!("自定义情报统计应返回命中统计数据");
+        };
+        assert!(items[0].hit_custom_intel_count > 0);
+        assert!(items[0].hit_custom_intel_count <= items[0].total_custom_intel_count);
+    }
+}
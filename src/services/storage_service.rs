@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use chrono::{Duration, Utc};
+use tokio::sync::RwLock;
+use tracing::{error, info};
+use anyhow::{Result, anyhow};
+use uuid::Uuid;
+
+use crate::models::domain::storage::{
+    IntegrityStatus, StorageJobStatus, StorageVerifyJob, StoredAttachment,
+};
+use crate::services::EmailService;
+
+/// 校验任务的保留时间（小时）
+const VERIFY_RETENTION_HOURS: i64 = 24;
+
+/// 附件存储校验服务
+///
+/// 逐个读取存储中的附件，与入库时记录的大小、MD5和SHA256比对，报告缺失或损坏的文件
+#[derive(Clone)]
+pub struct StorageService {
+    /// 邮件服务，用于读取附件记录与内容
+    email: EmailService,
+    /// 校验任务表
+    jobs: Arc<RwLock<HashMap<String, StorageVerifyJob>>>,
+}
+
+impl StorageService {
+    /// 创建新的存储校验服务实例
+    pub fn new(email: EmailService) -> Self {
+        Self {
+            email,
+            jobs: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// 创建存储校验任务，`mail_ids`为空时校验全部附件
+    pub async fn start_verify(&self, mail_ids: Option<Vec<String>>) -> Result<StorageVerifyJob> {
+        let records = self.email.attachment_records(mail_ids.as_deref()).await?;
+        info!("存储服务: 创建校验任务: attachments={}", records.len());
+
+        self.purge_expired_jobs().await;
+
+        let id = Uuid::new_v4().to_string();
+        let job = StorageVerifyJob {
+            id: id.clone(),
+            status: StorageJobStatus::Pending,
+            total: records.len() as u32,
+            checked: 0,
+            verified: 0,
+            unverified: 0,
+            corrupted: 0,
+            missing: 0,
+            problems: Vec::new(),
+            created_at: Utc::now(),
+            finished_at: None,
+            error: None,
+        };
+        self.jobs.write().await.insert(id.clone(), job.clone());
+
+        let service = self.clone();
+        tokio::spawn(async move {
+            service.run_job(id, records).await;
+        });
+
+        Ok(job)
+    }
+
+    /// 查询校验任务
+    pub async fn get_job(&self, job_id: &str) -> Result<StorageVerifyJob> {
+        self.jobs
+            .read()
+            .await
+            .get(job_id)
+            .cloned()
+            .ok_or_else(|| anyhow!("校验任务不存在: {}", job_id))
+    }
+
+    /// 执行校验任务
+    async fn run_job(&self, job_id: String, records: Vec<StoredAttachment>) {
+        self.update_job(&job_id, |job| job.status = StorageJobStatus::Running).await;
+
+        for record in &records {
+            let check = match self.email.check_attachment(record).await {
+                Ok(check) => check,
+                Err(e) => {
+                    error!("存储校验任务失败: job_id={}, attachment_id={}, error={}", job_id, record.attachment.id, e);
+                    self.update_job(&job_id, |job| {
+                        job.status = StorageJobStatus::Failed;
+                        job.finished_at = Some(Utc::now());
+                        job.error = Some(e.to_string());
+                    })
+                    .await;
+                    return;
+                }
+            };
+
+            if matches!(check.status, IntegrityStatus::Corrupted | IntegrityStatus::Missing) {
+                error!(
+                    target: "security",
+                    "存储校验发现异常附件: status={}, mail_id={}, attachment_id={}, file_path={}",
+                    check.status.as_str(), check.mail_id, check.attachment_id, check.file_path
+                );
+            }
+            self.update_job(&job_id, |job| {
+                job.checked += 1;
+                match check.status {
+                    IntegrityStatus::Verified => job.verified += 1,
+                    IntegrityStatus::Unverified => job.unverified += 1,
+                    IntegrityStatus::Corrupted => job.corrupted += 1,
+                    IntegrityStatus::Missing => job.missing += 1,
+                }
+                if matches!(check.status, IntegrityStatus::Corrupted | IntegrityStatus::Missing) {
+                    job.problems.push(check);
+                }
+            })
+            .await;
+        }
+
+        self.update_job(&job_id, |job| {
+            job.status = StorageJobStatus::Completed;
+            job.finished_at = Some(Utc::now());
+            info!(
+                "存储校验任务完成: job_id={}, total={}, corrupted={}, missing={}",
+                job.id, job.total, job.corrupted, job.missing
+            );
+        })
+        .await;
+    }
+
+    /// 更新任务状态
+    async fn update_job(&self, job_id: &str, update: impl FnOnce(&mut StorageVerifyJob)) {
+        if let Some(job) = self.jobs.write().await.get_mut(job_id) {
+            update(job);
+        }
+    }
+
+    /// 清理超过保留时间的任务
+    async fn purge_expired_jobs(&self) {
+        let deadline = Utc::now() - Duration::hours(VERIFY_RETENTION_HOURS);
+        self.jobs
+            .write()
+            .await
+            .retain(|_, job| job.finished_at.is_none_or(|finished| finished >= deadline));
+    }
+}
@@ -0,0 +1,251 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use chrono::{DateTime, Utc};
+use futures::stream::{self, StreamExt};
+use tracing::{info, warn};
+use anyhow::Result;
+
+use crate::content::{
+    normalize_subject, parse_headers, reconstruct_thread, MessageHeaders, ThreadLink, ThreadMessage, ThreadPosition,
+};
+use crate::db::{MailInfoRepository, ThreadMailRow};
+use crate::models::domain::email::EmailStatus;
+use crate::models::domain::thread::{MailThread, ThreadNode, ThreadParticipant};
+use crate::services::EmailService;
+
+/// 并发读取原始邮件的数量
+const FETCH_CONCURRENCY: usize = 8;
+
+/// 邮件会话重建服务
+///
+/// 以查询邮件的归一化主题在前后90天内检索候选邮件，读取原始邮件头后按Message-ID、
+/// In-Reply-To、References和主题重建会话树
+#[derive(Clone)]
+pub struct ThreadService {
+    /// 邮件信息存储库
+    mails: Arc<dyn MailInfoRepository>,
+    /// 邮件服务，用于读取原始邮件
+    email: EmailService,
+}
+
+impl ThreadService {
+    /// 创建新的会话重建服务实例
+    pub fn new(mails: Arc<dyn MailInfoRepository>, email: EmailService) -> Self {
+        Self { mails, email }
+    }
+
+    /// 重建邮件所在的会话，邮件不存在时返回None
+    pub async fn get_thread(&self, mail_id: u64) -> Result<Option<MailThread>> {
+        info!("会话服务: 重建会话: mail_id={}", mail_id);
+
+        let Some(seed) = self.mails.thread_seed(mail_id).await? else {
+            return Ok(None);
+        };
+
+        // 优先使用原始邮件中解码后的主题检索候选邮件
+        let seed_headers = self.fetch_headers(&seed).await;
+        let subject = normalize_subject(&seed_headers.subject);
+        let mut rows = if subject.is_empty() {
+            vec![]
+        } else {
+            self.mails.thread_candidates(&seed, &subject).await?
+        };
+        rows.retain(|row| row.id != seed.id);
+
+        let headers: Vec<MessageHeaders> = stream::iter(0..rows.len())
+            .map(|i| self.fetch_headers(&rows[i]))
+            .buffered(FETCH_CONCURRENCY)
+            .collect()
+            .await;
+        rows.insert(0, seed);
+        let headers: Vec<MessageHeaders> = std::iter::once(seed_headers).chain(headers).collect();
+
+        let messages: Vec<ThreadMessage> = rows
+            .iter()
+            .zip(&headers)
+            .map(|(row, headers)| ThreadMessage {
+                headers: headers.clone(),
+                time: row_time(row),
+            })
+            .collect();
+        let positions = reconstruct_thread(&messages, 0);
+        info!("会话服务: 候选邮件{}封，会话包含{}封", rows.len(), positions.len());
+
+        Ok(Some(Self::build_thread(mail_id, &rows, &headers, &positions)))
+    }
+
+    /// 读取原始邮件头，失败时仅保留数据库中的主题
+    async fn fetch_headers(&self, row: &ThreadMailRow) -> MessageHeaders {
+        match self.email.download_email_eml(&row.id.to_string()).await {
+            Ok(raw) => {
+                let mut headers = parse_headers(&raw);
+                if headers.subject.is_empty() {
+                    headers.subject = row.subject.clone();
+                }
+                headers
+            }
+            Err(e) => {
+                warn!("读取原始邮件失败，仅按主题关联: mail_id={}, error={}", row.id, e);
+                MessageHeaders {
+                    subject: row.subject.clone(),
+                    ..Default::default()
+                }
+            }
+        }
+    }
+
+    /// 将会话树位置整理为嵌套的会话
+    fn build_thread(
+        mail_id: u64,
+        rows: &[ThreadMailRow],
+        headers: &[MessageHeaders],
+        positions: &[ThreadPosition],
+    ) -> MailThread {
+        let mut children: HashMap<usize, Vec<usize>> = HashMap::new();
+        for position in positions {
+            if let Some(parent) = position.parent {
+                children.entry(parent).or_default().push(position.index);
+            }
+        }
+        let links: HashMap<usize, &ThreadPosition> = positions.iter().map(|position| (position.index, position)).collect();
+        let root = positions.first().map(|position| position.index).unwrap_or(0);
+
+        // 参与者按首次出现的顺序记录，再按往来邮件数排序
+        let mut participants: Vec<ThreadParticipant> = Vec::new();
+        let mut participant = |address: &str, name: &str, sent: u64, received: u64| {
+            match participants.iter_mut().find(|item| item.address == address) {
+                Some(item) => {
+                    if item.name.is_empty() {
+                        item.name = name.to_string();
+                    }
+                    item.sent += sent;
+                    item.received += received;
+                }
+                None => participants.push(ThreadParticipant {
+                    address: address.to_string(),
+                    name: name.to_string(),
+                    sent,
+                    received,
+                }),
+            }
+        };
+        for position in positions {
+            let headers = &headers[position.index];
+            if let Some(from) = &headers.from {
+                participant(&from.address, &from.name, 1, 0);
+            }
+            for mailbox in headers.to.iter().chain(&headers.cc) {
+                participant(&mailbox.address, &mailbox.name, 0, 1);
+            }
+        }
+        participants.sort_by_key(|item| std::cmp::Reverse(item.sent + item.received));
+
+        let times: Vec<DateTime<Utc>> = positions.iter().map(|position| row_time(&rows[position.index])).collect();
+        let build = Builder { rows, headers, children: &children, links: &links };
+
+        MailThread {
+            mail_id: mail_id.to_string(),
+            subject: headers[root].subject.clone(),
+            message_count: positions.len() as u64,
+            hit_messages: positions.iter().filter(|position| rows[position.index].hit != 0).count() as u64,
+            first_time: times.iter().min().copied().unwrap_or_else(Utc::now),
+            last_time: times.iter().max().copied().unwrap_or_else(Utc::now),
+            participants,
+            root: build.node(root),
+        }
+    }
+}
+
+/// 邮件检测时间
+fn row_time(row: &ThreadMailRow) -> DateTime<Utc> {
+    DateTime::from_timestamp(i64::from(row.timestamp_secs), 0).unwrap_or_else(Utc::now)
+}
+
+/// 递归构建会话树节点
+struct Builder<'a> {
+    rows: &'a [ThreadMailRow],
+    headers: &'a [MessageHeaders],
+    children: &'a HashMap<usize, Vec<usize>>,
+    links: &'a HashMap<usize, &'a ThreadPosition>,
+}
+
+impl Builder<'_> {
+    fn node(&self, index: usize) -> ThreadNode {
+        let row = &self.rows[index];
+        let headers = &self.headers[index];
+        ThreadNode {
+            mail_id: row.id.to_string(),
+            message_id: headers.message_id.clone(),
+            subject: headers.subject.clone(),
+            sender: headers
+                .from
+                .as_ref()
+                .map(|from| from.address.clone())
+                .unwrap_or_else(|| row.display_from.clone()),
+            recipients: headers.to.iter().chain(&headers.cc).map(|mailbox| mailbox.address.clone()).collect(),
+            time: row_time(row),
+            sent_time: headers.date,
+            status: EmailStatus::from_stored(row.id, &row.action_name),
+            hit_intelligence: row.hit != 0,
+            link: self.links.get(&index).map(|position| position.link).unwrap_or(ThreadLink::Root),
+            children: self
+                .children
+                .get(&index)
+                .map(|kids| kids.iter().map(|&kid| self.node(kid)).collect())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::InMemoryRepository;
+    use crate::services::DisabledDecoder;
+
+    fn service() -> ThreadService {
+        let memory = Arc::new(InMemoryRepository::fixture());
+        let email = EmailService::new(
+            memory.clone(),
+            memory.clone(),
+            memory.clone(),
+            memory.clone(),
+            "infected".to_string(),
+            Arc::new(DisabledDecoder),
+        );
+        ThreadService::new(memory, email)
+    }
+
+    /// 按深度优先顺序列出会话中的邮件ID和关联方式
+    fn flatten(node: &ThreadNode, out: &mut Vec<(String, ThreadLink)>) {
+        out.push((node.mail_id.clone(), node.link));
+        for child in &node.children {
+            flatten(child, out);
+        }
+    }
+
+    #[tokio::test]
+    async fn rebuilds_bec_thread_from_stored_messages() {
+        let thread = service().get_thread(103).await.unwrap().unwrap();
+        assert_eq!(thread.subject, "付款安排");
+        assert_eq!(thread.message_count, 5);
+        assert_eq!(thread.hit_messages, 1);
+
+        let mut nodes = Vec::new();
+        flatten(&thread.root, &mut nodes);
+        let ids: Vec<&str> = nodes.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, ["101", "102", "103", "104", "105"]);
+        assert_eq!(nodes[0].1, ThreadLink::Root);
+        assert_eq!(nodes[4].1, ThreadLink::Subject);
+
+        // 仿冒域名的发件人来自原始邮件头
+        let spoofed = &thread.root.children[0].children[0];
+        assert_eq!(spoofed.sender, "ceo@examp1e.org");
+        assert_eq!(spoofed.message_id.as_deref(), Some("m103@examp1e.org"));
+    }
+
+    #[tokio::test]
+    async fn unknown_mail_has_no_thread() {
+        assert!(service().get_thread(999).await.unwrap().is_none());
+    }
+}
@@ -1,80 +1,534 @@
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
-use chrono::Utc;
-use tracing::info;
+use chrono::{DateTime, Utc};
+use tracing::{info, warn};
+use anyhow::Result;
 use uuid::Uuid;
 
-use crate::db::{ClickHouseClient, DbResult, models::ActionType};
-use crate::models::domain::timeline::{Timeline, TimelineEmail};
+use crate::db::{
+    CountSpanRow, DispositionRepository, IntelligenceHitRepository, NamedCountRow, TimelineBucketRow, TimelineDispositionRow,
+    TimelineHits, TimelineIntelRow, TimelineRetroHuntRow, TimelineUpdateRow,
+};
+use crate::models::domain::email::EmailStatus;
+use crate::models::domain::statistics::TrendInterval;
+use crate::models::domain::timeline::{
+    DispositionChange, RetroHuntResult, Timeline, TimelineBucket, TimelineEmail, TimelineEvent, TimelineEventDetail,
+    TimelineEventKind, TimelineFilter, TimelineIntelligence, TimelineMatch,
+};
+use crate::services::aggregation::{action_counts, optional_time};
+
+/// 每页最多返回的邮件数
+pub const MAX_TIMELINE_PAGE_SIZE: u32 = 200;
+/// 最多返回的分桶数，超过时只返回有邮件的分桶
+pub const MAX_TIMELINE_BUCKETS: i64 = 2000;
+/// 一条时间线最多合并的情报数，按攻击组织查询时取命中最多的情报
+pub const MAX_TIMELINE_INTELLIGENCE: usize = 100;
 
 /// 时间线服务
 #[derive(Clone)]
 pub struct TimelineService {
-    /// ClickHouse客户端，可选（可能运行在内存模式）
-    db_client: Option<Arc<ClickHouseClient>>,
+    /// 情报命中存储库
+    hits: Arc<dyn IntelligenceHitRepository>,
+    /// 情报处置存储库
+    dispositions: Arc<dyn DispositionRepository>,
 }
 
 impl TimelineService {
     /// 创建新的时间线服务实例
-    pub fn new(db_client: Option<Arc<ClickHouseClient>>) -> Self {
-        Self { db_client }
+    pub fn new(hits: Arc<dyn IntelligenceHitRepository>, dispositions: Arc<dyn DispositionRepository>) -> Self {
+        Self { hits, dispositions }
     }
 
-    /// 查询攻击时间线
-    pub async fn get_timeline(&self, intelligence_id: Uuid) -> Timeline {
-        info!("时间线服务: 查询攻击时间线，情报ID: {}", intelligence_id);
+    /// 查询攻击时间线，情报都不存在时返回None
+    ///
+    /// 多个情报（或攻击组织的全部情报）合并为一条时间线，同一封邮件只出现一次并标注命中的全部情报。
+    /// 指定分桶粒度时返回各分桶按处置动作分组的邮件数，否则分页返回邮件。
+    /// 情报生命周期事件（发现、更新、处置变更、过期和回溯排查）与邮件合并为按时间倒序的事件流
+    pub async fn get_timeline(&self, mut filter: TimelineFilter) -> Result<Option<Timeline>> {
+        filter.page = filter.page.max(1);
+        filter.page_size = filter.page_size.clamp(1, MAX_TIMELINE_PAGE_SIZE);
+        filter.recipient_domain = filter
+            .recipient_domain
+            .map(|domain| domain.trim().trim_start_matches('@').to_lowercase())
+            .filter(|domain| !domain.is_empty());
+        filter.threat_actor = filter
+            .threat_actor
+            .map(|actor| actor.trim().to_string())
+            .filter(|actor| !actor.is_empty());
+        info!(
+            "时间线服务: 查询攻击时间线: intelligence_ids={:?}, threat_actor={:?}, start_time={:?}, end_time={:?}, \
+             actions={:?}, recipient_domain={:?}, page={}, page_size={}, interval={:?}",
+            filter.intelligence_ids, filter.threat_actor, filter.start_time, filter.end_time, filter.actions,
+            filter.recipient_domain, filter.page, filter.page_size, filter.interval
+        );
 
-        // 如果有数据库连接，记录一条日志
-        if let Some(_client) = &self.db_client {
-            info!("有数据库连接，但当前使用模拟数据 - 真实查询将在后续实现");
-            // 后续可以在这里实现从数据库获取数据的逻辑
-        } else {
-            info!("无数据库连接，使用模拟数据");
+        let data = self.hits.timeline(&filter, MAX_TIMELINE_INTELLIGENCE).await?;
+        if data.intel.is_empty() {
+            return Ok(None);
         }
 
-        // 返回模拟数据
-        let now = Utc::now();
-        
-        let mock_emails = vec![
-            TimelineEmail {
-                mail_id: 1001,
-                timestamp: now,
-                status: format!("{:?}", ActionType::Accept),
-                sender: "sender1@example.com".to_string(),
-                recipient: vec!["recipient1@example.com".to_string()],
-            },
-            TimelineEmail {
-                mail_id: 1002,
-                timestamp: now,
-                status: format!("{:?}", ActionType::Quarantine),
-                sender: "sender2@example.com".to_string(),
-                recipient: vec!["recipient2@example.com".to_string()],
-            },
-        ];
+        // 处置记录和回溯排查结果由情报平台写入，启动时的迁移检查保证两张表都存在，
+        // 查询失败（包括数据库不可用）时直接返回错误，而不是返回缺少生命周期事件的时间线
+        let ids: Vec<String> = data.intel.iter().map(|row| row.intel_id.clone()).collect();
+        let (dispositions, retro_hunts) =
+            tokio::try_join!(self.dispositions.dispositions(&ids), self.dispositions.retro_hunts(&ids))?;
+
+        Ok(Some(Self::build_timeline(&filter, data, dispositions, retro_hunts)))
+    }
+
+    /// 将查询结果整理为时间线
+    fn build_timeline(
+        filter: &TimelineFilter,
+        data: TimelineHits,
+        dispositions: Vec<TimelineDispositionRow>,
+        retro_hunts: Vec<TimelineRetroHuntRow>,
+    ) -> Timeline {
+        // 时间线情报按首次发现时间排序，邮件的命中标注沿用该顺序
+        let mut intel: Vec<(Uuid, TimelineIntelRow)> = data
+            .intel
+            .into_iter()
+            .filter_map(|row| match Uuid::parse_str(&row.intel_id) {
+                Ok(id) => Some((id, row)),
+                Err(e) => {
+                    warn!("情报ID无法识别: {}: {}", row.intel_id, e);
+                    None
+                }
+            })
+            .collect();
+        intel.sort_by(|a, b| a.1.first_secs.cmp(&b.1.first_secs).then(a.0.cmp(&b.0)));
+        let intelligence: Vec<TimelineIntelligence> = intel
+            .iter()
+            .map(|(id, row)| TimelineIntelligence {
+                intelligence_id: *id,
+                attribute: row.attribute_name.clone(),
+                value: row.value_text.clone(),
+                first_found_time: optional_time(row.first_secs).unwrap_or_else(Utc::now),
+                source: Self::source_label(row),
+                hit_count: row.count,
+            })
+            .collect();
+
+        let mut mail_hits: HashMap<u64, Vec<&str>> = HashMap::new();
+        for hit in &data.mail_hits {
+            mail_hits.entry(hit.mail_id).or_default().push(&hit.intel_id);
+        }
+        let matched = |mail_id: u64| -> Vec<TimelineMatch> {
+            let hit_ids = mail_hits.get(&mail_id).map(Vec::as_slice).unwrap_or_default();
+            intel
+                .iter()
+                .zip(&intelligence)
+                .filter(|((_, row), _)| hit_ids.contains(&row.intel_id.as_str()))
+                .map(|(_, item)| TimelineMatch {
+                    intelligence_id: item.intelligence_id,
+                    attribute: item.attribute.clone(),
+                    value: item.value.clone(),
+                })
+                .collect()
+        };
+
+        let emails: Vec<TimelineEmail> = data
+            .mails
+            .into_iter()
+            .map(|row| TimelineEmail {
+                matched: matched(row.id),
+                mail_id: row.id,
+                timestamp: DateTime::from_timestamp(i64::from(row.timestamp_secs), 0).unwrap_or_else(Utc::now),
+                status: EmailStatus::from_stored(row.id, &row.action_name),
+                sender: row.sender_address,
+                recipient: row.recipient_list,
+            })
+            .collect();
+
+        let buckets = match filter.interval {
+            Some(interval) => Self::fill_buckets(interval, filter, &data.span, data.buckets),
+            None => Vec::new(),
+        };
+        let mut sources: Vec<String> = Vec::new();
+        for item in &intelligence {
+            if !item.source.is_empty() && !sources.contains(&item.source) {
+                sources.push(item.source.clone());
+            }
+        }
+
+        // 生命周期事件按时间范围过滤；分页时只保留落在本页邮件时间区间内的事件，
+        // 区间为[本页最后一封邮件, 上一页最后一封邮件)，首页不设上限，末页不设下限
+        let mut lower = filter.start_time.map(|time| time.timestamp());
+        let mut upper = filter.end_time.map(|time| time.timestamp());
+        let mut include_lifecycle = true;
+        if filter.interval.is_none() {
+            let offset = u64::from(filter.page - 1) * u64::from(filter.page_size);
+            if filter.page > 1 && emails.is_empty() {
+                include_lifecycle = false;
+            }
+            if let Some(newer_secs) = data.newer_secs {
+                let newer = i64::from(newer_secs) - 1;
+                upper = Some(upper.map_or(newer, |upper| upper.min(newer)));
+            }
+            if offset + (emails.len() as u64) < data.span.count
+                && let Some(oldest) = emails.last()
+            {
+                let oldest = oldest.timestamp.timestamp();
+                lower = Some(lower.map_or(oldest, |lower| lower.max(oldest)));
+            }
+        }
+
+        let mut events: Vec<TimelineEvent> = emails
+            .iter()
+            .map(|email| TimelineEvent {
+                kind: TimelineEventKind::EmailHit,
+                intelligence_id: None,
+                time: email.timestamp,
+                actor: Some(email.sender.clone()),
+                detail: TimelineEventDetail::Email(email.clone()),
+            })
+            .collect();
+        if include_lifecycle {
+            let (mut updates, mut dispositions, mut retro_hunts) = (data.updates, dispositions, retro_hunts);
+            for ((id, row), item) in intel.iter().zip(&intelligence) {
+                let lifecycle = Self::lifecycle_events(
+                    row,
+                    &item.source,
+                    updates.extract_if(.., |update| update.intel_id == row.intel_id).collect(),
+                    dispositions.extract_if(.., |disposition| disposition.intel_id == row.intel_id).collect(),
+                    retro_hunts.extract_if(.., |retro_hunt| retro_hunt.intel_id == row.intel_id).collect(),
+                );
+                events.extend(
+                    lifecycle
+                        .into_iter()
+                        .filter(|event| {
+                            let time = event.time.timestamp();
+                            lower.is_none_or(|lower| time >= lower) && upper.is_none_or(|upper| time <= upper)
+                        })
+                        .map(|event| TimelineEvent {
+                            intelligence_id: Some(*id),
+                            ..event
+                        }),
+                );
+            }
+        }
+        events.sort_by(|a, b| {
+            b.time
+                .cmp(&a.time)
+                .then(a.kind.cmp(&b.kind))
+                .then(a.intelligence_id.cmp(&b.intelligence_id))
+        });
 
         Timeline {
-            intelligence_id,
-            first_found_time: now,
-            source: "Local-金融行业".to_string(),
-            emails: mock_emails,
+            first_found_time: intelligence.first().map(|item| item.first_found_time).unwrap_or_else(Utc::now),
+            intelligence,
+            source: sources.join("、"),
+            total: data.span.count,
+            interval: filter.interval,
+            emails,
+            buckets,
+            events,
+        }
+    }
+
+    /// 整理情报的生命周期事件，过期时间未到时不产生过期事件
+    fn lifecycle_events(
+        intel: &TimelineIntelRow,
+        source: &str,
+        updates: Vec<TimelineUpdateRow>,
+        dispositions: Vec<TimelineDispositionRow>,
+        retro_hunts: Vec<TimelineRetroHuntRow>,
+    ) -> Vec<TimelineEvent> {
+        let time = |secs: u32| DateTime::from_timestamp(i64::from(secs), 0).unwrap_or_else(Utc::now);
+        let actor = |name: String| (!name.trim().is_empty()).then_some(name);
+        let mut events = Vec::new();
+
+        // 首次发现时的描述取最早一次更新
+        let first_description = updates
+            .iter()
+            .min_by_key(|update| update.update_secs)
+            .map(|update| update.description_text.clone())
+            .unwrap_or_else(|| intel.description_text.clone());
+        if let Some(first_found_time) = optional_time(intel.first_secs) {
+            events.push(TimelineEvent {
+                kind: TimelineEventKind::FirstDiscovered,
+                intelligence_id: None,
+                time: first_found_time,
+                actor: actor(source.to_string()),
+                detail: TimelineEventDetail::Lifecycle { description: first_description },
+            });
+        }
+        events.extend(updates.into_iter().filter(|update| update.update_secs > intel.first_secs).map(|update| {
+            TimelineEvent {
+                kind: TimelineEventKind::Updated,
+                intelligence_id: None,
+                time: time(update.update_secs),
+                actor: actor(source.to_string()),
+                detail: TimelineEventDetail::Lifecycle { description: update.description_text },
+            }
+        }));
+        if let Some(expire_time) = optional_time(intel.expire_secs)
+            && expire_time <= Utc::now()
+        {
+            events.push(TimelineEvent {
+                kind: TimelineEventKind::Expired,
+                intelligence_id: None,
+                time: expire_time,
+                actor: None,
+                detail: TimelineEventDetail::Lifecycle { description: intel.description_text.clone() },
+            });
+        }
+
+        for row in dispositions {
+            let kind = match row.disposition_name.to_ascii_lowercase().as_str() {
+                "white" => TimelineEventKind::Whitelisted,
+                "black" => TimelineEventKind::Blacklisted,
+                "report" => TimelineEventKind::Reported,
+                other => {
+                    warn!("无法识别的情报处置类型: {}", other);
+                    continue;
+                }
+            };
+            events.push(TimelineEvent {
+                kind,
+                intelligence_id: None,
+                time: time(row.created_secs),
+                actor: actor(row.operator),
+                detail: TimelineEventDetail::Disposition(DispositionChange {
+                    enabled: row.enabled != 0,
+                    reason: row.reason,
+                }),
+            });
+        }
+
+        events.extend(retro_hunts.into_iter().map(|row| TimelineEvent {
+            kind: TimelineEventKind::RetroHunt,
+            intelligence_id: None,
+            time: time(row.finished_secs),
+            actor: actor(row.operator),
+            detail: TimelineEventDetail::RetroHunt(RetroHuntResult {
+                job_id: row.job_id,
+                scan_start_time: time(row.scan_start_secs),
+                scan_end_time: time(row.scan_end_secs),
+                scanned_mails: row.scanned_mails,
+                matched_mails: row.matched_mails,
+            }),
+        }));
+
+        events
+    }
+
+    /// 按分桶汇总各处置动作的邮件数，并补齐时间范围内没有邮件的分桶
+    ///
+    /// 未指定时间范围时从首封邮件到最后一封邮件，分桶数超过上限时只返回有邮件的分桶
+    fn fill_buckets(
+        interval: TrendInterval,
+        filter: &TimelineFilter,
+        span: &CountSpanRow,
+        rows: Vec<TimelineBucketRow>,
+    ) -> Vec<TimelineBucket> {
+        let mut grouped: BTreeMap<i64, Vec<NamedCountRow>> = BTreeMap::new();
+        for row in rows {
+            grouped
+                .entry(i64::from(row.bucket_secs))
+                .or_default()
+                .push(NamedCountRow { name: row.action_name, count: row.count });
+        }
+
+        let start = filter.start_time.or_else(|| optional_time(span.first_secs));
+        let end = filter.end_time.or_else(|| optional_time(span.last_secs));
+        if let (Some(start), Some(end)) = (start, end)
+            && start <= end
+            && (end - start).num_seconds() / interval.seconds() < MAX_TIMELINE_BUCKETS
+        {
+            for bucket in interval.buckets(start, end) {
+                grouped.entry(bucket).or_default();
+            }
+        }
+
+        grouped
+            .into_iter()
+            .map(|(bucket, rows)| {
+                let actions = action_counts(rows);
+                TimelineBucket {
+                    time: DateTime::from_timestamp(bucket, 0).unwrap_or_else(Utc::now),
+                    total: actions.iter().map(|action| action.count).sum(),
+                    actions,
+                }
+            })
+            .collect()
+    }
+
+    /// 情报来源显示名称，如"Local-金融行业"
+    fn source_label(intel: &TimelineIntelRow) -> String {
+        let industries = serde_json::from_str::<Vec<String>>(&intel.industry)
+            .map(|industries| industries.join("、"))
+            .unwrap_or_else(|_| intel.industry.trim().to_string());
+        if industries.is_empty() {
+            intel.source_name.clone()
+        } else {
+            format!("{}-{}", intel.source_name, industries)
         }
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use chrono::Duration;
+    use crate::db::{DbResult, DbUnavailable, InMemoryRepository, FIXTURE_TIME};
+
+    /// 钓鱼域名，命中1小时前的邮件2
+    const PHISHING_DOMAIN: &str = "3f2504e0-4f89-41d3-9a0c-0305e82c3301";
+    /// 仿冒域名，同样命中邮件2
+    const PHISHING_URL: &str = "6fa459ea-ee8a-4ca4-894e-db77e160355e";
+    /// 伪造发件人，命中26小时前的邮件4
+    const SPOOFED_SENDER: &str = "16fd2706-8baf-433b-82eb-8c7fada847da";
+
+    fn service() -> TimelineService {
+        let repository = Arc::new(InMemoryRepository::fixture());
+        TimelineService::new(repository.clone(), repository)
+    }
+
+    fn filter(ids: &[&str]) -> TimelineFilter {
+        TimelineFilter {
+            intelligence_ids: ids.iter().map(|id| Uuid::parse_str(id).unwrap()).collect(),
+            threat_actor: None,
+            start_time: None,
+            end_time: None,
+            actions: Vec::new(),
+            recipient_domain: None,
+            page: 1,
+            page_size: 20,
+            interval: None,
+        }
+    }
+
+    fn time(secs: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(secs, 0).unwrap()
+    }
+
+    /// 事件的类型与时间，按时间线顺序
+    fn events(timeline: &Timeline) -> Vec<(TimelineEventKind, i64)> {
+        timeline.events.iter().map(|event| (event.kind, event.time.timestamp())).collect()
+    }
+
+    #[tokio::test]
+    async fn merges_intelligence_into_one_timeline() {
+        let timeline = service()
+            .get_timeline(filter(&[PHISHING_DOMAIN, PHISHING_URL, SPOOFED_SENDER]))
+            .await
+            .unwrap()
+            .unwrap();
+        // 情报按首次发现时间排序，邮件2只出现一次并标注两条情报
+        let ids: Vec<String> = timeline.intelligence.iter().map(|item| item.intelligence_id.to_string()).collect();
+        assert_eq!(ids[0], SPOOFED_SENDER);
+        assert_eq!(timeline.total, 2);
+        let mails: Vec<u64> = timeline.emails.iter().map(|email| email.mail_id).collect();
+        assert_eq!(mails, [2, 4]);
+        assert_eq!(timeline.emails[0].matched.len(), 2);
+        assert_eq!(timeline.emails[1].matched.len(), 1);
+        assert_eq!(timeline.first_found_time, time(FIXTURE_TIME - 26 * 3600));
+
+        let events = events(&timeline);
+        assert!(events.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+        let hits = events.iter().filter(|(kind, _)| *kind == TimelineEventKind::EmailHit).count();
+        assert_eq!(hits, 2);
+        let discovered = events.iter().filter(|(kind, _)| *kind == TimelineEventKind::FirstDiscovered).count();
+        assert_eq!(discovered, 3);
+        assert!(service().get_timeline(filter(&["00000000-0000-0000-0000-000000000000"])).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn lifecycle_events_follow_time_range() {
+        let first = FIXTURE_TIME - 3600;
+        let mut filter = filter(&[PHISHING_DOMAIN]);
+        filter.start_time = Some(time(first));
+        filter.end_time = Some(time(first + 35 * 60));
+        let timeline = service().get_timeline(filter).await.unwrap().unwrap();
+        // 回溯排查（40分钟后）和过期时间不在范围内
+        assert_eq!(events(&timeline), [
+            (TimelineEventKind::Reported, first + 30 * 60),
+            (TimelineEventKind::Blacklisted, first + 25 * 60),
+            (TimelineEventKind::Updated, first + 20 * 60),
+            (TimelineEventKind::EmailHit, first),
+            (TimelineEventKind::FirstDiscovered, first),
+        ]);
+    }
 
-    // 保留此方法框架，供后续实现
-    #[allow(dead_code)]
-    async fn fetch_timeline_from_db(
-        &self,
-        _client: &ClickHouseClient,
-        intelligence_id: Uuid,
-    ) -> DbResult<Timeline> {
-        info!("数据库查询框架已准备，但具体查询暂未实现。查询情报ID: {}", intelligence_id);
-        
-        // 创建并返回一个空的时间线，表示当前没有实现具体查询
-        let now = Utc::now();
-        Ok(Timeline {
-            intelligence_id,
-            first_found_time: now,
-            source: "未知".to_string(),
-            emails: vec![],
-        })
-    }
-} 
\ No newline at end of file
+    #[tokio::test]
+    async fn paging_keeps_lifecycle_events_with_their_mails() {
+        let recent = FIXTURE_TIME - 3600;
+        let older = FIXTURE_TIME - 26 * 3600;
+        let page = |page: u32| {
+            let mut filter = filter(&[PHISHING_DOMAIN, SPOOFED_SENDER]);
+            filter.page = page;
+            filter.page_size = 1;
+            async move { service().get_timeline(filter).await.unwrap().unwrap() }
+        };
+
+        // 首页不设上限，下限为本页最后一封邮件
+        let first = page(1).await;
+        assert_eq!(first.emails.iter().map(|email| email.mail_id).collect::<Vec<_>>(), [2]);
+        assert!(events(&first).iter().all(|(_, secs)| *secs >= recent));
+        assert!(events(&first).contains(&(TimelineEventKind::RetroHunt, recent + 40 * 60)));
+
+        // 末页不设下限，上限为上一页最后一封邮件之前
+        let last = page(2).await;
+        assert_eq!(last.emails.iter().map(|email| email.mail_id).collect::<Vec<_>>(), [4]);
+        assert_eq!(events(&last), [
+            (TimelineEventKind::EmailHit, older),
+            (TimelineEventKind::FirstDiscovered, older),
+        ]);
+
+        // 超出末页时没有邮件，也不重复返回生命周期事件
+        let beyond = page(3).await;
+        assert!(beyond.emails.is_empty() && beyond.events.is_empty());
+        assert_eq!(beyond.total, 2);
+    }
+
+    #[tokio::test]
+    async fn buckets_fill_the_time_range() {
+        let mut ranged = filter(&[PHISHING_DOMAIN, SPOOFED_SENDER]);
+        ranged.interval = Some(TrendInterval::Day);
+        ranged.start_time = Some(time(FIXTURE_TIME) - Duration::days(3));
+        ranged.end_time = Some(time(FIXTURE_TIME));
+        let timeline = service().get_timeline(ranged).await.unwrap().unwrap();
+        assert!(timeline.emails.is_empty());
+        let totals: Vec<(String, u64)> = timeline
+            .buckets
+            .iter()
+            .map(|bucket| (bucket.time.format("%Y-%m-%d").to_string(), bucket.total))
+            .collect();
+        assert_eq!(totals, [
+            ("2025-05-30".to_string(), 0),
+            ("2025-05-31".to_string(), 0),
+            ("2025-06-01".to_string(), 1),
+            ("2025-06-02".to_string(), 1),
+        ]);
+
+        // 未指定时间范围时从首封邮件到最后一封邮件
+        let mut open = filter(&[PHISHING_DOMAIN, SPOOFED_SENDER]);
+        open.interval = Some(TrendInterval::Hour);
+        let timeline = service().get_timeline(open).await.unwrap().unwrap();
+        assert_eq!(timeline.buckets.len(), 26);
+        assert_eq!(timeline.buckets.iter().map(|bucket| bucket.total).sum::<u64>(), 2);
+    }
+
+    /// 数据库不可用时的处置存储库
+    struct FailingDispositions;
+
+    #[async_trait]
+    impl DispositionRepository for FailingDispositions {
+        async fn dispositions(&self, _intelligence_ids: &[String]) -> DbResult<Vec<TimelineDispositionRow>> {
+            Err(DbUnavailable { retry_after_secs: 5 }.into())
+        }
+
+        async fn retro_hunts(&self, _intelligence_ids: &[String]) -> DbResult<Vec<TimelineRetroHuntRow>> {
+            Ok(Vec::new())
+        }
+    }
+
+    #[tokio::test]
+    async fn disposition_errors_are_returned() {
+        let service = TimelineService::new(Arc::new(InMemoryRepository::fixture()), Arc::new(FailingDispositions));
+        // 错误原样返回，路由据此响应503
+        let error = service.get_timeline(filter(&[PHISHING_DOMAIN])).await.unwrap_err();
+        assert!(error.downcast_ref::<DbUnavailable>().is_some());
+    }
+}